@@ -4,7 +4,19 @@ search-placeholder = Product ID…
 search-empty-prompt = Type a product ID and press Enter or Search
 search-no-results = No products found
 search-product-details = Product Details
+search-selected-count = { $count } selected
+search-select-product = Select { $id }
+search-bulk-brand-placeholder = Brand, empty to remove…
+search-bulk-set-brand = Set brand
+search-bulk-delete = Delete selected
+search-bulk-clear = Clear selection
+search-brand-filter = Brand
+search-brand-all = All brands
+search-brand-option = { $brand } ({ $count })
+search-verified-only = Verified data only
+error-no-selection = No products selected
 db-type-local = Saved
+db-type-local-read-only = Saved (read-only)
 save-label = Save
 edit-label = Edit
 add-label = Add
@@ -19,11 +31,27 @@ label-carbohydrates = Carbohydrates
 label-sugar = Sugar
 label-protein = Protein
 label-calories = Calories
+label-calories-override = Calories from label
+label-calories-computed = computed: { $kcal }
+label-calories-with-alcohol = Calories including alcohol: { $calories }
+label-ignore-alcohol-calories = Leave alcohol out of calories
+label-nutri-grade = Nutrition grade: { $grade }
+label-serving-size = Serving size (g)
+label-per-serving-calories = Calories per serving: { $calories }
+action-convert-per-serving = Values are per serving, convert to 100g
 label-micro-nutrients = Micro Nutrients
 mn-fiber = Fiber
 mn-zinc = Zinc
 mn-sodium = Sodium
 mn-alcohol = Alcohol
+mn-iron = Iron
+mn-calcium = Calcium
+mn-potassium = Potassium
+mn-magnesium = Magnesium
+mn-vitamin-a = Vitamin A
+mn-vitamin-c = Vitamin C
+mn-vitamin-d = Vitamin D
+mn-vitamin-b12 = Vitamin B12
 mn-no-data = No data
 mn-none = None
 label-allowed-units = Allowed Units
@@ -44,6 +72,13 @@ action-meal-plan = Meal Plan
 action-swap-foods = Swap Foods
 action-db-manager = Products
 action-language = Language
+action-back = Back
+action-navigation = Main navigation
+action-resize-navigation = Resize sidebar
+accessibility-title = Accessibility
+accessibility-font-scale = Text size
+accessibility-high-contrast = High contrast
+accessibility-reduced-motion = Reduce motion
 action-modify = Edit
 lang-english = English
 lang-polish = Polish
@@ -58,5 +93,47 @@ label-product-count = { $count ->
 add-product-sentence = { action-add } product
 error-no-product = No product to add
 error-db-access = Could not access local database
+error-db-newer-version = This database was created by a newer version of the app. Update the app (now { $version }) to use it.
 popup-product-added = Product added successfully
 popup-error = Error
+action-summary = Data quality
+summary-total-products = Products in database
+summary-without-micro-nutrients = Without micro nutrients
+summary-incomplete-micro-nutrients = With incomplete micro nutrients
+summary-without-extra-units = Measured only in grams
+summary-without-brand = Without brand
+summary-without-barcode = Without barcode
+summary-unused-in-plans = Never used in a saved plan
+summary-least-complete = Least complete products
+summary-loading = Loading…
+targets-review-title = Time to review your nutrition targets
+targets-review-weight = Your weight changed by { $delta } kg since the targets were calculated.
+targets-review-age = The targets were calculated { $days } days ago.
+targets-review-dismiss = Dismiss
+
+action-usage-stats = Your year in food
+usage-title = Your year in food
+usage-local-only = Statistics are counted only on this device and never sent anywhere.
+usage-enabled = Count my usage
+usage-solves = Generated meal plans
+usage-products-added = Products added
+usage-most-used-views = Most used views
+usage-export = Export
+usage-wipe = Wipe statistics
+
+reminder-title = Time for { $meal }
+
+sandbox-title = Solver sandbox
+sandbox-request = Constraints JSON
+sandbox-solve = Solve
+sandbox-elapsed = Solved in { $ms } ms
+sandbox-objective = Objective value: { $value }
+sandbox-solution = Solution
+sandbox-binding = Binding constraints
+sandbox-lp = LP export
+saved-plan-empty = No saved meal plan yet.
+saved-plan-drag-hint = Drag a meal onto another one to change the order.
+
+notice-db-failure = Database error: { $error }
+notice-infeasible = No meal plan fits the constraints: { $error }
+notice-dismiss = Dismiss
@@ -4,7 +4,19 @@ search-placeholder = ID produktu…
 search-empty-prompt = Wpisz ID produktu i wciśnij Enter lub Szukaj
 search-no-results = Nie znaleziono produktow
 search-product-details = Szczegóły produktu
+search-selected-count = Zaznaczono: { $count }
+search-select-product = Zaznacz { $id }
+search-bulk-brand-placeholder = Marka, pusta aby usunąć…
+search-bulk-set-brand = Ustaw markę
+search-bulk-delete = Usuń zaznaczone
+search-bulk-clear = Wyczyść zaznaczenie
+search-brand-filter = Marka
+search-brand-all = Wszystkie marki
+search-brand-option = { $brand } ({ $count })
+search-verified-only = Tylko zweryfikowane dane
+error-no-selection = Nie zaznaczono produktów
 db-type-local = Zapisany
+db-type-local-read-only = Zapisany (tylko do odczytu)
 save-label = Zapisz
 edit-label = Edytuj
 add-label = Dodaj
@@ -19,11 +31,27 @@ label-carbohydrates = Weglowodany
 label-sugar = Cukry
 label-protein = Bialko
 label-calories = Kalorie
+label-calories-override = Kalorie z etykiety
+label-calories-computed = wyliczone: { $kcal }
+label-calories-with-alcohol = Kalorie z alkoholem: { $calories }
+label-ignore-alcohol-calories = Nie wliczaj alkoholu do kalorii
+label-nutri-grade = Ocena zywieniowa: { $grade }
+label-serving-size = Wielkosc porcji (g)
+label-per-serving-calories = Kalorie w porcji: { $calories }
+action-convert-per-serving = Wartosci sa na porcje, przelicz na 100g
 label-micro-nutrients = Mikroskladniki
 mn-fiber = Blonnik
 mn-zinc = Cynk
 mn-sodium = Sod
 mn-alcohol = Alkohol
+mn-iron = Zelazo
+mn-calcium = Wapn
+mn-potassium = Potas
+mn-magnesium = Magnez
+mn-vitamin-a = Witamina A
+mn-vitamin-c = Witamina C
+mn-vitamin-d = Witamina D
+mn-vitamin-b12 = Witamina B12
 mn-no-data = Brak danych
 mn-none = Brak
 label-allowed-units = Dozwolone jednostki
@@ -44,6 +72,13 @@ action-meal-plan = Plan posilkow
 action-swap-foods = Zamien produkty
 action-db-manager = Produkty
 action-language = Jezyk
+action-back = Wstecz
+action-navigation = Nawigacja glowna
+action-resize-navigation = Zmien szerokosc panelu
+accessibility-title = Dostepnosc
+accessibility-font-scale = Rozmiar tekstu
+accessibility-high-contrast = Wysoki kontrast
+accessibility-reduced-motion = Ogranicz animacje
 action-modify = Edytuj
 lang-english = Angielski
 lang-polish = Polski
@@ -60,5 +95,47 @@ label-product-count = { $count ->
 add-product-sentence = { action-add } produkt
 error-no-product = Brak produktu do dodania
 error-db-access = Brak dostepu do lokalnej bazy
+error-db-newer-version = Ta baza danych została utworzona przez nowszą wersję aplikacji. Zaktualizuj aplikację (obecnie { $version }), aby z niej korzystać.
 popup-product-added = Produkt dodany pomyslnie
 popup-error = Blad
+action-summary = Jakosc danych
+summary-total-products = Produkty w bazie
+summary-without-micro-nutrients = Bez mikroskladnikow
+summary-incomplete-micro-nutrients = Z niepelnymi mikroskladnikami
+summary-without-extra-units = Mierzone tylko w gramach
+summary-without-brand = Bez marki
+summary-without-barcode = Bez kodu kreskowego
+summary-unused-in-plans = Nieuzyte w zadnym zapisanym planie
+summary-least-complete = Produkty z najmniej kompletnymi danymi
+summary-loading = Ladowanie…
+targets-review-title = Czas przejrzec cele zywieniowe
+targets-review-weight = Twoja waga zmienila sie o { $delta } kg od obliczenia celow.
+targets-review-age = Cele zostaly obliczone { $days } dni temu.
+targets-review-dismiss = Zamknij
+
+action-usage-stats = Twoj rok z jedzeniem
+usage-title = Twoj rok z jedzeniem
+usage-local-only = Statystyki sa liczone tylko na tym urzadzeniu i nigdy nie sa wysylane.
+usage-enabled = Zliczaj moje uzycie
+usage-solves = Wygenerowane plany posilkow
+usage-products-added = Dodane produkty
+usage-most-used-views = Najczesciej uzywane widoki
+usage-export = Eksportuj
+usage-wipe = Wyczysc statystyki
+
+reminder-title = Czas na { $meal }
+
+sandbox-title = Piaskownica solvera
+sandbox-request = Ograniczenia JSON
+sandbox-solve = Rozwiaz
+sandbox-elapsed = Rozwiazano w { $ms } ms
+sandbox-objective = Wartosc celu: { $value }
+sandbox-solution = Rozwiazanie
+sandbox-binding = Wiazace ograniczenia
+sandbox-lp = Eksport LP
+saved-plan-empty = Brak zapisanego planu posiłków.
+saved-plan-drag-hint = Przeciągnij posiłek na inny, aby zmienić kolejność.
+
+notice-db-failure = Błąd bazy danych: { $error }
+notice-infeasible = Żaden plan posiłków nie spełnia ograniczeń: { $error }
+notice-dismiss = Zamknij
@@ -0,0 +1,101 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+
+const FONT_SCALES: [u16; 4] = [100, 125, 150, 200];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AccessibilitySettings {
+    pub font_scale_percent: u16,
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            font_scale_percent: 100,
+            high_contrast: false,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    /// Modifier classes for the app shell, the theme variants live in `main.css`.
+    pub fn shell_class(self) -> String {
+        let mut class = "app-shell".to_string();
+        if self.high_contrast {
+            class.push_str(" app-shell--high-contrast");
+        }
+        if self.reduced_motion {
+            class.push_str(" app-shell--reduced-motion");
+        }
+        class
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct AccessibilityContext {
+    pub settings: Signal<AccessibilitySettings>,
+}
+
+/// Convenience hook to access the accessibility settings context.
+pub fn use_accessibility_settings() -> Signal<AccessibilitySettings> {
+    use_context::<AccessibilityContext>().settings
+}
+
+/// Scales the root font size, every rem based size in the app follows it.
+pub fn use_font_scale(settings: Signal<AccessibilitySettings>) {
+    use_effect(move || {
+        let percent = settings().font_scale_percent;
+        document::eval(&format!(
+            "document.documentElement.style.fontSize = '{percent}%';"
+        ));
+    });
+}
+
+#[component]
+pub fn AccessibilitySettingsGroup() -> Element {
+    let mut settings = use_accessibility_settings();
+    let current = settings();
+
+    rsx! {
+        fieldset { class: "accessibility-settings",
+            legend { {t!("accessibility-title")} }
+            label { class: "accessibility-settings__row",
+                {t!("accessibility-font-scale")}
+                select {
+                    value: current.font_scale_percent.to_string(),
+                    onchange: move |e| {
+                        if let Ok(percent) = e.value().parse::<u16>() {
+                            settings.write().font_scale_percent = percent;
+                        }
+                    },
+                    for percent in FONT_SCALES {
+                        option {
+                            value: percent.to_string(),
+                            selected: current.font_scale_percent == percent,
+                            "{percent}%"
+                        }
+                    }
+                }
+            }
+            label { class: "accessibility-settings__row",
+                input {
+                    r#type: "checkbox",
+                    checked: current.high_contrast,
+                    onchange: move |e| settings.write().high_contrast = e.checked(),
+                }
+                {t!("accessibility-high-contrast")}
+            }
+            label { class: "accessibility-settings__row",
+                input {
+                    r#type: "checkbox",
+                    checked: current.reduced_motion,
+                    onchange: move |e| settings.write().reduced_motion = e.checked(),
+                }
+                {t!("accessibility-reduced-motion")}
+            }
+        }
+    }
+}
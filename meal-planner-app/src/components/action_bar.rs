@@ -8,6 +8,7 @@ use std::time;
 #[cfg(target_arch = "wasm32")]
 use web_sys::js_sys;
 
+use crate::components::accessibility::AccessibilitySettingsGroup;
 use crate::components::main_view::ViewKind;
 
 #[derive(Clone, Copy)]
@@ -38,6 +39,7 @@ impl SwipeDirection {
 
 const SIDEBAR_MIN_WIDTH: f32 = 160.0; // 10rem baseline
 const SIDEBAR_MAX_WIDTH: f32 = 320.0; // 20rem baseline
+const SIDEBAR_KEYBOARD_STEP: f32 = 16.0;
 
 fn clamp_sidebar_width(width: f32) -> f32 {
     width.clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH)
@@ -83,14 +85,39 @@ pub fn ActionBar(
     } else {
         "action-bar"
     };
+    let current_page = move |view: ViewKind| if selection() == view { "page" } else { "false" };
+    #[cfg(feature = "solver-sandbox")]
+    let sandbox_button = rsx! {
+        button {
+            class: "action-bar__button",
+            aria_current: current_page(ViewKind::SolverSandbox),
+            onclick: move |_| {
+                selection.set(ViewKind::SolverSandbox);
+                sidebar_open.set(false);
+            },
+            {t!("sandbox-title")}
+        }
+    };
+    #[cfg(not(feature = "solver-sandbox"))]
+    let sandbox_button = rsx! {};
 
     rsx! {
         // for small screen - suggests there is a side bar which is closed
         div {
             class: "sidebar-handle",
             role: "button",
+            tabindex: "0",
             aria_label: t!("action-open-navigation"),
+            aria_controls: "action-bar",
+            aria_expanded: sidebar_open(),
             onclick: move |_| *sidebar_open.write() = true,
+            // the handle is swipe driven, keyboard users open the side bar with Enter or Space
+            onkeydown: move |evt| {
+                if matches!(evt.key(), Key::Enter) || evt.key() == Key::Character(" ".to_string()) {
+                    evt.prevent_default();
+                    sidebar_open.set(true);
+                }
+            },
             onpointerdown: move |evt| {
                 record_pointer_down(pointer_down_time);
                 begin_swipe(open_swipe, &evt);
@@ -130,7 +157,14 @@ pub fn ActionBar(
         }
         // side bar
         nav {
+            id: "action-bar",
             class: nav_class,
+            aria_label: t!("action-navigation"),
+            onkeydown: move |evt| {
+                if evt.key() == Key::Escape && sidebar_open() {
+                    sidebar_open.set(false);
+                }
+            },
             onpointerdown: move |evt| {
                 if !sidebar_open() || resize_session().is_some() {
                     return;
@@ -164,6 +198,7 @@ pub fn ActionBar(
             // real side bar content
             button {
                 class: "action-bar__button",
+                aria_current: current_page(ViewKind::MealPlan),
                 onclick: move |_| {
                     selection.set(ViewKind::MealPlan);
                     sidebar_open.set(false);
@@ -172,6 +207,7 @@ pub fn ActionBar(
             }
             button {
                 class: "action-bar__button",
+                aria_current: current_page(ViewKind::SwapFood),
                 onclick: move |_| {
                     selection.set(ViewKind::SwapFood);
                     sidebar_open.set(false);
@@ -180,15 +216,27 @@ pub fn ActionBar(
             }
             button {
                 class: "action-bar__button",
+                aria_current: current_page(ViewKind::DbManager),
                 onclick: move |_| {
                     selection.set(ViewKind::DbManager);
                     sidebar_open.set(false);
                 },
                 {t!("label-product-count", count : 2)}
             }
+            button {
+                class: "action-bar__button",
+                aria_current: current_page(ViewKind::UsageStats),
+                onclick: move |_| {
+                    selection.set(ViewKind::UsageStats);
+                    sidebar_open.set(false);
+                },
+                {t!("action-usage-stats")}
+            }
+            {sandbox_button}
             div { class: "action-bar__button",
                 select {
                     id: "lang-select",
+                    aria_label: t!("action-language"),
                     value: locale(),
                     onchange: move |e| {
                         let val = e.value();
@@ -201,10 +249,25 @@ pub fn ActionBar(
                         option { value: code, selected: locale() == code, {t!(label_key)} }
                     }
                 }
+                AccessibilitySettingsGroup {}
                 div {
                     class: "action-bar__resize-handle",
                     role: "separator",
-                    aria_label: "Resize sidebar",
+                    tabindex: "0",
+                    aria_label: t!("action-resize-navigation"),
+                    aria_orientation: "vertical",
+                    aria_valuemin: SIDEBAR_MIN_WIDTH,
+                    aria_valuemax: SIDEBAR_MAX_WIDTH,
+                    aria_valuenow: sidebar_width(),
+                    onkeydown: move |evt| {
+                        let step = match evt.key() {
+                            Key::ArrowLeft => -SIDEBAR_KEYBOARD_STEP,
+                            Key::ArrowRight => SIDEBAR_KEYBOARD_STEP,
+                            _ => return,
+                        };
+                        evt.prevent_default();
+                        sidebar_width.set(clamp_sidebar_width(sidebar_width() + step));
+                    },
                     onpointerdown: move |evt| {
                         resize_session
                             .set(
@@ -4,7 +4,7 @@ use super::db_operation_helper::{operation_triggered, DbOperation};
 use super::popup::DbActionPopup;
 use crate::components::product_related::Product;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types as data;
+use meal_planner_lib::api::data_types as data;
 
 #[component]
 pub fn Add() -> Element {
@@ -0,0 +1,23 @@
+use std::cell::RefCell;
+
+use meal_planner_lib::api::database_access as db_access;
+
+pub(super) type CachedLocalDb = db_access::CachedDatabase<db_access::LocalProductDb>;
+
+thread_local! {
+    // shared by searches and product edits, so saving a product drops stale search results
+    static LOCAL_DB: RefCell<Option<CachedLocalDb>> = const { RefCell::new(None) };
+}
+
+/// Default local database behind the search cache of the database manager.
+pub(super) async fn cached_local_db() -> Option<CachedLocalDb> {
+    if let Some(db) = LOCAL_DB.with_borrow(Clone::clone) {
+        return Some(db);
+    }
+    let path = db_access::resolve_db_path(db_access::LOCAL_DB_DEFAULT_FILE).ok()?;
+    let db = db_access::CachedDatabase::new(
+        db_access::LocalProductDb::shared(&path).await?,
+        db_access::CacheConfig::default(),
+    );
+    Some(LOCAL_DB.with_borrow_mut(|cached| cached.get_or_insert(db).clone()))
+}
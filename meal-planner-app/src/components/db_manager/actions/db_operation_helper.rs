@@ -1,13 +1,17 @@
 use dioxus::prelude::*;
 
 use dioxus_i18n::t;
-use meal_planner_lib::data_types as data;
-use meal_planner_lib::database_access as db_access;
+use meal_planner_lib::api::data_types as data;
+use meal_planner_lib::api::database_access::MutableDatabase;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+use super::cached_db::cached_local_db;
+use crate::components::usage_stats::record_usage;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub(super) enum DbOperation {
     Add,
-    Edit,
+    /// Holds the id the product had when editing started, a changed name or brand renames it.
+    Edit(data::ProductId),
     Delete,
     None,
 }
@@ -27,22 +31,59 @@ pub(super) fn operation_triggered(
         let mut result_signal = result_signal;
         async move {
             tracing::info!("Creating DB access");
-            let Some(mut db) = db_access::get_mutable_db(db_access::DataBaseTypes::Local(
-                db_access::LOCAL_DB_DEFAULT_FILE.to_string(),
-            ))
-            .await
-            else {
+            let Some(mut db) = cached_local_db().await else {
                 result_signal.set(Some(Err(t!("error-db-access"))));
                 return;
             };
             tracing::info!("DB Accessed");
-            let res = match operation {
+            let res = match &operation {
                 DbOperation::Add => db.add_product(&product_id, product).await,
-                DbOperation::Edit => db.update_product(&product_id, product).await,
+                DbOperation::Edit(original_id) if *original_id != product_id => {
+                    db.rename_product(original_id, product).await
+                }
+                DbOperation::Edit(_) => db.update_product(&product_id, product).await,
                 DbOperation::Delete => db.delete_product(&product_id).await,
                 DbOperation::None => Ok(()),
             };
+            if operation == DbOperation::Add && res.is_ok() {
+                record_usage(data::UsageEvent::ProductAdded);
+            }
             result_signal.set(Some(res));
         }
     });
 }
+
+#[derive(Clone, PartialEq, Debug)]
+pub(super) enum BulkOperation {
+    Edit(data::ProductEdit),
+    Delete,
+}
+
+/// Runs `operation` on every selected product in one transaction, `on_done` is called after a
+/// success so the caller can refresh what it shows.
+pub(super) fn bulk_operation_triggered(
+    product_ids: Vec<String>,
+    mut result_signal: Signal<Option<Result<(), String>>>,
+    operation: BulkOperation,
+    on_done: EventHandler<()>,
+) {
+    if product_ids.is_empty() {
+        result_signal.set(Some(Err(t!("error-no-selection"))));
+        return;
+    }
+
+    spawn(async move {
+        let Some(mut db) = cached_local_db().await else {
+            result_signal.set(Some(Err(t!("error-db-access"))));
+            return;
+        };
+        let res = match &operation {
+            BulkOperation::Edit(edit) => db.edit_products(&product_ids, edit).await,
+            BulkOperation::Delete => db.delete_products(&product_ids).await,
+        };
+        if res.is_ok() {
+            on_done.call(());
+        }
+        result_signal.set(Some(res));
+    });
+}
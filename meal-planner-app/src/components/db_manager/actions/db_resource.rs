@@ -0,0 +1,65 @@
+use std::future::Future;
+
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use futures::StreamExt;
+use meal_planner_lib::api::database_access as db_access;
+
+use super::cached_db::{cached_local_db, CachedLocalDb};
+
+/// Runs `query` on the default local database, `Err` when the database can't be opened.
+/// Signals read by `query` rerun it when they change, so does any product change made through
+/// any handle of the database, e.g. in the planner or another window.
+pub(super) fn use_db_resource<T, Fut>(
+    query: impl Fn(CachedLocalDb) -> Fut + Clone + 'static,
+) -> Resource<Result<T, String>>
+where
+    T: 'static,
+    Fut: Future<Output = Result<T, String>> + 'static,
+{
+    let mut resource = use_resource(move || {
+        let query = query.clone();
+        async move {
+            let db = cached_local_db()
+                .await
+                .ok_or_else(|| t!("error-db-access"))?;
+            query(db).await
+        }
+    });
+
+    use_future(move || async move {
+        let Some(db) = cached_local_db().await else {
+            return;
+        };
+        let mut events = match db_access::Database::subscribe(&db) {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::warn!("Database views won't refresh on database changes: {e}");
+                return;
+            }
+        };
+        while events.next().await.is_some() {
+            db.clear();
+            resource.restart();
+        }
+    });
+
+    resource
+}
+
+/// `loading` until `resource` is done, then its error or the value rendered by `ready`.
+pub(super) fn render_db_resource<T: Clone + 'static>(
+    resource: Resource<Result<T, String>>,
+    loading: String,
+    ready: impl FnOnce(T) -> Element,
+) -> Element {
+    match resource() {
+        None => rsx! {
+            div { class: "view-content", {loading} }
+        },
+        Some(Err(err)) => rsx! {
+            div { class: "view-content", {format!("{}: {err}", t!("popup-error"))} }
+        },
+        Some(Ok(value)) => ready(value),
+    }
+}
@@ -1,5 +1,8 @@
 pub(super) mod add;
+mod cached_db;
 mod db_operation_helper;
+mod db_resource;
 mod popup;
 mod product_overlay;
 pub(super) mod search;
+pub(super) mod summary;
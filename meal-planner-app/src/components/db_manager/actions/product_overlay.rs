@@ -4,8 +4,8 @@ use super::db_operation_helper::{operation_triggered, DbOperation};
 use crate::components::{layout::use_sidebar_width, product_related::Product};
 use dioxus::prelude::*;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types::Product as ProductData;
-use meal_planner_lib::database_access as db_access;
+use meal_planner_lib::api::data_types::Product as ProductData;
+use meal_planner_lib::api::database_access as db_access;
 
 pub fn create_product_overlay(
     on_close: EventHandler<()>,
@@ -30,7 +30,9 @@ pub fn create_product_overlay(
                         class: "button db-button",
                         onclick: {
                             move |_| {
-                                current_operation.set(DbOperation::Edit);
+                                if let Some(product) = selected_product.peek().as_ref() {
+                                    current_operation.set(DbOperation::Edit(product.id()));
+                                }
                             }
                         },
                         {t!("edit-label")}
@@ -126,7 +128,8 @@ pub fn ProductOverlay(
                     class: "arrow-back-button",
                     style: "margin-bottom: 1rem; font-size: 1.5rem; background: none; border: none; color: var(--color-text); cursor: pointer;",
                     onclick: move |_| on_close.call(()),
-                    "← Back"
+                    span { aria_hidden: "true", "← " }
+                    {t!("action-back")}
                 }
                 div { class: "view-content view-content--overlay", {t!("search-product-details")} }
                 Product { product_signal: selected_product, editable }
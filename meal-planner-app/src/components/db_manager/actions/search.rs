@@ -1,18 +1,35 @@
+use super::db_operation_helper::{bulk_operation_triggered, BulkOperation};
+use super::db_resource::{render_db_resource, use_db_resource};
 use super::popup::DbActionPopup;
 use super::product_overlay::create_product_overlay;
+use crate::components::notifications::{use_notifications, Notice};
 use dioxus::prelude::*;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types::Product as ProductData;
-use meal_planner_lib::database_access as db_access;
+use meal_planner_lib::api::data_types::{Product as ProductData, ProductEdit, ProductId};
+use meal_planner_lib::api::database_access as db_access;
+use std::collections::BTreeSet;
 
 #[allow(clippy::match_wildcard_for_single_variants)]
 fn db_type_to_string(db_type: &db_access::DataBaseTypes) -> String {
     match db_type {
         db_access::DataBaseTypes::Local(_) => t!("db-type-local"),
+        db_access::DataBaseTypes::LocalReadOnly(_) => t!("db-type-local-read-only"),
         _ => todo!(),
     }
 }
 
+// typing pauses shorter than this don't start a search
+const SEARCH_DEBOUNCE_MS: u32 = 250;
+
+async fn debounce_delay() {
+    let delay = document::eval(&format!(
+        "await new Promise((resolve) => setTimeout(resolve, {SEARCH_DEBOUNCE_MS})); return true;"
+    ));
+    if let Err(e) = delay.await {
+        tracing::warn!("Search debounce timer failed: {e:?}");
+    }
+}
+
 #[component]
 pub fn Search() -> Element {
     let mut query = use_signal(String::new);
@@ -20,40 +37,63 @@ pub fn Search() -> Element {
     let mut selected_db_type = use_signal(|| None as Option<db_access::DataBaseTypes>);
     let mut selected_product = use_signal(|| None as Option<ProductData>);
     let operation_results = use_signal(|| None as Option<Result<(), String>>);
+    let mut selected_ids = use_signal(BTreeSet::<ProductId>::new);
+    let mut bulk_brand = use_signal(String::new);
+    // empty shows products of every brand
+    let mut brand_filter = use_signal(String::new);
+    // leaves out imported products with estimated or incomplete nutrients
+    let mut verified_only = use_signal(|| false);
+    // shared by pending debounces and running searches, the newest one cancels the rest
+    let canceller = use_hook(db_access::SearchCanceller::default);
+    // a new search hides the selected products, they shouldn't be changed unseen
+    use_effect(move || {
+        query.read();
+        brand_filter.read();
+        verified_only.read();
+        selected_ids.write().clear();
+    });
 
-    let results = use_resource(move || {
-        let search_text = query();
-
+    let search_canceller = canceller.clone();
+    // `Ok(None)` while a newer search is on its way
+    let mut results = use_db_resource(move |db| {
+        let search_canceller = search_canceller.clone();
         async move {
+            let search_text = query();
+            let brand = brand_filter();
+            let verified = verified_only();
+            let token = search_canceller.start();
+
             let trimmed = search_text.trim();
-            if trimmed.is_empty() {
-                return Vec::<(String, ProductData, db_access::DataBaseTypes)>::new();
+            if trimmed.is_empty() && brand.is_empty() {
+                return Ok(Some(Vec::new()));
             }
 
-            // TODO: change to iterating over the enum once everything is implemented
-            let db_types = vec![db_access::DataBaseTypes::Local(
-                db_access::LOCAL_DB_DEFAULT_FILE.to_string(),
-            )];
-            let mut aggregated: Vec<(String, ProductData, db_access::DataBaseTypes)> = Vec::new();
-
-            for db_type in db_types {
-                if let Some(db) = db_access::get_db(db_type.clone()).await {
-                    let map = db
-                        .get_products_matching_criteria(&[db_access::DbSearchCriteria::ById(
-                            search_text.clone(),
-                        )])
-                        .await;
-                    aggregated.extend(
-                        map.into_iter()
-                            .map(|(id, product)| (id, product, db_type.clone())),
-                    );
-                }
+            // TODO: search the other database types once they are implemented
+            let db_type =
+                db_access::DataBaseTypes::Local(db_access::LOCAL_DB_DEFAULT_FILE.to_string());
+            let mut criteria = vec![db_access::DbSearchCriteria::ById(search_text.clone())];
+            if !brand.is_empty() {
+                criteria.push(db_access::DbSearchCriteria::ByBrand(brand));
             }
-
-            aggregated
+            if verified {
+                criteria.push(db_access::DbSearchCriteria::VerifiedDataOnly);
+            }
+            let found = db_access::search_products(&db, &criteria, &token).await;
+            Ok(found.map(|map| {
+                map.into_iter()
+                    .map(|(id, product)| (id, product, db_type.clone()))
+                    .collect::<Vec<(ProductId, ProductData, db_access::DataBaseTypes)>>()
+            }))
         }
     });
 
+    let notifications = use_notifications();
+    let brands = use_db_resource(move |db| async move {
+        db_access::Database::list_brands(&db)
+            .await
+            .inspect_err(|e| notifications.report(Notice::DbFailure(e.clone())))
+    });
+
     let product_overlay = if let Some(db_type) = selected_db_type() {
         create_product_overlay(
             EventHandler::new(move |()| {
@@ -68,6 +108,60 @@ pub fn Search() -> Element {
         rsx! {}
     };
 
+    let run_bulk = move |operation: BulkOperation| {
+        bulk_operation_triggered(
+            selected_ids
+                .peek()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            operation_results,
+            operation,
+            EventHandler::new(move |()| {
+                selected_ids.write().clear();
+                results.restart();
+            }),
+        );
+    };
+    let selected_count = selected_ids.read().len();
+    let bulk_bar = if selected_count > 0 {
+        rsx! {
+            div {
+                class: "search-bulk-bar",
+                style: "display: flex; flex-wrap: wrap; gap: 0.5rem; align-items: center;",
+                span { {t!("search-selected-count", count : selected_count)} }
+                input {
+                    class: "navigation-button",
+                    r#type: "text",
+                    placeholder: t!("search-bulk-brand-placeholder"),
+                    value: bulk_brand(),
+                    oninput: move |e| bulk_brand.set(e.value()),
+                }
+                button {
+                    class: "navigation-button",
+                    onclick: move |_| {
+                        let brand = bulk_brand.peek().trim().to_string();
+                        let brand = (!brand.is_empty()).then_some(brand);
+                        run_bulk(BulkOperation::Edit(ProductEdit::SetBrand(brand)));
+                    },
+                    {t!("search-bulk-set-brand")}
+                }
+                button {
+                    class: "navigation-button",
+                    onclick: move |_| run_bulk(BulkOperation::Delete),
+                    {t!("search-bulk-delete")}
+                }
+                button {
+                    class: "navigation-button",
+                    onclick: move |_| selected_ids.write().clear(),
+                    {t!("search-bulk-clear")}
+                }
+            }
+        }
+    } else {
+        rsx! {}
+    };
+
     rsx! {
         div {
             class: "search-panel",
@@ -78,7 +172,17 @@ pub fn Search() -> Element {
                     r#type: "text",
                     placeholder: t!("search-placeholder"),
                     value: input_value(),
-                    oninput: move |e| input_value.set(e.value()),
+                    oninput: move |e| {
+                        let text = e.value();
+                        input_value.set(text.clone());
+                        let token = canceller.start();
+                        spawn(async move {
+                            debounce_delay().await;
+                            if !token.is_cancelled() {
+                                query.set(text);
+                            }
+                        });
+                    },
                     onkeydown: move |e| {
                         if e.key() == Key::Enter {
                             query.set(input_value.peek().clone());
@@ -86,6 +190,27 @@ pub fn Search() -> Element {
                     },
                     style: "flex: 1; min-width: 12rem;",
                 }
+                select {
+                    class: "navigation-button",
+                    aria_label: t!("search-brand-filter"),
+                    value: brand_filter(),
+                    onchange: move |e| brand_filter.set(e.value()),
+                    option { value: "", {t!("search-brand-all")} }
+                    for (brand , count) in brands().and_then(Result::ok).unwrap_or_default() {
+                        option {
+                            value: brand.clone(),
+                            {t!("search-brand-option", brand : brand.clone(), count : count)}
+                        }
+                    }
+                }
+                label { style: "display: flex; gap: 0.25rem; align-items: center;",
+                    input {
+                        r#type: "checkbox",
+                        checked: verified_only(),
+                        onchange: move |e: FormEvent| verified_only.set(e.checked()),
+                    }
+                    {t!("search-verified-only")}
+                }
                 button {
                     class: "navigation-button navigation-button--selected",
                     onclick: move |_| {
@@ -95,42 +220,68 @@ pub fn Search() -> Element {
                 }
             }
 
-            match results() {
-                None => rsx! {
-                    div { class: "view-content", {t!("search-loading")} }
-                },
-                Some(ref list) => {
-                    if query().trim().is_empty() {
-                        rsx! {
-                            div { class: "view-content", {t!("search-empty-prompt")} }
-                        }
-                    } else if list.is_empty() {
-                        rsx! {
-                            div { class: "view-content", {t!("search-no-results")} }
-                        }
-                    } else {
-                        rsx! {
-                            div {
-                                class: "search-results",
-                                style: "display: flex; flex-direction: column; gap: 0.5rem;",
-                                for (id , product , db_type) in list.iter().cloned() {
-                                    button {
-                                        class: "navigation-button",
-                                        style: "justify-content: space-between; display: flex; align-items: center;",
-                                        onclick: move |_| {
-                                            selected_db_type.set(Some(db_type.clone()));
-                                            selected_product.set(Some(product.clone()));
-                                        },
-                                        span { "{id}" }
-                                        span { style: "font-weight: 600; color: var(--color-highlight);",
-                                            {db_type_to_string(&db_type)}
+            {bulk_bar}
+
+            {
+                render_db_resource(
+                    results,
+                    t!("search-loading"),
+                    |list| match list {
+                        None => rsx! {
+                            div { class: "view-content", {t!("search-loading")} }
+                        },
+                        Some(ref list) => {
+                            if query().trim().is_empty() && brand_filter().is_empty() {
+                                rsx! {
+                                    div { class: "view-content", {t!("search-empty-prompt")} }
+                                }
+                            } else if list.is_empty() {
+                                rsx! {
+                                    div { class: "view-content", {t!("search-no-results")} }
+                                }
+                            } else {
+                                rsx! {
+                                    div {
+                                        class: "search-results",
+                                        style: "display: flex; flex-direction: column; gap: 0.5rem;",
+                                        for (id , product , db_type) in list.iter().cloned() {
+                                            div {
+                                                style: "display: flex; gap: 0.5rem; align-items: center;",
+                                                input {
+                                                    r#type: "checkbox",
+                                                    aria_label: t!("search-select-product", id : id.to_string()),
+                                                    checked: selected_ids.read().contains(&id),
+                                                    onchange: {
+                                                        let id = id.clone();
+                                                        move |e: FormEvent| {
+                                                            if e.checked() {
+                                                                selected_ids.write().insert(id.clone());
+                                                            } else {
+                                                                selected_ids.write().remove(&id);
+                                                            }
+                                                        }
+                                                    },
+                                                }
+                                            button {
+                                                class: "navigation-button",
+                                                style: "justify-content: space-between; display: flex; align-items: center; flex: 1;",
+                                                onclick: move |_| {
+                                                    selected_db_type.set(Some(db_type.clone()));
+                                                    selected_product.set(Some(product.clone()));
+                                                },
+                                                span { "{id}" }
+                                                span { style: "font-weight: 600; color: var(--color-highlight);",
+                                                    {db_type_to_string(&db_type)}
+                                                }
+                                            }
+                                            }
                                         }
                                     }
                                 }
                             }
                         }
-                    }
-                }
+                    },
+                )
             }
 
             {product_overlay}
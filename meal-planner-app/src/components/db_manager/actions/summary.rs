@@ -0,0 +1,83 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::database_access::Database;
+
+use super::db_resource::{render_db_resource, use_db_resource};
+
+#[component]
+fn SummaryRow(label: String, count: u64, total: u64) -> Element {
+    let share = (count * 100).checked_div(total).unwrap_or(0);
+    rsx! {
+        div { class: "db-summary__row",
+            span { {label} }
+            span { class: "db-summary__count", "{count} ({share}%)" }
+        }
+    }
+}
+
+const LEAST_COMPLETE_LIMIT: usize = 10;
+
+#[component]
+pub fn Summary() -> Element {
+    let summary = use_db_resource(|db| async move { db.data_quality_summary().await });
+    let least_complete =
+        use_db_resource(|db| async move { db.least_complete_products(LEAST_COMPLETE_LIMIT).await });
+
+    rsx! {
+        div { class: "db-summary",
+            {
+                render_db_resource(
+                    summary,
+                    t!("summary-loading"),
+                    |summary| rsx! {
+                        div { class: "db-summary__row",
+                            span { {t!("summary-total-products")} }
+                            span { class: "db-summary__count", "{summary.total_products}" }
+                        }
+                        SummaryRow {
+                            label: t!("summary-without-micro-nutrients"),
+                            count: summary.without_micro_nutrients,
+                            total: summary.total_products,
+                        }
+                        SummaryRow {
+                            label: t!("summary-incomplete-micro-nutrients"),
+                            count: summary.incomplete_micro_nutrients,
+                            total: summary.total_products,
+                        }
+                        SummaryRow {
+                            label: t!("summary-without-extra-units"),
+                            count: summary.without_extra_units,
+                            total: summary.total_products,
+                        }
+                        SummaryRow {
+                            label: t!("summary-without-brand"),
+                            count: summary.without_brand,
+                            total: summary.total_products,
+                        }
+                        SummaryRow {
+                            label: t!("summary-without-barcode"),
+                            count: summary.without_barcode,
+                            total: summary.total_products,
+                        }
+                        SummaryRow {
+                            label: t!("summary-unused-in-plans"),
+                            count: summary.unused_in_plans,
+                            total: summary.total_products,
+                        }
+                    },
+                )
+            }
+            if let Some(Ok(products)) = least_complete() {
+                if !products.is_empty() {
+                    div { class: "db-summary__header", {t!("summary-least-complete")} }
+                    for (id, report) in products {
+                        div { class: "db-summary__row",
+                            span { "{id}" }
+                            span { class: "db-summary__count", "{report.score:.0}%" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
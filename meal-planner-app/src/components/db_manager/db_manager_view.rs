@@ -1,4 +1,4 @@
-use super::actions::{add, search};
+use super::actions::{add, search, summary};
 use dioxus::prelude::*;
 use dioxus_i18n::t;
 
@@ -6,6 +6,7 @@ use dioxus_i18n::t;
 enum DbActionKinds {
     Add,
     Search,
+    Summary,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -14,10 +15,16 @@ struct ButtonData {
     action: DbActionKinds,
 }
 
-static BUTTONS: &[ButtonData] = &[ButtonData {
-    label: "action-add",
-    action: DbActionKinds::Add,
-}];
+static BUTTONS: &[ButtonData] = &[
+    ButtonData {
+        label: "action-add",
+        action: DbActionKinds::Add,
+    },
+    ButtonData {
+        label: "action-summary",
+        action: DbActionKinds::Summary,
+    },
+];
 
 #[component]
 pub fn DbManagerView() -> Element {
@@ -34,6 +41,7 @@ pub fn DbManagerView() -> Element {
         rsx!(
             button {
                 class: "{cls}",
+                aria_pressed: btn_data.action == curr_action,
                 onclick: move |_| {
                     let mut selected_action = selected_action;
                     selected_action.set(btn_data.action);
@@ -44,7 +52,7 @@ pub fn DbManagerView() -> Element {
     });
 
     match curr_action {
-        DbActionKinds::Add => rsx! {
+        DbActionKinds::Add | DbActionKinds::Summary => rsx! {
             // Arrow-like button to go back to Search
             div {
                 button {
@@ -53,14 +61,25 @@ pub fn DbManagerView() -> Element {
                         let mut selected_action = selected_action;
                         selected_action.set(DbActionKinds::Search);
                     },
-                    "← Back"
+                    span { aria_hidden: "true", "← " }
+                    {t!("action-back")}
+                }
+            }
+            div { class: "view-content",
+                if curr_action == DbActionKinds::Add {
+                    add::Add {}
+                } else {
+                    summary::Summary {}
                 }
             }
-            div { class: "view-content", add::Add {} }
         },
         DbActionKinds::Search => rsx! {
-            nav { class: "view-content",
-                nav { class: "navigation-button-bar", {buttons_elems} }
+            div { class: "view-content",
+                nav {
+                    class: "navigation-button-bar",
+                    aria_label: t!("action-db-manager"),
+                    {buttons_elems}
+                }
                 div { search::Search {} }
             }
         },
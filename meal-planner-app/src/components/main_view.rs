@@ -1,20 +1,80 @@
 use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::data_types::UsageEvent;
+use meal_planner_lib::api::database_access as db_access;
+use meal_planner_lib::api::version_info;
 
 use crate::components::{
-    db_manager::DbManagerView, food_swapper::SwapFoodView, meal_planner::MealPlanView,
+    db_manager::DbManagerView,
+    food_swapper::SwapFoodView,
+    meal_planner::MealPlanView,
+    usage_stats::{record_usage, UsageStatsView},
 };
 
+#[cfg(feature = "solver-sandbox")]
+use crate::components::solver_sandbox::SolverSandboxView;
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum ViewKind {
     MealPlan,
     SwapFood,
     DbManager,
+    UsageStats,
+    #[cfg(feature = "solver-sandbox")]
+    SolverSandbox,
+}
+
+impl ViewKind {
+    pub const ALL: [ViewKind; 4] = [
+        ViewKind::MealPlan,
+        ViewKind::SwapFood,
+        ViewKind::DbManager,
+        ViewKind::UsageStats,
+    ];
+
+    /// Name stored in the usage stats, must not change between releases.
+    pub fn usage_name(self) -> &'static str {
+        match self {
+            ViewKind::MealPlan => "meal-plan",
+            ViewKind::SwapFood => "swap-foods",
+            ViewKind::DbManager => "products",
+            ViewKind::UsageStats => "usage-stats",
+            #[cfg(feature = "solver-sandbox")]
+            ViewKind::SolverSandbox => "solver-sandbox",
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            ViewKind::MealPlan => t!("action-meal-plan"),
+            ViewKind::SwapFood => t!("action-swap-foods"),
+            ViewKind::DbManager => t!("label-product-count", count: 2),
+            ViewKind::UsageStats => t!("action-usage-stats"),
+            #[cfg(feature = "solver-sandbox")]
+            ViewKind::SolverSandbox => t!("sandbox-title"),
+        }
+    }
 }
 
 #[component]
 pub fn MainView(selection: Signal<ViewKind>) -> Element {
+    use_effect(move || {
+        record_usage(UsageEvent::ViewOpened(selection().usage_name().to_string()));
+    });
+    // a database of a newer app would fail on its first unknown column, say so up front
+    let newer_db = use_resource(|| async {
+        let path = db_access::resolve_db_path(db_access::LOCAL_DB_DEFAULT_FILE).ok()?;
+        let error = db_access::LocalProductDb::open(&path).await.err()?;
+        db_access::NewerSchema::matches(&error).then_some(())
+    });
+
     rsx! {
         main { class: "content-shell app-theme", role: "main",
+            if newer_db().flatten().is_some() {
+                div { class: "db-version-warning", role: "alert",
+                    {t!("error-db-newer-version", version : version_info().crate_version)}
+                }
+            }
             match selection() {
                 ViewKind::MealPlan => rsx! {
                     MealPlanView {}
@@ -25,6 +85,13 @@ pub fn MainView(selection: Signal<ViewKind>) -> Element {
                 ViewKind::DbManager => rsx! {
                     DbManagerView {}
                 },
+                ViewKind::UsageStats => rsx! {
+                    UsageStatsView {}
+                },
+                #[cfg(feature = "solver-sandbox")]
+                ViewKind::SolverSandbox => rsx! {
+                    SolverSandboxView {}
+                },
             }
         }
     }
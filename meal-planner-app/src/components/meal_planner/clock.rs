@@ -0,0 +1,20 @@
+use dioxus::prelude::*;
+
+/// Current Unix time in seconds, read from the webview as `SystemTime` panics on the web.
+pub async fn unix_now() -> Result<u64, String> {
+    document::eval("dioxus.send(Math.floor(Date.now() / 1000));")
+        .recv::<u64>()
+        .await
+        .map_err(|e| format!("Failed to read the current time: {e:?}"))
+}
+
+/// Unix time in seconds of the last local midnight.
+pub async fn local_day_start() -> Result<u64, String> {
+    document::eval(
+        "const day = new Date(); day.setHours(0, 0, 0, 0); \
+         dioxus.send(Math.floor(day.getTime() / 1000));",
+    )
+    .recv::<u64>()
+    .await
+    .map_err(|e| format!("Failed to read the current day: {e:?}"))
+}
@@ -1,8 +1,70 @@
+use std::collections::BTreeMap;
+
 use dioxus::prelude::*;
+use meal_planner_lib::api::notifications::{schedule_notifications, PendingNotification};
+use meal_planner_lib::api::targets_review::{ReviewReason, TargetsRecord, TargetsReviewRule};
+
+use super::clock::{local_day_start, unix_now};
+use super::meal_reminders::schedule_reminders;
+use super::saved_plan_meals::{load_first_plan, open_db, SavedPlanMeals};
+use super::targets_review_banner::TargetsReviewBanner;
+use crate::components::notifications::{use_notifications, Notice};
+
+/// Why the targets of the active profile should be recalculated, empty until it has a saved plan.
+async fn load_review_reasons() -> Result<Vec<ReviewReason>, String> {
+    let mut db = open_db().await?;
+    if db.get_plan_ids().await?.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(profile) = db.get_user_profile().await? else {
+        return Ok(Vec::new());
+    };
+    let now = unix_now().await?;
+    let Some(targets) = db.get_targets_record().await? else {
+        // the plans were solved for targets of the profile as it is now, later reviews compare
+        // against it
+        db.set_targets_record(&TargetsRecord {
+            computed_at: now,
+            weight_kg: profile.weight_kg,
+        })
+        .await?;
+        return Ok(Vec::new());
+    };
+    Ok(TargetsReviewRule::default().check(&targets, profile.weight_kg, now))
+}
+
+/// Reminders for the meals of the saved plan, its first day being today.
+async fn load_reminders() -> Result<Vec<PendingNotification>, String> {
+    let Some((_, plan)) = load_first_plan().await? else {
+        return Ok(Vec::new());
+    };
+    let now = unix_now().await?;
+    let today = local_day_start().await?;
+    // saved plans keep no meal times of their own, the reminders follow their meal slots
+    Ok(schedule_notifications(&plan, &BTreeMap::new(), today, now))
+}
 
 #[component]
 pub fn MealPlanView() -> Element {
+    let mut review_reasons = use_signal(Vec::new);
+    let notifications = use_notifications();
+    use_future(move || async move {
+        match load_review_reasons().await {
+            Ok(reasons) => review_reasons.set(reasons),
+            Err(e) => notifications.report(Notice::DbFailure(e)),
+        }
+    });
+    use_future(|| async {
+        match load_reminders().await {
+            Ok(reminders) if reminders.is_empty() => {}
+            Ok(reminders) => schedule_reminders(&reminders),
+            Err(e) => tracing::warn!("Meal reminders not scheduled: {e}"),
+        }
+    });
     rsx! {
-        div { class: "view-content", "Meal Plan View" }
+        TargetsReviewBanner { reasons: review_reasons }
+        div { class: "view-content",
+            SavedPlanMeals {}
+        }
     }
 }
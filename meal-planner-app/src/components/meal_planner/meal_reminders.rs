@@ -0,0 +1,58 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::notifications::PendingNotification;
+
+// Web Notifications API, available in browsers and the desktop webview. Android webviews do not
+// expose it, so on mobile the script reports the reminders as not scheduled.
+const SCHEDULE_SCRIPT: &str = r#"
+const reminders = await dioxus.recv();
+if (!("Notification" in window)) {
+    dioxus.send(false);
+    return;
+}
+let permission = Notification.permission;
+if (permission === "default") {
+    permission = await Notification.requestPermission();
+}
+if (permission !== "granted") {
+    dioxus.send(false);
+    return;
+}
+window.mealReminderTimers = window.mealReminderTimers || {};
+for (const [id, fireAt, title, body] of reminders) {
+    clearTimeout(window.mealReminderTimers[id]);
+    const delay = Math.max(0, fireAt * 1000 - Date.now());
+    window.mealReminderTimers[id] = setTimeout(
+        () => new Notification(title, { body, tag: id }),
+        delay,
+    );
+}
+dioxus.send(true);
+"#;
+
+/// Hands `pending` reminders to the platform, replacing earlier ones with the same ID.
+pub fn schedule_reminders(pending: &[PendingNotification]) {
+    let reminders: Vec<(String, u64, String, String)> = pending
+        .iter()
+        .map(|n| {
+            (
+                n.id.clone(),
+                n.fire_at,
+                t!("reminder-title", meal: n.payload.meal.clone()),
+                n.payload.summary(),
+            )
+        })
+        .collect();
+    spawn(async move {
+        let mut eval = document::eval(SCHEDULE_SCRIPT);
+        if let Err(e) = eval.send(reminders) {
+            tracing::warn!("Failed to pass meal reminders: {e:?}");
+            return;
+        }
+        match eval.recv::<bool>().await {
+            Ok(true) => tracing::info!("Meal reminders scheduled"),
+            Ok(false) => tracing::warn!("Notifications are unavailable or not permitted"),
+            Err(e) => tracing::warn!("Failed to schedule meal reminders: {e:?}"),
+        }
+    });
+}
@@ -1,3 +1,7 @@
+mod clock;
 mod meal_plan_view;
+mod meal_reminders;
+mod saved_plan_meals;
+mod targets_review_banner;
 
 pub use meal_plan_view::MealPlanView;
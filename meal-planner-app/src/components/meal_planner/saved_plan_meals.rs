@@ -0,0 +1,98 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::constraints_solver::Solution;
+use meal_planner_lib::api::database_access as db_access;
+
+use crate::components::notifications::{use_notifications, Notice};
+
+pub(super) async fn open_db() -> Result<Box<dyn db_access::MutableDatabase>, String> {
+    db_access::get_mutable_db(db_access::DataBaseTypes::Local(
+        db_access::LOCAL_DB_DEFAULT_FILE.to_string(),
+    ))
+    .await
+    .ok_or_else(|| t!("error-db-access"))
+}
+
+pub(super) async fn load_first_plan() -> Result<Option<(String, Solution)>, String> {
+    let db = open_db().await?;
+    let Some(plan_id) = db.get_plan_ids().await?.into_iter().next() else {
+        return Ok(None);
+    };
+    Ok(db.get_plan(&plan_id).await?.map(|plan| (plan_id, plan)))
+}
+
+/// Meals of the saved plan per day, reordered by dragging a meal onto another one.
+#[component]
+pub fn SavedPlanMeals() -> Element {
+    let mut plan = use_resource(load_first_plan);
+    // day and meal currently being dragged
+    let mut dragged = use_signal(|| None::<(String, String)>);
+    let notifications = use_notifications();
+
+    let mut drop_on = move |plan_id: String, day: String, index: usize| {
+        let Some((from_day, meal)) = dragged.take() else {
+            return;
+        };
+        if from_day != day {
+            return;
+        }
+        spawn(async move {
+            let result = async {
+                let mut db = open_db().await?;
+                db.reorder_meal(&plan_id, &day, &meal, index).await
+            };
+            match result.await {
+                Ok(()) => plan.restart(),
+                Err(e) => notifications.report(Notice::DbFailure(e)),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "saved-plan",
+            match &*plan.read() {
+                None => rsx! {
+                    div { {t!("summary-loading")} }
+                },
+                Some(Err(err)) => rsx! {
+                    div { role: "alert", {format!("{}: {err}", t!("popup-error"))} }
+                },
+                Some(Ok(None)) => rsx! {
+                    p { {t!("saved-plan-empty")} }
+                },
+                Some(Ok(Some((plan_id, solution)))) => rsx! {
+                    p { class: "saved-plan__hint", {t!("saved-plan-drag-hint")} }
+                    for day in solution.day_names() {
+                        section { key: "{day}", class: "saved-plan__day",
+                            h3 { "{day}" }
+                            ol {
+                                for (index, meal) in solution.meal_names(&day).into_iter().enumerate() {
+                                    li {
+                                        key: "{meal}",
+                                        class: "saved-plan__meal",
+                                        draggable: "true",
+                                        ondragstart: {
+                                            let day = day.clone();
+                                            let meal = meal.clone();
+                                            move |_| dragged.set(Some((day.clone(), meal.clone())))
+                                        },
+                                        ondragover: move |evt: DragEvent| evt.prevent_default(),
+                                        ondrop: {
+                                            let plan_id = plan_id.clone();
+                                            let day = day.clone();
+                                            move |evt: DragEvent| {
+                                                evt.prevent_default();
+                                                drop_on(plan_id.clone(), day.clone(), index);
+                                            }
+                                        },
+                                        "{meal}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::targets_review::ReviewReason;
+
+#[component]
+pub fn TargetsReviewBanner(reasons: Signal<Vec<ReviewReason>>) -> Element {
+    let messages: Vec<String> = reasons()
+        .iter()
+        .map(|reason| match reason {
+            ReviewReason::WeightChanged { delta_kg } => {
+                t!("targets-review-weight", delta: format!("{delta_kg:+.1}"))
+            }
+            ReviewReason::TargetsOutdated { age_days } => {
+                t!("targets-review-age", days: age_days.to_string())
+            }
+        })
+        .collect();
+
+    rsx! {
+        if !messages.is_empty() {
+            div { class: "targets-review-banner", role: "status",
+                div { class: "targets-review-banner__text",
+                    strong { {t!("targets-review-title")} }
+                    for message in messages {
+                        span { {message} }
+                    }
+                }
+                button {
+                    class: "targets-review-banner__dismiss",
+                    r#type: "button",
+                    aria_label: t!("targets-review-dismiss"),
+                    onclick: move |_| reasons.set(Vec::new()),
+                    "×"
+                }
+            }
+        }
+    }
+}
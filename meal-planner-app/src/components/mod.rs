@@ -2,10 +2,15 @@
 //! They can be used to defined common UI elements like buttons, forms, and modals. In this template, we define a Hero
 //! component  to be used in our app.
 
+pub mod accessibility;
 pub mod action_bar;
 pub mod db_manager;
 pub mod food_swapper;
 pub mod layout;
 pub mod main_view;
 pub mod meal_planner;
+pub mod notifications;
 pub mod product_related;
+#[cfg(feature = "solver-sandbox")]
+pub mod solver_sandbox;
+pub mod usage_stats;
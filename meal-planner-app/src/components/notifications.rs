@@ -0,0 +1,79 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+
+/// Outcome of a lib call worth telling the user about, shown as a toast.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Notice {
+    /// The database couldn't be opened, read or written.
+    DbFailure(String),
+    /// The solver found no plan satisfying the constraints.
+    Infeasible(String),
+}
+
+impl Notice {
+    fn message(&self) -> String {
+        match self {
+            Self::DbFailure(error) => t!("notice-db-failure", error : error.clone()),
+            Self::Infeasible(error) => t!("notice-infeasible", error : error.clone()),
+        }
+    }
+
+    fn is_error(&self) -> bool {
+        match self {
+            Self::DbFailure(_) | Self::Infeasible(_) => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct NotificationContext {
+    pub toasts: Signal<Vec<(u64, Notice)>>,
+    pub next_id: Signal<u64>,
+}
+
+impl NotificationContext {
+    /// Shows `notice` until the user dismisses it, the error is logged too.
+    pub fn report(mut self, notice: Notice) {
+        if notice.is_error() {
+            tracing::warn!("{notice:?}");
+        }
+        let id = *self.next_id.peek();
+        self.next_id.set(id + 1);
+        self.toasts.write().push((id, notice));
+    }
+
+    fn dismiss(mut self, id: u64) {
+        self.toasts.write().retain(|(toast_id, _)| *toast_id != id);
+    }
+}
+
+/// Convenience hook to access the notification context.
+pub fn use_notifications() -> NotificationContext {
+    use_context::<NotificationContext>()
+}
+
+/// Dismissible toasts of every reported notice, newest last.
+#[component]
+pub fn NotificationCenter() -> Element {
+    let notifications = use_notifications();
+    let toasts = notifications.toasts;
+
+    rsx! {
+        div { class: "notification-center", aria_live: "polite",
+            for (id , notice) in toasts() {
+                div {
+                    key: "{id}",
+                    class: if notice.is_error() { "toast toast--error" } else { "toast" },
+                    role: if notice.is_error() { "alert" } else { "status" },
+                    span { {notice.message()} }
+                    button {
+                        class: "toast__dismiss",
+                        aria_label: t!("notice-dismiss"),
+                        onclick: move |_| notifications.dismiss(id),
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use dioxus::html::geometry::WheelDelta;
 use dioxus::prelude::*;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types::{AllowedUnits as DataAllowedUnits, AllowedUnitsType, UnitData};
-use std::collections::HashSet;
+use meal_planner_lib::api::data_types::{
+    AllowedUnits as DataAllowedUnits, AllowedUnitsType, UnitData,
+};
+use std::collections::{BTreeMap, HashSet};
 use strum::IntoEnumIterator;
 
 const DEFAULT_UNIT: (AllowedUnitsType, UnitData) = (
@@ -29,6 +31,17 @@ fn unit_label(unit: AllowedUnitsType) -> String {
     }
 }
 
+/// Product specific label of `unit` when it has one, otherwise the localized unit name.
+pub fn product_unit_label(
+    unit_labels: &BTreeMap<AllowedUnitsType, String>,
+    unit: AllowedUnitsType,
+) -> String {
+    unit_labels
+        .get(&unit)
+        .cloned()
+        .unwrap_or_else(|| unit_label(unit))
+}
+
 fn ordered_units(units: &DataAllowedUnits) -> Vec<(AllowedUnitsType, UnitData)> {
     let mut list: Vec<(AllowedUnitsType, UnitData)> = Vec::new();
     for unit in AllowedUnitsType::iter() {
@@ -46,7 +59,11 @@ fn ordered_units(units: &DataAllowedUnits) -> Vec<(AllowedUnitsType, UnitData)>
 #[allow(clippy::cast_sign_loss)]
 #[allow(clippy::redundant_closure)]
 #[component]
-pub fn AllowedUnits(ad_signal: Signal<DataAllowedUnits>, editable: bool) -> Element {
+pub fn AllowedUnits(
+    ad_signal: Signal<DataAllowedUnits>,
+    unit_labels: BTreeMap<AllowedUnitsType, String>,
+    editable: bool,
+) -> Element {
     let mut rows_signal = use_signal(|| ordered_units(&ad_signal()));
     let mut last_seen_map = use_signal(|| ad_signal());
 
@@ -129,12 +146,12 @@ pub fn AllowedUnits(ad_signal: Signal<DataAllowedUnits>, editable: bool) -> Elem
                                 option {
                                     value: format!("{option:?}"),
                                     disabled: option != unit && used_units.contains(&option),
-                                    {unit_label(option)}
+                                    {product_unit_label(&unit_labels, option)}
                                 }
                             }
                         }
                     } else {
-                        span { class: "allowed-value", {unit_label(unit)} }
+                        span { class: "allowed-value", {product_unit_label(&unit_labels, unit)} }
                     }
                     span { class: "allowed-label", {t!("au-amount")} }
                     if editable {
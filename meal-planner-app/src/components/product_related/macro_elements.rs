@@ -1,9 +1,11 @@
 use dioxus::html::geometry::WheelDelta;
 use dioxus::prelude::*;
+use dioxus_i18n::prelude::i18n;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types::{
+use meal_planner_lib::api::data_types::{
     MacroElements as DataMacroElements, MacroElementsType as DataMEType,
 };
+use meal_planner_lib::api::number_format::{format_number, parse_number};
 use std::rc::Rc;
 
 #[component]
@@ -15,15 +17,16 @@ fn MacroElementSingleInputField(
     input_ref: Signal<Option<MountedData>>,
     editable: bool,
 ) -> Element {
+    let language = i18n().language().to_string();
     rsx! {
         div {
             {format!("{}: ", t!(label_key))}
             if editable {
                 input {
                     class: "nutrient-input",
-                    r#type: "number",
-                    step: "0.01",
-                    value: format!("{:.2}", signal()),
+                    r#type: "text",
+                    inputmode: "decimal",
+                    value: format_number(signal(), 2, &language),
                     onwheel: move |e| {
                         e.prevent_default();
                         e.stop_propagation();
@@ -38,7 +41,7 @@ fn MacroElementSingleInputField(
                         signal.set(next);
                     },
                     onchange: move |e| {
-                        if let Ok(val) = e.value().parse::<f32>() {
+                        if let Ok(val) = parse_number(&e.value()) {
                             signal.set(val.max(0.0));
                         } else {
                             signal.set(signal());
@@ -64,7 +67,7 @@ fn MacroElementSingleInputField(
                     },
                 }
             } else {
-                {format!("{:.2}", signal())}
+                {format_number(signal(), 2, &language)}
             }
         }
     }
@@ -116,7 +119,13 @@ pub fn MacroElements(me_signal: Signal<DataMacroElements>, editable: bool) -> El
         }
     });
 
-    let calories = me_signal()[DataMEType::Calories];
+    let language = i18n().language().to_string();
+    let calories = format_number(me_signal()[DataMEType::Calories], 2, &language);
+    let computed_calories = format_number(me_signal().computed_calories(), 2, &language);
+    let calories_override = me_signal()
+        .calories_override()
+        .map(|kcal| format_number(kcal, 2, &language))
+        .unwrap_or_default();
 
     rsx! {
         div {
@@ -162,7 +171,31 @@ pub fn MacroElements(me_signal: Signal<DataMacroElements>, editable: bool) -> El
             }
             div {
                 {format!("{}: ", t!("label-calories"))}
-                {format!("{calories:.2}")}
+                {calories}
+                if me_signal().calories_override().is_some() {
+                    {format!(" ({})", t!("label-calories-computed", kcal: computed_calories))}
+                }
+            }
+            if editable {
+                div {
+                    {format!("{}: ", t!("label-calories-override"))}
+                    input {
+                        class: "nutrient-input",
+                        r#type: "text",
+                        inputmode: "decimal",
+                        value: calories_override,
+                        onchange: move |e| {
+                            let value = e.value();
+                            let mut new_me = me_signal();
+                            if value.trim().is_empty() {
+                                new_me.set_calories_override(None);
+                            } else if let Ok(kcal) = parse_number(&value) {
+                                new_me.set_calories_override(Some(kcal.max(0.0)));
+                            }
+                            me_signal.set(new_me);
+                        },
+                    }
+                }
             }
         }
     }
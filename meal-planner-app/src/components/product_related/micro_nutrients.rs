@@ -1,9 +1,12 @@
 use dioxus::html::geometry::WheelDelta;
 use dioxus::prelude::*;
+use dioxus_i18n::prelude::i18n;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types::{
+use meal_planner_lib::api::data_types::{
     MicroNutrients as DataMicroNutrients, MicroNutrientsType as DataMNType,
 };
+use meal_planner_lib::api::number_format::{format_number, parse_number};
+use strum::IntoEnumIterator;
 
 #[component]
 fn MicroNutrientInput(
@@ -18,18 +21,19 @@ fn MicroNutrientInput(
     let is_none = signal().is_none();
     let disabled_key = format!("mn-{mn_type:?}-disabled");
     let enabled_key = format!("mn-{mn_type:?}-enabled");
+    let language = i18n().language().to_string();
 
     rsx! {
         div { class: "micro-row",
-            span { class: "micro-label", {format!("{}:", t!(label_key))} }
+            span { class: "micro-label", {format!("{} ({}):", t!(label_key), mn_type.info().unit)} }
             if editable {
                 span { class: "micro-controls",
                     if is_none {
                         input {
                             key: "{disabled_key}",
                             class: "micro-input nutrient-input nutrient-input--disabled",
-                            r#type: "number",
-                            step: "0.01",
+                            r#type: "text",
+                            inputmode: "decimal",
                             disabled: true,
                             value: "",
                             placeholder: none_label.clone(),
@@ -38,9 +42,9 @@ fn MicroNutrientInput(
                         input {
                             key: "{enabled_key}",
                             class: "micro-input nutrient-input",
-                            r#type: "number",
-                            step: "0.01",
-                            value: signal().map(|v| format!("{v:.2}")).unwrap_or_default(),
+                            r#type: "text",
+                            inputmode: "decimal",
+                            value: signal().map(|v| format_number(v, 2, &language)).unwrap_or_default(),
                             placeholder: none_label.clone(),
                             onwheel: move |e| {
                                 e.prevent_default();
@@ -61,7 +65,7 @@ fn MicroNutrientInput(
                                 }
                             },
                             onchange: move |e| {
-                                if let Ok(val) = e.value().parse::<f32>() {
+                                if let Ok(val) = parse_number(&e.value()) {
                                     signal.set(Some(val.max(0.0)));
                                 }
                                 let mut new_mn = mn_signal().clone();
@@ -94,37 +98,53 @@ fn MicroNutrientInput(
                 }
             } else {
                 if let Some(val) = signal() {
-                    span { class: "micro-value", {format!("{val:.2}")} }
+                    span { class: "micro-value", {format_number(val, 2, &language)} }
                 }
             }
         }
     }
 }
 
+fn label_key(mn_type: DataMNType) -> &'static str {
+    match mn_type {
+        DataMNType::Fiber => "mn-fiber",
+        DataMNType::Zinc => "mn-zinc",
+        DataMNType::Sodium => "mn-sodium",
+        DataMNType::Alcohol => "mn-alcohol",
+        DataMNType::Iron => "mn-iron",
+        DataMNType::Calcium => "mn-calcium",
+        DataMNType::Potassium => "mn-potassium",
+        DataMNType::Magnesium => "mn-magnesium",
+        DataMNType::VitaminA => "mn-vitamin-a",
+        DataMNType::VitaminC => "mn-vitamin-c",
+        DataMNType::VitaminD => "mn-vitamin-d",
+        DataMNType::VitaminB12 => "mn-vitamin-b12",
+    }
+}
+
 #[component]
 pub fn MicroNutrients(mn_signal: Signal<DataMicroNutrients>, editable: bool) -> Element {
-    // Signals per micro nutrient
-    let mut fiber_signal = use_signal(|| mn_signal()[DataMNType::Fiber]);
-    let mut zinc_signal = use_signal(|| mn_signal()[DataMNType::Zinc]);
-    let mut sodium_signal = use_signal(|| mn_signal()[DataMNType::Sodium]);
-    let mut alcohol_signal = use_signal(|| mn_signal()[DataMNType::Alcohol]);
+    // Signals per micro nutrient, always created in the same order
+    let signals: Vec<(DataMNType, Signal<Option<f32>>)> = DataMNType::iter()
+        .map(|mn_type| (mn_type, use_signal(|| mn_signal()[mn_type])))
+        .collect();
 
     // Keep local signals in sync with parent
+    let parent_signals = signals.clone();
     use_effect(move || {
         let mn = mn_signal();
-        fiber_signal.set(mn[DataMNType::Fiber]);
-        zinc_signal.set(mn[DataMNType::Zinc]);
-        sodium_signal.set(mn[DataMNType::Sodium]);
-        alcohol_signal.set(mn[DataMNType::Alcohol]);
+        for (mn_type, mut signal) in parent_signals.iter().copied() {
+            signal.set(mn[mn_type]);
+        }
     });
 
     // Push local changes back to parent
+    let local_signals = signals.clone();
     use_effect(move || {
         let mut new_mn = mn_signal().clone();
-        new_mn[DataMNType::Fiber] = fiber_signal();
-        new_mn[DataMNType::Zinc] = zinc_signal();
-        new_mn[DataMNType::Sodium] = sodium_signal();
-        new_mn[DataMNType::Alcohol] = alcohol_signal();
+        for (mn_type, signal) in local_signals.iter().copied() {
+            new_mn[mn_type] = signal();
+        }
         if mn_signal() != new_mn {
             mn_signal.set(new_mn);
         }
@@ -132,40 +152,16 @@ pub fn MicroNutrients(mn_signal: Signal<DataMicroNutrients>, editable: bool) ->
 
     rsx! {
         div { class: "micro-section",
-            if editable || fiber_signal().is_some() {
-                MicroNutrientInput {
-                    label_key: "mn-fiber",
-                    mn_type: DataMNType::Fiber,
-                    signal: fiber_signal,
-                    mn_signal,
-                    editable,
-                }
-            }
-            if editable || zinc_signal().is_some() {
-                MicroNutrientInput {
-                    label_key: "mn-zinc",
-                    mn_type: DataMNType::Zinc,
-                    signal: zinc_signal,
-                    mn_signal,
-                    editable,
-                }
-            }
-            if editable || sodium_signal().is_some() {
-                MicroNutrientInput {
-                    label_key: "mn-sodium",
-                    mn_type: DataMNType::Sodium,
-                    signal: sodium_signal,
-                    mn_signal,
-                    editable,
-                }
-            }
-            if editable || alcohol_signal().is_some() {
-                MicroNutrientInput {
-                    label_key: "mn-alcohol",
-                    mn_type: DataMNType::Alcohol,
-                    signal: alcohol_signal,
-                    mn_signal,
-                    editable,
+            for (mn_type , signal) in signals {
+                if editable || signal().is_some() {
+                    MicroNutrientInput {
+                        key: "{mn_type:?}",
+                        label_key: label_key(mn_type),
+                        mn_type,
+                        signal,
+                        mn_signal,
+                        editable,
+                    }
                 }
             }
         }
@@ -1,7 +1,11 @@
 use super::{AllowedUnits, MacroElements, MicroNutrients};
 use dioxus::prelude::*;
+use dioxus_i18n::prelude::i18n;
 use dioxus_i18n::t;
-use meal_planner_lib::data_types as data;
+use meal_planner_lib::api::data_types as data;
+use meal_planner_lib::api::number_format::{format_number, parse_number};
+use meal_planner_lib::api::scoring::score_product;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
 #[derive(Clone, Copy, PartialEq)]
@@ -58,6 +62,38 @@ fn EditableTextInput(
     }
 }
 
+#[component]
+fn ServingSize(serving_signal: Signal<Option<f32>>, editable: bool) -> Element {
+    let language = i18n().language().to_string();
+    let shown =
+        serving_signal().map_or_else(String::new, |grams| format_number(grams, 1, &language));
+    rsx! {
+        div {
+            {format!("{}: ", t!("label-serving-size"))}
+            if editable {
+                input {
+                    class: "nutrient-input",
+                    r#type: "text",
+                    inputmode: "decimal",
+                    value: shown,
+                    onchange: move |e| {
+                        let value = e.value();
+                        if value.trim().is_empty() {
+                            serving_signal.set(None);
+                        } else if let Ok(grams) = parse_number(&value) {
+                            serving_signal.set(Some(grams));
+                        } else {
+                            serving_signal.set(serving_signal());
+                        }
+                    },
+                }
+            } else {
+                {shown}
+            }
+        }
+    }
+}
+
 #[component]
 pub fn Product(product_signal: Signal<Option<data::Product>>, editable: bool) -> Element {
     let name_input_ref = use_signal(|| None);
@@ -68,9 +104,14 @@ pub fn Product(product_signal: Signal<Option<data::Product>>, editable: bool) ->
         use_signal(|| data::MacroElements::new(0.0, 0.0, 0.0, 0.0, 0.0));
     let mut micro_nutrients_signal = use_signal(data::MicroNutrients::default);
     let mut allowed_units_signal = use_signal(data::AllowedUnits::default);
+    let mut grams_per_ml_signal = use_signal(|| None);
+    let mut unit_labels_signal = use_signal(BTreeMap::new);
+    let mut serving_signal = use_signal(|| None::<f32>);
+    let mut ignore_alcohol_signal = use_signal(|| false);
     let mut macro_open = use_signal(|| true);
     let mut micro_open = use_signal(|| true);
     let mut allowed_units_open = use_signal(|| true);
+    let mut validation_errors = use_signal(Vec::<data::ProductValidationError>::new);
 
     use_effect(move || {
         let Some(product) = product_signal() else {
@@ -90,25 +131,51 @@ pub fn Product(product_signal: Signal<Option<data::Product>>, editable: bool) ->
         macro_elements_signal.set(product.macro_elements.as_ref().clone());
         micro_nutrients_signal.set(product.micro_nutrients.as_ref().clone());
         allowed_units_signal.set(product.allowed_units.clone());
+        grams_per_ml_signal.set(product.grams_per_ml);
+        unit_labels_signal.set(product.unit_labels.clone());
+        serving_signal.set(product.serving.map(|s| s.grams_per_serving));
+        ignore_alcohol_signal.set(product.ignore_alcohol_calories);
     });
 
     use_effect(move || {
-        let new_product = Some(data::Product::new(
-            name_signal(),
-            if brand_signal().is_empty() {
-                None
-            } else {
-                Some(brand_signal())
-            },
-            Box::new(macro_elements_signal().clone()),
-            Box::new(micro_nutrients_signal().clone()),
-            allowed_units_signal().clone(),
-        ));
+        let mut builder = data::ProductBuilder::new(name_signal())
+            .brand(brand_signal())
+            .macro_elements(macro_elements_signal())
+            .micro_nutrients(micro_nutrients_signal())
+            .units(allowed_units_signal())
+            .grams_per_ml(grams_per_ml_signal())
+            .unit_labels(unit_labels_signal())
+            .ignore_alcohol_calories(ignore_alcohol_signal());
+        if let Some(grams) = serving_signal() {
+            builder = builder.serving(grams);
+        }
+        // invalid products are still passed on, saving them shows the error to the user
+        let errors = builder.clone().build().err().unwrap_or_default();
+        if errors != validation_errors() {
+            validation_errors.set(errors);
+        }
+        let new_product = Some(builder.build_unchecked());
         if new_product != product_signal() {
             product_signal.set(new_product);
         }
     });
 
+    let language = i18n().language().to_string();
+    let calories_per_serving = product_signal().and_then(|p| {
+        p.nutrient_amounts(data::MacroElementsType::Calories.into())
+            .per_serving
+            .map(|calories| format_number(calories, 0, &language))
+    });
+    let has_alcohol = micro_nutrients_signal()[data::MicroNutrientsType::Alcohol]
+        .is_some_and(|grams| grams > 0.0);
+    // shown only when alcohol adds to the calories of the macro elements
+    let calories_with_alcohol = product_signal()
+        .filter(|p| has_alcohol && !p.ignore_alcohol_calories && p.label_calories().is_none())
+        .map(|p| format_number(p.computed_calories(), 0, &language));
+    let convertible_serving = serving_signal()
+        .map(data::ServingInfo::new)
+        .filter(|s| editable && s.is_valid());
+
     rsx! {
         div {
             EditableTextInput {
@@ -127,6 +194,33 @@ pub fn Product(product_signal: Signal<Option<data::Product>>, editable: bool) ->
                 input_ref: brand_input_ref,
                 editable,
             }
+            if let Some(product) = product_signal() {
+                div { class: "product-grade",
+                    {t!("label-nutri-grade", grade : format!("{:?}", score_product(&product).grade))}
+                }
+            }
+            ServingSize { serving_signal, editable }
+            if let Some(per_serving) = calories_per_serving {
+                div { {t!("label-per-serving-calories", calories : per_serving)} }
+            }
+            if let Some(serving) = convertible_serving {
+                // values typed from a label given per serving are converted in place
+                button {
+                    onclick: move |_| {
+                        macro_elements_signal.set(serving.macros_per_100g(&macro_elements_signal()));
+                        micro_nutrients_signal
+                            .set(serving.micros_per_100g(&micro_nutrients_signal()));
+                    },
+                    {t!("action-convert-per-serving")}
+                }
+            }
+            if editable && !validation_errors().is_empty() {
+                ul { class: "product-validation",
+                    for error in validation_errors() {
+                        li { {error.to_string()} }
+                    }
+                }
+            }
             div { class: "collapsible",
                 button {
                     class: "collapsible__header",
@@ -137,6 +231,19 @@ pub fn Product(product_signal: Signal<Option<data::Product>>, editable: bool) ->
                 if macro_open() {
                     div { class: "collapsible__content",
                         MacroElements { me_signal: macro_elements_signal, editable }
+                        if let Some(calories) = calories_with_alcohol {
+                            div { {t!("label-calories-with-alcohol", calories : calories)} }
+                        }
+                        if editable && has_alcohol {
+                            label {
+                                input {
+                                    r#type: "checkbox",
+                                    checked: ignore_alcohol_signal(),
+                                    onchange: move |e| ignore_alcohol_signal.set(e.checked()),
+                                }
+                                {t!("label-ignore-alcohol-calories")}
+                            }
+                        }
                     }
                 }
             }
@@ -164,7 +271,11 @@ pub fn Product(product_signal: Signal<Option<data::Product>>, editable: bool) ->
                 }
                 if allowed_units_open() {
                     div { class: "collapsible__content",
-                        AllowedUnits { ad_signal: allowed_units_signal, editable }
+                        AllowedUnits {
+                            ad_signal: allowed_units_signal,
+                            unit_labels: unit_labels_signal(),
+                            editable,
+                        }
                     }
                 }
             }
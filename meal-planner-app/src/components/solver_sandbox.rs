@@ -0,0 +1,130 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::constraints_solver::SolutionEntry;
+use meal_planner_lib::api::database_access as db_access;
+use meal_planner_lib::api::quantity::Quantity;
+use meal_planner_lib::sandbox::{run_sandbox, SandboxReport};
+
+use crate::components::notifications::{use_notifications, Notice};
+
+const EXAMPLE_REQUEST: &str = r#"{
+    "direction": "Max",
+    "objective": { "Nutrient": { "Macro": "Protein" } },
+    "meals": {
+        "Lunch": {
+            "products": [{ "product_id": "", "min": 0, "max": 300, "unit": "Gram" }],
+            "nutrients": [{ "nutrient": { "Macro": "Calories" }, "max": 800.0 }],
+            "max_products": 3
+        }
+    },
+    "nutrients": []
+}"#;
+
+fn solution_rows(entry: &SolutionEntry, scope: &str, rows: &mut Vec<String>) {
+    match entry {
+        SolutionEntry::Week { entries } => {
+            for e in entries {
+                solution_rows(e, scope, rows);
+            }
+        }
+        SolutionEntry::Day { name, entries } | SolutionEntry::Meal { name, entries, .. } => {
+            let scope = if scope.is_empty() {
+                name.clone()
+            } else {
+                format!("{scope} / {name}")
+            };
+            for e in entries {
+                solution_rows(e, &scope, rows);
+            }
+        }
+        SolutionEntry::Product {
+            product,
+            amount_grams,
+            ..
+        } => rows.push(format!(
+            "{scope}: {} {:.1}",
+            product.id(),
+            Quantity::from_grams(*amount_grams)
+        )),
+    }
+}
+
+async fn solve(json: String) -> Result<SandboxReport, String> {
+    let db = db_access::get_db(db_access::DataBaseTypes::Local(
+        db_access::LOCAL_DB_DEFAULT_FILE.to_string(),
+    ))
+    .await
+    .ok_or_else(|| t!("error-db-access"))?;
+    run_sandbox(&json, db.as_ref()).await
+}
+
+/// Developer view for solving constraint JSON directly, only built with `solver-sandbox`.
+#[component]
+pub fn SolverSandboxView() -> Element {
+    let mut request = use_signal(|| EXAMPLE_REQUEST.to_string());
+    let mut report = use_signal(|| None::<Result<SandboxReport, String>>);
+    let notifications = use_notifications();
+
+    rsx! {
+        div { class: "view-content solver-sandbox",
+            h2 { {t!("sandbox-title")} }
+            textarea {
+                class: "solver-sandbox__input",
+                aria_label: t!("sandbox-request"),
+                rows: "16",
+                value: request(),
+                oninput: move |e| request.set(e.value()),
+            }
+            button {
+                class: "navigation-button",
+                onclick: move |_| {
+                    let json = request();
+                    spawn(async move {
+                        let result = solve(json).await;
+                        if let Ok(SandboxReport { outcome: Err(err), .. }) = &result {
+                            notifications.report(Notice::Infeasible(err.clone()));
+                        }
+                        report.set(Some(result));
+                    });
+                },
+                {t!("sandbox-solve")}
+            }
+            match &*report.read() {
+                None => rsx! {},
+                Some(Err(err)) => rsx! {
+                    div { role: "alert", {format!("{}: {err}", t!("popup-error"))} }
+                },
+                Some(Ok(report)) => rsx! {
+                    div { {t!("sandbox-elapsed", ms : format!("{:.2}", report.elapsed_ms))} }
+                    match &report.outcome {
+                        // reported as a toast
+                        Err(_) => rsx! {},
+                        Ok(diagnostics) => {
+                            let mut rows = Vec::new();
+                            solution_rows(&diagnostics.solution.solution, "", &mut rows);
+                            rsx! {
+                                div { {t!("sandbox-objective", value : format!("{:.3}", diagnostics.objective_value))} }
+                                h3 { {t!("sandbox-solution")} }
+                                ul {
+                                    for row in rows {
+                                        li { {row} }
+                                    }
+                                }
+                                h3 { {t!("sandbox-binding")} }
+                                ul {
+                                    for name in diagnostics.binding_constraints.iter() {
+                                        li { {name.clone()} }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    details {
+                        summary { {t!("sandbox-lp")} }
+                        pre { class: "solver-sandbox__lp", {report.lp.clone()} }
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use dioxus::prelude::*;
+use dioxus_i18n::t;
+use meal_planner_lib::api::data_types::{UsageEvent, UsageStats};
+use meal_planner_lib::api::database_access as db_access;
+
+use crate::components::main_view::ViewKind;
+
+async fn load_stats() -> Result<UsageStats, String> {
+    // usage stats live in the mutable part of the local database
+    let db = db_access::get_mutable_db(db_access::DataBaseTypes::Local(
+        db_access::LOCAL_DB_DEFAULT_FILE.to_string(),
+    ))
+    .await
+    .ok_or_else(|| t!("error-db-access"))?;
+    db.get_usage_stats().await
+}
+
+async fn save_stats(stats: &UsageStats) -> Result<(), String> {
+    let mut db = db_access::get_mutable_db(db_access::DataBaseTypes::Local(
+        db_access::LOCAL_DB_DEFAULT_FILE.to_string(),
+    ))
+    .await
+    .ok_or_else(|| t!("error-db-access"))?;
+    db.set_usage_stats(stats).await
+}
+
+/// Counts `event` in the local usage stats, does nothing unless the user opted in.
+pub fn record_usage(event: UsageEvent) {
+    spawn(async move {
+        let result = async {
+            let mut stats = load_stats().await?;
+            if stats.record(&event) {
+                save_stats(&stats).await?;
+            }
+            Ok::<(), String>(())
+        };
+        if let Err(e) = result.await {
+            tracing::warn!("Failed to record usage: {e}");
+        }
+    });
+}
+
+fn view_label(name: &str) -> String {
+    ViewKind::ALL
+        .iter()
+        .find(|view| view.usage_name() == name)
+        .map_or_else(|| name.to_string(), |view| view.label())
+}
+
+#[component]
+pub fn UsageStatsView() -> Element {
+    let mut stats = use_resource(load_stats);
+    let mut export = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<String>);
+
+    let update = move |change: fn(&mut UsageStats)| {
+        let Some(Ok(mut current)) = stats() else {
+            return;
+        };
+        change(&mut current);
+        spawn(async move {
+            match save_stats(&current).await {
+                Ok(()) => stats.restart(),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "view-content usage-stats",
+            h2 { {t!("usage-title")} }
+            p { class: "usage-stats__note", {t!("usage-local-only")} }
+            if let Some(err) = error() {
+                div { role: "alert", {format!("{}: {err}", t!("popup-error"))} }
+            }
+            match stats() {
+                None => rsx! {
+                    div { {t!("summary-loading")} }
+                },
+                Some(Err(err)) => rsx! {
+                    div { {format!("{}: {err}", t!("popup-error"))} }
+                },
+                Some(Ok(current)) => rsx! {
+                    label { class: "usage-stats__row",
+                        input {
+                            r#type: "checkbox",
+                            checked: current.enabled,
+                            onchange: move |e| {
+                                if e.checked() {
+                                    update(|s| s.enabled = true);
+                                } else {
+                                    update(|s| s.enabled = false);
+                                }
+                            },
+                        }
+                        {t!("usage-enabled")}
+                    }
+                    div { class: "db-summary__row",
+                        span { {t!("usage-solves")} }
+                        span { class: "db-summary__count", "{current.solves}" }
+                    }
+                    div { class: "db-summary__row",
+                        span { {t!("usage-products-added")} }
+                        span { class: "db-summary__count", "{current.products_added}" }
+                    }
+                    h3 { {t!("usage-most-used-views")} }
+                    ol { class: "usage-stats__views",
+                        for (view , count) in current.most_used_views() {
+                            li { key: "{view}",
+                                span { {view_label(view)} }
+                                span { class: "db-summary__count", "{count}" }
+                            }
+                        }
+                    }
+                    div { class: "usage-stats__actions",
+                        button {
+                            class: "navigation-button",
+                            onclick: move |_| {
+                                let Some(Ok(current)) = stats() else {
+                                    return;
+                                };
+                                match current.export_json() {
+                                    Ok(json) => export.set(Some(json)),
+                                    Err(e) => error.set(Some(e)),
+                                }
+                            },
+                            {t!("usage-export")}
+                        }
+                        button {
+                            class: "button--danger",
+                            onclick: move |_| {
+                                export.set(None);
+                                update(UsageStats::wipe);
+                            },
+                            {t!("usage-wipe")}
+                        }
+                    }
+                    if let Some(json) = export() {
+                        textarea {
+                            class: "usage-stats__export",
+                            aria_label: t!("usage-export"),
+                            readonly: true,
+                            rows: "10",
+                            value: json,
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
@@ -8,9 +8,11 @@ use dioxus_i18n::unic_langid::langid;
 mod components;
 
 use components::{
+    accessibility::{use_font_scale, AccessibilityContext, AccessibilitySettings},
     action_bar::ActionBar,
     layout::SidebarLayoutContext,
     main_view::{MainView, ViewKind},
+    notifications::{NotificationCenter, NotificationContext},
 };
 
 // The asset macro also minifies some assets like CSS and JS to make bundled smaller
@@ -45,7 +47,16 @@ fn App() -> Element {
     let sidebar_open = use_signal(|| false);
     let sidebar_width = use_signal(|| 224.0_f32);
 
+    let accessibility = use_signal(AccessibilitySettings::default);
+
     use_context_provider(|| SidebarLayoutContext { sidebar_width });
+    use_context_provider(|| AccessibilityContext {
+        settings: accessibility,
+    });
+    use_font_scale(accessibility);
+    let toasts = use_signal(Vec::new);
+    let next_id = use_signal(|| 0);
+    use_context_provider(|| NotificationContext { toasts, next_id });
 
     // The `rsx!` macro lets us define HTML inside of rust. It expands to an Element with all of our HTML inside.
     rsx! {
@@ -58,10 +69,11 @@ fn App() -> Element {
         document::Link { rel: "stylesheet", href: PRODUCT_RELATED_CSS }
 
         div {
-            class: "app-shell",
+            class: accessibility().shell_class(),
             style: format!("--action-bar-width: {}px;", sidebar_width()),
             ActionBar { selection, sidebar_open, sidebar_width }
             MainView { selection }
+            NotificationCenter {}
         }
     }
 }
@@ -0,0 +1,164 @@
+//! Stable public API of the library.
+//!
+//! Everything reachable from this module (and from [`crate::prelude`]) follows semantic
+//! versioning: removing or renaming an item, or changing its signature, requires a major
+//! version bump (minor while the crate is `0.x`). Items not re-exported here are internal and
+//! may change at any time.
+//!
+//! The list of exported items is snapshotted in `tests/public_api.txt`, so additions and
+//! removals show up in review. Run the `public_api` test with `UPDATE_PUBLIC_API=1` to
+//! regenerate the snapshot after a deliberate change.
+
+/// Version of the library the API surface belongs to.
+pub const API_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub use crate::version::{VersionInfo, version_info};
+
+pub mod data_types {
+    pub use crate::data_types::{
+        ActivityLevel, ActivityLog, Allergen, AllowedUnits, AllowedUnitsType, BeverageEntry,
+        CompletenessReport, CookedVariant, DEFAULT_PROFILE_ID, DataQuality, DietFlag,
+        DietaryRestrictions, EatenItem, EnergyUnit, MAX_PHOTO_BYTES, MacroElements,
+        MacroElementsType, MealKind, MealNote, MealPhoto, MealSlot, MealTime, MicroNutrients,
+        MicroNutrientsType, NUTRITION_COMPLETED_TAG, NutrientAmounts, NutrientInfo, NutrientType,
+        NutrientUnit, Price, Product, ProductBuilder, ProductEdit, ProductId,
+        ProductValidationError, ProductVersion, Profile, Recipe, RecipeIngredient, ServingInfo,
+        Sex, UnitData, UsageEvent, UsageStats, UserProfile,
+    };
+
+    pub mod constraints {
+        pub use crate::data_types::constraints::{
+            AllowedUnitDividers, CONSTRAINT_DOCUMENT_VERSION, DayConstraintBuilder,
+            DayMealPlanConstraint, LeftoverConstraint, MacroPercentConstraint, MealConstraint,
+            MealConstraintBuilder, MealEditError, NutrientConstraint, ProductConstraint,
+            ProductRatioConstraint, RatioBase, RatioRelation, RecipeConstraint,
+            RepetitionConstraint, WeekMealPlanConstraint,
+        };
+    }
+}
+
+pub mod database_access {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crate::database_access::app_data_dir;
+    pub use crate::database_access::{
+        APP_DIR_NAME, BulkReport, CacheConfig, CachedDatabase, ConflictStrategy, DataBaseTypes,
+        DataQualitySummary, Database, DatabaseStats, DbEvent, DbEventStream, DbSearchCriteria,
+        ImportEntry, ImportOutcome, ImportReport, LOCAL_DB_DEFAULT_FILE, LocalProductDb,
+        MutableDatabase, NewerSchema, OfflineQueue, PendingRequest, PermissionDenied,
+        ReadOnlyDatabase, RemoteRequest, ScanReport, ScanResolution, ScanSession, SearchCanceller,
+        SearchToken, SortKey, get_db, get_mutable_db, get_mutable_db_types, resolve_db_path,
+        search_products, set_app_data_dir,
+    };
+    #[cfg(feature = "test-utils")]
+    pub use crate::database_access::{GROCERIES_FIXTURE, MockProductDb, PANTRY_FIXTURE};
+}
+
+pub mod activity_import {
+    pub use crate::bl::activity_import::parse_activity_file;
+}
+
+pub mod analytics {
+    pub use crate::bl::analytics::{
+        EnergyBalanceDay, HydrationDay, MissedDay, NutrientTrend, NutritionReport, PlanRecord,
+        TrendPoint, daily_energy_balance, daily_hydration,
+    };
+}
+
+pub mod compare {
+    pub use crate::bl::compare::{
+        ComparisonBasis, ComparisonRow, ComparisonTable, compare_products,
+    };
+}
+
+pub mod constraints_solver {
+    pub use crate::bl::background_solver::{solve_day_async, solve_day_cached};
+    pub use crate::bl::constraints_solver::{
+        ConstraintRelaxation, ConstraintsSolver, Fraction, MinOrMax, NutrientContribution,
+        NutrientTotals, Objective, Solution, SolutionEntry, SolveDiagnostics,
+    };
+    #[cfg(feature = "test-utils")]
+    pub use crate::bl::golden_plans::{PlanSnapshot, assert_golden_plan};
+}
+
+pub mod export {
+    pub use crate::bl::export::{PlanFormat, render_plan, to_ical};
+}
+
+pub mod grocery_haul {
+    pub use crate::bl::grocery_haul::{
+        ExpiringItem, ManualShoppingItem, MissingItem, Pantry, PlannedMeal, ShoppingList,
+        ShoppingListItem, find_missing_items,
+    };
+}
+
+pub mod label_parser {
+    pub use crate::bl::label_parser::{ParsedLabel, parse_label};
+}
+
+pub mod notifications {
+    pub use crate::bl::notifications::{
+        NotificationItem, NotificationPayload, PendingNotification, schedule_notifications,
+    };
+    pub use crate::data_types::MealTime;
+}
+
+pub mod number_format {
+    pub use crate::bl::number_format::{decimal_separator, format_number, parse_number};
+    pub use crate::bl::nutrient_format::{display_decimals, display_unit, format_nutrient};
+}
+
+pub mod presets {
+    pub use crate::bl::presets::MacroPreset;
+}
+
+pub mod preview {
+    pub use crate::bl::preview::{default_amount, estimate_meal};
+}
+
+pub mod rounding {
+    pub use crate::bl::rounding::{RoundedConstraint, RoundingPolicy, RoundingViolation};
+}
+
+pub mod quantity {
+    pub use crate::bl::quantity::{MassUnit, Quantity};
+}
+
+pub mod sanity {
+    pub use crate::bl::sanity::{SanityWarning, check_constraints};
+}
+
+pub mod scoring {
+    pub use crate::bl::scoring::{
+        NutriGrade, NutritionScore, ScoreWeights, SolutionScore, score_product, score_solution,
+    };
+}
+
+pub mod settings {
+    pub use crate::bl::settings::{SettingsStore, SubscriptionId};
+    pub use crate::data_types::AppSettings;
+}
+
+pub mod solution_report {
+    pub use crate::bl::solution_report::{NutrientConstraintReport, SolutionReport};
+}
+
+pub mod swap_products {
+    pub use crate::bl::swap_products::{
+        SwapCandidate, SwapSearchOptions, find_swap_candidates, find_swap_candidates_with,
+        swap_in_solution,
+    };
+}
+
+pub mod targets {
+    pub use crate::bl::targets::NutritionTargets;
+}
+
+pub mod targets_review {
+    pub use crate::bl::targets_review::{ReviewReason, TargetsRecord, TargetsReviewRule};
+}
+
+pub mod tracking {
+    pub use crate::bl::tracking::{
+        NutrientBudget, planned_meal_items, remaining_budget, resolve_remaining,
+    };
+}
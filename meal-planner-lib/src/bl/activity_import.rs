@@ -0,0 +1,223 @@
+//! Reading workouts from GPX and TCX files exported by sport watches and tracking apps.
+//!
+//! TCX files carry the calories computed by the device. GPX files only have the route, so the
+//! energy is estimated from the distance and the body weight.
+
+use crate::data_types::ActivityLog;
+
+// rough cost of moving a kilogram of body weight over a kilometer, walking and running alike
+const KCAL_PER_KG_KM: f64 = 1.0;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Parses a GPX or TCX file into an activity, `weight_kg` is used for GPX files without
+/// calories.
+pub fn parse_activity_file(contents: &str, weight_kg: f32) -> Result<ActivityLog, String> {
+    let activity = if contents.contains("<TrainingCenterDatabase") {
+        parse_tcx(contents)?
+    } else if contents.contains("<gpx") {
+        parse_gpx(contents, weight_kg)?
+    } else {
+        return Err("Unknown activity file format, expected GPX or TCX".to_string());
+    };
+    activity.validate()?;
+    Ok(activity)
+}
+
+fn parse_tcx(xml: &str) -> Result<ActivityLog, String> {
+    let start = *tag_texts(xml, "Id")
+        .first()
+        .ok_or_else(|| "TCX file has no activity id".to_string())?;
+    let mut kcal: u32 = 0;
+    for calories in tag_texts(xml, "Calories") {
+        let lap_kcal = calories
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid calories '{calories}': {e}"))?;
+        kcal = kcal.saturating_add(lap_kcal);
+    }
+    let name = opening_tag(xml, "Activity")
+        .and_then(|tag| attribute(tag, "Sport"))
+        .map(str::to_string);
+    Ok(ActivityLog {
+        performed_at: parse_timestamp(start)?,
+        kcal_burned: kcal,
+        name,
+    })
+}
+
+fn parse_gpx(xml: &str, weight_kg: f32) -> Result<ActivityLog, String> {
+    if !weight_kg.is_finite() || weight_kg <= 0.0 {
+        return Err(format!("Invalid body weight: {weight_kg} kg"));
+    }
+    let mut points = Vec::new();
+    let mut start = None;
+    for chunk in xml.split("<trkpt").skip(1) {
+        let tag = chunk.split('>').next().unwrap_or_default();
+        let coordinate = |name| {
+            attribute(tag, name)
+                .and_then(|value| value.parse::<f64>().ok())
+                .ok_or_else(|| format!("Track point without valid '{name}'"))
+        };
+        points.push((coordinate("lat")?, coordinate("lon")?));
+        if start.is_none() {
+            start = tag_texts(chunk, "time").first().copied();
+        }
+    }
+    let start = start.ok_or_else(|| "GPX track has no time".to_string())?;
+    let km: f64 = points
+        .windows(2)
+        .map(|pair| distance_km(pair[0], pair[1]))
+        .sum();
+    let name = xml
+        .split_once("<trk>")
+        .and_then(|(_, track)| tag_texts(track, "name").first().copied())
+        .map(str::to_string);
+    Ok(ActivityLog {
+        performed_at: parse_timestamp(start)?,
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        kcal_burned: (km * f64::from(weight_kg) * KCAL_PER_KG_KM).round() as u32,
+        name,
+    })
+}
+
+// texts of `<tag>text</tag>` elements, trimmed, in document order
+fn tag_texts<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let close = format!("</{tag}>");
+    xml.split(&format!("<{tag}>") as &str)
+        .skip(1)
+        .filter_map(|rest| rest.split_once(&close as &str).map(|(text, _)| text.trim()))
+        .collect()
+}
+
+// attribute list of the first opening tag of a `tag` element
+fn opening_tag<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let (_, rest) = xml.split_once(&format!("<{tag} ") as &str)?;
+    rest.split('>').next()
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = tag.split_once(&format!("{name}=\"") as &str)?;
+    rest.split_once('"').map(|(value, _)| value)
+}
+
+// great-circle distance between two (latitude, longitude) points in degrees
+fn distance_km((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+// ISO 8601 date and time, e.g. "2024-03-10T07:15:00Z" or "2024-03-10T08:15:00.5+01:00",
+// to Unix seconds
+fn parse_timestamp(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid time '{text}'");
+    let (date, time) = text.split_once('T').ok_or_else(invalid)?;
+    let mut date_parts = date.splitn(3, '-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (date_parts.next(), date_parts.next(), date_parts.next())
+    else {
+        return Err(invalid());
+    };
+    let (clock, offset_seconds) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(sign_at) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(sign_at);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':').ok_or_else(invalid)?;
+        let hours: i64 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+        (clock, sign * (hours * 3600 + minutes * 60))
+    } else {
+        // local time of the device, taken as UTC
+        (time, 0)
+    };
+    let mut clock_parts = clock.splitn(3, ':');
+    let (Some(hours), Some(minutes), Some(seconds)) =
+        (clock_parts.next(), clock_parts.next(), clock_parts.next())
+    else {
+        return Err(invalid());
+    };
+    let hours: i64 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+    let seconds: f64 = seconds.parse().map_err(|_| invalid())?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let timestamp = days_from_civil(year, month, day) * SECONDS_PER_DAY
+        + hours * 3600
+        + minutes * 60
+        + seconds as i64
+        - offset_seconds;
+    u64::try_from(timestamp).map_err(|_| invalid())
+}
+
+// days since the Unix epoch of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2">
+  <Activities>
+    <Activity Sport="Running">
+      <Id>2024-03-10T07:15:00Z</Id>
+      <Lap StartTime="2024-03-10T07:15:00Z"><Calories>210</Calories></Lap>
+      <Lap StartTime="2024-03-10T07:35:00Z"><Calories>195</Calories></Lap>
+    </Activity>
+  </Activities>
+</TrainingCenterDatabase>"#;
+
+    // about 1.1 km straight north
+    const GPX: &str = r#"<?xml version="1.0"?>
+<gpx version="1.1" creator="tracker">
+  <trk>
+    <name>Evening walk</name>
+    <trkseg>
+      <trkpt lat="52.2300" lon="21.0100"><time>2024-03-10T19:00:00+01:00</time></trkpt>
+      <trkpt lat="52.2350" lon="21.0100"><time>2024-03-10T19:05:00+01:00</time></trkpt>
+      <trkpt lat="52.2400" lon="21.0100"><time>2024-03-10T19:10:00+01:00</time></trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+    #[test]
+    fn reads_tcx_calories() {
+        let activity = parse_activity_file(TCX, 70.0).unwrap();
+        assert_eq!(activity.performed_at, 1_710_054_900);
+        assert_eq!(activity.kcal_burned, 405);
+        assert_eq!(activity.name.as_deref(), Some("Running"));
+    }
+
+    #[test]
+    fn estimates_gpx_energy_from_distance() {
+        let activity = parse_activity_file(GPX, 80.0).unwrap();
+        assert_eq!(activity.performed_at, 1_710_093_600);
+        // 1.11 km * 80 kg
+        assert_eq!(activity.kcal_burned, 89);
+        assert_eq!(activity.name.as_deref(), Some("Evening walk"));
+        assert!(parse_activity_file(GPX, 0.0).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_and_broken_files() {
+        assert!(parse_activity_file("name,kcal\nrun,300", 70.0).is_err());
+        let no_time = GPX
+            .replace("<time>", "<when>")
+            .replace("</time>", "</when>");
+        assert!(parse_activity_file(&no_time, 70.0).is_err());
+        assert!(parse_timestamp("2024-13-10T07:15:00Z").is_err());
+        assert_eq!(parse_timestamp("1970-01-02T00:00:00.75Z"), Ok(86_400));
+    }
+}
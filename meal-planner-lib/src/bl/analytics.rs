@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+
+use strum::IntoEnumIterator;
+
+use super::constraints_solver::Solution;
+use crate::data_types::{
+    ActivityLog, BeverageEntry, MacroElementsType, NutrientType, constraints::NutrientConstraint,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Plan followed on a past day. Several records on the same day are added up.
+pub struct PlanRecord {
+    /// Unix timestamp (seconds) of the day the plan was eaten.
+    pub eaten_at: u64,
+    pub solution: Solution,
+}
+
+/// Value of a nutrient on a single day, `day` counted in days since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendPoint {
+    pub day: u64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NutrientTrend {
+    pub nutrient: NutrientType,
+    /// One point per day with a recorded plan, oldest first.
+    pub points: Vec<TrendPoint>,
+}
+
+/// Day on which at least one nutrient ended up outside of its target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissedDay {
+    pub day: u64,
+    pub nutrients: Vec<NutrientType>,
+}
+
+/// Statistics over the recorded plans of the last `window_days` days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NutritionReport {
+    pub window_days: u64,
+    /// Days with at least one recorded plan, days without one do not lower the averages.
+    pub recorded_days: usize,
+    pub average_calories: f64,
+    pub average_protein: f64,
+    /// Trends of every macro element and every nutrient with a target.
+    pub trends: Vec<NutrientTrend>,
+    pub days_meeting_targets: Vec<u64>,
+    pub days_missing_targets: Vec<MissedDay>,
+}
+
+impl NutritionReport {
+    /// Builds the report from `records` eaten within `window_days` days before `now` (today
+    /// included), checking every day against `targets`.
+    pub fn new(
+        records: &[PlanRecord],
+        targets: &[NutrientConstraint],
+        now: u64,
+        window_days: u64,
+    ) -> Result<Self, String> {
+        if window_days == 0 {
+            return Err("Report window must be at least one day".to_string());
+        }
+        let today = now / SECONDS_PER_DAY;
+        let first_day = (today + 1).saturating_sub(window_days);
+
+        let mut nutrients: Vec<NutrientType> =
+            MacroElementsType::iter().map(NutrientType::Macro).collect();
+        for target in targets {
+            if !nutrients.contains(&target.element()) {
+                nutrients.push(target.element());
+            }
+        }
+
+        let mut days: BTreeMap<u64, Vec<f64>> = BTreeMap::new();
+        for record in records {
+            let day = record.eaten_at / SECONDS_PER_DAY;
+            if !(first_day..=today).contains(&day) {
+                continue;
+            }
+            let totals = days
+                .entry(day)
+                .or_insert_with(|| vec![0.0; nutrients.len()]);
+            for (total, nutrient) in totals.iter_mut().zip(&nutrients) {
+                *total += record.solution.nutrient_total(*nutrient);
+            }
+        }
+
+        let average = |nutrient: NutrientType| {
+            let idx = nutrients.iter().position(|n| *n == nutrient)?;
+            let sum: f64 = days.values().map(|totals| totals[idx]).sum();
+            #[allow(clippy::cast_precision_loss)]
+            Some(sum / days.len() as f64)
+        };
+        let (average_calories, average_protein) = if days.is_empty() {
+            (0.0, 0.0)
+        } else {
+            (
+                average(NutrientType::Macro(MacroElementsType::Calories)).unwrap_or(0.0),
+                average(NutrientType::Macro(MacroElementsType::Protein)).unwrap_or(0.0),
+            )
+        };
+
+        let trends = nutrients
+            .iter()
+            .enumerate()
+            .map(|(idx, nutrient)| NutrientTrend {
+                nutrient: *nutrient,
+                points: days
+                    .iter()
+                    .map(|(day, totals)| TrendPoint {
+                        day: *day,
+                        amount: totals[idx],
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let mut days_meeting_targets = Vec::new();
+        let mut days_missing_targets = Vec::new();
+        for (day, totals) in &days {
+            let missed: Vec<NutrientType> = targets
+                .iter()
+                .filter(|target| {
+                    nutrients
+                        .iter()
+                        .position(|n| *n == target.element())
+                        .is_some_and(|idx| !is_within(target, totals[idx]))
+                })
+                .map(NutrientConstraint::element)
+                .collect();
+            if missed.is_empty() {
+                days_meeting_targets.push(*day);
+            } else {
+                days_missing_targets.push(MissedDay {
+                    day: *day,
+                    nutrients: missed,
+                });
+            }
+        }
+
+        Ok(Self {
+            window_days,
+            recorded_days: days.len(),
+            average_calories,
+            average_protein,
+            trends,
+            days_meeting_targets,
+            days_missing_targets,
+        })
+    }
+
+    #[must_use]
+    pub fn trend(&self, nutrient: NutrientType) -> Option<&NutrientTrend> {
+        self.trends.iter().find(|t| t.nutrient == nutrient)
+    }
+}
+
+/// Fluids drunk on a single day, `day` counted in days since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HydrationDay {
+    pub day: u64,
+    pub total_ml: u32,
+    /// Part of the total drunk as plain water.
+    pub water_ml: u32,
+    pub target_ml: u32,
+}
+
+impl HydrationDay {
+    #[must_use]
+    pub fn target_met(&self) -> bool {
+        self.total_ml >= self.target_ml
+    }
+
+    /// Amount still to drink to reach the target, 0 once it is met.
+    #[must_use]
+    pub fn remaining_ml(&self) -> u32 {
+        self.target_ml.saturating_sub(self.total_ml)
+    }
+}
+
+/// Daily fluid totals of the last `window_days` days before `now` (today included), oldest
+/// first. Days without drinks are included, they count against the target too.
+pub fn daily_hydration(
+    entries: &[BeverageEntry],
+    target_ml: u32,
+    now: u64,
+    window_days: u64,
+) -> Result<Vec<HydrationDay>, String> {
+    if window_days == 0 {
+        return Err("Report window must be at least one day".to_string());
+    }
+    let today = now / SECONDS_PER_DAY;
+    let first_day = (today + 1).saturating_sub(window_days);
+    let mut days: BTreeMap<u64, HydrationDay> = (first_day..=today)
+        .map(|day| {
+            (
+                day,
+                HydrationDay {
+                    day,
+                    total_ml: 0,
+                    water_ml: 0,
+                    target_ml,
+                },
+            )
+        })
+        .collect();
+    for entry in entries {
+        if let Some(day) = days.get_mut(&(entry.drunk_at / SECONDS_PER_DAY)) {
+            day.total_ml = day.total_ml.saturating_add(entry.volume_ml);
+            if entry.is_water() {
+                day.water_ml = day.water_ml.saturating_add(entry.volume_ml);
+            }
+        }
+    }
+    Ok(days.into_values().collect())
+}
+
+/// Energy eaten against energy spent on a single day, `day` counted in days since the Unix
+/// epoch. A negative balance means a deficit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyBalanceDay {
+    pub day: u64,
+    pub intake_kcal: f64,
+    pub activity_kcal: f64,
+    /// Expenditure without logged activities, e.g. the basal metabolic rate.
+    pub resting_kcal: f64,
+}
+
+impl EnergyBalanceDay {
+    #[must_use]
+    pub fn expenditure_kcal(&self) -> f64 {
+        self.resting_kcal + self.activity_kcal
+    }
+
+    #[must_use]
+    pub fn balance_kcal(&self) -> f64 {
+        self.intake_kcal - self.expenditure_kcal()
+    }
+}
+
+/// Daily energy balance of the last `window_days` days before `now` (today included), oldest
+/// first, from the calories of `records` and the energy burned in `activities`. `resting_kcal`
+/// shouldn't include an activity multiplier, logged activities cover that part.
+pub fn daily_energy_balance(
+    records: &[PlanRecord],
+    activities: &[ActivityLog],
+    resting_kcal: f64,
+    now: u64,
+    window_days: u64,
+) -> Result<Vec<EnergyBalanceDay>, String> {
+    if window_days == 0 {
+        return Err("Report window must be at least one day".to_string());
+    }
+    if !resting_kcal.is_finite() || resting_kcal < 0.0 {
+        return Err(format!("Invalid resting expenditure: {resting_kcal} kcal"));
+    }
+    let today = now / SECONDS_PER_DAY;
+    let first_day = (today + 1).saturating_sub(window_days);
+    let mut days: BTreeMap<u64, EnergyBalanceDay> = (first_day..=today)
+        .map(|day| {
+            (
+                day,
+                EnergyBalanceDay {
+                    day,
+                    intake_kcal: 0.0,
+                    activity_kcal: 0.0,
+                    resting_kcal,
+                },
+            )
+        })
+        .collect();
+    for record in records {
+        if let Some(day) = days.get_mut(&(record.eaten_at / SECONDS_PER_DAY)) {
+            day.intake_kcal += record
+                .solution
+                .nutrient_total(NutrientType::Macro(MacroElementsType::Calories));
+        }
+    }
+    for activity in activities {
+        if let Some(day) = days.get_mut(&(activity.performed_at / SECONDS_PER_DAY)) {
+            day.activity_kcal += f64::from(activity.kcal_burned);
+        }
+    }
+    Ok(days.into_values().collect())
+}
+
+fn is_within(target: &NutrientConstraint, amount: f64) -> bool {
+    target.min().is_none_or(|min| amount >= f64::from(min))
+        && target.max().is_none_or(|max| amount <= f64::from(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::{Fraction, SolutionEntry};
+    use crate::data_types::{AllowedUnitsType, ProductBuilder};
+    use approx::assert_relative_eq;
+
+    const NOW: u64 = 1_700_000_000;
+
+    // 100 g of the product has 2 g protein and 100 kcal
+    fn record(days_ago: u64, grams: f64) -> PlanRecord {
+        PlanRecord {
+            eaten_at: NOW - days_ago * SECONDS_PER_DAY,
+            solution: Solution {
+                solution: SolutionEntry::Product {
+                    product: ProductBuilder::new("Rice")
+                        .carbs(23.0)
+                        .protein(2.0)
+                        .build()
+                        .unwrap(),
+                    amount_grams: grams,
+                    unit: AllowedUnitsType::Gram,
+                    amount_unit: Fraction {
+                        numerator: 1,
+                        denominator: 1,
+                    },
+                },
+            },
+        }
+    }
+
+    fn calories_target(min: f32, max: f32) -> Vec<NutrientConstraint> {
+        NutrientConstraint::new(MacroElementsType::Calories, Some(min), Some(max))
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn averages_only_recorded_days_in_window() {
+        let records = vec![
+            record(0, 1000.0),
+            record(0, 1000.0),
+            record(3, 1000.0),
+            record(10, 5000.0),
+        ];
+        let report = NutritionReport::new(&records, &[], NOW, 7).unwrap();
+        assert_eq!(report.recorded_days, 2);
+        assert_relative_eq!(report.average_calories, 1500.0);
+        assert_relative_eq!(report.average_protein, 30.0);
+
+        let calories = report
+            .trend(NutrientType::Macro(MacroElementsType::Calories))
+            .unwrap();
+        let today = NOW / SECONDS_PER_DAY;
+        assert_eq!(
+            calories.points,
+            vec![
+                TrendPoint {
+                    day: today - 3,
+                    amount: 1000.0
+                },
+                TrendPoint {
+                    day: today,
+                    amount: 2000.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_days_by_targets() {
+        let records = vec![record(0, 2000.0), record(1, 1000.0)];
+        let report =
+            NutritionReport::new(&records, &calories_target(1800.0, 2200.0), NOW, 7).unwrap();
+        let today = NOW / SECONDS_PER_DAY;
+        assert_eq!(report.days_meeting_targets, vec![today]);
+        assert_eq!(
+            report.days_missing_targets,
+            vec![MissedDay {
+                day: today - 1,
+                nutrients: vec![NutrientType::Macro(MacroElementsType::Calories)],
+            }]
+        );
+    }
+
+    #[test]
+    fn sums_drinks_per_day_against_target() {
+        let beer = BeverageEntry {
+            product_id: Some("Beer".into()),
+            ..BeverageEntry::water(NOW, 500)
+        };
+        let entries = vec![
+            BeverageEntry::water(NOW, 1500),
+            beer,
+            BeverageEntry::water(NOW - 2 * SECONDS_PER_DAY, 750),
+            BeverageEntry::water(NOW - 5 * SECONDS_PER_DAY, 3000),
+        ];
+        let days = daily_hydration(&entries, 2000, NOW, 3).unwrap();
+        let today = NOW / SECONDS_PER_DAY;
+        let totals: Vec<(u64, u32, u32)> = days
+            .iter()
+            .map(|d| (d.day, d.total_ml, d.water_ml))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                (today - 2, 750, 750),
+                (today - 1, 0, 0),
+                (today, 2000, 1500)
+            ]
+        );
+        assert!(days[2].target_met());
+        assert_eq!(days[0].remaining_ml(), 1250);
+        assert!(daily_hydration(&entries, 2000, NOW, 0).is_err());
+    }
+
+    #[test]
+    fn balances_intake_against_activities() {
+        let records = vec![record(0, 2000.0), record(1, 1500.0), record(9, 900.0)];
+        let activities = vec![
+            ActivityLog::new(NOW, 300),
+            ActivityLog::new(NOW - 10, 200),
+            ActivityLog::new(NOW - 2 * SECONDS_PER_DAY, 450),
+        ];
+        let days = daily_energy_balance(&records, &activities, 1600.0, NOW, 3).unwrap();
+        let today = NOW / SECONDS_PER_DAY;
+        assert_eq!(
+            days.iter().map(|d| d.day).collect::<Vec<_>>(),
+            [today - 2, today - 1, today]
+        );
+        assert_relative_eq!(days[0].balance_kcal(), -2050.0, epsilon = 1e-6);
+        assert_relative_eq!(days[1].balance_kcal(), -100.0, epsilon = 1e-6);
+        assert_relative_eq!(days[2].activity_kcal, 500.0, epsilon = 1e-6);
+        assert_relative_eq!(days[2].balance_kcal(), -100.0, epsilon = 1e-6);
+        assert!(daily_energy_balance(&records, &activities, -1.0, NOW, 3).is_err());
+    }
+
+    #[test]
+    fn empty_history_and_invalid_window() {
+        let report = NutritionReport::new(&[], &calories_target(0.0, 1.0), NOW, 30).unwrap();
+        assert_eq!(report.recorded_days, 0);
+        assert_relative_eq!(report.average_calories, 0.0);
+        assert!(report.days_meeting_targets.is_empty());
+        assert!(NutritionReport::new(&[], &[], NOW, 0).is_err());
+    }
+}
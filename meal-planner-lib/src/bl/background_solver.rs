@@ -0,0 +1,243 @@
+//! Solving without blocking the UI. Natively the LP runs on its own thread, on wasm in a web
+//! worker (`solver_worker.js`) loading the library glue, like the database worker does. When the
+//! worker can't be started the day is solved on the calling thread instead.
+
+use crate::bl::constraints_solver::{MinOrMax, Objective, Solution, SolveDayRequest};
+use crate::data_types::constraints::{DayMealPlanConstraint, stable_hash};
+use crate::database_access::MutableDatabase;
+
+#[cfg(target_arch = "wasm32")]
+const SOLVER_WORKER_URL: &str = "/meal-planner-lib/solver/solver_worker.js";
+
+/// [`ConstraintsSolver::solve_day`](crate::bl::constraints_solver::ConstraintsSolver::solve_day)
+/// run in the background, the returned future resolves once the solver is done.
+pub async fn solve_day_async(
+    direction: MinOrMax,
+    objective: Objective,
+    day: DayMealPlanConstraint,
+) -> Result<Solution, String> {
+    let request = SolveDayRequest {
+        direction,
+        objective,
+        day,
+    };
+    solve_in_background(request).await
+}
+
+/// [`solve_day_async`] skipped when a plan solved from the same constraints, direction and
+/// objective is saved in `db`, that plan is returned instead. A new solution is saved as `plan_id`.
+pub async fn solve_day_cached(
+    db: &mut dyn MutableDatabase,
+    plan_id: &str,
+    direction: MinOrMax,
+    objective: Objective,
+    day: DayMealPlanConstraint,
+) -> Result<Solution, String> {
+    let fingerprint = stable_hash(&(direction, objective, day.fingerprint()));
+    if let Some(solution) = db.find_solved_plan(fingerprint).await? {
+        return Ok(solution);
+    }
+    let solution = solve_day_async(direction, objective, day).await?;
+    db.save_solved_plan(plan_id, fingerprint, &solution).await?;
+    Ok(solution)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn solve_in_background(request: SolveDayRequest) -> Result<Solution, String> {
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    std::thread::Builder::new()
+        .name("meal-planner-solver".to_string())
+        .spawn(move || {
+            // the receiver is only gone when nobody waits for the result anymore
+            let _ = sender.send(request.solve());
+        })
+        .map_err(|e| format!("Failed to start solver thread: {e}"))?;
+    receiver
+        .await
+        .map_err(|_| "Solver thread stopped without a result".to_string())?
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn solve_in_background(request: SolveDayRequest) -> Result<Solution, String> {
+    let json = serde_json::to_string(&request)
+        .map_err(|e| format!("Failed to serialize solve request: {e}"))?;
+    match worker::solve(SOLVER_WORKER_URL, &json).await {
+        Ok(response) => serde_json::from_str::<Result<Solution, String>>(&response)
+            .map_err(|e| format!("Failed to parse solver worker response: {e}"))?,
+        Err(e) => {
+            tracing::warn!("Solver worker unavailable, solving on the main thread: {e}");
+            request.solve()
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod worker {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{ErrorEvent, MessageEvent, Worker, WorkerOptions, WorkerType};
+
+    use crate::bl::constraints_solver::SolveDayRequest;
+
+    /// Entry point of `solver_worker.js`, takes a serialized [`SolveDayRequest`] and returns the
+    /// serialized `Result` of solving it.
+    #[wasm_bindgen]
+    pub fn solve_day_json(request: &str) -> String {
+        let result = serde_json::from_str::<SolveDayRequest>(request)
+            .map_err(|e| format!("Invalid solve request: {e}"))
+            .and_then(|request| request.solve());
+        serde_json::to_string(&result)
+            .unwrap_or_else(|e| format!("{{\"Err\":\"Failed to serialize solution: {e}\"}}"))
+    }
+
+    // one worker per solve, terminated afterwards so a stale solve never answers a newer one
+    pub(super) async fn solve(worker_url: &str, request: &str) -> Result<String, String> {
+        let options = WorkerOptions::new();
+        options.set_type(WorkerType::Module);
+        let worker = Worker::new_with_options(worker_url, &options)
+            .map_err(|e| format!("Failed to create solver worker: {e:?}"))?;
+
+        let promise = js_sys::Promise::new(&mut |resolve, reject| {
+            let on_message = Closure::once_into_js(move |evt: MessageEvent| {
+                let _ = resolve.call1(&JsValue::UNDEFINED, &evt.data());
+            });
+            let on_error = Closure::once_into_js(move |evt: ErrorEvent| {
+                let _ = reject.call1(&JsValue::UNDEFINED, &JsValue::from_str(&evt.message()));
+            });
+            worker.set_onmessage(Some(on_message.unchecked_ref()));
+            worker.set_onerror(Some(on_error.unchecked_ref()));
+        });
+        if let Err(e) = worker.post_message(&JsValue::from_str(request)) {
+            worker.terminate();
+            return Err(format!("Failed to send solve request: {e:?}"));
+        }
+        let response = JsFuture::from(promise).await;
+        worker.terminate();
+        response
+            .map_err(|e| format!("Solver worker failed: {e:?}"))?
+            .as_string()
+            .ok_or_else(|| "Solver worker sent a non-text response".to_string())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::ConstraintsSolver;
+    use crate::data_types::{
+        AllowedUnitsType, DietaryRestrictions, MacroElementsType, NutrientType, ProductBuilder,
+        constraints::{MealConstraint, NutrientConstraint, ProductConstraint},
+    };
+    use approx::assert_relative_eq;
+    use futures::executor::block_on;
+    use std::collections::HashMap;
+
+    #[test]
+    fn background_solve_matches_solving_in_place() {
+        let protein = MacroElementsType::Protein;
+        let chicken = ProductBuilder::new("Chicken")
+            .protein(30.0)
+            .build()
+            .unwrap();
+        let lunch = MealConstraint {
+            products: vec![
+                ProductConstraint::new(Box::new(chicken), None, Some(200), AllowedUnitsType::Gram)
+                    .unwrap(),
+            ],
+            nutrients: vec![NutrientConstraint::new(protein, None, Some(45.0)).unwrap()],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day = DayMealPlanConstraint {
+            meals: HashMap::from([("Lunch".to_string(), lunch)]),
+            nutrients: Vec::new(),
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        };
+        let objective = Objective::from(NutrientType::Macro(protein));
+
+        let expected = ConstraintsSolver::new(MinOrMax::Max, objective)
+            .solve_day(&day)
+            .unwrap();
+        let solution = block_on(solve_day_async(MinOrMax::Max, objective, day)).unwrap();
+        assert_relative_eq!(
+            solution.nutrient_total(NutrientType::Macro(protein)),
+            expected.nutrient_total(NutrientType::Macro(protein))
+        );
+        assert_relative_eq!(
+            solution.nutrient_total(NutrientType::Macro(protein)),
+            45.0,
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn cached_solve_reuses_plan_of_unchanged_day() {
+        use crate::database_access::mock_db::MockProductDb;
+
+        let protein = MacroElementsType::Protein;
+        let tofu = ProductBuilder::new("Tofu").protein(15.0).build().unwrap();
+        let lunch = MealConstraint {
+            products: vec![
+                ProductConstraint::new(Box::new(tofu), None, Some(300), AllowedUnitsType::Gram)
+                    .unwrap(),
+            ],
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day = DayMealPlanConstraint {
+            meals: HashMap::from([("Lunch".to_string(), lunch)]),
+            nutrients: Vec::new(),
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        };
+        let objective = Objective::from(NutrientType::Macro(protein));
+        let mut db = MockProductDb::new();
+
+        let solve = |db: &mut MockProductDb, day: DayMealPlanConstraint| {
+            block_on(solve_day_cached(
+                db,
+                "monday",
+                MinOrMax::Max,
+                objective,
+                day,
+            ))
+            .unwrap()
+        };
+        let first = solve(&mut db, day.clone());
+        assert_relative_eq!(first.nutrient_total(NutrientType::Macro(protein)), 45.0);
+        assert_eq!(db.plans.len(), 1);
+
+        // a solved plan tampered with in storage proves the solver was skipped
+        let (fingerprint, json) = db.plans["monday"].clone();
+        db.plans.insert(
+            "monday".to_string(),
+            (fingerprint, json.replace("\"Lunch\"", "\"Brunch\"")),
+        );
+        let cached = solve(&mut db, day.clone());
+        assert_eq!(cached.meal_names(&cached.day_names()[0]), vec!["Brunch"]);
+
+        let mut changed = day;
+        changed.meals.get_mut("Lunch").unwrap().nutrients =
+            vec![NutrientConstraint::new(protein, None, Some(30.0)).unwrap()];
+        let resolved = solve(&mut db, changed);
+        assert_relative_eq!(
+            resolved.nutrient_total(NutrientType::Macro(protein)),
+            30.0,
+            epsilon = 1e-4
+        );
+        assert_ne!(db.plans["monday"].0, fingerprint);
+    }
+}
@@ -0,0 +1,206 @@
+//! Side by side comparison of products, e.g. when choosing a replacement for one of them.
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::data_types::{
+    AllowedUnitsType, MacroElementsType, MicroNutrientsType, NutrientType, Product, ProductId,
+};
+
+/// Amount of food a [`ComparisonRow`] gives its values for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComparisonBasis {
+    Per100g,
+    /// One unit of each product, only for units every compared product allows.
+    PerUnit(AllowedUnitsType),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonRow {
+    pub nutrient: NutrientType,
+    pub basis: ComparisonBasis,
+    /// One value per compared product in their order, `None` when the product doesn't list it.
+    pub values: Vec<Option<f32>>,
+    /// Index of the healthiest value. Markers are only set for nutrients where more or less is
+    /// clearly better and when at least two known values differ.
+    pub best: Option<usize>,
+    pub worst: Option<usize>,
+}
+
+/// Rows aligned over the compared products, see [`compare_products`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonTable {
+    pub products: Vec<ProductId>,
+    pub rows: Vec<ComparisonRow>,
+}
+
+/// Compares `products` by every macro element per 100g, micro nutrients listed by any of them
+/// per 100g and calories per each unit all of them allow besides grams.
+#[must_use]
+pub fn compare_products(products: &[Product]) -> ComparisonTable {
+    let per_100g = MacroElementsType::iter()
+        .map(NutrientType::Macro)
+        .chain(
+            MicroNutrientsType::iter()
+                .filter(|micro| products.iter().any(|p| p.micro_nutrients[*micro].is_some()))
+                .map(NutrientType::Micro),
+        )
+        .map(|nutrient| {
+            row(
+                nutrient,
+                ComparisonBasis::Per100g,
+                products
+                    .iter()
+                    .map(|product| product.get_nutrient_amount(nutrient))
+                    .collect(),
+            )
+        });
+    let calories = NutrientType::Macro(MacroElementsType::Calories);
+    let per_unit = AllowedUnitsType::iter()
+        .filter(|unit| {
+            *unit != AllowedUnitsType::Gram
+                && !products.is_empty()
+                && products.iter().all(|p| p.allowed_units.contains_key(unit))
+        })
+        .map(|unit| {
+            row(
+                calories,
+                ComparisonBasis::PerUnit(unit),
+                products
+                    .iter()
+                    .map(|product| {
+                        // a unit weighs `amount` grams
+                        let grams = f32::from(product.allowed_units[&unit].amount);
+                        product
+                            .get_nutrient_amount(calories)
+                            .map(|per_100g| per_100g * grams / 100.0)
+                    })
+                    .collect(),
+            )
+        });
+    ComparisonTable {
+        products: products.iter().map(Product::id).collect(),
+        rows: per_100g.chain(per_unit).collect(),
+    }
+}
+
+fn row(nutrient: NutrientType, basis: ComparisonBasis, values: Vec<Option<f32>>) -> ComparisonRow {
+    let known = || {
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| value.map(|value| (i, value)))
+    };
+    let min = known().min_by(|a, b| a.1.total_cmp(&b.1));
+    let max = known().max_by(|a, b| a.1.total_cmp(&b.1));
+    let (best, worst) = match (min, max, higher_is_better(nutrient)) {
+        (Some(min), Some(max), Some(higher)) if min.1 < max.1 => {
+            if higher {
+                (Some(max.0), Some(min.0))
+            } else {
+                (Some(min.0), Some(max.0))
+            }
+        }
+        _ => (None, None),
+    };
+    ComparisonRow {
+        nutrient,
+        basis,
+        values,
+        best,
+        worst,
+    }
+}
+
+// `None` for nutrients that are neither good nor bad in larger amounts
+fn higher_is_better(nutrient: NutrientType) -> Option<bool> {
+    match nutrient {
+        NutrientType::Macro(MacroElementsType::Protein)
+        | NutrientType::Micro(
+            MicroNutrientsType::Fiber
+            | MicroNutrientsType::Zinc
+            | MicroNutrientsType::Iron
+            | MicroNutrientsType::Calcium
+            | MicroNutrientsType::Potassium
+            | MicroNutrientsType::Magnesium
+            | MicroNutrientsType::VitaminA
+            | MicroNutrientsType::VitaminC
+            | MicroNutrientsType::VitaminD
+            | MicroNutrientsType::VitaminB12,
+        ) => Some(true),
+        NutrientType::Macro(
+            MacroElementsType::SaturatedFat
+            | MacroElementsType::Sugar
+            | MacroElementsType::Calories,
+        )
+        | NutrientType::Micro(MicroNutrientsType::Sodium | MicroNutrientsType::Alcohol) => {
+            Some(false)
+        }
+        NutrientType::Macro(MacroElementsType::Fat | MacroElementsType::Carbs) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::ProductBuilder;
+
+    #[test]
+    fn aligns_rows_and_marks_best_and_worst() {
+        let white = ProductBuilder::new("White bread")
+            .carbs(50.0)
+            .sugar(5.0)
+            .protein(8.0)
+            .unit(AllowedUnitsType::Piece, 30, 1)
+            .build()
+            .unwrap();
+        let rye = ProductBuilder::new("Rye bread")
+            .carbs(45.0)
+            .sugar(3.0)
+            .protein(8.0)
+            .micro(MicroNutrientsType::Fiber, 6.0)
+            .unit(AllowedUnitsType::Piece, 40, 1)
+            .build()
+            .unwrap();
+
+        let table = compare_products(&[white.clone(), rye.clone()]);
+        assert_eq!(table.products, vec![white.id(), rye.id()]);
+        let find = |nutrient, basis| {
+            table
+                .rows
+                .iter()
+                .find(|row| row.nutrient == nutrient && row.basis == basis)
+                .unwrap()
+        };
+
+        let sugar = find(
+            NutrientType::Macro(MacroElementsType::Sugar),
+            ComparisonBasis::Per100g,
+        );
+        assert_eq!(sugar.values, vec![Some(5.0), Some(3.0)]);
+        assert_eq!((sugar.best, sugar.worst), (Some(1), Some(0)));
+        let fiber = find(
+            NutrientType::Micro(MicroNutrientsType::Fiber),
+            ComparisonBasis::Per100g,
+        );
+        assert_eq!(fiber.values, vec![None, Some(6.0)]);
+        assert_eq!((fiber.best, fiber.worst), (None, None));
+        let protein = find(
+            NutrientType::Macro(MacroElementsType::Protein),
+            ComparisonBasis::Per100g,
+        );
+        assert_eq!((protein.best, protein.worst), (None, None));
+
+        let per_slice = find(
+            NutrientType::Macro(MacroElementsType::Calories),
+            ComparisonBasis::PerUnit(AllowedUnitsType::Piece),
+        );
+        let calories =
+            |p: &Product, grams: f32| p.macro_elements[MacroElementsType::Calories] * grams / 100.0;
+        assert_eq!(
+            per_slice.values,
+            vec![Some(calories(&white, 30.0)), Some(calories(&rye, 40.0))]
+        );
+        assert_eq!(table.rows.len(), MacroElementsType::iter().count() + 2);
+    }
+}
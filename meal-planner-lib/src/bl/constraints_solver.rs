@@ -1,18 +1,48 @@
 use crate::data_types::{
-    AllowedUnitsType, NutrientType, Product,
-    constraints::{DayMealPlanConstraint, MealConstraint, NutrientConstraint, ProductConstraint},
+    AllowedUnitsType, DietaryRestrictions, MacroElementsType, MealSlot, MicroNutrientsType,
+    NutrientType, Product, ProductId,
+    constraints::{
+        DayMealPlanConstraint, LeftoverConstraint, MacroPercentConstraint, MealConstraint,
+        NutrientConstraint, ProductConstraint, ProductRatioConstraint, RatioBase, RatioRelation,
+        RecipeConstraint, RepetitionConstraint, WeekMealPlanConstraint,
+    },
 };
 
-use microlp::{ComparisonOp, OptimizationDirection, Problem, Variable};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use microlp::{ComparisonOp, OptimizationDirection, Variable};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
-#[derive(Debug, Clone, Copy)]
+use super::grocery_haul::Pantry;
+use super::lp_model::LpModel;
+use super::quantity::Quantity;
+use super::rounding::{RoundingPolicy, RoundingViolation};
+use super::solution_report::SolutionReport;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum MinOrMax {
     Min,
     Max,
 }
 
+/// Value minimized or maximized by the solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    Nutrient(NutrientType),
+    /// Total grocery cost of the plan, requires every product to have a price.
+    Cost,
+}
+
+impl From<NutrientType> for Objective {
+    fn from(value: NutrientType) -> Self {
+        Objective::Nutrient(value)
+    }
+}
+
 enum ProductEntry {
     Variable(ProductVariable),
+    Fixed(FixedProduct),
     Subcontainer(ProductsContainer),
 }
 
@@ -20,6 +50,7 @@ impl ProductEntry {
     fn get_all_product_variables(&self) -> Box<dyn Iterator<Item = &ProductVariable> + '_> {
         match self {
             ProductEntry::Variable(var) => Box::new(std::iter::once(var)),
+            ProductEntry::Fixed(_) => Box::new(std::iter::empty()),
             ProductEntry::Subcontainer(container) => Box::new(
                 container
                     .inner
@@ -28,26 +59,60 @@ impl ProductEntry {
             ),
         }
     }
+
+    // pushes the gram variables of `product_id` to `terms` and returns its fixed grams
+    fn collect_product_grams(&self, product_id: &str, terms: &mut Vec<(Variable, f64)>) -> f64 {
+        match self {
+            ProductEntry::Variable(var) => {
+                if var.name == product_id {
+                    terms.push((var.variable_gram, 1.0));
+                }
+                0.0
+            }
+            ProductEntry::Fixed(fixed) if fixed.product.id() == product_id => fixed.amount_grams,
+            ProductEntry::Fixed(_) => 0.0,
+            ProductEntry::Subcontainer(container) => container
+                .inner
+                .iter()
+                .map(|entry| entry.collect_product_grams(product_id, terms))
+                .sum(),
+        }
+    }
 }
 
 struct ProductsContainer {
     name: String,
+    // only meals have a slot
+    slot: Option<MealSlot>,
     inner: Vec<ProductEntry>,
 }
 struct ProductVariable {
-    name: String,
+    name: ProductId,
     product: Product,
     unit: AllowedUnitsType,
     variable_gram: Variable,
     variable_unit_divided: Variable,
+    // binary "product is used" variable, only present when the meal limits the product count
+    variable_used: Option<Variable>,
+}
+
+// negligible product, kept in the plan at its low bound without entering the LP problem
+struct FixedProduct {
+    product: Product,
+    unit: AllowedUnitsType,
+    amount_grams: f64,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Fraction {
     pub numerator: u16,
     pub denominator: u16,
 }
 
+/// Solved plan, serializable so it can be saved and loaded back.
+// products are most of the entries, boxing them would only add allocations
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize)]
 pub enum SolutionEntry {
     Week {
         entries: Vec<SolutionEntry>,
@@ -58,6 +123,7 @@ pub enum SolutionEntry {
     },
     Meal {
         name: String,
+        slot: Option<MealSlot>,
         entries: Vec<SolutionEntry>,
     },
     Product {
@@ -68,42 +134,543 @@ pub enum SolutionEntry {
     },
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Solution {
     pub solution: SolutionEntry,
 }
 
+impl SolutionEntry {
+    /// Amount of a product entry, `None` for the other entries.
+    #[must_use]
+    pub fn quantity(&self) -> Option<Quantity> {
+        match self {
+            SolutionEntry::Product { amount_grams, .. } => {
+                Some(Quantity::from_grams(*amount_grams))
+            }
+            _ => None,
+        }
+    }
+
+    /// Total amount of `nutrient` in the entry. Negligible products are not counted.
+    #[must_use]
+    pub fn nutrient_total(&self, nutrient: NutrientType) -> f64 {
+        match self {
+            SolutionEntry::Week { entries }
+            | SolutionEntry::Day { entries, .. }
+            | SolutionEntry::Meal { entries, .. } => {
+                entries.iter().map(|e| e.nutrient_total(nutrient)).sum()
+            }
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                ..
+            } if !product.negligible => {
+                f64::from(product.get_nutrient_amount(nutrient).unwrap_or(0.0)) * amount_grams
+                    / 100.0
+            }
+            SolutionEntry::Product { .. } => 0.0,
+        }
+    }
+
+    /// Amounts of every macro element and micro nutrient in the entry.
+    #[must_use]
+    pub fn nutrient_totals(&self) -> NutrientTotals {
+        let amounts = MacroElementsType::iter()
+            .map(NutrientType::Macro)
+            .chain(MicroNutrientsType::iter().map(NutrientType::Micro))
+            .map(|nutrient| (nutrient, self.nutrient_total(nutrient)))
+            .collect();
+        NutrientTotals { amounts }
+    }
+
+    /// Share of every product in the total of `nutrient` in the entry, amounts of a product
+    /// eaten in several meals added up, largest first. Negligible products and products without
+    /// the nutrient are left out.
+    #[must_use]
+    pub fn nutrient_breakdown(&self, nutrient: NutrientType) -> Vec<NutrientContribution> {
+        let mut amounts: BTreeMap<ProductId, f64> = BTreeMap::new();
+        for (product, grams) in self.product_amounts() {
+            if product.negligible {
+                continue;
+            }
+            let amount =
+                f64::from(product.get_nutrient_amount(nutrient).unwrap_or(0.0)) * grams / 100.0;
+            if amount > 0.0 {
+                *amounts.entry(product.id()).or_default() += amount;
+            }
+        }
+        let total: f64 = amounts.values().sum();
+        let mut breakdown: Vec<NutrientContribution> = amounts
+            .into_iter()
+            .map(|(product_id, amount)| NutrientContribution {
+                product_id,
+                amount,
+                percent: amount / total * 100.0,
+            })
+            .collect();
+        // stable, equal amounts stay ordered by id
+        breakdown.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+        breakdown
+    }
+
+    fn product_amounts(&self) -> Vec<(&Product, f64)> {
+        match self {
+            SolutionEntry::Week { entries }
+            | SolutionEntry::Day { entries, .. }
+            | SolutionEntry::Meal { entries, .. } => entries
+                .iter()
+                .flat_map(SolutionEntry::product_amounts)
+                .collect(),
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                ..
+            } => vec![(product, *amount_grams)],
+        }
+    }
+}
+
+/// Amount of a nutrient coming from one product of a plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NutrientContribution {
+    pub product_id: ProductId,
+    pub amount: f64,
+    /// Percent of the total of the nutrient.
+    pub percent: f64,
+}
+
+/// Amounts of all nutrients in a part of a plan. Negligible products are not counted and micro
+/// nutrients missing from a product count as zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NutrientTotals {
+    amounts: Vec<(NutrientType, f64)>,
+}
+
+impl NutrientTotals {
+    /// Totals of `products` at the given grams, counted like in a solved plan.
+    pub(crate) fn of_products(products: &[(&Product, f64)]) -> Self {
+        let amounts = MacroElementsType::iter()
+            .map(NutrientType::Macro)
+            .chain(MicroNutrientsType::iter().map(NutrientType::Micro))
+            .map(|nutrient| {
+                let total = products
+                    .iter()
+                    .filter(|(product, _)| !product.negligible)
+                    .map(|(product, grams)| {
+                        f64::from(product.get_nutrient_amount(nutrient).unwrap_or(0.0)) * grams
+                            / 100.0
+                    })
+                    .sum();
+                (nutrient, total)
+            })
+            .collect();
+        Self { amounts }
+    }
+
+    #[must_use]
+    pub fn get(&self, nutrient: NutrientType) -> f64 {
+        self.amounts
+            .iter()
+            .find(|(n, _)| *n == nutrient)
+            .map_or(0.0, |(_, amount)| *amount)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NutrientType, f64)> + '_ {
+        self.amounts.iter().copied()
+    }
+}
+
+impl Solution {
+    /// Total amount of `nutrient` in the plan. Negligible products are not counted.
+    #[must_use]
+    pub fn nutrient_total(&self, nutrient: NutrientType) -> f64 {
+        self.solution.nutrient_total(nutrient)
+    }
+
+    /// Ids of every product in the plan.
+    pub(crate) fn product_ids(&self) -> BTreeSet<ProductId> {
+        self.solution
+            .product_amounts()
+            .into_iter()
+            .map(|(product, _)| product.id())
+            .collect()
+    }
+
+    /// Products of the plan with their share of `nutrient`, largest first. See
+    /// [`SolutionEntry::nutrient_breakdown`].
+    #[must_use]
+    pub fn nutrient_breakdown(&self, nutrient: NutrientType) -> Vec<NutrientContribution> {
+        self.solution.nutrient_breakdown(nutrient)
+    }
+
+    /// Nutrient totals of every day, in plan order.
+    #[must_use]
+    pub fn day_totals(&self) -> Vec<(String, NutrientTotals)> {
+        self.days()
+            .into_iter()
+            .filter_map(|day| match day {
+                SolutionEntry::Day { name, .. } => Some((name.clone(), day.nutrient_totals())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Nutrient totals of every meal as `(day, meal, totals)`, in plan order.
+    #[must_use]
+    pub fn meal_totals(&self) -> Vec<(String, String, NutrientTotals)> {
+        let mut totals = Vec::new();
+        for day in self.days() {
+            let SolutionEntry::Day {
+                name: day_name,
+                entries,
+            } = day
+            else {
+                continue;
+            };
+            for meal in entries {
+                if let SolutionEntry::Meal { name, .. } = meal {
+                    totals.push((day_name.clone(), name.clone(), meal.nutrient_totals()));
+                }
+            }
+        }
+        totals
+    }
+
+    /// Names of the days of the plan, in order.
+    #[must_use]
+    pub fn day_names(&self) -> Vec<String> {
+        self.days()
+            .into_iter()
+            .filter_map(|entry| match entry {
+                SolutionEntry::Day { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Names of the meals of `day`, in the order they are shown and saved.
+    #[must_use]
+    pub fn meal_names(&self, day: &str) -> Vec<String> {
+        self.days()
+            .into_iter()
+            .filter_map(|entry| match entry {
+                SolutionEntry::Day { name, entries } if name == day => Some(entries),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|entry| match entry {
+                SolutionEntry::Meal { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Moves `meal` of `day` to position `new_index` among the meals of the day. The order is
+    /// kept when the plan is saved.
+    pub fn reorder_meal(&mut self, day: &str, meal: &str, new_index: usize) -> Result<(), String> {
+        let entries = match &mut self.solution {
+            SolutionEntry::Week { entries } => entries.iter_mut().find_map(|entry| match entry {
+                SolutionEntry::Day { name, entries } if name == day => Some(entries),
+                _ => None,
+            }),
+            SolutionEntry::Day { name, entries } if name == day => Some(entries),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Day '{day}' not found"))?;
+        let index = entries
+            .iter()
+            .position(|entry| matches!(entry, SolutionEntry::Meal { name, .. } if name == meal))
+            .ok_or_else(|| format!("Meal '{meal}' not found in '{day}'"))?;
+        if new_index >= entries.len() {
+            return Err(format!(
+                "Position {new_index} is out of range for {} meals",
+                entries.len()
+            ));
+        }
+        let entry = entries.remove(index);
+        entries.insert(new_index, entry);
+        Ok(())
+    }
+
+    /// Day entries of the plan, a single day plan gives one.
+    pub(crate) fn days(&self) -> Vec<&SolutionEntry> {
+        match &self.solution {
+            SolutionEntry::Week { entries } => entries.iter().collect(),
+            day @ SolutionEntry::Day { .. } => vec![day],
+            _ => Vec::new(),
+        }
+    }
+
+    // grams of every product keyed by day, meal and product id
+    pub(crate) fn grams_by_meal(&self) -> HashMap<(String, String, ProductId), f64> {
+        let mut grams = HashMap::new();
+        for day in self.days() {
+            let SolutionEntry::Day {
+                name: day_name,
+                entries,
+            } = day
+            else {
+                continue;
+            };
+            for meal in entries {
+                let SolutionEntry::Meal {
+                    name: meal_name,
+                    entries,
+                    ..
+                } = meal
+                else {
+                    continue;
+                };
+                for entry in entries {
+                    if let SolutionEntry::Product {
+                        product,
+                        amount_grams,
+                        ..
+                    } = entry
+                    {
+                        *grams
+                            .entry((day_name.clone(), meal_name.clone(), product.id()))
+                            .or_insert(0.0) += amount_grams;
+                    }
+                }
+            }
+        }
+        grams
+    }
+}
+
+// largest relative change of an objective weight made by `SolverOptions::random_seed`
+const OBJECTIVE_JITTER: f64 = 0.05;
+
+/// Tweaks letting repeated solves of the same constraints return different plans.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SolverOptions {
+    /// Seed for slightly perturbing objective weights of products, so a different seed may pick
+    /// other products among nearly equal ones. `None` keeps the exact optimum.
+    pub random_seed: Option<u64>,
+    /// Relative worsening of objective weights of products used in plans passed to
+    /// [`ConstraintsSolver::avoid_solution`], between 0 and 1.
+    pub variety_penalty: f64,
+    /// Rounds solved amounts to practical values, constraints broken by it are reported in
+    /// [`SolveDiagnostics::rounding_violations`]. `None` keeps the exact amounts.
+    #[serde(default)]
+    pub rounding: Option<RoundingPolicy>,
+    /// Relative improvement of objective weights of products passed to
+    /// [`ConstraintsSolver::prefer_expiring`], between 0 and 1.
+    #[serde(default)]
+    pub expiry_bonus: f64,
+}
+
+/// Result of [`ConstraintsSolver::solve_day_with_diagnostics`].
+pub struct SolveDiagnostics {
+    pub solution: Solution,
+    pub objective_value: f64,
+    /// Names of the constraints met with equality, the ones limiting the objective.
+    pub binding_constraints: Vec<String>,
+    /// Constraints broken by [`SolverOptions::rounding`].
+    pub rounding_violations: Vec<RoundingViolation>,
+}
+
+/// Change of one constraint that, together with the others of
+/// [`ConstraintsSolver::diagnose_infeasibility`], makes an infeasible problem solvable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintRelaxation {
+    /// Name of the constraint, as in [`SolveDiagnostics::binding_constraints`].
+    pub constraint: String,
+    pub bound: f64,
+    pub suggested_bound: f64,
+}
+
+impl ConstraintRelaxation {
+    /// Change relative to the bound, used for ranking.
+    #[must_use]
+    pub fn relative_change(&self) -> f64 {
+        (self.suggested_bound - self.bound).abs() / self.bound.abs().max(1.0)
+    }
+}
+
+/// Single day solve, as sent by the FFI and server frontends.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SolveDayRequest {
+    pub direction: MinOrMax,
+    pub objective: Objective,
+    pub day: DayMealPlanConstraint,
+}
+
+impl SolveDayRequest {
+    pub(crate) fn solve(&self) -> Result<Solution, String> {
+        ConstraintsSolver::new(self.direction, self.objective).solve_day(&self.day)
+    }
+}
+
 pub struct ConstraintsSolver {
-    problem: Problem,
+    problem: LpModel,
     variables: ProductsContainer,
-    nutrient_to_optimize: NutrientType,
+    min_or_max: MinOrMax,
+    objective: Objective,
+    options: SolverOptions,
+    // ids of products used in the plans passed to `avoid_solution`
+    avoided_products: HashSet<ProductId>,
+    // ids of pantry products passed to `prefer_expiring`
+    expiring_products: HashSet<ProductId>,
 }
 
 impl ConstraintsSolver {
     #[must_use]
-    pub fn new(min_or_max: MinOrMax, nutrient_to_optimize: NutrientType) -> Self {
+    pub fn new<O>(min_or_max: MinOrMax, objective: O) -> Self
+    where
+        O: Into<Objective>,
+    {
         let problem = match min_or_max {
-            MinOrMax::Min => Problem::new(OptimizationDirection::Minimize),
-            MinOrMax::Max => Problem::new(OptimizationDirection::Maximize),
+            MinOrMax::Min => LpModel::new(OptimizationDirection::Minimize),
+            MinOrMax::Max => LpModel::new(OptimizationDirection::Maximize),
         };
 
         Self {
             problem,
             variables: ProductsContainer {
                 name: "root".to_string(),
+                slot: None,
                 inner: Vec::new(),
             },
-            nutrient_to_optimize,
+            min_or_max,
+            objective: objective.into(),
+            options: SolverOptions::default(),
+            avoided_products: HashSet::new(),
+            expiring_products: HashSet::new(),
         }
     }
 
+    #[must_use]
+    pub fn with_options(mut self, options: SolverOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Makes products used in `previous` less attractive by [`SolverOptions::variety_penalty`],
+    /// so the next solve prefers other products where constraints allow.
+    pub fn avoid_solution(&mut self, previous: &Solution) {
+        self.avoided_products.extend(
+            previous
+                .grams_by_meal()
+                .into_iter()
+                .filter(|(_, grams)| *grams > 0.0)
+                .map(|((_, _, product_id), _)| product_id),
+        );
+    }
+
+    /// Makes products of `pantry` expiring within `days` after `today` (days since the Unix
+    /// epoch) more attractive by [`SolverOptions::expiry_bonus`], so plans use them up first.
+    pub fn prefer_expiring(&mut self, pantry: &Pantry, today: u64, days: u64) {
+        self.expiring_products.extend(
+            pantry
+                .get_expiring(today, days)
+                .into_iter()
+                .filter(|item| item.grams > 0.0)
+                .map(|item| item.product_id),
+        );
+    }
+
     pub fn solve_day(
         &mut self,
         day_constraints: &DayMealPlanConstraint,
     ) -> Result<Solution, String> {
-        self.create_constraints(day_constraints);
+        self.solve_day_with_diagnostics(day_constraints)
+            .map(|diagnostics| diagnostics.solution)
+    }
+
+    /// Like [`Self::solve_day`], additionally reporting how close the plan is to the bounds of
+    /// every nutrient constraint.
+    pub fn solve_day_with_report(
+        &mut self,
+        day_constraints: &DayMealPlanConstraint,
+    ) -> Result<(Solution, SolutionReport), String> {
+        let solution = self.solve_day(day_constraints)?;
+        let report = SolutionReport::new(&solution, day_constraints);
+        Ok((solution, report))
+    }
+
+    /// Like [`Self::solve_day`], additionally reporting which constraints limit the solution.
+    pub fn solve_day_with_diagnostics(
+        &mut self,
+        day_constraints: &DayMealPlanConstraint,
+    ) -> Result<SolveDiagnostics, String> {
+        self.check_day(day_constraints)?;
+        self.create_constraints("Day1", day_constraints);
+        let mut diagnostics = self.solve_problem()?;
+        if let Some(rounding) = self.options.rounding {
+            diagnostics.rounding_violations =
+                rounding.round_day(&mut diagnostics.solution, day_constraints)?;
+        }
+        Ok(diagnostics)
+    }
+
+    /// Solves `day_constraints` keeping every product of `previous` close to its previous amount,
+    /// so small constraint edits give small plan changes. Amounts may move by
+    /// `max_deviation_percent` of the previous amount, but always by at least one unit.
+    pub fn resolve_with_anchor(
+        &mut self,
+        day_constraints: &DayMealPlanConstraint,
+        previous: &Solution,
+        max_deviation_percent: f64,
+    ) -> Result<Solution, String> {
+        if !max_deviation_percent.is_finite() || max_deviation_percent < 0.0 {
+            return Err(format!(
+                "Invalid maximal deviation {max_deviation_percent}%"
+            ));
+        }
+        self.check_day(day_constraints)?;
+        self.create_constraints("Day1", day_constraints);
+        self.add_anchor_constraints(previous, max_deviation_percent / 100.0);
+        let mut solution = self.solve_problem()?.solution;
+        if let Some(rounding) = self.options.rounding {
+            rounding.round(&mut solution)?;
+        }
+        Ok(solution)
+    }
+
+    /// Solves all days of `week` as one problem, needed for constraints spanning several days.
+    pub fn solve_week(&mut self, week: &WeekMealPlanConstraint) -> Result<Solution, String> {
+        self.solve_week_with_diagnostics(week)
+            .map(|diagnostics| diagnostics.solution)
+    }
+
+    /// Like [`Self::solve_week`], additionally reporting which constraints limit the solution.
+    pub fn solve_week_with_diagnostics(
+        &mut self,
+        week: &WeekMealPlanConstraint,
+    ) -> Result<SolveDiagnostics, String> {
+        for (_, day) in &week.days {
+            self.check_day(day)?;
+        }
+        Self::check_leftovers(week)?;
+        Self::check_repetitions(week)?;
+        for (day_name, day) in &week.days {
+            self.create_constraints(day_name, day);
+        }
+        for leftover in &week.leftovers {
+            self.add_leftover_constraint(&week.days[leftover.cooked_on()].0, leftover);
+        }
+        for repetition in &week.repetitions {
+            self.add_repetition_constraint(repetition);
+        }
+        let mut diagnostics = self.solve_problem()?;
+        if let Some(rounding) = self.options.rounding {
+            diagnostics.rounding_violations =
+                rounding.round_week(&mut diagnostics.solution, week)?;
+        }
+        Ok(diagnostics)
+    }
+
+    fn solve_problem(&self) -> Result<SolveDiagnostics, String> {
         #[allow(clippy::match_wildcard_for_single_variants)]
         match self.problem.solve() {
-            Ok(s) => Ok(self.solver_solution_to_output(&s)),
+            Ok(s) => Ok(SolveDiagnostics {
+                solution: self.solver_solution_to_output(&s),
+                objective_value: s.objective(),
+                binding_constraints: self.problem.binding_constraints(&s),
+                rounding_violations: Vec::new(),
+            }),
             Err(e) => match e {
                 microlp::Error::Infeasible => Err("Constraints are infeasible".to_string()),
                 microlp::Error::Unbounded => Err("Problem is unbounded".to_string()),
@@ -112,6 +679,203 @@ impl ConstraintsSolver {
         }
     }
 
+    /// Explains why the last solve failed with infeasible constraints: the smallest set of bound
+    /// changes (relative to each bound) making it solvable, most binding constraint first. Empty
+    /// when the last problem is feasible. Product amounts and inequalities are relaxed, problems
+    /// infeasible because of product units or ratios that must hold exactly are reported as an
+    /// error.
+    pub fn diagnose_infeasibility(&self) -> Result<Vec<ConstraintRelaxation>, String> {
+        let mut relaxations: Vec<ConstraintRelaxation> = self
+            .problem
+            .minimal_relaxations()
+            .ok_or_else(|| "Constraints are infeasible even with relaxed limits".to_string())?
+            .into_iter()
+            .map(
+                |(constraint, bound, suggested_bound)| ConstraintRelaxation {
+                    constraint,
+                    bound,
+                    suggested_bound,
+                },
+            )
+            .collect();
+        relaxations.sort_by(|a, b| b.relative_change().total_cmp(&a.relative_change()));
+        Ok(relaxations)
+    }
+
+    /// Problem built by the last solve in the CPLEX LP format, for inspecting it in other tools.
+    #[must_use]
+    pub fn lp_export(&self) -> String {
+        self.problem.to_lp_format()
+    }
+
+    fn check_day(&self, day_constraints: &DayMealPlanConstraint) -> Result<(), String> {
+        let penalty = self.options.variety_penalty;
+        if !(0.0..=1.0).contains(&penalty) {
+            return Err(format!("Invalid variety penalty {penalty}"));
+        }
+        let bonus = self.options.expiry_bonus;
+        if !(0.0..=1.0).contains(&bonus) {
+            return Err(format!("Invalid expiry bonus {bonus}"));
+        }
+        if let Some(rounding) = self.options.rounding {
+            rounding.validate()?;
+        }
+        if self.objective == Objective::Cost || day_constraints.max_cost.is_some() {
+            Self::check_prices(day_constraints)?;
+        }
+        Self::check_products(day_constraints)?;
+        Self::check_product_counts(day_constraints)?;
+        Self::check_day_products(day_constraints)?;
+        Self::check_ratios(day_constraints)
+    }
+
+    // products of every meal, recipe ingredients included
+    fn meal_products(day_constraints: &DayMealPlanConstraint) -> impl Iterator<Item = &Product> {
+        day_constraints.meals.values().flat_map(|meal| {
+            meal.products.iter().map(ProductConstraint::food).chain(
+                meal.recipes
+                    .iter()
+                    .flat_map(|recipe| recipe.recipe().ingredients())
+                    .map(|ingredient| &ingredient.product),
+            )
+        })
+    }
+
+    // garbage nutrient values would make the plan meaningless rather than infeasible
+    fn check_products(day_constraints: &DayMealPlanConstraint) -> Result<(), String> {
+        for product in Self::meal_products(day_constraints) {
+            if let Err(errors) = product.validate() {
+                return Err(format!("Invalid product '{}': {}", product.id(), errors[0]));
+            }
+        }
+        Ok(())
+    }
+
+    // cost is only meaningful when every product is priced in the same currency,
+    // negligible products are not part of the cost
+    fn check_prices(day_constraints: &DayMealPlanConstraint) -> Result<(), String> {
+        let mut currency: Option<&str> = None;
+        for product in Self::meal_products(day_constraints)
+            .filter(|product| !product.negligible && day_constraints.restrictions.allows(product))
+        {
+            let price = product
+                .price
+                .as_ref()
+                .ok_or_else(|| format!("Product '{}' has no price", product.id()))?;
+            match currency {
+                Some(c) if c != price.currency => {
+                    return Err(format!(
+                        "Products are priced in different currencies: {c}, {}",
+                        price.currency
+                    ));
+                }
+                _ => currency = Some(&price.currency),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_product_counts(day_constraints: &DayMealPlanConstraint) -> Result<(), String> {
+        for (meal_name, meal) in &day_constraints.meals {
+            if let (Some(min), Some(max)) = (meal.min_products, meal.max_products)
+                && min > max
+            {
+                return Err(format!(
+                    "Meal '{meal_name}' needs at least {min} products but allows at most {max}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_day_products(day_constraints: &DayMealPlanConstraint) -> Result<(), String> {
+        for product_constr in &day_constraints.products {
+            let product_id = product_constr.food().id();
+            let in_meals = day_constraints
+                .meals
+                .values()
+                .any(|meal| meal.products.iter().any(|p| p.food().id() == product_id));
+            if !in_meals {
+                return Err(format!(
+                    "Day total of '{product_id}' is bounded but no meal has it"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_ratios(day_constraints: &DayMealPlanConstraint) -> Result<(), String> {
+        for (meal_name, meal) in &day_constraints.meals {
+            let in_meal = |id: &str| meal.products.iter().any(|p| p.food().id() == id);
+            for ratio in &meal.ratios {
+                let base_id = match ratio.base() {
+                    RatioBase::Product(id) => Some(id),
+                    RatioBase::MealWeight => None,
+                };
+                if let Some(missing) = std::iter::once(ratio.product_id())
+                    .chain(base_id)
+                    .find(|id| !in_meal(id))
+                {
+                    return Err(format!(
+                        "Ratio in meal '{meal_name}' refers to '{missing}' which is not in the meal"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_leftovers(week: &WeekMealPlanConstraint) -> Result<(), String> {
+        for leftover in &week.leftovers {
+            let product_id = leftover.product_id();
+            let (day_name, day) = week.days.get(leftover.cooked_on()).ok_or_else(|| {
+                format!(
+                    "Leftovers of '{product_id}' are cooked on day {} but the plan has {} days",
+                    leftover.cooked_on() + 1,
+                    week.days.len()
+                )
+            })?;
+            let cooked = day
+                .meals
+                .values()
+                .flat_map(|meal| meal.products.iter())
+                .any(|p| p.food().id() == *product_id);
+            if !cooked {
+                return Err(format!(
+                    "Leftovers of '{product_id}' are cooked on '{day_name}' which doesn't use it"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_repetitions(week: &WeekMealPlanConstraint) -> Result<(), String> {
+        for repetition in &week.repetitions {
+            if let RepetitionConstraint::NoConsecutiveDays { meal, .. } = repetition
+                && !week
+                    .days
+                    .iter()
+                    .any(|(_, day)| day.meals.contains_key(meal))
+            {
+                return Err(format!("No day of the plan has meal '{meal}'"));
+            }
+            for product_id in repetition.product_ids() {
+                let used = week
+                    .days
+                    .iter()
+                    .flat_map(|(_, day)| day.meals.values())
+                    .flat_map(|meal| meal.products.iter())
+                    .any(|p| p.food().id() == *product_id);
+                if !used {
+                    return Err(format!(
+                        "Repetition of '{product_id}' is limited but no meal of the plan uses it"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn solver_solution_to_output(&self, solution: &microlp::Solution) -> Solution {
         let mut week = Vec::new();
         for day in self.variables.inner.iter().map(|x| {
@@ -130,13 +894,20 @@ impl ConstraintsSolver {
                 }
             }) {
                 let mut meal_entries: Vec<SolutionEntry> = Vec::new();
-                for product in meal.inner.iter().map(|x| {
-                    if let ProductEntry::Variable(v) = x {
-                        v
-                    } else {
-                        panic!("Expected product variable")
+                for entry in &meal.inner {
+                    let product = match entry {
+                        ProductEntry::Variable(v) => v,
+                        ProductEntry::Fixed(fixed) => {
+                            meal_entries.push(Self::fixed_product_to_output(fixed));
+                            continue;
+                        }
+                        ProductEntry::Subcontainer(_) => panic!("Expected product variable"),
+                    };
+                    if let Some(used) = product.variable_used
+                        && *solution.var_value(used) < 0.5
+                    {
+                        continue;
                     }
-                }) {
                     let product_solution = SolutionEntry::Product {
                         product: product.product.clone(),
                         amount_grams: *solution.var_value(product.variable_gram),
@@ -156,6 +927,7 @@ impl ConstraintsSolver {
                 }
                 let meal_solution = SolutionEntry::Meal {
                     name: meal.name.clone(),
+                    slot: meal.slot.clone(),
                     entries: meal_entries,
                 };
                 day_entries.push(meal_solution);
@@ -171,92 +943,523 @@ impl ConstraintsSolver {
         }
     }
 
-    fn create_constraints(&mut self, day_constraints: &DayMealPlanConstraint) {
+    fn fixed_product_to_output(fixed: &FixedProduct) -> SolutionEntry {
+        let unit_data = fixed.product.allowed_units.get(&fixed.unit).unwrap();
+        let unit_grams = f64::from(unit_data.divider) * f64::from(unit_data.amount);
+        SolutionEntry::Product {
+            product: fixed.product.clone(),
+            amount_grams: fixed.amount_grams,
+            unit: fixed.unit,
+            amount_unit: Fraction {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                numerator: (fixed.amount_grams / unit_grams).round() as u16,
+                denominator: unit_data.divider,
+            },
+        }
+    }
+
+    fn create_constraints(&mut self, day_name: &str, day_constraints: &DayMealPlanConstraint) {
         let mut day_vec = Vec::new();
         self.create_day_constraints(day_constraints, &mut day_vec);
         self.variables
             .inner
             .push(ProductEntry::Subcontainer(ProductsContainer {
-                name: "Day1".to_string(),
+                name: day_name.to_string(),
+                slot: None,
                 inner: day_vec,
             }));
     }
 
-    fn create_day_constraints(
-        &mut self,
-        day_constraints: &DayMealPlanConstraint,
-        product_entries: &mut Vec<ProductEntry>,
-    ) {
-        // TODO: maybe in the future there will be day-level product constraints
-        // 1st constraint all products (at they are variables not constraints)
-
-        // constraint all meals
-        for (meal_name, meal) in &day_constraints.meals {
-            let mut meal_container = ProductsContainer {
-                name: meal_name.clone(),
-                inner: Vec::new(),
+    // previous - allowed <= grams <= previous + allowed for products present in `previous`
+    fn add_anchor_constraints(&mut self, previous: &Solution, max_deviation: f64) {
+        let previous_grams = previous.grams_by_meal();
+        let mut anchors = Vec::new();
+        for day in &self.variables.inner {
+            let ProductEntry::Subcontainer(day) = day else {
+                continue;
             };
-
-            self.add_meal_constraints(meal, &mut meal_container.inner);
-            product_entries.push(ProductEntry::Subcontainer(meal_container));
+            for meal in &day.inner {
+                let ProductEntry::Subcontainer(meal) = meal else {
+                    continue;
+                };
+                for product in meal
+                    .inner
+                    .iter()
+                    .flat_map(ProductEntry::get_all_product_variables)
+                {
+                    let key = (day.name.clone(), meal.name.clone(), product.name.clone());
+                    let Some(previous_amount) = previous_grams.get(&key) else {
+                        continue;
+                    };
+                    let unit_grams = product
+                        .product
+                        .allowed_units
+                        .get(&product.unit)
+                        .map_or(1.0, |unit| f64::from(unit.divider) * f64::from(unit.amount));
+                    let allowed = (previous_amount * max_deviation).max(unit_grams);
+                    anchors.push((
+                        format!("{} anchor in {}", product.name, meal.name),
+                        product.variable_gram,
+                        *previous_amount,
+                        allowed,
+                    ));
+                }
+            }
         }
-
-        // then nutrients constraints
-        for nutrient_constr in &day_constraints.nutrients {
-            self.add_nutrient_constraints(nutrient_constr, product_entries);
+        for (name, variable, previous_amount, allowed) in anchors {
+            self.problem.add_constraint(
+                format!("{name} max"),
+                vec![(variable, 1.0)],
+                ComparisonOp::Le,
+                previous_amount + allowed,
+            );
+            self.problem.add_constraint(
+                format!("{name} min"),
+                vec![(variable, 1.0)],
+                ComparisonOp::Ge,
+                previous_amount - allowed,
+            );
         }
     }
 
-    fn add_meal_constraints(
-        &mut self,
-        meal: &MealConstraint,
-        product_entries: &mut Vec<ProductEntry>,
-    ) {
-        // 1st products as they are variables
-        for product_constraint in &meal.products {
-            let product_variable =
-                self.add_product_constraints(product_constraint.food(), product_constraint);
-            product_entries.push(ProductEntry::Variable(product_variable));
-        }
-
-        // then nutrients constraints
-        for nutrient_constr in &meal.nutrients {
-            self.add_nutrient_constraints(nutrient_constr, product_entries);
-        }
-    }
+    // grams eaten on the days of the leftover - batch grams = 0, the batch carries the bounds
+    fn add_leftover_constraint(&mut self, day_name: &str, leftover: &LeftoverConstraint) {
+        let product_id = leftover.product_id();
+        let mut terms = Vec::new();
+        let fixed_grams: f64 = self
+            .variables
+            .inner
+            .iter()
+            .skip(*leftover.days().start())
+            .take(leftover.days().count())
+            .map(|day| day.collect_product_grams(product_id, &mut terms))
+            .sum();
+        let batch = self.problem.add_var(
+            format!("{product_id} batch {day_name}"),
+            0.0,
+            (
+                f64::from(leftover.min_batch_grams().unwrap_or(0)),
+                leftover.max_batch_grams().map_or(f64::INFINITY, f64::from),
+            ),
+        );
+        terms.push((batch, -1.0));
+        self.problem.add_constraint(
+            format!("{product_id} leftovers from {day_name}"),
+            terms,
+            ComparisonOp::Eq,
+            -fixed_grams,
+        );
+    }
+
+    // sum of "product is eaten" indicators <= allowed count - meals where it is fixed
+    fn add_repetition_constraint(&mut self, repetition: &RepetitionConstraint) {
+        match repetition {
+            RepetitionConstraint::MaxTimesPerWeek {
+                product_id,
+                max_times,
+            } => {
+                let mut terms = Vec::new();
+                let mut fixed = 0.0;
+                for day_index in 0..self.variables.inner.len() {
+                    let (uses, fixed_uses) = self.product_uses(day_index, product_id, None);
+                    terms.extend(uses.into_iter().map(|used| (used, 1.0)));
+                    fixed += fixed_uses;
+                }
+                self.problem.add_constraint(
+                    format!("{product_id} max times per week"),
+                    terms,
+                    ComparisonOp::Le,
+                    f64::from(*max_times) - fixed,
+                );
+            }
+            RepetitionConstraint::NoConsecutiveDays { meal, product_ids } => {
+                for product_id in product_ids {
+                    let days: Vec<_> = (0..self.variables.inner.len())
+                        .map(|day_index| self.product_uses(day_index, product_id, Some(meal)))
+                        .collect();
+                    for (day_index, pair) in days.windows(2).enumerate() {
+                        let terms = pair
+                            .iter()
+                            .flat_map(|(uses, _)| uses.iter().map(|used| (*used, 1.0)))
+                            .collect();
+                        let fixed: f64 = pair.iter().map(|(_, fixed)| fixed).sum();
+                        self.problem.add_constraint(
+                            format!("{product_id} not in {meal} after day {}", day_index + 1),
+                            terms,
+                            ComparisonOp::Le,
+                            1.0 - fixed,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // binary "product is eaten" variables of `product_id` in the meals of day `day_index`, only
+    // in meal `meal` when given, and the number of those meals having it fixed
+    fn product_uses(
+        &mut self,
+        day_index: usize,
+        product_id: &str,
+        meal: Option<&str>,
+    ) -> (Vec<Variable>, f64) {
+        let Some(ProductEntry::Subcontainer(day)) = self.variables.inner.get(day_index) else {
+            return (Vec::new(), 0.0);
+        };
+        let mut variables = Vec::new();
+        let mut fixed = 0.0;
+        for entry in &day.inner {
+            let ProductEntry::Subcontainer(meal_container) = entry else {
+                continue;
+            };
+            if meal.is_some_and(|meal| meal != meal_container.name) {
+                continue;
+            }
+            for product in &meal_container.inner {
+                match product {
+                    ProductEntry::Variable(var) if var.name == product_id => variables.push((
+                        format!("{product_id} eaten {} {}", day.name, meal_container.name),
+                        var.variable_gram,
+                        var.variable_used,
+                    )),
+                    ProductEntry::Fixed(f) if f.product.id() == product_id => fixed += 1.0,
+                    _ => {}
+                }
+            }
+        }
+        let uses = variables
+            .into_iter()
+            .map(|(name, grams, used)| {
+                // grams - max * eaten <= 0
+                used.unwrap_or_else(|| {
+                    let eaten = self.problem.add_binary_var(name.clone(), 0.0);
+                    self.problem.add_constraint(
+                        format!("{name} when planned"),
+                        vec![(grams, 1.0), (eaten, -f64::from(u16::MAX))],
+                        ComparisonOp::Le,
+                        0.0,
+                    );
+                    eaten
+                })
+            })
+            .collect();
+        (uses, fixed)
+    }
+
+    fn create_day_constraints(
+        &mut self,
+        day_constraints: &DayMealPlanConstraint,
+        product_entries: &mut Vec<ProductEntry>,
+    ) {
+        // constraint all meals, in the order they are eaten
+        let mut meals: Vec<_> = day_constraints.meals.iter().collect();
+        meals.sort_by(|(a_name, a), (b_name, b)| {
+            (a.slot.is_none(), &a.slot, a_name).cmp(&(b.slot.is_none(), &b.slot, b_name))
+        });
+        for (meal_name, meal) in meals {
+            let mut meal_container = ProductsContainer {
+                name: meal_name.clone(),
+                slot: meal.slot.clone(),
+                inner: Vec::new(),
+            };
+
+            self.add_meal_constraints(
+                meal_name,
+                meal,
+                &day_constraints.restrictions,
+                &mut meal_container.inner,
+            );
+            product_entries.push(ProductEntry::Subcontainer(meal_container));
+        }
+
+        // then nutrients constraints
+        for nutrient_constr in &day_constraints.nutrients {
+            self.add_nutrient_constraints("day", nutrient_constr, product_entries);
+        }
+
+        // then totals of products over all meals
+        for product_constr in &day_constraints.products {
+            self.add_day_product_constraint(product_constr, product_entries);
+        }
+
+        for macro_percent in &day_constraints.macro_percents {
+            self.add_macro_percent_constraints(macro_percent, product_entries);
+        }
+
+        if let Some(max_cost) = day_constraints.max_cost {
+            self.add_cost_constraint(max_cost, product_entries);
+        }
+    }
+
+    fn add_meal_constraints(
+        &mut self,
+        meal_name: &str,
+        meal: &MealConstraint,
+        restrictions: &DietaryRestrictions,
+        product_entries: &mut Vec<ProductEntry>,
+    ) {
+        // 1st products as they are variables
+        let selectable = meal.max_products.is_some() || meal.min_products.is_some();
+        for product_constraint in &meal.products {
+            if !restrictions.allows(product_constraint.food()) {
+                continue;
+            }
+            if product_constraint.food().negligible {
+                product_entries.push(ProductEntry::Fixed(FixedProduct {
+                    product: product_constraint.food().clone(),
+                    unit: product_constraint.unit(),
+                    amount_grams: f64::from(product_constraint.raw_low_bound().unwrap_or(0.0)),
+                }));
+                continue;
+            }
+            let product_variable = self.add_product_constraints(
+                product_constraint.food(),
+                product_constraint,
+                selectable,
+            );
+            product_entries.push(ProductEntry::Variable(product_variable));
+        }
+
+        for recipe_constr in &meal.recipes {
+            let allowed = recipe_constr
+                .recipe()
+                .ingredients()
+                .iter()
+                .all(|ingredient| restrictions.allows(&ingredient.product));
+            if allowed {
+                self.add_recipe_constraints(meal_name, recipe_constr, product_entries);
+            }
+        }
+
+        if selectable {
+            let used_variables: Vec<_> = product_entries
+                .iter()
+                .flat_map(|entry| entry.get_all_product_variables())
+                .filter_map(|p| p.variable_used.map(|used| (used, 1.0)))
+                .collect();
+            if let Some(max_products) = meal.max_products {
+                self.problem.add_constraint(
+                    format!("{meal_name} max products"),
+                    used_variables.clone(),
+                    ComparisonOp::Le,
+                    f64::from(max_products),
+                );
+            }
+            if let Some(min_products) = meal.min_products {
+                self.problem.add_constraint(
+                    format!("{meal_name} min products"),
+                    used_variables,
+                    ComparisonOp::Ge,
+                    f64::from(min_products),
+                );
+            }
+        }
+
+        for ratio in &meal.ratios {
+            self.add_ratio_constraint(meal_name, ratio, product_entries);
+        }
+
+        // then nutrients constraints
+        for nutrient_constr in &meal.nutrients {
+            self.add_nutrient_constraints(meal_name, nutrient_constr, product_entries);
+        }
+    }
+
+    // product grams - ratio * base grams (op) 0, negligible products only shift the right side
+    fn add_ratio_constraint(
+        &mut self,
+        meal_name: &str,
+        ratio: &ProductRatioConstraint,
+        products: &[ProductEntry],
+    ) {
+        let factor = f64::from(ratio.ratio());
+        let coefficient = |product_id: &str| {
+            let own = if *ratio.product_id() == product_id {
+                1.0
+            } else {
+                0.0
+            };
+            let base = if Self::in_ratio_base(ratio.base(), product_id) {
+                factor
+            } else {
+                0.0
+            };
+            own - base
+        };
+
+        let mut terms = Vec::new();
+        let mut rhs = 0.0;
+        for entry in products {
+            match entry {
+                ProductEntry::Variable(var) => {
+                    let coeff = coefficient(&var.name);
+                    if coeff != 0.0 {
+                        terms.push((var.variable_gram, coeff));
+                    }
+                }
+                ProductEntry::Fixed(fixed) => {
+                    rhs -= coefficient(&fixed.product.id()) * fixed.amount_grams;
+                }
+                ProductEntry::Subcontainer(_) => {}
+            }
+        }
+
+        let op = match ratio.relation() {
+            RatioRelation::Equal => ComparisonOp::Eq,
+            RatioRelation::AtMost => ComparisonOp::Le,
+            RatioRelation::AtLeast => ComparisonOp::Ge,
+        };
+        let base_name = match ratio.base() {
+            RatioBase::Product(id) => id.as_str(),
+            RatioBase::MealWeight => "meal",
+        };
+        self.problem.add_constraint(
+            format!("{meal_name} ratio {} to {base_name}", ratio.product_id()),
+            terms,
+            op,
+            rhs,
+        );
+    }
+
+    // sum of the product grams over all meals (op) bound - grams where it is fixed
+    fn add_day_product_constraint(
+        &mut self,
+        product_constr: &ProductConstraint,
+        products: &[ProductEntry],
+    ) {
+        let product_id = product_constr.food().id();
+        let mut terms = Vec::new();
+        let fixed_grams: f64 = products
+            .iter()
+            .map(|entry| entry.collect_product_grams(&product_id, &mut terms))
+            .sum();
+        if let Some(low_bound) = product_constr.raw_low_bound() {
+            self.problem.add_constraint(
+                format!("day min {product_id}"),
+                terms.clone(),
+                ComparisonOp::Ge,
+                f64::from(low_bound) - fixed_grams,
+            );
+        }
+        if let Some(up_bound) = product_constr.raw_up_bound() {
+            self.problem.add_constraint(
+                format!("day max {product_id}"),
+                terms,
+                ComparisonOp::Le,
+                f64::from(up_bound) - fixed_grams,
+            );
+        }
+    }
+
+    fn in_ratio_base(base: &RatioBase, product_id: &str) -> bool {
+        match base {
+            RatioBase::Product(id) => id == product_id,
+            RatioBase::MealWeight => true,
+        }
+    }
+
+    // whole servings as an integer variable, grams of every ingredient = per serving * servings
+    fn add_recipe_constraints(
+        &mut self,
+        meal_name: &str,
+        recipe_constr: &RecipeConstraint,
+        product_entries: &mut Vec<ProductEntry>,
+    ) {
+        let recipe = recipe_constr.recipe();
+        let recipe_name = recipe.name();
+        let servings = self.problem.add_integer_var(
+            format!("{meal_name} {recipe_name} servings"),
+            0.0,
+            (
+                i32::from(recipe_constr.min_servings().unwrap_or(0)),
+                i32::from(recipe_constr.max_servings().unwrap_or(u16::MAX)),
+            ),
+        );
+        for (product, grams_per_serving) in recipe.per_serving() {
+            let product_id = product.id();
+            let grams = self.problem.add_var(
+                format!("{meal_name} {recipe_name} {product_id} grams"),
+                self.objective_amount(product) * 0.01,
+                (0.0, f64::INFINITY),
+            );
+            self.problem.add_constraint(
+                format!("{meal_name} {recipe_name} {product_id} per serving"),
+                vec![(grams, 1.0), (servings, -grams_per_serving)],
+                ComparisonOp::Eq,
+                0.0,
+            );
+            product_entries.push(ProductEntry::Variable(ProductVariable {
+                name: product_id,
+                product: product.clone(),
+                unit: AllowedUnitsType::Gram,
+                variable_gram: grams,
+                // amounts in grams need no separate unit variable
+                variable_unit_divided: grams,
+                variable_used: None,
+            }));
+        }
+    }
 
     // for now return product constraint in grams, but might change to return in allowed unit with divider
     fn add_product_constraints(
         &mut self,
         product: &Product,
         product_constraint: &ProductConstraint,
+        selectable: bool,
     ) -> ProductVariable {
         // create base product variable
-        let nutrient_amount = f64::from(
-            product
-                .get_nutrient_amount(self.nutrient_to_optimize)
-                .unwrap_or(0.0),
+        let objective_amount = self.objective_amount(product);
+
+        let low_bound = f64::from(product_constraint.raw_low_bound().unwrap_or(0.0));
+        let up_bound = f64::from(
+            product_constraint
+                .raw_up_bound()
+                .unwrap_or_else(|| f32::from(u16::MAX)),
         );
 
         // not int var as int constraint will be given on allowed_units level
+        let product_id = product.id();
         let product_gram_variable = self.problem.add_var(
-            // nutrient amount per 1g of product
-            nutrient_amount * 0.01,
-            (
-                f64::from(product_constraint.low_bound().unwrap_or(0)),
-                f64::from(product_constraint.up_bound().unwrap_or(u16::MAX)),
-            ),
+            format!("{product_id} grams"),
+            // objective amount per 1g of product
+            objective_amount * 0.01,
+            (if selectable { 0.0 } else { low_bound }, up_bound),
         );
 
+        // bounds apply only when the product is picked: used * low <= grams <= used * up
+        let used_var = selectable.then(|| {
+            let used_var = self
+                .problem
+                .add_binary_var(format!("{product_id} used"), 0.0);
+            self.problem.add_constraint(
+                format!("{product_id} max when used"),
+                vec![(product_gram_variable, 1.0), (used_var, -up_bound)],
+                ComparisonOp::Le,
+                0.0,
+            );
+            self.problem.add_constraint(
+                format!("{product_id} min when used"),
+                vec![(product_gram_variable, 1.0), (used_var, -low_bound)],
+                ComparisonOp::Ge,
+                0.0,
+            );
+            used_var
+        });
+
         // add variables for the product units including dividers
-        let unit_var = self.problem.add_integer_var(0.0, (0, i32::from(u16::MAX)));
+        let unit_var = self.problem.add_integer_var(
+            format!("{product_id} units"),
+            0.0,
+            (0, i32::from(u16::MAX)),
+        );
         let unit_data = product
             .allowed_units
             .get(&product_constraint.unit())
             .unwrap();
 
         self.problem.add_constraint(
-            [
+            format!("{product_id} unit amount"),
+            vec![
                 (
                     unit_var,
                     f64::from(unit_data.divider) * f64::from(unit_data.amount),
@@ -268,18 +1471,49 @@ impl ConstraintsSolver {
         );
 
         ProductVariable {
-            name: product.id(),
+            name: product_id,
             product: product.clone(),
             unit: product_constraint.unit(),
             variable_gram: product_gram_variable,
             variable_unit_divided: unit_var,
+            variable_used: used_var,
+        }
+    }
+
+    // objective value of 100 g of the product
+    fn objective_amount(&self, product: &Product) -> f64 {
+        f64::from(match self.objective {
+            Objective::Nutrient(nutrient) => product.get_nutrient_amount(nutrient).unwrap_or(0.0),
+            Objective::Cost => product.price.as_ref().map_or(0.0, |p| p.per_100g),
+        }) * self.objective_factor(&product.id())
+    }
+
+    // multiplier of the objective weight of a product coming from the solver options
+    fn objective_factor(&self, product_id: &str) -> f64 {
+        let mut factor = 1.0;
+        if let Some(seed) = self.options.random_seed {
+            factor += OBJECTIVE_JITTER * (2.0 * seeded_unit(seed, product_id) - 1.0);
+        }
+        if self.avoided_products.contains(product_id) {
+            factor *= match self.min_or_max {
+                MinOrMax::Min => 1.0 + self.options.variety_penalty,
+                MinOrMax::Max => 1.0 - self.options.variety_penalty,
+            };
+        }
+        if self.expiring_products.contains(product_id) {
+            factor *= match self.min_or_max {
+                MinOrMax::Min => 1.0 - self.options.expiry_bonus,
+                MinOrMax::Max => 1.0 + self.options.expiry_bonus,
+            };
         }
+        factor
     }
 
     // no need to keep references to nutrients as they are stored in the problem
     // Information about their values can be calculated based on products and their quantities
     fn add_nutrient_constraints(
         &mut self,
+        scope: &str,
         nutrient_constr: &NutrientConstraint,
         products: &[ProductEntry],
     ) {
@@ -297,28 +1531,139 @@ impl ConstraintsSolver {
                 ) * 0.01,
             ));
         }
+        let nutrient = nutrient_constr.element();
+        let mut min_terms = product_macros.clone();
+        if let Some(slack) =
+            self.add_soft_slack(format!("{scope} min {nutrient} slack"), nutrient_constr)
+        {
+            min_terms.push((slack, 1.0));
+        }
         self.problem.add_constraint(
-            &product_macros,
+            format!("{scope} min {nutrient}"),
+            min_terms,
             ComparisonOp::Ge,
             f64::from(nutrient_constr.min().unwrap_or(0.0)),
         );
 
         if let Some(max_val) = nutrient_constr.max() {
-            self.problem
-                .add_constraint(&product_macros, ComparisonOp::Le, f64::from(max_val));
+            let mut max_terms = product_macros;
+            if let Some(slack) =
+                self.add_soft_slack(format!("{scope} max {nutrient} slack"), nutrient_constr)
+            {
+                max_terms.push((slack, -1.0));
+            }
+            self.problem.add_constraint(
+                format!("{scope} max {nutrient}"),
+                max_terms,
+                ComparisonOp::Le,
+                f64::from(max_val),
+            );
         }
     }
+
+    // kcal of the macro - share * kcal of the day (op) 0, linear in the product grams
+    fn add_macro_percent_constraints(
+        &mut self,
+        macro_percent: &MacroPercentConstraint,
+        products: &[ProductEntry],
+    ) {
+        let element = macro_percent.element();
+        let kcal_per_gram =
+            f64::from(MacroPercentConstraint::kcal_per_gram(element).unwrap_or_default());
+        let product_energy: Vec<_> = products
+            .iter()
+            .flat_map(|entry| entry.get_all_product_variables())
+            .map(|p| {
+                let amount =
+                    |nutrient| f64::from(p.product.get_nutrient_amount(nutrient).unwrap_or(0.0));
+                (
+                    p.variable_gram,
+                    amount(NutrientType::Macro(element)) * kcal_per_gram * 0.01,
+                    amount(NutrientType::Macro(MacroElementsType::Calories)) * 0.01,
+                )
+            })
+            .collect();
+        let bounds = [
+            ("min", macro_percent.min_percent(), ComparisonOp::Ge),
+            ("max", macro_percent.max_percent(), ComparisonOp::Le),
+        ];
+        for (bound, percent, op) in bounds {
+            let Some(percent) = percent else {
+                continue;
+            };
+            let share = f64::from(percent) / 100.0;
+            let terms = product_energy
+                .iter()
+                .map(|&(grams, macro_kcal, kcal)| (grams, macro_kcal - share * kcal))
+                .collect();
+            self.problem.add_constraint(
+                format!("day {bound} {element} percent of calories"),
+                terms,
+                op,
+                0.0,
+            );
+        }
+    }
+
+    // amount by which a soft constraint is broken, made costly in the objective
+    fn add_soft_slack(
+        &mut self,
+        name: String,
+        nutrient_constr: &NutrientConstraint,
+    ) -> Option<Variable> {
+        let penalty = f64::from(nutrient_constr.penalty()?);
+        let objective = match self.min_or_max {
+            MinOrMax::Min => penalty,
+            MinOrMax::Max => -penalty,
+        };
+        Some(self.problem.add_var(name, objective, (0.0, f64::INFINITY)))
+    }
+
+    fn add_cost_constraint(&mut self, max_cost: f32, products: &[ProductEntry]) {
+        let product_costs: Vec<_> = products
+            .iter()
+            .flat_map(|entry| entry.get_all_product_variables())
+            .map(|p| {
+                (
+                    p.variable_gram,
+                    f64::from(p.product.price.as_ref().map_or(0.0, |p| p.per_100g)) * 0.01,
+                )
+            })
+            .collect();
+        self.problem.add_constraint(
+            "day max cost".to_string(),
+            product_costs,
+            ComparisonOp::Le,
+            f64::from(max_cost),
+        );
+    }
+}
+
+// deterministic value in [0, 1) for the seed and key, FNV-1a followed by a splitmix64 finalizer
+fn seeded_unit(seed: u64, key: &str) -> f64 {
+    let mut hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    }) ^ seed;
+    hash = hash.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^= hash >> 31;
+    // the top 53 bits fit the f64 mantissa exactly
+    #[allow(clippy::cast_precision_loss)]
+    let unit = (hash >> 11) as f64 / (1u64 << 53) as f64;
+    unit
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
 
     use crate::data_types::{
-        AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients, MicroNutrientsType,
-        NutrientType, Product, UnitData, constraints::*,
+        Allergen, AllowedUnitsType, CookedVariant, DietFlag, MacroElements, MacroElementsType,
+        MealKind, MealTime, MicroNutrients, MicroNutrientsType, NutrientType, Product, UnitData,
+        constraints::*,
     };
 
     fn build_product(
@@ -362,6 +1707,10 @@ mod tests {
         DayMealPlanConstraint {
             meals,
             nutrients: day_nutrients,
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
         }
     }
 
@@ -388,6 +1737,11 @@ mod tests {
                     .unwrap(),
                 NutrientConstraint::new(MacroElementsType::Fat, Some(0.0), None).unwrap(),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let day_constraint = make_day_constraint(
@@ -420,6 +1774,7 @@ mod tests {
                             SolutionEntry::Meal {
                                 name: meal_name,
                                 entries: meal_entries,
+                                ..
                             } => {
                                 assert_eq!(meal_name, "Breakfast");
                                 assert_eq!(meal_entries.len(), 1);
@@ -467,12 +1822,88 @@ mod tests {
                 NutrientConstraint::new(MacroElementsType::Protein, Some(200.0), Some(220.0))
                     .unwrap(),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
 
         let result = solver.solve_day(&day_constraint);
         assert!(matches!(result, Err(msg) if msg == "Constraints are infeasible"));
+
+        // 60g of powder gives 24g of protein, lowering the minimum is relatively cheaper than
+        // allowing 500g of powder
+        let relaxations = solver.diagnose_infeasibility().unwrap();
+        assert_eq!(relaxations.len(), 1);
+        assert_eq!(relaxations[0].constraint, "Lunch min Protein");
+        assert_relative_eq!(relaxations[0].bound, 200.0);
+        assert_relative_eq!(relaxations[0].suggested_bound, 24.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_diagnosis_ranks_most_binding_constraints_first() {
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        // every meal asks for more protein than its fat limit allows
+        let meal = |name: &str, protein: f32, min_protein: f32, max_fat: f32| MealConstraint {
+            products: vec![
+                ProductConstraint::new(
+                    Box::new(build_product(name, protein, 1, 1, None)),
+                    Some(0),
+                    Some(1000),
+                    AllowedUnitsType::Gram,
+                )
+                .expect("product constraint should be valid"),
+            ],
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(min_protein), None)
+                    .unwrap(),
+                NutrientConstraint::new(MacroElementsType::Fat, None, Some(max_fat)).unwrap(),
+            ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let mut day_constraint =
+            make_day_constraint("Breakfast", meal("Tofu", 10.0, 50.0, 10.0), Vec::new());
+        day_constraint
+            .meals
+            .insert("Dinner".to_string(), meal("Seitan", 40.0, 30.0, 1.0));
+
+        assert!(solver.solve_day(&day_constraint).is_err());
+        let relaxations = solver.diagnose_infeasibility().unwrap();
+        // protein minimums move by 22/30 and 30/50 of their values, fat limits would move more
+        let constraints: Vec<_> = relaxations.iter().map(|r| r.constraint.as_str()).collect();
+        assert_eq!(
+            constraints,
+            vec!["Dinner min Protein", "Breakfast min Protein"]
+        );
+        assert_relative_eq!(relaxations[1].suggested_bound, 20.0, epsilon = 1e-4);
+
+        let mut feasible = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let meal = MealConstraint {
+            products: Vec::new(),
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        feasible
+            .solve_day(&make_day_constraint("Dinner", meal, Vec::new()))
+            .unwrap();
+        assert_eq!(feasible.diagnose_infeasibility(), Ok(Vec::new()));
     }
 
     #[test]
@@ -481,7 +1912,9 @@ mod tests {
             MinOrMax::Max,
             NutrientType::Macro(MacroElementsType::Protein),
         );
-        solver.problem.add_var(1.0, (0.0, f64::INFINITY));
+        solver
+            .problem
+            .add_var("unbounded".to_string(), 1.0, (0.0, f64::INFINITY));
 
         let product = build_product("Unbounded", 10.0, 1, 1, None);
         let product_constraint =
@@ -491,6 +1924,11 @@ mod tests {
         let meal = MealConstraint {
             products: vec![product_constraint],
             nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let day_constraint = make_day_constraint("Dinner", meal, Vec::new());
@@ -529,6 +1967,11 @@ mod tests {
                 NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), Some(80.0))
                     .unwrap(),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let dinner = MealConstraint {
@@ -553,6 +1996,11 @@ mod tests {
                     .unwrap(),
                 NutrientConstraint::new(MicroNutrientsType::Fiber, Some(10.0), Some(20.0)).unwrap(),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let mut meals = HashMap::new();
@@ -566,6 +2014,10 @@ mod tests {
                     .unwrap(),
                 NutrientConstraint::new(MicroNutrientsType::Fiber, Some(25.0), Some(35.0)).unwrap(),
             ],
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
         };
 
         let solution = solver
@@ -592,7 +2044,7 @@ mod tests {
         let mut meals_map: HashMap<_, _> = HashMap::new();
         for meal_entry in day_entries {
             match meal_entry {
-                SolutionEntry::Meal { name, entries } => {
+                SolutionEntry::Meal { name, entries, .. } => {
                     meals_map.insert(name, entries);
                 }
                 _ => panic!("Expected meal entry"),
@@ -676,4 +2128,1211 @@ mod tests {
         assert!(total_protein >= 65.0);
         assert!(total_protein <= 90.0);
     }
+
+    fn priced(product: Product, per_100g: f32, currency: &str) -> Product {
+        product.with_price(Some(crate::data_types::Price {
+            per_100g,
+            currency: currency.to_string(),
+        }))
+    }
+
+    fn gram_constraint(product: &Product) -> ProductConstraint {
+        ProductConstraint::new(
+            Box::new(product.clone()),
+            Some(0),
+            Some(500),
+            AllowedUnitsType::Gram,
+        )
+        .expect("product constraint should be valid")
+    }
+
+    fn product_grams(solution: &Solution) -> HashMap<String, f64> {
+        let SolutionEntry::Week { entries } = &solution.solution else {
+            panic!("Expected week entry")
+        };
+        let mut grams = HashMap::new();
+        for day in entries {
+            let SolutionEntry::Day { entries, .. } = day else {
+                panic!("Expected day entry")
+            };
+            for meal in entries {
+                let SolutionEntry::Meal { entries, .. } = meal else {
+                    panic!("Expected meal entry")
+                };
+                for product in entries {
+                    if let SolutionEntry::Product {
+                        product,
+                        amount_grams,
+                        ..
+                    } = product
+                    {
+                        grams.insert(product.name().to_string(), *amount_grams);
+                    }
+                }
+            }
+        }
+        grams
+    }
+
+    #[test]
+    fn test_solver_minimizes_cost() {
+        let cheap = priced(build_product("Cheap", 10.0, 1, 1, None), 0.5, "PLN");
+        let pricey = priced(build_product("Pricey", 40.0, 1, 1, None), 4.0, "PLN");
+
+        let meal = MealConstraint {
+            products: vec![gram_constraint(&cheap), gram_constraint(&pricey)],
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap(),
+            ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, Objective::Cost);
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        // cheap: 0.05 per 1g protein, pricey: 0.1 per 1g protein
+        let grams = product_grams(&solution);
+        assert_relative_eq!(grams["Cheap"], 300.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Pricey"], 0.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_respects_max_cost() {
+        let cheap = priced(build_product("Cheap", 10.0, 1, 1, None), 0.5, "PLN");
+        let pricey = priced(build_product("Pricey", 40.0, 1, 1, None), 4.0, "PLN");
+
+        let meal = MealConstraint {
+            products: vec![gram_constraint(&cheap), gram_constraint(&pricey)],
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let mut day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+        day_constraint.max_cost = Some(4.5);
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        let grams = product_grams(&solution);
+        let cost = grams["Cheap"] * 0.005 + grams["Pricey"] * 0.04;
+        assert!(cost <= 4.5 + 1e-6);
+        // protein per cost unit is better for the cheap product, so it is used up first
+        assert_relative_eq!(grams["Cheap"], 500.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Pricey"], 50.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_rejects_cost_without_prices() {
+        let priced_product = priced(build_product("Priced", 10.0, 1, 1, None), 1.0, "PLN");
+        let unpriced = build_product("Unpriced", 10.0, 1, 1, None);
+        let meal = MealConstraint {
+            products: vec![gram_constraint(&priced_product), gram_constraint(&unpriced)],
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, Objective::Cost);
+        let result = solver.solve_day(&day_constraint);
+        assert!(matches!(result, Err(msg) if msg == "Product 'Unpriced' has no price"));
+    }
+
+    #[test]
+    fn test_solver_rejects_mixed_currencies() {
+        let pln = priced(build_product("Pln", 10.0, 1, 1, None), 1.0, "PLN");
+        let eur = priced(build_product("Eur", 10.0, 1, 1, None), 1.0, "EUR");
+        let meal = MealConstraint {
+            products: vec![gram_constraint(&pln), gram_constraint(&eur)],
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let mut day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+        day_constraint.max_cost = Some(10.0);
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let result = solver.solve_day(&day_constraint);
+        assert!(matches!(result, Err(msg) if msg.starts_with("Products are priced in different")));
+    }
+
+    #[test]
+    fn test_solver_picks_at_most_max_products() {
+        let candidates: Vec<Product> = [5.0, 30.0, 12.0, 25.0, 8.0]
+            .iter()
+            .enumerate()
+            .map(|(i, protein)| build_product(&format!("P{i}"), *protein, 1, 1, None))
+            .collect();
+        let meal = MealConstraint {
+            products: candidates
+                .iter()
+                .map(|p| {
+                    ProductConstraint::new(
+                        Box::new(p.clone()),
+                        Some(20),
+                        Some(100),
+                        AllowedUnitsType::Gram,
+                    )
+                    .expect("product constraint should be valid")
+                })
+                .collect(),
+            nutrients: Vec::new(),
+            max_products: Some(2),
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        let grams = product_grams(&solution);
+        assert_eq!(grams.len(), 2);
+        assert_relative_eq!(grams["P1"], 100.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["P3"], 100.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_low_bound_applies_only_to_picked_products() {
+        let lean = build_product("Lean", 30.0, 1, 1, None);
+        let fatty = build_product("Fatty", 2.0, 1, 1, None);
+        let meal = MealConstraint {
+            products: vec![
+                ProductConstraint::new(Box::new(lean), Some(50), Some(300), AllowedUnitsType::Gram)
+                    .expect("product constraint should be valid"),
+                ProductConstraint::new(
+                    Box::new(fatty),
+                    Some(50),
+                    Some(300),
+                    AllowedUnitsType::Gram,
+                )
+                .expect("product constraint should be valid"),
+            ],
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap(),
+            ],
+            max_products: Some(1),
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day_constraint = make_day_constraint("Dinner", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Calories),
+        );
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        // without selection "Fatty" would be forced to at least 50g
+        let grams = product_grams(&solution);
+        assert_eq!(grams.len(), 1);
+        assert_relative_eq!(grams["Lean"], 100.0, epsilon = 1e-4);
+    }
+
+    fn selectable_meal(proteins: &[f32], min: Option<u16>, max: Option<u16>) -> MealConstraint {
+        MealConstraint {
+            products: proteins
+                .iter()
+                .enumerate()
+                .map(|(i, protein)| {
+                    ProductConstraint::new(
+                        Box::new(build_product(&format!("P{i}"), *protein, 1, 1, None)),
+                        Some(20),
+                        Some(100),
+                        AllowedUnitsType::Gram,
+                    )
+                    .expect("product constraint should be valid")
+                })
+                .collect(),
+            nutrients: Vec::new(),
+            max_products: max,
+            min_products: min,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_solver_picks_at_least_min_products() {
+        let meal = selectable_meal(&[5.0, 30.0, 12.0, 25.0], Some(3), None);
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        let grams = product_grams(&solution);
+        assert_eq!(grams.len(), 3);
+        assert_relative_eq!(grams["P0"], 20.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["P2"], 20.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["P3"], 20.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_rejects_min_products_above_max() {
+        let meal = selectable_meal(&[5.0, 30.0, 12.0], Some(3), Some(2));
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        assert_eq!(
+            solver.solve_day(&day_constraint).err(),
+            Some("Meal 'Lunch' needs at least 3 products but allows at most 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solver_keeps_negligible_products_out_of_nutrients() {
+        let chicken = build_product("Chicken", 30.0, 1, 1, None);
+        let pepper = build_product("Pepper", 10.0, 1, 1, None).with_negligible(true);
+        let pepper_constraint = ProductConstraint::new(
+            Box::new(pepper.clone()),
+            Some(5),
+            Some(10),
+            AllowedUnitsType::Gram,
+        )
+        .expect("product constraint should be valid");
+
+        let meal = MealConstraint {
+            products: vec![gram_constraint(&chicken), pepper_constraint],
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap(),
+            ],
+            max_products: Some(1),
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        // pepper has no price, which is fine as it is not part of the cost
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        let grams = product_grams(&solution);
+        assert_relative_eq!(grams["Chicken"], 100.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Pepper"], 5.0, epsilon = 1e-4);
+        assert_relative_eq!(
+            solution.nutrient_total(NutrientType::Macro(MacroElementsType::Protein)),
+            30.0,
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_solver_leaves_out_restricted_products() {
+        let tofu = priced(build_product("Tofu", 15.0, 1, 1, None), 1.0, "PLN")
+            .with_allergens(BTreeSet::from([Allergen::Soy]))
+            .with_diet_flags(BTreeSet::from([DietFlag::Vegan, DietFlag::Vegetarian]));
+        let peanuts = build_product("Peanuts", 25.0, 1, 1, None)
+            .with_allergens(BTreeSet::from([Allergen::Peanuts]))
+            .with_diet_flags(BTreeSet::from([DietFlag::Vegan]));
+        let chicken = build_product("Chicken", 30.0, 1, 1, None);
+
+        let meal = MealConstraint {
+            products: vec![
+                gram_constraint(&tofu),
+                gram_constraint(&peanuts),
+                gram_constraint(&chicken),
+            ],
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap(),
+            ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let mut day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, Objective::Cost);
+        assert_eq!(
+            solver.solve_day(&day_constraint).err(),
+            Some("Product 'Peanuts' has no price".to_string())
+        );
+
+        // restricted products are not priced, but they are left out before prices are checked
+        day_constraint.restrictions = DietaryRestrictions {
+            excluded_allergens: BTreeSet::from([Allergen::Peanuts]),
+            required_diets: BTreeSet::from([DietFlag::Vegan]),
+        };
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, Objective::Cost);
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        let grams = product_grams(&solution);
+        assert_eq!(grams.len(), 1);
+        assert_relative_eq!(grams["Tofu"], 200.0, epsilon = 1e-4);
+    }
+
+    fn ratio_meal(products: &[&Product], ratios: Vec<ProductRatioConstraint>) -> MealConstraint {
+        MealConstraint {
+            products: products.iter().map(|p| gram_constraint(p)).collect(),
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios,
+            slot: None,
+            recipes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_solver_plans_whole_recipe_servings() {
+        use crate::data_types::{ProductBuilder, Recipe, RecipeIngredient};
+
+        let ingredient =
+            |name: &str, fat: f32, carbs: f32, protein: f32, grams: f32| RecipeIngredient {
+                product: ProductBuilder::new(name)
+                    .fat(fat)
+                    .carbs(carbs)
+                    .protein(protein)
+                    .build()
+                    .unwrap(),
+                grams,
+            };
+        // about 300 kcal per serving
+        let porridge = Recipe::new(
+            "Porridge",
+            2,
+            vec![
+                ingredient("Oats", 7.0, 60.0, 13.0, 100.0),
+                ingredient("Milk", 3.2, 4.8, 3.4, 400.0),
+            ],
+        )
+        .unwrap();
+        let mut meal = ratio_meal(&[], Vec::new());
+        meal.recipes
+            .push(RecipeConstraint::new(porridge, None, Some(5)).unwrap());
+        meal.nutrients
+            .push(NutrientConstraint::new(MacroElementsType::Calories, None, Some(700.0)).unwrap());
+        let day = make_day_constraint("Breakfast", meal, Vec::new());
+
+        let solution = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .solve_day(&day)
+        .unwrap();
+        let grams = product_grams(&solution);
+        assert_relative_eq!(grams["Oats"], 100.0, epsilon = 1e-6);
+        assert_relative_eq!(grams["Milk"], 400.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_solver_caps_product_total_over_meals() {
+        let coffee = build_product("Coffee", 1.0, 1, 1, None);
+        let milk = build_product("Milk", 3.0, 1, 1, None);
+        let mut day = make_day_constraint(
+            "Breakfast",
+            ratio_meal(&[&coffee, &milk], Vec::new()),
+            Vec::new(),
+        );
+        day.meals
+            .insert("Lunch".to_string(), ratio_meal(&[&coffee], Vec::new()));
+        day.products.push(
+            ProductConstraint::new_fractional(
+                Box::new(coffee.clone()),
+                None,
+                Some(300.0),
+                AllowedUnitsType::Gram,
+            )
+            .unwrap(),
+        );
+
+        let solution = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .solve_day(&day)
+        .unwrap();
+        assert_relative_eq!(grams_per_day(&solution, "Coffee")[0], 300.0, epsilon = 1e-6);
+        assert_relative_eq!(product_grams(&solution)["Milk"], 500.0, epsilon = 1e-6);
+
+        day.products[0] = gram_constraint(&build_product("Tea", 1.0, 1, 1, None));
+        assert!(
+            ConstraintsSolver::new(
+                MinOrMax::Max,
+                NutrientType::Macro(MacroElementsType::Protein)
+            )
+            .solve_day(&day)
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_solver_keeps_macro_percent_of_calories() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let chicken = build_product("Chicken", 30.0, 1, 1, None);
+        let mut day = make_day_constraint(
+            "Lunch",
+            ratio_meal(&[&rice, &chicken], Vec::new()),
+            vec![NutrientConstraint::new(MacroElementsType::Calories, None, Some(1000.0)).unwrap()],
+        );
+        day.macro_percents.push(
+            MacroPercentConstraint::new(MacroElementsType::Protein, None, Some(30.0)).unwrap(),
+        );
+
+        let solution = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .solve_day(&day)
+        .unwrap();
+        let protein_kcal = 4.0 * solution.nutrient_total(MacroElementsType::Protein.into());
+        let kcal = solution.nutrient_total(MacroElementsType::Calories.into());
+        // all chicken would be 58% protein, so rice has to dilute it
+        assert_relative_eq!(protein_kcal / kcal, 0.3, epsilon = 1e-3);
+        assert!(product_grams(&solution)["Rice"] > 0.0);
+    }
+
+    #[test]
+    fn test_solver_keeps_product_ratio() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let chicken = build_product("Chicken", 30.0, 1, 1, None);
+        let mut meal = ratio_meal(
+            &[&rice, &chicken],
+            vec![
+                ProductRatioConstraint::new(
+                    rice.id(),
+                    RatioBase::Product(chicken.id()),
+                    RatioRelation::Equal,
+                    2.0,
+                )
+                .unwrap(),
+            ],
+        );
+        meal.nutrients
+            .push(NutrientConstraint::new(MacroElementsType::Protein, Some(44.0), None).unwrap());
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver =
+            ConstraintsSolver::new(MinOrMax::Min, NutrientType::Macro(MacroElementsType::Carbs));
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        // 0.07 * 2c + 0.3 * c = 44
+        let grams = product_grams(&solution);
+        assert_relative_eq!(grams["Chicken"], 100.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Rice"], 200.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_limits_product_share_of_meal() {
+        let oil = build_product("Oil", 50.0, 1, 1, None);
+        let salad = build_product("Salad", 1.0, 1, 1, None);
+        let meal = ratio_meal(
+            &[&oil, &salad],
+            vec![
+                ProductRatioConstraint::new(
+                    oil.id(),
+                    RatioBase::MealWeight,
+                    RatioRelation::AtMost,
+                    0.1,
+                )
+                .unwrap(),
+            ],
+        );
+        // 10g carbs per 100g of each product caps the meal at 200g
+        let day_constraint = make_day_constraint(
+            "Lunch",
+            meal,
+            vec![NutrientConstraint::new(MacroElementsType::Carbs, None, Some(20.0)).unwrap()],
+        );
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let diagnostics = solver
+            .solve_day_with_diagnostics(&day_constraint)
+            .expect("solution should exist");
+
+        let grams = product_grams(&diagnostics.solution);
+        assert_relative_eq!(grams["Oil"], 20.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Salad"], 180.0, epsilon = 1e-4);
+        assert!(
+            diagnostics
+                .binding_constraints
+                .contains(&"Lunch ratio Oil to meal".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solver_rejects_ratio_to_product_outside_meal() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let meal = ratio_meal(
+            &[&rice],
+            vec![
+                ProductRatioConstraint::new(
+                    rice.id(),
+                    RatioBase::Product("Chicken".into()),
+                    RatioRelation::AtLeast,
+                    1.0,
+                )
+                .unwrap(),
+            ],
+        );
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        assert_eq!(
+            solver.solve_day(&day_constraint).err(),
+            Some("Ratio in meal 'Lunch' refers to 'Chicken' which is not in the meal".to_string())
+        );
+    }
+
+    fn rice_week(days: usize, leftovers: Vec<LeftoverConstraint>) -> WeekMealPlanConstraint {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        WeekMealPlanConstraint {
+            days: (0..days)
+                .map(|i| {
+                    let meal = ratio_meal(&[&rice], Vec::new());
+                    (
+                        format!("Day{}", i + 1),
+                        make_day_constraint("Lunch", meal, Vec::new()),
+                    )
+                })
+                .collect(),
+            leftovers,
+            repetitions: Vec::new(),
+        }
+    }
+
+    fn grams_per_day(solution: &Solution, product_name: &str) -> Vec<f64> {
+        let SolutionEntry::Week { entries } = &solution.solution else {
+            panic!("Expected week entry")
+        };
+        entries
+            .iter()
+            .map(|day| {
+                let SolutionEntry::Day { entries, .. } = day else {
+                    panic!("Expected day entry")
+                };
+                entries
+                    .iter()
+                    .flat_map(|meal| match meal {
+                        SolutionEntry::Meal { entries, .. } => entries.iter(),
+                        _ => panic!("Expected meal entry"),
+                    })
+                    .filter_map(|entry| match entry {
+                        SolutionEntry::Product {
+                            product,
+                            amount_grams,
+                            ..
+                        } if product.name() == product_name => Some(*amount_grams),
+                        _ => None,
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_solver_splits_leftover_batch_across_days() {
+        let leftover = LeftoverConstraint::new("Rice".to_string(), 0, 2, None, Some(600)).unwrap();
+        let week = rice_week(4, vec![leftover]);
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver.solve_week(&week).expect("solution should exist");
+
+        // without the batch every day could take 500g, the 4th day is not covered by it
+        let grams = grams_per_day(&solution, "Rice");
+        assert_eq!(grams.len(), 4);
+        assert_relative_eq!(grams[..3].iter().sum::<f64>(), 600.0, epsilon = 1e-4);
+        assert_relative_eq!(grams[3], 500.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_eats_whole_leftover_batch() {
+        let leftover = LeftoverConstraint::new("Rice".to_string(), 1, 1, Some(450), None).unwrap();
+        let week = rice_week(3, vec![leftover]);
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Calories),
+        );
+        let solution = solver.solve_week(&week).expect("solution should exist");
+
+        let grams = grams_per_day(&solution, "Rice");
+        assert_relative_eq!(grams[0], 0.0, epsilon = 1e-4);
+        assert_relative_eq!(grams[1] + grams[2], 450.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_rejects_invalid_leftovers() {
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Calories),
+        );
+        let outside = LeftoverConstraint::new("Rice".to_string(), 3, 1, None, None).unwrap();
+        assert_eq!(
+            solver.solve_week(&rice_week(3, vec![outside])).err(),
+            Some("Leftovers of 'Rice' are cooked on day 4 but the plan has 3 days".to_string())
+        );
+
+        let not_cooked = LeftoverConstraint::new("Pasta".to_string(), 0, 1, None, None).unwrap();
+        assert_eq!(
+            solver.solve_week(&rice_week(3, vec![not_cooked])).err(),
+            Some("Leftovers of 'Pasta' are cooked on 'Day1' which doesn't use it".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solver_limits_product_repetition() {
+        let mut week = rice_week(4, Vec::new());
+        week.repetitions = vec![RepetitionConstraint::MaxTimesPerWeek {
+            product_id: "Rice".into(),
+            max_times: 2,
+        }];
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver.solve_week(&week).expect("solution should exist");
+        let eaten_days: Vec<usize> = grams_per_day(&solution, "Rice")
+            .iter()
+            .enumerate()
+            .filter(|(_, grams)| **grams > 1e-4)
+            .map(|(day, _)| day)
+            .collect();
+        assert_eq!(eaten_days.len(), 2);
+
+        week.repetitions = vec![RepetitionConstraint::NoConsecutiveDays {
+            meal: "Lunch".to_string(),
+            product_ids: vec!["Rice".into()],
+        }];
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver.solve_week(&week).expect("solution should exist");
+        let eaten_days: Vec<usize> = grams_per_day(&solution, "Rice")
+            .iter()
+            .enumerate()
+            .filter(|(_, grams)| **grams > 1e-4)
+            .map(|(day, _)| day)
+            .collect();
+        assert_eq!(eaten_days.len(), 2);
+        assert!(eaten_days.windows(2).all(|days| days[1] - days[0] > 1));
+
+        week.repetitions = vec![RepetitionConstraint::NoConsecutiveDays {
+            meal: "Dinner".to_string(),
+            product_ids: vec!["Rice".into()],
+        }];
+        assert_eq!(
+            solver.solve_week(&week).err(),
+            Some("No day of the plan has meal 'Dinner'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solver_plans_cooked_grams_as_raw_amounts() {
+        let rice = build_product("Rice", 7.0, 1, 1, None)
+            .with_cooked(Some(CookedVariant::new("Rice (cooked)".into(), 2.5)));
+        let constraint =
+            ProductConstraint::new(Box::new(rice), None, Some(200), AllowedUnitsType::Gram)
+                .and_then(ProductConstraint::in_cooked_grams)
+                .expect("product constraint should be valid");
+        let meal = MealConstraint {
+            products: vec![constraint],
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver
+            .solve_day(&make_day_constraint("Lunch", meal, Vec::new()))
+            .expect("solution should exist");
+        // 200g cooked is 80g of raw rice to buy
+        assert_relative_eq!(grams_per_day(&solution, "Rice")[0], 80.0, epsilon = 1e-4);
+
+        let plain = build_product("Oats", 7.0, 1, 1, None);
+        assert!(
+            ProductConstraint::new(Box::new(plain), None, Some(200), AllowedUnitsType::Gram)
+                .and_then(ProductConstraint::in_cooked_grams)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_solver_orders_meals_by_slot_and_keeps_them_when_saved() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let mut meals = HashMap::new();
+        for (name, slot) in [
+            ("Supper", Some(MealSlot::new(MealKind::Dinner, None))),
+            ("Leftovers", None),
+            (
+                "Brunch",
+                Some(MealSlot::new(
+                    MealKind::Custom("Brunch".to_string()),
+                    Some(MealTime::new(10, 30).unwrap()),
+                )),
+            ),
+            ("Morning", Some(MealSlot::new(MealKind::Breakfast, None))),
+        ] {
+            let mut meal = ratio_meal(&[&rice], Vec::new());
+            meal.slot = slot;
+            meals.insert(name.to_string(), meal);
+        }
+        let day_constraint = DayMealPlanConstraint {
+            meals,
+            nutrients: Vec::new(),
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        };
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Calories),
+        );
+        let solution = solver
+            .solve_day(&day_constraint)
+            .expect("solution should exist");
+
+        let saved = serde_json::to_string(&solution).expect("solution should serialize");
+        let loaded: Solution = serde_json::from_str(&saved).expect("solution should load");
+        let SolutionEntry::Week { entries } = &loaded.solution else {
+            panic!("Expected week entry")
+        };
+        let SolutionEntry::Day { entries, .. } = &entries[0] else {
+            panic!("Expected day entry")
+        };
+        let meals: Vec<(&str, Option<&MealKind>)> = entries
+            .iter()
+            .map(|meal| match meal {
+                SolutionEntry::Meal { name, slot, .. } => {
+                    (name.as_str(), slot.as_ref().map(|slot| &slot.kind))
+                }
+                _ => panic!("Expected meal entry"),
+            })
+            .collect();
+        assert_eq!(
+            meals,
+            vec![
+                ("Morning", Some(&MealKind::Breakfast)),
+                ("Brunch", Some(&MealKind::Custom("Brunch".to_string()))),
+                ("Supper", Some(&MealKind::Dinner)),
+                ("Leftovers", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solver_resolve_with_anchor_limits_changes() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let chicken = build_product("Chicken", 30.0, 1, 1, None);
+        let mut meal = ratio_meal(&[&rice, &chicken], Vec::new());
+        meal.nutrients
+            .push(NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap());
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let previous = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Calories),
+        )
+        .solve_day(&day_constraint)
+        .expect("solution should exist");
+        assert_relative_eq!(product_grams(&previous)["Chicken"], 100.0, epsilon = 1e-4);
+
+        // unanchored both products would go up to 500g
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        let solution = solver
+            .resolve_with_anchor(&day_constraint, &previous, 10.0)
+            .expect("solution should exist");
+
+        let grams = product_grams(&solution);
+        assert_relative_eq!(grams["Chicken"], 110.0, epsilon = 1e-4);
+        // 10% of nothing is still allowed to move by one unit
+        assert_relative_eq!(grams["Rice"], 1.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_resolve_with_anchor_rejects_invalid_deviation() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let day_constraint =
+            make_day_constraint("Lunch", ratio_meal(&[&rice], Vec::new()), Vec::new());
+        let previous = Solution {
+            solution: SolutionEntry::Week {
+                entries: Vec::new(),
+            },
+        };
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+        assert_eq!(
+            solver
+                .resolve_with_anchor(&day_constraint, &previous, -5.0)
+                .err(),
+            Some("Invalid maximal deviation -5%".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solution_day_and_meal_totals() {
+        let product_entry = |product: Product, amount_grams: f64| SolutionEntry::Product {
+            product,
+            amount_grams,
+            unit: AllowedUnitsType::Gram,
+            amount_unit: Fraction {
+                numerator: 1,
+                denominator: 1,
+            },
+        };
+        let day = |name: &str, grams: f64| SolutionEntry::Day {
+            name: name.to_string(),
+            entries: vec![
+                SolutionEntry::Meal {
+                    name: "Breakfast".to_string(),
+                    slot: None,
+                    entries: vec![product_entry(
+                        build_product("Oats", 10.0, 1, 1, Some(4.0)),
+                        grams,
+                    )],
+                },
+                SolutionEntry::Meal {
+                    name: "Lunch".to_string(),
+                    slot: None,
+                    entries: vec![
+                        product_entry(build_product("Rice", 20.0, 1, 1, None), 100.0),
+                        product_entry(build_product("Chicken", 30.0, 1, 1, None), 50.0),
+                    ],
+                },
+            ],
+        };
+        let mut solution = Solution {
+            solution: SolutionEntry::Week {
+                entries: vec![day("Monday", 200.0), day("Tuesday", 100.0)],
+            },
+        };
+        let protein = NutrientType::Macro(MacroElementsType::Protein);
+        let fiber = NutrientType::Micro(MicroNutrientsType::Fiber);
+
+        let days = solution.day_totals();
+        let names: Vec<&str> = days.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Monday", "Tuesday"]);
+        assert_relative_eq!(days[0].1.get(protein), 55.0, epsilon = 1e-6);
+        assert_relative_eq!(days[1].1.get(protein), 45.0, epsilon = 1e-6);
+        assert_relative_eq!(days[0].1.get(fiber), 8.0, epsilon = 1e-6);
+
+        let meals = solution.meal_totals();
+        assert_eq!(meals.len(), 4);
+        assert_eq!(
+            (meals[1].0.as_str(), meals[1].1.as_str()),
+            ("Monday", "Lunch")
+        );
+        assert_relative_eq!(meals[1].2.get(protein), 35.0, epsilon = 1e-6);
+        assert_relative_eq!(meals[1].2.get(fiber), 0.0);
+
+        let week = solution.solution.nutrient_totals();
+        assert_relative_eq!(week.get(protein), solution.nutrient_total(protein));
+        assert_eq!(
+            week.iter().count(),
+            MacroElementsType::iter().count() + MicroNutrientsType::iter().count()
+        );
+
+        let monday = solution.days()[0].nutrient_breakdown(protein);
+        let ids: Vec<&str> = monday.iter().map(|c| c.product_id.as_str()).collect();
+        assert_eq!(ids, vec!["Oats", "Rice", "Chicken"]);
+        assert_relative_eq!(monday[2].amount, 15.0, epsilon = 1e-6);
+        let breakdown = solution.nutrient_breakdown(protein);
+        let ids: Vec<&str> = breakdown.iter().map(|c| c.product_id.as_str()).collect();
+        assert_eq!(ids, vec!["Rice", "Chicken", "Oats"]);
+        assert_relative_eq!(breakdown[0].amount, 40.0, epsilon = 1e-6);
+        assert_relative_eq!(breakdown[0].percent, 40.0, epsilon = 1e-6);
+        assert_relative_eq!(
+            breakdown.iter().map(|c| c.percent).sum::<f64>(),
+            100.0,
+            epsilon = 1e-6
+        );
+        let fiber_sources = solution.nutrient_breakdown(fiber);
+        assert_eq!(fiber_sources.len(), 1);
+        assert_relative_eq!(fiber_sources[0].percent, 100.0, epsilon = 1e-6);
+
+        solution.reorder_meal("Tuesday", "Lunch", 0).unwrap();
+        assert_eq!(solution.day_names(), vec!["Monday", "Tuesday"]);
+        assert_eq!(solution.meal_names("Tuesday"), vec!["Lunch", "Breakfast"]);
+        assert_eq!(solution.meal_names("Monday"), vec!["Breakfast", "Lunch"]);
+        assert!(solution.reorder_meal("Tuesday", "Lunch", 2).is_err());
+        assert!(solution.reorder_meal("Tuesday", "Dinner", 0).is_err());
+        assert!(solution.reorder_meal("Sunday", "Lunch", 0).is_err());
+    }
+
+    #[test]
+    fn test_constraints_and_solution_serde_round_trip() {
+        let rice = build_product("Rice", 7.0, 1, 1, None);
+        let chicken = build_product("Chicken", 30.0, 1, 1, None);
+        let mut meal = ratio_meal(&[&rice, &chicken], Vec::new());
+        meal.nutrients
+            .push(NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap());
+        let day_constraint = make_day_constraint("Lunch", meal, Vec::new());
+
+        let json = serde_json::to_string(&day_constraint).unwrap();
+        let restored: DayMealPlanConstraint = serde_json::from_str(&json).unwrap();
+        let solve = |constraint: &DayMealPlanConstraint| {
+            ConstraintsSolver::new(MinOrMax::Min, NutrientType::Macro(MacroElementsType::Carbs))
+                .solve_day(constraint)
+                .expect("solution should exist")
+        };
+        let solution = solve(&day_constraint);
+        let solution_json = serde_json::to_value(&solution).unwrap();
+        assert_eq!(
+            serde_json::to_value(solve(&restored)).unwrap(),
+            solution_json
+        );
+
+        let restored_solution: Solution = serde_json::from_value(solution_json.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&restored_solution).unwrap(),
+            solution_json
+        );
+        assert_relative_eq!(
+            product_grams(&restored_solution)["Chicken"],
+            100.0,
+            epsilon = 1e-4
+        );
+    }
+
+    // two products with the same protein, capped by the fat both contain
+    fn tied_day(rice_protein: f32) -> DayMealPlanConstraint {
+        let rice = build_product("Rice", rice_protein, 1, 1, None);
+        let pasta = build_product("Pasta", 10.0, 1, 1, None);
+        make_day_constraint(
+            "Lunch",
+            ratio_meal(&[&rice, &pasta], Vec::new()),
+            vec![NutrientConstraint::new(MacroElementsType::Fat, None, Some(25.0)).unwrap()],
+        )
+    }
+
+    fn solve_with(options: SolverOptions, day: &DayMealPlanConstraint) -> HashMap<String, f64> {
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .with_options(options);
+        product_grams(&solver.solve_day(day).expect("solution should exist"))
+    }
+
+    #[test]
+    fn test_solver_random_seed_varies_tied_plans() {
+        let day = tied_day(10.0);
+        let winner = |seed| {
+            let grams = solve_with(
+                SolverOptions {
+                    random_seed: Some(seed),
+                    variety_penalty: 0.0,
+                    rounding: None,
+                    expiry_bonus: 0.0,
+                },
+                &day,
+            );
+            if grams["Rice"] > grams["Pasta"] {
+                "Rice"
+            } else {
+                "Pasta"
+            }
+        };
+        let winners: Vec<&str> = (0..16).map(winner).collect();
+        assert!(winners.contains(&"Rice"));
+        assert!(winners.contains(&"Pasta"));
+        // the same seed gives the same plan
+        assert_eq!((0..16).map(winner).collect::<Vec<_>>(), winners);
+    }
+
+    #[test]
+    fn test_solver_variety_penalty_avoids_previous_products() {
+        let day = tied_day(10.5);
+        let options = SolverOptions {
+            random_seed: None,
+            variety_penalty: 0.1,
+            rounding: None,
+            expiry_bonus: 0.0,
+        };
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .with_options(options);
+        let previous = solver.solve_day(&day).expect("solution should exist");
+        let previous_grams = product_grams(&previous);
+        assert_relative_eq!(previous_grams["Rice"], 500.0, epsilon = 1e-4);
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .with_options(options);
+        solver.avoid_solution(&previous);
+        let grams = product_grams(&solver.solve_day(&day).expect("solution should exist"));
+        assert_relative_eq!(grams["Pasta"], 500.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Rice"], 0.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_solver_rounds_amounts_and_reports_violations() {
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .with_options(SolverOptions {
+            rounding: Some(RoundingPolicy {
+                gram_step: Some(30.0),
+                unit_parts: None,
+            }),
+            ..SolverOptions::default()
+        });
+        let diagnostics = solver
+            .solve_day_with_diagnostics(&tied_day(10.5))
+            .expect("solution should exist");
+        // 500g of rice becomes 510g, above the fat limit and its own bound
+        assert_relative_eq!(product_grams(&diagnostics.solution)["Rice"], 510.0);
+        let violations = &diagnostics.rounding_violations;
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].meal, None);
+        assert_relative_eq!(violations[0].amount, 25.5, epsilon = 1e-4);
+        assert_eq!(violations[1].meal.as_deref(), Some("Lunch"));
+        assert_eq!(violations[1].max, Some(500.0));
+    }
+
+    #[test]
+    fn test_solver_rejects_invalid_variety_penalty() {
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .with_options(SolverOptions {
+            random_seed: None,
+            variety_penalty: 1.5,
+            rounding: None,
+            expiry_bonus: 0.0,
+        });
+        assert_eq!(
+            solver.solve_day(&tied_day(10.0)).err(),
+            Some("Invalid variety penalty 1.5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solver_soft_nutrient_constraint_is_penalized_not_infeasible() {
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Protein),
+        );
+
+        let product = build_product("ProteinPowder", 40.0, 1, 1, None);
+        let product_constraint =
+            ProductConstraint::new(Box::new(product), Some(0), Some(60), AllowedUnitsType::Gram)
+                .expect("product constraint should be valid");
+
+        let meal = MealConstraint {
+            products: vec![product_constraint],
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(200.0), Some(220.0))
+                    .and_then(|c| c.soft(10.0))
+                    .unwrap(),
+            ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        };
+
+        // the hard version is infeasible, the soft one takes all the powder it can
+        let diagnostics = solver
+            .solve_day_with_diagnostics(&make_day_constraint("Lunch", meal, Vec::new()))
+            .unwrap();
+        let grams: f64 = diagnostics.solution.grams_by_meal().values().sum();
+        assert!((grams - 60.0).abs() < 1e-6, "{grams}");
+        assert!(solver.lp_export().contains("Lunch_min_Protein_slack"));
+    }
+
+    #[test]
+    fn test_solver_expiry_bonus_uses_up_expiring_products() {
+        let day = tied_day(10.5);
+        let mut pantry = Pantry::new();
+        pantry.add_with_expiry("Pasta", 500.0, 101);
+        pantry.add_with_expiry("Rice", 500.0, 130);
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Max,
+            NutrientType::Macro(MacroElementsType::Protein),
+        )
+        .with_options(SolverOptions {
+            expiry_bonus: 0.1,
+            ..SolverOptions::default()
+        });
+        solver.prefer_expiring(&pantry, 100, 3);
+        let grams = product_grams(&solver.solve_day(&day).expect("solution should exist"));
+        assert_relative_eq!(grams["Pasta"], 500.0, epsilon = 1e-4);
+        assert_relative_eq!(grams["Rice"], 0.0, epsilon = 1e-4);
+    }
 }
@@ -0,0 +1,491 @@
+use std::fmt::Write;
+
+use super::constraints_solver::{Fraction, Solution, SolutionEntry};
+use super::nutrient_format::format_nutrient;
+use crate::data_types::{AllowedUnitsType, EnergyUnit, MacroElementsType, MealSlot, NutrientType};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const MEAL_DURATION: &str = "PT30M";
+// content lines longer than this many bytes have to be folded
+const MAX_LINE_BYTES: usize = 75;
+const SUMMARY_NUTRIENTS: [MacroElementsType; 4] = [
+    MacroElementsType::Calories,
+    MacroElementsType::Protein,
+    MacroElementsType::Fat,
+    MacroElementsType::Carbs,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    Markdown,
+    /// HTML fragment without `<html>` and `<body>`, so it can be embedded or styled by the caller.
+    Html,
+}
+
+/// Writes the meals of `solution` as an iCalendar (RFC 5545) document with one event per meal,
+/// ready to be imported into Google Calendar, Outlook and similar.
+///
+/// Day `n` of the plan falls `n` days after `start_date` (Unix timestamp of its midnight). Meals
+/// with a time in their slot become 30 minute events in the local time of the calendar, the
+/// other ones all-day events. Event descriptions list the products and the meal nutrients.
+#[must_use]
+pub fn to_ical(solution: &Solution, start_date: u64) -> String {
+    let days = solution.days();
+    let first_day = start_date / SECONDS_PER_DAY;
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//meal-planner//plan export//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for (day_idx, day) in (0u64..).zip(days) {
+        let SolutionEntry::Day { entries, .. } = day else {
+            continue;
+        };
+        let date = ical_date(first_day + day_idx);
+        for (meal_idx, meal) in entries.iter().enumerate() {
+            let SolutionEntry::Meal {
+                name,
+                slot,
+                entries,
+            } = meal
+            else {
+                continue;
+            };
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!(
+                "UID:{start_date}-{day_idx}-{meal_idx}@meal-planner"
+            ));
+            // the plan itself is the only timestamp available, keeps the output reproducible
+            lines.push(format!("DTSTAMP:{}T000000Z", ical_date(first_day)));
+            match slot.as_ref().and_then(MealSlot::effective_time) {
+                Some(time) => {
+                    lines.push(format!(
+                        "DTSTART:{date}T{:02}{:02}00",
+                        time.hour, time.minute
+                    ));
+                    lines.push(format!("DURATION:{MEAL_DURATION}"));
+                }
+                None => lines.push(format!("DTSTART;VALUE=DATE:{date}")),
+            }
+            lines.push(format!("SUMMARY:{}", escape_text(name)));
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                escape_text(&meal_description(meal, entries))
+            ));
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ical = String::new();
+    for line in lines {
+        ical.push_str(&fold_line(&line));
+        ical.push_str("\r\n");
+    }
+    ical
+}
+
+/// Renders `solution` for printing or sharing: a section per day with a table of products per
+/// meal and the nutrient totals of the day.
+#[must_use]
+pub fn render_plan(solution: &Solution, format: PlanFormat) -> String {
+    let mut out = String::new();
+    match format {
+        PlanFormat::Markdown => out.push_str("# Meal plan\n"),
+        PlanFormat::Html => out.push_str("<article class=\"meal-plan\">\n<h1>Meal plan</h1>\n"),
+    }
+    for day in solution.days() {
+        let SolutionEntry::Day { name, entries } = day else {
+            continue;
+        };
+        match format {
+            PlanFormat::Markdown => render_day_markdown(&mut out, name, entries),
+            PlanFormat::Html => render_day_html(&mut out, name, entries),
+        }
+        let totals = nutrient_summary(day);
+        match format {
+            PlanFormat::Markdown => {
+                let _ = writeln!(out, "\n**Day totals:** {totals}");
+            }
+            PlanFormat::Html => {
+                let _ = writeln!(
+                    out,
+                    "<p><strong>Day totals:</strong> {}</p>\n</section>",
+                    escape_html(&totals)
+                );
+            }
+        }
+    }
+    if format == PlanFormat::Html {
+        out.push_str("</article>\n");
+    }
+    out
+}
+
+fn render_day_markdown(out: &mut String, day_name: &str, meals: &[SolutionEntry]) {
+    let _ = writeln!(out, "\n## {}", escape_markdown(day_name));
+    for meal in meals {
+        let SolutionEntry::Meal {
+            name,
+            slot,
+            entries,
+        } = meal
+        else {
+            continue;
+        };
+        let _ = writeln!(
+            out,
+            "\n### {}\n\n| Product | Amount | Grams |\n| --- | --- | ---: |",
+            escape_markdown(&meal_heading(name, slot.as_ref()))
+        );
+        for (product, amount, grams) in product_rows(entries) {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {grams} |",
+                escape_markdown(&product),
+                escape_markdown(&amount)
+            );
+        }
+    }
+}
+
+fn render_day_html(out: &mut String, day_name: &str, meals: &[SolutionEntry]) {
+    let _ = writeln!(out, "<section>\n<h2>{}</h2>", escape_html(day_name));
+    for meal in meals {
+        let SolutionEntry::Meal {
+            name,
+            slot,
+            entries,
+        } = meal
+        else {
+            continue;
+        };
+        let _ = writeln!(
+            out,
+            "<h3>{}</h3>\n<table>\n\
+             <thead><tr><th>Product</th><th>Amount</th><th>Grams</th></tr></thead>\n<tbody>",
+            escape_html(&meal_heading(name, slot.as_ref()))
+        );
+        for (product, amount, grams) in product_rows(entries) {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{grams}</td></tr>",
+                escape_html(&product),
+                escape_html(&amount)
+            );
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+}
+
+fn meal_heading(name: &str, slot: Option<&MealSlot>) -> String {
+    match slot.and_then(MealSlot::effective_time) {
+        Some(time) => format!("{name} ({}:{:02})", time.hour, time.minute),
+        None => name.to_string(),
+    }
+}
+
+// product name, amount in the unit picked by the solver and grams of every product in `entries`
+fn product_rows(entries: &[SolutionEntry]) -> Vec<(String, String, String)> {
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                unit,
+                amount_unit,
+            } => Some((
+                product.name().to_string(),
+                human_amount(
+                    *unit,
+                    product.unit_label(*unit),
+                    *amount_unit,
+                    *amount_grams,
+                ),
+                format!("{amount_grams:.0} g"),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+// whole units and the remaining fraction, e.g. "1 1/2 cup"
+// `label` is the product specific name of `unit`, if it has one
+fn human_amount(
+    unit: AllowedUnitsType,
+    label: Option<&str>,
+    amount: Fraction,
+    grams: f64,
+) -> String {
+    if unit == AllowedUnitsType::Gram || amount.denominator == 0 {
+        return format!("{grams:.0} g");
+    }
+    let whole = amount.numerator / amount.denominator;
+    let rest = amount.numerator % amount.denominator;
+    let quantity = match (whole, rest) {
+        (_, 0) => whole.to_string(),
+        (0, _) => format!("{rest}/{}", amount.denominator),
+        _ => format!("{whole} {rest}/{}", amount.denominator),
+    };
+    match label {
+        Some(label) => format!("{quantity} {label}"),
+        None => format!("{quantity} {unit}"),
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn nutrient_summary(entry: &SolutionEntry) -> String {
+    SUMMARY_NUTRIENTS
+        .iter()
+        .map(|nutrient| {
+            let nutrient = NutrientType::Macro(*nutrient);
+            let amount = entry.nutrient_total(nutrient);
+            format!(
+                "{nutrient}: {}",
+                format_nutrient(nutrient, amount, "en", EnergyUnit::Kcal)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn meal_description(meal: &SolutionEntry, entries: &[SolutionEntry]) -> String {
+    let mut description = String::new();
+    for entry in entries {
+        if let SolutionEntry::Product {
+            product,
+            amount_grams,
+            ..
+        } = entry
+        {
+            let _ = writeln!(description, "{amount_grams:.0} g {}", product.name());
+        }
+    }
+    description.push('\n');
+    description.push_str(&nutrient_summary(meal));
+    description
+}
+
+// YYYYMMDD of the day `days` after 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+fn ical_date(days: u64) -> String {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}{month:02}{day:02}")
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// continuation lines start with a space, which counts towards their length
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_bytes = 0;
+    for c in line.chars() {
+        if line_bytes + c.len_utf8() > MAX_LINE_BYTES {
+            folded.push_str("\r\n ");
+            line_bytes = 1;
+        }
+        folded.push(c);
+        line_bytes += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::Fraction;
+    use crate::data_types::{AllowedUnitsType, MealKind, MealTime, ProductBuilder};
+
+    // 2023-11-15
+    const START: u64 = 1_700_006_400;
+
+    fn product_entry(name: &str, protein: f32, grams: f64) -> SolutionEntry {
+        SolutionEntry::Product {
+            product: ProductBuilder::new(name).protein(protein).build().unwrap(),
+            amount_grams: grams,
+            unit: AllowedUnitsType::Gram,
+            amount_unit: Fraction {
+                numerator: 1,
+                denominator: 1,
+            },
+        }
+    }
+
+    fn day(name: &str) -> SolutionEntry {
+        SolutionEntry::Day {
+            name: name.to_string(),
+            entries: vec![
+                SolutionEntry::Meal {
+                    name: "Lunch, late".to_string(),
+                    slot: Some(MealSlot::new(
+                        MealKind::Lunch,
+                        Some(MealTime::new(13, 30).unwrap()),
+                    )),
+                    entries: vec![
+                        product_entry("Rice", 8.0, 150.0),
+                        product_entry("Chicken", 30.0, 100.0),
+                    ],
+                },
+                SolutionEntry::Meal {
+                    name: "Anytime".to_string(),
+                    slot: None,
+                    entries: vec![product_entry("Apple", 0.0, 120.0)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn exports_event_per_meal() {
+        let solution = Solution {
+            solution: SolutionEntry::Week {
+                entries: vec![day("Wednesday"), day("Thursday")],
+            },
+        };
+        let ical = to_ical(&solution, START);
+
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), 4);
+        assert!(ical.contains(
+            "BEGIN:VEVENT\r\nUID:1700006400-0-0@meal-planner\r\nDTSTAMP:20231115T000000Z\r\n\
+             DTSTART:20231115T133000\r\nDURATION:PT30M\r\nSUMMARY:Lunch\\, late\r\n\
+             DESCRIPTION:150 g Rice\\n100 g Chicken\\n\\nCalories: 168 kcal\\, Protein: 42 g\r\n \\, \
+             Fat: 0 g\\, Carbohydrates: 0 g\r\nEND:VEVENT\r\n"
+        ));
+        assert!(ical.contains("UID:1700006400-1-1@meal-planner\r\n"));
+        assert!(ical.contains("DTSTART;VALUE=DATE:20231116\r\n"));
+        assert!(ical.lines().all(|line| line.len() <= MAX_LINE_BYTES));
+    }
+
+    fn breakfast_day() -> Solution {
+        let SolutionEntry::Product { product, .. } = product_entry("Oats", 10.0, 0.0) else {
+            unreachable!()
+        };
+        let oats = SolutionEntry::Product {
+            product,
+            amount_grams: 120.0,
+            unit: AllowedUnitsType::Cup,
+            amount_unit: Fraction {
+                numerator: 3,
+                denominator: 2,
+            },
+        };
+        Solution {
+            solution: SolutionEntry::Day {
+                name: "Monday".to_string(),
+                entries: vec![SolutionEntry::Meal {
+                    name: "Breakfast".to_string(),
+                    slot: Some(MealSlot::new(MealKind::Breakfast, None)),
+                    entries: vec![oats, product_entry("Milk | <3.2%>", 3.0, 200.0)],
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn renders_markdown() {
+        assert_eq!(
+            render_plan(&breakfast_day(), PlanFormat::Markdown),
+            "# Meal plan\n\n## Monday\n\n### Breakfast (8:00)\n\n\
+             | Product | Amount | Grams |\n| --- | --- | ---: |\n\
+             | Oats | 1 1/2 cup | 120 g |\n| Milk \\| <3.2%> | 200 g | 200 g |\n\n\
+             **Day totals:** Calories: 72 kcal, Protein: 18 g, Fat: 0 g, Carbohydrates: 0 g\n"
+        );
+    }
+
+    #[test]
+    fn renders_html() {
+        let html = render_plan(&breakfast_day(), PlanFormat::Html);
+        assert!(html.starts_with("<article class=\"meal-plan\">\n<h1>Meal plan</h1>\n"));
+        assert!(html.contains("<section>\n<h2>Monday</h2>\n<h3>Breakfast (8:00)</h3>\n<table>"));
+        assert!(html.contains("<tr><td>Oats</td><td>1 1/2 cup</td><td>120 g</td></tr>"));
+        assert!(html.contains("<td>Milk | &lt;3.2%&gt;</td>"));
+        assert!(html.contains("<p><strong>Day totals:</strong> Calories: 72 kcal, "));
+        assert!(html.ends_with("</section>\n</article>\n"));
+    }
+
+    #[test]
+    fn formats_amounts_in_units() {
+        let fraction = |numerator, denominator| Fraction {
+            numerator,
+            denominator,
+        };
+        assert_eq!(
+            human_amount(AllowedUnitsType::Gram, None, fraction(1, 1), 99.6),
+            "100 g"
+        );
+        assert_eq!(
+            human_amount(AllowedUnitsType::Piece, None, fraction(4, 2), 100.0),
+            "2 piece"
+        );
+        assert_eq!(
+            human_amount(AllowedUnitsType::Teaspoon, None, fraction(1, 4), 2.0),
+            "1/4 teaspoon"
+        );
+        assert_eq!(
+            human_amount(AllowedUnitsType::Piece, Some("slice"), fraction(3, 1), 90.0),
+            "3 slice"
+        );
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(ical_date(0), "19700101");
+        assert_eq!(ical_date(START / SECONDS_PER_DAY), "20231115");
+        // leap day and the turn of the year
+        assert_eq!(ical_date(19_782), "20240229");
+        assert_eq!(ical_date(20_089), "20250101");
+    }
+
+    #[test]
+    fn folds_long_lines_on_char_boundaries() {
+        let line = "ż".repeat(50);
+        let folded = fold_line(&line);
+        assert!(
+            folded
+                .split("\r\n")
+                .all(|part| part.len() <= MAX_LINE_BYTES)
+        );
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
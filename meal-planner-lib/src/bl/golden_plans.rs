@@ -0,0 +1,134 @@
+//! Golden snapshots of solved plans, for checking that solver refactors keep its behavior.
+//!
+//! A snapshot keeps the grams of every product of every meal and the nutrient totals of every
+//! day, compared with a numeric tolerance so harmless floating point noise doesn't fail it.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bl::constraints_solver::Solution;
+
+/// Compact, order independent form of a [`Solution`]. Zero amounts are left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlanSnapshot {
+    /// Grams keyed by `"day / meal / product id"`.
+    pub products: BTreeMap<String, f64>,
+    /// Nutrient totals keyed by `"day / nutrient"`.
+    pub totals: BTreeMap<String, f64>,
+}
+
+impl PlanSnapshot {
+    #[must_use]
+    pub fn from_solution(solution: &Solution) -> Self {
+        let products = solution
+            .grams_by_meal()
+            .into_iter()
+            .filter(|(_, grams)| *grams != 0.0)
+            .map(|((day, meal, product_id), grams)| {
+                (format!("{day} / {meal} / {product_id}"), grams)
+            })
+            .collect();
+        let totals = solution
+            .day_totals()
+            .into_iter()
+            .flat_map(|(day, totals)| {
+                totals
+                    .iter()
+                    .filter(|(_, amount)| *amount != 0.0)
+                    .map(|(nutrient, amount)| (format!("{day} / {nutrient}"), amount))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Self { products, totals }
+    }
+
+    /// Values differing from `expected` by more than `tolerance`, relative to the expected value
+    /// but at least absolute for values below 1. Entries missing on one side count as zero.
+    #[must_use]
+    pub fn differences(&self, expected: &PlanSnapshot, tolerance: f64) -> Vec<String> {
+        let mut differences = Vec::new();
+        for (section, actual, expected) in [
+            ("product", &self.products, &expected.products),
+            ("total", &self.totals, &expected.totals),
+        ] {
+            let mut keys: Vec<&String> = actual.keys().chain(expected.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let actual_value = actual.get(key).copied().unwrap_or(0.0);
+                let expected_value = expected.get(key).copied().unwrap_or(0.0);
+                if (actual_value - expected_value).abs() > tolerance * expected_value.abs().max(1.0)
+                {
+                    differences.push(format!(
+                        "{section} {key}: expected {expected_value}, got {actual_value}"
+                    ));
+                }
+            }
+        }
+        differences
+    }
+}
+
+/// Compares `solution` with the snapshot stored at `path`. Setting the `UPDATE_GOLDEN`
+/// environment variable writes the snapshot instead, after a deliberate behavior change.
+///
+/// # Panics
+/// When the snapshot is missing, unreadable or differs from `solution` beyond `tolerance`.
+pub fn assert_golden_plan(path: &Path, solution: &Solution, tolerance: f64) {
+    let actual = PlanSnapshot::from_solution(solution);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let json = serde_json::to_string_pretty(&actual).expect("snapshot should serialize");
+        std::fs::write(path, json + "\n").expect("snapshot should be writable");
+        return;
+    }
+    let json = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "missing golden plan {}: {e}; rerun with UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    let expected: PlanSnapshot = serde_json::from_str(&json).expect("snapshot should be valid");
+    let differences = actual.differences(&expected, tolerance);
+    assert!(
+        differences.is_empty(),
+        "plan differs from {}; if this is intended, rerun with UPDATE_GOLDEN=1:\n{}",
+        path.display(),
+        differences.join("\n")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differences_respect_tolerance_and_missing_entries() {
+        let expected = PlanSnapshot {
+            products: BTreeMap::from([
+                ("Day1 / Lunch / Rice".to_string(), 200.0),
+                ("Day1 / Lunch / Tofu".to_string(), 1e-4),
+            ]),
+            totals: BTreeMap::from([("Day1 / Protein".to_string(), 30.0)]),
+        };
+        let mut actual = expected.clone();
+        actual
+            .products
+            .insert("Day1 / Lunch / Rice".to_string(), 200.1);
+        actual.products.remove("Day1 / Lunch / Tofu");
+        assert!(actual.differences(&expected, 1e-3).is_empty());
+
+        actual.totals.insert("Day1 / Protein".to_string(), 31.0);
+        actual
+            .products
+            .insert("Day1 / Dinner / Egg".to_string(), 60.0);
+        assert_eq!(
+            actual.differences(&expected, 1e-3),
+            vec![
+                "product Day1 / Dinner / Egg: expected 0, got 60".to_string(),
+                "total Day1 / Protein: expected 30, got 31".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::constraints_solver::{Solution, SolutionEntry};
+use super::quantity::Quantity;
+use crate::data_types::ProductId;
+
+/// Amount of a product needed by the plan and, once checked off, the amount actually bought.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShoppingListItem {
+    pub needed_grams: f64,
+    pub purchased_grams: Option<f64>,
+}
+
+impl ShoppingListItem {
+    #[must_use]
+    pub fn needed(&self) -> Quantity {
+        Quantity::from_grams(self.needed_grams)
+    }
+
+    #[must_use]
+    pub fn purchased(&self) -> Option<Quantity> {
+        self.purchased_grams.map(Quantity::from_grams)
+    }
+}
+
+/// Item added to a list by hand, e.g. something that isn't in the product database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManualShoppingItem {
+    pub name: String,
+    /// Free text amount, e.g. "2 packs", empty when not given.
+    pub quantity: String,
+    pub checked: bool,
+}
+
+/// Products needed by a plan, keyed by product ID.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShoppingList {
+    items: BTreeMap<ProductId, ShoppingListItem>,
+    #[serde(default)]
+    manual_items: Vec<ManualShoppingItem>,
+    /// Plan the list was generated from, chosen by the caller.
+    #[serde(default)]
+    plan_id: Option<String>,
+}
+
+impl ShoppingList {
+    /// Sums up the grams of every product used anywhere in `solution`.
+    #[must_use]
+    pub fn from_solution(solution: &Solution) -> Self {
+        let mut items = BTreeMap::new();
+        for (product_id, grams, _) in planned_products(&solution.solution) {
+            items
+                .entry(product_id)
+                .or_insert(ShoppingListItem {
+                    needed_grams: 0.0,
+                    purchased_grams: None,
+                })
+                .needed_grams += grams;
+        }
+        Self {
+            items,
+            ..Self::default()
+        }
+    }
+
+    /// Like [`Self::from_solution`], listing only the grams `pantry` does not cover.
+    #[must_use]
+    pub fn from_solution_with_pantry(solution: &Solution, pantry: &Pantry) -> Self {
+        let items = find_missing_items(solution, pantry)
+            .into_iter()
+            .map(|item| {
+                (
+                    item.product_id,
+                    ShoppingListItem {
+                        needed_grams: item.missing_grams,
+                        purchased_grams: None,
+                    },
+                )
+            })
+            .collect();
+        Self {
+            items,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_plan_id(mut self, plan_id: Option<String>) -> Self {
+        self.plan_id = plan_id;
+        self
+    }
+
+    #[must_use]
+    pub fn plan_id(&self) -> Option<&str> {
+        self.plan_id.as_deref()
+    }
+
+    #[must_use]
+    pub fn items(&self) -> &BTreeMap<ProductId, ShoppingListItem> {
+        &self.items
+    }
+
+    #[must_use]
+    pub fn manual_items(&self) -> &[ManualShoppingItem] {
+        &self.manual_items
+    }
+
+    /// Adds an unchecked item by hand, names are unique within the list.
+    pub fn add_manual_item(&mut self, name: &str, quantity: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Item name is empty".to_string());
+        }
+        if self.manual_items.iter().any(|item| item.name == name) {
+            return Err(format!("'{name}' is already on the shopping list"));
+        }
+        self.manual_items.push(ManualShoppingItem {
+            name: name.to_string(),
+            quantity: quantity.trim().to_string(),
+            checked: false,
+        });
+        Ok(())
+    }
+
+    pub fn remove_manual_item(&mut self, name: &str) {
+        self.manual_items.retain(|item| item.name != name);
+    }
+
+    /// Checks `product_id` off in the needed amount or unchecks it, returns whether it is checked.
+    pub fn toggle(&mut self, product_id: &str) -> Result<bool, String> {
+        let item = self
+            .items
+            .get_mut(product_id)
+            .ok_or_else(|| format!("Product '{product_id}' is not on the shopping list"))?;
+        item.purchased_grams = match item.purchased_grams {
+            Some(_) => None,
+            None => Some(item.needed_grams),
+        };
+        Ok(item.purchased_grams.is_some())
+    }
+
+    /// Like [`Self::toggle`] for an item added by hand.
+    pub fn toggle_manual_item(&mut self, name: &str) -> Result<bool, String> {
+        let item = self
+            .manual_items
+            .iter_mut()
+            .find(|item| item.name == name)
+            .ok_or_else(|| format!("'{name}' is not on the shopping list"))?;
+        item.checked = !item.checked;
+        Ok(item.checked)
+    }
+
+    /// Marks `product_id` as bought in the given amount, which may differ from the needed one.
+    pub fn check_off(&mut self, product_id: &str, purchased_grams: f64) -> Result<(), String> {
+        if !purchased_grams.is_finite() || purchased_grams < 0.0 {
+            return Err(format!("Invalid purchased amount: {purchased_grams}"));
+        }
+        let item = self
+            .items
+            .get_mut(product_id)
+            .ok_or_else(|| format!("Product '{product_id}' is not on the shopping list"))?;
+        item.purchased_grams = Some(purchased_grams);
+        Ok(())
+    }
+
+    pub fn uncheck(&mut self, product_id: &str) {
+        if let Some(item) = self.items.get_mut(product_id) {
+            item.purchased_grams = None;
+        }
+    }
+
+    /// Moves every checked item into `pantry` and removes it from the list, so unchecked items
+    /// stay for the next trip. Checked manual items are only removed. Returns the number of
+    /// products added to the pantry.
+    pub fn apply_to_pantry(&mut self, pantry: &mut Pantry) -> usize {
+        let mut added = 0;
+        self.items.retain(|product_id, item| {
+            let Some(grams) = item.purchased_grams else {
+                return true;
+            };
+            pantry.add(product_id, grams);
+            added += 1;
+            false
+        });
+        self.manual_items.retain(|item| !item.checked);
+        added
+    }
+}
+
+/// Products at home, in grams, keyed by product ID.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pantry {
+    stock: BTreeMap<ProductId, f64>,
+    // day (since the Unix epoch) the stocked product expires on, the earliest one when restocked
+    #[serde(default)]
+    expiry: BTreeMap<ProductId, u64>,
+}
+
+/// Stocked product expiring soon, see [`Pantry::get_expiring`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpiringItem {
+    pub product_id: ProductId,
+    pub grams: f64,
+    /// Day since the Unix epoch.
+    pub expiry_day: u64,
+}
+
+impl Pantry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, product_id: &str, grams: f64) {
+        *self.stock.entry(ProductId::from(product_id)).or_insert(0.0) += grams;
+    }
+
+    /// Like [`Self::add`], for products expiring on `expiry_day` (days since the Unix epoch).
+    /// The stock of a product shares one date, the earlier one is kept.
+    pub fn add_with_expiry(&mut self, product_id: &str, grams: f64, expiry_day: u64) {
+        self.add(product_id, grams);
+        let expiry = self
+            .expiry
+            .entry(ProductId::from(product_id))
+            .or_insert(expiry_day);
+        *expiry = (*expiry).min(expiry_day);
+    }
+
+    /// Day the stock of the product expires on, `None` when unknown.
+    #[must_use]
+    pub fn expiry(&self, product_id: &str) -> Option<u64> {
+        self.expiry.get(product_id).copied()
+    }
+
+    /// Stocked products expiring within `days` after `today` (days since the Unix epoch),
+    /// including already expired ones, soonest first.
+    #[must_use]
+    pub fn get_expiring(&self, today: u64, days: u64) -> Vec<ExpiringItem> {
+        let mut expiring: Vec<ExpiringItem> = self
+            .expiry
+            .iter()
+            .filter(|(_, expiry_day)| **expiry_day <= today.saturating_add(days))
+            .map(|(product_id, expiry_day)| ExpiringItem {
+                product_id: product_id.clone(),
+                grams: self.grams(product_id),
+                expiry_day: *expiry_day,
+            })
+            .collect();
+        expiring.sort_by_key(|item| item.expiry_day);
+        expiring
+    }
+
+    #[must_use]
+    pub fn grams(&self, product_id: &str) -> f64 {
+        self.stock.get(product_id).copied().unwrap_or(0.0)
+    }
+
+    #[must_use]
+    pub fn quantity(&self, product_id: &str) -> Quantity {
+        Quantity::from_grams(self.grams(product_id))
+    }
+
+    #[must_use]
+    pub fn stock(&self) -> &BTreeMap<ProductId, f64> {
+        &self.stock
+    }
+
+    /// Takes everything eaten in `solution` out of the stock, dropping products that run out.
+    /// Returns what the stock did not cover, like [`find_missing_items`].
+    pub fn consume(&mut self, solution: &Solution) -> Vec<MissingItem> {
+        self.consume_products(planned_products(&solution.solution))
+    }
+
+    /// Like [`Self::consume`] for the meals of `day` only, to track the stock day by day.
+    pub fn consume_day(&mut self, solution: &Solution, day: &str) -> Vec<MissingItem> {
+        let products = planned_products(&solution.solution)
+            .into_iter()
+            .filter(|(_, _, meal)| meal.day.as_deref() == Some(day))
+            .collect();
+        self.consume_products(products)
+    }
+
+    fn consume_products(
+        &mut self,
+        products: Vec<(ProductId, f64, PlannedMeal)>,
+    ) -> Vec<MissingItem> {
+        let missing = take_from_stock(&mut self.stock, products);
+        self.stock.retain(|_, grams| *grams > 0.0);
+        self.expiry
+            .retain(|product_id, _| self.stock.contains_key(product_id));
+        missing
+    }
+}
+
+/// Meal of a plan, identified by the names of its day and meal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedMeal {
+    pub day: Option<String>,
+    pub meal: Option<String>,
+}
+
+/// Product the pantry does not hold enough of for the plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingItem {
+    pub product_id: ProductId,
+    pub missing_grams: f64,
+    /// Meals that cannot be cooked, in plan order, when the stock is used up meal by meal.
+    pub meals: Vec<PlannedMeal>,
+}
+
+/// Lists products from `solution` that `pantry` does not cover, with the meals they block.
+#[must_use]
+pub fn find_missing_items(solution: &Solution, pantry: &Pantry) -> Vec<MissingItem> {
+    take_from_stock(
+        &mut pantry.stock.clone(),
+        planned_products(&solution.solution),
+    )
+}
+
+// uses up `stock` meal by meal, returning the products it runs short of
+fn take_from_stock(
+    stock: &mut BTreeMap<ProductId, f64>,
+    products: Vec<(ProductId, f64, PlannedMeal)>,
+) -> Vec<MissingItem> {
+    let mut missing: BTreeMap<ProductId, MissingItem> = BTreeMap::new();
+    for (product_id, grams, meal) in products {
+        let left = stock.entry(product_id.clone()).or_insert(0.0);
+        let shortfall = grams - *left;
+        *left = (*left - grams).max(0.0);
+        // ignore rounding leftovers of the solver
+        if shortfall <= f64::EPSILON * grams.max(1.0) {
+            continue;
+        }
+        let item = missing
+            .entry(product_id.clone())
+            .or_insert_with(|| MissingItem {
+                product_id,
+                missing_grams: 0.0,
+                meals: Vec::new(),
+            });
+        item.missing_grams += shortfall;
+        if !item.meals.contains(&meal) {
+            item.meals.push(meal);
+        }
+    }
+    missing.into_values().collect()
+}
+
+fn planned_products(entry: &SolutionEntry) -> Vec<(ProductId, f64, PlannedMeal)> {
+    fn walk(
+        entry: &SolutionEntry,
+        meal: &PlannedMeal,
+        products: &mut Vec<(ProductId, f64, PlannedMeal)>,
+    ) {
+        match entry {
+            SolutionEntry::Week { entries } => {
+                for e in entries {
+                    walk(e, meal, products);
+                }
+            }
+            SolutionEntry::Day { name, entries } => {
+                let meal = PlannedMeal {
+                    day: Some(name.clone()),
+                    meal: None,
+                };
+                for e in entries {
+                    walk(e, &meal, products);
+                }
+            }
+            SolutionEntry::Meal { name, entries, .. } => {
+                let meal = PlannedMeal {
+                    day: meal.day.clone(),
+                    meal: Some(name.clone()),
+                };
+                for e in entries {
+                    walk(e, &meal, products);
+                }
+            }
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                ..
+            } => products.push((product.id(), *amount_grams, meal.clone())),
+        }
+    }
+    let mut products = Vec::new();
+    walk(
+        entry,
+        &PlannedMeal {
+            day: None,
+            meal: None,
+        },
+        &mut products,
+    );
+    products
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::Fraction;
+    use crate::data_types::{AllowedUnitsType, ProductBuilder};
+    use approx::assert_relative_eq;
+
+    fn product_entry(name: &str, grams: f64) -> SolutionEntry {
+        SolutionEntry::Product {
+            product: ProductBuilder::new(name)
+                .fat(1.0)
+                .carbs(1.0)
+                .protein(1.0)
+                .build()
+                .unwrap(),
+            amount_grams: grams,
+            unit: AllowedUnitsType::Gram,
+            amount_unit: Fraction {
+                numerator: 1,
+                denominator: 1,
+            },
+        }
+    }
+
+    fn meal(name: &str, entries: Vec<SolutionEntry>) -> SolutionEntry {
+        SolutionEntry::Meal {
+            name: name.to_string(),
+            slot: None,
+            entries,
+        }
+    }
+
+    fn two_day_plan() -> Solution {
+        Solution {
+            solution: SolutionEntry::Week {
+                entries: vec![
+                    SolutionEntry::Day {
+                        name: "Monday".to_string(),
+                        entries: vec![
+                            meal(
+                                "Breakfast",
+                                vec![product_entry("Oats", 80.0), product_entry("Milk", 200.0)],
+                            ),
+                            meal("Dinner", vec![product_entry("Rice", 100.0)]),
+                        ],
+                    },
+                    SolutionEntry::Day {
+                        name: "Tuesday".to_string(),
+                        entries: vec![meal(
+                            "Breakfast",
+                            vec![product_entry("Oats", 80.0), product_entry("Milk", 200.0)],
+                        )],
+                    },
+                ],
+            },
+        }
+    }
+
+    fn planned(day: &str, meal: &str) -> PlannedMeal {
+        PlannedMeal {
+            day: Some(day.to_string()),
+            meal: Some(meal.to_string()),
+        }
+    }
+
+    #[test]
+    fn shopping_list_sums_product_amounts() {
+        let list = ShoppingList::from_solution(&two_day_plan());
+        assert_eq!(list.items().len(), 3);
+        assert_relative_eq!(list.items()["Oats"].needed_grams, 160.0);
+        assert_relative_eq!(list.items()["Milk"].needed_grams, 400.0);
+        assert!(list.items().values().all(|i| i.purchased_grams.is_none()));
+    }
+
+    #[test]
+    fn check_off_validates_input() {
+        let mut list = ShoppingList::from_solution(&two_day_plan());
+        assert_eq!(
+            list.check_off("Bread", 100.0),
+            Err("Product 'Bread' is not on the shopping list".to_string())
+        );
+        assert!(list.check_off("Oats", -1.0).is_err());
+        list.check_off("Oats", 500.0)
+            .expect("check off should succeed");
+        assert_eq!(list.items()["Oats"].purchased_grams, Some(500.0));
+        list.uncheck("Oats");
+        assert_eq!(list.items()["Oats"].purchased_grams, None);
+    }
+
+    #[test]
+    fn toggle_and_manual_items() {
+        let mut list = ShoppingList::from_solution(&two_day_plan()).with_plan_id(Some("w1".into()));
+        assert_eq!(list.plan_id(), Some("w1"));
+        assert_eq!(list.toggle("Oats"), Ok(true));
+        assert_eq!(list.items()["Oats"].purchased_grams, Some(160.0));
+        assert_eq!(list.toggle("Oats"), Ok(false));
+        assert!(list.toggle("Bread").is_err());
+
+        list.add_manual_item(" Napkins ", "1 pack")
+            .expect("manual item should be added");
+        assert!(list.add_manual_item("Napkins", "").is_err());
+        assert!(list.add_manual_item("  ", "").is_err());
+        assert_eq!(list.toggle_manual_item("Napkins"), Ok(true));
+        list.add_manual_item("Foil", "")
+            .expect("manual item should be added");
+
+        let mut pantry = Pantry::new();
+        assert_eq!(list.apply_to_pantry(&mut pantry), 0);
+        assert_eq!(
+            list.manual_items(),
+            [ManualShoppingItem {
+                name: "Foil".to_string(),
+                quantity: String::new(),
+                checked: false,
+            }]
+        );
+        list.remove_manual_item("Foil");
+        assert!(list.manual_items().is_empty());
+    }
+
+    #[test]
+    fn checked_items_move_to_pantry() {
+        let mut list = ShoppingList::from_solution(&two_day_plan());
+        let mut pantry = Pantry::new();
+        pantry.add("Oats", 20.0);
+        list.check_off("Oats", 500.0)
+            .expect("check off should succeed");
+        list.check_off("Milk", 300.0)
+            .expect("check off should succeed");
+
+        assert_eq!(list.apply_to_pantry(&mut pantry), 2);
+        assert_relative_eq!(pantry.grams("Oats"), 520.0);
+        assert_relative_eq!(pantry.grams("Milk"), 300.0);
+        assert_eq!(pantry.quantity("Milk").to_string(), "300 g");
+        assert_eq!(list.items().keys().collect::<Vec<_>>(), vec!["Rice"]);
+    }
+
+    #[test]
+    fn missing_items_list_blocked_meals() {
+        let mut pantry = Pantry::new();
+        pantry.add("Oats", 500.0);
+        pantry.add("Milk", 300.0);
+
+        let missing = find_missing_items(&two_day_plan(), &pantry);
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].product_id, "Milk");
+        assert_relative_eq!(missing[0].missing_grams, 100.0);
+        assert_eq!(missing[0].meals, vec![planned("Tuesday", "Breakfast")]);
+        assert_eq!(missing[1].product_id, "Rice");
+        assert_relative_eq!(missing[1].missing_grams, 100.0);
+        assert_eq!(missing[1].meals, vec![planned("Monday", "Dinner")]);
+
+        pantry.add("Milk", 100.0);
+        pantry.add("Rice", 100.0);
+        assert!(find_missing_items(&two_day_plan(), &pantry).is_empty());
+    }
+
+    #[test]
+    fn consuming_days_updates_the_stock() {
+        let mut pantry = Pantry::new();
+        pantry.add("Oats", 100.0);
+        pantry.add("Milk", 300.0);
+        pantry.add("Apple", 50.0);
+
+        assert_eq!(
+            pantry.consume_day(&two_day_plan(), "Monday"),
+            vec![MissingItem {
+                product_id: "Rice".into(),
+                missing_grams: 100.0,
+                meals: vec![planned("Monday", "Dinner")],
+            }]
+        );
+        assert_relative_eq!(pantry.grams("Oats"), 20.0);
+        assert_relative_eq!(pantry.grams("Milk"), 100.0);
+        assert!(!pantry.stock().contains_key("Rice"));
+
+        let missing = pantry.consume_day(&two_day_plan(), "Tuesday");
+        assert_eq!(missing.len(), 2);
+        assert_relative_eq!(missing[0].missing_grams, 100.0);
+        assert_relative_eq!(missing[1].missing_grams, 60.0);
+        assert_eq!(pantry.stock().keys().collect::<Vec<_>>(), vec!["Apple"]);
+
+        let mut whole_plan = Pantry::new();
+        whole_plan.add("Rice", 150.0);
+        assert_eq!(whole_plan.consume(&two_day_plan()).len(), 2);
+        assert_relative_eq!(whole_plan.grams("Rice"), 50.0);
+    }
+
+    #[test]
+    fn shopping_list_with_pantry_lists_shortfall() {
+        let mut pantry = Pantry::new();
+        pantry.add("Oats", 100.0);
+        pantry.add("Milk", 400.0);
+
+        let list = ShoppingList::from_solution_with_pantry(&two_day_plan(), &pantry);
+        assert_eq!(
+            list.items().keys().collect::<Vec<_>>(),
+            vec!["Oats", "Rice"]
+        );
+        assert_relative_eq!(list.items()["Oats"].needed_grams, 60.0);
+        assert_relative_eq!(list.items()["Rice"].needed_grams, 100.0);
+    }
+
+    #[test]
+    fn expiring_products_soonest_first() {
+        let mut pantry = Pantry::new();
+        pantry.add_with_expiry("Milk", 300.0, 103);
+        pantry.add_with_expiry("Milk", 100.0, 101);
+        pantry.add_with_expiry("Yogurt", 150.0, 100);
+        pantry.add_with_expiry("Rice", 1000.0, 400);
+        pantry.add("Oats", 500.0);
+        assert_eq!(pantry.expiry("Milk"), Some(101));
+        assert_eq!(pantry.expiry("Oats"), None);
+
+        let expiring = pantry.get_expiring(100, 3);
+        assert_eq!(
+            expiring
+                .iter()
+                .map(|item| item.product_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Yogurt", "Milk"]
+        );
+        assert_relative_eq!(expiring[1].grams, 400.0);
+
+        // used up products drop their date
+        pantry.consume(&two_day_plan());
+        assert_eq!(pantry.expiry("Milk"), None);
+        assert_eq!(pantry.expiry("Yogurt"), Some(100));
+    }
+}
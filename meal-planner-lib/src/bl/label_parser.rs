@@ -0,0 +1,322 @@
+//! Reading nutrient values from nutrition label text pasted by the user, in English or Polish.
+
+use crate::bl::number_format::parse_number;
+use crate::data_types::{
+    MacroElements, MacroElementsType, MicroNutrients, MicroNutrientsType, NutrientUnit,
+};
+
+const KJ_PER_KCAL: f32 = 4.184;
+// salt is 40% sodium
+const SODIUM_MG_PER_G_SALT: f32 = 400.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LabelField {
+    Energy,
+    Macro(MacroElementsType),
+    Micro(MicroNutrientsType),
+    Salt,
+    // recognized, but not stored (e.g. unsaturated fats, which would match "saturates")
+    Ignored,
+}
+
+// first match wins, so more specific names go before the ones they contain
+const KEYWORDS: &[(&str, LabelField)] = &[
+    ("energy", LabelField::Energy),
+    ("energia", LabelField::Energy),
+    ("wartość energetyczna", LabelField::Energy),
+    ("calories", LabelField::Energy),
+    ("unsaturat", LabelField::Ignored),
+    ("nienasycon", LabelField::Ignored),
+    ("polyols", LabelField::Ignored),
+    ("poliole", LabelField::Ignored),
+    ("starch", LabelField::Ignored),
+    ("skrobia", LabelField::Ignored),
+    (
+        "saturate",
+        LabelField::Macro(MacroElementsType::SaturatedFat),
+    ),
+    (
+        "nasycone",
+        LabelField::Macro(MacroElementsType::SaturatedFat),
+    ),
+    ("sugar", LabelField::Macro(MacroElementsType::Sugar)),
+    ("cukry", LabelField::Macro(MacroElementsType::Sugar)),
+    ("fat", LabelField::Macro(MacroElementsType::Fat)),
+    ("tłuszcz", LabelField::Macro(MacroElementsType::Fat)),
+    ("carbohydrate", LabelField::Macro(MacroElementsType::Carbs)),
+    ("węglowodany", LabelField::Macro(MacroElementsType::Carbs)),
+    ("protein", LabelField::Macro(MacroElementsType::Protein)),
+    ("białko", LabelField::Macro(MacroElementsType::Protein)),
+    ("fibre", LabelField::Micro(MicroNutrientsType::Fiber)),
+    ("fiber", LabelField::Micro(MicroNutrientsType::Fiber)),
+    ("błonnik", LabelField::Micro(MicroNutrientsType::Fiber)),
+    ("salt", LabelField::Salt),
+    ("sól", LabelField::Salt),
+    ("sodium", LabelField::Micro(MicroNutrientsType::Sodium)),
+    ("sód", LabelField::Micro(MicroNutrientsType::Sodium)),
+    ("alcohol", LabelField::Micro(MicroNutrientsType::Alcohol)),
+    ("alkohol", LabelField::Micro(MicroNutrientsType::Alcohol)),
+    ("vitamin a", LabelField::Micro(MicroNutrientsType::VitaminA)),
+    (
+        "witamina a",
+        LabelField::Micro(MicroNutrientsType::VitaminA),
+    ),
+    (
+        "vitamin b12",
+        LabelField::Micro(MicroNutrientsType::VitaminB12),
+    ),
+    (
+        "witamina b12",
+        LabelField::Micro(MicroNutrientsType::VitaminB12),
+    ),
+    ("vitamin c", LabelField::Micro(MicroNutrientsType::VitaminC)),
+    (
+        "witamina c",
+        LabelField::Micro(MicroNutrientsType::VitaminC),
+    ),
+    ("vitamin d", LabelField::Micro(MicroNutrientsType::VitaminD)),
+    (
+        "witamina d",
+        LabelField::Micro(MicroNutrientsType::VitaminD),
+    ),
+    ("iron", LabelField::Micro(MicroNutrientsType::Iron)),
+    ("żelazo", LabelField::Micro(MicroNutrientsType::Iron)),
+    ("calcium", LabelField::Micro(MicroNutrientsType::Calcium)),
+    ("wapń", LabelField::Micro(MicroNutrientsType::Calcium)),
+    (
+        "potassium",
+        LabelField::Micro(MicroNutrientsType::Potassium),
+    ),
+    ("potas", LabelField::Micro(MicroNutrientsType::Potassium)),
+    (
+        "magnesium",
+        LabelField::Micro(MicroNutrientsType::Magnesium),
+    ),
+    ("magnez", LabelField::Micro(MicroNutrientsType::Magnesium)),
+    ("zinc", LabelField::Micro(MicroNutrientsType::Zinc)),
+    ("cynk", LabelField::Micro(MicroNutrientsType::Zinc)),
+];
+
+/// Values read from a label, per 100g as printed in its first column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedLabel {
+    pub macro_elements: MacroElements,
+    pub micro_nutrients: MicroNutrients,
+    /// Energy printed on the label, to compare with the calories computed from the macros.
+    pub energy_kcal: Option<f32>,
+    /// Lines or parts of the label that were not understood, e.g. to show them to the user.
+    pub unrecognized: Vec<String>,
+}
+
+/// Parses label text such as `Energy 250 kJ / 60 kcal, Fat 0.3 g, of which saturates 0.1 g`.
+///
+/// Entries are separated by new lines, semicolons or commas followed by a space, so decimal
+/// commas (`0,3 g`) are kept. Units are converted to the ones values are stored in, salt is
+/// converted to sodium unless sodium is given too. Missing macro elements are 0.
+pub fn parse_label(text: &str) -> Result<ParsedLabel, String> {
+    let mut macro_elements = MacroElements::new(0.0, 0.0, 0.0, 0.0, 0.0);
+    let mut micro_nutrients = MicroNutrients::default();
+    let mut energy_kcal = None;
+    let mut salt_g = None;
+    let mut unrecognized = Vec::new();
+    let mut recognized_any = false;
+
+    for segment in split_entries(text) {
+        let lowercase = segment.to_lowercase();
+        let Some((field, rest)) = KEYWORDS.iter().find_map(|(keyword, field)| {
+            lowercase
+                .find(keyword)
+                .map(|idx| (*field, &lowercase[idx + keyword.len()..]))
+        }) else {
+            unrecognized.push(segment.to_string());
+            continue;
+        };
+        let amounts = amounts(rest);
+        let parsed = match field {
+            LabelField::Energy => energy(&amounts).map(|kcal| energy_kcal = Some(kcal)),
+            LabelField::Macro(macro_type) => amounts.first().map(|(value, unit)| {
+                let grams = convert(*value, unit.as_deref(), NutrientUnit::Gram);
+                // calories are computed, every other macro element can be set
+                let _ = macro_elements.set(macro_type, grams);
+            }),
+            LabelField::Micro(micro_type) => amounts.first().map(|(value, unit)| {
+                micro_nutrients[micro_type] =
+                    Some(convert(*value, unit.as_deref(), micro_type.info().unit));
+            }),
+            LabelField::Salt => amounts.first().map(|(value, unit)| {
+                salt_g = Some(convert(*value, unit.as_deref(), NutrientUnit::Gram));
+            }),
+            LabelField::Ignored => Some(()),
+        };
+        if parsed.is_some() {
+            recognized_any = true;
+        } else {
+            unrecognized.push(segment.to_string());
+        }
+    }
+
+    if !recognized_any {
+        return Err("No nutrient values recognized in the label".to_string());
+    }
+    if let Some(salt_g) = salt_g
+        && micro_nutrients[MicroNutrientsType::Sodium].is_none()
+    {
+        micro_nutrients[MicroNutrientsType::Sodium] = Some(salt_g * SODIUM_MG_PER_G_SALT);
+    }
+    Ok(ParsedLabel {
+        macro_elements,
+        micro_nutrients,
+        energy_kcal,
+        unrecognized,
+    })
+}
+
+fn split_entries(text: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let ends_entry = match c {
+            '\n' | ';' => true,
+            ',' => chars.peek().is_none_or(|(_, next)| !next.is_ascii_digit()),
+            _ => false,
+        };
+        if ends_entry {
+            entries.push(&text[start..idx]);
+            start = idx + c.len_utf8();
+        }
+    }
+    entries.push(&text[start..]);
+    entries
+        .into_iter()
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+// numbers with the unit written right after them, e.g. `0,3 g` or `<0.5g`
+fn amounts(text: &str) -> Vec<(f32, Option<String>)> {
+    let mut amounts = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        let number_len = rest[start..]
+            .char_indices()
+            .find(|&(idx, c)| {
+                !(c.is_ascii_digit()
+                    || ((c == '.' || c == ',')
+                        && rest[start + idx + 1..].starts_with(|d: char| d.is_ascii_digit())))
+            })
+            .map_or(rest.len() - start, |(idx, _)| idx);
+        let number = &rest[start..start + number_len];
+        let after = rest[start + number_len..].trim_start();
+        let unit: String = after
+            .chars()
+            .take_while(|c| c.is_alphabetic() || *c == 'µ')
+            .collect();
+        if let Ok(value) = parse_number(number) {
+            amounts.push((value, (!unit.is_empty()).then_some(unit)));
+        }
+        rest = &rest[start + number_len..];
+    }
+    amounts
+}
+
+fn energy(amounts: &[(f32, Option<String>)]) -> Option<f32> {
+    let with_unit = |wanted: &str| {
+        amounts
+            .iter()
+            .find(|(_, unit)| unit.as_deref() == Some(wanted))
+            .map(|(value, _)| *value)
+    };
+    with_unit("kcal").or_else(|| with_unit("kj").map(|kj| kj / KJ_PER_KCAL))
+}
+
+// values without a unit are assumed to already be in `target`
+fn convert(value: f32, unit: Option<&str>, target: NutrientUnit) -> f32 {
+    let grams_per = |unit: NutrientUnit| match unit {
+        NutrientUnit::Gram => 1.0,
+        NutrientUnit::Milligram => 1e-3,
+        NutrientUnit::Microgram => 1e-6,
+    };
+    let source = match unit {
+        Some("g") => NutrientUnit::Gram,
+        Some("mg") => NutrientUnit::Milligram,
+        Some("µg" | "μg" | "ug" | "mcg") => NutrientUnit::Microgram,
+        _ => target,
+    };
+    if source == target {
+        value
+    } else {
+        value * grams_per(source) / grams_per(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn parses_english_label() {
+        let label = parse_label(
+            "Nutrition per 100 g\n\
+             Energy 250 kJ / 60 kcal, Fat 0.3 g, of which saturates 0.1 g, \
+             of which mono-unsaturates 0.1 g, Carbohydrate 12 g, of which sugars 10 g, \
+             Fibre 2.4 g, Protein 1.1 g, Salt 0.02 g, Vitamin C 12 mg, Vitamin B12 0.5 µg",
+        )
+        .unwrap();
+        assert_eq!(label.energy_kcal, Some(60.0));
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Fat], 0.3);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::SaturatedFat], 0.1);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Carbs], 12.0);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Sugar], 10.0);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Protein], 1.1);
+        assert_eq!(label.micro_nutrients[MicroNutrientsType::Fiber], Some(2.4));
+        assert_relative_eq!(
+            label.micro_nutrients[MicroNutrientsType::Sodium].unwrap(),
+            8.0
+        );
+        assert_eq!(
+            label.micro_nutrients[MicroNutrientsType::VitaminC],
+            Some(12.0)
+        );
+        assert_eq!(
+            label.micro_nutrients[MicroNutrientsType::VitaminB12],
+            Some(0.5)
+        );
+        assert_eq!(label.unrecognized, vec!["Nutrition per 100 g"]);
+    }
+
+    #[test]
+    fn parses_polish_label_with_decimal_commas() {
+        let label = parse_label(
+            "Wartość energetyczna 1046 kJ; Tłuszcz 0,5 g; w tym kwasy tłuszczowe nasycone 0,1 g; \
+             Węglowodany 52,3 g; w tym cukry <0,5 g; Błonnik 3 g; Białko 7,1 g; Sód 0,4 g; \
+             Żelazo 1200 µg",
+        )
+        .unwrap();
+        assert_relative_eq!(label.energy_kcal.unwrap(), 250.0, epsilon = 0.01);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Fat], 0.5);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::SaturatedFat], 0.1);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Carbs], 52.3);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Sugar], 0.5);
+        assert_relative_eq!(label.macro_elements[MacroElementsType::Protein], 7.1);
+        assert_relative_eq!(
+            label.micro_nutrients[MicroNutrientsType::Sodium].unwrap(),
+            400.0
+        );
+        assert_relative_eq!(
+            label.micro_nutrients[MicroNutrientsType::Iron].unwrap(),
+            1.2
+        );
+        assert!(label.unrecognized.is_empty());
+    }
+
+    #[test]
+    fn rejects_text_without_nutrients() {
+        assert!(parse_label("Best before: see lid").is_err());
+        let partial = parse_label("Protein 20g\nIngredients: milk").unwrap();
+        assert_relative_eq!(partial.macro_elements[MacroElementsType::Calories], 80.0);
+        assert_eq!(partial.unrecognized, vec!["Ingredients: milk"]);
+    }
+}
@@ -0,0 +1,357 @@
+use std::fmt::Write;
+
+use microlp::{ComparisonOp, OptimizationDirection, Problem, Variable};
+
+// relative tolerance used to decide whether a constraint is met with equality
+const BINDING_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VariableKind {
+    Real,
+    Integer,
+    Binary,
+}
+
+struct LpVariable {
+    name: String,
+    objective: f64,
+    bounds: (f64, f64),
+    kind: VariableKind,
+}
+
+struct LpConstraint {
+    name: String,
+    terms: Vec<(Variable, f64)>,
+    op: ComparisonOp,
+    rhs: f64,
+}
+
+/// [`Problem`] that also keeps names of its variables and constraints, so it can be exported and
+/// inspected after solving.
+pub(super) struct LpModel {
+    problem: Problem,
+    direction: OptimizationDirection,
+    variables: Vec<LpVariable>,
+    constraints: Vec<LpConstraint>,
+}
+
+impl LpModel {
+    pub fn new(direction: OptimizationDirection) -> Self {
+        Self {
+            problem: Problem::new(direction),
+            direction,
+            variables: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn add_var(&mut self, name: String, objective: f64, bounds: (f64, f64)) -> Variable {
+        self.variables.push(LpVariable {
+            name,
+            objective,
+            bounds,
+            kind: VariableKind::Real,
+        });
+        self.problem.add_var(objective, bounds)
+    }
+
+    pub fn add_integer_var(
+        &mut self,
+        name: String,
+        objective: f64,
+        bounds: (i32, i32),
+    ) -> Variable {
+        self.variables.push(LpVariable {
+            name,
+            objective,
+            bounds: (f64::from(bounds.0), f64::from(bounds.1)),
+            kind: VariableKind::Integer,
+        });
+        self.problem.add_integer_var(objective, bounds)
+    }
+
+    pub fn add_binary_var(&mut self, name: String, objective: f64) -> Variable {
+        self.variables.push(LpVariable {
+            name,
+            objective,
+            bounds: (0.0, 1.0),
+            kind: VariableKind::Binary,
+        });
+        self.problem.add_binary_var(objective)
+    }
+
+    pub fn add_constraint(
+        &mut self,
+        name: String,
+        terms: Vec<(Variable, f64)>,
+        op: ComparisonOp,
+        rhs: f64,
+    ) {
+        self.problem.add_constraint(&terms, op, rhs);
+        self.constraints.push(LpConstraint {
+            name,
+            terms,
+            op,
+            rhs,
+        });
+    }
+
+    pub fn solve(&self) -> Result<microlp::Solution, microlp::Error> {
+        self.problem.solve()
+    }
+
+    /// Names of the inequality constraints met with equality in `solution`, i.e. the ones
+    /// limiting the objective.
+    pub fn binding_constraints(&self, solution: &microlp::Solution) -> Vec<String> {
+        self.constraints
+            .iter()
+            .filter(|c| !matches!(c.op, ComparisonOp::Eq))
+            .filter(|c| {
+                let lhs: f64 = c
+                    .terms
+                    .iter()
+                    .map(|(var, coeff)| coeff * solution.var_value(*var))
+                    .sum();
+                (lhs - c.rhs).abs() <= BINDING_TOLERANCE * c.rhs.abs().max(1.0)
+            })
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    /// Smallest changes making the problem feasible, as `(name, bound, relaxed bound)`, found by
+    /// adding a slack to every inequality and to the bounds of real variables (named
+    /// `<variable> min` and `<variable> max`), then minimizing their sum, each relative to its
+    /// bound. `None` when relaxing those is not enough, e.g. because of equalities.
+    pub fn minimal_relaxations(&self) -> Option<Vec<(String, f64, f64)>> {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let mut relaxed = Vec::new();
+        // adds `terms (op) bound` with a slack weighted by the inverse of the bound
+        let mut add_relaxed = |problem: &mut Problem,
+                               name: String,
+                               mut terms: Vec<(Variable, f64)>,
+                               op: ComparisonOp,
+                               bound: f64| {
+            let sign = match op {
+                ComparisonOp::Le => -1.0,
+                ComparisonOp::Ge => 1.0,
+                ComparisonOp::Eq => {
+                    problem.add_constraint(&terms, op, bound);
+                    return;
+                }
+            };
+            let slack = problem.add_var(1.0 / bound.abs().max(1.0), (0.0, f64::INFINITY));
+            terms.push((slack, sign));
+            problem.add_constraint(&terms, op, bound);
+            relaxed.push((name, slack, sign, bound));
+        };
+        let mut variables = Vec::new();
+        for v in &self.variables {
+            let (low, up) = v.bounds;
+            let var = match v.kind {
+                // negative amounts are never a sensible relaxation, so a zero minimum stays
+                VariableKind::Real => problem.add_var(0.0, (low.min(0.0), f64::INFINITY)),
+                #[allow(clippy::cast_possible_truncation)]
+                VariableKind::Integer => problem.add_integer_var(0.0, (low as i32, up as i32)),
+                VariableKind::Binary => problem.add_binary_var(0.0),
+            };
+            if v.kind == VariableKind::Real {
+                if low > 0.0 {
+                    let name = format!("{} min", v.name);
+                    add_relaxed(&mut problem, name, vec![(var, 1.0)], ComparisonOp::Ge, low);
+                }
+                if up.is_finite() {
+                    let name = format!("{} max", v.name);
+                    add_relaxed(&mut problem, name, vec![(var, 1.0)], ComparisonOp::Le, up);
+                }
+            }
+            variables.push(var);
+        }
+        for c in &self.constraints {
+            let terms = c
+                .terms
+                .iter()
+                .map(|(var, coeff)| (variables[var.idx()], *coeff))
+                .collect();
+            add_relaxed(&mut problem, c.name.clone(), terms, c.op, c.rhs);
+        }
+        let solution = problem.solve().ok()?;
+        Some(
+            relaxed
+                .into_iter()
+                .filter_map(|(name, slack, sign, bound)| {
+                    let value = *solution.var_value(slack);
+                    (value > BINDING_TOLERANCE * bound.abs().max(1.0)).then_some((
+                        name,
+                        bound,
+                        bound - sign * value,
+                    ))
+                })
+                .collect(),
+        )
+    }
+
+    /// Writes the problem in the CPLEX LP format understood by most LP tools.
+    pub fn to_lp_format(&self) -> String {
+        let var_name = |var: Variable| lp_identifier(&self.variables[var.idx()].name, var.idx());
+        let mut lp = String::new();
+        lp.push_str(match self.direction {
+            OptimizationDirection::Minimize => "Minimize\n",
+            OptimizationDirection::Maximize => "Maximize\n",
+        });
+        let objective: Vec<(usize, f64)> = self
+            .variables
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.objective != 0.0)
+            .map(|(idx, v)| (idx, v.objective))
+            .collect();
+        let _ = writeln!(
+            lp,
+            " obj: {}",
+            linear_expression(
+                objective
+                    .iter()
+                    .map(|(idx, coeff)| (lp_identifier(&self.variables[*idx].name, *idx), *coeff))
+            )
+        );
+
+        lp.push_str("Subject To\n");
+        for (idx, c) in self.constraints.iter().enumerate() {
+            let op = match c.op {
+                ComparisonOp::Eq => "=",
+                ComparisonOp::Le => "<=",
+                ComparisonOp::Ge => ">=",
+            };
+            let _ = writeln!(
+                lp,
+                " {}: {} {op} {}",
+                lp_identifier(&c.name, idx),
+                linear_expression(c.terms.iter().map(|(var, coeff)| (var_name(*var), *coeff))),
+                c.rhs
+            );
+        }
+
+        lp.push_str("Bounds\n");
+        for (idx, v) in self.variables.iter().enumerate() {
+            if v.kind != VariableKind::Binary {
+                let _ = writeln!(
+                    lp,
+                    " {} <= {} <= {}",
+                    v.bounds.0,
+                    lp_identifier(&v.name, idx),
+                    v.bounds.1
+                );
+            }
+        }
+        for (section, kind) in [
+            ("General", VariableKind::Integer),
+            ("Binary", VariableKind::Binary),
+        ] {
+            let names: Vec<String> = self
+                .variables
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.kind == kind)
+                .map(|(idx, v)| lp_identifier(&v.name, idx))
+                .collect();
+            if !names.is_empty() {
+                let _ = writeln!(lp, "{section}\n {}", names.join(" "));
+            }
+        }
+        lp.push_str("End\n");
+        lp
+    }
+}
+
+// LP identifiers cannot contain spaces or most punctuation, the index keeps them unique
+fn lp_identifier(name: &str, idx: usize) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{sanitized}_{idx}")
+}
+
+fn linear_expression(terms: impl Iterator<Item = (String, f64)>) -> String {
+    let mut expr = String::new();
+    for (name, coeff) in terms {
+        if expr.is_empty() {
+            let _ = write!(expr, "{coeff} {name}");
+        } else if coeff < 0.0 {
+            let _ = write!(expr, " - {} {name}", -coeff);
+        } else {
+            let _ = write!(expr, " + {coeff} {name}");
+        }
+    }
+    if expr.is_empty() {
+        expr.push('0');
+    }
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_model() -> (LpModel, Variable, Variable) {
+        let mut model = LpModel::new(OptimizationDirection::Maximize);
+        let x = model.add_var("rice grams".to_string(), 1.0, (0.0, 10.0));
+        let y = model.add_integer_var("rice units".to_string(), 2.0, (0, 3));
+        model.add_constraint(
+            "total".to_string(),
+            vec![(x, 1.0), (y, 1.0)],
+            ComparisonOp::Le,
+            5.0,
+        );
+        model.add_constraint(
+            "loose".to_string(),
+            vec![(x, 1.0), (y, -1.0)],
+            ComparisonOp::Ge,
+            -100.0,
+        );
+        (model, x, y)
+    }
+
+    #[test]
+    fn exports_lp_format() {
+        let (model, _, _) = small_model();
+        assert_eq!(
+            model.to_lp_format(),
+            "Maximize\n obj: 1 rice_grams_0 + 2 rice_units_1\n\
+             Subject To\n total_0: 1 rice_grams_0 + 1 rice_units_1 <= 5\n \
+             loose_1: 1 rice_grams_0 - 1 rice_units_1 >= -100\n\
+             Bounds\n 0 <= rice_grams_0 <= 10\n 0 <= rice_units_1 <= 3\n\
+             General\n rice_units_1\nEnd\n"
+        );
+    }
+
+    #[test]
+    fn reports_binding_constraints() {
+        let (model, x, y) = small_model();
+        let solution = model.solve().expect("model should be feasible");
+        assert!((solution.var_value(x) + solution.var_value(y) - 5.0).abs() < 1e-9);
+        assert_eq!(model.binding_constraints(&solution), vec!["total"]);
+    }
+
+    #[test]
+    fn finds_minimal_relaxations() {
+        let (mut model, x, y) = small_model();
+        assert_eq!(model.minimal_relaxations(), Some(Vec::new()));
+
+        model.add_constraint(
+            "at least 8".to_string(),
+            vec![(x, 1.0)],
+            ComparisonOp::Ge,
+            8.0,
+        );
+        // 3 is a smaller part of the minimum than of the total
+        assert_eq!(
+            model.minimal_relaxations(),
+            Some(vec![("at least 8".to_string(), 8.0, 5.0)])
+        );
+
+        // bounds of integer variables are never relaxed
+        model.add_constraint("fixed".to_string(), vec![(y, 1.0)], ComparisonOp::Eq, 5.0);
+        assert_eq!(model.minimal_relaxations(), None);
+    }
+}
@@ -1,4 +1,30 @@
 // TODO: use microlp for linear programming
 // variables are referenced by index, what is the best way to change them to names? inheritance doesnt exist
+pub mod activity_import;
+pub mod analytics;
+pub mod background_solver;
+pub mod compare;
 pub mod constraints_solver;
+pub mod export;
+#[cfg(feature = "test-utils")]
+pub mod golden_plans;
+pub mod grocery_haul;
+pub mod label_parser;
+mod lp_model;
+pub mod notifications;
+pub mod number_format;
+pub mod nutrient_format;
+pub mod presets;
+pub mod preview;
+pub mod quantity;
+pub mod rounding;
+pub mod sanity;
+pub mod scoring;
+pub mod settings;
+pub mod solution_report;
+#[cfg(feature = "solver-sandbox")]
+pub mod solver_sandbox;
 pub mod swap_products;
+pub mod targets;
+pub mod targets_review;
+pub mod tracking;
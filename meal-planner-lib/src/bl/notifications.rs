@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::constraints_solver::{Solution, SolutionEntry};
+use crate::data_types::{MealSlot, MealTime};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationItem {
+    pub product_name: String,
+    pub grams: f64,
+}
+
+/// Data the platform needs to show a reminder, kept language neutral so the app can localize it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationPayload {
+    pub day: String,
+    pub meal: String,
+    pub items: Vec<NotificationItem>,
+}
+
+impl NotificationPayload {
+    /// Lists the meal products, e.g. "150 g Rice + 120 g Chicken".
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for (idx, item) in self.items.iter().enumerate() {
+            if idx > 0 {
+                summary.push_str(" + ");
+            }
+            let _ = write!(summary, "{:.0} g {}", item.grams, item.product_name);
+        }
+        summary
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingNotification {
+    /// Stable per day and meal, so rescheduling a plan replaces earlier reminders.
+    pub id: String,
+    /// Unix timestamp (seconds) the reminder should be shown at.
+    pub fire_at: u64,
+    pub payload: NotificationPayload,
+}
+
+/// Turns the meals of `solution` into reminders. Day `n` of the plan starts `n` days after
+/// `first_day_start` (Unix timestamp of its midnight). Times in `meal_times` take precedence over
+/// the meal slots, meals without any time and reminders before `now` are skipped. The result is ordered by `fire_at`.
+#[must_use]
+pub fn schedule_notifications(
+    solution: &Solution,
+    meal_times: &BTreeMap<String, MealTime>,
+    first_day_start: u64,
+    now: u64,
+) -> Vec<PendingNotification> {
+    let mut pending = Vec::new();
+    for (day_idx, day) in (0u64..).zip(solution.days()) {
+        let SolutionEntry::Day {
+            name: day_name,
+            entries,
+        } = day
+        else {
+            continue;
+        };
+        let day_start = first_day_start + day_idx * SECONDS_PER_DAY;
+        for meal in entries {
+            let SolutionEntry::Meal {
+                name: meal_name,
+                slot,
+                entries,
+            } = meal
+            else {
+                continue;
+            };
+            let Some(time) = meal_times
+                .get(meal_name)
+                .copied()
+                .or_else(|| slot.as_ref().and_then(MealSlot::effective_time))
+            else {
+                continue;
+            };
+            let fire_at = day_start + time.seconds_after_midnight();
+            if fire_at < now {
+                continue;
+            }
+            let items: Vec<NotificationItem> = entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    SolutionEntry::Product {
+                        product,
+                        amount_grams,
+                        ..
+                    } => Some(NotificationItem {
+                        product_name: product.name().to_string(),
+                        grams: *amount_grams,
+                    }),
+                    _ => None,
+                })
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+            pending.push(PendingNotification {
+                id: format!("meal-{day_idx}-{meal_name}"),
+                fire_at,
+                payload: NotificationPayload {
+                    day: day_name.clone(),
+                    meal: meal_name.clone(),
+                    items,
+                },
+            });
+        }
+    }
+    pending.sort_by_key(|n| n.fire_at);
+    pending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::Fraction;
+    use crate::data_types::{AllowedUnitsType, MealKind, ProductBuilder};
+
+    const MONDAY: u64 = 1_700_006_400;
+
+    fn product_entry(name: &str, grams: f64) -> SolutionEntry {
+        SolutionEntry::Product {
+            product: ProductBuilder::new(name)
+                .fat(1.0)
+                .carbs(1.0)
+                .protein(1.0)
+                .build()
+                .unwrap(),
+            amount_grams: grams,
+            unit: AllowedUnitsType::Gram,
+            amount_unit: Fraction {
+                numerator: 1,
+                denominator: 1,
+            },
+        }
+    }
+
+    fn day(name: &str) -> SolutionEntry {
+        SolutionEntry::Day {
+            name: name.to_string(),
+            entries: vec![
+                SolutionEntry::Meal {
+                    name: "Lunch".to_string(),
+                    slot: None,
+                    entries: vec![
+                        product_entry("Rice", 150.0),
+                        product_entry("Chicken", 120.4),
+                    ],
+                },
+                SolutionEntry::Meal {
+                    name: "Breakfast".to_string(),
+                    slot: None,
+                    entries: vec![product_entry("Oats", 80.0)],
+                },
+                SolutionEntry::Meal {
+                    name: "Snack".to_string(),
+                    slot: Some(MealSlot::new(
+                        MealKind::Snack,
+                        Some(MealTime::new(16, 0).unwrap()),
+                    )),
+                    entries: vec![product_entry("Apple", 100.0)],
+                },
+            ],
+        }
+    }
+
+    fn meal_times() -> BTreeMap<String, MealTime> {
+        BTreeMap::from([
+            ("Breakfast".to_string(), MealTime::new(8, 0).unwrap()),
+            ("Lunch".to_string(), MealTime::new(13, 30).unwrap()),
+        ])
+    }
+
+    #[test]
+    fn schedules_timed_meals_in_order() {
+        let solution = Solution {
+            solution: SolutionEntry::Week {
+                entries: vec![day("Monday"), day("Tuesday")],
+            },
+        };
+        let pending = schedule_notifications(&solution, &meal_times(), MONDAY, MONDAY);
+
+        let fire_times: Vec<u64> = pending.iter().map(|n| n.fire_at).collect();
+        assert_eq!(
+            fire_times,
+            vec![
+                MONDAY + 8 * 3600,
+                MONDAY + 13 * 3600 + 1800,
+                MONDAY + 16 * 3600,
+                MONDAY + SECONDS_PER_DAY + 8 * 3600,
+                MONDAY + SECONDS_PER_DAY + 13 * 3600 + 1800,
+                MONDAY + SECONDS_PER_DAY + 16 * 3600,
+            ]
+        );
+        assert_eq!(pending[1].id, "meal-0-Lunch");
+        assert_eq!(pending[1].payload.day, "Monday");
+        assert_eq!(pending[1].payload.summary(), "150 g Rice + 120 g Chicken");
+    }
+
+    #[test]
+    fn skips_reminders_in_the_past() {
+        let solution = Solution {
+            solution: day("Monday"),
+        };
+        let pending = schedule_notifications(&solution, &meal_times(), MONDAY, MONDAY + 9 * 3600);
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].payload.meal, "Lunch");
+        assert_eq!(pending[1].payload.meal, "Snack");
+    }
+}
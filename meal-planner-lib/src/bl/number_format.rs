@@ -0,0 +1,95 @@
+//! Numbers as users type and read them. Languages like Polish write decimals with a comma, so
+//! parsing accepts both separators and formatting follows the language of the app.
+
+// primary language subtags writing `1,5` instead of `1.5`
+const COMMA_DECIMAL_LANGUAGES: &[&str] = &[
+    "bg", "cs", "da", "de", "es", "fi", "fr", "hr", "hu", "it", "lt", "lv", "nb", "nl", "no", "pl",
+    "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr", "uk",
+];
+
+/// Decimal separator used by `language`, a tag like `pl-PL` or `en`.
+#[must_use]
+pub fn decimal_separator(language: &str) -> char {
+    let primary = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if COMMA_DECIMAL_LANGUAGES.contains(&primary.as_str()) {
+        ','
+    } else {
+        '.'
+    }
+}
+
+/// `value` with `decimals` digits after the separator of `language`, without digit grouping so
+/// the text can be parsed back by [`parse_number`].
+#[must_use]
+pub fn format_number(value: f32, decimals: usize, language: &str) -> String {
+    let formatted = format!("{value:.decimals$}");
+    match decimal_separator(language) {
+        '.' => formatted,
+        separator => formatted.replace('.', &separator.to_string()),
+    }
+}
+
+/// Parses a number written with either `.` or `,` as the decimal separator, whatever the
+/// language. When both appear, or one appears several times, the other one groups digits, as
+/// do spaces: `1 234,5`, `1.234,5` and `1,234.5` are all `1234.5`.
+pub fn parse_number(input: &str) -> Result<f32, String> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let last_comma = compact.rfind(',');
+    let last_dot = compact.rfind('.');
+    let decimal = match (last_comma, last_dot) {
+        (Some(comma), Some(dot)) => Some(if comma > dot { ',' } else { '.' }),
+        (Some(_), None) if compact.matches(',').count() == 1 => Some(','),
+        (None, Some(_)) if compact.matches('.').count() == 1 => Some('.'),
+        _ => None,
+    };
+    let normalized: String = compact
+        .chars()
+        .filter_map(|c| match c {
+            ',' | '.' if Some(c) == decimal => Some('.'),
+            ',' | '.' => None,
+            c => Some(c),
+        })
+        .collect();
+    normalized
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("Invalid number '{}'", input.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn parses_both_decimal_separators_and_grouping() {
+        assert_relative_eq!(parse_number("1,5").unwrap(), 1.5);
+        assert_relative_eq!(parse_number(" 1.5 ").unwrap(), 1.5);
+        assert_relative_eq!(parse_number("-0,25").unwrap(), -0.25);
+        assert_relative_eq!(parse_number("1 234,5").unwrap(), 1234.5);
+        assert_relative_eq!(parse_number("1.234,5").unwrap(), 1234.5);
+        assert_relative_eq!(parse_number("1,234.5").unwrap(), 1234.5);
+        assert_relative_eq!(parse_number("1,234,567").unwrap(), 1_234_567.0);
+        assert!(parse_number("").is_err());
+        assert!(parse_number("1,2a").is_err());
+        assert!(parse_number("inf").is_err());
+    }
+
+    #[test]
+    fn formats_with_separator_of_language() {
+        assert_eq!(decimal_separator("pl-PL"), ',');
+        assert_eq!(decimal_separator("en-US"), '.');
+        assert_eq!(decimal_separator("DE"), ',');
+        assert_eq!(format_number(1.5, 2, "pl-PL"), "1,50");
+        assert_eq!(format_number(1234.5, 1, "en-US"), "1234.5");
+        assert_relative_eq!(
+            parse_number(&format_number(12.25, 2, "pl-PL")).unwrap(),
+            12.25
+        );
+    }
+}
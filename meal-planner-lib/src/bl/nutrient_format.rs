@@ -0,0 +1,88 @@
+//! Nutrient amounts as shown to users, in the unit each nutrient is usually read in.
+
+use super::number_format::format_number;
+use crate::data_types::{EnergyUnit, MacroElementsType, NutrientType, NutrientUnit};
+
+/// Unit `nutrient` is shown in, calories in `energy`.
+#[must_use]
+pub fn display_unit(nutrient: NutrientType, energy: EnergyUnit) -> String {
+    match nutrient {
+        NutrientType::Macro(MacroElementsType::Calories) => energy.to_string(),
+        NutrientType::Macro(_) => NutrientUnit::Gram.to_string(),
+        NutrientType::Micro(micro) => micro.info().unit.to_string(),
+    }
+}
+
+/// Digits shown after the decimal separator, energy is never read more precisely than a whole
+/// kcal or kJ.
+#[must_use]
+pub fn display_decimals(nutrient: NutrientType) -> usize {
+    match nutrient {
+        NutrientType::Macro(MacroElementsType::Calories) => 0,
+        _ => 1,
+    }
+}
+
+/// `value`, stored per nutrient as in [`crate::data_types::NutrientInfo`] and in kcal for
+/// calories, with its unit, e.g. `12,5 g` for `pl-PL`. Trailing zeros are left out.
+#[must_use]
+pub fn format_nutrient(
+    nutrient: NutrientType,
+    value: f64,
+    language: &str,
+    energy: EnergyUnit,
+) -> String {
+    let value = match nutrient {
+        NutrientType::Macro(MacroElementsType::Calories) => energy.from_kcal(value),
+        _ => value,
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let number = format_number(value as f32, display_decimals(nutrient), language);
+    let number = if number.contains(['.', ',']) {
+        number.trim_end_matches('0').trim_end_matches(['.', ','])
+    } else {
+        &number
+    };
+    // rounding small negative values gives "-0"
+    let number = if number == "-0" { "0" } else { number };
+    format!("{number} {}", display_unit(nutrient, energy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::MicroNutrientsType;
+
+    #[test]
+    fn formats_with_unit_precision_and_language() {
+        let calories = NutrientType::Macro(MacroElementsType::Calories);
+        let protein = NutrientType::Macro(MacroElementsType::Protein);
+        let vitamin_d = NutrientType::Micro(MicroNutrientsType::VitaminD);
+        let sodium = NutrientType::Micro(MicroNutrientsType::Sodium);
+
+        assert_eq!(
+            format_nutrient(calories, 250.4, "en-US", EnergyUnit::Kcal),
+            "250 kcal"
+        );
+        assert_eq!(
+            format_nutrient(calories, 100.0, "en-US", EnergyUnit::Kj),
+            "418 kJ"
+        );
+        assert_eq!(
+            format_nutrient(protein, 12.54, "pl-PL", EnergyUnit::Kcal),
+            "12,5 g"
+        );
+        assert_eq!(
+            format_nutrient(protein, 42.0, "en-US", EnergyUnit::Kcal),
+            "42 g"
+        );
+        assert_eq!(
+            format_nutrient(vitamin_d, 2.5, "en-US", EnergyUnit::Kcal),
+            "2.5 µg"
+        );
+        assert_eq!(
+            format_nutrient(sodium, -0.01, "en-US", EnergyUnit::Kcal),
+            "0 mg"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use super::targets::{KCAL_PER_G_CARBS_OR_PROTEIN, KCAL_PER_G_FAT, TARGET_TOLERANCE};
+use crate::data_types::{
+    DietaryRestrictions, MacroElementsType,
+    constraints::{DayMealPlanConstraint, NutrientConstraint},
+};
+
+// caps shared by all presets, as shares of the calorie target
+const SATURATED_FAT_SHARE_CAP: f32 = 0.1;
+const SUGAR_SHARE_CAP: f32 = 0.1;
+
+/// Named split of calories between fat, carbs and protein.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
+pub enum MacroPreset {
+    /// 70% fat, 25% protein, 5% carbs.
+    Keto,
+    /// 35% protein, 25% fat, 40% carbs.
+    HighProtein,
+    /// 20% protein, 30% fat, 50% carbs.
+    Balanced,
+}
+
+impl MacroPreset {
+    /// Shares of calories from fat, carbs and protein, summing up to 1.
+    #[must_use]
+    pub fn shares(self) -> (f32, f32, f32) {
+        match self {
+            MacroPreset::Keto => (0.7, 0.05, 0.25),
+            MacroPreset::HighProtein => (0.25, 0.4, 0.35),
+            MacroPreset::Balanced => (0.3, 0.5, 0.2),
+        }
+    }
+
+    /// Converts `calories_kcal` into calorie, fat, carb and protein ranges within 10% of the
+    /// preset split, capping saturated fat and sugar at 10% of the calories each.
+    pub fn nutrient_constraints(
+        self,
+        calories_kcal: f32,
+    ) -> Result<Vec<NutrientConstraint>, String> {
+        if !calories_kcal.is_finite() || calories_kcal <= 0.0 {
+            return Err(format!("Invalid calorie target: {calories_kcal}"));
+        }
+        let (fat_share, carbs_share, protein_share) = self.shares();
+        let around = |element: MacroElementsType, target: f32| {
+            NutrientConstraint::new(
+                element,
+                Some(target * (1.0 - TARGET_TOLERANCE)),
+                Some(target * (1.0 + TARGET_TOLERANCE)),
+            )
+        };
+        let at_most = |element: MacroElementsType, cap: f32| {
+            NutrientConstraint::new(element, None, Some(cap))
+        };
+        Ok([
+            around(MacroElementsType::Calories, calories_kcal),
+            around(
+                MacroElementsType::Fat,
+                calories_kcal * fat_share / KCAL_PER_G_FAT,
+            ),
+            around(
+                MacroElementsType::Carbs,
+                calories_kcal * carbs_share / KCAL_PER_G_CARBS_OR_PROTEIN,
+            ),
+            around(
+                MacroElementsType::Protein,
+                calories_kcal * protein_share / KCAL_PER_G_CARBS_OR_PROTEIN,
+            ),
+            at_most(
+                MacroElementsType::SaturatedFat,
+                calories_kcal * SATURATED_FAT_SHARE_CAP / KCAL_PER_G_FAT,
+            ),
+            at_most(
+                MacroElementsType::Sugar,
+                calories_kcal * SUGAR_SHARE_CAP / KCAL_PER_G_CARBS_OR_PROTEIN,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect())
+    }
+
+    /// Day constraint with the preset nutrients and no meals yet.
+    pub fn day_constraint(self, calories_kcal: f32) -> Result<DayMealPlanConstraint, String> {
+        Ok(DayMealPlanConstraint {
+            meals: HashMap::new(),
+            nutrients: self.nutrient_constraints(calories_kcal)?,
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        })
+    }
+}
+
+impl fmt::Display for MacroPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MacroPreset::Keto => "keto",
+            MacroPreset::HighProtein => "high_protein",
+            MacroPreset::Balanced => "balanced",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for MacroPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keto" => Ok(MacroPreset::Keto),
+            "high_protein" => Ok(MacroPreset::HighProtein),
+            "balanced" => Ok(MacroPreset::Balanced),
+            _ => Err(format!("Unknown macro preset '{s}'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::NutrientType;
+    use approx::assert_relative_eq;
+    use strum::IntoEnumIterator;
+
+    fn bounds(constraints: &[NutrientConstraint], element: MacroElementsType) -> (f32, f32) {
+        let constraint = constraints
+            .iter()
+            .find(|c| c.element() == NutrientType::Macro(element))
+            .unwrap();
+        (constraint.min().unwrap_or(0.0), constraint.max().unwrap())
+    }
+
+    #[test]
+    fn shares_sum_to_one() {
+        for preset in MacroPreset::iter() {
+            let (fat, carbs, protein) = preset.shares();
+            assert_relative_eq!(fat + carbs + protein, 1.0, epsilon = 1e-6);
+            assert_eq!(preset.to_string().parse::<MacroPreset>(), Ok(preset));
+        }
+        assert!("paleo".parse::<MacroPreset>().is_err());
+    }
+
+    #[test]
+    fn converts_calories_to_gram_ranges() {
+        let constraints = MacroPreset::Keto.nutrient_constraints(2000.0).unwrap();
+        assert_eq!(constraints.len(), 6);
+        // 1400 kcal of fat, 100 kcal of carbs, 500 kcal of protein
+        let (min, max) = bounds(&constraints, MacroElementsType::Fat);
+        assert_relative_eq!(min, 1400.0 / 9.0 * 0.9, epsilon = 1e-3);
+        assert_relative_eq!(max, 1400.0 / 9.0 * 1.1, epsilon = 1e-3);
+        assert_relative_eq!(
+            bounds(&constraints, MacroElementsType::Carbs).1,
+            27.5,
+            epsilon = 1e-3
+        );
+        assert_relative_eq!(
+            bounds(&constraints, MacroElementsType::Protein).0,
+            112.5,
+            epsilon = 1e-3
+        );
+        assert_eq!(bounds(&constraints, MacroElementsType::Sugar), (0.0, 50.0));
+    }
+
+    #[test]
+    fn day_constraint_has_no_meals() {
+        let day = MacroPreset::Balanced.day_constraint(1800.0).unwrap();
+        assert!(day.meals.is_empty());
+        assert_eq!(day.nutrients.len(), 6);
+        assert_eq!(
+            MacroPreset::Balanced.day_constraint(-5.0).err(),
+            Some("Invalid calorie target: -5".to_string())
+        );
+    }
+}
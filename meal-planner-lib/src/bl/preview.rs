@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use super::constraints_solver::NutrientTotals;
+use crate::data_types::ProductId;
+use crate::data_types::constraints::{MealConstraint, ProductConstraint};
+
+/// Amount a product is previewed at when the user hasn't picked one: the mid-point of its
+/// bounds, the lower bound when there is no upper one. In the grams the bounds are given in.
+#[must_use]
+pub fn default_amount(constraint: &ProductConstraint) -> f32 {
+    match (constraint.low_bound(), constraint.up_bound()) {
+        (low, Some(up)) => f32::midpoint(low.unwrap_or(0.0), up),
+        (low, None) => low.unwrap_or(0.0),
+    }
+}
+
+/// Nutrient totals of `meal` with its products at `assumed_amounts`, without solving, e.g. for
+/// live totals while amounts are being picked. Amounts are in the grams the bounds are given
+/// in, products without one are at their [`default_amount`].
+#[must_use]
+pub fn estimate_meal(
+    meal: &MealConstraint,
+    assumed_amounts: &BTreeMap<ProductId, f32>,
+) -> NutrientTotals {
+    let products: Vec<_> = meal
+        .products
+        .iter()
+        .map(|constraint| {
+            let grams = assumed_amounts
+                .get(&constraint.food().id())
+                .copied()
+                .unwrap_or_else(|| default_amount(constraint));
+            (constraint.food(), f64::from(constraint.to_raw(grams)))
+        })
+        .collect();
+    NutrientTotals::of_products(&products)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{
+        AllowedUnitsType, MacroElements, MacroElementsType, NutrientType, Product, UnitData,
+    };
+    use approx::assert_relative_eq;
+
+    fn product(name: &str, protein: f32) -> Box<Product> {
+        let mut product = Product::new(
+            name.to_string(),
+            None,
+            Box::new(MacroElements::new(0.0, 0.0, 0.0, 0.0, protein)),
+            Box::default(),
+            std::collections::HashMap::new(),
+        );
+        product.allowed_units.insert(
+            AllowedUnitsType::Gram,
+            UnitData {
+                amount: 1,
+                divider: 1,
+            },
+        );
+        Box::new(product)
+    }
+
+    #[test]
+    fn test_estimate_meal_uses_mid_point_unless_amount_is_assumed() {
+        let chicken = product("Chicken", 20.0);
+        let rice = product("Rice", 10.0);
+        let chicken_id = chicken.id();
+        let meal = MealConstraint {
+            products: vec![
+                ProductConstraint::new(chicken, Some(100), Some(300), AllowedUnitsType::Gram)
+                    .unwrap(),
+                ProductConstraint::new(rice, Some(50), None, AllowedUnitsType::Gram).unwrap(),
+            ],
+            ..Default::default()
+        };
+        let protein = NutrientType::Macro(MacroElementsType::Protein);
+
+        let totals = estimate_meal(&meal, &BTreeMap::new());
+        // 200g of chicken and 50g of rice
+        assert_relative_eq!(totals.get(protein), 45.0, epsilon = 1e-6);
+
+        let totals = estimate_meal(&meal, &BTreeMap::from([(chicken_id, 100.0)]));
+        assert_relative_eq!(totals.get(protein), 25.0, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,173 @@
+//! Masses with their unit, so amounts can't be mixed up between grams and imperial units.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::number_format::format_number;
+
+const GRAMS_PER_OUNCE: f64 = 28.349_523_125;
+const GRAMS_PER_POUND: f64 = 453.592_37;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MassUnit {
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+}
+
+impl MassUnit {
+    #[must_use]
+    pub fn grams_per_unit(self) -> f64 {
+        match self {
+            MassUnit::Gram => 1.0,
+            MassUnit::Kilogram => 1000.0,
+            MassUnit::Ounce => GRAMS_PER_OUNCE,
+            MassUnit::Pound => GRAMS_PER_POUND,
+        }
+    }
+
+    #[must_use]
+    pub fn symbol(self) -> &'static str {
+        match self {
+            MassUnit::Gram => "g",
+            MassUnit::Kilogram => "kg",
+            MassUnit::Ounce => "oz",
+            MassUnit::Pound => "lb",
+        }
+    }
+}
+
+impl fmt::Display for MassUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Non-negative, finite mass. Arithmetic keeps the unit of the left operand and returns `None`
+/// instead of an invalid quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    value: f64,
+    unit: MassUnit,
+}
+
+impl Quantity {
+    #[must_use]
+    pub fn new(value: f64, unit: MassUnit) -> Option<Self> {
+        (value.is_finite() && value >= 0.0).then_some(Self { value, unit })
+    }
+
+    /// Quantity of `grams`, negative rounding leftovers of the solver become zero.
+    #[must_use]
+    pub fn from_grams(grams: f64) -> Self {
+        Self {
+            value: if grams.is_finite() {
+                grams.max(0.0)
+            } else {
+                0.0
+            },
+            unit: MassUnit::Gram,
+        }
+    }
+
+    #[must_use]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    #[must_use]
+    pub fn unit(&self) -> MassUnit {
+        self.unit
+    }
+
+    #[must_use]
+    pub fn grams(&self) -> f64 {
+        self.value * self.unit.grams_per_unit()
+    }
+
+    /// The same mass expressed in `unit`.
+    #[must_use]
+    pub fn to(&self, unit: MassUnit) -> Self {
+        Self {
+            value: self.grams() / unit.grams_per_unit(),
+            unit,
+        }
+    }
+
+    #[must_use]
+    pub fn checked_add(self, other: Quantity) -> Option<Self> {
+        Self::new(self.value + other.to(self.unit).value, self.unit)
+    }
+
+    /// `None` when `other` is more than `self`.
+    #[must_use]
+    pub fn checked_sub(self, other: Quantity) -> Option<Self> {
+        Self::new(self.value - other.to(self.unit).value, self.unit)
+    }
+
+    /// `None` for negative or non-finite factors.
+    #[must_use]
+    pub fn checked_mul(self, factor: f64) -> Option<Self> {
+        Self::new(self.value * factor, self.unit)
+    }
+
+    /// Value with `decimals` digits in the number format of `language`, followed by the unit.
+    #[must_use]
+    pub fn format(&self, decimals: usize, language: &str) -> String {
+        #[allow(clippy::cast_possible_truncation)]
+        let value = format_number(self.value as f32, decimals, language);
+        format!("{value} {}", self.unit)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.precision$} {}", self.value, self.unit),
+            None => write!(f, "{} {}", self.value, self.unit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn converts_between_units() {
+        let pound = Quantity::new(1.0, MassUnit::Pound).unwrap();
+        assert_relative_eq!(pound.grams(), 453.592_37);
+        assert_relative_eq!(pound.to(MassUnit::Ounce).value(), 16.0, epsilon = 1e-9);
+        assert_relative_eq!(
+            Quantity::from_grams(1500.0).to(MassUnit::Kilogram).value(),
+            1.5
+        );
+        assert!(Quantity::new(-1.0, MassUnit::Gram).is_none());
+        assert!(Quantity::new(f64::NAN, MassUnit::Gram).is_none());
+        assert_relative_eq!(Quantity::from_grams(-1e-9).value(), 0.0);
+    }
+
+    #[test]
+    fn arithmetic_is_checked() {
+        let kilo = Quantity::new(1.0, MassUnit::Kilogram).unwrap();
+        let grams = Quantity::from_grams(250.0);
+        let sum = kilo.checked_add(grams).unwrap();
+        assert_eq!(sum.unit(), MassUnit::Kilogram);
+        assert_relative_eq!(sum.value(), 1.25);
+        assert_relative_eq!(kilo.checked_sub(grams).unwrap().grams(), 750.0);
+        assert!(grams.checked_sub(kilo).is_none());
+        assert_relative_eq!(grams.checked_mul(2.0).unwrap().grams(), 500.0);
+        assert!(grams.checked_mul(-1.0).is_none());
+    }
+
+    #[test]
+    fn formats_for_the_language() {
+        let amount = Quantity::new(1.25, MassUnit::Kilogram).unwrap();
+        assert_eq!(amount.format(1, "pl-PL"), "1,2 kg");
+        assert_eq!(amount.format(2, "en-US"), "1.25 kg");
+        assert_eq!(format!("{:.1}", Quantity::from_grams(80.0)), "80.0 g");
+    }
+}
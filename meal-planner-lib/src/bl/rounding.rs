@@ -0,0 +1,376 @@
+//! Rounding of solved amounts to values practical in a kitchen, e.g. 5g steps or half pieces.
+//! The solver doesn't know about it, so constraints are checked again after rounding.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bl::constraints_solver::{Fraction, Solution, SolutionEntry};
+use crate::data_types::{
+    AllowedUnitsType, NutrientType, ProductId,
+    constraints::{DayMealPlanConstraint, WeekMealPlanConstraint},
+};
+
+// rounding leaves amounts this far outside of a bound without reporting it
+const TOLERANCE: f64 = 1e-6;
+
+/// How solved amounts are rounded, see [`crate::bl::constraints_solver::SolverOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RoundingPolicy {
+    /// Step in grams products planned in grams are rounded to. `None` keeps them as solved.
+    pub gram_step: Option<f64>,
+    /// Parts of a unit other units are rounded to, e.g. 2 for half units. `None` keeps them as
+    /// solved.
+    pub unit_parts: Option<u16>,
+}
+
+impl Default for RoundingPolicy {
+    /// Nearest 5g and nearest half unit.
+    fn default() -> Self {
+        Self {
+            gram_step: Some(5.0),
+            unit_parts: Some(2),
+        }
+    }
+}
+
+/// Constraint a rounded amount broke.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RoundedConstraint {
+    Nutrient(NutrientType),
+    /// Amount bounds of a product, in grams.
+    Product(ProductId),
+    Cost,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundingViolation {
+    pub day: String,
+    /// Meal the constraint belongs to, `None` for day constraints.
+    pub meal: Option<String>,
+    pub constraint: RoundedConstraint,
+    /// Amount after rounding.
+    pub amount: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl RoundingPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(step) = self.gram_step
+            && (!step.is_finite() || step <= 0.0)
+        {
+            return Err(format!("Invalid rounding step {step}g"));
+        }
+        if self.unit_parts == Some(0) {
+            return Err("Units can't be rounded to zero parts".to_string());
+        }
+        Ok(())
+    }
+
+    /// Rounds every product amount of `solution`. Negligible products keep their amounts.
+    pub fn round(&self, solution: &mut Solution) -> Result<(), String> {
+        self.validate()?;
+        self.round_entry(&mut solution.solution);
+        Ok(())
+    }
+
+    /// Rounds the first day of `solution` solved from `day` and reports the constraints of
+    /// `day` the rounded amounts break.
+    pub fn round_day(
+        &self,
+        solution: &mut Solution,
+        day: &DayMealPlanConstraint,
+    ) -> Result<Vec<RoundingViolation>, String> {
+        self.round(solution)?;
+        Ok(solution
+            .days()
+            .first()
+            .map(|entry| check_day(entry, day))
+            .unwrap_or_default())
+    }
+
+    /// Rounds `solution` of `week` and reports the constraints of its days the rounded amounts
+    /// break.
+    pub fn round_week(
+        &self,
+        solution: &mut Solution,
+        week: &WeekMealPlanConstraint,
+    ) -> Result<Vec<RoundingViolation>, String> {
+        self.round(solution)?;
+        Ok(solution
+            .days()
+            .into_iter()
+            .zip(&week.days)
+            .flat_map(|(entry, (_, day))| check_day(entry, day))
+            .collect())
+    }
+
+    fn round_entry(&self, entry: &mut SolutionEntry) {
+        match entry {
+            SolutionEntry::Week { entries }
+            | SolutionEntry::Day { entries, .. }
+            | SolutionEntry::Meal { entries, .. } => {
+                for inner in entries {
+                    self.round_entry(inner);
+                }
+            }
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                unit,
+                amount_unit,
+            } if !product.negligible => {
+                // a unit weighs `amount` grams
+                let unit_grams = product
+                    .allowed_units
+                    .get(unit)
+                    .map_or(1.0, |data| f64::from(data.amount));
+                let parts = if *unit == AllowedUnitsType::Gram {
+                    self.gram_step.map(|step| unit_grams / step)
+                } else {
+                    self.unit_parts.map(f64::from)
+                };
+                let Some(parts) = parts else {
+                    return;
+                };
+                let rounded_parts = (*amount_grams / unit_grams * parts).round();
+                *amount_grams = rounded_parts * unit_grams / parts;
+                // grams are counted whole, other units in `unit_parts`
+                let (units, denominator) = if *unit == AllowedUnitsType::Gram {
+                    ((*amount_grams / unit_grams).round(), 1)
+                } else {
+                    (rounded_parts, self.unit_parts.unwrap_or(1))
+                };
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let numerator = units.min(f64::from(u16::MAX)) as u16;
+                *amount_unit = Fraction {
+                    numerator,
+                    denominator,
+                };
+            }
+            SolutionEntry::Product { .. } => {}
+        }
+    }
+}
+
+fn check_day(entry: &SolutionEntry, day: &DayMealPlanConstraint) -> Vec<RoundingViolation> {
+    let SolutionEntry::Day {
+        name: day_name,
+        entries,
+    } = entry
+    else {
+        return Vec::new();
+    };
+    let mut violations = Vec::new();
+    let mut violation =
+        |meal: Option<&String>, constraint, amount: f64, min: Option<f64>, max: Option<f64>| {
+            let below = min.is_some_and(|min| amount < min - TOLERANCE);
+            let above = max.is_some_and(|max| amount > max + TOLERANCE);
+            if below || above {
+                violations.push(RoundingViolation {
+                    day: day_name.clone(),
+                    meal: meal.cloned(),
+                    constraint,
+                    amount,
+                    min,
+                    max,
+                });
+            }
+        };
+
+    for nutrient in &day.nutrients {
+        violation(
+            None,
+            RoundedConstraint::Nutrient(nutrient.element()),
+            entry.nutrient_total(nutrient.element()),
+            nutrient.min().map(f64::from),
+            nutrient.max().map(f64::from),
+        );
+    }
+    if let Some(max_cost) = day.max_cost {
+        violation(
+            None,
+            RoundedConstraint::Cost,
+            cost(entry),
+            None,
+            Some(f64::from(max_cost)),
+        );
+    }
+    for meal_entry in entries {
+        let SolutionEntry::Meal {
+            name,
+            entries: products,
+            ..
+        } = meal_entry
+        else {
+            continue;
+        };
+        let Some(meal) = day.meals.get(name) else {
+            continue;
+        };
+        for nutrient in &meal.nutrients {
+            violation(
+                Some(name),
+                RoundedConstraint::Nutrient(nutrient.element()),
+                meal_entry.nutrient_total(nutrient.element()),
+                nutrient.min().map(f64::from),
+                nutrient.max().map(f64::from),
+            );
+        }
+        let selectable = meal.max_products.is_some() || meal.min_products.is_some();
+        for product_entry in products {
+            let SolutionEntry::Product {
+                product,
+                amount_grams,
+                ..
+            } = product_entry
+            else {
+                continue;
+            };
+            let product_id = product.id();
+            let Some(constraint) = meal.products.iter().find(|p| p.food().id() == product_id)
+            else {
+                continue;
+            };
+            // low bounds of selectable meals only apply to picked products
+            let min = constraint
+                .raw_low_bound()
+                .filter(|_| !selectable || *amount_grams > 0.0)
+                .map(f64::from);
+            violation(
+                Some(name),
+                RoundedConstraint::Product(product_id),
+                *amount_grams,
+                min,
+                constraint.raw_up_bound().map(f64::from),
+            );
+        }
+    }
+    violations
+}
+
+// price of the products of `entry` counted by the solver's cost constraint
+fn cost(entry: &SolutionEntry) -> f64 {
+    match entry {
+        SolutionEntry::Week { entries }
+        | SolutionEntry::Day { entries, .. }
+        | SolutionEntry::Meal { entries, .. } => entries.iter().map(cost).sum(),
+        SolutionEntry::Product {
+            product,
+            amount_grams,
+            ..
+        } if !product.negligible => {
+            f64::from(product.price.as_ref().map_or(0.0, |p| p.per_100g)) * amount_grams / 100.0
+        }
+        SolutionEntry::Product { .. } => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{
+        MacroElementsType, Product, ProductBuilder, constraints::DayConstraintBuilder,
+    };
+    use approx::assert_relative_eq;
+
+    fn entry(product: &Product, amount_grams: f64, unit: AllowedUnitsType) -> SolutionEntry {
+        SolutionEntry::Product {
+            product: product.clone(),
+            amount_grams,
+            unit,
+            amount_unit: Fraction {
+                numerator: 0,
+                denominator: 1,
+            },
+        }
+    }
+
+    fn grams(solution: &Solution) -> Vec<f64> {
+        let SolutionEntry::Day { entries, .. } = &solution.solution else {
+            panic!("Expected a day");
+        };
+        let SolutionEntry::Meal { entries, .. } = &entries[0] else {
+            panic!("Expected a meal");
+        };
+        entries
+            .iter()
+            .map(|entry| match entry {
+                SolutionEntry::Product { amount_grams, .. } => *amount_grams,
+                _ => panic!("Expected a product"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rounds_amounts_and_reports_broken_constraints() {
+        let rice = ProductBuilder::new("Rice")
+            .carbs(80.0)
+            .unit(AllowedUnitsType::Gram, 1, 1)
+            .build()
+            .unwrap();
+        let egg = ProductBuilder::new("Egg")
+            .protein(12.0)
+            .fat(10.0)
+            .unit(AllowedUnitsType::Piece, 60, 1)
+            .build()
+            .unwrap();
+        let day = DayConstraintBuilder::new()
+            .meal("Lunch", |m| {
+                m.product_fractional(rice.clone(), ..=101.0, AllowedUnitsType::Gram)
+                    .product(egg.clone(), .., AllowedUnitsType::Piece)
+            })
+            .nutrient(MacroElementsType::Protein, 10.0..)
+            .build()
+            .unwrap();
+        let plan = || Solution {
+            solution: SolutionEntry::Day {
+                name: "Monday".to_string(),
+                entries: vec![SolutionEntry::Meal {
+                    name: "Lunch".to_string(),
+                    slot: None,
+                    entries: vec![
+                        entry(&rice, 101.0, AllowedUnitsType::Gram),
+                        entry(&egg, 82.0, AllowedUnitsType::Piece),
+                    ],
+                }],
+            },
+        };
+
+        let mut solution = plan();
+        let violations = RoundingPolicy::default()
+            .round_day(&mut solution, &day)
+            .unwrap();
+        assert_eq!(grams(&solution), vec![100.0, 90.0]);
+        let SolutionEntry::Day { entries, .. } = &solution.solution else {
+            panic!("Expected a day");
+        };
+        let SolutionEntry::Meal { entries, .. } = &entries[0] else {
+            panic!("Expected a meal");
+        };
+        let SolutionEntry::Product { amount_unit, .. } = &entries[1] else {
+            panic!("Expected a product");
+        };
+        assert_eq!((amount_unit.numerator, amount_unit.denominator), (3, 2));
+        assert!(violations.is_empty());
+
+        let coarse = RoundingPolicy {
+            gram_step: Some(50.0),
+            unit_parts: Some(1),
+        };
+        let mut solution = plan();
+        let violations = coarse.round_day(&mut solution, &day).unwrap();
+        assert_eq!(grams(&solution), vec![100.0, 60.0]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].constraint,
+            RoundedConstraint::Nutrient(NutrientType::Macro(MacroElementsType::Protein))
+        );
+        assert_relative_eq!(violations[0].amount, 7.2);
+
+        let invalid = RoundingPolicy {
+            gram_step: Some(0.0),
+            unit_parts: None,
+        };
+        assert!(invalid.round(&mut solution).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Checks of user entered constraints against nutrient reference intakes, run before solving so
+//! contradictory or unhealthy bounds can be pointed out instead of silently followed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::{
+    MacroElementsType, NutrientType, UserProfile,
+    constraints::{DayMealPlanConstraint, NutrientConstraint},
+};
+
+// protein maxima below this many grams per kg of body weight are too low for any diet
+const MIN_PROTEIN_G_PER_KG: f32 = 0.4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SanityWarning {
+    /// The minimum of the day, or of `meal`, is above the recommended daily maximum.
+    MinAboveRecommendedMax {
+        meal: Option<String>,
+        nutrient: NutrientType,
+        min: f32,
+        recommended_max: f32,
+    },
+    /// The day maximum doesn't allow reaching the recommended daily intake.
+    MaxBelowRecommendedIntake {
+        nutrient: NutrientType,
+        max: f32,
+        recommended: f32,
+    },
+    /// The day protein maximum is below 0.4 g per kg of the user weight.
+    ProteinMaxTooLow { max: f32, minimum: f32 },
+    /// Meal minimums add up to more than the day maximum, so no plan can meet both.
+    MealMinimumsAboveDayMax {
+        nutrient: NutrientType,
+        meals_min: f32,
+        day_max: f32,
+    },
+    /// Every meal has a maximum and together they stay below the day minimum.
+    MealMaximumsBelowDayMin {
+        nutrient: NutrientType,
+        meals_max: f32,
+        day_min: f32,
+    },
+}
+
+/// Warnings about the bounds of `day`, day constraints first, then meals by name. The protein
+/// check needs the user `profile`.
+#[must_use]
+pub fn check_constraints(
+    day: &DayMealPlanConstraint,
+    profile: Option<&UserProfile>,
+) -> Vec<SanityWarning> {
+    let mut meal_names: Vec<&String> = day.meals.keys().collect();
+    meal_names.sort();
+
+    let mut warnings = Vec::new();
+    for constraint in &day.nutrients {
+        warnings.extend(min_above_recommended_max(None, constraint));
+        let nutrient = constraint.element();
+        if let (Some(max), Some(recommended)) = (constraint.max(), recommended_intake(nutrient))
+            && max < recommended
+        {
+            warnings.push(SanityWarning::MaxBelowRecommendedIntake {
+                nutrient,
+                max,
+                recommended,
+            });
+        }
+        if nutrient == NutrientType::Macro(MacroElementsType::Protein)
+            && let (Some(max), Some(profile)) = (constraint.max(), profile)
+        {
+            let minimum = MIN_PROTEIN_G_PER_KG * profile.weight_kg;
+            if max < minimum {
+                warnings.push(SanityWarning::ProteinMaxTooLow { max, minimum });
+            }
+        }
+
+        let meal_bounds: Vec<Option<&NutrientConstraint>> = meal_names
+            .iter()
+            .map(|name| {
+                day.meals[*name]
+                    .nutrients
+                    .iter()
+                    .find(|c| c.element() == nutrient)
+            })
+            .collect();
+        if let Some(day_max) = constraint.max() {
+            let meals_min: f32 = meal_bounds
+                .iter()
+                .filter_map(|c| c.and_then(NutrientConstraint::min))
+                .sum();
+            if meals_min > day_max {
+                warnings.push(SanityWarning::MealMinimumsAboveDayMax {
+                    nutrient,
+                    meals_min,
+                    day_max,
+                });
+            }
+        }
+        if let Some(day_min) = constraint.min()
+            && let Some(meals_max) = meal_bounds
+                .iter()
+                .map(|c| c.and_then(NutrientConstraint::max))
+                .sum::<Option<f32>>()
+            && meals_max < day_min
+        {
+            warnings.push(SanityWarning::MealMaximumsBelowDayMin {
+                nutrient,
+                meals_max,
+                day_min,
+            });
+        }
+    }
+
+    for name in meal_names {
+        for constraint in &day.meals[name].nutrients {
+            warnings.extend(min_above_recommended_max(Some(name), constraint));
+        }
+    }
+    warnings
+}
+
+fn min_above_recommended_max(
+    meal: Option<&str>,
+    constraint: &NutrientConstraint,
+) -> Option<SanityWarning> {
+    let nutrient = constraint.element();
+    let min = constraint.min()?;
+    let recommended_max = match nutrient {
+        NutrientType::Micro(micro) => micro.info().adult_max?,
+        NutrientType::Macro(_) => return None,
+    };
+    (min > recommended_max).then(|| SanityWarning::MinAboveRecommendedMax {
+        meal: meal.map(str::to_string),
+        nutrient,
+        min,
+        recommended_max,
+    })
+}
+
+fn recommended_intake(nutrient: NutrientType) -> Option<f32> {
+    match nutrient {
+        NutrientType::Micro(micro) => micro.info().adult_rda,
+        NutrientType::Macro(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::data_types::{
+        ActivityLevel, DietaryRestrictions, MicroNutrientsType, Sex, constraints::MealConstraint,
+    };
+
+    fn meal(nutrients: Vec<NutrientConstraint>) -> MealConstraint {
+        MealConstraint {
+            products: Vec::new(),
+            nutrients,
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        }
+    }
+
+    fn day(
+        nutrients: Vec<NutrientConstraint>,
+        meals: Vec<(&str, MealConstraint)>,
+    ) -> DayMealPlanConstraint {
+        DayMealPlanConstraint {
+            meals: meals
+                .into_iter()
+                .map(|(name, meal)| (name.to_string(), meal))
+                .collect::<HashMap<_, _>>(),
+            nutrients,
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        }
+    }
+
+    fn profile(weight_kg: f32) -> UserProfile {
+        UserProfile {
+            age_years: 30,
+            sex: Sex::Female,
+            weight_kg,
+            height_cm: 170.0,
+            activity_level: ActivityLevel::Moderate,
+        }
+    }
+
+    #[test]
+    fn reasonable_constraints_have_no_warnings() {
+        let day = day(
+            vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(80.0), Some(150.0))
+                    .unwrap(),
+                NutrientConstraint::new(MicroNutrientsType::Sodium, None, Some(2000.0)).unwrap(),
+            ],
+            vec![(
+                "Lunch",
+                meal(vec![
+                    NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap(),
+                ]),
+            )],
+        );
+        assert!(check_constraints(&day, Some(&profile(70.0))).is_empty());
+    }
+
+    #[test]
+    fn unhealthy_bounds_are_reported() {
+        let day = day(
+            vec![
+                NutrientConstraint::new(MicroNutrientsType::Sodium, Some(2500.0), None).unwrap(),
+                NutrientConstraint::new(MicroNutrientsType::Fiber, None, Some(10.0)).unwrap(),
+                NutrientConstraint::new(MacroElementsType::Protein, None, Some(20.0)).unwrap(),
+            ],
+            vec![(
+                "Dinner",
+                meal(vec![
+                    NutrientConstraint::new(MicroNutrientsType::Zinc, Some(30.0), None).unwrap(),
+                ]),
+            )],
+        );
+        assert_eq!(
+            check_constraints(&day, Some(&profile(80.0))),
+            vec![
+                SanityWarning::MinAboveRecommendedMax {
+                    meal: None,
+                    nutrient: NutrientType::Micro(MicroNutrientsType::Sodium),
+                    min: 2500.0,
+                    recommended_max: 2000.0,
+                },
+                SanityWarning::MaxBelowRecommendedIntake {
+                    nutrient: NutrientType::Micro(MicroNutrientsType::Fiber),
+                    max: 10.0,
+                    recommended: 25.0,
+                },
+                SanityWarning::ProteinMaxTooLow {
+                    max: 20.0,
+                    minimum: 32.0,
+                },
+                SanityWarning::MinAboveRecommendedMax {
+                    meal: Some("Dinner".to_string()),
+                    nutrient: NutrientType::Micro(MicroNutrientsType::Zinc),
+                    min: 30.0,
+                    recommended_max: 25.0,
+                },
+            ]
+        );
+        // without the weight the protein maximum can't be judged
+        assert_eq!(check_constraints(&day, None).len(), 3);
+    }
+
+    #[test]
+    fn meal_bounds_contradicting_the_day_are_reported() {
+        let protein = |min, max| NutrientConstraint::new(MacroElementsType::Protein, min, max);
+        let overlapping = day(
+            vec![protein(Some(100.0), Some(120.0)).unwrap()],
+            vec![
+                (
+                    "Breakfast",
+                    meal(vec![protein(Some(70.0), Some(80.0)).unwrap()]),
+                ),
+                (
+                    "Lunch",
+                    meal(vec![protein(Some(60.0), Some(90.0)).unwrap()]),
+                ),
+            ],
+        );
+        assert_eq!(
+            check_constraints(&overlapping, None),
+            vec![SanityWarning::MealMinimumsAboveDayMax {
+                nutrient: NutrientType::Macro(MacroElementsType::Protein),
+                meals_min: 130.0,
+                day_max: 120.0,
+            }]
+        );
+
+        let too_small = day(
+            vec![protein(Some(100.0), None).unwrap()],
+            vec![
+                ("Breakfast", meal(vec![protein(None, Some(30.0)).unwrap()])),
+                ("Lunch", meal(vec![protein(None, Some(40.0)).unwrap()])),
+            ],
+        );
+        assert_eq!(
+            check_constraints(&too_small, None),
+            vec![SanityWarning::MealMaximumsBelowDayMin {
+                nutrient: NutrientType::Macro(MacroElementsType::Protein),
+                meals_max: 70.0,
+                day_min: 100.0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Healthiness of products and plans: a Nutri-Score like grade and a custom score with
+//! configurable weights. Both are computed per 100g, plans are scored by what their meals and days
+//! are made of. Fruit and vegetable content isn't known, so it never earns points.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bl::constraints_solver::{Solution, SolutionEntry};
+use crate::data_types::{MacroElementsType, MicroNutrientsType, NutrientType, Product};
+
+const KJ_PER_KCAL: f64 = 4.184;
+// negative points of a food above which protein doesn't earn points anymore
+const PROTEIN_CUTOFF_POINTS: i32 = 11;
+
+const ENERGY_KJ: [f64; 10] = [
+    335.0, 670.0, 1005.0, 1340.0, 1675.0, 2010.0, 2345.0, 2680.0, 3015.0, 3350.0,
+];
+const SUGAR_G: [f64; 10] = [4.5, 9.0, 13.5, 18.0, 22.5, 27.0, 31.0, 36.0, 40.0, 45.0];
+const SATURATED_FAT_G: [f64; 10] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+const SODIUM_MG: [f64; 10] = [
+    90.0, 180.0, 270.0, 360.0, 450.0, 540.0, 630.0, 720.0, 810.0, 900.0,
+];
+const FIBER_G: [f64; 5] = [0.9, 1.9, 2.8, 3.7, 4.7];
+const PROTEIN_G: [f64; 5] = [1.6, 3.2, 4.8, 6.4, 8.0];
+
+/// Nutri-Score like grade, `A` is the healthiest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum NutriGrade {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl NutriGrade {
+    /// Grade of a Nutri-Score point total, lower totals are healthier.
+    #[must_use]
+    pub fn from_points(points: i32) -> Self {
+        match points {
+            ..=-1 => Self::A,
+            0..=2 => Self::B,
+            3..=10 => Self::C,
+            11..=18 => Self::D,
+            _ => Self::E,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NutritionScore {
+    /// Nutri-Score points, from -10 to 40.
+    pub points: i32,
+    pub grade: NutriGrade,
+    /// Score by [`ScoreWeights`], higher is healthier.
+    pub custom: f64,
+}
+
+/// Scores of every meal and day of a plan. Parts without any counted grams have no score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolutionScore {
+    pub days: Vec<(String, NutritionScore)>,
+    /// `(day, meal, score)` in plan order.
+    pub meals: Vec<(String, String, NutritionScore)>,
+}
+
+/// Weights of the custom score, which adds up `weight * amount per 100g` of every listed
+/// nutrient. Missing micro nutrients count as zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreWeights {
+    pub weights: Vec<(NutrientType, f64)>,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            weights: vec![
+                (NutrientType::Macro(MacroElementsType::Protein), 1.0),
+                (NutrientType::Micro(MicroNutrientsType::Fiber), 2.0),
+                (NutrientType::Macro(MacroElementsType::Sugar), -1.0),
+                (NutrientType::Macro(MacroElementsType::SaturatedFat), -1.5),
+                (NutrientType::Micro(MicroNutrientsType::Sodium), -0.005),
+            ],
+        }
+    }
+}
+
+impl ScoreWeights {
+    #[must_use]
+    pub fn score_product(&self, product: &Product) -> NutritionScore {
+        self.score(|nutrient| f64::from(product.get_nutrient_amount(nutrient).unwrap_or(0.0)))
+    }
+
+    #[must_use]
+    pub fn score_solution(&self, solution: &Solution) -> SolutionScore {
+        let mut score = SolutionScore {
+            days: Vec::new(),
+            meals: Vec::new(),
+        };
+        for day in solution.days() {
+            let SolutionEntry::Day { name, entries } = day else {
+                continue;
+            };
+            if let Some(day_score) = self.score_entry(day) {
+                score.days.push((name.clone(), day_score));
+            }
+            for meal in entries {
+                if let SolutionEntry::Meal {
+                    name: meal_name, ..
+                } = meal
+                    && let Some(meal_score) = self.score_entry(meal)
+                {
+                    score
+                        .meals
+                        .push((name.clone(), meal_name.clone(), meal_score));
+                }
+            }
+        }
+        score
+    }
+
+    fn score_entry(&self, entry: &SolutionEntry) -> Option<NutritionScore> {
+        let grams = counted_grams(entry);
+        (grams > 0.0).then(|| self.score(|nutrient| entry.nutrient_total(nutrient) / grams * 100.0))
+    }
+
+    // `per_100g` gives the amount of a nutrient in 100g of the scored food
+    fn score(&self, per_100g: impl Fn(NutrientType) -> f64) -> NutritionScore {
+        let amount = |macro_type| per_100g(NutrientType::Macro(macro_type));
+        let negative = points(
+            &ENERGY_KJ,
+            amount(MacroElementsType::Calories) * KJ_PER_KCAL,
+        ) + points(&SUGAR_G, amount(MacroElementsType::Sugar))
+            + points(&SATURATED_FAT_G, amount(MacroElementsType::SaturatedFat))
+            + points(
+                &SODIUM_MG,
+                per_100g(NutrientType::Micro(MicroNutrientsType::Sodium)),
+            );
+        let mut positive = points(
+            &FIBER_G,
+            per_100g(NutrientType::Micro(MicroNutrientsType::Fiber)),
+        );
+        if negative < PROTEIN_CUTOFF_POINTS {
+            positive += points(&PROTEIN_G, amount(MacroElementsType::Protein));
+        }
+        let total = negative - positive;
+        NutritionScore {
+            points: total,
+            grade: NutriGrade::from_points(total),
+            custom: self
+                .weights
+                .iter()
+                .map(|(nutrient, weight)| weight * per_100g(*nutrient))
+                .sum(),
+        }
+    }
+}
+
+/// Scores `product` with the default [`ScoreWeights`].
+#[must_use]
+pub fn score_product(product: &Product) -> NutritionScore {
+    ScoreWeights::default().score_product(product)
+}
+
+/// Scores every meal and day of `solution` with the default [`ScoreWeights`].
+#[must_use]
+pub fn score_solution(solution: &Solution) -> SolutionScore {
+    ScoreWeights::default().score_solution(solution)
+}
+
+// number of thresholds `value` is above
+fn points(thresholds: &[f64], value: f64) -> i32 {
+    thresholds.iter().map(|t| i32::from(value > *t)).sum()
+}
+
+// grams of products whose nutrients are counted, negligible ones are left out like in totals
+fn counted_grams(entry: &SolutionEntry) -> f64 {
+    match entry {
+        SolutionEntry::Week { entries }
+        | SolutionEntry::Day { entries, .. }
+        | SolutionEntry::Meal { entries, .. } => entries.iter().map(counted_grams).sum(),
+        SolutionEntry::Product {
+            product,
+            amount_grams,
+            ..
+        } if !product.negligible => *amount_grams,
+        SolutionEntry::Product { .. } => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::Fraction;
+    use crate::data_types::{AllowedUnitsType, ProductBuilder};
+    use approx::assert_relative_eq;
+
+    fn entry(product: Product, amount_grams: f64) -> SolutionEntry {
+        SolutionEntry::Product {
+            product,
+            amount_grams,
+            unit: AllowedUnitsType::Gram,
+            amount_unit: Fraction {
+                numerator: 1,
+                denominator: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn grades_products() {
+        let lentils = ProductBuilder::new("Lentils")
+            .carbs(20.0)
+            .protein(9.0)
+            .fat(0.4)
+            .micro(MicroNutrientsType::Fiber, 8.0)
+            .micro(MicroNutrientsType::Sodium, 2.0)
+            .build()
+            .unwrap();
+        let score = score_product(&lentils);
+        // 488 kJ gives 1 point, fiber and protein 5 each
+        assert_eq!(score.points, -9);
+        assert_eq!(score.grade, NutriGrade::A);
+        assert_relative_eq!(score.custom, 9.0 + 16.0 - 0.01, epsilon = 1e-6);
+
+        let cookie = ProductBuilder::new("Cookie")
+            .carbs(65.0)
+            .sugar(35.0)
+            .fat(25.0)
+            .saturated_fat(12.0)
+            .protein(6.0)
+            .micro(MicroNutrientsType::Sodium, 400.0)
+            .build()
+            .unwrap();
+        let score = score_product(&cookie);
+        // 2088 kJ 6, sugar 7, saturated fat 10, sodium 4, protein above the cutoff
+        assert_eq!(score.points, 27);
+        assert_eq!(score.grade, NutriGrade::E);
+    }
+
+    #[test]
+    fn scores_meals_and_days_per_100g() {
+        let chicken = ProductBuilder::new("Chicken")
+            .protein(30.0)
+            .build()
+            .unwrap();
+        let salt = ProductBuilder::new("Salt")
+            .micro(MicroNutrientsType::Sodium, 38_000.0)
+            .negligible(true)
+            .build()
+            .unwrap();
+        let solution = Solution {
+            solution: SolutionEntry::Day {
+                name: "Monday".to_string(),
+                entries: vec![
+                    SolutionEntry::Meal {
+                        name: "Lunch".to_string(),
+                        slot: None,
+                        entries: vec![entry(chicken.clone(), 200.0), entry(salt, 2.0)],
+                    },
+                    SolutionEntry::Meal {
+                        name: "Snack".to_string(),
+                        slot: None,
+                        entries: Vec::new(),
+                    },
+                ],
+            },
+        };
+
+        let score = score_solution(&solution);
+        assert_eq!(score.meals.len(), 1);
+        let (day, meal, lunch) = &score.meals[0];
+        assert_eq!((day.as_str(), meal.as_str()), ("Monday", "Lunch"));
+        assert_eq!(*lunch, score_product(&chicken));
+        assert_eq!(score.days, vec![("Monday".to_string(), *lunch)]);
+
+        let weights = ScoreWeights {
+            weights: vec![(NutrientType::Macro(MacroElementsType::Protein), 0.5)],
+        };
+        assert_relative_eq!(weights.score_solution(&solution).days[0].1.custom, 15.0);
+    }
+}
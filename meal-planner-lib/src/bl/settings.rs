@@ -0,0 +1,112 @@
+use crate::data_types::AppSettings;
+use crate::database_access::MutableDatabase;
+
+/// Handle returned by [`SettingsStore::subscribe`], used to unsubscribe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Listener = Box<dyn FnMut(&AppSettings)>;
+
+/// Current [`AppSettings`] with listeners called on every change, e.g. to update app signals.
+/// Settings are persisted through [`MutableDatabase`], `SQLite` natively and OPFS on the web.
+#[derive(Default)]
+pub struct SettingsStore {
+    settings: AppSettings,
+    listeners: Vec<(SubscriptionId, Listener)>,
+    next_id: u64,
+}
+
+impl SettingsStore {
+    #[must_use]
+    pub fn new(settings: AppSettings) -> Self {
+        Self {
+            settings,
+            listeners: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Loads the settings stored in `db`, defaults when nothing was saved yet.
+    pub async fn load(db: &dyn MutableDatabase) -> Result<Self, String> {
+        Ok(Self::new(db.get_app_settings().await?))
+    }
+
+    pub async fn save(&self, db: &mut dyn MutableDatabase) -> Result<(), String> {
+        db.set_app_settings(&self.settings).await
+    }
+
+    #[must_use]
+    pub fn settings(&self) -> &AppSettings {
+        &self.settings
+    }
+
+    /// Calls `listener` with the new settings after every change.
+    pub fn subscribe(&mut self, listener: impl FnMut(&AppSettings) + 'static) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Returns whether the listener was still subscribed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|(listener_id, _)| *listener_id != id);
+        self.listeners.len() != before
+    }
+
+    /// Applies `change` and notifies the listeners. Invalid results are rejected and leave the
+    /// settings untouched. Returns whether anything changed.
+    pub fn update(&mut self, change: impl FnOnce(&mut AppSettings)) -> Result<bool, String> {
+        let mut updated = self.settings.clone();
+        change(&mut updated);
+        updated.validate()?;
+        if updated == self.settings {
+            return Ok(false);
+        }
+        self.settings = updated;
+        for (_, listener) in &mut self.listeners {
+            listener(&self.settings);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn notifies_subscribers_of_changes() {
+        let mut store = SettingsStore::default();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_by_listener = Rc::clone(&seen);
+        let id = store.subscribe(move |settings| {
+            seen_by_listener
+                .borrow_mut()
+                .push(settings.language.clone());
+        });
+
+        assert_eq!(store.update(|s| s.language = "pl-PL".to_string()), Ok(true));
+        assert_eq!(
+            store.update(|s| s.language = "pl-PL".to_string()),
+            Ok(false)
+        );
+        assert!(store.update(|s| s.sidebar_width = 0.0).is_err());
+        assert_eq!(
+            store.settings(),
+            &AppSettings {
+                language: "pl-PL".to_string(),
+                ..AppSettings::default()
+            }
+        );
+        assert_eq!(*seen.borrow(), vec!["pl-PL".to_string()]);
+
+        assert!(store.unsubscribe(id));
+        assert!(!store.unsubscribe(id));
+        assert_eq!(store.update(|s| s.sidebar_width = 200.0), Ok(true));
+        assert_eq!(seen.borrow().len(), 1);
+    }
+}
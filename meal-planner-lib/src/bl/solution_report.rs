@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bl::constraints_solver::{Solution, SolutionEntry};
+use crate::data_types::{
+    NutrientType, ProductId,
+    constraints::{DayMealPlanConstraint, NutrientConstraint},
+};
+
+// number of products listed as the main sources of a nutrient
+const TOP_PRODUCTS: usize = 3;
+
+/// How a solved plan stands against one nutrient constraint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NutrientConstraintReport {
+    /// Meal the constraint belongs to, `None` for day constraints.
+    pub meal: Option<String>,
+    pub nutrient: NutrientType,
+    pub amount: f64,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    /// Products giving most of the nutrient as `(product id, amount)`, largest first.
+    pub top_products: Vec<(ProductId, f64)>,
+}
+
+impl NutrientConstraintReport {
+    /// How much the amount could still drop, `None` without a minimum.
+    #[must_use]
+    pub fn room_to_min(&self) -> Option<f64> {
+        self.min.map(|min| self.amount - f64::from(min))
+    }
+
+    /// How much the amount could still grow, `None` without a maximum.
+    #[must_use]
+    pub fn room_to_max(&self) -> Option<f64> {
+        self.max.map(|max| f64::from(max) - self.amount)
+    }
+
+    /// Amount as a percentage of the maximum, e.g. "protein is at 98% of your max".
+    #[must_use]
+    pub fn percent_of_max(&self) -> Option<f64> {
+        self.max
+            .filter(|max| *max > 0.0)
+            .map(|max| self.amount / f64::from(max) * 100.0)
+    }
+}
+
+/// Room left to the bounds of every nutrient constraint of a solved day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolutionReport {
+    /// Day constraints first, then meal constraints in the order of the plan.
+    pub constraints: Vec<NutrientConstraintReport>,
+}
+
+impl SolutionReport {
+    /// Reports on the first day of `solution` solved from `day`.
+    #[must_use]
+    pub fn new(solution: &Solution, day: &DayMealPlanConstraint) -> Self {
+        let mut constraints = Vec::new();
+        let Some(day_entry) = solution.days().into_iter().next() else {
+            return Self { constraints };
+        };
+        for nutrient_constraint in &day.nutrients {
+            constraints.push(Self::constraint_report(
+                None,
+                day_entry,
+                nutrient_constraint,
+            ));
+        }
+        if let SolutionEntry::Day { entries, .. } = day_entry {
+            for meal_entry in entries {
+                let SolutionEntry::Meal { name, .. } = meal_entry else {
+                    continue;
+                };
+                let Some(meal) = day.meals.get(name) else {
+                    continue;
+                };
+                for nutrient_constraint in &meal.nutrients {
+                    constraints.push(Self::constraint_report(
+                        Some(name.clone()),
+                        meal_entry,
+                        nutrient_constraint,
+                    ));
+                }
+            }
+        }
+        Self { constraints }
+    }
+
+    fn constraint_report(
+        meal: Option<String>,
+        entry: &SolutionEntry,
+        constraint: &NutrientConstraint,
+    ) -> NutrientConstraintReport {
+        let nutrient = constraint.element();
+        let mut contributions = Vec::new();
+        Self::collect_contributions(entry, nutrient, &mut contributions);
+        contributions.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        contributions.truncate(TOP_PRODUCTS);
+        NutrientConstraintReport {
+            meal,
+            nutrient,
+            amount: entry.nutrient_total(nutrient),
+            min: constraint.min(),
+            max: constraint.max(),
+            top_products: contributions,
+        }
+    }
+
+    // positive amounts of `nutrient` per product id, summed over meals
+    fn collect_contributions(
+        entry: &SolutionEntry,
+        nutrient: NutrientType,
+        contributions: &mut Vec<(ProductId, f64)>,
+    ) {
+        match entry {
+            SolutionEntry::Week { entries }
+            | SolutionEntry::Day { entries, .. }
+            | SolutionEntry::Meal { entries, .. } => {
+                for inner in entries {
+                    Self::collect_contributions(inner, nutrient, contributions);
+                }
+            }
+            SolutionEntry::Product { product, .. } => {
+                let amount = entry.nutrient_total(nutrient);
+                if amount <= 0.0 {
+                    return;
+                }
+                let product_id = product.id();
+                match contributions.iter_mut().find(|(id, _)| *id == product_id) {
+                    Some((_, total)) => *total += amount,
+                    None => contributions.push((product_id, amount)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::{ConstraintsSolver, MinOrMax};
+    use crate::data_types::{
+        AllowedUnitsType, DietaryRestrictions, MacroElementsType, ProductBuilder,
+        constraints::{MealConstraint, ProductConstraint},
+    };
+    use approx::assert_relative_eq;
+    use std::collections::HashMap;
+
+    fn meal(products: &[(&str, f32, u16)], nutrients: Vec<NutrientConstraint>) -> MealConstraint {
+        MealConstraint {
+            products: products
+                .iter()
+                .map(|(name, protein, max)| {
+                    let product = ProductBuilder::new(*name)
+                        .protein(*protein)
+                        .build()
+                        .unwrap();
+                    ProductConstraint::new(
+                        Box::new(product),
+                        None,
+                        Some(*max),
+                        AllowedUnitsType::Gram,
+                    )
+                    .unwrap()
+                })
+                .collect(),
+            nutrients,
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn reports_room_and_top_products() {
+        let protein = MacroElementsType::Protein;
+        // every product at its max gives 87g of protein
+        let lunch = meal(
+            &[
+                ("Chicken", 30.0, 200),
+                ("Rice", 7.0, 300),
+                ("Peas", 5.0, 100),
+                ("Lettuce", 1.0, 100),
+            ],
+            vec![NutrientConstraint::new(protein, Some(50.0), Some(100.0)).unwrap()],
+        );
+        let day = DayMealPlanConstraint {
+            meals: HashMap::from([("Lunch".to_string(), lunch)]),
+            nutrients: vec![NutrientConstraint::new(protein, None, Some(90.0)).unwrap()],
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        };
+        let (solution, report) =
+            ConstraintsSolver::new(MinOrMax::Max, NutrientType::Macro(protein))
+                .solve_day_with_report(&day)
+                .unwrap();
+        assert_relative_eq!(
+            solution.nutrient_total(NutrientType::Macro(protein)),
+            87.0,
+            epsilon = 1e-4
+        );
+
+        let [day_report, lunch_report] = report.constraints.as_slice() else {
+            panic!("expected a day and a meal constraint, got {report:?}");
+        };
+        assert_eq!(day_report.meal, None);
+        assert_relative_eq!(day_report.percent_of_max().unwrap(), 96.67, epsilon = 0.01);
+        assert_eq!(day_report.room_to_min(), None);
+        assert_eq!(lunch_report.meal.as_deref(), Some("Lunch"));
+        assert_relative_eq!(lunch_report.room_to_max().unwrap(), 13.0, epsilon = 1e-4);
+        assert_relative_eq!(lunch_report.room_to_min().unwrap(), 37.0, epsilon = 1e-4);
+        let top: Vec<_> = lunch_report
+            .top_products
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .collect();
+        assert_eq!(top, vec!["Chicken", "Rice", "Peas"]);
+        assert_relative_eq!(lunch_report.top_products[0].1, 60.0, epsilon = 1e-4);
+    }
+}
@@ -0,0 +1,257 @@
+//! Solving constraints pasted as JSON, used by the developer sandbox view of the app.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+use super::constraints_solver::{ConstraintsSolver, MinOrMax, Objective, SolveDiagnostics};
+use crate::data_types::{
+    AllowedUnitsType, DietaryRestrictions, MealSlot, NutrientType, Product,
+    constraints::{
+        DayMealPlanConstraint, MacroPercentConstraint, MealConstraint, NutrientConstraint,
+        ProductConstraint, ProductRatioConstraint, RatioBase, RatioRelation, RecipeConstraint,
+    },
+};
+use crate::database_access::Database;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SandboxRequest {
+    pub direction: MinOrMax,
+    pub objective: Objective,
+    pub meals: BTreeMap<String, SandboxMeal>,
+    #[serde(default)]
+    pub nutrients: Vec<SandboxNutrient>,
+    #[serde(default)]
+    pub max_cost: Option<f32>,
+    #[serde(default)]
+    pub restrictions: DietaryRestrictions,
+    #[serde(default)]
+    pub macro_percents: Vec<MacroPercentConstraint>,
+    /// Caps on the total of a product over all meals.
+    #[serde(default)]
+    pub products: Vec<SandboxProduct>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SandboxMeal {
+    pub products: Vec<SandboxProduct>,
+    #[serde(default)]
+    pub nutrients: Vec<SandboxNutrient>,
+    #[serde(default)]
+    pub max_products: Option<u16>,
+    #[serde(default)]
+    pub min_products: Option<u16>,
+    #[serde(default)]
+    pub ratios: Vec<SandboxRatio>,
+    #[serde(default)]
+    pub slot: Option<MealSlot>,
+    #[serde(default)]
+    pub recipes: Vec<RecipeConstraint>,
+}
+
+/// Product taken from the database by `product_id`, or given inline as `product`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SandboxProduct {
+    #[serde(default)]
+    pub product_id: Option<String>,
+    #[serde(default)]
+    pub product: Option<Product>,
+    #[serde(default)]
+    pub min: Option<f32>,
+    #[serde(default)]
+    pub max: Option<f32>,
+    #[serde(default = "default_unit")]
+    pub unit: AllowedUnitsType,
+}
+
+fn default_unit() -> AllowedUnitsType {
+    AllowedUnitsType::Gram
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SandboxRatio {
+    pub product_id: String,
+    pub base: RatioBase,
+    pub relation: RatioRelation,
+    pub ratio: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SandboxNutrient {
+    pub nutrient: NutrientType,
+    #[serde(default)]
+    pub min: Option<f32>,
+    #[serde(default)]
+    pub max: Option<f32>,
+}
+
+pub struct SandboxReport {
+    /// Built problem in the CPLEX LP format, present even when solving failed.
+    pub lp: String,
+    pub elapsed_ms: f64,
+    pub outcome: Result<SolveDiagnostics, String>,
+}
+
+/// Parses `json` as a [`SandboxRequest`] and solves it. Errors in the request itself are returned
+/// as `Err`, solver failures end up in [`SandboxReport::outcome`].
+pub async fn run_sandbox(json: &str, db: &dyn Database) -> Result<SandboxReport, String> {
+    let request: SandboxRequest =
+        serde_json::from_str(json).map_err(|e| format!("Invalid sandbox request: {e}"))?;
+
+    let mut meals = HashMap::new();
+    for (meal_name, meal) in request.meals {
+        let mut products = Vec::new();
+        for entry in meal.products {
+            products.push(product_constraint(entry, db).await?);
+        }
+        meals.insert(
+            meal_name.clone(),
+            MealConstraint {
+                products,
+                nutrients: nutrient_constraints(&meal.nutrients, &meal_name)?,
+                max_products: meal.max_products,
+                min_products: meal.min_products,
+                ratios: ratio_constraints(meal.ratios, &meal_name)?,
+                slot: meal.slot,
+                recipes: meal.recipes,
+            },
+        );
+    }
+    let mut day_products = Vec::new();
+    for entry in request.products {
+        day_products.push(product_constraint(entry, db).await?);
+    }
+    let day = DayMealPlanConstraint {
+        meals,
+        nutrients: nutrient_constraints(&request.nutrients, "day")?,
+        max_cost: request.max_cost,
+        restrictions: request.restrictions,
+        macro_percents: request.macro_percents,
+        products: day_products,
+    };
+
+    let mut solver = ConstraintsSolver::new(request.direction, request.objective);
+    let start = now_ms();
+    let outcome = solver.solve_day_with_diagnostics(&day);
+    let elapsed_ms = now_ms() - start;
+    Ok(SandboxReport {
+        lp: solver.lp_export(),
+        elapsed_ms,
+        outcome,
+    })
+}
+
+async fn product_constraint(
+    entry: SandboxProduct,
+    db: &dyn Database,
+) -> Result<ProductConstraint, String> {
+    let product = match (entry.product, entry.product_id) {
+        (Some(product), _) => product,
+        (None, Some(product_id)) => db
+            .get_product_by_id(&product_id)
+            .await
+            .ok_or_else(|| format!("Product '{product_id}' not found"))?,
+        (None, None) => return Err("Every product needs 'product_id' or 'product'".to_string()),
+    };
+    let product_id = product.id();
+    ProductConstraint::new_fractional(Box::new(product), entry.min, entry.max, entry.unit)
+        .ok_or_else(|| {
+            format!(
+                "Invalid constraint for '{product_id}': unit {} not allowed or min above max",
+                entry.unit
+            )
+        })
+}
+
+fn nutrient_constraints(
+    nutrients: &[SandboxNutrient],
+    scope: &str,
+) -> Result<Vec<NutrientConstraint>, String> {
+    nutrients
+        .iter()
+        .map(|n| {
+            NutrientConstraint::new(n.nutrient, n.min, n.max)
+                .ok_or_else(|| format!("Invalid {} constraint in {scope}", n.nutrient))
+        })
+        .collect()
+}
+
+fn ratio_constraints(
+    ratios: Vec<SandboxRatio>,
+    scope: &str,
+) -> Result<Vec<ProductRatioConstraint>, String> {
+    ratios
+        .into_iter()
+        .map(|r| {
+            let product_id = r.product_id.clone();
+            ProductRatioConstraint::new(r.product_id, r.base, r.relation, r.ratio)
+                .ok_or_else(|| format!("Invalid ratio for '{product_id}' in {scope}"))
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::ProductBuilder;
+    use crate::database_access::mock_db::MockProductDb;
+    use futures::executor::block_on;
+
+    fn request() -> String {
+        let chicken = ProductBuilder::new("Chicken")
+            .protein(30.0)
+            .build()
+            .unwrap();
+        format!(
+            r#"{{
+                "direction": "Min",
+                "objective": {{ "Nutrient": {{ "Macro": "Calories" }} }},
+                "meals": {{
+                    "Lunch": {{
+                        "products": [{{ "product": {}, "max": 500 }}],
+                        "nutrients": [{{ "nutrient": {{ "Macro": "Protein" }}, "min": 30.0 }}]
+                    }}
+                }}
+            }}"#,
+            serde_json::to_string(&chicken).unwrap()
+        )
+    }
+
+    #[test]
+    fn solves_request_and_reports_binding_constraints() {
+        let db = MockProductDb::new();
+        let report = block_on(run_sandbox(&request(), &db)).expect("request should be valid");
+        let diagnostics = report.outcome.expect("request should be solvable");
+        assert!((diagnostics.objective_value - 120.0).abs() < 1e-6);
+        assert_eq!(diagnostics.binding_constraints, vec!["Lunch min Protein"]);
+        assert!(report.lp.starts_with("Minimize\n"));
+        assert!(report.elapsed_ms >= 0.0);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let db = MockProductDb::new();
+        assert!(block_on(run_sandbox("{", &db)).is_err());
+        let missing = r#"{
+            "direction": "Max",
+            "objective": "Cost",
+            "meals": { "Lunch": { "products": [{ "product_id": "Nope" }] } }
+        }"#;
+        assert_eq!(
+            block_on(run_sandbox(missing, &db)).err(),
+            Some("Product 'Nope' not found".to_string())
+        );
+    }
+}
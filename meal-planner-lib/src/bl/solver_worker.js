@@ -0,0 +1,15 @@
+// Web worker solving one day plan off the UI thread, see `background_solver.rs`. It is served
+// next to the wasm-bindgen glue of the library and answers every request with the JSON encoded
+// `Result<Solution, String>` returned by `solve_day_json`.
+import init, { solve_day_json } from "./meal_planner_lib.js";
+
+const ready = init();
+
+self.addEventListener("message", async (evt) => {
+    try {
+        await ready;
+        self.postMessage(solve_day_json(String(evt.data)));
+    } catch (err) {
+        self.postMessage(JSON.stringify({ Err: err?.message || String(err) }));
+    }
+});
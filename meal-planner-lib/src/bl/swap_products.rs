@@ -1,85 +1,260 @@
-use super::constraints_solver::Fraction;
-use crate::data_types::{AllowedUnitsType, NutrientType, Product};
+use super::constraints_solver::{Fraction, Solution, SolutionEntry};
+use crate::data_types::{AllowedUnitsType, MacroElementsType, NutrientType, Product};
+use crate::database_access::{Database, DbSearchCriteria};
 use approx::{self, relative_eq};
 
-struct ProductSwapper {}
-
-impl ProductSwapper {
-    pub fn get_grams_of_swapped_product(
-        input: &Product,
-        amount: f32,
-        output: &Product,
-        nutrient_equivalent: NutrientType,
-    ) -> Result<(u16, u16), String> {
-        let (low, high) = Self::get_amount_of_swapped_product(
-            input,
-            amount,
-            output,
-            nutrient_equivalent,
-            AllowedUnitsType::Gram,
-        )?;
-        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-        Ok((
-            (f32::from(low.numerator) / f32::from(low.denominator)).floor() as u16,
-            (f32::from(high.numerator) / f32::from(high.denominator)).ceil() as u16,
+// saturated fat and sugar are part of fat and carbs, calories follow from the rest
+const SWAP_PROFILE: [MacroElementsType; 3] = [
+    MacroElementsType::Fat,
+    MacroElementsType::Carbs,
+    MacroElementsType::Protein,
+];
+
+/// Product with a macro profile close to the one being swapped out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapCandidate {
+    pub product: Product,
+    /// Largest difference over fat, carbs and protein per 100g, in percent of the original.
+    pub deviation: f32,
+}
+
+/// Options for [`find_swap_candidates_with`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapSearchOptions {
+    /// Maximum difference in percent, see [`find_swap_candidates`].
+    pub tolerance: f32,
+    /// Stop evaluating once this many candidates within tolerance were found. The returned
+    /// candidates are then the first ones found, not necessarily the closest ones.
+    pub max_results: Option<usize>,
+}
+
+impl SwapSearchOptions {
+    #[must_use]
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance,
+            max_results: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+}
+
+/// Searches `db` for products whose fat, carbs and protein per 100g are all within `tolerance`
+/// percent of the product with `product_id`. Results are sorted from the closest match.
+///
+/// Values below 1g per 100g are compared as if they were 1g, so trace amounts do not rule
+/// candidates out.
+pub async fn find_swap_candidates(
+    product_id: &str,
+    tolerance: f32,
+    db: &dyn Database,
+) -> Result<Vec<SwapCandidate>, String> {
+    find_swap_candidates_with(product_id, SwapSearchOptions::new(tolerance), db).await
+}
+
+/// Same as [`find_swap_candidates`], with the search tuned by `options`.
+///
+/// Candidates are evaluated in batches, on wasm the search yields to the event loop in between
+/// so the UI stays responsive. They are not evaluated in parallel: each one is a comparison of
+/// three macro elements, cheaper than handing it to another thread, so the search time is
+/// spent reading the products from `db`.
+pub async fn find_swap_candidates_with(
+    product_id: &str,
+    options: SwapSearchOptions,
+    db: &dyn Database,
+) -> Result<Vec<SwapCandidate>, String> {
+    let tolerance = options.tolerance;
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        return Err(format!("Invalid tolerance: {tolerance}"));
+    }
+    if options.max_results == Some(0) {
+        return Ok(Vec::new());
+    }
+    let original = db
+        .get_product_by_id(product_id)
+        .await
+        .ok_or_else(|| format!("Product with ID '{product_id}' not found."))?;
+
+    let products: Vec<Product> = db
+        .get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())])
+        .await
+        .into_iter()
+        .filter(|(id, _)| id != product_id)
+        .map(|(_, product)| product)
+        .collect();
+    let mut candidates = evaluate_candidates(&original, products, &options).await;
+    candidates.sort_by(|a, b| a.deviation.total_cmp(&b.deviation));
+    if let Some(max_results) = options.max_results {
+        candidates.truncate(max_results);
+    }
+    Ok(candidates)
+}
+
+fn evaluate_candidate(
+    original: &Product,
+    product: Product,
+    tolerance: f32,
+) -> Option<SwapCandidate> {
+    let deviation = macro_profile_deviation(original, &product);
+    (deviation <= tolerance).then_some(SwapCandidate { product, deviation })
+}
+
+// candidates evaluated between two yields to the event loop on wasm
+const BATCH_SIZE: usize = 64;
+
+#[cfg_attr(not(target_arch = "wasm32"), allow(clippy::unused_async))]
+async fn evaluate_candidates(
+    original: &Product,
+    products: Vec<Product>,
+    options: &SwapSearchOptions,
+) -> Vec<SwapCandidate> {
+    let mut candidates = Vec::new();
+    let mut products = products.into_iter().peekable();
+    while products.peek().is_some() {
+        for product in products.by_ref().take(BATCH_SIZE) {
+            candidates.extend(evaluate_candidate(original, product, options.tolerance));
+            if options
+                .max_results
+                .is_some_and(|max| candidates.len() >= max)
+            {
+                return candidates;
+            }
+        }
+        // let other tasks run before the next batch
+        #[cfg(target_arch = "wasm32")]
+        let _ = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(
+            &wasm_bindgen::JsValue::NULL,
         ))
+        .await;
     }
+    candidates
+}
 
-    pub fn get_amount_of_swapped_product(
-        input: &Product,
-        amount: f32,
-        output: &Product,
-        nutrient_equivalent: NutrientType,
-        allowed_units: AllowedUnitsType,
-    ) -> Result<(Fraction, Fraction), String> {
-        let Some(nutrient_amount) = input.get_nutrient_amount(nutrient_equivalent) else {
-            return Err(format!(
-                "Input product '{}' does not have nutrient '{:?}'",
-                input.id(),
-                nutrient_equivalent
-            ));
-        };
-        let Some(nutrient_amount_output) = output.get_nutrient_amount(nutrient_equivalent) else {
-            return Err(format!(
-                "Output product '{}' does not have nutrient '{:?}'",
-                output.id(),
-                nutrient_equivalent
-            ));
-        };
-        let Some(unit_size) = input.allowed_units.get(&allowed_units) else {
-            return Err(format!(
-                "Input product '{}' does not have allowed unit '{:?}'",
-                input.id(),
-                allowed_units
-            ));
-        };
-        if relative_eq!(nutrient_amount_output, 0.0) {
-            return Err(format!(
-                "Output product '{}' has zero amount of nutrient '{:?}'",
-                output.id(),
-                nutrient_equivalent
-            ));
+fn macro_profile_deviation(original: &Product, candidate: &Product) -> f32 {
+    SWAP_PROFILE
+        .iter()
+        .map(|m| {
+            let reference = original.macro_elements[*m];
+            (candidate.macro_elements[*m] - reference).abs() / reference.max(1.0) * 100.0
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Replaces every occurrence of `from_id` in `solution` with `to`.
+///
+/// The new amount keeps the `preserved` nutrients as close as possible to the original entry
+/// (least squares of the relative differences, exact when a single nutrient is given). The
+/// original unit is kept when `to` supports it, otherwise the amount is given in grams.
+/// Returns the number of replaced entries.
+pub fn swap_in_solution(
+    solution: &mut Solution,
+    from_id: &str,
+    to: &Product,
+    preserved: &[NutrientType],
+) -> Result<usize, String> {
+    let swapped = swap_in_entry(&mut solution.solution, from_id, to, preserved)?;
+    if swapped == 0 {
+        return Err(format!("Product '{from_id}' is not in the solution"));
+    }
+    Ok(swapped)
+}
+
+fn swap_in_entry(
+    entry: &mut SolutionEntry,
+    from_id: &str,
+    to: &Product,
+    preserved: &[NutrientType],
+) -> Result<usize, String> {
+    match entry {
+        SolutionEntry::Week { entries }
+        | SolutionEntry::Day { entries, .. }
+        | SolutionEntry::Meal { entries, .. } => {
+            let mut swapped = 0;
+            for entry in entries {
+                swapped += swap_in_entry(entry, from_id, to, preserved)?;
+            }
+            Ok(swapped)
         }
-        let calculated_amount = amount * nutrient_amount / nutrient_amount_output;
-        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-        let low_calculated_amount = Fraction {
-            numerator: (calculated_amount * f32::from(unit_size.divider)
-                / f32::from(unit_size.amount))
-            .floor() as u16,
-            denominator: unit_size.divider,
-        };
-        let high_calculated_amount = Fraction {
-            numerator: low_calculated_amount.numerator + 1,
-            denominator: unit_size.divider,
-        };
-        Ok((low_calculated_amount, high_calculated_amount))
+        SolutionEntry::Product {
+            product,
+            amount_grams,
+            unit,
+            amount_unit,
+        } if product.id() == from_id => {
+            let grams = preserving_amount(product, *amount_grams, to, preserved)?;
+            let new_unit = if to.allowed_units.contains_key(unit) {
+                *unit
+            } else {
+                AllowedUnitsType::Gram
+            };
+            let unit_data = to.allowed_units[&new_unit];
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let numerator = (grams * f64::from(unit_data.divider) / f64::from(unit_data.amount))
+                .round()
+                .clamp(0.0, f64::from(u16::MAX)) as u16;
+            *product = to.clone();
+            // the shown amount is rounded to the unit, nutrients follow the rounded amount
+            *amount_grams =
+                f64::from(numerator) * f64::from(unit_data.amount) / f64::from(unit_data.divider);
+            *unit = new_unit;
+            *amount_unit = Fraction {
+                numerator,
+                denominator: unit_data.divider,
+            };
+            Ok(1)
+        }
+        SolutionEntry::Product { .. } => Ok(0),
     }
 }
 
+fn preserving_amount(
+    from: &Product,
+    grams: f64,
+    to: &Product,
+    preserved: &[NutrientType],
+) -> Result<f64, String> {
+    if preserved.is_empty() {
+        return Ok(grams);
+    }
+    // minimize sum(((to_i * x - target_i) / target_i)^2) over nutrients present in the original
+    let mut numerator = 0.0;
+    let mut denominator = 0.0;
+    for nutrient in preserved {
+        let target = f64::from(from.get_nutrient_amount(*nutrient).unwrap_or(0.0)) * grams;
+        if relative_eq!(target, 0.0) {
+            continue;
+        }
+        let per_gram = f64::from(to.get_nutrient_amount(*nutrient).ok_or_else(|| {
+            format!(
+                "Output product '{}' does not have nutrient '{nutrient:?}'",
+                to.id()
+            )
+        })?);
+        let ratio = per_gram / target;
+        numerator += ratio;
+        denominator += ratio * ratio;
+    }
+    if relative_eq!(denominator, 0.0) {
+        return Err(format!(
+            "Output product '{}' has none of the preserved nutrients",
+            to.id()
+        ));
+    }
+    Ok(numerator / denominator)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::data_types::{MacroElements, MacroElementsType, MicroNutrientsType, UnitData};
+    use crate::data_types::{MacroElements, MicroNutrientsType, ProductBuilder, UnitData};
+    use crate::database_access::{MutableDatabase, mock_db::MockProductDb};
+    use approx::assert_relative_eq;
+    use futures::executor::block_on;
 
     fn make_product(
         name: &str,
@@ -106,112 +281,344 @@ mod tests {
         )
     }
 
+    fn make_macro_product(name: &str, fat: f32, carbs: f32, protein: f32) -> Product {
+        ProductBuilder::new(name)
+            .fat(fat)
+            .carbs(carbs)
+            .protein(protein)
+            .build()
+            .unwrap()
+    }
+
     #[test]
-    fn converts_between_products_in_grams() {
-        let olive = make_product("Olive", 91.0, &[]);
-        let milk = make_product("Milk", 3.0, &[]);
-        let (low, high) = ProductSwapper::get_grams_of_swapped_product(
-            &olive,
-            55.0,
-            &milk,
-            NutrientType::Macro(MacroElementsType::Fat),
+    fn finds_candidates_within_tolerance_sorted_by_deviation() {
+        let mut db = MockProductDb::new();
+        for product in [
+            make_macro_product("Chicken", 3.0, 0.0, 31.0),
+            make_macro_product("Turkey", 3.2, 0.0, 29.0),
+            make_macro_product("Tuna", 3.27, 0.05, 30.0),
+            make_macro_product("Bacon", 42.0, 1.0, 37.0),
+        ] {
+            let id = product.id();
+            block_on(db.add_product(&id, product)).expect("add should succeed");
+        }
+
+        let candidates =
+            block_on(find_swap_candidates("Chicken", 10.0, &db)).expect("search should succeed");
+        let names: Vec<_> = candidates.iter().map(|c| c.product.name()).collect();
+        assert_eq!(names, vec!["Turkey", "Tuna"]);
+        assert_relative_eq!(candidates[0].deviation, 20.0 / 3.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn find_candidates_stops_after_max_results() {
+        let mut db = MockProductDb::new();
+        let original = make_macro_product("Chicken", 3.0, 0.0, 31.0);
+        block_on(db.add_product(&original.id(), original)).expect("add should succeed");
+        for i in 0..200u16 {
+            let product = make_macro_product(&format!("Lean{i}"), 3.0, 0.0, 30.0);
+            block_on(db.add_product(&product.id(), product)).expect("add should succeed");
+        }
+
+        let options = SwapSearchOptions::new(10.0).with_max_results(5);
+        let candidates = block_on(find_swap_candidates_with("Chicken", options, &db))
+            .expect("search should succeed");
+        assert_eq!(candidates.len(), 5);
+        assert!(candidates.iter().all(|c| c.deviation <= 10.0));
+
+        let none = block_on(find_swap_candidates_with(
+            "Chicken",
+            SwapSearchOptions::new(10.0).with_max_results(0),
+            &db,
+        ))
+        .expect("search should succeed");
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn find_candidates_reports_missing_product() {
+        let db = MockProductDb::new();
+        let err = block_on(find_swap_candidates("Missing", 10.0, &db))
+            .expect_err("missing product should fail");
+        assert_eq!(err, "Product with ID 'Missing' not found.");
+    }
+
+    fn single_product_solution(product: Product, grams: f64, unit: AllowedUnitsType) -> Solution {
+        let unit_data = product.allowed_units[&unit];
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let numerator = (grams * f64::from(unit_data.divider) / f64::from(unit_data.amount)) as u16;
+        Solution {
+            solution: SolutionEntry::Week {
+                entries: vec![SolutionEntry::Day {
+                    name: "Day1".to_string(),
+                    entries: vec![SolutionEntry::Meal {
+                        name: "Breakfast".to_string(),
+                        slot: None,
+                        entries: vec![SolutionEntry::Product {
+                            product,
+                            amount_grams: grams,
+                            unit,
+                            amount_unit: Fraction {
+                                numerator,
+                                denominator: unit_data.divider,
+                            },
+                        }],
+                    }],
+                }],
+            },
+        }
+    }
+
+    fn first_product(solution: &Solution) -> (&Product, f64, AllowedUnitsType, Fraction) {
+        let SolutionEntry::Week { entries } = &solution.solution else {
+            panic!("Expected week entry")
+        };
+        let SolutionEntry::Day { entries, .. } = &entries[0] else {
+            panic!("Expected day entry")
+        };
+        let SolutionEntry::Meal { entries, .. } = &entries[0] else {
+            panic!("Expected meal entry")
+        };
+        match &entries[0] {
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                unit,
+                amount_unit,
+            } => (product, *amount_grams, *unit, *amount_unit),
+            _ => panic!("Expected product entry"),
+        }
+    }
+
+    // only the unit of the entry changes, its amount stays in grams
+    fn set_first_unit(solution: &mut Solution, new_unit: AllowedUnitsType) {
+        if let SolutionEntry::Week { entries } = &mut solution.solution
+            && let SolutionEntry::Day { entries, .. } = &mut entries[0]
+            && let SolutionEntry::Meal { entries, .. } = &mut entries[0]
+            && let SolutionEntry::Product { unit, .. } = &mut entries[0]
+        {
+            *unit = new_unit;
+        }
+    }
+
+    #[test]
+    fn swap_in_solution_preserves_single_nutrient() {
+        let cup = UnitData {
+            amount: 200,
+            divider: 2,
+        };
+        let olive = make_product("Olive", 90.0, &[(AllowedUnitsType::Cup, cup)]);
+        let butter = make_product("Butter", 80.0, &[]);
+        let mut solution = single_product_solution(olive, 100.0, AllowedUnitsType::Cup);
+
+        let swapped = swap_in_solution(
+            &mut solution,
+            "Olive",
+            &butter,
+            &[NutrientType::Macro(MacroElementsType::Fat)],
         )
-        .expect("conversion should succeed");
-        assert_eq!(low, 1668);
-        assert_eq!(high, 1669);
+        .expect("swap should succeed");
+        assert_eq!(swapped, 1);
+
+        let (product, grams, unit, amount_unit) = first_product(&solution);
+        assert_eq!(product.name(), "Butter");
+        // 112.5g rounded to whole grams
+        assert_relative_eq!(grams, 113.0);
+        // butter has no cup unit, so the amount falls back to grams
+        assert_eq!(unit, AllowedUnitsType::Gram);
+        assert_eq!(amount_unit.numerator, 113);
+        assert_eq!(amount_unit.denominator, 1);
     }
 
     #[test]
-    fn converts_using_custom_units() {
-        let glass_unit = UnitData {
+    fn swap_in_solution_balances_multiple_nutrients() {
+        let from = make_macro_product("From", 10.0, 0.0, 20.0);
+        let to = make_macro_product("To", 10.0, 0.0, 10.0);
+        let mut solution = single_product_solution(from, 100.0, AllowedUnitsType::Gram);
+
+        swap_in_solution(
+            &mut solution,
+            "From",
+            &to,
+            &[
+                NutrientType::Macro(MacroElementsType::Fat),
+                NutrientType::Macro(MacroElementsType::Protein),
+            ],
+        )
+        .expect("swap should succeed");
+
+        // fat alone needs 100g, protein alone 200g; squared relative error is lowest at 120g
+        let (_, grams, _, _) = first_product(&solution);
+        assert_relative_eq!(grams, 120.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn swap_in_solution_snaps_grams_to_custom_unit() {
+        let glass = UnitData {
             amount: 250,
             divider: 2,
         };
-        let olive = make_product("Olive", 91.0, &[(AllowedUnitsType::Custom, glass_unit)]);
-        let milk = make_product("Milk", 3.0, &[(AllowedUnitsType::Custom, glass_unit)]);
-        let (low, high) = ProductSwapper::get_amount_of_swapped_product(
-            &olive,
-            55.0,
+        let olive = make_product("Olive", 91.0, &[(AllowedUnitsType::Custom, glass)]);
+        let milk = make_product("Milk", 3.0, &[(AllowedUnitsType::Custom, glass)]);
+        let mut solution = single_product_solution(olive, 55.0, AllowedUnitsType::Gram);
+        set_first_unit(&mut solution, AllowedUnitsType::Custom);
+
+        swap_in_solution(
+            &mut solution,
+            "Olive",
             &milk,
-            NutrientType::Macro(MacroElementsType::Fat),
-            AllowedUnitsType::Custom,
+            &[NutrientType::Macro(MacroElementsType::Fat)],
         )
-        .expect("conversion should succeed");
-        assert_eq!(low.numerator, 13);
-        assert_eq!(low.denominator, 2);
-        assert_eq!(high.numerator, 14);
-        assert_eq!(high.denominator, 2);
+        .expect("swap should succeed");
+
+        // 1668.3g of milk is 13.3 half glasses
+        let (_, grams, unit, amount_unit) = first_product(&solution);
+        assert_eq!(unit, AllowedUnitsType::Custom);
+        assert_eq!((amount_unit.numerator, amount_unit.denominator), (13, 2));
+        assert_relative_eq!(grams, 1625.0);
+    }
+
+    #[test]
+    fn swap_in_solution_needs_preserved_nutrients_in_output() {
+        let mut olive = make_product("Olive", 91.0, &[]);
+        let mut milk = make_product("Milk", 3.0, &[]);
+        olive.micro_nutrients[MicroNutrientsType::Fiber] = Some(5.0);
+        let fiber = NutrientType::Micro(MicroNutrientsType::Fiber);
+        let mut solution = single_product_solution(olive, 10.0, AllowedUnitsType::Gram);
+
+        let err = swap_in_solution(&mut solution, "Olive", &milk, &[fiber])
+            .expect_err("missing output nutrient should fail");
+        assert_eq!(
+            err,
+            format!("Output product 'Milk' does not have nutrient '{fiber:?}'")
+        );
+
+        milk.micro_nutrients[MicroNutrientsType::Fiber] = Some(0.0);
+        let err = swap_in_solution(&mut solution, "Olive", &milk, &[fiber])
+            .expect_err("zero output nutrient should fail");
+        assert_eq!(
+            err,
+            "Output product 'Milk' has none of the preserved nutrients"
+        );
     }
 
     #[test]
-    fn error_when_input_missing_nutrient() {
+    fn swap_in_solution_converts_between_products_in_grams() {
         let olive = make_product("Olive", 91.0, &[]);
         let milk = make_product("Milk", 3.0, &[]);
-        let nutrient = NutrientType::Micro(MicroNutrientsType::Fiber);
-        let err = ProductSwapper::get_amount_of_swapped_product(
-            &olive,
-            10.0,
+        let mut solution = single_product_solution(olive, 55.0, AllowedUnitsType::Gram);
+
+        swap_in_solution(
+            &mut solution,
+            "Olive",
             &milk,
-            nutrient,
-            AllowedUnitsType::Gram,
+            &[NutrientType::Macro(MacroElementsType::Fat)],
         )
-        .expect_err("missing nutrient should return error");
-        let expected = format!("Input product 'Olive' does not have nutrient '{nutrient:?}'");
-        assert_eq!(err, expected);
+        .expect("swap should succeed");
+
+        // 55g * 91 / 3 is 1668.3g
+        let (_, grams, unit, amount_unit) = first_product(&solution);
+        assert_eq!(unit, AllowedUnitsType::Gram);
+        assert_eq!((amount_unit.numerator, amount_unit.denominator), (1668, 1));
+        assert_relative_eq!(grams, 1668.0);
+    }
+
+    #[test]
+    fn swap_in_solution_keeps_unit_only_when_output_has_it() {
+        let cup = UnitData {
+            amount: 240,
+            divider: 4,
+        };
+        // the input has no cup unit, only its grams are used
+        let olive = make_product("Olive", 90.0, &[]);
+        let with_cup = make_product("Oil", 90.0, &[(AllowedUnitsType::Cup, cup)]);
+        let without_cup = make_product(
+            "Butter",
+            90.0,
+            &[(
+                AllowedUnitsType::Custom,
+                UnitData {
+                    amount: 10,
+                    divider: 1,
+                },
+            )],
+        );
+        let fat = [NutrientType::Macro(MacroElementsType::Fat)];
+
+        let mut solution = single_product_solution(olive.clone(), 120.0, AllowedUnitsType::Gram);
+        set_first_unit(&mut solution, AllowedUnitsType::Cup);
+        let mut fallback = single_product_solution(olive, 120.0, AllowedUnitsType::Gram);
+        set_first_unit(&mut fallback, AllowedUnitsType::Cup);
+
+        swap_in_solution(&mut solution, "Olive", &with_cup, &fat).expect("swap should succeed");
+        let (_, grams, unit, amount_unit) = first_product(&solution);
+        assert_eq!(unit, AllowedUnitsType::Cup);
+        assert_eq!((amount_unit.numerator, amount_unit.denominator), (2, 4));
+        assert_relative_eq!(grams, 120.0);
+
+        swap_in_solution(&mut fallback, "Olive", &without_cup, &fat).expect("swap should succeed");
+        let (_, grams, unit, amount_unit) = first_product(&fallback);
+        assert_eq!(unit, AllowedUnitsType::Gram);
+        assert_eq!((amount_unit.numerator, amount_unit.denominator), (120, 1));
+        assert_relative_eq!(grams, 120.0);
     }
 
     #[test]
-    fn error_when_allowed_unit_missing_on_input() {
+    fn swap_in_solution_ignores_nutrients_missing_in_input() {
         let olive = make_product("Olive", 91.0, &[]);
         let milk = make_product("Milk", 3.0, &[]);
-        let err = ProductSwapper::get_amount_of_swapped_product(
-            &olive,
-            55.0,
-            &milk,
-            NutrientType::Macro(MacroElementsType::Fat),
-            AllowedUnitsType::Cup,
-        )
-        .expect_err("missing unit should return error");
+        let fiber = NutrientType::Micro(MicroNutrientsType::Fiber);
+        let fat = NutrientType::Macro(MacroElementsType::Fat);
+
+        // the output has no fiber either, it isn't needed when the input has none
+        let mut solution = single_product_solution(olive.clone(), 30.0, AllowedUnitsType::Gram);
+        swap_in_solution(&mut solution, "Olive", &milk, &[fiber, fat])
+            .expect("swap should succeed");
+        let (_, grams, _, _) = first_product(&solution);
+        assert_relative_eq!(grams, 910.0);
+
+        let mut solution = single_product_solution(olive, 30.0, AllowedUnitsType::Gram);
+        let err = swap_in_solution(&mut solution, "Olive", &milk, &[fiber])
+            .expect_err("input without preserved nutrients should fail");
         assert_eq!(
             err,
-            "Input product 'Olive' does not have allowed unit 'Cup'"
+            "Output product 'Milk' has none of the preserved nutrients"
         );
+        let (product, grams, _, _) = first_product(&solution);
+        assert_eq!(product.name(), "Olive");
+        assert_relative_eq!(grams, 30.0);
     }
 
     #[test]
-    fn error_when_output_missing_nutrient() {
+    fn swap_in_solution_balances_around_zero_output_nutrient() {
         let mut olive = make_product("Olive", 91.0, &[]);
-        let milk = make_product("Milk", 3.0, &[]);
+        let mut milk = make_product("Milk", 3.0, &[]);
         olive.micro_nutrients[MicroNutrientsType::Fiber] = Some(5.0);
-        let nutrient = NutrientType::Micro(MicroNutrientsType::Fiber);
-        let err = ProductSwapper::get_amount_of_swapped_product(
-            &olive,
-            10.0,
+        milk.micro_nutrients[MicroNutrientsType::Fiber] = Some(0.0);
+        let mut solution = single_product_solution(olive, 30.0, AllowedUnitsType::Gram);
+
+        // milk can't match the fiber, the amount follows the fat
+        swap_in_solution(
+            &mut solution,
+            "Olive",
             &milk,
-            nutrient,
-            AllowedUnitsType::Gram,
+            &[
+                NutrientType::Micro(MicroNutrientsType::Fiber),
+                NutrientType::Macro(MacroElementsType::Fat),
+            ],
         )
-        .expect_err("missing output nutrient should return error");
-        let expected = format!("Output product 'Milk' does not have nutrient '{nutrient:?}'");
-        assert_eq!(err, expected);
+        .expect("swap should succeed");
+        let (_, grams, _, _) = first_product(&solution);
+        assert_relative_eq!(grams, 910.0);
     }
 
     #[test]
-    fn error_when_output_nutrient_is_zero() {
-        let mut olive = make_product("Olive", 91.0, &[]);
-        let mut milk = make_product("Milk", 3.0, &[]);
-        olive.micro_nutrients[MicroNutrientsType::Fiber] = Some(5.0);
-        milk.micro_nutrients[MicroNutrientsType::Fiber] = Some(0.0);
-        let nutrient = NutrientType::Micro(MicroNutrientsType::Fiber);
-        let err = ProductSwapper::get_amount_of_swapped_product(
-            &olive,
-            10.0,
-            &milk,
-            nutrient,
-            AllowedUnitsType::Gram,
-        )
-        .expect_err("zero output nutrient should return error");
-        let expected = format!("Output product 'Milk' has zero amount of nutrient '{nutrient:?}'");
-        assert_eq!(err, expected);
+    fn swap_in_solution_reports_missing_product() {
+        let olive = make_product("Olive", 90.0, &[]);
+        let butter = make_product("Butter", 80.0, &[]);
+        let mut solution = single_product_solution(olive, 100.0, AllowedUnitsType::Gram);
+        let err = swap_in_solution(&mut solution, "Milk", &butter, &[])
+            .expect_err("missing product should fail");
+        assert_eq!(err, "Product 'Milk' is not in the solution");
     }
 }
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::{
+    DietaryRestrictions, MacroElementsType, MicroNutrientsType, Sex, UserProfile,
+    constraints::{DayMealPlanConstraint, MealConstraint, NutrientConstraint},
+};
+
+const PROTEIN_G_PER_KG: f32 = 1.6;
+const FAT_SHARE_OF_CALORIES: f32 = 0.3;
+const FIBER_G_PER_1000_KCAL: f32 = 14.0;
+pub(super) const KCAL_PER_G_FAT: f32 = 9.0;
+pub(super) const KCAL_PER_G_CARBS_OR_PROTEIN: f32 = 4.0;
+/// Relative deviation from the targets accepted by [`NutritionTargets::day_constraint`].
+pub(super) const TARGET_TOLERANCE: f32 = 0.1;
+
+/// Daily energy and macro targets computed from a [`UserProfile`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NutritionTargets {
+    /// Basal metabolic rate (Mifflin-St Jeor), in kcal.
+    pub bmr_kcal: f32,
+    /// Total daily energy expenditure, the calorie target, in kcal.
+    pub tdee_kcal: f32,
+    pub protein_g: f32,
+    pub fat_g: f32,
+    pub carbs_g: f32,
+    pub fiber_g: f32,
+}
+
+impl NutritionTargets {
+    /// Computes maintenance targets: 1.6 g protein per kg, 30% of energy from fat, carbs for the
+    /// rest and 14 g fiber per 1000 kcal.
+    pub fn from_profile(profile: &UserProfile) -> Result<Self, String> {
+        profile.validate()?;
+        let bmr_kcal = mifflin_st_jeor(profile);
+        let tdee_kcal = bmr_kcal * profile.activity_level.multiplier();
+        let protein_g = PROTEIN_G_PER_KG * profile.weight_kg;
+        let fat_g = tdee_kcal * FAT_SHARE_OF_CALORIES / KCAL_PER_G_FAT;
+        let carbs_g =
+            ((tdee_kcal - protein_g * KCAL_PER_G_CARBS_OR_PROTEIN - fat_g * KCAL_PER_G_FAT)
+                / KCAL_PER_G_CARBS_OR_PROTEIN)
+                .max(0.0);
+        Ok(Self {
+            bmr_kcal,
+            tdee_kcal,
+            protein_g,
+            fat_g,
+            carbs_g,
+            fiber_g: tdee_kcal / 1000.0 * FIBER_G_PER_1000_KCAL,
+        })
+    }
+
+    /// Builds a day constraint keeping calories and macros within 10% of the targets, with the
+    /// fiber target as a minimum.
+    #[must_use]
+    pub fn day_constraint(&self, meals: HashMap<String, MealConstraint>) -> DayMealPlanConstraint {
+        let around = |element: MacroElementsType, target: f32| {
+            NutrientConstraint::new(
+                element,
+                Some(target * (1.0 - TARGET_TOLERANCE)),
+                Some(target * (1.0 + TARGET_TOLERANCE)),
+            )
+        };
+        let nutrients = [
+            around(MacroElementsType::Calories, self.tdee_kcal),
+            around(MacroElementsType::Protein, self.protein_g),
+            around(MacroElementsType::Fat, self.fat_g),
+            around(MacroElementsType::Carbs, self.carbs_g),
+            NutrientConstraint::new(MicroNutrientsType::Fiber, Some(self.fiber_g), None),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        DayMealPlanConstraint {
+            meals,
+            nutrients,
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        }
+    }
+}
+
+fn mifflin_st_jeor(profile: &UserProfile) -> f32 {
+    let sex_offset = match profile.sex {
+        Sex::Male => 5.0,
+        Sex::Female => -161.0,
+    };
+    10.0 * profile.weight_kg + 6.25 * profile.height_cm - 5.0 * f32::from(profile.age_years)
+        + sex_offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{ActivityLevel, NutrientType};
+    use approx::assert_relative_eq;
+
+    fn profile(sex: Sex) -> UserProfile {
+        UserProfile {
+            age_years: 30,
+            sex,
+            weight_kg: 80.0,
+            height_cm: 180.0,
+            activity_level: ActivityLevel::Sedentary,
+        }
+    }
+
+    #[test]
+    fn computes_mifflin_st_jeor_targets() {
+        let targets = NutritionTargets::from_profile(&profile(Sex::Male)).unwrap();
+        // 800 + 1125 - 150 + 5
+        assert_relative_eq!(targets.bmr_kcal, 1780.0);
+        assert_relative_eq!(targets.tdee_kcal, 2136.0, epsilon = 1e-3);
+        assert_relative_eq!(targets.protein_g, 128.0);
+        assert_relative_eq!(targets.fat_g, 71.2, epsilon = 1e-3);
+        assert_relative_eq!(targets.carbs_g, 245.8, epsilon = 1e-3);
+        assert_relative_eq!(targets.fiber_g, 29.904, epsilon = 1e-3);
+
+        let female = NutritionTargets::from_profile(&profile(Sex::Female)).unwrap();
+        assert_relative_eq!(female.bmr_kcal, 1614.0);
+    }
+
+    #[test]
+    fn rejects_invalid_profile() {
+        let invalid = UserProfile {
+            weight_kg: -1.0,
+            ..profile(Sex::Male)
+        };
+        assert!(NutritionTargets::from_profile(&invalid).is_err());
+    }
+
+    #[test]
+    fn day_constraint_bounds_follow_targets() {
+        let targets = NutritionTargets::from_profile(&profile(Sex::Male)).unwrap();
+        let constraint = targets.day_constraint(HashMap::new());
+        assert_eq!(constraint.nutrients.len(), 5);
+
+        let calories = constraint
+            .nutrients
+            .iter()
+            .find(|n| n.element() == NutrientType::Macro(MacroElementsType::Calories))
+            .unwrap();
+        assert_relative_eq!(calories.min().unwrap(), 2136.0 * 0.9, epsilon = 1e-2);
+        assert_relative_eq!(calories.max().unwrap(), 2136.0 * 1.1, epsilon = 1e-2);
+
+        let fiber = constraint
+            .nutrients
+            .iter()
+            .find(|n| n.element() == NutrientType::Micro(MicroNutrientsType::Fiber))
+            .unwrap();
+        assert_eq!(fiber.max(), None);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Nutrition targets as computed from the user profile, with the data they were based on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TargetsRecord {
+    /// Unix timestamp (seconds) of the targets calculation.
+    pub computed_at: u64,
+    /// Body weight used in the calculation.
+    pub weight_kg: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReviewReason {
+    WeightChanged { delta_kg: f32 },
+    TargetsOutdated { age_days: u64 },
+}
+
+/// Decides when nutrition targets should be recalculated.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TargetsReviewRule {
+    /// How often the check runs, so a dismissed prompt does not come back right away.
+    pub check_interval_days: u64,
+    pub max_weight_change_kg: f32,
+    pub max_age_days: u64,
+}
+
+impl Default for TargetsReviewRule {
+    fn default() -> Self {
+        Self {
+            check_interval_days: 7,
+            max_weight_change_kg: 3.0,
+            max_age_days: 182,
+        }
+    }
+}
+
+impl TargetsReviewRule {
+    /// Returns true when the previous check (if any) is at least `check_interval_days` old.
+    #[must_use]
+    pub fn is_due(&self, last_checked: Option<u64>, now: u64) -> bool {
+        last_checked.is_none_or(|last| {
+            now.saturating_sub(last) >= self.check_interval_days * SECONDS_PER_DAY
+        })
+    }
+
+    /// Lists the reasons why `targets` should be recalculated, empty when they are up to date.
+    #[must_use]
+    pub fn check(
+        &self,
+        targets: &TargetsRecord,
+        current_weight_kg: f32,
+        now: u64,
+    ) -> Vec<ReviewReason> {
+        let mut reasons = Vec::new();
+        let delta_kg = current_weight_kg - targets.weight_kg;
+        if delta_kg.abs() > self.max_weight_change_kg {
+            reasons.push(ReviewReason::WeightChanged { delta_kg });
+        }
+        let age_days = now.saturating_sub(targets.computed_at) / SECONDS_PER_DAY;
+        if age_days >= self.max_age_days {
+            reasons.push(ReviewReason::TargetsOutdated { age_days });
+        }
+        reasons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn targets(days_ago: u64, weight_kg: f32) -> TargetsRecord {
+        TargetsRecord {
+            computed_at: NOW - days_ago * SECONDS_PER_DAY,
+            weight_kg,
+        }
+    }
+
+    #[test]
+    fn test_fresh_targets_need_no_review() {
+        let rule = TargetsReviewRule::default();
+        assert!(rule.check(&targets(30, 80.0), 81.5, NOW).is_empty());
+    }
+
+    #[test]
+    fn test_weight_change_and_age_are_reported() {
+        let rule = TargetsReviewRule::default();
+        let reasons = rule.check(&targets(200, 80.0), 76.5, NOW);
+        assert_eq!(
+            reasons,
+            vec![
+                ReviewReason::WeightChanged { delta_kg: -3.5 },
+                ReviewReason::TargetsOutdated { age_days: 200 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_is_due_after_interval() {
+        let rule = TargetsReviewRule {
+            check_interval_days: 3,
+            ..TargetsReviewRule::default()
+        };
+        assert!(rule.is_due(None, NOW));
+        assert!(!rule.is_due(Some(NOW - 2 * SECONDS_PER_DAY), NOW));
+        assert!(rule.is_due(Some(NOW - 3 * SECONDS_PER_DAY), NOW));
+    }
+}
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+
+use super::constraints_solver::{ConstraintsSolver, Solution, SolutionEntry};
+use crate::data_types::{
+    AllowedUnitsType, EatenItem, NutrientType, Product,
+    constraints::{DayMealPlanConstraint, ProductConstraint},
+};
+
+/// How much of a day nutrient constraint is used up by what was already eaten.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NutrientBudget {
+    pub nutrient: NutrientType,
+    pub eaten: f64,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+}
+
+impl NutrientBudget {
+    /// Amount still needed to reach the minimum, 0 once it is reached.
+    #[must_use]
+    pub fn min_left(&self) -> Option<f64> {
+        self.min.map(|min| (f64::from(min) - self.eaten).max(0.0))
+    }
+
+    /// Amount that can still be eaten, negative once the maximum is exceeded.
+    #[must_use]
+    pub fn max_left(&self) -> Option<f64> {
+        self.max.map(|max| f64::from(max) - self.eaten)
+    }
+
+    #[must_use]
+    pub fn is_exceeded(&self) -> bool {
+        self.max_left().is_some_and(|left| left < 0.0)
+    }
+}
+
+/// Every product of `meal` in the first day of `solution` at its planned amount, for marking a
+/// whole meal as eaten.
+#[must_use]
+pub fn planned_meal_items(solution: &Solution, meal: &str) -> Vec<EatenItem> {
+    meal_products(solution, meal)
+        .into_iter()
+        .map(|(product, amount_grams)| EatenItem {
+            meal: meal.to_string(),
+            product_id: product.id(),
+            grams: amount_grams,
+        })
+        .collect()
+}
+
+/// Budget left in every nutrient constraint of `day` after eating `eaten` from the first day of
+/// `solution`. Fails when an item is not part of the plan.
+pub fn remaining_budget(
+    solution: &Solution,
+    day: &DayMealPlanConstraint,
+    eaten: &[EatenItem],
+) -> Result<Vec<NutrientBudget>, String> {
+    let eaten_products = eaten
+        .iter()
+        .map(|item| {
+            let product = meal_products(solution, &item.meal)
+                .into_iter()
+                .map(|(product, _)| product)
+                .find(|product| product.id() == item.product_id)
+                .ok_or_else(|| {
+                    format!(
+                        "Product '{}' is not planned for meal '{}'",
+                        item.product_id, item.meal
+                    )
+                })?;
+            Ok((product, item.grams))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(day
+        .nutrients
+        .iter()
+        .map(|constraint| {
+            let nutrient = constraint.element();
+            let eaten = eaten_products
+                .iter()
+                .filter(|(product, _)| !product.negligible)
+                .map(|(product, grams)| {
+                    f64::from(product.get_nutrient_amount(nutrient).unwrap_or(0.0)) * grams / 100.0
+                })
+                .sum();
+            NutrientBudget {
+                nutrient,
+                eaten,
+                min: constraint.min(),
+                max: constraint.max(),
+            }
+        })
+        .collect())
+}
+
+/// Solves `day` again with every eaten product fixed at its eaten amount, so the rest of the
+/// day makes up for what was eaten differently than planned.
+pub fn resolve_remaining(
+    solver: &mut ConstraintsSolver,
+    mut day: DayMealPlanConstraint,
+    eaten: &[EatenItem],
+) -> Result<Solution, String> {
+    for item in eaten {
+        item.validate()?;
+        let not_planned = || {
+            format!(
+                "Product '{}' is not planned for meal '{}'",
+                item.product_id, item.meal
+            )
+        };
+        let constraint = day
+            .meals
+            .get_mut(&item.meal)
+            .and_then(|meal| {
+                meal.products
+                    .iter_mut()
+                    .find(|constraint| constraint.food().id() == item.product_id)
+            })
+            .ok_or_else(not_planned)?;
+        // eaten amounts are in grams, whatever unit the product was planned in
+        #[allow(clippy::cast_possible_truncation)]
+        let grams = item.grams as f32;
+        let food = Box::new(constraint.food().clone());
+        let unit = if food.allowed_units.contains_key(&AllowedUnitsType::Gram) {
+            AllowedUnitsType::Gram
+        } else {
+            constraint.unit()
+        };
+        let fixed = ProductConstraint::new_fractional(food, Some(grams), Some(grams), unit)
+            .ok_or_else(not_planned)?;
+        constraint.update(fixed);
+    }
+    solver.solve_day(&day)
+}
+
+// products of `meal` in the first day of `solution` with their planned grams
+fn meal_products<'a>(solution: &'a Solution, meal: &str) -> Vec<(&'a Product, f64)> {
+    let Some(SolutionEntry::Day { entries, .. }) = solution.days().into_iter().next() else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            SolutionEntry::Meal { name, entries, .. } if name == meal => Some(entries),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|entry| match entry {
+            SolutionEntry::Product {
+                product,
+                amount_grams,
+                ..
+            } => Some((product, *amount_grams)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::MinOrMax;
+    use crate::data_types::{
+        DietaryRestrictions, MacroElementsType, ProductBuilder,
+        constraints::{MealConstraint, NutrientConstraint},
+    };
+    use approx::assert_relative_eq;
+    use std::collections::HashMap;
+
+    const PROTEIN: MacroElementsType = MacroElementsType::Protein;
+
+    fn meal(products: &[(&str, f32, u16)]) -> MealConstraint {
+        MealConstraint {
+            products: products
+                .iter()
+                .map(|(name, protein, min)| {
+                    let product = ProductBuilder::new(*name)
+                        .protein(*protein)
+                        .build()
+                        .unwrap();
+                    ProductConstraint::new(
+                        Box::new(product),
+                        Some(*min),
+                        Some(300),
+                        AllowedUnitsType::Gram,
+                    )
+                    .unwrap()
+                })
+                .collect(),
+            nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
+        }
+    }
+
+    fn day() -> DayMealPlanConstraint {
+        DayMealPlanConstraint {
+            meals: HashMap::from([
+                ("Breakfast".to_string(), meal(&[("Eggs", 13.0, 100)])),
+                ("Dinner".to_string(), meal(&[("Tofu", 8.0, 0)])),
+            ]),
+            nutrients: vec![NutrientConstraint::new(PROTEIN, Some(40.0), Some(50.0)).unwrap()],
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn budget_and_resolve_follow_eaten_amounts() {
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, NutrientType::Macro(PROTEIN));
+        let solution = solver.solve_day(&day()).unwrap();
+
+        // more eggs than planned, enough for the whole day minimum
+        let mut eaten = planned_meal_items(&solution, "Breakfast");
+        assert_eq!(eaten.len(), 1);
+        eaten[0].grams = 350.0;
+        let budget = remaining_budget(&solution, &day(), &eaten).unwrap();
+        assert_relative_eq!(budget[0].eaten, 45.5, epsilon = 1e-6);
+        assert_relative_eq!(budget[0].min_left().unwrap(), 0.0);
+        assert_relative_eq!(budget[0].max_left().unwrap(), 4.5, epsilon = 1e-6);
+        assert!(!budget[0].is_exceeded());
+
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, NutrientType::Macro(PROTEIN));
+        let resolved = resolve_remaining(&mut solver, day(), &eaten).unwrap();
+        assert_relative_eq!(
+            resolved.nutrient_total(NutrientType::Macro(PROTEIN)),
+            45.5,
+            epsilon = 1e-4
+        );
+
+        let unplanned = vec![EatenItem {
+            meal: "Dinner".to_string(),
+            product_id: "Eggs".into(),
+            grams: 100.0,
+        }];
+        assert!(remaining_budget(&solution, &day(), &unplanned).is_err());
+        let mut solver = ConstraintsSolver::new(MinOrMax::Min, NutrientType::Macro(PROTEIN));
+        assert!(resolve_remaining(&mut solver, day(), &unplanned).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+// single workouts above this are most likely typos
+const MAX_ACTIVITY_KCAL: u32 = 10_000;
+
+/// Workout logged by the user or imported from a GPX/TCX file, stored per profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityLog {
+    /// Unix timestamp (seconds) of the start of the activity.
+    pub performed_at: u64,
+    /// Estimated energy burned on top of the resting expenditure.
+    pub kcal_burned: u32,
+    /// E.g. "Running", `None` when not named.
+    pub name: Option<String>,
+}
+
+impl ActivityLog {
+    #[must_use]
+    pub fn new(performed_at: u64, kcal_burned: u32) -> Self {
+        Self {
+            performed_at,
+            kcal_burned,
+            name: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_ACTIVITY_KCAL).contains(&self.kcal_burned) {
+            return Err(format!(
+                "Burned energy must be between 1 and {MAX_ACTIVITY_KCAL} kcal: {}",
+                self.kcal_burned
+            ));
+        }
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err("Activity name is empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_checks_energy_and_name() {
+        assert_eq!(ActivityLog::new(0, 350).validate(), Ok(()));
+        assert!(ActivityLog::new(0, 0).validate().is_err());
+        assert!(ActivityLog::new(0, 20_000).validate().is_err());
+        let unnamed = ActivityLog {
+            name: Some(" ".to_string()),
+            ..ActivityLog::new(0, 350)
+        };
+        assert!(unnamed.validate().is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use super::EnergyUnit;
+
+const LANGUAGE_KEY: &str = "language";
+const SIDEBAR_WIDTH_KEY: &str = "sidebar_width";
+const SELECTED_DB_KEY: &str = "selected_db";
+const ENERGY_UNIT_KEY: &str = "energy_unit";
+
+// same range the app allows when resizing the sidebar
+const MIN_SIDEBAR_WIDTH: f32 = 160.0;
+const MAX_SIDEBAR_WIDTH: f32 = 320.0;
+
+/// App preferences kept between sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Language identifier, e.g. "en-US".
+    pub language: String,
+    /// Width of the sidebar in pixels.
+    pub sidebar_width: f32,
+    /// File of the local database last worked with, `None` for the default one.
+    pub selected_db: Option<String>,
+    /// Unit calories are shown in.
+    #[serde(default)]
+    pub energy_unit: EnergyUnit,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            language: "en-US".to_string(),
+            sidebar_width: 224.0,
+            selected_db: None,
+            energy_unit: EnergyUnit::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.language.trim().is_empty() {
+            return Err("Language cannot be empty".to_string());
+        }
+        if !(MIN_SIDEBAR_WIDTH..=MAX_SIDEBAR_WIDTH).contains(&self.sidebar_width) {
+            return Err(format!(
+                "Sidebar width must be between {MIN_SIDEBAR_WIDTH} and {MAX_SIDEBAR_WIDTH}, got {}",
+                self.sidebar_width
+            ));
+        }
+        if self
+            .selected_db
+            .as_ref()
+            .is_some_and(|db| db.trim().is_empty())
+        {
+            return Err("Selected database cannot be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Flattens the settings into `(key, value)` rows of the `app_settings` table.
+    pub(crate) fn to_rows(&self) -> Vec<(String, String)> {
+        let mut rows = vec![
+            (LANGUAGE_KEY.to_string(), self.language.clone()),
+            (
+                SIDEBAR_WIDTH_KEY.to_string(),
+                self.sidebar_width.to_string(),
+            ),
+            (ENERGY_UNIT_KEY.to_string(), self.energy_unit.to_string()),
+        ];
+        if let Some(db) = &self.selected_db {
+            rows.push((SELECTED_DB_KEY.to_string(), db.clone()));
+        }
+        rows
+    }
+
+    /// Missing, unknown or invalid rows fall back to the defaults, so older or newer apps can
+    /// share the table.
+    pub(crate) fn from_rows(rows: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut settings = Self::default();
+        for (key, value) in rows {
+            match key.as_str() {
+                LANGUAGE_KEY if !value.trim().is_empty() => settings.language = value,
+                SIDEBAR_WIDTH_KEY => {
+                    if let Ok(width) = value.parse::<f32>() {
+                        settings.sidebar_width = width.clamp(MIN_SIDEBAR_WIDTH, MAX_SIDEBAR_WIDTH);
+                    }
+                }
+                SELECTED_DB_KEY if !value.trim().is_empty() => settings.selected_db = Some(value),
+                ENERGY_UNIT_KEY => {
+                    if let Some(unit) = [EnergyUnit::Kcal, EnergyUnit::Kj]
+                        .into_iter()
+                        .find(|unit| unit.to_string() == value)
+                    {
+                        settings.energy_unit = unit;
+                    }
+                }
+                _ => {}
+            }
+        }
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip_and_fall_back_to_defaults() {
+        let settings = AppSettings {
+            language: "pl-PL".to_string(),
+            sidebar_width: 300.5,
+            selected_db: Some("work.db".to_string()),
+            energy_unit: EnergyUnit::Kj,
+        };
+        assert_eq!(AppSettings::from_rows(settings.to_rows()), settings);
+
+        let rows = vec![
+            (SIDEBAR_WIDTH_KEY.to_string(), "wide".to_string()),
+            (LANGUAGE_KEY.to_string(), String::new()),
+            ("theme".to_string(), "dark".to_string()),
+            (ENERGY_UNIT_KEY.to_string(), "joules".to_string()),
+        ];
+        assert_eq!(AppSettings::from_rows(rows), AppSettings::default());
+        let too_wide = vec![(SIDEBAR_WIDTH_KEY.to_string(), "9000".to_string())];
+        assert!(AppSettings::from_rows(too_wide).validate().is_ok());
+    }
+
+    #[test]
+    fn validates_fields() {
+        assert!(AppSettings::default().validate().is_ok());
+        let narrow = AppSettings {
+            sidebar_width: 10.0,
+            ..AppSettings::default()
+        };
+        assert!(narrow.validate().is_err());
+        let blank_db = AppSettings {
+            selected_db: Some(" ".to_string()),
+            ..AppSettings::default()
+        };
+        assert!(blank_db.validate().is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use super::MicroNutrientsType;
+
+/// How much of a product's nutrition data is filled in, see [`super::Product::completeness`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletenessReport {
+    /// Micro nutrients without a value, constraints on them treat the product as having none.
+    pub missing_micro_nutrients: Vec<MicroNutrientsType>,
+    /// Percentage of micro nutrients with a value, 100 when nothing is missing.
+    pub score: f32,
+}
+
+impl CompletenessReport {
+    #[must_use]
+    pub fn from_missing(missing_micro_nutrients: Vec<MicroNutrientsType>) -> Self {
+        let total = MicroNutrientsType::iter().count();
+        let known = total.saturating_sub(missing_micro_nutrients.len());
+        // both counts are bounded by the number of micro nutrients
+        let as_f32 = |count: usize| f32::from(u16::try_from(count).unwrap_or(u16::MAX));
+        Self {
+            missing_micro_nutrients,
+            score: 100.0 * as_f32(known) / as_f32(total),
+        }
+    }
+
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.missing_micro_nutrients.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn score_is_share_of_known_micro_nutrients() {
+        assert_relative_eq!(CompletenessReport::from_missing(Vec::new()).score, 100.0);
+        assert_relative_eq!(
+            CompletenessReport::from_missing(MicroNutrientsType::iter().collect()).score,
+            0.0
+        );
+
+        let report = CompletenessReport::from_missing(vec![MicroNutrientsType::Zinc]);
+        let total = f32::from(u16::try_from(MicroNutrientsType::iter().count()).unwrap());
+        assert_relative_eq!(report.score, 100.0 * (total - 1.0) / total, epsilon = 1e-4);
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,306 @@
+use std::ops::{Bound, RangeBounds};
+
+use super::{
+    DayMealPlanConstraint, MealConstraint, NutrientConstraint, ProductConstraint,
+    ProductRatioConstraint,
+};
+use crate::data_types::{AllowedUnitsType, DietaryRestrictions, MealSlot, NutrientType, Product};
+
+/// Fluent way of putting a [`DayMealPlanConstraint`] together, e.g.
+/// `DayConstraintBuilder::new().meal("Breakfast", |m| m.product(apple, 0..=300, Gram))
+/// .nutrient(Calories, ..=2200.0)`. Invalid constraints are reported by [`Self::build`].
+#[derive(Clone, Default)]
+pub struct DayConstraintBuilder {
+    day: DayMealPlanConstraint,
+    error: Option<String>,
+}
+
+/// Constraints of one meal, see [`DayConstraintBuilder::meal`].
+#[derive(Clone, Default)]
+pub struct MealConstraintBuilder {
+    meal: MealConstraint,
+    error: Option<String>,
+}
+
+impl DayConstraintBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds meal `name` with the constraints set by `meal`.
+    #[must_use]
+    pub fn meal(
+        mut self,
+        name: impl Into<String>,
+        meal: impl FnOnce(MealConstraintBuilder) -> MealConstraintBuilder,
+    ) -> Self {
+        let name = name.into();
+        let built = meal(MealConstraintBuilder::new());
+        let error = if name.trim().is_empty() {
+            Some("Meal name can't be empty".to_string())
+        } else if self.day.meals.contains_key(&name) {
+            Some(format!("Meal '{name}' already exists"))
+        } else {
+            built.error.map(|e| format!("Meal '{name}': {e}"))
+        };
+        if let Some(error) = error {
+            self.error.get_or_insert(error);
+        } else {
+            self.day.meals.insert(name, built.meal);
+        }
+        self
+    }
+
+    /// Bounds the daily total of `nutrient`, open ends are unbounded.
+    #[must_use]
+    pub fn nutrient(
+        mut self,
+        nutrient: impl Into<NutrientType>,
+        range: impl RangeBounds<f32>,
+    ) -> Self {
+        match nutrient_constraint(nutrient.into(), &range) {
+            Ok(constraint) => self.day.nutrients.push(constraint),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    /// Bounds the daily total of `product` over all meals in `unit`, e.g. at most 2 cups of
+    /// coffee. The product has to be added to at least one meal too.
+    #[must_use]
+    pub fn product_total(
+        mut self,
+        product: impl Into<Box<Product>>,
+        amount: impl RangeBounds<f32>,
+        unit: AllowedUnitsType,
+    ) -> Self {
+        match product_constraint(product.into(), f32_bounds(&amount), unit) {
+            Ok(constraint) => self.day.products.push(constraint),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn max_cost(mut self, max_cost: f32) -> Self {
+        self.day.max_cost = Some(max_cost);
+        self
+    }
+
+    #[must_use]
+    pub fn restrictions(mut self, restrictions: DietaryRestrictions) -> Self {
+        self.day.restrictions = restrictions;
+        self
+    }
+
+    /// The day, or the first invalid constraint that was given.
+    pub fn build(self) -> Result<DayMealPlanConstraint, String> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.day),
+        }
+    }
+}
+
+impl MealConstraintBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `product` with whole `amount` bounds in `unit`, exclusive ends are moved inwards.
+    #[must_use]
+    pub fn product(
+        self,
+        product: impl Into<Box<Product>>,
+        amount: impl RangeBounds<u16>,
+        unit: AllowedUnitsType,
+    ) -> Self {
+        let low = match amount.start_bound() {
+            Bound::Included(low) => Ok(Some(*low)),
+            Bound::Excluded(low) => low
+                .checked_add(1)
+                .map(Some)
+                .ok_or_else(|| "Empty amount range".to_string()),
+            Bound::Unbounded => Ok(None),
+        };
+        let up = match amount.end_bound() {
+            Bound::Included(up) => Ok(Some(*up)),
+            Bound::Excluded(up) => up
+                .checked_sub(1)
+                .map(Some)
+                .ok_or_else(|| "Empty amount range".to_string()),
+            Bound::Unbounded => Ok(None),
+        };
+        let bounds = low.and_then(|low| up.map(|up| (low.map(f32::from), up.map(f32::from))));
+        self.add_product(product.into(), bounds, unit)
+    }
+
+    /// Adds `product` with fractional `amount` bounds in `unit`, e.g. for oils and spices.
+    /// Exclusive ends count as inclusive, amounts are continuous.
+    #[must_use]
+    pub fn product_fractional(
+        self,
+        product: impl Into<Box<Product>>,
+        amount: impl RangeBounds<f32>,
+        unit: AllowedUnitsType,
+    ) -> Self {
+        let bounds = Ok(f32_bounds(&amount));
+        self.add_product(product.into(), bounds, unit)
+    }
+
+    /// Bounds the total of `nutrient` in the meal, open ends are unbounded.
+    #[must_use]
+    pub fn nutrient(
+        mut self,
+        nutrient: impl Into<NutrientType>,
+        range: impl RangeBounds<f32>,
+    ) -> Self {
+        match nutrient_constraint(nutrient.into(), &range) {
+            Ok(constraint) => self.meal.nutrients.push(constraint),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn ratio(mut self, ratio: ProductRatioConstraint) -> Self {
+        self.meal.ratios.push(ratio);
+        self
+    }
+
+    #[must_use]
+    pub fn max_products(mut self, max_products: u16) -> Self {
+        self.meal.max_products = Some(max_products);
+        self
+    }
+
+    #[must_use]
+    pub fn min_products(mut self, min_products: u16) -> Self {
+        self.meal.min_products = Some(min_products);
+        self
+    }
+
+    #[must_use]
+    pub fn slot(mut self, slot: MealSlot) -> Self {
+        self.meal.slot = Some(slot);
+        self
+    }
+
+    /// The meal, or the first invalid constraint that was given.
+    pub fn build(self) -> Result<MealConstraint, String> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.meal),
+        }
+    }
+
+    fn add_product(
+        mut self,
+        product: Box<Product>,
+        bounds: Result<(Option<f32>, Option<f32>), String>,
+        unit: AllowedUnitsType,
+    ) -> Self {
+        match bounds.and_then(|bounds| product_constraint(product, bounds, unit)) {
+            Ok(constraint) => self.meal.products.push(constraint),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+}
+
+fn f32_bounds(range: &impl RangeBounds<f32>) -> (Option<f32>, Option<f32>) {
+    let value = |bound: Bound<&f32>| match bound {
+        Bound::Included(value) | Bound::Excluded(value) => Some(*value),
+        Bound::Unbounded => None,
+    };
+    (value(range.start_bound()), value(range.end_bound()))
+}
+
+fn product_constraint(
+    product: Box<Product>,
+    (low, up): (Option<f32>, Option<f32>),
+    unit: AllowedUnitsType,
+) -> Result<ProductConstraint, String> {
+    let product_id = product.id();
+    ProductConstraint::new_fractional(product, low, up, unit).ok_or_else(|| {
+        format!("Invalid constraint for '{product_id}': unit {unit} not allowed or min above max")
+    })
+}
+
+fn nutrient_constraint(
+    nutrient: NutrientType,
+    range: &impl RangeBounds<f32>,
+) -> Result<NutrientConstraint, String> {
+    let (min, max) = f32_bounds(range);
+    NutrientConstraint::new(nutrient, min, max)
+        .ok_or_else(|| format!("Invalid {nutrient} constraint"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{AllowedUnitsType::Gram, MacroElementsType, ProductBuilder};
+
+    #[test]
+    fn builds_days_and_reports_invalid_constraints() {
+        let apple = ProductBuilder::new("Apple")
+            .carbs(14.0)
+            .unit(Gram, 1, 1)
+            .build()
+            .unwrap();
+        let day = DayConstraintBuilder::new()
+            .meal("Breakfast", |m| {
+                m.product(apple.clone(), 1..300, Gram)
+                    .nutrient(MacroElementsType::Protein, 20.0..=40.0)
+                    .max_products(2)
+            })
+            .meal("Snack", |m| {
+                m.product_fractional(apple.clone(), ..=0.5, Gram)
+            })
+            .nutrient(MacroElementsType::Calories, ..=2200.0)
+            .build()
+            .unwrap();
+
+        let breakfast = &day.meals["Breakfast"];
+        let product = &breakfast.products[0];
+        assert_eq!(
+            (product.low_bound(), product.up_bound()),
+            (Some(1.0), Some(299.0))
+        );
+        assert_eq!(breakfast.nutrients[0].min(), Some(20.0));
+        assert_eq!(breakfast.max_products, Some(2));
+        assert_eq!(day.meals["Snack"].products[0].up_bound(), Some(0.5));
+        let calories = day.nutrients[0];
+        assert_eq!((calories.min(), calories.max()), (None, Some(2200.0)));
+
+        let duplicate = DayConstraintBuilder::new()
+            .meal("Lunch", |m| m)
+            .meal("Lunch", |m| m)
+            .build();
+        assert!(duplicate.is_err());
+        let wrong_unit = DayConstraintBuilder::new()
+            .meal("Lunch", |m| {
+                m.product(apple.clone(), .., AllowedUnitsType::Cup)
+            })
+            .build();
+        assert!(wrong_unit.is_err());
+        let empty_range = MealConstraintBuilder::new()
+            .product(apple, ..0, Gram)
+            .build();
+        assert!(empty_range.is_err());
+        let min_above_max = DayConstraintBuilder::new()
+            .nutrient(MacroElementsType::Fat, 50.0..=10.0)
+            .build();
+        assert!(min_above_max.is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! Day constraints as a JSON document to share between people and apps.
+//!
+//! Products are referenced by id instead of embedded, so a document prepared with one product
+//! database loads into another one with the same products. Version 1 of the document:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "meals": {
+//!     "Breakfast": {
+//!       "products": [
+//!         { "product_id": "Oats (Brand)", "low_bound": 40.0, "up_bound": 80.0, "unit": "Gram" },
+//!         { "product_id": "Rice", "up_bound": 200.0, "unit": "Gram", "cooked": true }
+//!       ],
+//!       "nutrients": [{ "element": { "Macro": "Protein" }, "min": 20.0, "max": null }],
+//!       "max_products": null,
+//!       "min_products": null,
+//!       "ratios": [],
+//!       "slot": null
+//!     }
+//!   },
+//!   "nutrients": [{ "element": { "Macro": "Calories" }, "min": 1800.0, "max": 2200.0 }],
+//!   "max_cost": null,
+//!   "restrictions": { "excluded_allergens": [], "required_diets": [] },
+//!   "macro_percents": [{ "element": "Protein", "min_percent": 25.0, "max_percent": 35.0 }],
+//!   "products": [{ "product_id": "Coffee", "up_bound": 300.0, "unit": "Gram" }]
+//! }
+//! ```
+//!
+//! Bounds are grams, of the cooked product when `cooked` is set, and `unit` is the unit amounts
+//! are shown in. Meals may also have `recipes`, which embed their ingredients. Everything except
+//! `version`, `meals` and `product_id` may be left out.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DayMealPlanConstraint, MacroPercentConstraint, MealConstraint, NutrientConstraint,
+    ProductConstraint, ProductRatioConstraint, RecipeConstraint,
+};
+use crate::data_types::{AllowedUnitsType, DietaryRestrictions, MealSlot, Product, ProductId};
+
+/// Newest version of the document written by [`DayMealPlanConstraint::to_json`].
+pub const CONSTRAINT_DOCUMENT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct DayDocument {
+    version: u32,
+    meals: BTreeMap<String, MealDocument>,
+    #[serde(default)]
+    nutrients: Vec<NutrientConstraint>,
+    #[serde(default)]
+    max_cost: Option<f32>,
+    #[serde(default)]
+    restrictions: DietaryRestrictions,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    macro_percents: Vec<MacroPercentConstraint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    products: Vec<ProductReference>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MealDocument {
+    products: Vec<ProductReference>,
+    #[serde(default)]
+    nutrients: Vec<NutrientConstraint>,
+    #[serde(default)]
+    max_products: Option<u16>,
+    #[serde(default)]
+    min_products: Option<u16>,
+    #[serde(default)]
+    ratios: Vec<ProductRatioConstraint>,
+    #[serde(default)]
+    slot: Option<MealSlot>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recipes: Vec<RecipeConstraint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProductReference {
+    product_id: ProductId,
+    #[serde(default)]
+    low_bound: Option<f32>,
+    #[serde(default)]
+    up_bound: Option<f32>,
+    #[serde(default = "gram")]
+    unit: AllowedUnitsType,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cooked: bool,
+}
+
+fn gram() -> AllowedUnitsType {
+    AllowedUnitsType::Gram
+}
+
+impl ProductReference {
+    fn of(constraint: &ProductConstraint) -> Self {
+        Self {
+            product_id: constraint.food().id(),
+            low_bound: constraint.low_bound(),
+            up_bound: constraint.up_bound(),
+            unit: constraint.unit(),
+            cooked: constraint.is_cooked(),
+        }
+    }
+
+    fn resolve(
+        self,
+        product: &mut impl FnMut(&ProductId) -> Option<Product>,
+    ) -> Result<ProductConstraint, String> {
+        let id = self.product_id;
+        let food = product(&id).ok_or_else(|| format!("Unknown product '{id}'"))?;
+        let constraint = ProductConstraint::new_fractional(
+            Box::new(food),
+            self.low_bound,
+            self.up_bound,
+            self.unit,
+        )
+        .ok_or_else(|| {
+            format!(
+                "Invalid constraint for '{id}': unit {} not allowed or min above max",
+                self.unit
+            )
+        })?;
+        if self.cooked {
+            constraint
+                .in_cooked_grams()
+                .ok_or_else(|| format!("Product '{id}' has no cooked variant"))
+        } else {
+            Ok(constraint)
+        }
+    }
+}
+
+impl DayMealPlanConstraint {
+    /// The constraints as a JSON document with products referenced by id, see the
+    /// [module docs](self) for the schema.
+    pub fn to_json(&self) -> Result<String, String> {
+        let meals = self
+            .meals
+            .iter()
+            .map(|(name, meal)| {
+                let document = MealDocument {
+                    products: meal.products.iter().map(ProductReference::of).collect(),
+                    nutrients: meal.nutrients.clone(),
+                    max_products: meal.max_products,
+                    min_products: meal.min_products,
+                    ratios: meal.ratios.clone(),
+                    slot: meal.slot.clone(),
+                    recipes: meal.recipes.clone(),
+                };
+                (name.clone(), document)
+            })
+            .collect();
+        let document = DayDocument {
+            version: CONSTRAINT_DOCUMENT_VERSION,
+            meals,
+            nutrients: self.nutrients.clone(),
+            max_cost: self.max_cost,
+            restrictions: self.restrictions.clone(),
+            macro_percents: self.macro_percents.clone(),
+            products: self.products.iter().map(ProductReference::of).collect(),
+        };
+        serde_json::to_string_pretty(&document)
+            .map_err(|e| format!("Failed to serialize constraints: {e}"))
+    }
+
+    /// Reads a document written by [`Self::to_json`] or by hand, looking up referenced products
+    /// with `product`. Fails on newer documents, unknown products and invalid constraints.
+    pub fn from_json(
+        json: &str,
+        mut product: impl FnMut(&ProductId) -> Option<Product>,
+    ) -> Result<Self, String> {
+        let document: DayDocument =
+            serde_json::from_str(json).map_err(|e| format!("Invalid constraints document: {e}"))?;
+        if document.version > CONSTRAINT_DOCUMENT_VERSION {
+            return Err(format!(
+                "Constraints document version {} is newer than supported {CONSTRAINT_DOCUMENT_VERSION}",
+                document.version
+            ));
+        }
+        let mut meals = std::collections::HashMap::new();
+        for (name, meal) in document.meals {
+            if name.trim().is_empty() {
+                return Err("Meal name can't be empty".to_string());
+            }
+            let products = meal
+                .products
+                .into_iter()
+                .map(|reference| reference.resolve(&mut product))
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|e| format!("Meal '{name}': {e}"))?;
+            let meal = MealConstraint {
+                products,
+                nutrients: meal.nutrients,
+                max_products: meal.max_products,
+                min_products: meal.min_products,
+                ratios: meal.ratios,
+                slot: meal.slot,
+                recipes: meal.recipes,
+            };
+            meals.insert(name, meal);
+        }
+        let products = document
+            .products
+            .into_iter()
+            .map(|reference| reference.resolve(&mut product))
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(|e| format!("Day: {e}"))?;
+        Ok(Self {
+            meals,
+            nutrients: document.nutrients,
+            max_cost: document.max_cost,
+            restrictions: document.restrictions,
+            macro_percents: document.macro_percents,
+            products,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{MacroElements, MacroElementsType, UnitData};
+
+    fn product(name: &str) -> Product {
+        Product::new(
+            name.to_string(),
+            Some("Farm".to_string()),
+            Box::new(MacroElements::new(1.0, 0.0, 10.0, 1.0, 5.0)),
+            Box::default(),
+            std::collections::HashMap::from([(
+                AllowedUnitsType::Gram,
+                UnitData {
+                    amount: 1,
+                    divider: 1,
+                },
+            )]),
+        )
+    }
+
+    #[test]
+    fn constraints_round_trip_with_products_by_id() {
+        let products: BTreeMap<_, _> = [product("Oats"), product("Milk")]
+            .into_iter()
+            .map(|p| (p.id(), p))
+            .collect();
+        let day = crate::data_types::constraints::DayConstraintBuilder::new()
+            .meal("Breakfast", |meal| {
+                meal.product(
+                    products["Oats (Farm)"].clone(),
+                    40..=80,
+                    AllowedUnitsType::Gram,
+                )
+                .product(products["Milk (Farm)"].clone(), .., AllowedUnitsType::Gram)
+                .nutrient(MacroElementsType::Protein, 20.0..)
+            })
+            .nutrient(MacroElementsType::Calories, 1800.0..=2200.0)
+            .product_total(
+                products["Milk (Farm)"].clone(),
+                ..=250.0,
+                AllowedUnitsType::Gram,
+            )
+            .build()
+            .unwrap();
+
+        let json = day.to_json().unwrap();
+        assert!(json.contains("\"product_id\": \"Oats (Farm)\""));
+        assert!(!json.contains("macro_elements"));
+        let loaded =
+            DayMealPlanConstraint::from_json(&json, |id| products.get(id).cloned()).unwrap();
+        assert_eq!(loaded.fingerprint(), day.fingerprint());
+
+        let missing = DayMealPlanConstraint::from_json(&json, |_| None)
+            .err()
+            .unwrap();
+        assert!(missing.contains("Unknown product"), "{missing}");
+        let newer = json.replace("\"version\": 1", "\"version\": 2");
+        assert!(DayMealPlanConstraint::from_json(&newer, |id| products.get(id).cloned()).is_err());
+    }
+
+    #[test]
+    fn hand_written_document_uses_defaults() {
+        let oats = product("Oats");
+        let json = r#"{
+            "version": 1,
+            "meals": { "Breakfast": { "products": [{ "product_id": "Oats (Farm)", "up_bound": 60 }] } }
+        }"#;
+        let day = DayMealPlanConstraint::from_json(json, |_| Some(oats.clone())).unwrap();
+        let constraint = &day.meals["Breakfast"].products[0];
+        assert_eq!(constraint.unit(), AllowedUnitsType::Gram);
+        assert_eq!(constraint.low_bound(), None);
+        assert_eq!(constraint.up_bound(), Some(60.0));
+        assert!(day.nutrients.is_empty());
+    }
+}
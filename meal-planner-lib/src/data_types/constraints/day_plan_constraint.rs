@@ -1,11 +1,229 @@
 use std::collections::HashMap;
+use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
+use super::MacroPercentConstraint;
 use super::MealConstraint;
 use super::NutrientConstraint;
+use super::ProductConstraint;
+use super::RatioBase;
+use crate::data_types::{DietaryRestrictions, NutrientType, ProductId};
 
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct DayMealPlanConstraint {
     pub meals: HashMap<String, MealConstraint>,
+    #[serde(default)]
     pub nutrients: Vec<NutrientConstraint>,
+    /// Upper bound on the total price of the day, in the currency of its products.
+    #[serde(default)]
+    pub max_cost: Option<f32>,
+    /// Products not meeting these are left out of every meal of the day.
+    #[serde(default)]
+    pub restrictions: DietaryRestrictions,
+    /// Shares of the calories of the day from fat, carbs or protein.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub macro_percents: Vec<MacroPercentConstraint>,
+    /// Bounds on the total amount of a product over all meals of the day, e.g. at most 300 ml
+    /// of coffee. The product has to be in at least one meal.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub products: Vec<ProductConstraint>,
+}
+
+/// Reason an edit of the meals of a day was refused, the day is left unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MealEditError {
+    EmptyName,
+    UnknownMeal(String),
+    MealExists(String),
+    UnknownProduct {
+        meal: String,
+        product: ProductId,
+    },
+    /// The target meal already has a constraint on the product.
+    ProductExists {
+        meal: String,
+        product: ProductId,
+    },
+    /// A ratio of the meal refers to the product, it has to be removed first.
+    ProductInRatio {
+        meal: String,
+        product: ProductId,
+    },
+    /// The meal would be left with fewer products than its `min_products`.
+    TooFewProducts {
+        meal: String,
+        min_products: u16,
+    },
+}
+
+impl fmt::Display for MealEditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MealEditError::EmptyName => write!(f, "Meal name can't be empty"),
+            MealEditError::UnknownMeal(meal) => write!(f, "Meal '{meal}' doesn't exist"),
+            MealEditError::MealExists(meal) => write!(f, "Meal '{meal}' already exists"),
+            MealEditError::UnknownProduct { meal, product } => {
+                write!(f, "Meal '{meal}' has no product '{product}'")
+            }
+            MealEditError::ProductExists { meal, product } => {
+                write!(f, "Meal '{meal}' already has product '{product}'")
+            }
+            MealEditError::ProductInRatio { meal, product } => {
+                write!(f, "Product '{product}' is used in a ratio of meal '{meal}'")
+            }
+            MealEditError::TooFewProducts { meal, min_products } => {
+                write!(f, "Meal '{meal}' needs at least {min_products} products")
+            }
+        }
+    }
+}
+
+impl From<MealEditError> for String {
+    fn from(error: MealEditError) -> Self {
+        error.to_string()
+    }
+}
+
+impl DayMealPlanConstraint {
+    /// Renames meal `from` to `to`, keeping its constraints. Fails when `from` doesn't exist or
+    /// `to` is already taken.
+    pub fn rename_meal(&mut self, from: &str, to: &str) -> Result<(), MealEditError> {
+        if to.trim().is_empty() {
+            return Err(MealEditError::EmptyName);
+        }
+        if !self.meals.contains_key(from) {
+            return Err(MealEditError::UnknownMeal(from.to_string()));
+        }
+        if from == to {
+            return Ok(());
+        }
+        if self.meals.contains_key(to) {
+            return Err(MealEditError::MealExists(to.to_string()));
+        }
+        if let Some(meal) = self.meals.remove(from) {
+            self.meals.insert(to.to_string(), meal);
+        }
+        Ok(())
+    }
+
+    /// Removes meal `name` with all its constraints and returns it.
+    pub fn remove_meal(&mut self, name: &str) -> Result<MealConstraint, MealEditError> {
+        self.meals
+            .remove(name)
+            .ok_or_else(|| MealEditError::UnknownMeal(name.to_string()))
+    }
+
+    /// Moves the constraint on `product` from meal `from` to meal `to`, bounds and unit
+    /// included. Fails when the constraint is missing, `to` already has one on the product, a
+    /// ratio of `from` refers to it or `from` would go below its `min_products`.
+    pub fn move_product_between_meals(
+        &mut self,
+        product: &ProductId,
+        from: &str,
+        to: &str,
+    ) -> Result<(), MealEditError> {
+        let source = self
+            .meals
+            .get(from)
+            .ok_or_else(|| MealEditError::UnknownMeal(from.to_string()))?;
+        let target = self
+            .meals
+            .get(to)
+            .ok_or_else(|| MealEditError::UnknownMeal(to.to_string()))?;
+        let index = source
+            .products
+            .iter()
+            .position(|constraint| constraint.food().id() == *product)
+            .ok_or_else(|| MealEditError::UnknownProduct {
+                meal: from.to_string(),
+                product: product.clone(),
+            })?;
+        if from == to {
+            return Ok(());
+        }
+        if target
+            .products
+            .iter()
+            .any(|constraint| constraint.food().id() == *product)
+        {
+            return Err(MealEditError::ProductExists {
+                meal: to.to_string(),
+                product: product.clone(),
+            });
+        }
+        if source.ratios.iter().any(|ratio| {
+            ratio.product_id() == product || *ratio.base() == RatioBase::Product(product.clone())
+        }) {
+            return Err(MealEditError::ProductInRatio {
+                meal: from.to_string(),
+                product: product.clone(),
+            });
+        }
+        if let Some(min_products) = source.min_products
+            && source.products.len() <= usize::from(min_products)
+        {
+            return Err(MealEditError::TooFewProducts {
+                meal: from.to_string(),
+                min_products,
+            });
+        }
+
+        let constraint = self
+            .meals
+            .get_mut(from)
+            .map(|meal| meal.products.remove(index));
+        if let (Some(constraint), Some(meal)) = (constraint, self.meals.get_mut(to)) {
+            meal.products.push(constraint);
+        }
+        Ok(())
+    }
+
+    /// Moves min and max of every day and meal nutrient constraint by `percent`, e.g. `-10.0`
+    /// for 10% fewer. With `nutrient` set, only constraints on it are changed.
+    pub fn shift_nutrients(
+        &mut self,
+        percent: f32,
+        nutrient: Option<NutrientType>,
+    ) -> Result<(), String> {
+        if !percent.is_finite() || percent < -100.0 {
+            return Err(format!("Invalid nutrient shift: {percent}%"));
+        }
+        let factor = 1.0 + percent / 100.0;
+        let constraints = self
+            .nutrients
+            .iter_mut()
+            .chain(self.meals.values_mut().flat_map(|meal| &mut meal.nutrients))
+            .filter(|constraint| nutrient.is_none_or(|n| constraint.element() == n));
+        for constraint in constraints {
+            let shifted = NutrientConstraint::new(
+                constraint.element(),
+                constraint.min().map(|min| min * factor),
+                constraint.max().map(|max| max * factor),
+            )
+            .ok_or_else(|| format!("Invalid {} constraint", constraint.element()))?;
+            constraint.update(shifted);
+        }
+        Ok(())
+    }
+
+    /// Hash of the constraints, products included, equal for equal constraints across runs and
+    /// platforms, regardless of the order meals were added in.
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        stable_hash(self)
+    }
+}
+
+// FNV-1a over the JSON with sorted object keys, unlike `DefaultHasher` it doesn't change between
+// Rust versions
+pub(crate) fn stable_hash<T: Serialize>(value: &T) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    let json = serde_json::to_value(value).unwrap_or_default().to_string();
+    json.bytes().fold(OFFSET, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
 }
 
 #[cfg(test)]
@@ -16,14 +234,29 @@ mod tests {
         let breakfast = MealConstraint {
             products: Vec::new(),
             nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
         let lunch = MealConstraint {
             products: Vec::new(),
             nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
         let dinner = MealConstraint {
             products: Vec::new(),
             nutrients: Vec::new(),
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
         let mut meals = HashMap::new();
         meals.insert("breakfast".to_string(), breakfast);
@@ -32,6 +265,10 @@ mod tests {
         DayMealPlanConstraint {
             meals,
             nutrients: Vec::new(),
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
         }
     }
 
@@ -48,8 +285,207 @@ mod tests {
             MealConstraint {
                 products: Vec::new(),
                 nutrients: Vec::new(),
+                max_products: None,
+                min_products: None,
+                ratios: Vec::new(),
+                slot: None,
+                recipes: Vec::new(),
             },
         );
         assert!(plan.meals.contains_key(&keys[1]));
     }
+
+    #[test]
+    fn test_day_meal_plan_serde_round_trip() {
+        use crate::data_types::{
+            Allergen, MacroElementsType, MealKind, MealSlot, constraints::ProductRatioConstraint,
+            constraints::RatioBase, constraints::RatioRelation,
+        };
+
+        let mut plan = init_day_plan();
+        let lunch = plan.meals.get_mut("lunch").unwrap();
+        lunch
+            .nutrients
+            .push(NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap());
+        lunch.ratios.push(
+            ProductRatioConstraint::new(
+                "Oil".to_string(),
+                RatioBase::MealWeight,
+                RatioRelation::AtMost,
+                0.1,
+            )
+            .unwrap(),
+        );
+        lunch.max_products = Some(3);
+        lunch.slot = Some(MealSlot::new(MealKind::Lunch, None));
+        plan.max_cost = Some(25.0);
+        plan.restrictions.excluded_allergens.insert(Allergen::Nuts);
+
+        let json = serde_json::to_value(&plan).unwrap();
+        let restored: DayMealPlanConstraint = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), json);
+        assert_eq!(restored.meals["lunch"].max_products, Some(3));
+        assert_eq!(restored.restrictions, plan.restrictions);
+
+        // everything but the meals and their products is optional
+        let minimal: DayMealPlanConstraint =
+            serde_json::from_str(r#"{"meals":{"dinner":{"products":[]}}}"#).unwrap();
+        assert_eq!(minimal.meals.len(), 1);
+        assert!(minimal.nutrients.is_empty());
+        assert_eq!(minimal.max_cost, None);
+    }
+
+    #[test]
+    fn test_day_meal_plan_rename_meal() {
+        let mut plan = init_day_plan();
+        plan.rename_meal("lunch", "brunch").unwrap();
+        assert!(plan.meals.contains_key("brunch"));
+        assert!(!plan.meals.contains_key("lunch"));
+        assert!(plan.rename_meal("lunch", "supper").is_err());
+        assert!(plan.rename_meal("brunch", "dinner").is_err());
+        assert_eq!(
+            plan.rename_meal("brunch", " "),
+            Err(MealEditError::EmptyName)
+        );
+        assert_eq!(plan.meals.len(), 3);
+
+        assert!(plan.remove_meal("brunch").is_ok());
+        assert_eq!(
+            plan.remove_meal("brunch").err(),
+            Some(MealEditError::UnknownMeal("brunch".to_string()))
+        );
+        assert_eq!(plan.meals.len(), 2);
+    }
+
+    #[test]
+    fn test_day_meal_plan_move_product_between_meals() {
+        use super::super::{ProductConstraint, ProductRatioConstraint, RatioRelation};
+        use crate::data_types::{AllowedUnitsType, MacroElements, Product, UnitData};
+
+        let product = |name: &str| {
+            Box::new(Product::new(
+                name.to_string(),
+                None,
+                Box::new(MacroElements::new(1.0, 0.0, 2.0, 0.0, 3.0)),
+                Box::default(),
+                HashMap::from([(
+                    AllowedUnitsType::Gram,
+                    UnitData {
+                        amount: 1,
+                        divider: 1,
+                    },
+                )]),
+            ))
+        };
+        let constraint = |name: &str| {
+            ProductConstraint::new(product(name), Some(10), Some(50), AllowedUnitsType::Gram)
+                .unwrap()
+        };
+        let eggs = product("Eggs").id();
+        let oats = product("Oats").id();
+        let mut plan = init_day_plan();
+        plan.meals.get_mut("breakfast").unwrap().products =
+            vec![constraint("Eggs"), constraint("Oats")];
+        plan.meals.get_mut("lunch").unwrap().products = vec![constraint("Oats")];
+
+        plan.move_product_between_meals(&eggs, "breakfast", "dinner")
+            .unwrap();
+        assert_eq!(plan.meals["breakfast"].products.len(), 1);
+        assert_eq!(plan.meals["dinner"].products[0].food().id(), eggs);
+
+        let unchanged = |plan: &DayMealPlanConstraint| {
+            plan.meals
+                .iter()
+                .map(|(name, meal)| (name.clone(), meal.products.len()))
+                .collect::<HashMap<_, _>>()
+        };
+        let before = unchanged(&plan);
+        assert!(matches!(
+            plan.move_product_between_meals(&oats, "breakfast", "lunch"),
+            Err(MealEditError::ProductExists { .. })
+        ));
+        assert!(matches!(
+            plan.move_product_between_meals(&eggs, "breakfast", "lunch"),
+            Err(MealEditError::UnknownProduct { .. })
+        ));
+        assert_eq!(
+            plan.move_product_between_meals(&oats, "breakfast", "supper"),
+            Err(MealEditError::UnknownMeal("supper".to_string()))
+        );
+        plan.meals.get_mut("lunch").unwrap().ratios = vec![
+            ProductRatioConstraint::new(
+                oats.clone(),
+                RatioBase::MealWeight,
+                RatioRelation::AtMost,
+                0.5,
+            )
+            .unwrap(),
+        ];
+        assert!(matches!(
+            plan.move_product_between_meals(&oats, "lunch", "dinner"),
+            Err(MealEditError::ProductInRatio { .. })
+        ));
+        plan.meals.get_mut("breakfast").unwrap().min_products = Some(1);
+        assert!(matches!(
+            plan.move_product_between_meals(&oats, "breakfast", "dinner"),
+            Err(MealEditError::TooFewProducts { .. })
+        ));
+        assert_eq!(unchanged(&plan), before);
+    }
+
+    #[test]
+    fn test_day_meal_plan_shift_nutrients() {
+        use crate::data_types::MacroElementsType;
+        use approx::assert_relative_eq;
+
+        let calories = NutrientType::Macro(MacroElementsType::Calories);
+        let mut plan = init_day_plan();
+        plan.nutrients = vec![
+            NutrientConstraint::new(calories, Some(2000.0), Some(2200.0)).unwrap(),
+            NutrientConstraint::new(MacroElementsType::Protein, Some(100.0), None).unwrap(),
+        ];
+        plan.meals.get_mut("lunch").unwrap().nutrients =
+            vec![NutrientConstraint::new(calories, None, Some(800.0)).unwrap()];
+        let mut copy = plan.clone();
+
+        copy.shift_nutrients(-10.0, Some(calories)).unwrap();
+        assert_relative_eq!(copy.nutrients[0].min().unwrap(), 1800.0);
+        assert_relative_eq!(copy.nutrients[0].max().unwrap(), 1980.0);
+        assert_eq!(copy.nutrients[1], plan.nutrients[1]);
+        assert_relative_eq!(copy.meals["lunch"].nutrients[0].max().unwrap(), 720.0);
+        assert_eq!(copy.meals["lunch"].nutrients[0].min(), None);
+
+        copy.shift_nutrients(50.0, None).unwrap();
+        assert_relative_eq!(copy.nutrients[1].min().unwrap(), 150.0);
+        assert!(copy.shift_nutrients(-101.0, None).is_err());
+        assert!(copy.shift_nutrients(f32::NAN, None).is_err());
+        // the original stays untouched
+        assert_relative_eq!(plan.nutrients[0].min().unwrap(), 2000.0);
+    }
+
+    #[test]
+    fn test_day_meal_plan_fingerprint() {
+        use crate::data_types::MacroElementsType;
+
+        let plan = init_day_plan();
+        let mut reordered = DayMealPlanConstraint::default();
+        for name in ["dinner", "lunch", "breakfast"] {
+            reordered
+                .meals
+                .insert(name.to_string(), plan.meals[name].clone());
+        }
+        assert_eq!(plan.fingerprint(), reordered.fingerprint());
+        assert_eq!(plan.fingerprint(), plan.clone().fingerprint());
+
+        reordered
+            .meals
+            .get_mut("lunch")
+            .unwrap()
+            .nutrients
+            .push(NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap());
+        assert_ne!(plan.fingerprint(), reordered.fingerprint());
+        let mut cheaper = plan.clone();
+        cheaper.max_cost = Some(10.0);
+        assert_ne!(plan.fingerprint(), cheaper.fingerprint());
+    }
 }
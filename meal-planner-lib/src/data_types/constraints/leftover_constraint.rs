@@ -0,0 +1,99 @@
+use crate::data_types::ProductId;
+
+/// Product cooked once as a batch and eaten over several days of a week plan.
+///
+/// All of the product eaten from day `cooked_on` up to `eaten_within_days` days later comes from
+/// the one batch, e.g. rice cooked on Monday and finished by Wednesday.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeftoverConstraint {
+    product_id: ProductId,
+    cooked_on: usize,
+    eaten_within_days: u8,
+    min_batch_grams: Option<u16>,
+    max_batch_grams: Option<u16>,
+}
+
+impl LeftoverConstraint {
+    /// Longest time leftovers are planned to be kept for.
+    pub const MAX_DAYS_KEPT: u8 = 2;
+
+    /// `cooked_on` is the index of the day in the week plan.
+    #[must_use]
+    pub fn new(
+        product_id: impl Into<ProductId>,
+        cooked_on: usize,
+        eaten_within_days: u8,
+        min_batch_grams: Option<u16>,
+        max_batch_grams: Option<u16>,
+    ) -> Option<Self> {
+        if eaten_within_days == 0 || eaten_within_days > Self::MAX_DAYS_KEPT {
+            return None;
+        }
+        if let (Some(min), Some(max)) = (min_batch_grams, max_batch_grams)
+            && min > max
+        {
+            return None;
+        }
+        Some(Self {
+            product_id: product_id.into(),
+            cooked_on,
+            eaten_within_days,
+            min_batch_grams,
+            max_batch_grams,
+        })
+    }
+
+    #[must_use]
+    pub fn product_id(&self) -> &ProductId {
+        &self.product_id
+    }
+
+    #[must_use]
+    pub fn cooked_on(&self) -> usize {
+        self.cooked_on
+    }
+
+    #[must_use]
+    pub fn eaten_within_days(&self) -> u8 {
+        self.eaten_within_days
+    }
+
+    /// Indexes of the days the batch is eaten on, the cooking day included.
+    #[must_use]
+    pub fn days(&self) -> std::ops::RangeInclusive<usize> {
+        self.cooked_on..=self.cooked_on + usize::from(self.eaten_within_days)
+    }
+
+    #[must_use]
+    pub fn min_batch_grams(&self) -> Option<u16> {
+        self.min_batch_grams
+    }
+
+    #[must_use]
+    pub fn max_batch_grams(&self) -> Option<u16> {
+        self.max_batch_grams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leftover_constraint_constructor() {
+        let leftover =
+            LeftoverConstraint::new("Rice".to_string(), 1, 2, Some(300), Some(600)).unwrap();
+        assert_eq!(leftover.product_id(), "Rice");
+        assert_eq!(leftover.cooked_on(), 1);
+        assert_eq!(leftover.days(), 1..=3);
+        assert_eq!(leftover.min_batch_grams(), Some(300));
+        assert_eq!(leftover.max_batch_grams(), Some(600));
+    }
+
+    #[test]
+    fn test_leftover_constraint_rejects_invalid_values() {
+        assert!(LeftoverConstraint::new("Rice".to_string(), 0, 0, None, None).is_none());
+        assert!(LeftoverConstraint::new("Rice".to_string(), 0, 3, None, None).is_none());
+        assert!(LeftoverConstraint::new("Rice".to_string(), 0, 1, Some(500), Some(400)).is_none());
+    }
+}
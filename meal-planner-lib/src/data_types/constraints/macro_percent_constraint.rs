@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::MacroElementsType;
+
+/// Share of the calories of the day coming from fat, carbs or protein, in percent,
+/// e.g. "25–35% of calories from protein".
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "MacroPercentConstraintFields")]
+pub struct MacroPercentConstraint {
+    element: MacroElementsType,
+    min_percent: Option<f32>,
+    max_percent: Option<f32>,
+}
+
+// deserialized fields, checked by `MacroPercentConstraint::new`
+#[derive(Deserialize)]
+struct MacroPercentConstraintFields {
+    element: MacroElementsType,
+    min_percent: Option<f32>,
+    max_percent: Option<f32>,
+}
+
+impl TryFrom<MacroPercentConstraintFields> for MacroPercentConstraint {
+    type Error = String;
+
+    fn try_from(fields: MacroPercentConstraintFields) -> Result<Self, Self::Error> {
+        Self::new(fields.element, fields.min_percent, fields.max_percent)
+            .ok_or_else(|| format!("Invalid {} percent of calories", fields.element))
+    }
+}
+
+impl MacroPercentConstraint {
+    /// `None` unless `element` is fat, carbs or protein and the percents are within 0–100 with
+    /// the minimum not above the maximum.
+    #[must_use]
+    pub fn new(
+        element: MacroElementsType,
+        min_percent: Option<f32>,
+        max_percent: Option<f32>,
+    ) -> Option<Self> {
+        // only macros with a known energy per gram
+        Self::kcal_per_gram(element)?;
+        let valid =
+            |percent: Option<f32>| percent.is_none_or(|percent| (0.0..=100.0).contains(&percent));
+        if !valid(min_percent) || !valid(max_percent) {
+            return None;
+        }
+        if let (Some(min), Some(max)) = (min_percent, max_percent)
+            && min > max
+        {
+            return None;
+        }
+        Some(Self {
+            element,
+            min_percent,
+            max_percent,
+        })
+    }
+
+    /// Energy of a gram of `element`, for the macros that can be given as percent of calories.
+    #[must_use]
+    pub fn kcal_per_gram(element: MacroElementsType) -> Option<f32> {
+        match element {
+            MacroElementsType::Fat => Some(9.0),
+            MacroElementsType::Carbs | MacroElementsType::Protein => Some(4.0),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn element(&self) -> MacroElementsType {
+        self.element
+    }
+
+    #[must_use]
+    pub fn min_percent(&self) -> Option<f32> {
+        self.min_percent
+    }
+
+    #[must_use]
+    pub fn max_percent(&self) -> Option<f32> {
+        self.max_percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macro_percent_constraint_validation() {
+        assert!(
+            MacroPercentConstraint::new(MacroElementsType::Protein, Some(25.0), Some(35.0))
+                .is_some()
+        );
+        assert!(MacroPercentConstraint::new(MacroElementsType::Fat, None, Some(30.0)).is_some());
+        assert!(MacroPercentConstraint::new(MacroElementsType::Sugar, None, Some(10.0)).is_none());
+        assert!(
+            MacroPercentConstraint::new(MacroElementsType::Carbs, Some(60.0), Some(40.0)).is_none()
+        );
+        assert!(MacroPercentConstraint::new(MacroElementsType::Carbs, None, Some(120.0)).is_none());
+        assert!(
+            serde_json::from_str::<MacroPercentConstraint>(
+                r#"{"element":"Fat","min_percent":-5.0,"max_percent":null}"#
+            )
+            .is_err()
+        );
+    }
+}
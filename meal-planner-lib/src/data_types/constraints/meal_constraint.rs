@@ -1,9 +1,33 @@
+use serde::{Deserialize, Serialize};
+
 use super::NutrientConstraint;
 use super::ProductConstraint;
+use super::ProductRatioConstraint;
+use super::RecipeConstraint;
+use crate::data_types::MealSlot;
 
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct MealConstraint {
     pub products: Vec<ProductConstraint>,
+    #[serde(default)]
     pub nutrients: Vec<NutrientConstraint>,
+    /// When set, the solver picks at most this many of `products` instead of using all of them.
+    /// Product low bounds then apply only to the picked products.
+    #[serde(default)]
+    pub max_products: Option<u16>,
+    /// When set, the solver picks at least this many of `products`. Negligible products are not
+    /// counted towards either limit.
+    #[serde(default)]
+    pub min_products: Option<u16>,
+    /// Proportions between products of the meal, so amounts stay realistic for a recipe.
+    #[serde(default)]
+    pub ratios: Vec<ProductRatioConstraint>,
+    /// When the meal is eaten, meals of a solved day are ordered by it.
+    #[serde(default)]
+    pub slot: Option<MealSlot>,
+    /// Recipes eaten in whole servings, their ingredients count like products of the meal.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipes: Vec<RecipeConstraint>,
 }
 
 #[cfg(test)]
@@ -16,6 +40,7 @@ mod tests {
         UnitData,
     };
 
+    #[allow(clippy::too_many_lines)]
     fn initialize_meal_with_products() -> MealConstraint {
         let mut macro_elements_vec = vec![
             Box::new(MacroElements::new(1.0, 2.0, 3.0, 4.0, 5.0)),
@@ -116,6 +141,11 @@ mod tests {
         MealConstraint {
             products,
             nutrients,
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         }
     }
 
@@ -173,7 +203,7 @@ mod tests {
             );
         }
         let modified = &meal.products[1];
-        assert_eq!(modified.low_bound(), Some(4));
-        assert_eq!(modified.up_bound(), Some(5));
+        assert_eq!(modified.low_bound(), Some(4.0));
+        assert_eq!(modified.up_bound(), Some(5.0));
     }
 }
@@ -1,9 +1,25 @@
+mod builder;
+mod constraint_document;
 mod day_plan_constraint;
+mod leftover_constraint;
+mod macro_percent_constraint;
 mod meal_constraint;
 mod nutrient_constraint;
 mod product_constraint;
+mod product_ratio_constraint;
+mod recipe_constraint;
+mod repetition_constraint;
+mod week_plan_constraint;
 
+pub use builder::*;
+pub use constraint_document::*;
 pub use day_plan_constraint::*;
+pub use leftover_constraint::*;
+pub use macro_percent_constraint::*;
 pub use meal_constraint::*;
 pub use nutrient_constraint::*;
 pub use product_constraint::*;
+pub use product_ratio_constraint::*;
+pub use recipe_constraint::*;
+pub use repetition_constraint::*;
+pub use week_plan_constraint::*;
@@ -1,11 +1,40 @@
+use serde::{Deserialize, Serialize};
+
 use crate::data_types::{MacroElementsType, MicroNutrientsType, NutrientType};
 
 // Constraint on a nutritional element (macro or micro)
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "NutrientConstraintFields")]
 pub struct NutrientConstraint {
     element: NutrientType,
     min: Option<f32>,
     max: Option<f32>,
+    // soft constraints may be broken at this cost per unit of the nutrient
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    penalty: Option<f32>,
+}
+
+// deserialized fields, checked by `NutrientConstraint::new`
+#[derive(Deserialize)]
+struct NutrientConstraintFields {
+    element: NutrientType,
+    min: Option<f32>,
+    max: Option<f32>,
+    #[serde(default)]
+    penalty: Option<f32>,
+}
+
+impl TryFrom<NutrientConstraintFields> for NutrientConstraint {
+    type Error = String;
+
+    fn try_from(fields: NutrientConstraintFields) -> Result<Self, Self::Error> {
+        let constraint = Self::new(fields.element, fields.min, fields.max);
+        match fields.penalty {
+            Some(penalty) => constraint.and_then(|c| c.soft(penalty)),
+            None => constraint,
+        }
+        .ok_or_else(|| format!("Invalid {} constraint", fields.element))
+    }
 }
 
 impl From<MacroElementsType> for NutrientType {
@@ -46,9 +75,31 @@ impl NutrientConstraint {
             element: element.into(),
             min,
             max,
+            penalty: None,
         })
     }
 
+    /// Makes the constraint soft: the solver may break its bounds, paying `penalty` in the
+    /// objective per unit of the nutrient outside them, instead of reporting infeasibility.
+    #[must_use]
+    pub fn soft(mut self, penalty: f32) -> Option<Self> {
+        if !penalty.is_finite() || penalty <= 0.0 {
+            return None;
+        }
+        self.penalty = Some(penalty);
+        Some(self)
+    }
+
+    #[must_use]
+    pub fn penalty(&self) -> Option<f32> {
+        self.penalty
+    }
+
+    #[must_use]
+    pub fn is_soft(&self) -> bool {
+        self.penalty.is_some()
+    }
+
     #[must_use]
     pub fn element(&self) -> NutrientType {
         self.element
@@ -230,4 +281,46 @@ mod tests {
         assert_eq!(constraint.min(), Some(1.0));
         assert_eq!(constraint.max(), Some(3.0));
     }
+
+    #[test]
+    fn test_nutrient_constraint_serde_round_trip() {
+        let constraint =
+            NutrientConstraint::new(MicroNutrientsType::Fiber, Some(25.0), None).unwrap();
+        let json = serde_json::to_string(&constraint).unwrap();
+        assert_eq!(
+            json,
+            r#"{"element":{"Micro":"Fiber"},"min":25.0,"max":null}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<NutrientConstraint>(&json).unwrap(),
+            constraint
+        );
+
+        let min_above_max = r#"{"element":{"Micro":"Fiber"},"min":30.0,"max":20.0}"#;
+        assert!(serde_json::from_str::<NutrientConstraint>(min_above_max).is_err());
+    }
+
+    #[test]
+    fn test_nutrient_constraint_soft() {
+        let constraint =
+            NutrientConstraint::new(MicroNutrientsType::Fiber, Some(25.0), None).unwrap();
+        assert!(!constraint.is_soft());
+        assert!(constraint.soft(0.0).is_none());
+        assert!(constraint.soft(f32::NAN).is_none());
+
+        let soft = constraint.soft(2.5).unwrap();
+        assert!(soft.is_soft());
+        assert_eq!(soft.penalty(), Some(2.5));
+        let json = serde_json::to_string(&soft).unwrap();
+        assert_eq!(
+            json,
+            r#"{"element":{"Micro":"Fiber"},"min":25.0,"max":null,"penalty":2.5}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<NutrientConstraint>(&json).unwrap(),
+            soft
+        );
+        let negative = r#"{"element":{"Micro":"Fiber"},"min":25.0,"max":null,"penalty":-1.0}"#;
+        assert!(serde_json::from_str::<NutrientConstraint>(negative).is_err());
+    }
 }
@@ -1,27 +1,90 @@
-use crate::data_types::{AllowedUnitsType, Product};
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::{AllowedUnitsType, CookedVariant, Product};
 
 pub type AllowedUnitDividers = std::collections::HashMap<AllowedUnitsType, u16>;
 
 // Constraint on a product (food item)
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ProductConstraintFields")]
 pub struct ProductConstraint {
     food: Box<Product>,
-    low_bound: Option<u16>,
-    up_bound: Option<u16>,
+    low_bound: Option<f32>,
+    up_bound: Option<f32>,
+    unit: AllowedUnitsType,
+    /// Bounds are grams of the cooked variant, the solved amount stays in raw grams.
+    cooked: bool,
+}
+
+// deserialized fields, checked by `ProductConstraint::new`
+#[derive(Deserialize)]
+struct ProductConstraintFields {
+    food: Box<Product>,
+    low_bound: Option<f32>,
+    up_bound: Option<f32>,
     unit: AllowedUnitsType,
+    #[serde(default)]
+    cooked: bool,
+}
+
+impl TryFrom<ProductConstraintFields> for ProductConstraint {
+    type Error = String;
+
+    fn try_from(fields: ProductConstraintFields) -> Result<Self, Self::Error> {
+        let product_id = fields.food.id();
+        let unit = fields.unit;
+        let constraint =
+            Self::new_fractional(fields.food, fields.low_bound, fields.up_bound, unit)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid constraint for '{product_id}': unit {unit} not allowed or min above max"
+                    )
+                })?;
+        if !fields.cooked {
+            return Ok(constraint);
+        }
+        constraint
+            .in_cooked_grams()
+            .ok_or_else(|| format!("Product '{product_id}' has no cooked variant"))
+    }
 }
 
 impl ProductConstraint {
+    /// Bounds in whole grams, see [`Self::new_fractional`].
     #[must_use]
     pub fn new(
         food: Box<Product>,
         low_bound: Option<u16>,
         up_bound: Option<u16>,
         unit: AllowedUnitsType,
+    ) -> Option<Self> {
+        Self::new_fractional(
+            food,
+            low_bound.map(f32::from),
+            up_bound.map(f32::from),
+            unit,
+        )
+    }
+
+    /// Bounds in grams, fractions allowed for oils, spices and the like. `None` when `unit` is
+    /// not allowed for the product, a bound is negative or not finite, or min is above max.
+    #[must_use]
+    pub fn new_fractional(
+        food: Box<Product>,
+        low_bound: Option<f32>,
+        up_bound: Option<f32>,
+        unit: AllowedUnitsType,
     ) -> Option<Self> {
         if !food.allowed_units.contains_key(&unit) {
             return None;
         }
+        if low_bound
+            .into_iter()
+            .chain(up_bound)
+            .any(|bound| !bound.is_finite() || bound < 0.0)
+        {
+            return None;
+        }
         if let (Some(lb), Some(ub)) = (low_bound, up_bound)
             && lb > ub
         {
@@ -32,25 +95,63 @@ impl ProductConstraint {
             low_bound,
             up_bound,
             unit,
+            cooked: false,
         })
     }
 
+    /// Bounds are read as grams of the product's cooked variant, e.g. 200g of cooked rice for a
+    /// constraint on raw rice. `None` when the product has no valid cooked variant.
+    #[must_use]
+    pub fn in_cooked_grams(mut self) -> Option<Self> {
+        if !self
+            .food
+            .cooked
+            .as_ref()
+            .is_some_and(CookedVariant::is_valid)
+        {
+            return None;
+        }
+        self.cooked = true;
+        Some(self)
+    }
+
     #[must_use]
     pub fn food(&self) -> &Product {
         &self.food
     }
     #[must_use]
-    pub fn low_bound(&self) -> Option<u16> {
+    pub fn low_bound(&self) -> Option<f32> {
         self.low_bound
     }
     #[must_use]
-    pub fn up_bound(&self) -> Option<u16> {
+    pub fn up_bound(&self) -> Option<f32> {
         self.up_bound
     }
     #[must_use]
     pub fn unit(&self) -> AllowedUnitsType {
         self.unit
     }
+    #[must_use]
+    pub fn is_cooked(&self) -> bool {
+        self.cooked
+    }
+    /// Lower bound in grams of the product itself, raw ones for cooked bounds.
+    #[must_use]
+    pub fn raw_low_bound(&self) -> Option<f32> {
+        self.low_bound.map(|bound| self.to_raw(bound))
+    }
+    /// Upper bound in grams of the product itself, raw ones for cooked bounds.
+    #[must_use]
+    pub fn raw_up_bound(&self) -> Option<f32> {
+        self.up_bound.map(|bound| self.to_raw(bound))
+    }
+    /// Grams of the product itself for `grams` given the way the bounds are.
+    pub(crate) fn to_raw(&self, grams: f32) -> f32 {
+        match &self.food.cooked {
+            Some(cooked) if self.cooked => cooked.raw_grams(grams),
+            _ => grams,
+        }
+    }
     pub fn update(&mut self, other: ProductConstraint) {
         *self = other;
     }
@@ -182,8 +283,8 @@ mod tests {
         constraint1.update(constraint2);
 
         assert_eq!(constraint1.food().name(), "Product 2");
-        assert_eq!(constraint1.low_bound(), Some(2));
-        assert_eq!(constraint1.up_bound(), Some(10));
+        assert_eq!(constraint1.low_bound(), Some(2.0));
+        assert_eq!(constraint1.up_bound(), Some(10.0));
         assert_eq!(constraint1.unit(), AllowedUnitsType::Gram);
     }
 
@@ -235,7 +336,85 @@ mod tests {
         let constraint = ProductConstraint::new(product, Some(3), Some(3), AllowedUnitsType::Cup);
         assert!(constraint.is_some());
         let constraint = constraint.unwrap();
-        assert_eq!(constraint.low_bound(), Some(3));
-        assert_eq!(constraint.up_bound(), Some(3));
+        assert_eq!(constraint.low_bound(), Some(3.0));
+        assert_eq!(constraint.up_bound(), Some(3.0));
+    }
+
+    #[test]
+    fn test_product_constraint_fractional_bounds() {
+        let product = || {
+            Box::new(Product::new(
+                "Olive Oil".to_string(),
+                None,
+                Box::new(MacroElements::new(100.0, 14.0, 0.0, 0.0, 0.0)),
+                Box::default(),
+                std::collections::HashMap::new(),
+            ))
+        };
+        let constraint = ProductConstraint::new_fractional(
+            product(),
+            Some(0.5),
+            Some(1.5),
+            AllowedUnitsType::Gram,
+        )
+        .unwrap();
+        assert_eq!(constraint.low_bound(), Some(0.5));
+        assert_eq!(constraint.up_bound(), Some(1.5));
+
+        for (low, up) in [
+            (Some(-1.0), None),
+            (None, Some(f32::NAN)),
+            (None, Some(f32::INFINITY)),
+            (Some(2.5), Some(1.5)),
+        ] {
+            assert!(
+                ProductConstraint::new_fractional(product(), low, up, AllowedUnitsType::Gram)
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn test_product_constraint_serde_round_trip() {
+        let mut allowed_units = std::collections::HashMap::new();
+        allowed_units.insert(
+            AllowedUnitsType::Cup,
+            UnitData {
+                amount: 250,
+                divider: 1,
+            },
+        );
+        let product = Box::new(Product::new(
+            "Test Product".to_string(),
+            None,
+            Box::new(MacroElements::new(1.0, 2.0, 3.0, 4.0, 5.0)),
+            Box::default(),
+            allowed_units,
+        ));
+        let constraint =
+            ProductConstraint::new(product, Some(1), None, AllowedUnitsType::Cup).unwrap();
+        let json = serde_json::to_value(&constraint).unwrap();
+        assert_eq!(json["low_bound"], 1.0);
+        assert_eq!(json["unit"], "Cup");
+
+        let restored: ProductConstraint = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(restored.food(), constraint.food());
+        assert_eq!(restored.low_bound(), Some(1.0));
+        assert_eq!(restored.up_bound(), None);
+        assert_eq!(restored.unit(), AllowedUnitsType::Cup);
+
+        let mut fractional = json.clone();
+        fractional["up_bound"] = 2.5.into();
+        let restored: ProductConstraint = serde_json::from_value(fractional).unwrap();
+        assert_eq!(restored.up_bound(), Some(2.5));
+
+        // the product has no cooked variant to read the bounds for
+        let mut cooked = json.clone();
+        cooked["cooked"] = true.into();
+        assert!(serde_json::from_value::<ProductConstraint>(cooked).is_err());
+
+        let mut not_allowed_unit = json;
+        not_allowed_unit["unit"] = "Piece".into();
+        assert!(serde_json::from_value::<ProductConstraint>(not_allowed_unit).is_err());
     }
 }
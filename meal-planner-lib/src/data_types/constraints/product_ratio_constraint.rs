@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::ProductId;
+
+/// What the amount of a product is compared against.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RatioBase {
+    /// Grams of another product of the same meal, identified by its id.
+    Product(ProductId),
+    /// Total grams of the meal, the product itself included.
+    MealWeight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RatioRelation {
+    Equal,
+    AtMost,
+    AtLeast,
+}
+
+/// Links the amount of a product to another product or to the whole meal,
+/// e.g. "rice = 2 × chicken" or "oil ≤ 10% of the meal".
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ProductRatioConstraintFields")]
+pub struct ProductRatioConstraint {
+    product_id: ProductId,
+    base: RatioBase,
+    relation: RatioRelation,
+    ratio: f32,
+}
+
+// deserialized fields, checked by `ProductRatioConstraint::new`
+#[derive(Deserialize)]
+struct ProductRatioConstraintFields {
+    product_id: ProductId,
+    base: RatioBase,
+    relation: RatioRelation,
+    ratio: f32,
+}
+
+impl TryFrom<ProductRatioConstraintFields> for ProductRatioConstraint {
+    type Error = String;
+
+    fn try_from(fields: ProductRatioConstraintFields) -> Result<Self, Self::Error> {
+        let product_id = fields.product_id.clone();
+        Self::new(
+            fields.product_id,
+            fields.base,
+            fields.relation,
+            fields.ratio,
+        )
+        .ok_or_else(|| format!("Invalid ratio for '{product_id}'"))
+    }
+}
+
+impl ProductRatioConstraint {
+    #[must_use]
+    pub fn new(
+        product_id: impl Into<ProductId>,
+        base: RatioBase,
+        relation: RatioRelation,
+        ratio: f32,
+    ) -> Option<Self> {
+        let product_id = product_id.into();
+        if !ratio.is_finite() || ratio < 0.0 {
+            return None;
+        }
+        if base == RatioBase::Product(product_id.clone()) {
+            return None;
+        }
+        // a product can't weigh more than the meal it is part of
+        if base == RatioBase::MealWeight && ratio > 1.0 && relation != RatioRelation::AtMost {
+            return None;
+        }
+        Some(Self {
+            product_id,
+            base,
+            relation,
+            ratio,
+        })
+    }
+
+    #[must_use]
+    pub fn product_id(&self) -> &ProductId {
+        &self.product_id
+    }
+
+    #[must_use]
+    pub fn base(&self) -> &RatioBase {
+        &self.base
+    }
+
+    #[must_use]
+    pub fn relation(&self) -> RatioRelation {
+        self.relation
+    }
+
+    #[must_use]
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratio_constraint_constructor() {
+        let constraint = ProductRatioConstraint::new(
+            "Rice".to_string(),
+            RatioBase::Product("Chicken".into()),
+            RatioRelation::Equal,
+            2.0,
+        )
+        .unwrap();
+        assert_eq!(constraint.product_id(), "Rice");
+        assert_eq!(constraint.base(), &RatioBase::Product("Chicken".into()));
+        assert_eq!(constraint.relation(), RatioRelation::Equal);
+        assert!((constraint.ratio() - 2.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_ratio_constraint_rejects_invalid_ratio() {
+        let new = |base, relation, ratio| {
+            ProductRatioConstraint::new("Oil".to_string(), base, relation, ratio)
+        };
+        assert!(new(RatioBase::MealWeight, RatioRelation::AtMost, -0.1).is_none());
+        assert!(new(RatioBase::MealWeight, RatioRelation::AtMost, f32::NAN).is_none());
+        assert!(new(RatioBase::MealWeight, RatioRelation::AtLeast, 1.5).is_none());
+        assert!(new(RatioBase::MealWeight, RatioRelation::AtMost, 1.5).is_some());
+        assert!(new(RatioBase::Product("Oil".into()), RatioRelation::Equal, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_ratio_constraint_serde_round_trip() {
+        let constraint = ProductRatioConstraint::new(
+            "Oil".to_string(),
+            RatioBase::MealWeight,
+            RatioRelation::AtMost,
+            0.1,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&constraint).unwrap();
+        assert_eq!(
+            json,
+            r#"{"product_id":"Oil","base":"MealWeight","relation":"AtMost","ratio":0.1}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ProductRatioConstraint>(&json).unwrap(),
+            constraint
+        );
+
+        let own_base =
+            r#"{"product_id":"Oil","base":{"Product":"Oil"},"relation":"Equal","ratio":1.0}"#;
+        assert!(serde_json::from_str::<ProductRatioConstraint>(own_base).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::Recipe;
+
+/// Recipe eaten in a meal in whole servings, the solver picks how many.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RecipeConstraintFields")]
+pub struct RecipeConstraint {
+    recipe: Recipe,
+    min_servings: Option<u16>,
+    max_servings: Option<u16>,
+}
+
+// deserialized fields, checked by `RecipeConstraint::new`
+#[derive(Deserialize)]
+struct RecipeConstraintFields {
+    recipe: Recipe,
+    min_servings: Option<u16>,
+    max_servings: Option<u16>,
+}
+
+impl TryFrom<RecipeConstraintFields> for RecipeConstraint {
+    type Error = String;
+
+    fn try_from(fields: RecipeConstraintFields) -> Result<Self, Self::Error> {
+        let name = fields.recipe.name().to_string();
+        Self::new(fields.recipe, fields.min_servings, fields.max_servings)
+            .ok_or_else(|| format!("Invalid servings of recipe '{name}'"))
+    }
+}
+
+impl RecipeConstraint {
+    /// `None` when `min_servings` is above `max_servings`.
+    #[must_use]
+    pub fn new(
+        recipe: Recipe,
+        min_servings: Option<u16>,
+        max_servings: Option<u16>,
+    ) -> Option<Self> {
+        if let (Some(min), Some(max)) = (min_servings, max_servings)
+            && min > max
+        {
+            return None;
+        }
+        Some(Self {
+            recipe,
+            min_servings,
+            max_servings,
+        })
+    }
+
+    #[must_use]
+    pub fn recipe(&self) -> &Recipe {
+        &self.recipe
+    }
+
+    #[must_use]
+    pub fn min_servings(&self) -> Option<u16> {
+        self.min_servings
+    }
+
+    #[must_use]
+    pub fn max_servings(&self) -> Option<u16> {
+        self.max_servings
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data_types::ProductId;
+
+/// Limit on how often products come back over the days of a week plan, against monotonous
+/// plans. A product counts as eaten in a meal when any amount of it is planned there.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RepetitionConstraint {
+    /// The product is eaten in at most `max_times` meals of the week.
+    MaxTimesPerWeek {
+        product_id: ProductId,
+        max_times: u8,
+    },
+    /// None of `product_ids` is eaten in meal `meal` on two consecutive days, e.g. the protein
+    /// sources of dinners.
+    NoConsecutiveDays {
+        meal: String,
+        product_ids: Vec<ProductId>,
+    },
+}
+
+impl RepetitionConstraint {
+    /// Products the constraint is about.
+    #[must_use]
+    pub fn product_ids(&self) -> Vec<&ProductId> {
+        match self {
+            RepetitionConstraint::MaxTimesPerWeek { product_id, .. } => vec![product_id],
+            RepetitionConstraint::NoConsecutiveDays { product_ids, .. } => {
+                product_ids.iter().collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use super::DayMealPlanConstraint;
+use super::LeftoverConstraint;
+use super::RepetitionConstraint;
+
+#[derive(Clone)]
+pub struct WeekMealPlanConstraint {
+    /// Days in the order they are eaten, with their names.
+    pub days: Vec<(String, DayMealPlanConstraint)>,
+    pub leftovers: Vec<LeftoverConstraint>,
+    pub repetitions: Vec<RepetitionConstraint>,
+}
+
+impl WeekMealPlanConstraint {
+    /// Appends a copy of day `day_name` as `new_name` and returns it for editing. The copy goes
+    /// last so leftovers, which refer to days by position, keep their days.
+    pub fn duplicate_day(
+        &mut self,
+        day_name: &str,
+        new_name: &str,
+    ) -> Result<&mut DayMealPlanConstraint, String> {
+        if new_name.trim().is_empty() {
+            return Err("Day name can't be empty".to_string());
+        }
+        if self.days.iter().any(|(name, _)| name == new_name) {
+            return Err(format!("Day '{new_name}' already exists"));
+        }
+        let copy = self
+            .days
+            .iter()
+            .find(|(name, _)| name == day_name)
+            .map(|(_, day)| day.clone())
+            .ok_or_else(|| format!("Day '{day_name}' doesn't exist"))?;
+        self.days.push((new_name.to_string(), copy));
+        let last = self.days.len() - 1;
+        Ok(&mut self.days[last].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::DietaryRestrictions;
+    use std::collections::HashMap;
+
+    fn day(max_cost: f32) -> DayMealPlanConstraint {
+        DayMealPlanConstraint {
+            meals: HashMap::new(),
+            nutrients: Vec::new(),
+            max_cost: Some(max_cost),
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn duplicate_day_appends_editable_copy() {
+        let mut week = WeekMealPlanConstraint {
+            days: vec![
+                ("Monday".to_string(), day(20.0)),
+                ("Tuesday".to_string(), day(30.0)),
+            ],
+            leftovers: Vec::new(),
+            repetitions: Vec::new(),
+        };
+        week.duplicate_day("Monday", "Wednesday").unwrap().max_cost = Some(25.0);
+        let names: Vec<&str> = week.days.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Monday", "Tuesday", "Wednesday"]);
+        assert_eq!(week.days[0].1.max_cost, Some(20.0));
+        assert_eq!(week.days[2].1.max_cost, Some(25.0));
+
+        assert!(week.duplicate_day("Sunday", "Thursday").is_err());
+        assert!(week.duplicate_day("Monday", "Tuesday").is_err());
+        assert!(week.duplicate_day("Monday", "").is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use super::ProductId;
+
+/// Cooked form of a raw product, e.g. cooked rice for raw rice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CookedVariant {
+    pub product_id: ProductId,
+    /// Grams of the cooked product made from one gram of the raw one, 2.5 for rice.
+    pub yield_factor: f32,
+}
+
+impl CookedVariant {
+    #[must_use]
+    pub fn new(product_id: ProductId, yield_factor: f32) -> Self {
+        Self {
+            product_id,
+            yield_factor,
+        }
+    }
+
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.yield_factor.is_finite() && self.yield_factor > 0.0
+    }
+
+    #[must_use]
+    pub fn cooked_grams(&self, raw_grams: f32) -> f32 {
+        raw_grams * self.yield_factor
+    }
+
+    /// Raw grams to buy for `cooked_grams` of the cooked product.
+    #[must_use]
+    pub fn raw_grams(&self, cooked_grams: f32) -> f32 {
+        cooked_grams / self.yield_factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn converts_between_raw_and_cooked() {
+        let rice = CookedVariant::new("Rice (cooked)".into(), 2.5);
+        assert_relative_eq!(rice.cooked_grams(80.0), 200.0);
+        assert_relative_eq!(rice.raw_grams(200.0), 80.0);
+
+        assert!(rice.is_valid());
+        assert!(!CookedVariant::new("Rice (cooked)".into(), 0.0).is_valid());
+        assert!(!CookedVariant::new("Rice (cooked)".into(), f32::INFINITY).is_valid());
+    }
+}
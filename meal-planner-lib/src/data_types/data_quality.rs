@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// `OpenFoodFacts` state tag of products whose nutrition table was filled in completely.
+pub const NUTRITION_COMPLETED_TAG: &str = "en:nutrition-facts-completed";
+
+/// How far the nutrient values of an imported product can be trusted, see
+/// [`super::Product::data_quality`]. Products entered by hand have none.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataQuality {
+    /// Nutrients estimated by the source (e.g. from ingredients) instead of read from a label.
+    #[serde(default)]
+    pub estimated_fields: BTreeSet<String>,
+    /// Completeness tags of the source, e.g. [`NUTRITION_COMPLETED_TAG`].
+    #[serde(default)]
+    pub completeness_tags: BTreeSet<String>,
+    /// Problems the source found in the data, e.g. energy not matching the macro elements.
+    #[serde(default)]
+    pub warnings: BTreeSet<String>,
+    /// Last modification of the source entry, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub last_modified: Option<u64>,
+}
+
+impl DataQuality {
+    /// Reads the indicators of an `OpenFoodFacts` product object: `nutriments_estimated`,
+    /// `states_tags`, `data_quality_warnings_tags` and `last_modified_t`.
+    #[must_use]
+    pub fn from_off_product(product: &serde_json::Value) -> Self {
+        let tags = |key: &str| -> BTreeSet<String> {
+            product[key]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|tag| tag.as_str().map(str::to_string))
+                .collect()
+        };
+        Self {
+            estimated_fields: product["nutriments_estimated"]
+                .as_object()
+                .map(|fields| fields.keys().cloned().collect())
+                .unwrap_or_default(),
+            completeness_tags: tags("states_tags"),
+            warnings: tags("data_quality_warnings_tags"),
+            last_modified: product["last_modified_t"].as_u64(),
+        }
+    }
+
+    /// Nutrition table marked complete, with nothing estimated and no warnings.
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        self.estimated_fields.is_empty()
+            && self.warnings.is_empty()
+            && self.completeness_tags.contains(NUTRITION_COMPLETED_TAG)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_quality_from_off_product() {
+        let product = serde_json::json!({
+            "product_name": "Oat milk",
+            "states_tags": ["en:nutrition-facts-completed", "en:photos-uploaded"],
+            "data_quality_warnings_tags": [],
+            "last_modified_t": 1_700_000_000,
+        });
+        let quality = DataQuality::from_off_product(&product);
+        assert_eq!(quality.completeness_tags.len(), 2);
+        assert_eq!(quality.last_modified, Some(1_700_000_000));
+        assert!(quality.is_verified());
+
+        let estimated = serde_json::json!({
+            "states_tags": ["en:nutrition-facts-completed"],
+            "nutriments_estimated": { "fiber_100g": 1.2 },
+        });
+        let quality = DataQuality::from_off_product(&estimated);
+        assert_eq!(
+            quality.estimated_fields,
+            BTreeSet::from(["fiber_100g".to_string()])
+        );
+        assert!(!quality.is_verified());
+
+        let with_warning = serde_json::json!({
+            "states_tags": ["en:nutrition-facts-completed"],
+            "data_quality_warnings_tags": ["en:energy-value-in-kcal-does-not-match-value-computed-from-other-nutrients"],
+        });
+        assert!(!DataQuality::from_off_product(&with_warning).is_verified());
+
+        let incomplete = DataQuality::from_off_product(&serde_json::json!({}));
+        assert_eq!(incomplete, DataQuality::default());
+        assert!(!incomplete.is_verified());
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use super::Product;
+
+// values of the `kind` column in the `product_flags` table
+pub(crate) const ALLERGEN_FLAG_KIND: &str = "allergen";
+pub(crate) const DIET_FLAG_KIND: &str = "diet";
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum Allergen {
+    Gluten,
+    Lactose,
+    /// Tree nuts, peanuts are listed separately.
+    Nuts,
+    Peanuts,
+    Soy,
+    Egg,
+    Fish,
+    Shellfish,
+    Sesame,
+}
+
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Allergen::Gluten => "gluten",
+            Allergen::Lactose => "lactose",
+            Allergen::Nuts => "nuts",
+            Allergen::Peanuts => "peanuts",
+            Allergen::Soy => "soy",
+            Allergen::Egg => "egg",
+            Allergen::Fish => "fish",
+            Allergen::Shellfish => "shellfish",
+            Allergen::Sesame => "sesame",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Allergen {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gluten" => Ok(Allergen::Gluten),
+            "lactose" => Ok(Allergen::Lactose),
+            "nuts" => Ok(Allergen::Nuts),
+            "peanuts" => Ok(Allergen::Peanuts),
+            "soy" => Ok(Allergen::Soy),
+            "egg" => Ok(Allergen::Egg),
+            "fish" => Ok(Allergen::Fish),
+            "shellfish" => Ok(Allergen::Shellfish),
+            "sesame" => Ok(Allergen::Sesame),
+            _ => Err(format!("Unknown allergen '{s}'")),
+        }
+    }
+}
+
+/// Diets a product is suitable for.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum DietFlag {
+    Vegan,
+    Vegetarian,
+    Halal,
+    Kosher,
+}
+
+impl fmt::Display for DietFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DietFlag::Vegan => "vegan",
+            DietFlag::Vegetarian => "vegetarian",
+            DietFlag::Halal => "halal",
+            DietFlag::Kosher => "kosher",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for DietFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vegan" => Ok(DietFlag::Vegan),
+            "vegetarian" => Ok(DietFlag::Vegetarian),
+            "halal" => Ok(DietFlag::Halal),
+            "kosher" => Ok(DietFlag::Kosher),
+            _ => Err(format!("Unknown diet '{s}'")),
+        }
+    }
+}
+
+/// Products left out when solving: ones containing any excluded allergen and ones missing any
+/// of the required diet flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DietaryRestrictions {
+    #[serde(default)]
+    pub excluded_allergens: BTreeSet<Allergen>,
+    #[serde(default)]
+    pub required_diets: BTreeSet<DietFlag>,
+}
+
+impl DietaryRestrictions {
+    #[must_use]
+    pub fn allows(&self, product: &Product) -> bool {
+        product.allergens.is_disjoint(&self.excluded_allergens)
+            && self.required_diets.is_subset(&product.diet_flags)
+    }
+}
+
+impl Product {
+    /// Flattens allergens and diet flags into `(kind, flag)` rows of the `product_flags` table.
+    pub(crate) fn flag_rows(&self) -> Vec<(&'static str, String)> {
+        self.allergens
+            .iter()
+            .map(|a| (ALLERGEN_FLAG_KIND, a.to_string()))
+            .chain(
+                self.diet_flags
+                    .iter()
+                    .map(|d| (DIET_FLAG_KIND, d.to_string())),
+            )
+            .collect()
+    }
+
+    /// Sets flags from `kind:flag` entries. Entries this version doesn't know are skipped.
+    #[must_use]
+    pub(crate) fn with_flag_entries<'a>(
+        mut self,
+        entries: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        for entry in entries {
+            match entry.split_once(':') {
+                Some((ALLERGEN_FLAG_KIND, flag)) => {
+                    if let Ok(allergen) = flag.parse() {
+                        self.allergens.insert(allergen);
+                    }
+                }
+                Some((DIET_FLAG_KIND, flag)) => {
+                    if let Ok(diet) = flag.parse() {
+                        self.diet_flags.insert(diet);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::MacroElements;
+    use strum::IntoEnumIterator;
+
+    fn product() -> Product {
+        Product::new(
+            "Bread".to_string(),
+            None,
+            Box::new(MacroElements::new(1.0, 0.0, 50.0, 2.0, 8.0)),
+            Box::default(),
+            std::collections::HashMap::new(),
+        )
+        .with_allergens(BTreeSet::from([Allergen::Gluten, Allergen::Sesame]))
+        .with_diet_flags(BTreeSet::from([DietFlag::Vegan, DietFlag::Vegetarian]))
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for allergen in Allergen::iter() {
+            assert_eq!(allergen.to_string().parse::<Allergen>(), Ok(allergen));
+        }
+        for diet in DietFlag::iter() {
+            assert_eq!(diet.to_string().parse::<DietFlag>(), Ok(diet));
+        }
+        assert!("milk".parse::<Allergen>().is_err());
+    }
+
+    #[test]
+    fn flag_rows_round_trip() {
+        let bread = product();
+        let entries: Vec<String> = bread
+            .flag_rows()
+            .into_iter()
+            .map(|(kind, flag)| format!("{kind}:{flag}"))
+            .chain(["allergen:unknown".to_string(), "garbage".to_string()])
+            .collect();
+        let restored = Product::new(
+            "Bread".to_string(),
+            None,
+            bread.macro_elements.clone(),
+            Box::default(),
+            std::collections::HashMap::new(),
+        )
+        .with_flag_entries(entries.iter().map(String::as_str));
+        assert_eq!(restored, bread);
+    }
+
+    #[test]
+    fn restrictions_filter_products() {
+        let bread = product();
+        assert!(DietaryRestrictions::default().allows(&bread));
+        let no_gluten = DietaryRestrictions {
+            excluded_allergens: BTreeSet::from([Allergen::Gluten]),
+            ..DietaryRestrictions::default()
+        };
+        assert!(!no_gluten.allows(&bread));
+        let vegan_halal = DietaryRestrictions {
+            required_diets: BTreeSet::from([DietFlag::Vegan, DietFlag::Halal]),
+            ..DietaryRestrictions::default()
+        };
+        assert!(!vegan_halal.allows(&bread));
+        let vegan = DietaryRestrictions {
+            excluded_allergens: BTreeSet::from([Allergen::Nuts]),
+            required_diets: BTreeSet::from([DietFlag::Vegan]),
+        };
+        assert!(vegan.allows(&bread));
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use super::ProductId;
+
+/// Product of a planned meal the user marked as eaten, stored per profile and day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EatenItem {
+    pub meal: String,
+    pub product_id: ProductId,
+    /// Amount actually eaten, may differ from the planned one.
+    pub grams: f64,
+}
+
+impl EatenItem {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.meal.trim().is_empty() || self.product_id.trim().is_empty() {
+            return Err("Eaten item needs a meal and a product".to_string());
+        }
+        if !self.grams.is_finite() || self.grams < 0.0 {
+            return Err(format!(
+                "Invalid eaten amount of '{}': {}",
+                self.product_id, self.grams
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_checks_names_and_amount() {
+        let item = EatenItem {
+            meal: "Lunch".to_string(),
+            product_id: "Rice".into(),
+            grams: 150.0,
+        };
+        assert_eq!(item.validate(), Ok(()));
+        let negative = EatenItem {
+            grams: -1.0,
+            ..item.clone()
+        };
+        assert!(negative.validate().is_err());
+        let no_meal = EatenItem {
+            meal: " ".to_string(),
+            ..item
+        };
+        assert!(no_meal.validate().is_err());
+    }
+}
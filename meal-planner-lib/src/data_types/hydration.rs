@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use super::ProductId;
+
+// single drinks above this are most likely typos
+const MAX_BEVERAGE_ML: u32 = 5000;
+
+/// Drink logged by the user, stored per profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeverageEntry {
+    /// Unix timestamp (seconds) of the drink.
+    pub drunk_at: u64,
+    pub volume_ml: u32,
+    /// Product drunk, e.g. a beer from the database, `None` for plain water.
+    pub product_id: Option<ProductId>,
+}
+
+impl BeverageEntry {
+    #[must_use]
+    pub fn water(drunk_at: u64, volume_ml: u32) -> Self {
+        Self {
+            drunk_at,
+            volume_ml,
+            product_id: None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_water(&self) -> bool {
+        self.product_id.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_BEVERAGE_ML).contains(&self.volume_ml) {
+            return Err(format!(
+                "Drink volume must be between 1 and {MAX_BEVERAGE_ML} ml: {}",
+                self.volume_ml
+            ));
+        }
+        if self
+            .product_id
+            .as_ref()
+            .is_some_and(|id| id.trim().is_empty())
+        {
+            return Err("Drink product id is empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_checks_volume_and_product() {
+        assert_eq!(BeverageEntry::water(0, 250).validate(), Ok(()));
+        assert!(BeverageEntry::water(0, 0).validate().is_err());
+        assert!(BeverageEntry::water(0, 10_000).validate().is_err());
+        let beer = BeverageEntry {
+            product_id: Some("Beer".into()),
+            ..BeverageEntry::water(0, 500)
+        };
+        assert!(!beer.is_water());
+        assert_eq!(beer.validate(), Ok(()));
+        let unnamed = BeverageEntry {
+            product_id: Some(" ".into()),
+            ..beer
+        };
+        assert!(unnamed.validate().is_err());
+    }
+}
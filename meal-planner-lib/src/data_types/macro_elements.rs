@@ -31,10 +31,44 @@ impl fmt::Display for MacroElementsType {
     }
 }
 
+const KJ_PER_KCAL: f64 = 4.184;
+
+/// Unit energy is shown in, values are stored in kcal either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnergyUnit {
+    #[default]
+    Kcal,
+    Kj,
+}
+
+impl EnergyUnit {
+    /// `kcal` in this unit.
+    #[must_use]
+    pub fn from_kcal(self, kcal: f64) -> f64 {
+        match self {
+            EnergyUnit::Kcal => kcal,
+            EnergyUnit::Kj => kcal * KJ_PER_KCAL,
+        }
+    }
+}
+
+impl fmt::Display for EnergyUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self {
+            EnergyUnit::Kcal => "kcal",
+            EnergyUnit::Kj => "kJ",
+        };
+        write!(f, "{unit}")
+    }
+}
+
 /// Macro elements per 100g
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MacroElements {
     elements: std::collections::HashMap<MacroElementsType, f32>,
+    /// Calories from the label, used instead of the 4/4/9 formula when set.
+    #[serde(default)]
+    calories_override: Option<f32>,
 }
 
 impl MacroElements {
@@ -53,7 +87,10 @@ impl MacroElements {
             };
             elements.insert(elem, value);
         }
-        let mut me = Self { elements };
+        let mut me = Self {
+            elements,
+            calories_override: None,
+        };
         me.recompute_calories();
         me
     }
@@ -80,6 +117,31 @@ impl MacroElements {
         Ok(())
     }
 
+    /// Calories from the 4/4/9 formula, even when they are overridden.
+    #[must_use]
+    pub fn computed_calories(&self) -> f32 {
+        *self
+            .elements
+            .get(&MacroElementsType::Calories)
+            .unwrap_or(&0.0)
+    }
+
+    #[must_use]
+    pub fn calories_override(&self) -> Option<f32> {
+        self.calories_override
+    }
+
+    /// Sets calories that take precedence over the computed ones, `None` goes back to the formula.
+    pub fn set_calories_override(&mut self, calories: Option<f32>) {
+        self.calories_override = calories;
+    }
+
+    #[must_use]
+    pub fn with_calories_override(mut self, calories: Option<f32>) -> Self {
+        self.set_calories_override(calories);
+        self
+    }
+
     #[must_use]
     pub fn add_ref(lhs: &MacroElements, rhs: &MacroElements) -> MacroElements {
         let mut elements = std::collections::HashMap::new();
@@ -87,7 +149,14 @@ impl MacroElements {
             let value = lhs[elem] + rhs[elem];
             elements.insert(elem, value);
         }
-        let mut result = MacroElements { elements };
+        // a sum with any overridden side has to keep the label calories of that side
+        let calories_override = (lhs.calories_override.is_some()
+            || rhs.calories_override.is_some())
+        .then(|| lhs[MacroElementsType::Calories] + rhs[MacroElementsType::Calories]);
+        let mut result = MacroElements {
+            elements,
+            calories_override,
+        };
         result.recompute_calories();
         result
     }
@@ -102,8 +171,12 @@ impl IntoIterator for MacroElements {
     type IntoIter = MacroElementsIter;
 
     fn into_iter(self) -> Self::IntoIter {
+        let mut elements = self.elements;
+        if let Some(calories) = self.calories_override {
+            elements.insert(MacroElementsType::Calories, calories);
+        }
         MacroElementsIter {
-            inner: self.elements.clone().into_iter(),
+            inner: elements.into_iter(),
         }
     }
 }
@@ -120,6 +193,11 @@ impl Index<MacroElementsType> for MacroElements {
     type Output = f32;
 
     fn index(&self, key: MacroElementsType) -> &Self::Output {
+        if key == MacroElementsType::Calories
+            && let Some(calories) = &self.calories_override
+        {
+            return calories;
+        }
         self.elements.get(&key).expect("Macro element not found")
     }
 }
@@ -236,6 +314,27 @@ mod tests {
         assert_relative_eq!(sum[MacroElementsType::Calories], 67.0);
     }
 
+    #[test]
+    fn test_calories_override_takes_precedence() {
+        let mut me = MacroElements::new(10.0, 3.0, 20.0, 5.0, 15.0);
+        me.set_calories_override(Some(200.0));
+        assert_relative_eq!(me[MacroElementsType::Calories], 200.0);
+        assert_relative_eq!(me.computed_calories(), 230.0);
+
+        assert!(me.set(MacroElementsType::Fat, 0.0).is_ok());
+        assert_relative_eq!(me[MacroElementsType::Calories], 200.0);
+        assert_relative_eq!(me.computed_calories(), 140.0);
+
+        let iterated: std::collections::HashMap<_, _> = me.clone().into_iter().collect();
+        assert_relative_eq!(iterated[&MacroElementsType::Calories], 200.0);
+
+        let sum = &me + &MacroElements::new(1.0, 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(sum.calories_override(), Some(209.0));
+
+        me.set_calories_override(None);
+        assert_relative_eq!(me[MacroElementsType::Calories], 140.0);
+    }
+
     #[test]
     fn test_macro_elements_index_panic() {
         let me = MacroElements::new(1.0, 0.5, 2.0, 0.5, 3.0);
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Largest photo kept in the database, so a single note can't bloat it.
+pub const MAX_PHOTO_BYTES: usize = 5 * 1024 * 1024;
+
+/// Picture of how a meal turned out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MealPhoto {
+    /// Image kept in the database, `mime_type` like `image/jpeg`. Serialized as hex.
+    Blob {
+        mime_type: String,
+        #[serde(serialize_with = "to_hex", deserialize_with = "from_hex")]
+        data: Vec<u8>,
+    },
+    /// Image file stored by the app itself, e.g. in the gallery of a phone.
+    Path(String),
+}
+
+/// How a planned meal actually turned out, stored per profile, day and meal.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MealNote {
+    pub meal: String,
+    pub text: String,
+    #[serde(default)]
+    pub photos: Vec<MealPhoto>,
+}
+
+impl MealNote {
+    /// A note without text and photos has nothing to keep.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.photos.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.meal.trim().is_empty() {
+            return Err("Meal note needs a meal".to_string());
+        }
+        for photo in &self.photos {
+            match photo {
+                MealPhoto::Blob { mime_type, data } => {
+                    if !mime_type.starts_with("image/") {
+                        return Err(format!("Photo of '{}' is not an image", self.meal));
+                    }
+                    if data.is_empty() || data.len() > MAX_PHOTO_BYTES {
+                        return Err(format!(
+                            "Photo of '{}' has {} bytes, at most {MAX_PHOTO_BYTES} are allowed",
+                            self.meal,
+                            data.len()
+                        ));
+                    }
+                }
+                MealPhoto::Path(path) if path.trim().is_empty() => {
+                    return Err(format!("Photo of '{}' has an empty path", self.meal));
+                }
+                MealPhoto::Path(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_hex<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let hex = data.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    });
+    serializer.serialize_str(&hex)
+}
+
+fn from_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    if hex.len() % 2 != 0 {
+        return Err(serde::de::Error::custom("hex data of odd length"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid hex data at {i}")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn photos_round_trip_and_are_validated() {
+        let note = MealNote {
+            meal: "Dinner".to_string(),
+            text: "Too salty".to_string(),
+            photos: vec![
+                MealPhoto::Blob {
+                    mime_type: "image/png".to_string(),
+                    data: vec![0x89, 0x50, 0x0a],
+                },
+                MealPhoto::Path("/photos/dinner.jpg".to_string()),
+            ],
+        };
+        assert_eq!(note.validate(), Ok(()));
+        let json = serde_json::to_string(&note).unwrap();
+        assert!(json.contains("\"89500a\""));
+        assert_eq!(serde_json::from_str::<MealNote>(&json).unwrap(), note);
+
+        let not_image = MealNote {
+            photos: vec![MealPhoto::Blob {
+                mime_type: "text/plain".to_string(),
+                data: vec![1],
+            }],
+            ..note.clone()
+        };
+        assert!(not_image.validate().is_err());
+        assert!(
+            MealNote {
+                meal: " ".to_string(),
+                ..note
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(MealNote::default().is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// Time of day a meal is eaten at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MealTime {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl MealTime {
+    pub fn new(hour: u8, minute: u8) -> Result<Self, String> {
+        if hour > 23 || minute > 59 {
+            return Err(format!("Invalid meal time {hour}:{minute:02}"));
+        }
+        Ok(Self { hour, minute })
+    }
+
+    pub(crate) fn seconds_after_midnight(self) -> u64 {
+        u64::from(self.hour) * 3600 + u64::from(self.minute) * 60
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MealKind {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+    Custom(String),
+}
+
+impl MealKind {
+    /// Usual time of the meal, used for ordering when the slot has no explicit time.
+    #[must_use]
+    pub fn typical_time(&self) -> Option<MealTime> {
+        let (hour, minute) = match self {
+            MealKind::Breakfast => (8, 0),
+            MealKind::Lunch => (13, 0),
+            MealKind::Dinner => (19, 0),
+            MealKind::Snack | MealKind::Custom(_) => return None,
+        };
+        Some(MealTime { hour, minute })
+    }
+}
+
+/// Kind and optional time of a meal. Slots are ordered chronologically, the ones without any
+/// known time go last.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MealSlot {
+    pub kind: MealKind,
+    pub time: Option<MealTime>,
+}
+
+impl MealSlot {
+    #[must_use]
+    pub fn new(kind: MealKind, time: Option<MealTime>) -> Self {
+        Self { kind, time }
+    }
+
+    /// Explicit time of the slot, or the typical time of its kind.
+    #[must_use]
+    pub fn effective_time(&self) -> Option<MealTime> {
+        self.time.or_else(|| self.kind.typical_time())
+    }
+}
+
+impl Ord for MealSlot {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |slot: &Self| (slot.effective_time().is_none(), slot.effective_time());
+        key(self)
+            .cmp(&key(other))
+            .then_with(|| self.kind.cmp(&other.kind))
+            .then_with(|| self.time.cmp(&other.time))
+    }
+}
+
+impl PartialOrd for MealSlot {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_ordered_chronologically() {
+        let time = |h, m| Some(MealTime::new(h, m).unwrap());
+        let mut slots = [
+            MealSlot::new(MealKind::Snack, None),
+            MealSlot::new(MealKind::Dinner, None),
+            MealSlot::new(
+                MealKind::Custom("Second breakfast".to_string()),
+                time(10, 30),
+            ),
+            MealSlot::new(MealKind::Snack, time(16, 0)),
+            MealSlot::new(MealKind::Breakfast, None),
+            MealSlot::new(MealKind::Lunch, time(12, 15)),
+        ];
+        slots.sort();
+        let kinds: Vec<&MealKind> = slots.iter().map(|slot| &slot.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &MealKind::Breakfast,
+                &MealKind::Custom("Second breakfast".to_string()),
+                &MealKind::Lunch,
+                &MealKind::Snack,
+                &MealKind::Dinner,
+                &MealKind::Snack,
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_meal_time() {
+        assert!(MealTime::new(24, 0).is_err());
+        assert!(MealTime::new(7, 60).is_err());
+    }
+}
@@ -13,6 +13,14 @@ pub enum MicroNutrientsType {
     Zinc,
     Sodium,
     Alcohol,
+    Iron,
+    Calcium,
+    Potassium,
+    Magnesium,
+    VitaminA,
+    VitaminC,
+    VitaminD,
+    VitaminB12,
 }
 
 impl fmt::Display for MicroNutrientsType {
@@ -22,11 +30,78 @@ impl fmt::Display for MicroNutrientsType {
             MicroNutrientsType::Zinc => "Zinc",
             MicroNutrientsType::Sodium => "Sodium",
             MicroNutrientsType::Alcohol => "Alcohol",
+            MicroNutrientsType::Iron => "Iron",
+            MicroNutrientsType::Calcium => "Calcium",
+            MicroNutrientsType::Potassium => "Potassium",
+            MicroNutrientsType::Magnesium => "Magnesium",
+            MicroNutrientsType::VitaminA => "Vitamin A",
+            MicroNutrientsType::VitaminC => "Vitamin C",
+            MicroNutrientsType::VitaminD => "Vitamin D",
+            MicroNutrientsType::VitaminB12 => "Vitamin B12",
         };
         write!(f, "{name}")
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NutrientUnit {
+    Gram,
+    Milligram,
+    Microgram,
+}
+
+impl fmt::Display for NutrientUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self {
+            NutrientUnit::Gram => "g",
+            NutrientUnit::Milligram => "mg",
+            NutrientUnit::Microgram => "µg",
+        };
+        write!(f, "{unit}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NutrientInfo {
+    /// Unit of the per 100g values stored for this nutrient.
+    pub unit: NutrientUnit,
+    /// Daily reference intake for adults, in `unit`. `None` when there is no intake to reach.
+    pub adult_rda: Option<f32>,
+    /// Daily intake adults should stay below, in `unit`. `None` when there is no known limit.
+    #[serde(default)]
+    pub adult_max: Option<f32>,
+}
+
+impl MicroNutrientsType {
+    /// Unit, adult reference intake and adult upper intake of the nutrient.
+    ///
+    /// Intakes are the EU nutrient reference values, fiber uses the EFSA adequate intake. Upper
+    /// intakes are the EFSA tolerable upper intake levels, sodium uses the WHO recommended
+    /// maximum.
+    #[must_use]
+    pub fn info(self) -> NutrientInfo {
+        let (unit, adult_rda, adult_max) = match self {
+            MicroNutrientsType::Fiber => (NutrientUnit::Gram, Some(25.0), None),
+            MicroNutrientsType::Zinc => (NutrientUnit::Milligram, Some(10.0), Some(25.0)),
+            MicroNutrientsType::Sodium => (NutrientUnit::Milligram, None, Some(2000.0)),
+            MicroNutrientsType::Alcohol => (NutrientUnit::Gram, None, None),
+            MicroNutrientsType::Iron => (NutrientUnit::Milligram, Some(14.0), None),
+            MicroNutrientsType::Calcium => (NutrientUnit::Milligram, Some(800.0), Some(2500.0)),
+            MicroNutrientsType::Potassium => (NutrientUnit::Milligram, Some(2000.0), None),
+            MicroNutrientsType::Magnesium => (NutrientUnit::Milligram, Some(375.0), None),
+            MicroNutrientsType::VitaminA => (NutrientUnit::Microgram, Some(800.0), Some(3000.0)),
+            MicroNutrientsType::VitaminC => (NutrientUnit::Milligram, Some(80.0), None),
+            MicroNutrientsType::VitaminD => (NutrientUnit::Microgram, Some(5.0), Some(100.0)),
+            MicroNutrientsType::VitaminB12 => (NutrientUnit::Microgram, Some(2.5), None),
+        };
+        NutrientInfo {
+            unit,
+            adult_rda,
+            adult_max,
+        }
+    }
+}
+
 // Micro nutrients per 100g
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MicroNutrients {
@@ -166,6 +241,17 @@ mod tests {
         assert_eq!(MicroNutrientsType::Zinc.to_string(), "Zinc");
         assert_eq!(MicroNutrientsType::Sodium.to_string(), "Sodium");
         assert_eq!(MicroNutrientsType::Alcohol.to_string(), "Alcohol");
+        assert_eq!(MicroNutrientsType::VitaminB12.to_string(), "Vitamin B12");
+    }
+
+    #[test]
+    fn test_micro_nutrients_info() {
+        let iron = MicroNutrientsType::Iron.info();
+        assert_eq!(iron.unit, NutrientUnit::Milligram);
+        assert_eq!(iron.adult_rda, Some(14.0));
+        assert_eq!(MicroNutrientsType::VitaminD.info().unit.to_string(), "µg");
+        assert_eq!(MicroNutrientsType::Sodium.info().adult_rda, None);
+        assert_eq!(MicroNutrientsType::Sodium.info().adult_max, Some(2000.0));
     }
 
     #[test]
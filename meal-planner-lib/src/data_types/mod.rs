@@ -1,8 +1,40 @@
+mod activity;
+mod app_settings;
+mod completeness;
 pub mod constraints;
+mod cooking;
+mod data_quality;
+mod dietary;
+mod eaten_item;
+mod hydration;
 mod macro_elements;
+mod meal_note;
+mod meal_slot;
 mod micro_nutrients;
 mod product;
+mod product_builder;
+mod product_id;
+mod recipe;
+mod serving;
+mod usage_stats;
+mod user_profile;
 
+pub use activity::*;
+pub use app_settings::*;
+pub use completeness::*;
+pub use cooking::*;
+pub use data_quality::*;
+pub use dietary::*;
+pub use eaten_item::*;
+pub use hydration::*;
 pub use macro_elements::*;
+pub use meal_note::*;
+pub use meal_slot::*;
 pub use micro_nutrients::*;
 pub use product::*;
+pub use product_builder::*;
+pub use product_id::*;
+pub use recipe::*;
+pub use serving::*;
+pub use usage_stats::*;
+pub use user_profile::*;
@@ -1,20 +1,47 @@
 use core::fmt;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::hash::Hash;
+use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 
 use super::{
-    macro_elements::MacroElements, macro_elements::MacroElementsType,
-    micro_nutrients::MicroNutrients, micro_nutrients::MicroNutrientsType,
+    completeness::CompletenessReport, cooking::CookedVariant, data_quality::DataQuality,
+    dietary::Allergen, dietary::DietFlag, macro_elements::MacroElements,
+    macro_elements::MacroElementsType, micro_nutrients::MicroNutrients,
+    micro_nutrients::MicroNutrientsType, product_id::ProductId, serving::NutrientAmounts,
+    serving::ServingInfo,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NutrientType {
     Macro(MacroElementsType),
     Micro(MicroNutrientsType),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, EnumCount, Serialize, Deserialize)]
+impl fmt::Display for NutrientType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NutrientType::Macro(m) => write!(f, "{m}"),
+            NutrientType::Micro(m) => write!(f, "{m}"),
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    EnumCount,
+    Serialize,
+    Deserialize,
+)]
 pub enum AllowedUnitsType {
     Gram,
     Piece,
@@ -40,6 +67,19 @@ impl fmt::Display for AllowedUnitsType {
     }
 }
 
+impl AllowedUnitsType {
+    /// Kitchen volume of the unit in millilitres, `None` for units without a standard size.
+    #[must_use]
+    pub fn standard_ml(self) -> Option<f32> {
+        match self {
+            AllowedUnitsType::Cup => Some(240.0),
+            AllowedUnitsType::Tablespoon => Some(15.0),
+            AllowedUnitsType::Teaspoon => Some(5.0),
+            _ => None,
+        }
+    }
+}
+
 const DEFAULT_ALLOWED_UNITS: (AllowedUnitsType, UnitData) = (
     AllowedUnitsType::Gram,
     UnitData {
@@ -56,6 +96,102 @@ pub struct UnitData {
 
 pub type AllowedUnits = std::collections::HashMap<AllowedUnitsType, UnitData>;
 
+/// Grocery price of a product, normalized to 100g like nutrient values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub per_100g: f32,
+    /// Currency code as entered by the user (e.g. `PLN`, `EUR`).
+    pub currency: String,
+}
+
+// nutrition labels round their values, so small excesses are not treated as errors
+const LABEL_ROUNDING_G: f32 = 0.5;
+
+pub(crate) const ALCOHOL_KCAL_PER_G: f32 = 7.0;
+
+/// Reason a product can't be stored or planned with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProductValidationError {
+    EmptyName,
+    /// Negative, infinite or NaN amount.
+    InvalidNutrient {
+        nutrient: NutrientType,
+        value: f32,
+    },
+    SugarAboveCarbs {
+        sugar: f32,
+        carbs: f32,
+    },
+    SaturatedFatAboveFat {
+        saturated_fat: f32,
+        fat: f32,
+    },
+    /// Fat, carbs and protein weigh more than the 100g they are given for.
+    MacrosAbove100g {
+        total: f32,
+    },
+    InvalidPrice(f32),
+    /// Unit with zero amount or divider.
+    InvalidUnit(AllowedUnitsType),
+    /// Non-positive, infinite or NaN grams per millilitre.
+    InvalidDensity(f32),
+    /// Non-positive, infinite or NaN serving size.
+    InvalidServing(f32),
+    /// Non-positive, infinite or NaN yield of the cooked variant.
+    InvalidCookedYield(f32),
+    EmptyUnitLabel(AllowedUnitsType),
+    /// Barcode that isn't 8 to 14 digits, see [`is_valid_barcode`].
+    InvalidBarcode(String),
+}
+
+impl fmt::Display for ProductValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProductValidationError::EmptyName => write!(f, "Product name is empty"),
+            ProductValidationError::InvalidNutrient { nutrient, value } => {
+                write!(f, "Invalid {nutrient} value: {value}")
+            }
+            ProductValidationError::SugarAboveCarbs { sugar, carbs } => {
+                write!(f, "Sugar ({sugar}g) exceeds carbohydrates ({carbs}g)")
+            }
+            ProductValidationError::SaturatedFatAboveFat { saturated_fat, fat } => {
+                write!(f, "Saturated fat ({saturated_fat}g) exceeds fat ({fat}g)")
+            }
+            ProductValidationError::MacrosAbove100g { total } => {
+                write!(
+                    f,
+                    "Fat, carbohydrates and protein add up to {total}g per 100g"
+                )
+            }
+            ProductValidationError::InvalidPrice(price) => write!(f, "Invalid price: {price}"),
+            ProductValidationError::InvalidUnit(unit) => {
+                write!(f, "Unit '{unit}' must have non-zero amount and divider")
+            }
+            ProductValidationError::InvalidDensity(density) => {
+                write!(f, "Invalid density: {density} g/ml")
+            }
+            ProductValidationError::InvalidServing(grams) => {
+                write!(f, "Invalid serving size: {grams}g")
+            }
+            ProductValidationError::InvalidCookedYield(yield_factor) => {
+                write!(f, "Invalid cooked yield: x{yield_factor}")
+            }
+            ProductValidationError::EmptyUnitLabel(unit) => {
+                write!(f, "Label of unit '{unit}' is empty")
+            }
+            ProductValidationError::InvalidBarcode(barcode) => {
+                write!(f, "Invalid barcode '{barcode}'")
+            }
+        }
+    }
+}
+
+/// Whether `barcode` looks like an EAN-8, UPC, EAN-13 or GTIN-14 code: 8 to 14 digits.
+#[must_use]
+pub fn is_valid_barcode(barcode: &str) -> bool {
+    (8..=14).contains(&barcode.len()) && barcode.bytes().all(|b| b.is_ascii_digit())
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Product {
     name: String,
@@ -63,6 +199,36 @@ pub struct Product {
     pub macro_elements: Box<MacroElements>,
     pub micro_nutrients: Box<MicroNutrients>,
     pub allowed_units: AllowedUnits,
+    #[serde(default)]
+    pub price: Option<Price>,
+    /// Spices, coffee and similar products with no meaningful nutrients. They stay in meals and
+    /// shopping lists, but are left out of nutrient accounting.
+    #[serde(default)]
+    pub negligible: bool,
+    #[serde(default)]
+    pub allergens: BTreeSet<Allergen>,
+    #[serde(default)]
+    pub diet_flags: BTreeSet<DietFlag>,
+    /// Density of liquids, lets volume units be defined in millilitres.
+    #[serde(default)]
+    pub grams_per_ml: Option<f32>,
+    /// Product specific unit names, e.g. "slice" for a piece of bread or "can" for a box.
+    #[serde(default)]
+    pub unit_labels: BTreeMap<AllowedUnitsType, String>,
+    /// Serving size from the label, nutrients are still stored per 100g.
+    #[serde(default)]
+    pub serving: Option<ServingInfo>,
+    /// Cooked form of a raw product, lets amounts be planned cooked and bought raw.
+    #[serde(default)]
+    pub cooked: Option<CookedVariant>,
+    /// Leaves alcohol out of computed calories, for products whose alcohol value isn't in grams.
+    #[serde(default)]
+    pub ignore_alcohol_calories: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data_quality: Option<DataQuality>,
+    /// Code printed on the package, e.g. an EAN-13, for finding the product by scanning it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub barcode: Option<String>,
 }
 
 impl Product {
@@ -81,7 +247,226 @@ impl Product {
             macro_elements,
             micro_nutrients,
             allowed_units,
+            price: None,
+            negligible: false,
+            allergens: BTreeSet::new(),
+            diet_flags: BTreeSet::new(),
+            grams_per_ml: None,
+            unit_labels: BTreeMap::new(),
+            serving: None,
+            cooked: None,
+            ignore_alcohol_calories: false,
+            data_quality: None,
+            barcode: None,
+        }
+    }
+
+    /// Same as [`Product::new`], but rejects products failing [`Product::validate`].
+    pub fn try_new(
+        name: String,
+        brand: Option<String>,
+        macro_elements: Box<MacroElements>,
+        micro_nutrients: Box<MicroNutrients>,
+        allowed_units: AllowedUnits,
+    ) -> Result<Self, Vec<ProductValidationError>> {
+        let product = Self::new(name, brand, macro_elements, micro_nutrients, allowed_units);
+        product.validate()?;
+        Ok(product)
+    }
+
+    /// Checks that the values are physically possible, returning every problem found.
+    pub fn validate(&self) -> Result<(), Vec<ProductValidationError>> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push(ProductValidationError::EmptyName);
+        }
+        let invalid = |value: f32| !value.is_finite() || value < 0.0;
+        for macro_type in MacroElementsType::iter() {
+            let value = self.macro_elements[macro_type];
+            if invalid(value) {
+                errors.push(ProductValidationError::InvalidNutrient {
+                    nutrient: NutrientType::Macro(macro_type),
+                    value,
+                });
+            }
+        }
+        for micro_type in MicroNutrientsType::iter() {
+            if let Some(value) = self.micro_nutrients[micro_type]
+                && invalid(value)
+            {
+                errors.push(ProductValidationError::InvalidNutrient {
+                    nutrient: NutrientType::Micro(micro_type),
+                    value,
+                });
+            }
+        }
+        let fat = self.macro_elements[MacroElementsType::Fat];
+        let saturated_fat = self.macro_elements[MacroElementsType::SaturatedFat];
+        let carbs = self.macro_elements[MacroElementsType::Carbs];
+        let sugar = self.macro_elements[MacroElementsType::Sugar];
+        let protein = self.macro_elements[MacroElementsType::Protein];
+        if sugar > carbs + LABEL_ROUNDING_G {
+            errors.push(ProductValidationError::SugarAboveCarbs { sugar, carbs });
+        }
+        if saturated_fat > fat + LABEL_ROUNDING_G {
+            errors.push(ProductValidationError::SaturatedFatAboveFat { saturated_fat, fat });
+        }
+        let total = fat + carbs + protein;
+        if total > 100.0 + LABEL_ROUNDING_G {
+            errors.push(ProductValidationError::MacrosAbove100g { total });
+        }
+        if let Some(price) = &self.price
+            && invalid(price.per_100g)
+        {
+            errors.push(ProductValidationError::InvalidPrice(price.per_100g));
+        }
+        for (unit, data) in &self.allowed_units {
+            if data.amount == 0 || data.divider == 0 {
+                errors.push(ProductValidationError::InvalidUnit(*unit));
+            }
+        }
+        if let Some(density) = self.grams_per_ml
+            && (!density.is_finite() || density <= 0.0)
+        {
+            errors.push(ProductValidationError::InvalidDensity(density));
+        }
+        if let Some(serving) = self.serving
+            && !serving.is_valid()
+        {
+            errors.push(ProductValidationError::InvalidServing(
+                serving.grams_per_serving,
+            ));
+        }
+        if let Some(cooked) = &self.cooked
+            && !cooked.is_valid()
+        {
+            errors.push(ProductValidationError::InvalidCookedYield(
+                cooked.yield_factor,
+            ));
+        }
+        for (unit, label) in &self.unit_labels {
+            if label.trim().is_empty() {
+                errors.push(ProductValidationError::EmptyUnitLabel(*unit));
+            }
+        }
+        if let Some(barcode) = &self.barcode
+            && !is_valid_barcode(barcode)
+        {
+            errors.push(ProductValidationError::InvalidBarcode(barcode.clone()));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    #[must_use]
+    pub fn with_price(mut self, price: Option<Price>) -> Self {
+        self.price = price;
+        self
+    }
+
+    #[must_use]
+    pub fn with_negligible(mut self, negligible: bool) -> Self {
+        self.negligible = negligible;
+        self
+    }
+
+    #[must_use]
+    pub fn with_ignore_alcohol_calories(mut self, ignore: bool) -> Self {
+        self.ignore_alcohol_calories = ignore;
+        self
+    }
+
+    #[must_use]
+    pub fn with_allergens(mut self, allergens: BTreeSet<Allergen>) -> Self {
+        self.allergens = allergens;
+        self
+    }
+
+    #[must_use]
+    pub fn with_diet_flags(mut self, diet_flags: BTreeSet<DietFlag>) -> Self {
+        self.diet_flags = diet_flags;
+        self
+    }
+
+    #[must_use]
+    pub fn with_grams_per_ml(mut self, grams_per_ml: Option<f32>) -> Self {
+        self.grams_per_ml = grams_per_ml;
+        self
+    }
+
+    #[must_use]
+    pub fn with_unit_labels(mut self, unit_labels: BTreeMap<AllowedUnitsType, String>) -> Self {
+        self.unit_labels = unit_labels;
+        self
+    }
+
+    #[must_use]
+    pub fn with_unit_label(mut self, unit: AllowedUnitsType, label: &str) -> Self {
+        self.unit_labels.insert(unit, label.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn with_serving(mut self, serving: Option<ServingInfo>) -> Self {
+        self.serving = serving;
+        self
+    }
+
+    #[must_use]
+    pub fn with_cooked(mut self, cooked: Option<CookedVariant>) -> Self {
+        self.cooked = cooked;
+        self
+    }
+
+    #[must_use]
+    pub fn with_barcode(mut self, barcode: Option<String>) -> Self {
+        self.barcode = barcode;
+        self
+    }
+
+    #[must_use]
+    pub fn with_data_quality(mut self, data_quality: Option<DataQuality>) -> Self {
+        self.data_quality = data_quality;
+        self
+    }
+
+    /// Product specific name of `unit`, `None` when the generic one should be shown.
+    #[must_use]
+    pub fn unit_label(&self, unit: AllowedUnitsType) -> Option<&str> {
+        self.unit_labels.get(&unit).map(String::as_str)
+    }
+
+    /// Defines `unit` by volume using [`Product::grams_per_ml`]. `ml` defaults to
+    /// [`AllowedUnitsType::standard_ml`], the divider of an already allowed unit is kept.
+    pub fn set_volume_unit(
+        &mut self,
+        unit: AllowedUnitsType,
+        ml: Option<f32>,
+    ) -> Result<UnitData, String> {
+        if unit == AllowedUnitsType::Gram {
+            return Err("Grams can't be defined by volume".to_string());
         }
+        let density = self
+            .grams_per_ml
+            .ok_or_else(|| format!("Product '{}' has no density", self.id()))?;
+        let ml = ml
+            .or(unit.standard_ml())
+            .ok_or_else(|| format!("Unit '{unit}' needs a volume in ml"))?;
+        let grams = (ml * density).round();
+        if !grams.is_finite() || grams < 1.0 || grams > f32::from(u16::MAX) {
+            return Err(format!("Invalid weight of unit '{unit}': {grams}g"));
+        }
+        let divider = self.allowed_units.get(&unit).map_or(1, |data| data.divider);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let data = UnitData {
+            amount: grams as u16,
+            divider,
+        };
+        self.allowed_units.insert(unit, data);
+        Ok(data)
     }
 
     #[must_use]
@@ -95,20 +480,140 @@ impl Product {
     }
 
     #[must_use]
-    pub fn id(&self) -> String {
-        match &self.brand {
-            Some(brand) if !brand.is_empty() => format!("{} ({})", self.name(), brand),
-            _ => self.name.clone(),
-        }
+    pub fn id(&self) -> ProductId {
+        ProductId::from(self)
     }
 
     #[must_use]
     pub fn get_nutrient_amount(&self, nutrient: NutrientType) -> Option<f32> {
         match nutrient {
+            NutrientType::Macro(MacroElementsType::Calories) => Some(
+                self.label_calories()
+                    .unwrap_or_else(|| self.computed_calories()),
+            ),
             NutrientType::Macro(macro_type) => Some(self.macro_elements[macro_type]),
             NutrientType::Micro(micro_type) => self.micro_nutrients[micro_type],
         }
     }
+
+    /// Calories per 100g given on the label, see [`MacroElements::calories_override`].
+    #[must_use]
+    pub fn label_calories(&self) -> Option<f32> {
+        self.macro_elements.calories_override()
+    }
+
+    /// Calories per 100g from fat, carbs, protein and, unless ignored, alcohol.
+    #[must_use]
+    pub fn computed_calories(&self) -> f32 {
+        let alcohol = if self.ignore_alcohol_calories {
+            0.0
+        } else {
+            self.micro_nutrients[MicroNutrientsType::Alcohol].unwrap_or(0.0)
+        };
+        self.macro_elements.computed_calories() + ALCOHOL_KCAL_PER_G * alcohol
+    }
+
+    /// Quality indicators recorded when the product was imported, `None` for products entered by
+    /// hand.
+    #[must_use]
+    pub fn data_quality(&self) -> Option<&DataQuality> {
+        self.data_quality.as_ref()
+    }
+
+    /// Whether the nutrient values can be planned with, i.e. entered by hand or imported with
+    /// [`DataQuality::is_verified`] data.
+    #[must_use]
+    pub fn has_verified_data(&self) -> bool {
+        self.data_quality
+            .as_ref()
+            .is_none_or(DataQuality::is_verified)
+    }
+
+    /// Which micro nutrients are unknown, to find products whose data needs fixing first.
+    #[must_use]
+    pub fn completeness(&self) -> CompletenessReport {
+        CompletenessReport::from_missing(
+            MicroNutrientsType::iter()
+                .filter(|m| self.micro_nutrients[*m].is_none())
+                .collect(),
+        )
+    }
+
+    /// Amount of `nutrient` per 100g and per serving, for showing both next to each other.
+    #[must_use]
+    pub fn nutrient_amounts(&self, nutrient: NutrientType) -> NutrientAmounts {
+        let per_100g = self.get_nutrient_amount(nutrient);
+        NutrientAmounts {
+            per_100g,
+            per_serving: self
+                .serving
+                .filter(|serving| serving.is_valid())
+                .zip(per_100g)
+                .map(|(serving, amount)| serving.per_serving(amount)),
+        }
+    }
+}
+
+/// Product as it was before an update, see
+/// [`crate::database_access::MutableDatabase::get_product_history`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductVersion {
+    /// Unix timestamp (seconds) of the update replacing this version.
+    pub archived_at: u64,
+    pub product: Product,
+}
+
+/// Change applied to many products at once, see
+/// [`crate::database_access::MutableDatabase::edit_products`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProductEdit {
+    SetBrand(Option<String>),
+    SetPrice(Option<Price>),
+    SetNegligible(bool),
+    /// Adds the unit, or replaces it when the product already has it.
+    AddUnit(AllowedUnitsType, UnitData),
+    AddAllergen(Allergen),
+    AddDietFlag(DietFlag),
+}
+
+impl ProductEdit {
+    /// Checks the edit can't make a valid product invalid.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ProductEdit::SetBrand(Some(brand)) if brand.trim().is_empty() => {
+                Err("Brand cannot be empty, remove it instead".to_string())
+            }
+            ProductEdit::SetPrice(Some(price))
+                if !price.per_100g.is_finite() || price.per_100g < 0.0 =>
+            {
+                Err(ProductValidationError::InvalidPrice(price.per_100g).to_string())
+            }
+            ProductEdit::AddUnit(AllowedUnitsType::Gram, _) => {
+                Err("Gram unit cannot be changed".to_string())
+            }
+            ProductEdit::AddUnit(unit, data) if data.amount == 0 || data.divider == 0 => {
+                Err(ProductValidationError::InvalidUnit(*unit).to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn apply(&self, product: &mut Product) {
+        match self {
+            ProductEdit::SetBrand(brand) => product.brand.clone_from(brand),
+            ProductEdit::SetPrice(price) => product.price.clone_from(price),
+            ProductEdit::SetNegligible(negligible) => product.negligible = *negligible,
+            ProductEdit::AddUnit(unit, data) => {
+                product.allowed_units.insert(*unit, *data);
+            }
+            ProductEdit::AddAllergen(allergen) => {
+                product.allergens.insert(*allergen);
+            }
+            ProductEdit::AddDietFlag(flag) => {
+                product.diet_flags.insert(*flag);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -116,6 +621,7 @@ mod tests {
     use super::*;
     use crate::data_types::{MacroElementsType, MicroNutrientsType};
     use approx::assert_relative_eq;
+    use std::collections::HashMap;
 
     #[test]
     fn test_product_new_and_accessors() {
@@ -181,6 +687,17 @@ mod tests {
                 );
                 allowed_units
             },
+            price: None,
+            negligible: false,
+            allergens: BTreeSet::new(),
+            diet_flags: BTreeSet::new(),
+            grams_per_ml: None,
+            unit_labels: BTreeMap::new(),
+            serving: None,
+            cooked: None,
+            ignore_alcohol_calories: false,
+            data_quality: None,
+            barcode: None,
         };
         assert_eq!(product.name, "Test Product");
         assert_eq!(product.brand.as_deref(), Some("Test Brand"));
@@ -222,6 +739,30 @@ mod tests {
         );
         assert_eq!(product.allowed_units, expected_allowed_units);
         assert_eq!(product.brand(), None);
+        assert_eq!(product.price, None);
+    }
+
+    #[test]
+    fn test_product_price_defaults_when_missing_in_json() {
+        let product = Product::new(
+            "Priced".to_string(),
+            None,
+            Box::new(MacroElements::new(0.0, 0.0, 0.0, 0.0, 0.0)),
+            Box::default(),
+            std::collections::HashMap::new(),
+        )
+        .with_price(Some(Price {
+            per_100g: 1.25,
+            currency: "PLN".to_string(),
+        }));
+
+        let mut json = serde_json::to_value(&product).unwrap();
+        let restored: Product = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(restored, product);
+
+        json.as_object_mut().unwrap().remove("price");
+        let without_price: Product = serde_json::from_value(json).unwrap();
+        assert_eq!(without_price.price, None);
     }
 
     #[test]
@@ -327,4 +868,232 @@ mod tests {
         );
         assert_eq!(product.allowed_units, expected_units);
     }
+
+    #[test]
+    fn test_volume_units_and_labels() {
+        let mut milk = Product::new(
+            "Milk".to_string(),
+            None,
+            Box::new(MacroElements::new(3.2, 2.0, 4.8, 4.8, 3.3)),
+            Box::default(),
+            HashMap::new(),
+        );
+        assert!(milk.set_volume_unit(AllowedUnitsType::Cup, None).is_err());
+
+        let mut milk = milk
+            .with_grams_per_ml(Some(1.03))
+            .with_unit_label(AllowedUnitsType::Box, "carton");
+        assert_eq!(
+            milk.set_volume_unit(AllowedUnitsType::Cup, None),
+            Ok(UnitData {
+                amount: 247,
+                divider: 1
+            })
+        );
+        assert_eq!(
+            milk.set_volume_unit(AllowedUnitsType::Box, Some(1000.0)),
+            Ok(UnitData {
+                amount: 1030,
+                divider: 1
+            })
+        );
+        assert!(
+            milk.set_volume_unit(AllowedUnitsType::Custom, None)
+                .is_err()
+        );
+        assert!(
+            milk.set_volume_unit(AllowedUnitsType::Gram, Some(1.0))
+                .is_err()
+        );
+        assert_eq!(milk.unit_label(AllowedUnitsType::Box), Some("carton"));
+        assert_eq!(milk.unit_label(AllowedUnitsType::Cup), None);
+        assert_eq!(milk.validate(), Ok(()));
+
+        let invalid = milk
+            .with_grams_per_ml(Some(0.0))
+            .with_unit_label(AllowedUnitsType::Piece, " ");
+        assert_eq!(
+            invalid.validate(),
+            Err(vec![
+                ProductValidationError::InvalidDensity(0.0),
+                ProductValidationError::EmptyUnitLabel(AllowedUnitsType::Piece),
+            ])
+        );
+
+        // products saved before volume units load without them
+        let mut json = serde_json::to_value(&invalid).unwrap();
+        json.as_object_mut().unwrap().remove("grams_per_ml");
+        json.as_object_mut().unwrap().remove("unit_labels");
+        let old: Product = serde_json::from_value(json).unwrap();
+        assert_eq!(old.grams_per_ml, None);
+        assert!(old.unit_labels.is_empty());
+    }
+
+    #[test]
+    fn test_nutrient_amounts_per_serving() {
+        let cereal = Product::new(
+            "Cereal".to_string(),
+            None,
+            Box::new(MacroElements::new(2.0, 0.5, 80.0, 20.0, 8.0)),
+            Box::default(),
+            HashMap::new(),
+        );
+        let sugar = NutrientType::Macro(MacroElementsType::Sugar);
+        assert_eq!(
+            cereal.nutrient_amounts(sugar),
+            NutrientAmounts {
+                per_100g: Some(20.0),
+                per_serving: None,
+            }
+        );
+
+        let cereal = cereal.with_serving(Some(ServingInfo::new(30.0)));
+        assert_eq!(cereal.nutrient_amounts(sugar).per_serving, Some(6.0));
+        let fiber = NutrientType::Micro(MicroNutrientsType::Fiber);
+        assert_eq!(cereal.nutrient_amounts(fiber).per_serving, None);
+
+        let invalid = cereal.with_serving(Some(ServingInfo::new(-5.0)));
+        assert_eq!(invalid.nutrient_amounts(sugar).per_serving, None);
+        assert_eq!(
+            invalid.validate(),
+            Err(vec![ProductValidationError::InvalidServing(-5.0)])
+        );
+    }
+
+    #[test]
+    fn test_alcohol_counts_in_computed_calories() {
+        let mut micro_nutrients = MicroNutrients::default();
+        micro_nutrients[MicroNutrientsType::Alcohol] = Some(4.0);
+        let beer = Product::new(
+            "Beer".to_string(),
+            None,
+            Box::new(MacroElements::new(0.0, 0.0, 3.0, 0.0, 0.5)),
+            Box::new(micro_nutrients),
+            HashMap::new(),
+        );
+        let calories = NutrientType::Macro(MacroElementsType::Calories);
+        // 4 * 3 + 4 * 0.5 + 7 * 4
+        assert_relative_eq!(beer.computed_calories(), 42.0);
+        assert_eq!(beer.get_nutrient_amount(calories), Some(42.0));
+        assert_eq!(beer.label_calories(), None);
+
+        let beer = beer.with_ignore_alcohol_calories(true);
+        assert_eq!(beer.get_nutrient_amount(calories), Some(14.0));
+
+        let mut beer = beer.with_ignore_alcohol_calories(false);
+        beer.macro_elements.set_calories_override(Some(43.0));
+        assert_eq!(beer.get_nutrient_amount(calories), Some(43.0));
+        assert_relative_eq!(beer.computed_calories(), 42.0);
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let valid = Product::new(
+            "Oats".to_string(),
+            None,
+            Box::new(MacroElements::new(7.0, 1.2, 60.0, 1.0, 13.0)),
+            Box::default(),
+            HashMap::new(),
+        );
+        assert_eq!(valid.validate(), Ok(()));
+
+        let mut units = HashMap::new();
+        units.insert(
+            AllowedUnitsType::Cup,
+            UnitData {
+                amount: 0,
+                divider: 1,
+            },
+        );
+        let errors = Product::try_new(
+            " ".to_string(),
+            None,
+            Box::new(MacroElements::new(-1.0, 2.0, 80.0, 90.0, 30.0)),
+            Box::default(),
+            units,
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ProductValidationError::EmptyName,
+                ProductValidationError::InvalidNutrient {
+                    nutrient: NutrientType::Macro(MacroElementsType::Fat),
+                    value: -1.0,
+                },
+                ProductValidationError::SugarAboveCarbs {
+                    sugar: 90.0,
+                    carbs: 80.0,
+                },
+                ProductValidationError::SaturatedFatAboveFat {
+                    saturated_fat: 2.0,
+                    fat: -1.0,
+                },
+                ProductValidationError::MacrosAbove100g { total: 109.0 },
+                ProductValidationError::InvalidUnit(AllowedUnitsType::Cup),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_allows_label_rounding() {
+        let oil = Product::new(
+            "Oil".to_string(),
+            None,
+            Box::new(MacroElements::new(100.0, 14.0, 0.0, 0.2, 0.3)),
+            Box::default(),
+            HashMap::new(),
+        );
+        assert_eq!(oil.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_product_edit_apply_and_validate() {
+        let mut product = Product::new(
+            "Oats".to_string(),
+            None,
+            Box::new(MacroElements::new(7.0, 1.0, 66.0, 1.0, 13.0)),
+            Box::default(),
+            HashMap::new(),
+        );
+        let cup = UnitData {
+            amount: 90,
+            divider: 1,
+        };
+        for edit in [
+            ProductEdit::SetBrand(Some("Mill".to_string())),
+            ProductEdit::AddUnit(AllowedUnitsType::Cup, cup),
+            ProductEdit::AddAllergen(Allergen::Gluten),
+        ] {
+            assert!(edit.validate().is_ok());
+            edit.apply(&mut product);
+        }
+        assert_eq!(product.id(), "Oats (Mill)");
+        assert_eq!(
+            product.allowed_units.get(&AllowedUnitsType::Cup),
+            Some(&cup)
+        );
+        assert!(product.allergens.contains(&Allergen::Gluten));
+        assert!(product.validate().is_ok());
+
+        let zero_cup = UnitData {
+            amount: 0,
+            divider: 1,
+        };
+        assert!(
+            ProductEdit::AddUnit(AllowedUnitsType::Cup, zero_cup)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            ProductEdit::AddUnit(AllowedUnitsType::Gram, cup)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            ProductEdit::SetBrand(Some(" ".to_string()))
+                .validate()
+                .is_err()
+        );
+    }
 }
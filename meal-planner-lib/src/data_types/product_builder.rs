@@ -0,0 +1,321 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+    AllowedUnits, AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients,
+    MicroNutrientsType, Price, Product, ProductValidationError, ServingInfo, UnitData,
+    dietary::{Allergen, DietFlag},
+};
+
+/// Fluent way of putting a [`Product`] together, nutrient values are per 100g unless
+/// [`Self::per_serving`] is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductBuilder {
+    name: String,
+    brand: Option<String>,
+    macro_elements: MacroElements,
+    micro_nutrients: MicroNutrients,
+    allowed_units: AllowedUnits,
+    price: Option<Price>,
+    negligible: bool,
+    allergens: BTreeSet<Allergen>,
+    diet_flags: BTreeSet<DietFlag>,
+    grams_per_ml: Option<f32>,
+    unit_labels: BTreeMap<AllowedUnitsType, String>,
+    serving: Option<ServingInfo>,
+    ignore_alcohol_calories: bool,
+    // nutrient values are given for a serving and converted on build
+    per_serving: bool,
+}
+
+impl ProductBuilder {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            brand: None,
+            macro_elements: MacroElements::new(0.0, 0.0, 0.0, 0.0, 0.0),
+            micro_nutrients: MicroNutrients::default(),
+            allowed_units: AllowedUnits::new(),
+            price: None,
+            negligible: false,
+            allergens: BTreeSet::new(),
+            diet_flags: BTreeSet::new(),
+            grams_per_ml: None,
+            unit_labels: BTreeMap::new(),
+            serving: None,
+            ignore_alcohol_calories: false,
+            per_serving: false,
+        }
+    }
+
+    /// Empty brand is the same as no brand.
+    #[must_use]
+    pub fn brand(mut self, brand: impl Into<String>) -> Self {
+        let brand = brand.into();
+        self.brand = (!brand.is_empty()).then_some(brand);
+        self
+    }
+
+    #[must_use]
+    pub fn fat(self, grams: f32) -> Self {
+        self.macro_value(MacroElementsType::Fat, grams)
+    }
+
+    #[must_use]
+    pub fn saturated_fat(self, grams: f32) -> Self {
+        self.macro_value(MacroElementsType::SaturatedFat, grams)
+    }
+
+    #[must_use]
+    pub fn carbs(self, grams: f32) -> Self {
+        self.macro_value(MacroElementsType::Carbs, grams)
+    }
+
+    #[must_use]
+    pub fn sugar(self, grams: f32) -> Self {
+        self.macro_value(MacroElementsType::Sugar, grams)
+    }
+
+    #[must_use]
+    pub fn protein(self, grams: f32) -> Self {
+        self.macro_value(MacroElementsType::Protein, grams)
+    }
+
+    /// Replaces every macro element at once, e.g. with values edited in a form.
+    #[must_use]
+    pub fn macro_elements(mut self, macro_elements: MacroElements) -> Self {
+        self.macro_elements = macro_elements;
+        self
+    }
+
+    #[must_use]
+    pub fn micro(mut self, micro_type: MicroNutrientsType, amount: f32) -> Self {
+        self.micro_nutrients[micro_type] = Some(amount);
+        self
+    }
+
+    /// Replaces every micro nutrient at once, e.g. with values edited in a form.
+    #[must_use]
+    pub fn micro_nutrients(mut self, micro_nutrients: MicroNutrients) -> Self {
+        self.micro_nutrients = micro_nutrients;
+        self
+    }
+
+    /// One `unit` weighs `amount / divider` grams. Gram is always allowed.
+    #[must_use]
+    pub fn unit(mut self, unit: AllowedUnitsType, amount: u16, divider: u16) -> Self {
+        self.allowed_units
+            .insert(unit, UnitData { amount, divider });
+        self
+    }
+
+    /// Replaces every unit set so far, gram is still added on build.
+    #[must_use]
+    pub fn units(mut self, allowed_units: AllowedUnits) -> Self {
+        self.allowed_units = allowed_units;
+        self
+    }
+
+    #[must_use]
+    pub fn price(mut self, per_100g: f32, currency: impl Into<String>) -> Self {
+        self.price = Some(Price {
+            per_100g,
+            currency: currency.into(),
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn negligible(mut self, negligible: bool) -> Self {
+        self.negligible = negligible;
+        self
+    }
+
+    #[must_use]
+    pub fn ignore_alcohol_calories(mut self, ignore: bool) -> Self {
+        self.ignore_alcohol_calories = ignore;
+        self
+    }
+
+    #[must_use]
+    pub fn allergen(mut self, allergen: Allergen) -> Self {
+        self.allergens.insert(allergen);
+        self
+    }
+
+    #[must_use]
+    pub fn diet_flag(mut self, diet_flag: DietFlag) -> Self {
+        self.diet_flags.insert(diet_flag);
+        self
+    }
+
+    #[must_use]
+    pub fn grams_per_ml(mut self, grams_per_ml: Option<f32>) -> Self {
+        self.grams_per_ml = grams_per_ml;
+        self
+    }
+
+    #[must_use]
+    pub fn unit_label(mut self, unit: AllowedUnitsType, label: impl Into<String>) -> Self {
+        self.unit_labels.insert(unit, label.into());
+        self
+    }
+
+    /// Replaces every unit label set so far, e.g. with labels of a product being edited.
+    #[must_use]
+    pub fn unit_labels(mut self, unit_labels: BTreeMap<AllowedUnitsType, String>) -> Self {
+        self.unit_labels = unit_labels;
+        self
+    }
+
+    /// Serving size shown next to the values per 100g, nutrients are still given per 100g.
+    #[must_use]
+    pub fn serving(mut self, grams_per_serving: f32) -> Self {
+        self.serving = Some(ServingInfo::new(grams_per_serving));
+        self.per_serving = false;
+        self
+    }
+
+    /// Nutrient values are given for a serving of `grams_per_serving`, e.g. copied from a label,
+    /// and converted to per 100g on build.
+    #[must_use]
+    pub fn per_serving(mut self, grams_per_serving: f32) -> Self {
+        self.serving = Some(ServingInfo::new(grams_per_serving));
+        self.per_serving = true;
+        self
+    }
+
+    /// Builds the product, rejecting it with every problem found by [`Product::validate`].
+    pub fn build(self) -> Result<Product, Vec<ProductValidationError>> {
+        let product = self.build_unchecked();
+        product.validate()?;
+        Ok(product)
+    }
+
+    /// Builds the product without validating it, for forms still being filled in.
+    #[must_use]
+    pub fn build_unchecked(self) -> Product {
+        let (macro_elements, micro_nutrients) = match self.serving {
+            // an invalid serving is reported by validation, values are kept as given
+            Some(serving) if self.per_serving && serving.is_valid() => (
+                serving.macros_per_100g(&self.macro_elements),
+                serving.micros_per_100g(&self.micro_nutrients),
+            ),
+            _ => (self.macro_elements, self.micro_nutrients),
+        };
+        Product::new(
+            self.name,
+            self.brand,
+            Box::new(macro_elements),
+            Box::new(micro_nutrients),
+            self.allowed_units,
+        )
+        .with_price(self.price)
+        .with_negligible(self.negligible)
+        .with_allergens(self.allergens)
+        .with_diet_flags(self.diet_flags)
+        .with_grams_per_ml(self.grams_per_ml)
+        .with_unit_labels(self.unit_labels)
+        .with_serving(self.serving)
+        .with_ignore_alcohol_calories(self.ignore_alcohol_calories)
+    }
+
+    fn macro_value(mut self, macro_type: MacroElementsType, grams: f32) -> Self {
+        // only calories are refused, and they have no setter here
+        let _ = self.macro_elements.set(macro_type, grams);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn builds_product_with_every_field() {
+        let product = ProductBuilder::new("Oats")
+            .brand("Mill")
+            .fat(7.0)
+            .saturated_fat(1.2)
+            .carbs(60.0)
+            .sugar(1.0)
+            .protein(13.0)
+            .micro(MicroNutrientsType::Fiber, 10.0)
+            .unit(AllowedUnitsType::Cup, 80, 1)
+            .price(0.9, "PLN")
+            .allergen(Allergen::Gluten)
+            .diet_flag(DietFlag::Vegan)
+            .grams_per_ml(Some(0.4))
+            .unit_label(AllowedUnitsType::Cup, "mug")
+            .build()
+            .unwrap();
+
+        assert_eq!(product.id(), "Oats (Mill)");
+        assert_relative_eq!(product.macro_elements[MacroElementsType::Calories], 355.0);
+        assert_eq!(
+            product.micro_nutrients[MicroNutrientsType::Fiber],
+            Some(10.0)
+        );
+        assert_eq!(
+            product.allowed_units.get(&AllowedUnitsType::Cup),
+            Some(&UnitData {
+                amount: 80,
+                divider: 1
+            })
+        );
+        assert!(product.allowed_units.contains_key(&AllowedUnitsType::Gram));
+        assert_eq!(
+            product.price.as_ref().map(|p| p.currency.as_str()),
+            Some("PLN")
+        );
+        assert!(product.allergens.contains(&Allergen::Gluten));
+        assert!(product.diet_flags.contains(&DietFlag::Vegan));
+        assert_eq!(product.grams_per_ml, Some(0.4));
+        assert_eq!(product.unit_label(AllowedUnitsType::Cup), Some("mug"));
+    }
+
+    #[test]
+    fn build_validates_values() {
+        let builder = ProductBuilder::new("Jam").brand("").carbs(40.0).sugar(60.0);
+        assert_eq!(
+            builder.clone().build(),
+            Err(vec![ProductValidationError::SugarAboveCarbs {
+                sugar: 60.0,
+                carbs: 40.0
+            }])
+        );
+        let unchecked = builder.build_unchecked();
+        assert_eq!(unchecked.brand(), None);
+        assert!(unchecked.validate().is_err());
+    }
+
+    #[test]
+    fn converts_values_given_per_serving() {
+        let bar = ProductBuilder::new("Bar")
+            .per_serving(50.0)
+            .fat(5.0)
+            .carbs(30.0)
+            .protein(10.0)
+            .micro(MicroNutrientsType::Fiber, 2.0)
+            .build()
+            .unwrap();
+        assert_relative_eq!(bar.macro_elements[MacroElementsType::Carbs], 60.0);
+        assert_relative_eq!(bar.macro_elements[MacroElementsType::Fat], 10.0);
+        assert_eq!(bar.micro_nutrients[MicroNutrientsType::Fiber], Some(4.0));
+        assert_eq!(bar.serving, Some(ServingInfo::new(50.0)));
+
+        let labelled = ProductBuilder::new("Bar").serving(50.0).carbs(60.0).build();
+        assert_relative_eq!(
+            labelled.unwrap().macro_elements[MacroElementsType::Carbs],
+            60.0
+        );
+        assert_eq!(
+            ProductBuilder::new("Bar")
+                .per_serving(0.0)
+                .carbs(30.0)
+                .build(),
+            Err(vec![ProductValidationError::InvalidServing(0.0)])
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use super::Product;
+
+/// Identity of a product, `Name (Brand)` or just `Name` for products without a brand. Derefs to
+/// `str`, so it can be passed wherever a product id string is expected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProductId(String);
+
+impl ProductId {
+    /// Empty brand is the same as no brand.
+    #[must_use]
+    pub fn new(name: &str, brand: Option<&str>) -> Self {
+        match brand {
+            Some(brand) if !brand.is_empty() => Self(format!("{name} ({brand})")),
+            _ => Self(name.to_string()),
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Name and brand the id is made of. The brand is the last part in parentheses, so names
+    /// containing parentheses only round trip when the product has a brand.
+    #[must_use]
+    pub fn parts(&self) -> (&str, Option<&str>) {
+        if let Some((name, brand)) = self
+            .0
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once(" ("))
+            && !name.is_empty()
+            && !brand.is_empty()
+        {
+            return (name, Some(brand));
+        }
+        (&self.0, None)
+    }
+}
+
+impl fmt::Display for ProductId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ProductId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = Self(s.trim().to_string());
+        let (name, brand) = id.parts();
+        if name.trim().is_empty() {
+            return Err("Product id is empty".to_string());
+        }
+        Ok(Self::new(name.trim(), brand.map(str::trim)))
+    }
+}
+
+impl From<&Product> for ProductId {
+    fn from(product: &Product) -> Self {
+        Self::new(product.name(), product.brand())
+    }
+}
+
+// ids read back from storage are trusted as they are
+impl From<String> for ProductId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for ProductId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl From<ProductId> for String {
+    fn from(id: ProductId) -> Self {
+        id.0
+    }
+}
+
+impl Deref for ProductId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ProductId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ProductId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for ProductId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ProductId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for ProductId {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl PartialEq<ProductId> for String {
+    fn eq(&self, other: &ProductId) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialEq<ProductId> for &str {
+    fn eq(&self, other: &ProductId) -> bool {
+        *self == other.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::ProductBuilder;
+
+    #[test]
+    fn id_round_trips_through_display_and_parse() {
+        let branded = ProductBuilder::new("Oats").brand("Mill").build().unwrap();
+        let id = ProductId::from(&branded);
+        assert_eq!(id, "Oats (Mill)");
+        assert_eq!(id.parts(), ("Oats", Some("Mill")));
+        assert_eq!(id.to_string().parse::<ProductId>(), Ok(id.clone()));
+        assert_eq!(branded.id(), id);
+
+        let plain = ProductId::new("Banana", Some(""));
+        assert_eq!(plain.parts(), ("Banana", None));
+        assert_eq!(" Banana ".parse::<ProductId>(), Ok(plain));
+        assert_eq!(
+            "Milk (2%) (Farm)".parse::<ProductId>().unwrap().parts(),
+            ("Milk (2%)", Some("Farm"))
+        );
+        assert!("  ".parse::<ProductId>().is_err());
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"Oats (Mill)\"");
+        assert_eq!(serde_json::from_str::<ProductId>(&json).unwrap(), id);
+    }
+}
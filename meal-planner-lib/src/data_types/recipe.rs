@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use super::Product;
+
+/// Product and its amount in a whole [`Recipe`] batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecipeIngredient {
+    pub product: Product,
+    pub grams: f32,
+}
+
+/// Dish made of several products, cooked as a batch split into `servings` equal servings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RecipeFields")]
+pub struct Recipe {
+    name: String,
+    servings: u16,
+    ingredients: Vec<RecipeIngredient>,
+}
+
+// deserialized fields, checked by `Recipe::new`
+#[derive(Deserialize)]
+struct RecipeFields {
+    name: String,
+    servings: u16,
+    ingredients: Vec<RecipeIngredient>,
+}
+
+impl TryFrom<RecipeFields> for Recipe {
+    type Error = String;
+
+    fn try_from(fields: RecipeFields) -> Result<Self, Self::Error> {
+        Self::new(fields.name, fields.servings, fields.ingredients)
+    }
+}
+
+impl Recipe {
+    /// Fails on an empty name, no servings, no ingredients or an ingredient without a positive
+    /// amount.
+    pub fn new(
+        name: impl Into<String>,
+        servings: u16,
+        ingredients: Vec<RecipeIngredient>,
+    ) -> Result<Self, String> {
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err("Recipe name can't be empty".to_string());
+        }
+        if servings == 0 {
+            return Err(format!("Recipe '{name}' needs at least one serving"));
+        }
+        if ingredients.is_empty() {
+            return Err(format!("Recipe '{name}' has no ingredients"));
+        }
+        if let Some(ingredient) = ingredients
+            .iter()
+            .find(|i| !i.grams.is_finite() || i.grams <= 0.0)
+        {
+            return Err(format!(
+                "Invalid amount of '{}' in recipe '{name}': {} g",
+                ingredient.product.id(),
+                ingredient.grams
+            ));
+        }
+        Ok(Self {
+            name,
+            servings,
+            ingredients,
+        })
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn servings(&self) -> u16 {
+        self.servings
+    }
+
+    #[must_use]
+    pub fn ingredients(&self) -> &[RecipeIngredient] {
+        &self.ingredients
+    }
+
+    /// The recipe with ingredient amounts adjusted to make `servings` servings.
+    pub fn scaled(&self, servings: u16) -> Result<Self, String> {
+        let factor = f32::from(servings) / f32::from(self.servings);
+        let ingredients = self
+            .ingredients
+            .iter()
+            .map(|ingredient| RecipeIngredient {
+                product: ingredient.product.clone(),
+                grams: ingredient.grams * factor,
+            })
+            .collect();
+        Self::new(self.name.clone(), servings, ingredients)
+    }
+
+    /// Grams of every ingredient in a single serving.
+    #[must_use]
+    pub fn per_serving(&self) -> Vec<(&Product, f64)> {
+        self.ingredients
+            .iter()
+            .map(|ingredient| {
+                (
+                    &ingredient.product,
+                    f64::from(ingredient.grams) / f64::from(self.servings),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::ProductBuilder;
+    use approx::assert_relative_eq;
+
+    fn ingredient(name: &str, grams: f32) -> RecipeIngredient {
+        RecipeIngredient {
+            product: ProductBuilder::new(name).build().unwrap(),
+            grams,
+        }
+    }
+
+    #[test]
+    fn scales_ingredients_to_servings() {
+        let stew = Recipe::new(
+            "Stew",
+            4,
+            vec![ingredient("Beans", 400.0), ingredient("Tomatoes", 800.0)],
+        )
+        .unwrap();
+        let for_two = stew.scaled(2).unwrap();
+        assert_eq!(for_two.servings(), 2);
+        assert_relative_eq!(for_two.ingredients()[0].grams, 200.0);
+        assert_relative_eq!(for_two.ingredients()[1].grams, 400.0);
+        assert_relative_eq!(stew.per_serving()[1].1, 200.0);
+        assert!(stew.scaled(0).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_recipes() {
+        assert!(Recipe::new(" ", 1, vec![ingredient("Beans", 100.0)]).is_err());
+        assert!(Recipe::new("Stew", 0, vec![ingredient("Beans", 100.0)]).is_err());
+        assert!(Recipe::new("Stew", 1, Vec::new()).is_err());
+        assert!(Recipe::new("Stew", 1, vec![ingredient("Beans", 0.0)]).is_err());
+        let json = r#"{"name":"Stew","servings":0,"ingredients":[]}"#;
+        assert!(serde_json::from_str::<Recipe>(json).is_err());
+    }
+}
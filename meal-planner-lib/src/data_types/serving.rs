@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use super::{MacroElements, MacroElementsType, MicroNutrients, MicroNutrientsType};
+
+/// Size of one serving, for products whose labels give nutrients per serving.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ServingInfo {
+    pub grams_per_serving: f32,
+}
+
+/// Amount of a nutrient for display, see [`super::Product::nutrient_amounts`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NutrientAmounts {
+    pub per_100g: Option<f32>,
+    /// `None` also when the product has no serving size.
+    pub per_serving: Option<f32>,
+}
+
+impl ServingInfo {
+    #[must_use]
+    pub fn new(grams_per_serving: f32) -> Self {
+        Self { grams_per_serving }
+    }
+
+    #[must_use]
+    pub fn is_valid(self) -> bool {
+        self.grams_per_serving.is_finite() && self.grams_per_serving > 0.0
+    }
+
+    /// Amount in one serving of a nutrient given per 100g.
+    #[must_use]
+    pub fn per_serving(self, per_100g: f32) -> f32 {
+        per_100g * self.grams_per_serving / 100.0
+    }
+
+    /// Amount in 100g of a nutrient given per serving.
+    #[must_use]
+    pub fn per_100g(self, per_serving: f32) -> f32 {
+        per_serving * 100.0 / self.grams_per_serving
+    }
+
+    /// Macro elements per 100g from the ones of a serving, calories are recomputed unless overridden.
+    #[must_use]
+    pub fn macros_per_100g(self, per_serving: &MacroElements) -> MacroElements {
+        let value = |macro_type| self.per_100g(per_serving[macro_type]);
+        MacroElements::new(
+            value(MacroElementsType::Fat),
+            value(MacroElementsType::SaturatedFat),
+            value(MacroElementsType::Carbs),
+            value(MacroElementsType::Sugar),
+            value(MacroElementsType::Protein),
+        )
+        .with_calories_override(
+            per_serving
+                .calories_override()
+                .map(|kcal| self.per_100g(kcal)),
+        )
+    }
+
+    /// Micro nutrients per 100g from the ones of a serving, unknown values stay unknown.
+    #[must_use]
+    pub fn micros_per_100g(self, per_serving: &MicroNutrients) -> MicroNutrients {
+        let mut micro_nutrients = MicroNutrients::default();
+        for micro_type in MicroNutrientsType::iter() {
+            micro_nutrients[micro_type] = per_serving[micro_type].map(|v| self.per_100g(v));
+        }
+        micro_nutrients
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn converts_between_serving_and_100g() {
+        let serving = ServingInfo::new(40.0);
+        assert_relative_eq!(serving.per_serving(25.0), 10.0);
+        assert_relative_eq!(serving.per_100g(10.0), 25.0);
+
+        let macros = serving.macros_per_100g(&MacroElements::new(2.0, 1.0, 24.0, 4.0, 4.0));
+        assert_relative_eq!(macros[MacroElementsType::Carbs], 60.0);
+        assert_relative_eq!(macros[MacroElementsType::Calories], 325.0);
+        let mut per_serving = MicroNutrients::default();
+        per_serving[MicroNutrientsType::Fiber] = Some(3.0);
+        let per_100g = serving.micros_per_100g(&per_serving);
+        assert_eq!(per_100g[MicroNutrientsType::Fiber], Some(7.5));
+        assert_eq!(per_100g[MicroNutrientsType::Iron], None);
+
+        assert!(!ServingInfo::new(0.0).is_valid());
+        assert!(!ServingInfo::new(f32::NAN).is_valid());
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+const ENABLED_KEY: &str = "enabled";
+const SOLVES_KEY: &str = "solves";
+const PRODUCTS_ADDED_KEY: &str = "products_added";
+const VIEW_KEY_PREFIX: &str = "view:";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsageEvent {
+    Solve,
+    ProductAdded,
+    /// View opened by the user, identified by a stable name chosen by the app.
+    ViewOpened(String),
+}
+
+/// Opt-in usage counters. They are kept in the local database only and never sent anywhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Events are only counted after the user opts in.
+    pub enabled: bool,
+    pub solves: u64,
+    pub products_added: u64,
+    pub view_opens: BTreeMap<String, u64>,
+}
+
+impl UsageStats {
+    /// Counts `event` when stats are enabled. Returns whether anything changed.
+    pub fn record(&mut self, event: &UsageEvent) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match event {
+            UsageEvent::Solve => self.solves += 1,
+            UsageEvent::ProductAdded => self.products_added += 1,
+            UsageEvent::ViewOpened(view) => {
+                *self.view_opens.entry(view.clone()).or_insert(0) += 1;
+            }
+        }
+        true
+    }
+
+    /// Views ordered from the most opened, ties by name.
+    #[must_use]
+    pub fn most_used_views(&self) -> Vec<(&str, u64)> {
+        let mut views: Vec<(&str, u64)> = self
+            .view_opens
+            .iter()
+            .map(|(view, count)| (view.as_str(), *count))
+            .collect();
+        views.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        views
+    }
+
+    /// Resets every counter, the opt-in choice is kept.
+    pub fn wipe(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            ..Self::default()
+        };
+    }
+
+    pub fn export_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to export usage stats: {e}"))
+    }
+
+    /// Flattens the stats into `(name, count)` rows of the `usage_stats` table.
+    pub(crate) fn to_rows(&self) -> Vec<(String, i64)> {
+        let count = |value: u64| i64::try_from(value).unwrap_or(i64::MAX);
+        [
+            (ENABLED_KEY.to_string(), i64::from(self.enabled)),
+            (SOLVES_KEY.to_string(), count(self.solves)),
+            (PRODUCTS_ADDED_KEY.to_string(), count(self.products_added)),
+        ]
+        .into_iter()
+        .chain(
+            self.view_opens
+                .iter()
+                .map(|(view, opens)| (format!("{VIEW_KEY_PREFIX}{view}"), count(*opens))),
+        )
+        .collect()
+    }
+
+    pub(crate) fn from_rows(rows: impl IntoIterator<Item = (String, i64)>) -> Self {
+        let mut stats = Self::default();
+        for (name, value) in rows {
+            let value_u64 = u64::try_from(value).unwrap_or(0);
+            match name.as_str() {
+                ENABLED_KEY => stats.enabled = value != 0,
+                SOLVES_KEY => stats.solves = value_u64,
+                PRODUCTS_ADDED_KEY => stats.products_added = value_u64,
+                _ => {
+                    if let Some(view) = name.strip_prefix(VIEW_KEY_PREFIX) {
+                        stats.view_opens.insert(view.to_string(), value_u64);
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_when_enabled() {
+        let mut stats = UsageStats::default();
+        assert!(!stats.record(&UsageEvent::Solve));
+        assert_eq!(stats, UsageStats::default());
+
+        stats.enabled = true;
+        assert!(stats.record(&UsageEvent::Solve));
+        stats.record(&UsageEvent::ProductAdded);
+        stats.record(&UsageEvent::ViewOpened("swap".to_string()));
+        stats.record(&UsageEvent::ViewOpened("plan".to_string()));
+        stats.record(&UsageEvent::ViewOpened("plan".to_string()));
+        stats.record(&UsageEvent::ViewOpened("db".to_string()));
+        assert_eq!(stats.solves, 1);
+        assert_eq!(stats.products_added, 1);
+        assert_eq!(
+            stats.most_used_views(),
+            vec![("plan", 2), ("db", 1), ("swap", 1)]
+        );
+    }
+
+    #[test]
+    fn wipe_keeps_opt_in() {
+        let mut stats = UsageStats {
+            enabled: true,
+            ..UsageStats::default()
+        };
+        stats.record(&UsageEvent::Solve);
+        stats.wipe();
+        assert_eq!(
+            stats,
+            UsageStats {
+                enabled: true,
+                ..UsageStats::default()
+            }
+        );
+        stats.record(&UsageEvent::ViewOpened("plan".to_string()));
+        assert_eq!(UsageStats::from_rows(stats.to_rows()), stats);
+
+        let exported = stats.export_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<UsageStats>(&exported).unwrap(),
+            stats
+        );
+    }
+}
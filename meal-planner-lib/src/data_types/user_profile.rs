@@ -0,0 +1,201 @@
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+impl fmt::Display for Sex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Sex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "male" => Ok(Sex::Male),
+            "female" => Ok(Sex::Female),
+            _ => Err(format!("Unknown sex '{s}'")),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
+pub enum ActivityLevel {
+    /// Desk job, little or no exercise.
+    Sedentary,
+    /// Exercise 1-3 days a week.
+    Light,
+    /// Exercise 3-5 days a week.
+    Moderate,
+    /// Exercise 6-7 days a week.
+    Active,
+    /// Physical job or training twice a day.
+    VeryActive,
+}
+
+impl ActivityLevel {
+    /// Factor converting basal metabolic rate to total daily energy expenditure.
+    #[must_use]
+    pub fn multiplier(self) -> f32 {
+        match self {
+            ActivityLevel::Sedentary => 1.2,
+            ActivityLevel::Light => 1.375,
+            ActivityLevel::Moderate => 1.55,
+            ActivityLevel::Active => 1.725,
+            ActivityLevel::VeryActive => 1.9,
+        }
+    }
+}
+
+impl fmt::Display for ActivityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ActivityLevel::Sedentary => "sedentary",
+            ActivityLevel::Light => "light",
+            ActivityLevel::Moderate => "moderate",
+            ActivityLevel::Active => "active",
+            ActivityLevel::VeryActive => "very active",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ActivityLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sedentary" => Ok(ActivityLevel::Sedentary),
+            "light" => Ok(ActivityLevel::Light),
+            "moderate" => Ok(ActivityLevel::Moderate),
+            "active" => Ok(ActivityLevel::Active),
+            "very active" => Ok(ActivityLevel::VeryActive),
+            _ => Err(format!("Unknown activity level '{s}'")),
+        }
+    }
+}
+
+/// Body data used to compute personal nutrition targets.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub age_years: u8,
+    pub sex: Sex,
+    pub weight_kg: f32,
+    pub height_cm: f32,
+    pub activity_level: ActivityLevel,
+}
+
+impl UserProfile {
+    /// Rejects values outside of what the targets formulas are meant for.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(18..=100).contains(&self.age_years) {
+            return Err(format!(
+                "Age must be between 18 and 100: {}",
+                self.age_years
+            ));
+        }
+        if !self.weight_kg.is_finite() || !(30.0..=300.0).contains(&self.weight_kg) {
+            return Err(format!(
+                "Weight must be between 30 and 300 kg: {}",
+                self.weight_kg
+            ));
+        }
+        if !self.height_cm.is_finite() || !(100.0..=250.0).contains(&self.height_cm) {
+            return Err(format!(
+                "Height must be between 100 and 250 cm: {}",
+                self.height_cm
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Id of the profile that always exists, owning the data saved before profiles were added.
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Person sharing the app, each one with their own [`UserProfile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    /// Name shown when switching profiles.
+    pub name: String,
+}
+
+impl Profile {
+    #[must_use]
+    pub fn default_profile() -> Self {
+        Self {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "Default".to_string(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Profile id is empty".to_string());
+        }
+        if self.name.trim().is_empty() {
+            return Err(format!("Profile '{}' has an empty name", self.id));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    fn profile() -> UserProfile {
+        UserProfile {
+            age_years: 30,
+            sex: Sex::Female,
+            weight_kg: 60.0,
+            height_cm: 165.0,
+            activity_level: ActivityLevel::Moderate,
+        }
+    }
+
+    #[test]
+    fn enums_round_trip_through_strings() {
+        for sex in Sex::iter() {
+            assert_eq!(sex.to_string().parse::<Sex>(), Ok(sex));
+        }
+        for level in ActivityLevel::iter() {
+            assert_eq!(level.to_string().parse::<ActivityLevel>(), Ok(level));
+        }
+        assert!("tired".parse::<ActivityLevel>().is_err());
+    }
+
+    #[test]
+    fn validate_checks_ranges() {
+        assert_eq!(profile().validate(), Ok(()));
+        let young = UserProfile {
+            age_years: 12,
+            ..profile()
+        };
+        assert!(young.validate().is_err());
+        let heavy = UserProfile {
+            weight_kg: f32::NAN,
+            ..profile()
+        };
+        assert!(heavy.validate().is_err());
+        let short = UserProfile {
+            height_cm: 50.0,
+            ..profile()
+        };
+        assert!(short.validate().is_err());
+    }
+}
@@ -0,0 +1,517 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::data_quality::{DataQualitySummary, DatabaseStats};
+use super::db_events::DbEventStream;
+use super::db_wrapper::{Database, DbSearchCriteria, MutableDatabase, SortKey};
+use super::product_import::BulkReport;
+use crate::bl::constraints_solver::Solution;
+use crate::bl::grocery_haul::{Pantry, ShoppingList};
+use crate::bl::targets_review::TargetsRecord;
+use crate::data_types::{
+    ActivityLog, AllowedUnitsType, AppSettings, BeverageEntry, CompletenessReport, EatenItem,
+    MealNote, Product, ProductEdit, ProductId, ProductVersion, Profile, UnitData, UsageStats,
+    UserProfile,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Most searches kept, the least recently used one is dropped first. Zero disables caching.
+    pub capacity: usize,
+    /// How long a search result stays valid, `None` keeps it until the next mutation.
+    pub ttl: Option<Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            ttl: Some(Duration::from_mins(5)),
+        }
+    }
+}
+
+struct CacheEntry {
+    products: BTreeMap<ProductId, Product>,
+    stored_at: Duration,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct SearchCache {
+    entries: HashMap<String, CacheEntry>,
+    // incremented on every access, orders entries by their last use
+    tick: u64,
+}
+
+impl SearchCache {
+    fn get(
+        &mut self,
+        key: &str,
+        now: Duration,
+        ttl: Option<Duration>,
+    ) -> Option<BTreeMap<ProductId, Product>> {
+        let expired = self
+            .entries
+            .get(key)
+            .is_some_and(|entry| ttl.is_some_and(|ttl| now.saturating_sub(entry.stored_at) >= ttl));
+        if expired {
+            self.entries.remove(key);
+            return None;
+        }
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.tick;
+        Some(entry.products.clone())
+    }
+
+    fn insert(
+        &mut self,
+        key: String,
+        products: BTreeMap<ProductId, Product>,
+        now: Duration,
+        capacity: usize,
+    ) {
+        if capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= capacity && !self.entries.contains_key(&key) {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.tick += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                products,
+                stored_at: now,
+                last_used: self.tick,
+            },
+        );
+    }
+}
+
+/// [`Database`] decorator keeping results of recent product searches in memory.
+///
+/// Every mutation made through it clears the whole cache. Changes made to the wrapped database
+/// in any other way are only seen once the cached searches expire. Clones share the cache.
+#[derive(Clone)]
+pub struct CachedDatabase<D> {
+    inner: D,
+    config: CacheConfig,
+    cache: Rc<RefCell<SearchCache>>,
+}
+
+impl<D> CachedDatabase<D> {
+    #[must_use]
+    pub fn new(inner: D, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            config,
+            cache: Rc::new(RefCell::new(SearchCache::default())),
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    #[must_use]
+    pub fn config(&self) -> CacheConfig {
+        self.config
+    }
+
+    /// Drops every cached search.
+    pub fn clear(&self) {
+        self.cache.borrow_mut().entries.clear();
+    }
+}
+
+#[async_trait(?Send)]
+impl<D: Database> Database for CachedDatabase<D> {
+    async fn get_products_matching_criteria(
+        &self,
+        criteria: &[DbSearchCriteria],
+    ) -> BTreeMap<ProductId, Product> {
+        let key = format!("{criteria:?}");
+        if let Some(products) = self.cache.borrow_mut().get(&key, now(), self.config.ttl) {
+            return products;
+        }
+        let products = self.inner.get_products_matching_criteria(criteria).await;
+        self.cache
+            .borrow_mut()
+            .insert(key, products.clone(), now(), self.config.capacity);
+        products
+    }
+
+    async fn set_product_unit(
+        &mut self,
+        product_id: &str,
+        allowed_unit: AllowedUnitsType,
+        unit_data: UnitData,
+    ) -> Result<(), String> {
+        let result = self
+            .inner
+            .set_product_unit(product_id, allowed_unit, unit_data)
+            .await;
+        self.clear();
+        result
+    }
+
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        self.inner.data_quality_summary().await
+    }
+
+    async fn least_complete_products(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, CompletenessReport)>, String> {
+        self.inner.least_complete_products(limit).await
+    }
+
+    async fn get_products_page(
+        &self,
+        sort: SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, Product)>, String> {
+        self.inner.get_products_page(sort, offset, limit).await
+    }
+
+    async fn list_brands(&self) -> Result<BTreeMap<String, u64>, String> {
+        self.inner.list_brands().await
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats, String> {
+        self.inner.stats().await
+    }
+
+    fn subscribe(&self) -> Result<DbEventStream, String> {
+        self.inner.subscribe()
+    }
+}
+
+// failed mutations may still have changed something, so the cache is cleared either way
+#[async_trait(?Send)]
+impl<D: MutableDatabase> MutableDatabase for CachedDatabase<D> {
+    async fn add_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        let result = self.inner.add_product(product_id, product).await;
+        self.clear();
+        result
+    }
+
+    async fn update_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        let result = self.inner.update_product(product_id, product).await;
+        self.clear();
+        result
+    }
+
+    async fn delete_product(&mut self, product_id: &str) -> Result<(), String> {
+        let result = self.inner.delete_product(product_id).await;
+        self.clear();
+        result
+    }
+
+    async fn add_products_bulk(
+        &mut self,
+        products: Vec<(String, Product)>,
+    ) -> Result<BulkReport, String> {
+        let result = self.inner.add_products_bulk(products).await;
+        self.clear();
+        result
+    }
+
+    async fn delete_products(&mut self, product_ids: &[String]) -> Result<(), String> {
+        let result = self.inner.delete_products(product_ids).await;
+        self.clear();
+        result
+    }
+
+    async fn edit_products(
+        &mut self,
+        product_ids: &[String],
+        edit: &ProductEdit,
+    ) -> Result<(), String> {
+        let result = self.inner.edit_products(product_ids, edit).await;
+        self.clear();
+        result
+    }
+
+    async fn get_product_history(&self, product_id: &str) -> Result<Vec<ProductVersion>, String> {
+        self.inner.get_product_history(product_id).await
+    }
+
+    async fn rename_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        let result = self.inner.rename_product(product_id, product).await;
+        self.clear();
+        result
+    }
+
+    async fn get_product_uuid(&self, product_id: &str) -> Result<Option<String>, String> {
+        self.inner.get_product_uuid(product_id).await
+    }
+
+    async fn get_product_by_uuid(&self, uuid: &str) -> Result<Option<Product>, String> {
+        self.inner.get_product_by_uuid(uuid).await
+    }
+
+    async fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        self.inner.get_profiles().await
+    }
+
+    async fn set_profile(&mut self, profile: &Profile) -> Result<(), String> {
+        self.inner.set_profile(profile).await
+    }
+
+    async fn delete_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        self.inner.delete_profile(profile_id).await
+    }
+
+    async fn active_profile(&self) -> Result<String, String> {
+        self.inner.active_profile().await
+    }
+
+    async fn set_active_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        self.inner.set_active_profile(profile_id).await
+    }
+
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String> {
+        self.inner.get_user_profile().await
+    }
+
+    async fn set_user_profile(&mut self, profile: &UserProfile) -> Result<(), String> {
+        self.inner.set_user_profile(profile).await
+    }
+
+    async fn get_targets_record(&self) -> Result<Option<TargetsRecord>, String> {
+        self.inner.get_targets_record().await
+    }
+
+    async fn set_targets_record(&mut self, record: &TargetsRecord) -> Result<(), String> {
+        self.inner.set_targets_record(record).await
+    }
+
+    async fn add_beverage_entry(&mut self, entry: &BeverageEntry) -> Result<(), String> {
+        self.inner.add_beverage_entry(entry).await
+    }
+
+    async fn get_beverage_entries(&self, from: u64, to: u64) -> Result<Vec<BeverageEntry>, String> {
+        self.inner.get_beverage_entries(from, to).await
+    }
+
+    async fn add_activity(&mut self, activity: &ActivityLog) -> Result<(), String> {
+        self.inner.add_activity(activity).await
+    }
+
+    async fn get_activities(&self, from: u64, to: u64) -> Result<Vec<ActivityLog>, String> {
+        self.inner.get_activities(from, to).await
+    }
+
+    async fn get_eaten(&self, day: u64) -> Result<Vec<EatenItem>, String> {
+        self.inner.get_eaten(day).await
+    }
+
+    async fn mark_eaten(&mut self, day: u64, item: &EatenItem) -> Result<(), String> {
+        self.inner.mark_eaten(day, item).await
+    }
+
+    async fn unmark_eaten(&mut self, day: u64, meal: &str, product_id: &str) -> Result<(), String> {
+        self.inner.unmark_eaten(day, meal, product_id).await
+    }
+
+    async fn get_meal_notes(&self, day: u64) -> Result<Vec<MealNote>, String> {
+        self.inner.get_meal_notes(day).await
+    }
+
+    async fn set_meal_note(&mut self, day: u64, note: &MealNote) -> Result<(), String> {
+        self.inner.set_meal_note(day, note).await
+    }
+
+    async fn get_shopping_list_ids(&self) -> Result<Vec<String>, String> {
+        self.inner.get_shopping_list_ids().await
+    }
+
+    async fn get_shopping_list(&self, list_id: &str) -> Result<Option<ShoppingList>, String> {
+        self.inner.get_shopping_list(list_id).await
+    }
+
+    async fn save_shopping_list(
+        &mut self,
+        list_id: &str,
+        list: &ShoppingList,
+    ) -> Result<(), String> {
+        self.inner.save_shopping_list(list_id, list).await
+    }
+
+    async fn delete_shopping_list(&mut self, list_id: &str) -> Result<(), String> {
+        self.inner.delete_shopping_list(list_id).await
+    }
+
+    async fn get_pantry(&self) -> Result<Pantry, String> {
+        self.inner.get_pantry().await
+    }
+
+    async fn save_pantry(&mut self, pantry: &Pantry) -> Result<(), String> {
+        self.inner.save_pantry(pantry).await
+    }
+
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        self.inner.get_plan_ids().await
+    }
+
+    async fn get_plan(&self, plan_id: &str) -> Result<Option<Solution>, String> {
+        self.inner.get_plan(plan_id).await
+    }
+
+    async fn save_plan(&mut self, plan_id: &str, plan: &Solution) -> Result<(), String> {
+        self.inner.save_plan(plan_id, plan).await
+    }
+
+    async fn delete_plan(&mut self, plan_id: &str) -> Result<(), String> {
+        self.inner.delete_plan(plan_id).await
+    }
+
+    async fn reorder_meal(
+        &mut self,
+        plan_id: &str,
+        day: &str,
+        meal: &str,
+        new_index: usize,
+    ) -> Result<(), String> {
+        self.inner.reorder_meal(plan_id, day, meal, new_index).await
+    }
+
+    async fn find_solved_plan(&self, fingerprint: u64) -> Result<Option<Solution>, String> {
+        self.inner.find_solved_plan(fingerprint).await
+    }
+
+    async fn save_solved_plan(
+        &mut self,
+        plan_id: &str,
+        fingerprint: u64,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        self.inner
+            .save_solved_plan(plan_id, fingerprint, plan)
+            .await
+    }
+
+    async fn get_app_settings(&self) -> Result<AppSettings, String> {
+        self.inner.get_app_settings().await
+    }
+
+    async fn set_app_settings(&mut self, settings: &AppSettings) -> Result<(), String> {
+        self.inner.set_app_settings(settings).await
+    }
+
+    async fn get_usage_stats(&self) -> Result<UsageStats, String> {
+        self.inner.get_usage_stats().await
+    }
+
+    async fn set_usage_stats(&mut self, stats: &UsageStats) -> Result<(), String> {
+        self.inner.set_usage_stats(stats).await
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_access::mock_db::MockProductDb;
+    use futures::executor::block_on;
+
+    const APPLE: &str = "Apple (BrandedApple)";
+
+    fn search(db: &CachedDatabase<MockProductDb>, prefix: &str) -> Vec<ProductId> {
+        block_on(db.get_products_matching_criteria(&[DbSearchCriteria::ById(prefix.to_string())]))
+            .into_keys()
+            .collect()
+    }
+
+    #[test]
+    fn serves_cached_searches_until_mutation() {
+        let mut db = CachedDatabase::new(MockProductDb::new(), CacheConfig::default());
+        assert_eq!(search(&db, "App"), vec![APPLE]);
+
+        // bypasses the cache, so the stale result is still served
+        let apple = db.inner.products.remove(APPLE).unwrap();
+        assert_eq!(search(&db, "App"), vec![APPLE]);
+
+        block_on(db.add_product(APPLE, apple)).unwrap();
+        block_on(db.delete_product(APPLE)).unwrap();
+        assert!(search(&db, "App").is_empty());
+
+        // clones share the cache
+        let clone = db.clone();
+        db.clear();
+        assert!(db.cache.borrow().entries.is_empty());
+        assert!(search(&clone, "App").is_empty());
+        assert_eq!(db.cache.borrow().entries.len(), 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_searches() {
+        let db = CachedDatabase::new(
+            MockProductDb::new(),
+            CacheConfig {
+                capacity: 2,
+                ttl: None,
+            },
+        );
+        search(&db, "A");
+        search(&db, "B");
+        search(&db, "A");
+        search(&db, "C");
+        let cache = db.cache.borrow();
+        let mut keys: Vec<&String> = cache.entries.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec![r#"[ById("A")]"#, r#"[ById("C")]"#]);
+    }
+
+    #[test]
+    fn expired_searches_are_not_served() {
+        let mut db = CachedDatabase::new(
+            MockProductDb::new(),
+            CacheConfig {
+                capacity: 8,
+                ttl: Some(Duration::ZERO),
+            },
+        );
+        assert_eq!(search(&db, "App"), vec![APPLE]);
+        db.inner.products.remove(APPLE);
+        assert!(search(&db, "App").is_empty());
+
+        let disabled = CachedDatabase::new(
+            MockProductDb::new(),
+            CacheConfig {
+                capacity: 0,
+                ttl: None,
+            },
+        );
+        search(&disabled, "App");
+        assert!(disabled.cache.borrow().entries.is_empty());
+    }
+}
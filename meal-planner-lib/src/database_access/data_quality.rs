@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use strum::IntoEnumIterator;
+
+use super::MutableDatabase;
+use crate::data_types::{AllowedUnitsType, MicroNutrientsType, Product, ProductId};
+
+/// Aggregated data-quality counters over all products stored in a database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataQualitySummary {
+    pub total_products: u64,
+    /// Products without a value for any micro nutrient.
+    pub without_micro_nutrients: u64,
+    /// Products missing a value for at least one micro nutrient.
+    pub incomplete_micro_nutrients: u64,
+    /// Products that can only be measured in grams.
+    pub without_extra_units: u64,
+    pub without_brand: u64,
+    pub without_barcode: u64,
+    /// Products in none of the saved plans of any profile.
+    pub unused_in_plans: u64,
+}
+
+/// Overview of a database for a data-quality dashboard.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    pub quality: DataQualitySummary,
+    /// Number of products of each brand, products without a brand are counted in `quality`.
+    pub products_per_brand: BTreeMap<String, u64>,
+    /// Size of the stored database in bytes, `None` when it isn't stored.
+    pub size_bytes: Option<u64>,
+}
+
+impl DatabaseStats {
+    pub(crate) fn from_products<'a>(products: impl Iterator<Item = &'a Product> + Clone) -> Self {
+        let mut products_per_brand = BTreeMap::new();
+        for brand in products
+            .clone()
+            .filter_map(Product::brand)
+            .filter(|b| !b.is_empty())
+        {
+            *products_per_brand.entry(brand.to_string()).or_insert(0) += 1;
+        }
+        Self {
+            quality: DataQualitySummary::from_products(products, &BTreeSet::new()),
+            products_per_brand,
+            size_bytes: None,
+        }
+    }
+}
+
+impl DataQualitySummary {
+    /// `planned` are the ids of the products used in the saved plans.
+    pub(crate) fn from_products<'a>(
+        products: impl Iterator<Item = &'a Product>,
+        planned: &BTreeSet<ProductId>,
+    ) -> Self {
+        let mut summary = Self::default();
+        for product in products {
+            summary.total_products += 1;
+            let known_micros = MicroNutrientsType::iter()
+                .filter(|m| product.micro_nutrients[*m].is_some())
+                .count();
+            if known_micros == 0 {
+                summary.without_micro_nutrients += 1;
+            }
+            if known_micros < MicroNutrientsType::iter().count() {
+                summary.incomplete_micro_nutrients += 1;
+            }
+            if !AllowedUnitsType::iter()
+                .any(|u| u != AllowedUnitsType::Gram && product.allowed_units.contains_key(&u))
+            {
+                summary.without_extra_units += 1;
+            }
+            if product.brand().is_none_or(str::is_empty) {
+                summary.without_brand += 1;
+            }
+            if product.barcode.as_deref().is_none_or(str::is_empty) {
+                summary.without_barcode += 1;
+            }
+            if !planned.contains(&product.id()) {
+                summary.unused_in_plans += 1;
+            }
+        }
+        summary
+    }
+}
+
+/// Ids of the products used in any saved plan of `db`, for databases without profiles.
+pub(crate) async fn planned_product_ids<D: MutableDatabase + ?Sized>(
+    db: &D,
+) -> Result<BTreeSet<ProductId>, String> {
+    let mut ids = BTreeSet::new();
+    for plan_id in db.get_plan_ids().await? {
+        if let Some(plan) = db.get_plan(&plan_id).await? {
+            ids.extend(plan.product_ids());
+        }
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bl::constraints_solver::{Fraction, Solution, SolutionEntry};
+    use crate::database_access::{
+        Database, DbSearchCriteria, MutableDatabase, mock_db::MockProductDb,
+    };
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_summary_over_mock_products() {
+        let db = MockProductDb::new();
+        let summary = block_on(db.data_quality_summary()).expect("summary should be computed");
+        assert_eq!(
+            summary,
+            DataQualitySummary {
+                total_products: 6,
+                without_micro_nutrients: 0,
+                incomplete_micro_nutrients: 6,
+                without_extra_units: 1,
+                without_brand: 2,
+                without_barcode: 6,
+                unused_in_plans: 6,
+            }
+        );
+
+        let stats = block_on(db.stats()).expect("stats should be computed");
+        assert_eq!(stats.quality, summary);
+        assert_eq!(stats.products_per_brand.values().sum::<u64>(), 4);
+        assert_eq!(stats.size_bytes, None);
+
+        let least_complete =
+            block_on(db.least_complete_products(3)).expect("products should be ranked");
+        assert_eq!(least_complete.len(), 3);
+        assert!(
+            least_complete
+                .windows(2)
+                .all(|pair| pair[0].1.score <= pair[1].1.score)
+        );
+    }
+
+    #[test]
+    fn test_summary_counts_barcodes_and_planned_products() {
+        let mut db = MockProductDb::new();
+        let (id, mut product) =
+            block_on(db.get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())]))
+                .pop_first()
+                .expect("mock should have products");
+        product.barcode = Some("4006381333931".to_string());
+        block_on(db.update_product(&id, product.clone())).expect("product should be updated");
+        let plan = Solution {
+            solution: SolutionEntry::Product {
+                product,
+                amount_grams: 100.0,
+                unit: AllowedUnitsType::Gram,
+                amount_unit: Fraction {
+                    numerator: 1,
+                    denominator: 1,
+                },
+            },
+        };
+        block_on(db.save_plan("week", &plan)).expect("plan should be saved");
+
+        let summary = block_on(db.data_quality_summary()).expect("summary should be computed");
+        assert_eq!(summary.without_barcode, 5);
+        assert_eq!(summary.unused_in_plans, 5);
+        let stats = block_on(db.stats()).expect("stats should be computed");
+        assert_eq!(stats.quality, summary);
+    }
+}
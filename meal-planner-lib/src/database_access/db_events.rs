@@ -0,0 +1,56 @@
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+
+use crate::data_types::ProductId;
+
+/// Change of a product, sent to subscribers after the change was saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbEvent {
+    Added(ProductId),
+    Updated(ProductId),
+    Deleted(ProductId),
+}
+
+/// Stream of [`DbEvent`]s returned by [`super::Database::subscribe`], dropping it unsubscribes.
+pub type DbEventStream = UnboundedReceiver<DbEvent>;
+
+/// Senders of every open [`DbEventStream`] of a database.
+#[derive(Default)]
+pub(crate) struct DbSubscribers {
+    senders: Vec<UnboundedSender<DbEvent>>,
+}
+
+impl DbSubscribers {
+    pub(crate) fn subscribe(&mut self) -> DbEventStream {
+        let (sender, receiver) = unbounded();
+        self.senders.push(sender);
+        receiver
+    }
+
+    /// Sends `events` to every subscriber, forgetting the ones whose stream was dropped.
+    pub(crate) fn notify(&mut self, events: impl IntoIterator<Item = DbEvent>) {
+        for event in events {
+            self.senders
+                .retain(|sender| sender.unbounded_send(event.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn dropped_streams_are_unsubscribed() {
+        let mut subscribers = DbSubscribers::default();
+        let mut stream = subscribers.subscribe();
+        drop(subscribers.subscribe());
+
+        subscribers.notify([DbEvent::Added("Rice".into())]);
+        assert_eq!(subscribers.senders.len(), 1);
+        assert_eq!(
+            futures::executor::block_on(stream.next()),
+            Some(DbEvent::Added("Rice".into()))
+        );
+    }
+}
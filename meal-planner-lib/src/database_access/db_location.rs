@@ -0,0 +1,239 @@
+//! Where local database files live on each platform.
+//!
+//! Bare file names such as [`LOCAL_DB_DEFAULT_FILE`](super::LOCAL_DB_DEFAULT_FILE) are placed in
+//! a per-user app data directory, so installed builds don't depend on the working directory.
+//! Paths with a directory are used as given. Databases that earlier versions kept in the working
+//! directory are moved to the app data directory the first time they are resolved.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::path::PathBuf;
+
+use once_cell::sync::OnceCell;
+
+/// Name of the app directory created inside the platform data directory.
+pub const APP_DIR_NAME: &str = "meal-planner";
+
+static DATA_DIR_OVERRIDE: OnceCell<PathBuf> = OnceCell::new();
+
+/// Uses `dir` instead of the platform data directory, e.g. a files directory handed over by the
+/// host app. Can be set once, before the first database is opened.
+pub fn set_app_data_dir(dir: PathBuf) -> Result<(), String> {
+    DATA_DIR_OVERRIDE
+        .set(dir)
+        .map_err(|dir| format!("App data directory is already set to {}", dir.display()))
+}
+
+/// Directory holding the app data, created if missing: `$XDG_DATA_HOME/meal-planner` on Linux,
+/// `~/Library/Application Support/meal-planner` on macOS, `%APPDATA%\meal-planner` on Windows and
+/// the app files directory on Android.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    let dir = match DATA_DIR_OVERRIDE.get() {
+        Some(dir) => dir.clone(),
+        None => platform_data_dir(|name| std::env::var_os(name))?,
+    };
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Cannot create app data directory {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Path opened for the local database `path`. Bare file names are placed in [`app_data_dir`],
+/// paths with a directory are kept. A bare file name found in the working directory, where
+/// earlier versions kept it, is moved to the app data directory unless one is already there.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resolve_db_path(path: &str) -> Result<String, String> {
+    if path.is_empty() {
+        return Err("Database path is empty".to_string());
+    }
+    if Path::new(path)
+        .parent()
+        .is_some_and(|p| !p.as_os_str().is_empty())
+    {
+        return Ok(path.to_string());
+    }
+    let resolved = app_data_dir()?.join(path);
+    move_legacy_db(Path::new(path), &resolved)?;
+    resolved
+        .into_os_string()
+        .into_string()
+        .map_err(|p| format!("Database path {} is not valid UTF-8", p.display()))
+}
+
+// sqlite keeps uncheckpointed changes next to the database, they move with it
+#[cfg(not(target_arch = "wasm32"))]
+const DB_SIDE_FILE_SUFFIXES: [&str; 3] = ["-wal", "-shm", "-journal"];
+
+/// Moves the database `legacy` with its side files to `target` when only `legacy` exists.
+/// Returns whether it was moved.
+#[cfg(not(target_arch = "wasm32"))]
+fn move_legacy_db(legacy: &Path, target: &Path) -> Result<bool, String> {
+    if target.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    let with_suffix = |path: &Path, suffix: &str| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    // side files first, the database is only seen as moved once they are next to it
+    for suffix in DB_SIDE_FILE_SUFFIXES {
+        let side_file = with_suffix(legacy, suffix);
+        if side_file.is_file() {
+            move_file(&side_file, &with_suffix(target, suffix))?;
+        }
+    }
+    move_file(legacy, target)?;
+    tracing::info!(
+        "Moved database {} to {}",
+        legacy.display(),
+        target.display()
+    );
+    Ok(true)
+}
+
+// renaming fails across file systems, the file is copied there instead
+#[cfg(not(target_arch = "wasm32"))]
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    std::fs::rename(from, to)
+        .or_else(|_| std::fs::copy(from, to).and_then(|_| std::fs::remove_file(from)))
+        .map_err(|e| {
+            format!(
+                "Cannot move database file {} to {}: {e}",
+                from.display(),
+                to.display()
+            )
+        })
+}
+
+/// On wasm databases are stored in the origin private file system, the path is the OPFS key.
+#[cfg(target_arch = "wasm32")]
+pub fn resolve_db_path(path: &str) -> Result<String, String> {
+    if path.is_empty() {
+        return Err("Database path is empty".to_string());
+    }
+    Ok(path.trim_start_matches('/').to_string())
+}
+
+#[cfg(target_os = "android")]
+fn platform_data_dir(_env: impl Fn(&str) -> Option<std::ffi::OsString>) -> Result<PathBuf, String> {
+    // the process of an app is named after its package, which names its private files directory
+    let cmdline = std::fs::read_to_string("/proc/self/cmdline").map_err(|e| e.to_string())?;
+    let package = cmdline
+        .split(['\0', ':'])
+        .next()
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "Cannot find the app package name".to_string())?;
+    Ok(Path::new("/data/data").join(package).join("files"))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_data_dir(env: impl Fn(&str) -> Option<std::ffi::OsString>) -> Result<PathBuf, String> {
+    env("APPDATA")
+        .map(|dir| PathBuf::from(dir).join(APP_DIR_NAME))
+        .ok_or_else(|| "APPDATA is not set".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn platform_data_dir(env: impl Fn(&str) -> Option<std::ffi::OsString>) -> Result<PathBuf, String> {
+    env("HOME")
+        .map(|home| {
+            PathBuf::from(home)
+                .join("Library/Application Support")
+                .join(APP_DIR_NAME)
+        })
+        .ok_or_else(|| "HOME is not set".to_string())
+}
+
+#[cfg(not(any(
+    target_arch = "wasm32",
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos"
+)))]
+fn platform_data_dir(env: impl Fn(&str) -> Option<std::ffi::OsString>) -> Result<PathBuf, String> {
+    // relative XDG paths are invalid and must be ignored
+    let data_home = env("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| "Neither XDG_DATA_HOME nor HOME is set".to_string())?;
+    Ok(data_home.join(APP_DIR_NAME))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    fn platform_data_dir_follows_xdg() {
+        assert_eq!(
+            platform_data_dir(env(&[("XDG_DATA_HOME", "/data"), ("HOME", "/home/u")])).unwrap(),
+            PathBuf::from("/data/meal-planner")
+        );
+        assert_eq!(
+            platform_data_dir(env(&[("XDG_DATA_HOME", "data"), ("HOME", "/home/u")])).unwrap(),
+            PathBuf::from("/home/u/.local/share/meal-planner")
+        );
+        assert!(platform_data_dir(env(&[])).is_err());
+    }
+
+    #[test]
+    fn paths_with_directory_are_kept() {
+        assert_eq!(
+            resolve_db_path("some/dir/db.sqlite3").unwrap(),
+            "some/dir/db.sqlite3"
+        );
+        assert_eq!(
+            resolve_db_path("/tmp/db.sqlite3").unwrap(),
+            "/tmp/db.sqlite3"
+        );
+        assert!(resolve_db_path("").is_err());
+    }
+
+    #[test]
+    fn legacy_db_is_moved_once() {
+        let dir = std::env::temp_dir().join(format!(
+            "meal_planner_legacy_db_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("Clock went backwards")
+                .as_nanos()
+        ));
+        let legacy_dir = dir.join("cwd");
+        let data_dir = dir.join("data");
+        std::fs::create_dir_all(&legacy_dir).expect("Failed to create working directory");
+        std::fs::create_dir_all(&data_dir).expect("Failed to create data directory");
+        let legacy = legacy_dir.join("local_db.sqlite3");
+        let target = data_dir.join("local_db.sqlite3");
+        std::fs::write(&legacy, "old").expect("Failed to write legacy database");
+        std::fs::write(legacy_dir.join("local_db.sqlite3-wal"), "wal")
+            .expect("Failed to write legacy wal");
+
+        assert_eq!(move_legacy_db(&legacy, &target), Ok(true));
+        assert!(!legacy.exists());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "old");
+        assert_eq!(
+            std::fs::read_to_string(data_dir.join("local_db.sqlite3-wal")).unwrap(),
+            "wal"
+        );
+
+        std::fs::write(&legacy, "stale").expect("Failed to write legacy database");
+        assert_eq!(move_legacy_db(&legacy, &target), Ok(false));
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "old");
+        assert!(legacy.exists());
+
+        std::fs::remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+}
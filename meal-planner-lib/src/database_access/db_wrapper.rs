@@ -1,13 +1,28 @@
 use core::panic;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::data_types::{Product, UnitData};
+use crate::data_types::{
+    ActivityLog, Allergen, AppSettings, BeverageEntry, CompletenessReport, DEFAULT_PROFILE_ID,
+    EatenItem, MacroElementsType, MealNote, NutrientType, Product, ProductEdit, ProductId,
+    ProductVersion, Profile, UnitData, UsageStats, UserProfile,
+};
 use async_trait::async_trait;
 
+use crate::bl::constraints_solver::Solution;
+use crate::bl::grocery_haul::{Pantry, ShoppingList};
+use crate::bl::targets_review::TargetsRecord;
+
+use super::data_quality::{DataQualitySummary, DatabaseStats};
+use super::db_events::DbEventStream;
+use super::db_location;
 use super::local_db;
 #[cfg(any(test, feature = "test-utils"))]
 use super::mock_db;
+use super::product_import::{
+    self, BulkReport, ConflictStrategy, ImportEntry, ImportOutcome, ImportReport,
+};
+use super::read_only_db::ReadOnlyDatabase;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DataBaseTypes {
@@ -15,6 +30,8 @@ pub enum DataBaseTypes {
     Mock,
     OpenFoodFacts,
     Local(String),
+    /// Local database opened without write access, mutations fail with [`PermissionDenied`](super::PermissionDenied).
+    LocalReadOnly(String),
 }
 
 impl DataBaseTypes {
@@ -29,15 +46,134 @@ impl DataBaseTypes {
     }
 }
 
+/// Name of the default local database, placed in the app data directory by [`get_db`].
 pub const LOCAL_DB_DEFAULT_FILE: &str = "local_db.sqlite3";
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DbSearchCriteria {
+    /// Products whose name starts with the text.
     ById(String),
-    // ByBarcode(String),
+    /// Product with exactly this barcode.
+    ByBarcode(String),
+    /// Products of exactly this brand.
+    ByBrand(String),
+    /// Amount per 100g is at least the given value, products with unknown amount don't match.
+    ByNutrientAtLeast(NutrientType, f32),
+    /// Amount per 100g is at most the given value, products with unknown amount don't match.
+    ByNutrientAtMost(NutrientType, f32),
+    /// Leaves out products containing any of the allergens.
+    ExcludeAllergens(Vec<Allergen>),
+    /// Leaves out imported products with estimated, incomplete or suspicious nutrient values,
+    /// see [`Product::has_verified_data`].
+    VerifiedDataOnly,
+}
+
+impl DbSearchCriteria {
+    /// Whether `product` satisfies the criterion, for databases filtering in memory.
+    #[must_use]
+    pub fn matches(&self, product: &Product) -> bool {
+        match self {
+            DbSearchCriteria::ById(name) => product.name().starts_with(name.as_str()),
+            DbSearchCriteria::ByBarcode(barcode) => product.barcode.as_ref() == Some(barcode),
+            DbSearchCriteria::ByBrand(brand) => product.brand() == Some(brand.as_str()),
+            DbSearchCriteria::ByNutrientAtLeast(nutrient, min) => product
+                .get_nutrient_amount(*nutrient)
+                .is_some_and(|amount| amount >= *min),
+            DbSearchCriteria::ByNutrientAtMost(nutrient, max) => product
+                .get_nutrient_amount(*nutrient)
+                .is_some_and(|amount| amount <= *max),
+            DbSearchCriteria::ExcludeAllergens(allergens) => {
+                !allergens.iter().any(|a| product.allergens.contains(a))
+            }
+            DbSearchCriteria::VerifiedDataOnly => product.has_verified_data(),
+        }
+    }
+}
+
+/// Order of [`Database::get_products_page`], ascending with ties ordered by id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SortKey {
+    #[default]
+    Name,
+    /// Products without a brand come first.
+    Brand,
+    /// Calories per 100g, from the label when overridden.
+    Calories,
+    /// Protein per 100g.
+    Protein,
+}
+
+impl SortKey {
+    fn compare(self, a: &Product, b: &Product) -> std::cmp::Ordering {
+        let amount = |product: &Product, nutrient: MacroElementsType| {
+            product
+                .get_nutrient_amount(NutrientType::Macro(nutrient))
+                .unwrap_or(0.0)
+        };
+        match self {
+            SortKey::Name => a.name().cmp(b.name()),
+            SortKey::Brand => a.brand().cmp(&b.brand()),
+            SortKey::Calories => amount(a, MacroElementsType::Calories)
+                .total_cmp(&amount(b, MacroElementsType::Calories)),
+            SortKey::Protein => amount(a, MacroElementsType::Protein)
+                .total_cmp(&amount(b, MacroElementsType::Protein)),
+        }
+    }
+}
+
+/// Window of `products` ordered by `sort`, for databases without SQL ordering.
+pub(crate) fn products_page(
+    products: impl Iterator<Item = (ProductId, Product)>,
+    sort: SortKey,
+    offset: usize,
+    limit: usize,
+) -> Vec<(ProductId, Product)> {
+    let mut products: Vec<_> = products.collect();
+    products.sort_by(|(a_id, a), (b_id, b)| sort.compare(a, b).then_with(|| a_id.cmp(b_id)));
+    products.into_iter().skip(offset).take(limit).collect()
 }
 
-/// Returns a database instance for the given type.
+/// Incomplete entries of `reports`, most missing micro nutrients first, ties ordered by id.
+pub(crate) fn least_complete(
+    reports: impl Iterator<Item = (ProductId, CompletenessReport)>,
+    limit: usize,
+) -> Vec<(ProductId, CompletenessReport)> {
+    let mut incomplete: Vec<_> = reports.filter(|(_, r)| !r.is_complete()).collect();
+    incomplete.sort_by(|(a_id, a), (b_id, b)| {
+        b.missing_micro_nutrients
+            .len()
+            .cmp(&a.missing_micro_nutrients.len())
+            .then_with(|| a_id.cmp(b_id))
+    });
+    incomplete.truncate(limit);
+    incomplete
+}
+
+// bare file names go to the app data directory, see `db_location`
+async fn open_local_db(db_path: &str) -> Option<local_db::LocalProductDb> {
+    match db_location::resolve_db_path(db_path) {
+        Ok(path) => local_db::LocalProductDb::shared(&path).await,
+        Err(e) => {
+            tracing::error!("Cannot open local database '{db_path}': {e}");
+            None
+        }
+    }
+}
+
+// like `open_local_db`, without writing to the file or sharing the writable handle
+async fn open_local_db_read_only(db_path: &str) -> Option<local_db::LocalProductDb> {
+    let opened = match db_location::resolve_db_path(db_path) {
+        Ok(path) => local_db::LocalProductDb::open_read_only(&path).await,
+        Err(e) => Err(e),
+    };
+    opened
+        .inspect_err(|e| tracing::error!("Cannot open local database '{db_path}' read-only: {e}"))
+        .ok()
+}
+
+/// Returns a database instance for the given type. A bare file name of
+/// [`DataBaseTypes::Local`] is opened in the app data directory, see
+/// [`resolve_db_path`](super::resolve_db_path).
 ///
 /// # Panics
 /// Panics if the database type is not supported in this build.
@@ -46,9 +182,10 @@ pub async fn get_db(db_type: DataBaseTypes) -> Option<Box<dyn Database>> {
     match db_type {
         #[cfg(any(test, feature = "test-utils"))]
         DataBaseTypes::Mock => Some(Box::new(mock_db::MockProductDb::new())),
-        DataBaseTypes::Local(db_path) => {
-            Some(Box::new(local_db::LocalProductDb::new(&db_path).await?))
-        }
+        DataBaseTypes::Local(db_path) => Some(Box::new(open_local_db(&db_path).await?)),
+        DataBaseTypes::LocalReadOnly(db_path) => Some(Box::new(ReadOnlyDatabase::new(
+            open_local_db_read_only(&db_path).await?,
+        ))),
         _ => panic!("Database type not supported in this build."),
         // DataBaseTypes::OpenFoodFactsDb => {
         //     Box::new(open_food_facts_wrapper::OpenFoodFactsDbWrapper::new())
@@ -56,7 +193,8 @@ pub async fn get_db(db_type: DataBaseTypes) -> Option<Box<dyn Database>> {
     }
 }
 
-/// Returns a mutable database instance for the given type.
+/// Returns a mutable database instance for the given type, opening local ones like [`get_db`].
+/// Mutations of a [`DataBaseTypes::LocalReadOnly`] one fail with [`PermissionDenied`](super::PermissionDenied).
 ///
 /// # Panics
 /// Panics if the database type is not mutable.
@@ -65,9 +203,10 @@ pub async fn get_mutable_db(db_type: DataBaseTypes) -> Option<Box<dyn MutableDat
     match db_type {
         #[cfg(any(test, feature = "test-utils"))]
         DataBaseTypes::Mock => Some(Box::new(mock_db::MockProductDb::new())),
-        DataBaseTypes::Local(db_path) => {
-            Some(Box::new(local_db::LocalProductDb::new(&db_path).await?))
-        }
+        DataBaseTypes::Local(db_path) => Some(Box::new(open_local_db(&db_path).await?)),
+        DataBaseTypes::LocalReadOnly(db_path) => Some(Box::new(ReadOnlyDatabase::new(
+            open_local_db_read_only(&db_path).await?,
+        ))),
         _ => panic!("Database type not mutable."),
     }
 }
@@ -87,7 +226,7 @@ pub trait Database {
     async fn get_products_matching_criteria(
         &self,
         criteria: &[DbSearchCriteria],
-    ) -> BTreeMap<String, crate::data_types::Product>;
+    ) -> BTreeMap<ProductId, crate::data_types::Product>;
 
     async fn set_product_unit(
         &mut self,
@@ -123,11 +262,83 @@ pub trait Database {
     }
 
     async fn get_product_by_id(&self, product_id: &str) -> Option<crate::data_types::Product> {
+        // names are searched, the brand part of the id is matched by the lookup below
+        let id = ProductId::from(product_id);
+        let (name, _) = id.parts();
         let mut results = self
-            .get_products_matching_criteria(&[DbSearchCriteria::ById(product_id.to_string())])
+            .get_products_matching_criteria(&[DbSearchCriteria::ById(name.to_string())])
             .await;
         results.remove(product_id)
     }
+
+    /// Counts products with missing data so users can see which entries need attention.
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        let products = self
+            .get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())])
+            .await;
+        // plans are saved in mutable databases only, they count their planned products
+        Ok(DataQualitySummary::from_products(
+            products.values(),
+            &BTreeSet::new(),
+        ))
+    }
+
+    /// Up to `limit` products with missing micro nutrients, least complete first, so the data
+    /// making micro nutrient constraints unreliable can be fixed first.
+    async fn least_complete_products(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, CompletenessReport)>, String> {
+        let products = self
+            .get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())])
+            .await;
+        Ok(least_complete(
+            products
+                .into_iter()
+                .map(|(id, product)| (id, product.completeness())),
+            limit,
+        ))
+    }
+
+    /// Up to `limit` products ordered by `sort`, skipping the first `offset`, so long product
+    /// lists can be loaded one window at a time.
+    async fn get_products_page(
+        &self,
+        sort: SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, Product)>, String> {
+        let products = self
+            .get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())])
+            .await;
+        Ok(products_page(products.into_iter(), sort, offset, limit))
+    }
+
+    /// Brands with the number of their products, e.g. for a brand filter. Products without a
+    /// brand are left out.
+    async fn list_brands(&self) -> Result<BTreeMap<String, u64>, String> {
+        let products = self
+            .get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())])
+            .await;
+        Ok(DatabaseStats::from_products(products.values()).products_per_brand)
+    }
+
+    /// Product counts, overall and per brand, missing data and size of the database.
+    async fn stats(&self) -> Result<DatabaseStats, String> {
+        let products = self
+            .get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())])
+            .await;
+        Ok(DatabaseStats {
+            quality: self.data_quality_summary().await?,
+            ..DatabaseStats::from_products(products.values())
+        })
+    }
+
+    /// Stream of product changes made through any handle of this database, so open views can
+    /// refresh themselves. Errors when the backend doesn't report its changes.
+    fn subscribe(&self) -> Result<DbEventStream, String> {
+        Err("This database does not report changes".to_string())
+    }
 }
 
 #[async_trait(?Send)]
@@ -139,6 +350,369 @@ pub trait MutableDatabase: Database {
     ) -> Result<(), String>;
     async fn update_product(&mut self, product_id: &str, product: Product) -> Result<(), String>;
     async fn delete_product(&mut self, product_id: &str) -> Result<(), String>;
+
+    /// Adds many products at once. A product failing to be added doesn't stop the others, the
+    /// outcome of each one is in the returned [`BulkReport`]. `Err` means nothing was added.
+    async fn add_products_bulk(
+        &mut self,
+        products: Vec<(String, Product)>,
+    ) -> Result<BulkReport, String> {
+        let mut report = BulkReport::default();
+        for (product_id, product) in products {
+            let result = self.add_product(&product_id, product).await;
+            report.entries.push((product_id, result));
+        }
+        Ok(report)
+    }
+
+    /// Deletes every product in `product_ids`, in one transaction where the backend supports it.
+    /// Ids without a product are skipped.
+    async fn delete_products(&mut self, product_ids: &[String]) -> Result<(), String> {
+        for product_id in product_ids {
+            self.delete_product(product_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Applies `edit` to every product in `product_ids`, in one transaction where the backend
+    /// supports it. Fails without changes when any of the products doesn't exist.
+    async fn edit_products(
+        &mut self,
+        product_ids: &[String],
+        edit: &ProductEdit,
+    ) -> Result<(), String> {
+        let mut edited = Vec::with_capacity(product_ids.len());
+        for product_id in product_ids {
+            let mut product = self
+                .get_product_by_id(product_id)
+                .await
+                .ok_or_else(|| format!("Product '{product_id}' not found"))?;
+            edit.apply(&mut product);
+            edited.push((product_id, product));
+        }
+        for (product_id, product) in edited {
+            self.update_product(product_id, product).await?;
+        }
+        Ok(())
+    }
+
+    /// Versions of a product replaced by [`Self::update_product`], newest first.
+    async fn get_product_history(&self, _product_id: &str) -> Result<Vec<ProductVersion>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Replaces the product stored as `product_id` with `product` saved under its own id. Backends
+    /// keeping a stable uuid carry it over and move the history, logs and pantry stock of the
+    /// product to the new id, so nothing referencing it is orphaned.
+    async fn rename_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        if self.get_product_by_id(product_id).await.is_none() {
+            return Err(format!("Product '{product_id}' not found"));
+        }
+        self.add_product(&product.id(), product).await?;
+        self.delete_product(product_id).await
+    }
+
+    /// Uuid of the product, kept when the product is renamed. `None` when the product doesn't
+    /// exist or the backend has no uuids.
+    async fn get_product_uuid(&self, _product_id: &str) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    /// Product currently holding `uuid`, whatever its name is now.
+    async fn get_product_by_uuid(&self, _uuid: &str) -> Result<Option<Product>, String> {
+        Ok(None)
+    }
+
+    /// Profiles sharing the database, the default one always exists.
+    async fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        Ok(vec![Profile::default_profile()])
+    }
+    /// Adds a profile, or renames it when the id is already used.
+    async fn set_profile(&mut self, profile: &Profile) -> Result<(), String> {
+        Err(format!(
+            "Cannot save profile '{}': profiles not supported",
+            profile.id
+        ))
+    }
+    /// Deletes a profile with all of its data. The default profile and profiles active in any
+    /// handle of the database stay.
+    async fn delete_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        Err(format!(
+            "Cannot delete profile '{profile_id}': profiles not supported"
+        ))
+    }
+    /// Id of the profile whose data is read and written, [`DEFAULT_PROFILE_ID`] until changed.
+    async fn active_profile(&self) -> Result<String, String> {
+        Ok(DEFAULT_PROFILE_ID.to_string())
+    }
+    /// Switches the profile whose data is read and written, e.g. [`Self::get_user_profile`].
+    async fn set_active_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        if profile_id == DEFAULT_PROFILE_ID {
+            Ok(())
+        } else {
+            Err(format!("Profile '{profile_id}' not found"))
+        }
+    }
+
+    /// Returns the user profile of the active profile, `None` until the user enters one.
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String>;
+    async fn set_user_profile(&mut self, profile: &UserProfile) -> Result<(), String>;
+
+    /// What the nutrition targets of the active profile were last computed from, `None` before
+    /// the first calculation.
+    async fn get_targets_record(&self) -> Result<Option<TargetsRecord>, String> {
+        Ok(None)
+    }
+    async fn set_targets_record(&mut self, _record: &TargetsRecord) -> Result<(), String> {
+        Err("Cannot save targets record: targets records not supported".to_string())
+    }
+
+    /// Logs a drink of the active profile.
+    async fn add_beverage_entry(&mut self, entry: &BeverageEntry) -> Result<(), String> {
+        Err(format!(
+            "Cannot log {} ml drink: beverage log not supported",
+            entry.volume_ml
+        ))
+    }
+    /// Drinks of the active profile drunk at `from..to` (Unix seconds), oldest first.
+    async fn get_beverage_entries(
+        &self,
+        _from: u64,
+        _to: u64,
+    ) -> Result<Vec<BeverageEntry>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Logs a workout of the active profile.
+    async fn add_activity(&mut self, activity: &ActivityLog) -> Result<(), String> {
+        Err(format!(
+            "Cannot log {} kcal activity: activity log not supported",
+            activity.kcal_burned
+        ))
+    }
+    /// Workouts of the active profile started at `from..to` (Unix seconds), oldest first.
+    async fn get_activities(&self, _from: u64, _to: u64) -> Result<Vec<ActivityLog>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Products the active profile marked as eaten on `day` (days since the Unix epoch).
+    async fn get_eaten(&self, _day: u64) -> Result<Vec<EatenItem>, String> {
+        Ok(Vec::new())
+    }
+    /// Marks a product of the active profile as eaten, replacing the amount when already marked.
+    async fn mark_eaten(&mut self, _day: u64, item: &EatenItem) -> Result<(), String> {
+        Err(format!(
+            "Cannot mark '{}' as eaten: meal tracking not supported",
+            item.product_id
+        ))
+    }
+    async fn unmark_eaten(
+        &mut self,
+        _day: u64,
+        meal: &str,
+        product_id: &str,
+    ) -> Result<(), String> {
+        Err(format!(
+            "Cannot unmark '{product_id}' of '{meal}': meal tracking not supported"
+        ))
+    }
+
+    /// Notes of the active profile on how the meals of `day` turned out.
+    async fn get_meal_notes(&self, _day: u64) -> Result<Vec<MealNote>, String> {
+        Ok(Vec::new())
+    }
+    /// Replaces the note on `note.meal` of `day`, an empty note removes it.
+    async fn set_meal_note(&mut self, _day: u64, note: &MealNote) -> Result<(), String> {
+        Err(format!(
+            "Cannot save note of '{}': meal notes not supported",
+            note.meal
+        ))
+    }
+
+    /// IDs of the shopping lists of the active profile, in the order they were first saved.
+    async fn get_shopping_list_ids(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+    async fn get_shopping_list(&self, _list_id: &str) -> Result<Option<ShoppingList>, String> {
+        Ok(None)
+    }
+    /// Stores `list` under `list_id` of the active profile, replacing the one saved before.
+    async fn save_shopping_list(
+        &mut self,
+        list_id: &str,
+        _list: &ShoppingList,
+    ) -> Result<(), String> {
+        Err(format!(
+            "Cannot save shopping list '{list_id}': shopping lists not supported"
+        ))
+    }
+    async fn delete_shopping_list(&mut self, list_id: &str) -> Result<(), String> {
+        Err(format!(
+            "Cannot delete shopping list '{list_id}': shopping lists not supported"
+        ))
+    }
+
+    /// Products at home of the active profile with their expiry dates, empty until saved.
+    async fn get_pantry(&self) -> Result<Pantry, String> {
+        Ok(Pantry::new())
+    }
+    /// Replaces the stored pantry of the active profile with `pantry`.
+    async fn save_pantry(&mut self, _pantry: &Pantry) -> Result<(), String> {
+        Err("Cannot save pantry: pantry not supported".to_string())
+    }
+
+    /// IDs of the saved plans of the active profile, in the order they were first saved.
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+    async fn get_plan(&self, _plan_id: &str) -> Result<Option<Solution>, String> {
+        Ok(None)
+    }
+    /// Stores `plan` under `plan_id` of the active profile, replacing the one saved before.
+    async fn save_plan(&mut self, plan_id: &str, _plan: &Solution) -> Result<(), String> {
+        Err(format!(
+            "Cannot save plan '{plan_id}': saved plans not supported"
+        ))
+    }
+    async fn delete_plan(&mut self, plan_id: &str) -> Result<(), String> {
+        Err(format!(
+            "Cannot delete plan '{plan_id}': saved plans not supported"
+        ))
+    }
+    /// Moves `meal` of `day` in the saved plan to position `new_index` among the meals of the
+    /// day, see [`Solution::reorder_meal`].
+    async fn reorder_meal(
+        &mut self,
+        plan_id: &str,
+        day: &str,
+        meal: &str,
+        new_index: usize,
+    ) -> Result<(), String> {
+        let mut plan = self
+            .get_plan(plan_id)
+            .await?
+            .ok_or_else(|| format!("Plan '{plan_id}' not found"))?;
+        plan.reorder_meal(day, meal, new_index)?;
+        self.save_plan(plan_id, &plan).await
+    }
+    /// Latest plan saved with `fingerprint`, see
+    /// [`solve_day_cached`](crate::bl::background_solver::solve_day_cached).
+    async fn find_solved_plan(&self, _fingerprint: u64) -> Result<Option<Solution>, String> {
+        Ok(None)
+    }
+    /// Saves `plan` like [`MutableDatabase::save_plan`], remembering the `fingerprint` of what it
+    /// was solved from. Backends without fingerprints just save the plan.
+    async fn save_solved_plan(
+        &mut self,
+        plan_id: &str,
+        _fingerprint: u64,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        self.save_plan(plan_id, plan).await
+    }
+
+    /// Returns the stored app settings, defaults until they are first saved.
+    async fn get_app_settings(&self) -> Result<AppSettings, String> {
+        Ok(AppSettings::default())
+    }
+    async fn set_app_settings(&mut self, _settings: &AppSettings) -> Result<(), String> {
+        Err("Saving app settings not supported".to_string())
+    }
+
+    /// Returns the stored usage stats, disabled and empty until the user opts in.
+    async fn get_usage_stats(&self) -> Result<UsageStats, String>;
+    /// Replaces the stored usage stats, e.g. after recording an event or wiping them.
+    async fn set_usage_stats(&mut self, stats: &UsageStats) -> Result<(), String>;
+
+    /// Imports products from JSON (serialized `Product` array) or CSV text.
+    ///
+    /// Fails only when the whole document cannot be read; problems with single products are
+    /// reported per entry in the returned [`ImportReport`].
+    async fn import_products(
+        &mut self,
+        data: &str,
+        strategy: ConflictStrategy,
+    ) -> Result<ImportReport, String> {
+        let mut report = ImportReport::default();
+        // new products are added in one go at the end, with the indices of the report entries
+        // folded into them
+        let mut new_products: Vec<(String, Product)> = Vec::new();
+        let mut new_entries: Vec<Vec<usize>> = Vec::new();
+        for (product_id, parsed) in product_import::parse_products(data)? {
+            let product =
+                match parsed.and_then(|p| product_import::validate_product(&p).map(|()| p)) {
+                    Ok(product) => product,
+                    Err(e) => {
+                        report.entries.push(ImportEntry {
+                            product_id,
+                            outcome: ImportOutcome::Failed(e),
+                        });
+                        continue;
+                    }
+                };
+
+            // repeated in the same import, so not in the database yet
+            if let Some(pos) = new_products.iter().position(|(id, _)| *id == product_id) {
+                let pending = &mut new_products[pos].1;
+                let outcome = match strategy {
+                    ConflictStrategy::Skip => ImportOutcome::Skipped,
+                    ConflictStrategy::Overwrite => {
+                        *pending = product;
+                        ImportOutcome::Overwritten
+                    }
+                    ConflictStrategy::Merge => {
+                        *pending = product_import::merge_products(pending, product);
+                        ImportOutcome::Merged
+                    }
+                };
+                // skipped entries aren't written, the bulk add can't fail them
+                if strategy != ConflictStrategy::Skip {
+                    new_entries[pos].push(report.entries.len());
+                }
+                report.entries.push(ImportEntry {
+                    product_id,
+                    outcome,
+                });
+                continue;
+            }
+
+            let result = match (self.get_product_by_id(&product_id).await, strategy) {
+                (None, _) => {
+                    new_entries.push(vec![report.entries.len()]);
+                    new_products.push((product_id.clone(), product));
+                    Ok(ImportOutcome::Added)
+                }
+                (Some(_), ConflictStrategy::Skip) => Ok(ImportOutcome::Skipped),
+                (Some(_), ConflictStrategy::Overwrite) => self
+                    .update_product(&product_id, product)
+                    .await
+                    .map(|()| ImportOutcome::Overwritten),
+                (Some(existing), ConflictStrategy::Merge) => {
+                    let merged = product_import::merge_products(&existing, product);
+                    self.update_product(&product_id, merged)
+                        .await
+                        .map(|()| ImportOutcome::Merged)
+                }
+            };
+            report.entries.push(ImportEntry {
+                product_id,
+                outcome: result.unwrap_or_else(ImportOutcome::Failed),
+            });
+        }
+
+        let added: Vec<Result<(), String>> = match self.add_products_bulk(new_products).await {
+            Ok(bulk) => bulk.entries.into_iter().map(|(_, result)| result).collect(),
+            Err(e) => vec![Err(e); new_entries.len()],
+        };
+        for (indices, result) in new_entries.into_iter().zip(added) {
+            if let Err(e) = result {
+                for idx in indices {
+                    report.entries[idx].outcome = ImportOutcome::Failed(e.clone());
+                }
+            }
+        }
+        Ok(report)
+    }
 }
 
 #[cfg(test)]
@@ -159,19 +733,12 @@ mod dbwrapper_trait_default_impl_tests {
         async fn get_products_matching_criteria(
             &self,
             criteria: &[DbSearchCriteria],
-        ) -> BTreeMap<String, Product> {
-            // Only ById supported for this dummy
-            let mut map = BTreeMap::new();
-            for crit in criteria {
-                // TODO: wont be necessary when there are more different criteria
-                #[allow(irrefutable_let_patterns)]
-                if let DbSearchCriteria::ById(name) = crit
-                    && let Some(prod) = self.products.get(name)
-                {
-                    map.insert(name.clone(), prod.clone());
-                }
-            }
-            map
+        ) -> BTreeMap<ProductId, Product> {
+            self.products
+                .values()
+                .filter(|product| criteria.iter().all(|crit| crit.matches(product)))
+                .map(|product| (product.id(), product.clone()))
+                .collect()
         }
 
         async fn set_product_unit(
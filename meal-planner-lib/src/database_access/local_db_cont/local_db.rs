@@ -1,7 +1,21 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
-use crate::data_types::{AllowedUnitsType, Product, UnitData};
-use crate::database_access::{Database, DbSearchCriteria, MutableDatabase};
+use futures::lock::{Mutex, MutexGuard};
+
+use crate::bl::constraints_solver::Solution;
+use crate::bl::grocery_haul::{Pantry, ShoppingList};
+use crate::bl::targets_review::TargetsRecord;
+use crate::data_types::{
+    ActivityLog, AllowedUnitsType, AppSettings, BeverageEntry, CompletenessReport,
+    DEFAULT_PROFILE_ID, EatenItem, MealNote, Product, ProductEdit, ProductId, ProductVersion,
+    Profile, UnitData, UsageStats, UserProfile,
+};
+use crate::database_access::{
+    BulkReport, DataQualitySummary, Database, DatabaseStats, DbEvent, DbEventStream,
+    DbSearchCriteria, MutableDatabase, SortKey, db_events::DbSubscribers,
+    product_import::validate_product,
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::database_access::local_db_cont::local_db_generic;
@@ -14,16 +28,195 @@ use local_db_generic::LocalProductDbConcrete;
 #[cfg(target_arch = "wasm32")]
 use local_db_wasm::LocalProductDbConcrete;
 
+/// Handle to a local database, clones share the same connection and change subscribers.
+/// Each handle reads and writes the data of its own active profile.
 pub struct LocalProductDb {
-    inner: LocalProductDbConcrete,
+    inner: Arc<Mutex<LocalProductDbConcrete>>,
+    subscribers: Arc<std::sync::Mutex<DbSubscribers>>,
+    active_profile: String,
+    // number of handles sharing the connection with each profile active
+    profiles_in_use: Arc<std::sync::Mutex<BTreeMap<String, usize>>>,
+}
+
+impl Clone for LocalProductDb {
+    fn clone(&self) -> Self {
+        self.hold_profile(&self.active_profile);
+        Self {
+            inner: Arc::clone(&self.inner),
+            subscribers: Arc::clone(&self.subscribers),
+            active_profile: self.active_profile.clone(),
+            profiles_in_use: Arc::clone(&self.profiles_in_use),
+        }
+    }
+}
+
+impl Drop for LocalProductDb {
+    fn drop(&mut self) {
+        self.release_profile(&self.active_profile);
+    }
+}
+
+// one handle per database file, reused by every `shared` call
+#[cfg(not(target_arch = "wasm32"))]
+static SHARED_DBS: std::sync::Mutex<BTreeMap<String, LocalProductDb>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static SHARED_DBS: std::cell::RefCell<BTreeMap<String, LocalProductDb>> =
+        const { std::cell::RefCell::new(BTreeMap::new()) };
 }
 
 impl LocalProductDb {
-    /// Creates a new local database instance backed by `SQLite`.
+    /// Creates a new local database instance backed by `SQLite`, `None` when it can't be opened.
     pub async fn new(database_file: &str) -> Option<Self> {
-        LocalProductDbConcrete::new(database_file)
+        Self::open(database_file)
             .await
-            .map(|inner| Self { inner })
+            .inspect_err(|e| tracing::error!("Failed to open '{database_file}': {e}"))
+            .ok()
+    }
+
+    /// Same as [`Self::new`] but tells why the database can't be opened, e.g. [`NewerSchema`]
+    /// for one written by a newer version of the app.
+    ///
+    /// [`NewerSchema`]: crate::database_access::NewerSchema
+    // the wasm database is single threaded, the Arc only makes the handle clonable
+    #[cfg_attr(target_arch = "wasm32", allow(clippy::arc_with_non_send_sync))]
+    pub async fn open(database_file: &str) -> Result<Self, String> {
+        LocalProductDbConcrete::open(database_file)
+            .await
+            .map(Self::from_concrete)
+    }
+
+    // the wasm database is single threaded, the Arc only makes the handle clonable
+    #[cfg_attr(target_arch = "wasm32", allow(clippy::arc_with_non_send_sync))]
+    fn from_concrete(inner: LocalProductDbConcrete) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+            subscribers: Arc::default(),
+            active_profile: DEFAULT_PROFILE_ID.to_string(),
+            profiles_in_use: Arc::new(std::sync::Mutex::new(BTreeMap::from([(
+                DEFAULT_PROFILE_ID.to_string(),
+                1,
+            )]))),
+        }
+    }
+
+    fn hold_profile(&self, profile_id: &str) {
+        if let Ok(mut in_use) = self.profiles_in_use.lock() {
+            *in_use.entry(profile_id.to_string()).or_default() += 1;
+        }
+    }
+
+    fn release_profile(&self, profile_id: &str) {
+        if let Ok(mut in_use) = self.profiles_in_use.lock()
+            && let Some(count) = in_use.get_mut(profile_id)
+        {
+            *count -= 1;
+            if *count == 0 {
+                in_use.remove(profile_id);
+            }
+        }
+    }
+
+    fn switch_profile(&mut self, profile_id: &str) {
+        self.hold_profile(profile_id);
+        self.release_profile(&self.active_profile);
+        self.active_profile = profile_id.to_string();
+    }
+
+    fn profile_in_use(&self, profile_id: &str) -> bool {
+        self.profiles_in_use
+            .lock()
+            .map_or(true, |in_use| in_use.contains_key(profile_id))
+    }
+
+    // the connection is shared, so profile scoped queries run for the profile of this handle
+    async fn lock(&self) -> MutexGuard<'_, LocalProductDbConcrete> {
+        let mut inner = self.inner.lock().await;
+        inner.use_profile(&self.active_profile);
+        inner
+    }
+
+    /// Opens an existing database without writing to it, see
+    /// [`LocalProductDbConcrete::open_read_only`]. The handle doesn't share the connection of
+    /// [`Self::shared`] ones.
+    pub async fn open_read_only(database_file: &str) -> Result<Self, String> {
+        LocalProductDbConcrete::open_read_only(database_file)
+            .await
+            .map(Self::from_concrete)
+    }
+
+    fn notify(&self, events: impl IntoIterator<Item = DbEvent>) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.notify(events);
+        }
+    }
+
+    /// Copies a consistent snapshot of the database to `path`, e.g. before an app update.
+    /// On the web `path` names a file in the origin private file system.
+    pub async fn backup_to(&self, path: &str) -> Result<(), String> {
+        self.lock().await.backup_to(path).await
+    }
+
+    /// Replaces the whole database with a backup made by [`Self::backup_to`], once it passes
+    /// the integrity check. Every handle sharing the connection sees the restored data, this one
+    /// switches to the default profile.
+    pub async fn restore_from(&mut self, path: &str) -> Result<(), String> {
+        self.lock().await.restore_from(path).await?;
+        self.switch_profile(DEFAULT_PROFILE_ID);
+        Ok(())
+    }
+
+    /// Runs `PRAGMA integrity_check`, the error lists every problem found.
+    pub async fn integrity_check(&self) -> Result<(), String> {
+        self.lock().await.integrity_check().await
+    }
+
+    #[cfg(test)]
+    pub(crate) fn shares_connection_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the handle shared by all users of `database_file`, opening it on first use.
+    pub async fn shared(database_file: &str) -> Option<Self> {
+        if let Some(db) = Self::cached(database_file) {
+            return Some(db);
+        }
+        let db = Self::new(database_file).await?;
+        // another task may have opened the file meanwhile, keep the first handle
+        Some(Self::cache(database_file, db))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cached(database_file: &str) -> Option<Self> {
+        SHARED_DBS
+            .lock()
+            .ok()
+            .and_then(|dbs| dbs.get(database_file).cloned())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cache(database_file: &str, db: Self) -> Self {
+        match SHARED_DBS.lock() {
+            Ok(mut dbs) => dbs.entry(database_file.to_string()).or_insert(db).clone(),
+            Err(_) => db,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn cached(database_file: &str) -> Option<Self> {
+        SHARED_DBS.with(|dbs| dbs.borrow().get(database_file).cloned())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn cache(database_file: &str, db: Self) -> Self {
+        SHARED_DBS.with(|dbs| {
+            dbs.borrow_mut()
+                .entry(database_file.to_string())
+                .or_insert(db)
+                .clone()
+        })
     }
 }
 
@@ -32,8 +225,11 @@ impl Database for LocalProductDb {
     async fn get_products_matching_criteria(
         &self,
         criteria: &[DbSearchCriteria],
-    ) -> BTreeMap<String, Product> {
-        self.inner.get_products_matching_criteria(criteria).await
+    ) -> BTreeMap<ProductId, Product> {
+        self.lock()
+            .await
+            .get_products_matching_criteria(criteria)
+            .await
     }
 
     async fn set_product_unit(
@@ -42,23 +238,329 @@ impl Database for LocalProductDb {
         allowed_unit: AllowedUnitsType,
         unit_data: UnitData,
     ) -> Result<(), String> {
-        self.inner
+        self.lock()
+            .await
             .set_product_unit(product_id, allowed_unit, unit_data)
+            .await?;
+        self.notify([DbEvent::Updated(product_id.into())]);
+        Ok(())
+    }
+
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        self.lock().await.data_quality_summary().await
+    }
+
+    async fn least_complete_products(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, CompletenessReport)>, String> {
+        self.lock().await.least_complete_products(limit).await
+    }
+
+    async fn get_products_page(
+        &self,
+        sort: SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, Product)>, String> {
+        self.lock()
             .await
+            .get_products_page(sort, offset, limit)
+            .await
+    }
+
+    async fn list_brands(&self) -> Result<BTreeMap<String, u64>, String> {
+        self.lock().await.list_brands().await
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats, String> {
+        self.lock().await.stats().await
+    }
+
+    fn subscribe(&self) -> Result<DbEventStream, String> {
+        self.subscribers
+            .lock()
+            .map(|mut subscribers| subscribers.subscribe())
+            .map_err(|e| format!("Failed to subscribe to database changes: {e}"))
     }
 }
 
 #[async_trait::async_trait(?Send)]
 impl MutableDatabase for LocalProductDb {
     async fn add_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
-        self.inner.add_product(product_id, product).await
+        validate_product(&product)?;
+        self.lock().await.add_product(product_id, product).await?;
+        self.notify([DbEvent::Added(product_id.into())]);
+        Ok(())
     }
 
     async fn update_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
-        self.inner.update_product(product_id, product).await
+        validate_product(&product)?;
+        self.lock()
+            .await
+            .update_product(product_id, product)
+            .await?;
+        self.notify([DbEvent::Updated(product_id.into())]);
+        Ok(())
     }
 
     async fn delete_product(&mut self, product_id: &str) -> Result<(), String> {
-        self.inner.delete_product(product_id).await
+        self.lock().await.delete_product(product_id).await?;
+        self.notify([DbEvent::Deleted(product_id.into())]);
+        Ok(())
+    }
+
+    async fn add_products_bulk(
+        &mut self,
+        products: Vec<(String, Product)>,
+    ) -> Result<BulkReport, String> {
+        let mut entries = Vec::with_capacity(products.len());
+        let mut valid = Vec::new();
+        for (product_id, product) in products {
+            let validation = validate_product(&product);
+            if validation.is_ok() {
+                valid.push((product_id.clone(), product));
+            }
+            entries.push((product_id, validation));
+        }
+        // entries of valid products take the database result, keeping the input order
+        let mut added = self
+            .lock()
+            .await
+            .add_products_bulk(valid)
+            .await?
+            .entries
+            .into_iter();
+        for (_, result) in &mut entries {
+            if result.is_ok()
+                && let Some((_, added_result)) = added.next()
+            {
+                *result = added_result;
+            }
+        }
+        self.notify(
+            entries
+                .iter()
+                .filter(|(_, result)| result.is_ok())
+                .map(|(product_id, _)| DbEvent::Added(product_id.as_str().into())),
+        );
+        Ok(BulkReport { entries })
+    }
+
+    async fn delete_products(&mut self, product_ids: &[String]) -> Result<(), String> {
+        self.lock().await.delete_products(product_ids).await?;
+        self.notify(
+            product_ids
+                .iter()
+                .map(|product_id| DbEvent::Deleted(product_id.as_str().into())),
+        );
+        Ok(())
+    }
+
+    async fn edit_products(
+        &mut self,
+        product_ids: &[String],
+        edit: &ProductEdit,
+    ) -> Result<(), String> {
+        edit.validate()?;
+        self.lock().await.edit_products(product_ids, edit).await?;
+        self.notify(
+            product_ids
+                .iter()
+                .map(|product_id| DbEvent::Updated(product_id.as_str().into())),
+        );
+        Ok(())
+    }
+
+    async fn get_product_history(&self, product_id: &str) -> Result<Vec<ProductVersion>, String> {
+        self.lock().await.get_product_history(product_id).await
+    }
+
+    async fn rename_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        validate_product(&product)?;
+        let new_id = product.id();
+        self.lock()
+            .await
+            .rename_product(product_id, product)
+            .await?;
+        self.notify([DbEvent::Deleted(product_id.into()), DbEvent::Added(new_id)]);
+        Ok(())
+    }
+
+    async fn get_product_uuid(&self, product_id: &str) -> Result<Option<String>, String> {
+        self.lock().await.get_product_uuid(product_id).await
+    }
+
+    async fn get_product_by_uuid(&self, uuid: &str) -> Result<Option<Product>, String> {
+        self.lock().await.get_product_by_uuid(uuid).await
+    }
+
+    async fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        self.lock().await.get_profiles().await
+    }
+
+    async fn set_profile(&mut self, profile: &Profile) -> Result<(), String> {
+        self.lock().await.set_profile(profile).await
+    }
+
+    async fn delete_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        if profile_id == DEFAULT_PROFILE_ID || self.profile_in_use(profile_id) {
+            return Err(format!(
+                "Cannot delete profile '{profile_id}': it is the default one or still active"
+            ));
+        }
+        self.lock().await.delete_profile(profile_id).await
+    }
+
+    async fn active_profile(&self) -> Result<String, String> {
+        Ok(self.active_profile.clone())
+    }
+
+    async fn set_active_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        self.lock().await.set_active_profile(profile_id).await?;
+        self.switch_profile(profile_id);
+        Ok(())
+    }
+
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String> {
+        self.lock().await.get_user_profile().await
+    }
+
+    async fn set_user_profile(&mut self, profile: &UserProfile) -> Result<(), String> {
+        self.lock().await.set_user_profile(profile).await
+    }
+
+    async fn get_targets_record(&self) -> Result<Option<TargetsRecord>, String> {
+        self.lock().await.get_targets_record().await
+    }
+
+    async fn set_targets_record(&mut self, record: &TargetsRecord) -> Result<(), String> {
+        self.lock().await.set_targets_record(record).await
+    }
+
+    async fn add_beverage_entry(&mut self, entry: &BeverageEntry) -> Result<(), String> {
+        entry.validate()?;
+        self.lock().await.add_beverage_entry(entry).await
+    }
+
+    async fn get_beverage_entries(&self, from: u64, to: u64) -> Result<Vec<BeverageEntry>, String> {
+        self.lock().await.get_beverage_entries(from, to).await
+    }
+
+    async fn add_activity(&mut self, activity: &ActivityLog) -> Result<(), String> {
+        activity.validate()?;
+        self.lock().await.add_activity(activity).await
+    }
+
+    async fn get_activities(&self, from: u64, to: u64) -> Result<Vec<ActivityLog>, String> {
+        self.lock().await.get_activities(from, to).await
+    }
+
+    async fn get_eaten(&self, day: u64) -> Result<Vec<EatenItem>, String> {
+        self.lock().await.get_eaten(day).await
+    }
+
+    async fn mark_eaten(&mut self, day: u64, item: &EatenItem) -> Result<(), String> {
+        item.validate()?;
+        self.lock().await.mark_eaten(day, item).await
+    }
+
+    async fn unmark_eaten(&mut self, day: u64, meal: &str, product_id: &str) -> Result<(), String> {
+        self.lock().await.unmark_eaten(day, meal, product_id).await
+    }
+
+    async fn get_meal_notes(&self, day: u64) -> Result<Vec<MealNote>, String> {
+        self.lock().await.get_meal_notes(day).await
+    }
+
+    async fn set_meal_note(&mut self, day: u64, note: &MealNote) -> Result<(), String> {
+        note.validate()?;
+        self.lock().await.set_meal_note(day, note).await
+    }
+
+    async fn get_shopping_list_ids(&self) -> Result<Vec<String>, String> {
+        self.lock().await.get_shopping_list_ids().await
+    }
+
+    async fn get_shopping_list(&self, list_id: &str) -> Result<Option<ShoppingList>, String> {
+        self.lock().await.get_shopping_list(list_id).await
+    }
+
+    async fn save_shopping_list(
+        &mut self,
+        list_id: &str,
+        list: &ShoppingList,
+    ) -> Result<(), String> {
+        if list_id.trim().is_empty() {
+            return Err("Shopping list ID is empty".to_string());
+        }
+        self.lock().await.save_shopping_list(list_id, list).await
+    }
+
+    async fn delete_shopping_list(&mut self, list_id: &str) -> Result<(), String> {
+        self.lock().await.delete_shopping_list(list_id).await
+    }
+
+    async fn get_pantry(&self) -> Result<Pantry, String> {
+        self.lock().await.get_pantry().await
+    }
+
+    async fn save_pantry(&mut self, pantry: &Pantry) -> Result<(), String> {
+        self.lock().await.save_pantry(pantry).await
+    }
+
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        self.lock().await.get_plan_ids().await
+    }
+
+    async fn get_plan(&self, plan_id: &str) -> Result<Option<Solution>, String> {
+        self.lock().await.get_plan(plan_id).await
+    }
+
+    async fn save_plan(&mut self, plan_id: &str, plan: &Solution) -> Result<(), String> {
+        if plan_id.trim().is_empty() {
+            return Err("Plan ID is empty".to_string());
+        }
+        self.lock().await.save_plan(plan_id, plan).await
+    }
+
+    async fn delete_plan(&mut self, plan_id: &str) -> Result<(), String> {
+        self.lock().await.delete_plan(plan_id).await
+    }
+
+    async fn find_solved_plan(&self, fingerprint: u64) -> Result<Option<Solution>, String> {
+        self.lock().await.find_solved_plan(fingerprint).await
+    }
+
+    async fn save_solved_plan(
+        &mut self,
+        plan_id: &str,
+        fingerprint: u64,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        if plan_id.trim().is_empty() {
+            return Err("Plan ID is empty".to_string());
+        }
+        self.lock()
+            .await
+            .save_solved_plan(plan_id, fingerprint, plan)
+            .await
+    }
+
+    async fn get_app_settings(&self) -> Result<AppSettings, String> {
+        self.lock().await.get_app_settings().await
+    }
+
+    async fn set_app_settings(&mut self, settings: &AppSettings) -> Result<(), String> {
+        settings.validate()?;
+        self.lock().await.set_app_settings(settings).await
+    }
+
+    async fn get_usage_stats(&self) -> Result<UsageStats, String> {
+        self.lock().await.get_usage_stats().await
+    }
+
+    async fn set_usage_stats(&mut self, stats: &UsageStats) -> Result<(), String> {
+        self.lock().await.set_usage_stats(stats).await
     }
 }
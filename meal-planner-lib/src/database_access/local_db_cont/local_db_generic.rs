@@ -7,14 +7,25 @@ use std::marker::PhantomData;
 use std::os::raw::c_void;
 use std::ptr;
 use std::slice;
-use strum::{EnumCount, IntoEnumIterator};
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+use crate::bl::constraints_solver::Solution;
+use crate::bl::grocery_haul::{Pantry, ShoppingList};
+use crate::bl::targets_review::TargetsRecord;
 use crate::data_types::{
-    AllowedUnits, AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients,
-    MicroNutrientsType, Product, UnitData,
+    ALCOHOL_KCAL_PER_G, ALLERGEN_FLAG_KIND, ActivityLog, AllowedUnits, AllowedUnitsType,
+    AppSettings, BeverageEntry, CompletenessReport, CookedVariant, DEFAULT_PROFILE_ID, EatenItem,
+    MacroElements, MacroElementsType, MealNote, MicroNutrients, MicroNutrientsType, NutrientType,
+    Price, Product, ProductEdit, ProductId, ProductVersion, Profile, ServingInfo, UnitData,
+    UsageStats, UserProfile,
 };
-use crate::database_access::{Database, DbSearchCriteria, MutableDatabase};
+use crate::database_access::{
+    BulkReport, DataQualitySummary, Database, DatabaseStats, DbSearchCriteria, MutableDatabase,
+    SortKey,
+};
+
+use super::migrations::{self, AddColumn};
 
 use libsqlite3_sys as ffi;
 
@@ -25,12 +36,26 @@ struct SqliteConnection {
     raw: *mut ffi::sqlite3,
 }
 
+// SAFETY: connections are opened with SQLITE_OPEN_FULLMUTEX, so SQLite serializes every call
+// made through `raw` and it can be used from any thread.
+unsafe impl Send for SqliteConnection {}
+
 impl SqliteConnection {
     fn open(path: &str) -> Result<Self, String> {
+        Self::open_with_flags(
+            path,
+            ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_FULLMUTEX,
+        )
+    }
+
+    fn open_read_only(path: &str) -> Result<Self, String> {
+        Self::open_with_flags(path, ffi::SQLITE_OPEN_READONLY | ffi::SQLITE_OPEN_FULLMUTEX)
+    }
+
+    fn open_with_flags(path: &str, flags: i32) -> Result<Self, String> {
         let c_path = CString::new(path)
             .map_err(|_| "Database path contains interior null byte".to_string())?;
         let mut db_ptr: *mut ffi::sqlite3 = ptr::null_mut();
-        let flags = ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_READWRITE;
         let rc =
             unsafe { ffi::sqlite3_open_v2(c_path.as_ptr(), &raw mut db_ptr, flags, ptr::null()) };
         if rc != ffi::SQLITE_OK {
@@ -101,6 +126,20 @@ impl SqliteConnection {
         Ok(results)
     }
 
+    /// Same as [`Self::query_map`], with the result columns looked up by name.
+    fn query_map_named<T, F>(&self, sql: &str, mut mapper: F) -> Result<Vec<T>, String>
+    where
+        F: FnMut(&Row, &ColumnIndex) -> Result<T, String>,
+    {
+        let mut stmt = self.prepare(sql)?;
+        let columns = stmt.column_index()?;
+        let mut results = Vec::new();
+        while let Some(row) = stmt.next()? {
+            results.push(mapper(&row, &columns)?);
+        }
+        Ok(results)
+    }
+
     fn query_first<T, F>(&self, sql: &str, mut mapper: F) -> Result<Option<T>, String>
     where
         F: FnMut(&Row) -> Result<T, String>,
@@ -122,6 +161,34 @@ impl SqliteConnection {
         Ok(stmt.next()?.is_some())
     }
 
+    // copies the whole database into `dest` with the online backup API, replacing its content
+    fn copy_to(&self, dest: &SqliteConnection) -> Result<(), String> {
+        let backup = unsafe {
+            ffi::sqlite3_backup_init(dest.raw, c"main".as_ptr(), self.raw, c"main".as_ptr())
+        };
+        if backup.is_null() {
+            return Err(dest.last_error(unsafe { ffi::sqlite3_errcode(dest.raw) }));
+        }
+        let step = unsafe { ffi::sqlite3_backup_step(backup, -1) };
+        let finish = unsafe { ffi::sqlite3_backup_finish(backup) };
+        if step != ffi::SQLITE_DONE {
+            return Err(dest.last_error(step));
+        }
+        if finish != ffi::SQLITE_OK {
+            return Err(dest.last_error(finish));
+        }
+        Ok(())
+    }
+
+    fn integrity_check(&self) -> Result<(), String> {
+        let problems = self.query_map("PRAGMA integrity_check;", |row| row.get_string(0))?;
+        if problems == ["ok"] {
+            Ok(())
+        } else {
+            Err(format!("Database is damaged: {}", problems.join("; ")))
+        }
+    }
+
     fn last_error(&self, code: i32) -> String {
         let message = unsafe {
             let msg_ptr = ffi::sqlite3_errmsg(self.raw);
@@ -166,6 +233,26 @@ struct Statement<'conn> {
 }
 
 impl Statement<'_> {
+    fn column_index(&self) -> Result<ColumnIndex, String> {
+        let count = unsafe { ffi::sqlite3_column_count(self.stmt) };
+        let mut positions = HashMap::new();
+        for idx in 0..count {
+            let name_ptr = unsafe { ffi::sqlite3_column_name(self.stmt, idx) };
+            if name_ptr.is_null() {
+                return Err(format!("SQLite returned no name for column {idx}"));
+            }
+            let name = unsafe { CStr::from_ptr(name_ptr) }
+                .to_string_lossy()
+                .into_owned();
+            let position = usize::try_from(idx)
+                .map_err(|_| "Negative column index reported by SQLite".to_string())?;
+            if positions.insert(name.clone(), position).is_some() {
+                return Err(format!("Column '{name}' appears twice in the query result"));
+            }
+        }
+        Ok(ColumnIndex { positions })
+    }
+
     fn next(&mut self) -> Result<Option<Row<'_>>, String> {
         let rc = unsafe { ffi::sqlite3_step(self.stmt) };
         match rc {
@@ -187,6 +274,20 @@ impl Drop for Statement<'_> {
     }
 }
 
+/// Positions of the result columns by name, so rows don't depend on the order of the columns.
+struct ColumnIndex {
+    positions: HashMap<String, usize>,
+}
+
+impl ColumnIndex {
+    fn get(&self, name: &str) -> Result<usize, String> {
+        self.positions
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("Query result has no column '{name}'"))
+    }
+}
+
 struct Row<'stmt> {
     stmt: *mut ffi::sqlite3_stmt,
     _marker: PhantomData<&'stmt ffi::sqlite3_stmt>,
@@ -242,6 +343,14 @@ impl Row<'_> {
         Self::f64_to_f32(value)
     }
 
+    fn get_f64(&self, index: usize) -> Result<f64, String> {
+        let (idx, column_type) = self.column_details(index)?;
+        if column_type == ffi::SQLITE_NULL {
+            return Err("Unexpected NULL float column".to_string());
+        }
+        Ok(unsafe { ffi::sqlite3_column_double(self.stmt, idx) })
+    }
+
     fn get_f32_optional(&self, index: usize) -> Result<Option<f32>, String> {
         let (idx, column_type) = self.column_details(index)?;
         if column_type == ffi::SQLITE_NULL {
@@ -282,6 +391,7 @@ impl Row<'_> {
 
 pub struct LocalProductDbConcrete {
     sqlite_con: SqliteConnection,
+    active_profile: String,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, EnumIter)]
@@ -290,6 +400,20 @@ enum SqlTablesNames {
     MacroElements,
     MicroNutrients,
     AllowedUnits,
+    UserProfile,
+    UsageStats,
+    ProductFlags,
+    Profiles,
+    ProductsHistory,
+    BeverageLog,
+    ActivityLog,
+    TargetsRecords,
+    AppSettings,
+    EatenLog,
+    MealNotes,
+    ShoppingLists,
+    Pantry,
+    Plans,
 }
 
 impl fmt::Display for SqlTablesNames {
@@ -299,6 +423,20 @@ impl fmt::Display for SqlTablesNames {
             SqlTablesNames::MacroElements => "macro_elements",
             SqlTablesNames::MicroNutrients => "micronutrients",
             SqlTablesNames::AllowedUnits => "allowed_units",
+            SqlTablesNames::UserProfile => "user_profile",
+            SqlTablesNames::UsageStats => "usage_stats",
+            SqlTablesNames::ProductFlags => "product_flags",
+            SqlTablesNames::Profiles => "profiles",
+            SqlTablesNames::ProductsHistory => "products_history",
+            SqlTablesNames::BeverageLog => "beverage_log",
+            SqlTablesNames::ActivityLog => "activity_log",
+            SqlTablesNames::TargetsRecords => "targets_records",
+            SqlTablesNames::AppSettings => "app_settings",
+            SqlTablesNames::EatenLog => "eaten_log",
+            SqlTablesNames::MealNotes => "meal_notes",
+            SqlTablesNames::ShoppingLists => "shopping_lists",
+            SqlTablesNames::Pantry => "pantry",
+            SqlTablesNames::Plans => "plans",
         };
         write!(f, "{table_name}")
     }
@@ -306,79 +444,198 @@ impl fmt::Display for SqlTablesNames {
 
 // TODO panicking to be replaced with proper error handling
 impl LocalProductDbConcrete {
-    /// Creates a SQLite-backed product database.
+    /// Opens or creates a SQLite-backed product database, failing with
+    /// [`NewerSchema`](crate::database_access::NewerSchema) for databases of a newer library.
+    #[allow(clippy::unused_async)]
+    pub async fn open(database_file: &str) -> Result<Self, String> {
+        let con = SqliteConnection::open(database_file)?;
+        con.enable_foreign_keys()?;
+        Self::init_db_if_new_created(&con)?;
+        Ok(LocalProductDbConcrete {
+            sqlite_con: con,
+            active_profile: DEFAULT_PROFILE_ID.to_string(),
+        })
+    }
+
+    /// Opens an existing database without writing to it, nothing is created or migrated. Fails
+    /// for files of another schema version, e.g. with [`NewerSchema`] for newer ones.
+    ///
+    /// [`NewerSchema`]: crate::database_access::NewerSchema
+    #[allow(clippy::unused_async)]
+    pub async fn open_read_only(database_file: &str) -> Result<Self, String> {
+        let con = SqliteConnection::open_read_only(database_file)?;
+        if !con.table_exists(&SqlTablesNames::Products.to_string())? {
+            return Err(format!("'{database_file}' is not a meal planner database"));
+        }
+        migrations::check_current(Self::schema_version(&con)?)?;
+        Self::table_columns(&con, &migrations::typed_columns())
+            .and_then(|existing| migrations::check_typed_columns(&existing))?;
+        Ok(LocalProductDbConcrete {
+            sqlite_con: con,
+            active_profile: DEFAULT_PROFILE_ID.to_string(),
+        })
+    }
+
+    /// Copies a consistent snapshot of the database to `path`, replacing any file there.
+    #[allow(clippy::unused_async)]
+    pub async fn backup_to(&self, path: &str) -> Result<(), String> {
+        let dest = SqliteConnection::open(path)?;
+        self.sqlite_con
+            .copy_to(&dest)
+            .map_err(|e| format!("Failed to back up to '{path}': {e}"))
+    }
+
+    /// Replaces the whole database with the backup at `path`, which is opened read-only and
+    /// checked first. Older backups are migrated, newer ones are refused with
+    /// [`NewerSchema`](crate::database_access::NewerSchema) before anything is replaced. The
+    /// default profile becomes active.
     #[allow(clippy::unused_async)]
-    pub async fn new(database_file: &str) -> Option<Self> {
-        let con = SqliteConnection::open(database_file).ok()?;
-        if con.enable_foreign_keys().is_err() {
-            return None;
+    pub async fn restore_from(&mut self, path: &str) -> Result<(), String> {
+        let source = SqliteConnection::open_read_only(path)?;
+        source.integrity_check()?;
+        if !source.table_exists(&SqlTablesNames::Products.to_string())? {
+            return Err(format!("'{path}' is not a meal planner database"));
+        }
+        migrations::check_supported(Self::schema_version(&source)?)?;
+        source
+            .copy_to(&self.sqlite_con)
+            .map_err(|e| format!("Failed to restore from '{path}': {e}"))?;
+        Self::migrate(&self.sqlite_con)?;
+        Self::table_columns(&self.sqlite_con, &migrations::typed_columns())
+            .and_then(|existing| migrations::check_typed_columns(&existing))?;
+        self.active_profile = DEFAULT_PROFILE_ID.to_string();
+        Ok(())
+    }
+
+    /// Makes the next queries read and write the data of `profile_id`, the handles sharing the
+    /// connection each set their own profile before querying.
+    pub(super) fn use_profile(&mut self, profile_id: &str) {
+        if self.active_profile != profile_id {
+            self.active_profile = profile_id.to_string();
         }
-        Self::init_db_if_new_created(&con);
-        Some(LocalProductDbConcrete { sqlite_con: con })
     }
 
-    fn init_db_if_new_created(sqlite_con: &SqliteConnection) {
+    /// Runs `PRAGMA integrity_check`, the error lists every problem found.
+    #[allow(clippy::unused_async)]
+    pub async fn integrity_check(&self) -> Result<(), String> {
+        self.sqlite_con.integrity_check()
+    }
+
+    fn init_db_if_new_created(sqlite_con: &SqliteConnection) -> Result<(), String> {
         let products_table = SqlTablesNames::Products.to_string();
         let table_exists = sqlite_con
             .table_exists(&products_table)
-            .unwrap_or_else(|_| panic!("Failed to check table existence for '{products_table}'"));
-        if !table_exists {
+            .map_err(|e| format!("Failed to check table existence for '{products_table}': {e}"))?;
+        if table_exists {
+            Self::migrate(sqlite_con)?;
+        } else {
             Self::create_tables(sqlite_con);
+            sqlite_con
+                .execute(&migrations::create_brand_index_sql())
+                .expect("Failed to create the brand index");
+            sqlite_con
+                .execute(&migrations::create_version_table_sql())
+                .and_then(|()| {
+                    sqlite_con
+                        .execute(&migrations::record_version_sql(migrations::latest_version()))
+                })
+                .unwrap_or_else(|e| panic!("Failed to create schema version table: {e}"));
         }
+        let typed_columns = migrations::typed_columns();
+        Self::table_columns(sqlite_con, &typed_columns)
+            .and_then(|existing| migrations::check_typed_columns(&existing))
     }
 
-    // this function should be run only after upgrading version of this library (i.e., when new micro nutrient or unit is added)
-    fn _update_table_columns(&self, table_name: SqlTablesNames) -> Result<(), String> {
-        let (all_columns, col_type) = match table_name {
-            t @ (SqlTablesNames::Products | SqlTablesNames::MacroElements) => {
-                return Err(format!("{t} table should have all necessary columns"));
-            }
-            SqlTablesNames::MicroNutrients => (
-                MicroNutrientsType::iter()
-                    .map(|x| x.to_string())
-                    .collect::<HashSet<String>>(),
-                "FLOAT",
-            ),
-            SqlTablesNames::AllowedUnits => (
-                AllowedUnitsType::iter()
-                    .map(|x: AllowedUnitsType| x.to_string())
-                    .collect::<HashSet<String>>(),
-                "INTEGER",
-            ),
-        };
-
-        let db_columns = self
-            .sqlite_con
-            .query_map(
-                format!("SELECT name FROM pragma_table_info('{table_name}')").as_str(),
-                |row| row.get_string(0),
-            )
-            .unwrap_or_else(|_| panic!("Getting column names of the {table_name} table failed"));
+    // stored schema version, 0 for databases older than the versioning
+    fn schema_version(sqlite_con: &SqliteConnection) -> Result<u32, String> {
+        if !sqlite_con.table_exists(migrations::SCHEMA_VERSION_TABLE)? {
+            return Ok(0);
+        }
+        sqlite_con
+            .query_first(&migrations::current_version_sql(), |row| {
+                row.get_i64_optional(0)
+            })?
+            .flatten()
+            .map_or(Ok(0), u32::try_from)
+            .map_err(|e| format!("Invalid schema version: {e}"))
+    }
 
-        let mut missing_columns = Vec::new();
-        for col_name in db_columns {
-            if !all_columns.contains(&col_name) {
-                missing_columns.push(col_name);
+    // applies migrations newer than the stored schema version, each in its own transaction
+    fn migrate(sqlite_con: &SqliteConnection) -> Result<(), String> {
+        sqlite_con.execute(&migrations::create_version_table_sql())?;
+        let current_version = Self::schema_version(sqlite_con)?;
+        migrations::check_supported(current_version)?;
+
+        for migration in migrations::pending_migrations(current_version) {
+            let version = migration.version;
+            sqlite_con.execute("BEGIN;")?;
+            let applied = migration
+                .sql
+                .iter()
+                .try_for_each(|sql| sqlite_con.execute(sql))
+                .and_then(|()| Self::add_missing_columns(sqlite_con, &migration.columns))
+                .and_then(|()| sqlite_con.execute(&migrations::record_version_sql(version)));
+            if let Err(e) = applied {
+                let _ = sqlite_con.execute("ROLLBACK;");
+                return Err(format!("Schema migration {version} failed: {e}"));
             }
+            sqlite_con.execute("COMMIT;")?;
         }
+        Self::add_missing_columns(sqlite_con, &migrations::enum_columns())
+    }
 
-        if missing_columns.is_empty() {
-            return Ok(());
+    // column names of every table `columns` belong to
+    fn table_columns<'a>(
+        sqlite_con: &SqliteConnection,
+        columns: &'a [AddColumn],
+    ) -> Result<HashMap<&'a str, HashSet<String>>, String> {
+        let mut existing: HashMap<&str, HashSet<String>> = HashMap::new();
+        for column in columns {
+            if existing.contains_key(column.table) {
+                continue;
+            }
+            let table_columns = sqlite_con
+                .query_map(
+                    format!("SELECT name FROM pragma_table_info('{}')", column.table).as_str(),
+                    |row| row.get_string(0),
+                )
+                .map_err(|e| {
+                    format!(
+                        "Getting column names of the {} table failed: {e}",
+                        column.table
+                    )
+                })?;
+            existing.insert(column.table, table_columns.into_iter().collect());
         }
+        Ok(existing)
+    }
 
-        for col in missing_columns {
-            let alter_table_query =
-                format!("ALTER TABLE {table_name} ADD COLUMN \"{col}\" {col_type};");
-            self.sqlite_con
-                .execute(alter_table_query.as_str())
+    fn add_missing_columns(
+        sqlite_con: &SqliteConnection,
+        columns: &[AddColumn],
+    ) -> Result<(), String> {
+        let existing = Self::table_columns(sqlite_con, columns)?;
+        for column in columns {
+            if existing[column.table].contains(&column.column) {
+                continue;
+            }
+            sqlite_con
+                .execute(&column.alter_sql())
+                .and_then(|()| match &column.backfill {
+                    Some(backfill) => sqlite_con.execute(backfill),
+                    None => Ok(()),
+                })
                 .map_err(|e| {
-                    format!("Failed to add column '{col}' to table '{table_name}': {e}")
+                    format!(
+                        "Failed to add column '{}' to table '{}': {e}",
+                        column.column, column.table
+                    )
                 })?;
         }
-
         Ok(())
     }
 
+    #[allow(clippy::too_many_lines)]
     fn create_tables(sqlite_con: &SqliteConnection) {
         sqlite_con
             .execute(
@@ -386,7 +643,21 @@ impl LocalProductDbConcrete {
                     "CREATE TABLE {} (
                     id TEXT NOT NULL PRIMARY KEY,
                     name CHAR NOT NULL,
-                    brand CHAR
+                    brand CHAR,
+                    price_per_100g FLOAT,
+                    currency CHAR,
+                    negligible INTEGER NOT NULL DEFAULT 0,
+                    grams_per_ml FLOAT,
+                    unit_labels TEXT,
+                    uuid TEXT,
+                    grams_per_serving FLOAT,
+                    cooked_id TEXT,
+                    cooked_yield FLOAT,
+                    calories_override FLOAT,
+                    ignore_alcohol_calories INTEGER NOT NULL DEFAULT 0,
+                    data_quality TEXT,
+                    verified_data INTEGER NOT NULL DEFAULT 1,
+                    barcode TEXT
                 )",
                     SqlTablesNames::Products
                 )
@@ -441,6 +712,64 @@ impl LocalProductDbConcrete {
             allowed_units_fields.as_str(),
         )
         .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::AllowedUnits));
+
+        sqlite_con
+            .execute(&migrations::create_profiles_table_sql())
+            .and_then(|()| sqlite_con.execute(&migrations::insert_default_profile_sql()))
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::Profiles));
+        sqlite_con
+            .execute(&migrations::create_user_profile_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::UserProfile));
+        sqlite_con
+            .execute(&migrations::create_usage_stats_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::UsageStats));
+        sqlite_con
+            .execute(&migrations::create_product_flags_table_sql())
+            .unwrap_or_else(|_| {
+                panic!("Failed to create '{}' table", SqlTablesNames::ProductFlags)
+            });
+        sqlite_con
+            .execute(&migrations::create_products_history_table_sql())
+            .unwrap_or_else(|_| {
+                panic!(
+                    "Failed to create '{}' table",
+                    SqlTablesNames::ProductsHistory
+                )
+            });
+        sqlite_con
+            .execute(&migrations::create_beverage_log_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::BeverageLog));
+        sqlite_con
+            .execute(&migrations::create_activity_log_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::ActivityLog));
+        sqlite_con
+            .execute(&migrations::create_targets_records_table_sql())
+            .unwrap_or_else(|_| {
+                panic!(
+                    "Failed to create '{}' table",
+                    SqlTablesNames::TargetsRecords
+                )
+            });
+        sqlite_con
+            .execute(&migrations::create_app_settings_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::AppSettings));
+        sqlite_con
+            .execute(&migrations::create_eaten_log_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::EatenLog));
+        sqlite_con
+            .execute(&migrations::create_meal_notes_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::MealNotes));
+        sqlite_con
+            .execute(&migrations::create_shopping_lists_table_sql())
+            .unwrap_or_else(|_| {
+                panic!("Failed to create '{}' table", SqlTablesNames::ShoppingLists)
+            });
+        sqlite_con
+            .execute(&migrations::create_pantry_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::Pantry));
+        sqlite_con
+            .execute(&migrations::create_plans_table_sql())
+            .unwrap_or_else(|_| panic!("Failed to create '{}' table", SqlTablesNames::Plans));
     }
 
     fn create_table_for_table_name(
@@ -464,6 +793,59 @@ impl LocalProductDbConcrete {
     }
 }
 
+/// Select of whole products, rows are read by `map_query_row_to_product`.
+fn select_products_sql() -> String {
+    let mut query_template = format!(
+        "SELECT {p}.id, {p}.name, {p}.brand, {p}.price_per_100g, {p}.currency, {p}.negligible, \
+         {p}.grams_per_ml, {p}.unit_labels, {p}.grams_per_serving, {p}.cooked_id, \
+         {p}.cooked_yield, {p}.calories_override, {p}.ignore_alcohol_calories, \
+         {p}.data_quality, {p}.barcode, \
+         (SELECT GROUP_CONCAT({f}.kind || ':' || {f}.flag) FROM {f} WHERE {f}.id = {p}.id) \
+         AS flags",
+        p = SqlTablesNames::Products,
+        f = SqlTablesNames::ProductFlags
+    );
+    // Helper closure to append columns from an enum iterator
+    let mut append_columns =
+        |table: SqlTablesNames, iter: &mut dyn Iterator<Item = Option<String>>| {
+            for col in iter.flatten() {
+                write!(query_template, ", {table}.\"{col}\"").unwrap();
+            }
+        };
+
+    append_columns(
+        SqlTablesNames::MacroElements,
+        &mut MacroElementsType::iter().map(|m| {
+            if m == MacroElementsType::Calories {
+                None
+            } else {
+                Some(m.to_string())
+            }
+        }),
+    );
+    append_columns(
+        SqlTablesNames::MicroNutrients,
+        &mut MicroNutrientsType::iter().map(|m| Some(m.to_string())),
+    );
+    let mut allowed_columns_iter = AllowedUnitsType::iter()
+        .flat_map(|unit| [Some(unit.to_string()), Some(format!("{unit} divider"))].into_iter());
+    append_columns(SqlTablesNames::AllowedUnits, &mut allowed_columns_iter);
+
+    write!(
+        query_template,
+        " FROM {p}
+        INNER JOIN {me} ON {p}.id = {me}.id
+        INNER JOIN {au} ON {p}.id = {au}.id
+        LEFT JOIN {mn} ON {p}.id = {mn}.id",
+        p = SqlTablesNames::Products,
+        me = SqlTablesNames::MacroElements,
+        au = SqlTablesNames::AllowedUnits,
+        mn = SqlTablesNames::MicroNutrients
+    )
+    .unwrap();
+    query_template
+}
+
 fn db_search_criteria_to_sql_query_fragment(
     criteria: &[DbSearchCriteria],
 ) -> Result<String, String> {
@@ -481,52 +863,259 @@ fn db_search_criteria_to_sql_query_fragment(
                     query_fragment,
                     "{}.name LIKE '{}%'",
                     SqlTablesNames::Products,
-                    name
+                    escape(name)
+                )
+                .unwrap();
+            }
+            DbSearchCriteria::ByBarcode(barcode) => {
+                write!(
+                    query_fragment,
+                    "{}.barcode = '{}'",
+                    SqlTablesNames::Products,
+                    escape(barcode)
                 )
                 .unwrap();
             }
+            DbSearchCriteria::ByBrand(brand) => {
+                write!(
+                    query_fragment,
+                    "{}.brand = '{}'",
+                    SqlTablesNames::Products,
+                    escape(brand)
+                )
+                .unwrap();
+            }
+            DbSearchCriteria::ByNutrientAtLeast(nutrient, value) => {
+                write_nutrient_comparison(&mut query_fragment, *nutrient, ">=", *value);
+            }
+            DbSearchCriteria::ByNutrientAtMost(nutrient, value) => {
+                write_nutrient_comparison(&mut query_fragment, *nutrient, "<=", *value);
+            }
+            DbSearchCriteria::ExcludeAllergens(allergens) => {
+                if allergens.is_empty() {
+                    query_fragment.push('1');
+                    continue;
+                }
+                let flags = allergens
+                    .iter()
+                    .map(|a| format!("'{a}'"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    query_fragment,
+                    "NOT EXISTS (SELECT 1 FROM {f} WHERE {f}.id = {p}.id \
+                     AND {f}.kind = '{ALLERGEN_FLAG_KIND}' AND {f}.flag IN ({flags}))",
+                    f = SqlTablesNames::ProductFlags,
+                    p = SqlTablesNames::Products,
+                )
+                .unwrap();
+            }
+            DbSearchCriteria::VerifiedDataOnly => {
+                write!(query_fragment, "{}.verified_data", SqlTablesNames::Products).unwrap();
+            }
         }
     }
     Ok(query_fragment)
 }
 
-fn map_query_row_to_product(row: &Row) -> Result<(String, Product), String> {
-    let id = row.get_string(0)?;
-    let name = row.get_string(1)?;
-    let brand = row.get_string_optional(2)?;
-
-    let mut offset = 3;
+/// Calories per 100g, only overridden ones are stored, others are derived as in
+/// `Product::computed_calories`.
+fn calories_sql() -> String {
+    format!(
+        "COALESCE({p}.calories_override, \
+         9 * {me}.\"{}\" + 4 * {me}.\"{}\" + 4 * {me}.\"{}\" \
+         + CASE WHEN {p}.ignore_alcohol_calories THEN 0 \
+         ELSE {ALCOHOL_KCAL_PER_G} * COALESCE({mn}.\"{}\", 0) END)",
+        MacroElementsType::Fat,
+        MacroElementsType::Carbs,
+        MacroElementsType::Protein,
+        MicroNutrientsType::Alcohol,
+        p = SqlTablesNames::Products,
+        me = SqlTablesNames::MacroElements,
+        mn = SqlTablesNames::MicroNutrients
+    )
+}
 
-    let mut macro_values = Vec::with_capacity(MacroElementsType::COUNT - 1);
-    for macro_type in MacroElementsType::iter() {
-        if macro_type == MacroElementsType::Calories {
-            continue;
+fn write_nutrient_comparison(query: &mut String, nutrient: NutrientType, op: &str, value: f32) {
+    // NaN or infinity can't be written as SQL literal, such criterion matches nothing
+    if !value.is_finite() {
+        query.push('0');
+        return;
+    }
+    let column = match nutrient {
+        NutrientType::Macro(MacroElementsType::Calories) => calories_sql(),
+        NutrientType::Macro(macro_type) => {
+            format!("{}.\"{macro_type}\"", SqlTablesNames::MacroElements)
+        }
+        NutrientType::Micro(micro_type) => {
+            format!("{}.\"{micro_type}\"", SqlTablesNames::MicroNutrients)
         }
-        macro_values.push(row.get_f32(offset)?);
-        offset += 1;
+    };
+    write!(query, "{column} {op} {value}").unwrap();
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// doubles single quotes, so the value can be put between them in SQL
+fn escape(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+fn price_to_sql(price: Option<&Price>) -> (String, String) {
+    match price {
+        Some(price) => (
+            price.per_100g.to_string(),
+            format!("'{}'", escape(&price.currency)),
+        ),
+        None => ("NULL".to_string(), "NULL".to_string()),
+    }
+}
+
+// unit labels are stored as JSON, NULL when the product has none
+fn volume_to_sql(product: &Product) -> Result<(String, String), String> {
+    let grams_per_ml = product
+        .grams_per_ml
+        .map_or_else(|| "NULL".to_string(), |density| density.to_string());
+    if product.unit_labels.is_empty() {
+        return Ok((grams_per_ml, "NULL".to_string()));
     }
-    if macro_values.len() != (MacroElementsType::COUNT - 1) {
-        return Err("Unexpected number of macro nutrient columns".to_string());
+    let labels = serde_json::to_string(&product.unit_labels)
+        .map_err(|e| format!("Failed to serialize unit labels of '{}': {e}", product.id()))?;
+    Ok((grams_per_ml, format!("'{}'", escape(&labels))))
+}
+
+fn serving_to_sql(product: &Product) -> String {
+    product.serving.map_or_else(
+        || "NULL".to_string(),
+        |serving| serving.grams_per_serving.to_string(),
+    )
+}
+
+fn cooked_to_sql(product: &Product) -> (String, String) {
+    product.cooked.as_ref().map_or_else(
+        || ("NULL".to_string(), "NULL".to_string()),
+        |cooked| {
+            (
+                format!("'{}'", escape(cooked.product_id.as_str())),
+                cooked.yield_factor.to_string(),
+            )
+        },
+    )
+}
+
+// quality JSON and whether the product counts as verified
+fn data_quality_to_sql(product: &Product) -> Result<(String, i32), String> {
+    let verified = i32::from(product.has_verified_data());
+    let Some(quality) = product.data_quality() else {
+        return Ok(("NULL".to_string(), verified));
+    };
+    let json = serde_json::to_string(quality).map_err(|e| {
+        format!(
+            "Failed to serialize data quality of '{}': {e}",
+            product.id()
+        )
+    })?;
+    Ok((format!("'{}'", escape(&json)), verified))
+}
+
+fn barcode_to_sql(product: &Product) -> String {
+    product
+        .barcode
+        .as_ref()
+        .map_or_else(|| "NULL".to_string(), |code| format!("'{}'", escape(code)))
+}
+
+fn calories_override_to_sql(product: &Product) -> String {
+    product
+        .macro_elements
+        .calories_override()
+        .map_or_else(|| "NULL".to_string(), |kcal| kcal.to_string())
+}
+
+// deletes stored flags of the product and inserts the current ones
+fn replace_flags_sql(product_id: &str, product: &Product) -> String {
+    let table = SqlTablesNames::ProductFlags;
+    let mut sql = format!("DELETE FROM {table} WHERE id = '{product_id}';");
+    for (kind, flag) in product.flag_rows() {
+        write!(
+            sql,
+            " INSERT INTO {table} (id, kind, flag) VALUES ('{product_id}', '{kind}', '{flag}');"
+        )
+        .unwrap();
     }
+    sql
+}
+
+fn map_query_row_to_product(
+    row: &Row,
+    columns: &ColumnIndex,
+) -> Result<(ProductId, Product), String> {
+    let id: ProductId = row.get_string(columns.get("id")?)?.into();
+    let name = row.get_string(columns.get("name")?)?;
+    let brand = row.get_string_optional(columns.get("brand")?)?;
+    let currency = row.get_string_optional(columns.get("currency")?)?;
+    let price = row
+        .get_f32_optional(columns.get("price_per_100g")?)?
+        .map(|per_100g| Price {
+            per_100g,
+            currency: currency.unwrap_or_default(),
+        });
+    let negligible = row
+        .get_i64_optional(columns.get("negligible")?)?
+        .is_some_and(|v| v != 0);
+    let ignore_alcohol_calories = row
+        .get_i64_optional(columns.get("ignore_alcohol_calories")?)?
+        .is_some_and(|v| v != 0);
+    let grams_per_ml = row.get_f32_optional(columns.get("grams_per_ml")?)?;
+    let barcode = row.get_string_optional(columns.get("barcode")?)?;
+    let data_quality = row
+        .get_string_optional(columns.get("data_quality")?)?
+        .map(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid data quality of product '{id}': {e}"))
+        })
+        .transpose()?;
+    let unit_labels = match row.get_string_optional(columns.get("unit_labels")?)? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid unit labels of product '{id}': {e}"))?,
+        None => BTreeMap::new(),
+    };
+    let serving = row
+        .get_f32_optional(columns.get("grams_per_serving")?)?
+        .map(ServingInfo::new);
+    let cooked = row
+        .get_string_optional(columns.get("cooked_id")?)?
+        .zip(row.get_f32_optional(columns.get("cooked_yield")?)?)
+        .map(|(cooked_id, yield_factor)| CookedVariant::new(cooked_id.into(), yield_factor));
+    let flags = row
+        .get_string_optional(columns.get("flags")?)?
+        .unwrap_or_default();
+
+    let macro_value = |macro_type: MacroElementsType| -> Result<f32, String> {
+        row.get_f32(columns.get(&macro_type.to_string())?)
+    };
     let macro_elems = MacroElements::new(
-        macro_values[0],
-        macro_values[1],
-        macro_values[2],
-        macro_values[3],
-        macro_values[4],
-    );
+        macro_value(MacroElementsType::Fat)?,
+        macro_value(MacroElementsType::SaturatedFat)?,
+        macro_value(MacroElementsType::Carbs)?,
+        macro_value(MacroElementsType::Sugar)?,
+        macro_value(MacroElementsType::Protein)?,
+    )
+    .with_calories_override(row.get_f32_optional(columns.get("calories_override")?)?);
 
     let mut micronutrients = Box::new(MicroNutrients::default());
     for micro_type in MicroNutrientsType::iter() {
-        micronutrients[micro_type] = row.get_f32_optional(offset)?;
-        offset += 1;
+        micronutrients[micro_type] = row.get_f32_optional(columns.get(&micro_type.to_string())?)?;
     }
 
     let mut allowed_units: AllowedUnits = HashMap::new();
     for unit in AllowedUnitsType::iter() {
-        let quantity = row.get_u16_optional(offset)?;
-        let divider = row.get_u16_optional(offset + 1)?;
-        offset += 2;
+        let quantity = row.get_u16_optional(columns.get(&unit.to_string())?)?;
+        let divider = row.get_u16_optional(columns.get(&format!("{unit} divider"))?)?;
         if let (Some(amount), Some(divider)) = (quantity, divider) {
             allowed_units.insert(unit, UnitData { amount, divider });
         }
@@ -538,7 +1127,17 @@ fn map_query_row_to_product(row: &Row) -> Result<(String, Product), String> {
         Box::new(macro_elems),
         micronutrients,
         allowed_units,
-    );
+    )
+    .with_price(price)
+    .with_negligible(negligible)
+    .with_grams_per_ml(grams_per_ml)
+    .with_unit_labels(unit_labels)
+    .with_serving(serving)
+    .with_cooked(cooked)
+    .with_ignore_alcohol_calories(ignore_alcohol_calories)
+    .with_data_quality(data_quality)
+    .with_barcode(barcode)
+    .with_flag_entries(flags.split(',').filter(|f| !f.is_empty()));
     Ok((id, product))
 }
 
@@ -547,49 +1146,8 @@ impl Database for LocalProductDbConcrete {
     async fn get_products_matching_criteria(
         &self,
         criteria: &[DbSearchCriteria],
-    ) -> BTreeMap<String, Product> {
-        let mut query_template = format!(
-            "SELECT {p}.id, {p}.name, {p}.brand",
-            p = SqlTablesNames::Products
-        );
-        // Helper closure to append columns from an enum iterator
-        let mut append_columns =
-            |table: SqlTablesNames, iter: &mut dyn Iterator<Item = Option<String>>| {
-                for col in iter.flatten() {
-                    write!(query_template, ", {table}.\"{col}\"").unwrap();
-                }
-            };
-
-        append_columns(
-            SqlTablesNames::MacroElements,
-            &mut MacroElementsType::iter().map(|m| {
-                if m == MacroElementsType::Calories {
-                    None
-                } else {
-                    Some(m.to_string())
-                }
-            }),
-        );
-        append_columns(
-            SqlTablesNames::MicroNutrients,
-            &mut MicroNutrientsType::iter().map(|m| Some(m.to_string())),
-        );
-        let mut allowed_columns_iter = AllowedUnitsType::iter()
-            .flat_map(|unit| [Some(unit.to_string()), Some(format!("{unit} divider"))].into_iter());
-        append_columns(SqlTablesNames::AllowedUnits, &mut allowed_columns_iter);
-
-        write!(
-            query_template,
-            " FROM {p}
-            INNER JOIN {me} ON {p}.id = {me}.id
-            INNER JOIN {au} ON {p}.id = {au}.id
-            LEFT JOIN {mn} ON {p}.id = {mn}.id",
-            p = SqlTablesNames::Products,
-            me = SqlTablesNames::MacroElements,
-            au = SqlTablesNames::AllowedUnits,
-            mn = SqlTablesNames::MicroNutrients
-        )
-        .unwrap();
+    ) -> BTreeMap<ProductId, Product> {
+        let mut query_template = select_products_sql();
         query_template.push_str(
             db_search_criteria_to_sql_query_fragment(criteria)
                 .expect("Failed to convert search criteria to SQL query fragment")
@@ -599,7 +1157,7 @@ impl Database for LocalProductDbConcrete {
 
         let products = self
             .sqlite_con
-            .query_map(&query_template, map_query_row_to_product)
+            .query_map_named(&query_template, map_query_row_to_product)
             .unwrap_or_else(|e| panic!("Failed to map query results: {e}"));
 
         let mut result_map = BTreeMap::new();
@@ -631,6 +1189,157 @@ impl Database for LocalProductDbConcrete {
             })?;
         Ok(())
     }
+
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        let all_null = |table: SqlTablesNames, columns: Vec<String>| {
+            columns
+                .iter()
+                .map(|c| format!("{table}.\"{c}\" IS NULL"))
+                .collect::<Vec<_>>()
+        };
+        let micro_nulls = all_null(
+            SqlTablesNames::MicroNutrients,
+            MicroNutrientsType::iter().map(|m| m.to_string()).collect(),
+        );
+        let extra_unit_nulls = all_null(
+            SqlTablesNames::AllowedUnits,
+            AllowedUnitsType::iter()
+                .filter(|u| *u != AllowedUnitsType::Gram)
+                .map(|u| u.to_string())
+                .collect(),
+        );
+        let query = format!(
+            "WITH planned(id) AS ({planned})
+            SELECT COUNT(*),
+                COALESCE(SUM(CASE WHEN {no_micro} THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN {any_micro_missing} THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN {no_extra_units} THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN {p}.brand IS NULL OR {p}.brand = '' THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN {p}.barcode IS NULL OR {p}.barcode = '' THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN NOT EXISTS (SELECT 1 FROM planned WHERE planned.id = {p}.id) THEN 1 ELSE 0 END), 0)
+            FROM {p}
+            LEFT JOIN {mn} ON {p}.id = {mn}.id
+            LEFT JOIN {au} ON {p}.id = {au}.id;",
+            no_micro = micro_nulls.join(" AND "),
+            any_micro_missing = micro_nulls.join(" OR "),
+            no_extra_units = extra_unit_nulls.join(" AND "),
+            planned = migrations::planned_product_ids_sql(),
+            p = SqlTablesNames::Products,
+            mn = SqlTablesNames::MicroNutrients,
+            au = SqlTablesNames::AllowedUnits,
+        );
+        let count = |row: &Row, index| {
+            row.get_i64(index)
+                .and_then(|v| u64::try_from(v).map_err(|_| "Negative count".to_string()))
+        };
+        self.sqlite_con
+            .query_first(&query, |row| {
+                Ok(DataQualitySummary {
+                    total_products: count(row, 0)?,
+                    without_micro_nutrients: count(row, 1)?,
+                    incomplete_micro_nutrients: count(row, 2)?,
+                    without_extra_units: count(row, 3)?,
+                    without_brand: count(row, 4)?,
+                    without_barcode: count(row, 5)?,
+                    unused_in_plans: count(row, 6)?,
+                })
+            })?
+            .ok_or_else(|| "Data quality query returned no rows".to_string())
+    }
+
+    async fn least_complete_products(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, CompletenessReport)>, String> {
+        let missing = MicroNutrientsType::iter()
+            .map(|m| format!("({}.\"{m}\" IS NULL)", SqlTablesNames::MicroNutrients))
+            .collect::<Vec<_>>();
+        let query = format!(
+            "SELECT {p}.id, {flags} FROM {p} LEFT JOIN {mn} ON {p}.id = {mn}.id \
+             WHERE {any_missing} ORDER BY {missing_count} DESC, {p}.id LIMIT {limit};",
+            flags = MicroNutrientsType::iter()
+                .zip(&missing)
+                .map(|(m, is_null)| format!("{is_null} AS \"{m}\""))
+                .collect::<Vec<_>>()
+                .join(", "),
+            any_missing = missing.join(" OR "),
+            missing_count = missing.join(" + "),
+            p = SqlTablesNames::Products,
+            mn = SqlTablesNames::MicroNutrients,
+        );
+        self.sqlite_con.query_map_named(&query, |row, columns| {
+            let mut missing = Vec::new();
+            for micro_type in MicroNutrientsType::iter() {
+                if row.get_i64(columns.get(&micro_type.to_string())?)? != 0 {
+                    missing.push(micro_type);
+                }
+            }
+            Ok((
+                row.get_string(columns.get("id")?)?.into(),
+                CompletenessReport::from_missing(missing),
+            ))
+        })
+    }
+
+    async fn get_products_page(
+        &self,
+        sort: SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, Product)>, String> {
+        let order = match sort {
+            SortKey::Name => format!("{}.name", SqlTablesNames::Products),
+            SortKey::Brand => format!("{}.brand", SqlTablesNames::Products),
+            SortKey::Calories => calories_sql(),
+            SortKey::Protein => format!(
+                "{}.\"{}\"",
+                SqlTablesNames::MacroElements,
+                MacroElementsType::Protein
+            ),
+        };
+        let query = format!(
+            "{} ORDER BY {order}, {}.id LIMIT {limit} OFFSET {offset};",
+            select_products_sql(),
+            SqlTablesNames::Products
+        );
+        self.sqlite_con
+            .query_map_named(&query, map_query_row_to_product)
+    }
+
+    async fn list_brands(&self) -> Result<BTreeMap<String, u64>, String> {
+        self.sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT brand, COUNT(*) FROM {} WHERE brand IS NOT NULL AND brand != '' \
+                     GROUP BY brand;",
+                    SqlTablesNames::Products
+                ),
+                |row| {
+                    let count =
+                        u64::try_from(row.get_i64(1)?).map_err(|_| "Negative count".to_string())?;
+                    Ok((row.get_string(0)?, count))
+                },
+            )
+            .map(|brands| brands.into_iter().collect())
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats, String> {
+        let quality = self.data_quality_summary().await?;
+        let count = |row: &Row, index| {
+            row.get_i64(index)
+                .and_then(|v| u64::try_from(v).map_err(|_| "Negative count".to_string()))
+        };
+        let products_per_brand = self.list_brands().await?;
+        let size_bytes = self.sqlite_con.query_first(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size();",
+            |row| count(row, 0),
+        )?;
+        Ok(DatabaseStats {
+            quality,
+            products_per_brand,
+            size_bytes,
+        })
+    }
 }
 
 // function is long because there are 2 macro definitions inside
@@ -652,17 +1361,37 @@ impl MutableDatabase for LocalProductDbConcrete {
             Ok(())
         };
 
+        let (price_sql, currency_sql) = price_to_sql(product.price.as_ref());
+        let (grams_per_ml_sql, unit_labels_sql) = volume_to_sql(&product)?;
+        let (cooked_id_sql, cooked_yield_sql) = cooked_to_sql(&product);
+        let (data_quality_sql, verified_data) = data_quality_to_sql(&product)?;
         run_query(
             &SqlTablesNames::Products.to_string(),
-            "id, name, brand",
+            "id, name, brand, price_per_100g, currency, negligible, grams_per_ml, unit_labels, \
+             grams_per_serving, cooked_id, cooked_yield, calories_override, \
+             ignore_alcohol_calories, data_quality, verified_data, barcode, uuid",
             format!(
-                "'{}', '{}', {}",
+                "'{}', '{}', {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}",
                 product_id,
                 product.name(),
                 match product.brand() {
                     Some(brand) => format!("'{brand}'"),
                     None => "NULL".to_string(),
-                }
+                },
+                price_sql,
+                currency_sql,
+                i32::from(product.negligible),
+                grams_per_ml_sql,
+                unit_labels_sql,
+                serving_to_sql(&product),
+                cooked_id_sql,
+                cooked_yield_sql,
+                calories_override_to_sql(&product),
+                i32::from(product.ignore_alcohol_calories),
+                data_quality_sql,
+                verified_data,
+                barcode_to_sql(&product),
+                migrations::NEW_UUID_SQL
             )
             .as_str(),
         )?;
@@ -750,10 +1479,31 @@ impl MutableDatabase for LocalProductDbConcrete {
             SqlTablesNames::AllowedUnits
         );
 
+        self.sqlite_con
+            .execute(&replace_flags_sql(product_id, &product))
+            .map_err(|e| format!("Failed to insert flags of product '{product_id}': {e}"))?;
+
         Ok(())
     }
 
     async fn update_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        // saving an unchanged product adds no version
+        if let Some(previous) = self.get_product_by_id(product_id).await
+            && previous != product
+        {
+            let json = serde_json::to_string(&previous)
+                .map_err(|e| format!("Failed to archive product '{product_id}': {e}"))?;
+            self.sqlite_con
+                .execute(&format!(
+                    "INSERT INTO {} (id, archived_at, product) VALUES ('{}', {}, '{}');",
+                    SqlTablesNames::ProductsHistory,
+                    escape(product_id),
+                    unix_now(),
+                    escape(&json),
+                ))
+                .map_err(|e| format!("Failed to archive product '{product_id}': {e}"))?;
+        }
+
         let run_exec = |sql: String| {
             self.sqlite_con
                 .execute(&sql)
@@ -764,13 +1514,46 @@ impl MutableDatabase for LocalProductDbConcrete {
             Some(brand) => format!("'{brand}'"),
             None => "NULL".to_string(),
         };
+        let (price_sql, currency_sql) = price_to_sql(product.price.as_ref());
+        let (grams_per_ml_sql, unit_labels_sql) = volume_to_sql(&product)?;
+        let (cooked_id_sql, cooked_yield_sql) = cooked_to_sql(&product);
+        let (data_quality_sql, verified_data) = data_quality_to_sql(&product)?;
         run_exec(format!(
-            "INSERT INTO {table} (id, name, brand) VALUES ('{id}', '{name}', {brand}) \
-             ON CONFLICT(id) DO UPDATE SET name = excluded.name, brand = excluded.brand;",
+            "INSERT INTO {table} (id, name, brand, price_per_100g, currency, negligible, \
+             grams_per_ml, unit_labels, grams_per_serving, cooked_id, cooked_yield, \
+             calories_override, ignore_alcohol_calories, data_quality, verified_data, barcode, \
+             uuid) \
+             VALUES ('{id}', '{name}', {brand}, {price}, {currency}, {negligible}, \
+             {grams_per_ml}, {unit_labels}, {serving}, {cooked_id}, {cooked_yield}, \
+             {calories_override}, {ignore_alcohol_calories}, {data_quality}, {verified_data}, \
+             {barcode}, {uuid}) \
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, brand = excluded.brand, \
+             price_per_100g = excluded.price_per_100g, currency = excluded.currency, \
+             negligible = excluded.negligible, grams_per_ml = excluded.grams_per_ml, \
+             unit_labels = excluded.unit_labels, grams_per_serving = excluded.grams_per_serving, \
+             cooked_id = excluded.cooked_id, cooked_yield = excluded.cooked_yield, \
+             calories_override = excluded.calories_override, \
+             ignore_alcohol_calories = excluded.ignore_alcohol_calories, \
+             data_quality = excluded.data_quality, verified_data = excluded.verified_data, \
+             barcode = excluded.barcode;",
             table = SqlTablesNames::Products,
             id = product_id,
             name = product.name(),
             brand = brand_sql,
+            price = price_sql,
+            currency = currency_sql,
+            negligible = i32::from(product.negligible),
+            grams_per_ml = grams_per_ml_sql,
+            unit_labels = unit_labels_sql,
+            serving = serving_to_sql(&product),
+            cooked_id = cooked_id_sql,
+            cooked_yield = cooked_yield_sql,
+            calories_override = calories_override_to_sql(&product),
+            ignore_alcohol_calories = i32::from(product.ignore_alcohol_calories),
+            data_quality = data_quality_sql,
+            verified_data = verified_data,
+            barcode = barcode_to_sql(&product),
+            uuid = migrations::NEW_UUID_SQL,
         ))?;
 
         let macro_cols: Vec<String> = MacroElementsType::iter()
@@ -848,6 +1631,8 @@ impl MutableDatabase for LocalProductDbConcrete {
             id = product_id,
         ))?;
 
+        run_exec(replace_flags_sql(product_id, &product))?;
+
         Ok(())
     }
 
@@ -867,663 +1652,3275 @@ impl MutableDatabase for LocalProductDbConcrete {
             })?;
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::data_types::{
-        AllowedUnits, AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients,
-        MicroNutrientsType, UnitData,
-    };
-    use crate::database_access::{Database, DbSearchCriteria, MutableDatabase};
-    use approx::assert_relative_eq;
+    // one transaction, every product behind a savepoint so a failing one is undone alone
+    async fn add_products_bulk(
+        &mut self,
+        products: Vec<(String, Product)>,
+    ) -> Result<BulkReport, String> {
+        self.sqlite_con.execute("BEGIN;")?;
+        let mut report = BulkReport::default();
+        for (product_id, product) in products {
+            if let Err(e) = self.sqlite_con.execute("SAVEPOINT bulk_product;") {
+                let _ = self.sqlite_con.execute("ROLLBACK;");
+                return Err(e);
+            }
+            let result = self.add_product(&product_id, product).await;
+            let end = if result.is_ok() {
+                "RELEASE bulk_product;"
+            } else {
+                "ROLLBACK TO bulk_product; RELEASE bulk_product;"
+            };
+            if let Err(e) = self.sqlite_con.execute(end) {
+                let _ = self.sqlite_con.execute("ROLLBACK;");
+                return Err(e);
+            }
+            report.entries.push((product_id, result));
+        }
+        if let Err(e) = self.sqlite_con.execute("COMMIT;") {
+            let _ = self.sqlite_con.execute("ROLLBACK;");
+            return Err(e);
+        }
+        Ok(report)
+    }
+
+    async fn delete_products(&mut self, product_ids: &[String]) -> Result<(), String> {
+        if product_ids.is_empty() {
+            return Ok(());
+        }
+        let ids = product_ids
+            .iter()
+            .map(|id| format!("'{}'", escape(id)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.sqlite_con
+            .execute(&format!(
+                "DELETE FROM {} WHERE id IN ({ids});",
+                SqlTablesNames::Products
+            ))
+            .map_err(|e| format!("Failed to delete {} products: {e}", product_ids.len()))
+    }
+
+    // one transaction, a missing or failing product rolls back every edit
+    async fn edit_products(
+        &mut self,
+        product_ids: &[String],
+        edit: &ProductEdit,
+    ) -> Result<(), String> {
+        self.sqlite_con.execute("BEGIN;")?;
+        for product_id in product_ids {
+            let result = match self.get_product_by_id(product_id).await {
+                Some(mut product) => {
+                    edit.apply(&mut product);
+                    self.update_product(product_id, product).await
+                }
+                None => Err(format!("Product '{product_id}' not found")),
+            };
+            if let Err(e) = result {
+                let _ = self.sqlite_con.execute("ROLLBACK;");
+                return Err(e);
+            }
+        }
+        if let Err(e) = self.sqlite_con.execute("COMMIT;") {
+            let _ = self.sqlite_con.execute("ROLLBACK;");
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn get_product_history(&self, product_id: &str) -> Result<Vec<ProductVersion>, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT archived_at, product FROM {} WHERE id = '{}' \
+                     ORDER BY archived_at DESC, rowid DESC;",
+                    SqlTablesNames::ProductsHistory,
+                    escape(product_id)
+                ),
+                |row| Ok((row.get_i64(0)?, row.get_string(1)?)),
+            )
+            .map_err(|e| format!("Failed to read history of product '{product_id}': {e}"))?;
+        rows.into_iter()
+            .map(|(archived_at, json)| {
+                Ok(ProductVersion {
+                    archived_at: u64::try_from(archived_at)
+                        .map_err(|e| format!("Invalid archive time: {e}"))?,
+                    product: serde_json::from_str(&json)
+                        .map_err(|e| format!("Invalid archived product '{product_id}': {e}"))?,
+                })
+            })
+            .collect()
+    }
+
+    async fn rename_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        self.sqlite_con.execute("BEGIN;")?;
+        let result = async {
+            let uuid = self
+                .get_product_uuid(product_id)
+                .await?
+                .ok_or_else(|| format!("Product '{product_id}' not found"))?;
+            let new_id = product.id();
+            self.delete_product(product_id).await?;
+            self.add_product(&new_id, product).await?;
+            self.sqlite_con
+                .execute(&format!(
+                    "UPDATE {} SET uuid = '{}' WHERE id = '{}';",
+                    SqlTablesNames::Products,
+                    escape(&uuid),
+                    escape(&new_id)
+                ))
+                .map_err(|e| format!("Failed to keep uuid of renamed product '{new_id}': {e}"))?;
+            for (table, column) in migrations::PRODUCT_ID_REFERENCES {
+                self.sqlite_con
+                    .execute(&format!(
+                        "UPDATE {table} SET {column} = '{}' WHERE {column} = '{}';",
+                        escape(&new_id),
+                        escape(product_id)
+                    ))
+                    .map_err(|e| format!("Failed to move {table} to '{new_id}': {e}"))?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            let _ = self.sqlite_con.execute("ROLLBACK;");
+            return Err(e);
+        }
+        if let Err(e) = self.sqlite_con.execute("COMMIT;") {
+            let _ = self.sqlite_con.execute("ROLLBACK;");
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn get_product_uuid(&self, product_id: &str) -> Result<Option<String>, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT uuid FROM {} WHERE id = '{}';",
+                    SqlTablesNames::Products,
+                    escape(product_id)
+                ),
+                |row| row.get_string_optional(0),
+            )
+            .map_err(|e| format!("Failed to read uuid of product '{product_id}': {e}"))?;
+        Ok(rows.into_iter().next().flatten())
+    }
+
+    async fn get_product_by_uuid(&self, uuid: &str) -> Result<Option<Product>, String> {
+        let ids = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT id FROM {} WHERE uuid = '{}';",
+                    SqlTablesNames::Products,
+                    escape(uuid)
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to find product with uuid '{uuid}': {e}"))?;
+        match ids.first() {
+            Some(product_id) => Ok(self.get_product_by_id(product_id).await),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        self.sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT id, name FROM {} ORDER BY id;",
+                    SqlTablesNames::Profiles
+                ),
+                |row| {
+                    Ok(Profile {
+                        id: row.get_string(0)?,
+                        name: row.get_string(1)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to read profiles: {e}"))
+    }
+
+    async fn set_profile(&mut self, profile: &Profile) -> Result<(), String> {
+        profile.validate()?;
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (id, name) VALUES ('{}', '{}') \
+                 ON CONFLICT(id) DO UPDATE SET name = excluded.name;",
+                SqlTablesNames::Profiles,
+                escape(&profile.id),
+                escape(&profile.name)
+            ))
+            .map_err(|e| format!("Failed to save profile '{}': {e}", profile.id))
+    }
+
+    async fn delete_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        self.sqlite_con
+            .execute(&format!(
+                "DELETE FROM {} WHERE id = '{}';",
+                SqlTablesNames::Profiles,
+                escape(profile_id)
+            ))
+            .map_err(|e| format!("Failed to delete profile '{profile_id}': {e}"))
+    }
+
+    async fn active_profile(&self) -> Result<String, String> {
+        Ok(self.active_profile.clone())
+    }
+
+    async fn set_active_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        let exists = self
+            .sqlite_con
+            .query_first(
+                &format!(
+                    "SELECT id FROM {} WHERE id = '{}';",
+                    SqlTablesNames::Profiles,
+                    escape(profile_id)
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to read profiles: {e}"))?
+            .is_some();
+        if !exists {
+            return Err(format!("Profile '{profile_id}' not found"));
+        }
+        self.active_profile = profile_id.to_string();
+        Ok(())
+    }
+
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String> {
+        self.sqlite_con
+            .query_first(
+                &format!(
+                    "SELECT age_years, sex, weight_kg, height_cm, activity_level FROM {} \
+                     WHERE profile_id = '{}';",
+                    SqlTablesNames::UserProfile,
+                    escape(&self.active_profile)
+                ),
+                |row| {
+                    Ok(UserProfile {
+                        age_years: u8::try_from(row.get_i64(0)?)
+                            .map_err(|e| format!("Invalid stored age: {e}"))?,
+                        sex: row.get_string(1)?.parse()?,
+                        weight_kg: row.get_f32(2)?,
+                        height_cm: row.get_f32(3)?,
+                        activity_level: row.get_string(4)?.parse()?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to read user profile: {e}"))
+    }
+
+    async fn set_user_profile(&mut self, profile: &UserProfile) -> Result<(), String> {
+        profile.validate()?;
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {table} \
+                 (profile_id, age_years, sex, weight_kg, height_cm, activity_level) \
+                 VALUES ('{profile}', {age}, '{sex}', {weight}, {height}, '{activity}') \
+                 ON CONFLICT(profile_id) DO UPDATE SET age_years = excluded.age_years, \
+                 sex = excluded.sex, \
+                 weight_kg = excluded.weight_kg, height_cm = excluded.height_cm, \
+                 activity_level = excluded.activity_level;",
+                table = SqlTablesNames::UserProfile,
+                profile = escape(&self.active_profile),
+                age = profile.age_years,
+                sex = profile.sex,
+                weight = profile.weight_kg,
+                height = profile.height_cm,
+                activity = profile.activity_level,
+            ))
+            .map_err(|e| format!("Failed to save user profile: {e}"))
+    }
+
+    async fn get_targets_record(&self) -> Result<Option<TargetsRecord>, String> {
+        self.sqlite_con
+            .query_first(
+                &format!(
+                    "SELECT computed_at, weight_kg FROM {} WHERE profile_id = '{}';",
+                    SqlTablesNames::TargetsRecords,
+                    escape(&self.active_profile)
+                ),
+                |row| {
+                    Ok(TargetsRecord {
+                        computed_at: u64::try_from(row.get_i64(0)?)
+                            .map_err(|e| format!("Invalid targets calculation time: {e}"))?,
+                        weight_kg: row.get_f32(1)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to read targets record: {e}"))
+    }
+
+    async fn set_targets_record(&mut self, record: &TargetsRecord) -> Result<(), String> {
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, computed_at, weight_kg) VALUES ('{}', {}, {}) \
+                 ON CONFLICT(profile_id) DO UPDATE SET computed_at = excluded.computed_at, \
+                 weight_kg = excluded.weight_kg;",
+                SqlTablesNames::TargetsRecords,
+                escape(&self.active_profile),
+                record.computed_at,
+                record.weight_kg,
+            ))
+            .map_err(|e| format!("Failed to save targets record: {e}"))
+    }
+
+    async fn add_beverage_entry(&mut self, entry: &BeverageEntry) -> Result<(), String> {
+        let product_sql = entry
+            .product_id
+            .as_ref()
+            .map_or_else(|| "NULL".to_string(), |id| format!("'{}'", escape(id)));
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, drunk_at, volume_ml, product_id) \
+                 VALUES ('{}', {}, {}, {product_sql});",
+                SqlTablesNames::BeverageLog,
+                escape(&self.active_profile),
+                entry.drunk_at,
+                entry.volume_ml,
+            ))
+            .map_err(|e| format!("Failed to log drink: {e}"))
+    }
+
+    async fn get_beverage_entries(&self, from: u64, to: u64) -> Result<Vec<BeverageEntry>, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT drunk_at, volume_ml, product_id FROM {} \
+                     WHERE profile_id = '{}' AND drunk_at >= {from} AND drunk_at < {to} \
+                     ORDER BY drunk_at, rowid;",
+                    SqlTablesNames::BeverageLog,
+                    escape(&self.active_profile),
+                ),
+                |row| {
+                    Ok((
+                        row.get_i64(0)?,
+                        row.get_i64(1)?,
+                        row.get_string_optional(2)?,
+                    ))
+                },
+            )
+            .map_err(|e| format!("Failed to read drinks: {e}"))?;
+        rows.into_iter()
+            .map(|(drunk_at, volume_ml, product_id)| {
+                Ok(BeverageEntry {
+                    drunk_at: u64::try_from(drunk_at)
+                        .map_err(|e| format!("Invalid drink time: {e}"))?,
+                    volume_ml: u32::try_from(volume_ml)
+                        .map_err(|e| format!("Invalid drink volume: {e}"))?,
+                    product_id: product_id.map(ProductId::from),
+                })
+            })
+            .collect()
+    }
+
+    async fn add_activity(&mut self, activity: &ActivityLog) -> Result<(), String> {
+        let name_sql = activity
+            .name
+            .as_ref()
+            .map_or_else(|| "NULL".to_string(), |name| format!("'{}'", escape(name)));
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, performed_at, kcal_burned, name) \
+                 VALUES ('{}', {}, {}, {name_sql});",
+                SqlTablesNames::ActivityLog,
+                escape(&self.active_profile),
+                activity.performed_at,
+                activity.kcal_burned,
+            ))
+            .map_err(|e| format!("Failed to log activity: {e}"))
+    }
+
+    async fn get_activities(&self, from: u64, to: u64) -> Result<Vec<ActivityLog>, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT performed_at, kcal_burned, name FROM {} \
+                     WHERE profile_id = '{}' AND performed_at >= {from} AND performed_at < {to} \
+                     ORDER BY performed_at, rowid;",
+                    SqlTablesNames::ActivityLog,
+                    escape(&self.active_profile),
+                ),
+                |row| {
+                    Ok((
+                        row.get_i64(0)?,
+                        row.get_i64(1)?,
+                        row.get_string_optional(2)?,
+                    ))
+                },
+            )
+            .map_err(|e| format!("Failed to read activities: {e}"))?;
+        rows.into_iter()
+            .map(|(performed_at, kcal_burned, name)| {
+                Ok(ActivityLog {
+                    performed_at: u64::try_from(performed_at)
+                        .map_err(|e| format!("Invalid activity time: {e}"))?,
+                    kcal_burned: u32::try_from(kcal_burned)
+                        .map_err(|e| format!("Invalid burned energy: {e}"))?,
+                    name,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_eaten(&self, day: u64) -> Result<Vec<EatenItem>, String> {
+        self.sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT meal, product_id, grams FROM {} \
+                     WHERE profile_id = '{}' AND day = {day} ORDER BY rowid;",
+                    SqlTablesNames::EatenLog,
+                    escape(&self.active_profile),
+                ),
+                |row| {
+                    Ok(EatenItem {
+                        meal: row.get_string(0)?,
+                        product_id: row.get_string(1)?.into(),
+                        grams: row.get_f64(2)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to read eaten products: {e}"))
+    }
+
+    async fn mark_eaten(&mut self, day: u64, item: &EatenItem) -> Result<(), String> {
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, day, meal, product_id, grams) \
+                 VALUES ('{}', {day}, '{}', '{}', {}) \
+                 ON CONFLICT(profile_id, day, meal, product_id) DO UPDATE SET grams = excluded.grams;",
+                SqlTablesNames::EatenLog,
+                escape(&self.active_profile),
+                escape(&item.meal),
+                escape(&item.product_id),
+                item.grams,
+            ))
+            .map_err(|e| format!("Failed to mark '{}' as eaten: {e}", item.product_id))
+    }
+
+    async fn unmark_eaten(&mut self, day: u64, meal: &str, product_id: &str) -> Result<(), String> {
+        self.sqlite_con
+            .execute(&format!(
+                "DELETE FROM {} WHERE profile_id = '{}' AND day = {day} AND meal = '{}' \
+                 AND product_id = '{}';",
+                SqlTablesNames::EatenLog,
+                escape(&self.active_profile),
+                escape(meal),
+                escape(product_id),
+            ))
+            .map_err(|e| format!("Failed to unmark '{product_id}' as eaten: {e}"))
+    }
+
+    async fn get_meal_notes(&self, day: u64) -> Result<Vec<MealNote>, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT meal, note, photos FROM {} \
+                     WHERE profile_id = '{}' AND day = {day} ORDER BY rowid;",
+                    SqlTablesNames::MealNotes,
+                    escape(&self.active_profile),
+                ),
+                |row| Ok((row.get_string(0)?, row.get_string(1)?, row.get_string(2)?)),
+            )
+            .map_err(|e| format!("Failed to read meal notes: {e}"))?;
+        rows.into_iter()
+            .map(|(meal, text, photos)| {
+                let photos = serde_json::from_str(&photos)
+                    .map_err(|e| format!("Invalid photos of '{meal}': {e}"))?;
+                Ok(MealNote { meal, text, photos })
+            })
+            .collect()
+    }
+
+    async fn set_meal_note(&mut self, day: u64, note: &MealNote) -> Result<(), String> {
+        let sql = if note.is_empty() {
+            format!(
+                "DELETE FROM {} WHERE profile_id = '{}' AND day = {day} AND meal = '{}';",
+                SqlTablesNames::MealNotes,
+                escape(&self.active_profile),
+                escape(&note.meal),
+            )
+        } else {
+            let photos = serde_json::to_string(&note.photos)
+                .map_err(|e| format!("Failed to serialize photos of '{}': {e}", note.meal))?;
+            format!(
+                "INSERT INTO {} (profile_id, day, meal, note, photos) \
+                 VALUES ('{}', {day}, '{}', '{}', '{}') \
+                 ON CONFLICT(profile_id, day, meal) DO UPDATE SET note = excluded.note, \
+                 photos = excluded.photos;",
+                SqlTablesNames::MealNotes,
+                escape(&self.active_profile),
+                escape(&note.meal),
+                escape(&note.text),
+                escape(&photos),
+            )
+        };
+        self.sqlite_con
+            .execute(&sql)
+            .map_err(|e| format!("Failed to save note of '{}': {e}", note.meal))
+    }
+
+    async fn get_shopping_list_ids(&self) -> Result<Vec<String>, String> {
+        self.sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT id FROM {} WHERE profile_id = '{}' ORDER BY rowid;",
+                    SqlTablesNames::ShoppingLists,
+                    escape(&self.active_profile),
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to read shopping lists: {e}"))
+    }
+
+    async fn get_shopping_list(&self, list_id: &str) -> Result<Option<ShoppingList>, String> {
+        self.sqlite_con
+            .query_first(
+                &format!(
+                    "SELECT list FROM {} WHERE profile_id = '{}' AND id = '{}';",
+                    SqlTablesNames::ShoppingLists,
+                    escape(&self.active_profile),
+                    escape(list_id),
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to read shopping list '{list_id}': {e}"))?
+            .map(|list| {
+                serde_json::from_str(&list)
+                    .map_err(|e| format!("Invalid shopping list '{list_id}': {e}"))
+            })
+            .transpose()
+    }
+
+    async fn save_shopping_list(
+        &mut self,
+        list_id: &str,
+        list: &ShoppingList,
+    ) -> Result<(), String> {
+        let json = serde_json::to_string(list)
+            .map_err(|e| format!("Failed to serialize shopping list '{list_id}': {e}"))?;
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, id, list) VALUES ('{}', '{}', '{}') \
+                 ON CONFLICT(profile_id, id) DO UPDATE SET list = excluded.list;",
+                SqlTablesNames::ShoppingLists,
+                escape(&self.active_profile),
+                escape(list_id),
+                escape(&json),
+            ))
+            .map_err(|e| format!("Failed to save shopping list '{list_id}': {e}"))
+    }
+
+    async fn delete_shopping_list(&mut self, list_id: &str) -> Result<(), String> {
+        self.sqlite_con
+            .execute(&format!(
+                "DELETE FROM {} WHERE profile_id = '{}' AND id = '{}';",
+                SqlTablesNames::ShoppingLists,
+                escape(&self.active_profile),
+                escape(list_id),
+            ))
+            .map_err(|e| format!("Failed to delete shopping list '{list_id}': {e}"))
+    }
+
+    async fn get_pantry(&self) -> Result<Pantry, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT product_id, grams, expiry_day FROM {} WHERE profile_id = '{}' \
+                     ORDER BY rowid;",
+                    SqlTablesNames::Pantry,
+                    escape(&self.active_profile),
+                ),
+                |row| {
+                    Ok((
+                        row.get_string(0)?,
+                        row.get_f64(1)?,
+                        row.get_i64_optional(2)?,
+                    ))
+                },
+            )
+            .map_err(|e| format!("Failed to read pantry: {e}"))?;
+        let mut pantry = Pantry::new();
+        for (product_id, grams, expiry_day) in rows {
+            match expiry_day {
+                Some(day) => pantry.add_with_expiry(
+                    &product_id,
+                    grams,
+                    u64::try_from(day).map_err(|e| format!("Invalid expiry day: {e}"))?,
+                ),
+                None => pantry.add(&product_id, grams),
+            }
+        }
+        Ok(pantry)
+    }
+
+    // one transaction, so a failing product keeps the pantry saved before
+    async fn save_pantry(&mut self, pantry: &Pantry) -> Result<(), String> {
+        let profile = escape(&self.active_profile);
+        let mut sql = format!(
+            "DELETE FROM {} WHERE profile_id = '{profile}';",
+            SqlTablesNames::Pantry
+        );
+        for (product_id, grams) in pantry.stock() {
+            let expiry_sql = pantry
+                .expiry(product_id)
+                .map_or_else(|| "NULL".to_string(), |day| day.to_string());
+            let _ = write!(
+                sql,
+                "INSERT INTO {} (profile_id, product_id, grams, expiry_day) \
+                 VALUES ('{profile}', '{}', {grams}, {expiry_sql});",
+                SqlTablesNames::Pantry,
+                escape(product_id),
+            );
+        }
+        self.sqlite_con.execute("BEGIN;")?;
+        if let Err(e) = self.sqlite_con.execute(&sql) {
+            let _ = self.sqlite_con.execute("ROLLBACK;");
+            return Err(format!("Failed to save pantry: {e}"));
+        }
+        if let Err(e) = self.sqlite_con.execute("COMMIT;") {
+            let _ = self.sqlite_con.execute("ROLLBACK;");
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        self.sqlite_con
+            .query_map(
+                &format!(
+                    "SELECT id FROM {} WHERE profile_id = '{}' ORDER BY rowid;",
+                    SqlTablesNames::Plans,
+                    escape(&self.active_profile),
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to read plans: {e}"))
+    }
+
+    async fn get_plan(&self, plan_id: &str) -> Result<Option<Solution>, String> {
+        self.sqlite_con
+            .query_first(
+                &format!(
+                    "SELECT plan FROM {} WHERE profile_id = '{}' AND id = '{}';",
+                    SqlTablesNames::Plans,
+                    escape(&self.active_profile),
+                    escape(plan_id),
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to read plan '{plan_id}': {e}"))?
+            .map(|plan| {
+                serde_json::from_str(&plan).map_err(|e| format!("Invalid plan '{plan_id}': {e}"))
+            })
+            .transpose()
+    }
+
+    async fn save_plan(&mut self, plan_id: &str, plan: &Solution) -> Result<(), String> {
+        let json = serde_json::to_string(plan)
+            .map_err(|e| format!("Failed to serialize plan '{plan_id}': {e}"))?;
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, id, plan) VALUES ('{}', '{}', '{}') \
+                 ON CONFLICT(profile_id, id) DO UPDATE SET plan = excluded.plan;",
+                SqlTablesNames::Plans,
+                escape(&self.active_profile),
+                escape(plan_id),
+                escape(&json),
+            ))
+            .map_err(|e| format!("Failed to save plan '{plan_id}': {e}"))
+    }
+
+    async fn find_solved_plan(&self, fingerprint: u64) -> Result<Option<Solution>, String> {
+        self.sqlite_con
+            .query_first(
+                &format!(
+                    "SELECT plan FROM {} WHERE profile_id = '{}' AND fingerprint = '{fingerprint:016x}' \
+                     ORDER BY rowid DESC LIMIT 1;",
+                    SqlTablesNames::Plans,
+                    escape(&self.active_profile),
+                ),
+                |row| row.get_string(0),
+            )
+            .map_err(|e| format!("Failed to read plans: {e}"))?
+            .map(|plan| {
+                serde_json::from_str(&plan).map_err(|e| format!("Invalid solved plan: {e}"))
+            })
+            .transpose()
+    }
+
+    async fn save_solved_plan(
+        &mut self,
+        plan_id: &str,
+        fingerprint: u64,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        let json = serde_json::to_string(plan)
+            .map_err(|e| format!("Failed to serialize plan '{plan_id}': {e}"))?;
+        self.sqlite_con
+            .execute(&format!(
+                "INSERT INTO {} (profile_id, id, plan, fingerprint) \
+                 VALUES ('{}', '{}', '{}', '{fingerprint:016x}') \
+                 ON CONFLICT(profile_id, id) DO UPDATE SET \
+                 plan = excluded.plan, fingerprint = excluded.fingerprint;",
+                SqlTablesNames::Plans,
+                escape(&self.active_profile),
+                escape(plan_id),
+                escape(&json),
+            ))
+            .map_err(|e| format!("Failed to save plan '{plan_id}': {e}"))
+    }
+
+    async fn delete_plan(&mut self, plan_id: &str) -> Result<(), String> {
+        self.sqlite_con
+            .execute(&format!(
+                "DELETE FROM {} WHERE profile_id = '{}' AND id = '{}';",
+                SqlTablesNames::Plans,
+                escape(&self.active_profile),
+                escape(plan_id),
+            ))
+            .map_err(|e| format!("Failed to delete plan '{plan_id}': {e}"))
+    }
+
+    async fn get_app_settings(&self) -> Result<AppSettings, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!("SELECT key, value FROM {};", SqlTablesNames::AppSettings),
+                |row| Ok((row.get_string(0)?, row.get_string(1)?)),
+            )
+            .map_err(|e| format!("Failed to read app settings: {e}"))?;
+        Ok(AppSettings::from_rows(rows))
+    }
+
+    async fn set_app_settings(&mut self, settings: &AppSettings) -> Result<(), String> {
+        let table = SqlTablesNames::AppSettings;
+        let inserts = settings
+            .to_rows()
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "INSERT INTO {table} (key, value) VALUES ('{}', '{}');",
+                    escape(key),
+                    escape(value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.sqlite_con
+            .execute(&format!("BEGIN; DELETE FROM {table}; {inserts} COMMIT;"))
+            .map_err(|e| {
+                let _ = self.sqlite_con.execute("ROLLBACK;");
+                format!("Failed to save app settings: {e}")
+            })
+    }
+
+    async fn get_usage_stats(&self) -> Result<UsageStats, String> {
+        let rows = self
+            .sqlite_con
+            .query_map(
+                &format!("SELECT name, count FROM {};", SqlTablesNames::UsageStats),
+                |row| Ok((row.get_string(0)?, row.get_i64(1)?)),
+            )
+            .map_err(|e| format!("Failed to read usage stats: {e}"))?;
+        Ok(UsageStats::from_rows(rows))
+    }
+
+    async fn set_usage_stats(&mut self, stats: &UsageStats) -> Result<(), String> {
+        let table = SqlTablesNames::UsageStats;
+        let inserts = stats
+            .to_rows()
+            .iter()
+            .map(|(name, count)| {
+                format!(
+                    "INSERT INTO {table} (name, count) VALUES ('{}', {count});",
+                    escape(name)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.sqlite_con
+            .execute(&format!("BEGIN; DELETE FROM {table}; {inserts} COMMIT;"))
+            .map_err(|e| {
+                let _ = self.sqlite_con.execute("ROLLBACK;");
+                format!("Failed to save usage stats: {e}")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::local_db::LocalProductDb;
+    use super::*;
+    use crate::bl::constraints_solver::{Fraction, SolutionEntry};
+    use crate::data_types::{
+        ActivityLevel, Allergen, AllowedUnits, AllowedUnitsType, DietFlag, MacroElements,
+        MacroElementsType, MealPhoto, MicroNutrients, MicroNutrientsType, ProductBuilder, Sex,
+        UnitData, UsageEvent,
+    };
+    use crate::database_access::{Database, DbEvent, DbSearchCriteria, MutableDatabase};
+    use approx::assert_relative_eq;
     use futures::executor::block_on;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
     use std::fs;
     use std::path::{Path, PathBuf};
     use std::sync::Once;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
-    fn assert_table_columns(
-        connection: &SqliteConnection,
-        table: &str,
-        expected_columns: &[String],
-    ) {
-        let count = connection
-            .query_first(
-                format!(
-                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='{table}';"
-                )
-                .as_str(),
-                |row| row.get_i64(0),
+    fn assert_table_columns(
+        connection: &SqliteConnection,
+        table: &str,
+        expected_columns: &[String],
+    ) {
+        let count = connection
+            .query_first(
+                format!(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='{table}';"
+                )
+                .as_str(),
+                |row| row.get_i64(0),
+            )
+            .expect("Failed to check table existence")
+            .unwrap_or(0);
+        assert!(count > 0, "Expected '{table}' table to exist");
+
+        let columns = connection
+            .query_map(
+                format!("SELECT name FROM pragma_table_info('{table}');").as_str(),
+                |row| row.get_string(0),
+            )
+            .expect("Failed to read table columns");
+
+        assert_eq!(
+            columns, expected_columns,
+            "Unexpected columns for table '{table}'"
+        );
+    }
+
+    #[test]
+    fn test_00_local_product_db_new_creates_schema() {
+        let db_path = unique_test_db_path();
+        let _cleanup_guard = FileCleanup::new(db_path.clone());
+        cleanup_existing_files(&db_path).expect("Failed to clean up test database files");
+        assert!(
+            !db_path.exists(),
+            "Expected database file to be absent before initialization"
+        );
+
+        if let Some(parent) = db_path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create database directory");
+        }
+
+        let path_str = db_path
+            .to_str()
+            .expect("Database path contains invalid UTF-8");
+
+        {
+            let _db = block_on(LocalProductDbConcrete::open(path_str))
+                .expect("Expected LocalProductDbConcrete::open to succeed for fresh database");
+        }
+
+        assert!(db_path.exists(), "Expected SQLite file to be created");
+
+        let connection = SqliteConnection::open(&db_path.to_string_lossy())
+            .expect("Failed to open created database");
+
+        let mut macro_columns = vec!["id".to_string()];
+        macro_columns.extend(
+            MacroElementsType::iter()
+                .filter(|m| *m != MacroElementsType::Calories)
+                .map(|m| m.to_string()),
+        );
+
+        let mut nutrient_columns = vec!["id".to_string()];
+        nutrient_columns.extend(MicroNutrientsType::iter().map(|m| m.to_string()));
+
+        let mut allowed_columns = vec!["id".to_string()];
+        for unit in AllowedUnitsType::iter() {
+            allowed_columns.push(unit.to_string());
+            allowed_columns.push(format!("{unit} divider"));
+        }
+
+        let product_columns = vec![
+            "id".to_string(),
+            "name".to_string(),
+            "brand".to_string(),
+            "price_per_100g".to_string(),
+            "currency".to_string(),
+            "negligible".to_string(),
+            "grams_per_ml".to_string(),
+            "unit_labels".to_string(),
+            "uuid".to_string(),
+            "grams_per_serving".to_string(),
+            "cooked_id".to_string(),
+            "cooked_yield".to_string(),
+            "calories_override".to_string(),
+            "ignore_alcohol_calories".to_string(),
+            "data_quality".to_string(),
+            "verified_data".to_string(),
+            "barcode".to_string(),
+        ];
+        assert_table_columns(&connection, "products", &product_columns);
+        assert_table_columns(&connection, "macro_elements", &macro_columns);
+        assert_table_columns(&connection, "micronutrients", &nutrient_columns);
+        assert_table_columns(&connection, "allowed_units", &allowed_columns);
+
+        drop(connection);
+    }
+
+    #[test]
+    fn test_01_local_product_db_new_preserves_existing_data() {
+        let test_db = TestDbGuard::create_empty().expect("Failed to create empty database");
+
+        {
+            let mut db = test_db.local_db();
+            let mut allowed_units: AllowedUnits = HashMap::new();
+            allowed_units.insert(
+                AllowedUnitsType::Gram,
+                UnitData {
+                    amount: 1,
+                    divider: 1,
+                },
+            );
+            let product = Product::new(
+                "Persisted".to_string(),
+                Some("BrandP".to_string()),
+                Box::new(MacroElements::new(
+                    1.0_f32, 0.5_f32, 2.0_f32, 1.0_f32, 3.0_f32,
+                )),
+                Box::default(),
+                allowed_units,
+            );
+
+            let product_id = product.id();
+            block_on(db.add_product(product_id.as_str(), product))
+                .expect("Expected add_product to succeed for persisted product");
+        }
+
+        let db = test_db.local_db();
+        let results = block_on(
+            db.get_products_matching_criteria(&[DbSearchCriteria::ById("Persisted".to_string())]),
+        );
+        let key = "Persisted (BrandP)";
+        assert!(
+            results.contains_key(key),
+            "Expected previously inserted product '{key}' to remain after reopening"
+        );
+    }
+
+    struct TestDbGuard {
+        path: PathBuf,
+        _cleanup_guard: FileCleanup,
+    }
+
+    impl TestDbGuard {
+        fn create_empty() -> Result<Self, String> {
+            let path = unique_test_db_path();
+            cleanup_existing_files(&path)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let cleanup_guard = FileCleanup::new(path.clone());
+            let path_str = path
+                .to_str()
+                .ok_or_else(|| "Database path contains invalid UTF-8".to_string())?;
+            let db = block_on(LocalProductDbConcrete::open(path_str))?;
+            drop(db);
+            Ok(Self {
+                path,
+                _cleanup_guard: cleanup_guard,
+            })
+        }
+
+        fn create_seeded() -> Result<Self, String> {
+            let guard = Self::create_empty()?;
+            {
+                let mut db = guard.local_db();
+                seed_products(&mut db)?;
+            }
+            Ok(guard)
+        }
+
+        fn connection(&self) -> SqliteConnection {
+            SqliteConnection::open(
+                self.path
+                    .to_str()
+                    .expect("Database path contains invalid UTF-8"),
+            )
+            .expect("Failed to reopen test database")
+        }
+
+        fn local_db(&self) -> LocalProductDbConcrete {
+            block_on(LocalProductDbConcrete::open(
+                self.path
+                    .to_str()
+                    .expect("Database path contains invalid UTF-8"),
+            ))
+            .expect("Failed to reopen seeded LocalProductDbConcrete")
+        }
+
+        // the public handle, with the checks made on top of the concrete database
+        fn handle(&self) -> LocalProductDb {
+            block_on(LocalProductDb::new(
+                self.path
+                    .to_str()
+                    .expect("Database path contains invalid UTF-8"),
+            ))
+            .expect("Failed to reopen seeded LocalProductDb")
+        }
+    }
+
+    impl Drop for TestDbGuard {
+        fn drop(&mut self) {
+            let _ = cleanup_existing_files(&self.path);
+        }
+    }
+
+    fn cleanup_existing_files(path: &Path) -> Result<(), String> {
+        use std::io::ErrorKind;
+
+        let attempt_remove = |candidate: &Path| -> Result<(), String> {
+            match fs::remove_file(candidate) {
+                Ok(()) => Ok(()),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+                Err(err) => Err(err.to_string()),
+            }
+        };
+
+        attempt_remove(path)?;
+        for suffix in ["-journal", "-wal", "-shm"] {
+            let mut os_string = path.as_os_str().to_os_string();
+            os_string.push(suffix);
+            let candidate: PathBuf = os_string.into();
+            attempt_remove(&candidate)?;
+        }
+        Ok(())
+    }
+
+    fn unique_test_db_path() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        static INIT_CLEANUP: Once = Once::new();
+        INIT_CLEANUP.call_once(|| {
+            if let Err(err) = cleanup_previous_test_databases() {
+                panic!("Failed to cleanup previous test databases: {err}");
+            }
+        });
+
+        let suffix = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        let base_path = Path::new(DATABASE_FILENAME);
+        let parent = base_path.parent();
+        let stem = base_path.file_stem().map_or_else(
+            || "test_db".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        let filename = if let Some(ext) = base_path.extension() {
+            format!("{}_{}.{}", stem, suffix, ext.to_string_lossy())
+        } else {
+            format!("{stem}_{suffix}")
+        };
+
+        match parent {
+            Some(dir) => dir.join(&filename),
+            None => PathBuf::from(&filename),
+        }
+    }
+
+    fn cleanup_previous_test_databases() -> Result<(), String> {
+        let base_path = Path::new(DATABASE_FILENAME);
+        let parent = base_path
+            .parent()
+            .ok_or_else(|| "DATABASE_FILENAME has no parent directory".to_string())?;
+        let stem = base_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| "DATABASE_FILENAME stem is not valid UTF-8".to_string())?;
+        let prefix = format!("{stem}_");
+        let extension = base_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(std::string::ToString::to_string);
+
+        let entries = fs::read_dir(parent).map_err(|e| e.to_string())?;
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !file_stem.starts_with(&prefix) {
+                continue;
+            }
+            if let Some(ref expected_ext) = extension {
+                let actual_ext = path.extension().and_then(|e| e.to_str());
+                if actual_ext != Some(expected_ext.as_str()) {
+                    continue;
+                }
+            }
+            cleanup_existing_files(&path)?;
+        }
+        Ok(())
+    }
+
+    fn seed_products(db: &mut LocalProductDbConcrete) -> Result<(), String> {
+        let mut apple_allowed: AllowedUnits = HashMap::new();
+        apple_allowed.insert(
+            AllowedUnitsType::Gram,
+            UnitData {
+                amount: 1,
+                divider: 1,
+            },
+        );
+        apple_allowed.insert(
+            AllowedUnitsType::Cup,
+            UnitData {
+                amount: 1,
+                divider: 2,
+            },
+        );
+        let mut apple_micro = Box::new(MicroNutrients::default());
+        apple_micro[MicroNutrientsType::Fiber] = Some(2.4_f32);
+        apple_micro[MicroNutrientsType::Zinc] = Some(0.1_f32);
+        apple_micro[MicroNutrientsType::Sodium] = Some(1.0_f32);
+        let apple = Product::new(
+            "Apple".to_string(),
+            Some("BrandA".to_string()),
+            Box::new(MacroElements::new(
+                0.2_f32, 0.1_f32, 14.0_f32, 10.0_f32, 0.3_f32,
+            )),
+            apple_micro,
+            apple_allowed,
+        );
+        let apple_id = apple.id();
+        block_on(db.add_product(apple_id.as_str(), apple))
+            .map_err(|e| format!("Failed to seed product {apple_id}: {e}"))?;
+
+        let mut banana_allowed: AllowedUnits = HashMap::new();
+        banana_allowed.insert(
+            AllowedUnitsType::Gram,
+            UnitData {
+                amount: 1,
+                divider: 1,
+            },
+        );
+        banana_allowed.insert(
+            AllowedUnitsType::Tablespoon,
+            UnitData {
+                amount: 2,
+                divider: 1,
+            },
+        );
+        banana_allowed.insert(
+            AllowedUnitsType::Custom,
+            UnitData {
+                amount: 50,
+                divider: 1,
+            },
+        );
+        let mut banana_micro = Box::new(MicroNutrients::default());
+        banana_micro[MicroNutrientsType::Fiber] = Some(2.6_f32);
+        banana_micro[MicroNutrientsType::Sodium] = Some(1.0_f32);
+        let banana = Product::new(
+            "Banana".to_string(),
+            None,
+            Box::new(MacroElements::new(
+                0.3_f32, 0.1_f32, 23.0_f32, 12.0_f32, 1.1_f32,
+            )),
+            banana_micro,
+            banana_allowed,
+        );
+        let banana_id = banana.id();
+        block_on(db.add_product(banana_id.as_str(), banana))
+            .map_err(|e| format!("Failed to seed product {banana_id}: {e}"))?;
+
+        Ok(())
+    }
+
+    struct FileCleanup {
+        path: PathBuf,
+    }
+
+    impl FileCleanup {
+        fn new(path: PathBuf) -> Self {
+            Self { path }
+        }
+    }
+
+    impl Drop for FileCleanup {
+        fn drop(&mut self) {
+            let _ = cleanup_existing_files(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_02_initialize_and_seed_database() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let conn = test_db.connection();
+        let product_count = conn
+            .query_first("SELECT COUNT(*) FROM products;", |row| row.get_i64(0))
+            .expect("Failed to count products")
+            .expect("Missing count row");
+        assert_eq!(product_count, 2);
+        let macro_columns = conn
+            .query_first(
+                "SELECT COUNT(*) FROM pragma_table_info('macro_elements');",
+                |row| row.get_i64(0),
+            )
+            .expect("Failed to count macro columns")
+            .expect("Missing macro columns row");
+        assert_eq!(macro_columns, 6);
+    }
+
+    #[test]
+    fn test_03_get_products_matching_criteria_returns_expected_product() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let db = test_db.local_db();
+        let results = block_on(
+            db.get_products_matching_criteria(&[DbSearchCriteria::ById("Apple".to_string())]),
+        );
+        assert_eq!(results.len(), 1);
+        let apple = results
+            .get("Apple (BrandA)")
+            .expect("Missing Apple product");
+        assert_eq!(apple.name(), "Apple");
+        assert_eq!(apple.brand(), Some("BrandA"));
+        assert_relative_eq!(apple.macro_elements[MacroElementsType::Fat], 0.2_f32);
+        assert_eq!(
+            apple.micro_nutrients[MicroNutrientsType::Fiber],
+            Some(2.4_f32)
+        );
+        assert_eq!(apple.allowed_units[&AllowedUnitsType::Cup].amount, 1);
+        assert_eq!(apple.allowed_units[&AllowedUnitsType::Cup].divider, 2);
+    }
+
+    #[test]
+    fn test_04_set_product_unit_updates_allowed_units_table() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        let result = block_on(db.set_product_unit(
+            "Apple (BrandA)",
+            AllowedUnitsType::Cup,
+            UnitData {
+                amount: 3,
+                divider: 2,
+            },
+        ));
+        assert!(result.is_ok());
+        let conn = test_db.connection();
+        let updated = conn
+            .query_first(
+                "SELECT cup FROM allowed_units WHERE id = 'Apple (BrandA)';",
+                |row| row.get_u16_optional(0),
+            )
+            .expect("Failed to fetch updated unit")
+            .expect("Missing cup value");
+        assert_eq!(updated, Some(3));
+        let updated_div = conn
+            .query_first(
+                "SELECT \"cup divider\" FROM allowed_units WHERE id = 'Apple (BrandA)';",
+                |row| row.get_u16_optional(0),
+            )
+            .expect("Failed to fetch updated divider")
+            .expect("Missing cup divider");
+        assert_eq!(updated_div, Some(2));
+    }
+
+    #[test]
+    fn test_05_add_product_inserts_all_related_rows() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        let mut allowed_units: AllowedUnits = HashMap::new();
+        allowed_units.insert(
+            AllowedUnitsType::Gram,
+            UnitData {
+                amount: 1,
+                divider: 1,
+            },
+        );
+        allowed_units.insert(
+            AllowedUnitsType::Cup,
+            UnitData {
+                amount: 2,
+                divider: 1,
+            },
+        );
+        let new_product = Product::new(
+            "Orange".to_string(),
+            Some("CitrusCo".to_string()),
+            Box::new(MacroElements::new(
+                0.1_f32, 0.05_f32, 11.0_f32, 9.0_f32, 1.0_f32,
+            )),
+            Box::default(),
+            allowed_units,
+        );
+        let new_id = new_product.id();
+        assert!(
+            block_on(db.add_product(new_id.as_str(), new_product)).is_ok(),
+            "Expected add_product to succeed"
+        );
+        let conn = test_db.connection();
+        let product_count = conn
+            .query_first(
+                format!("SELECT COUNT(*) FROM products WHERE id = '{new_id}';").as_str(),
+                |row| row.get_i64(0),
+            )
+            .expect("Failed to verify inserted product")
+            .expect("Missing product count");
+        assert_eq!(product_count, 1);
+    }
+
+    #[test]
+    fn test_06_update_product_modifies_macro_and_micro_nutr_values() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        let mut allowed_units: AllowedUnits = HashMap::new();
+        allowed_units.insert(
+            AllowedUnitsType::Gram,
+            UnitData {
+                amount: 1,
+                divider: 1,
+            },
+        );
+        allowed_units.insert(
+            AllowedUnitsType::Custom,
+            UnitData {
+                amount: 250,
+                divider: 1,
+            },
+        );
+        let mut micro = Box::new(MicroNutrients::default());
+        micro[MicroNutrientsType::Fiber] = Some(3.0_f32);
+        micro[MicroNutrientsType::Zinc] = Some(0.2_f32);
+        let updated = Product::new(
+            "Apple".to_string(),
+            Some("BrandA".to_string()),
+            Box::new(MacroElements::new(
+                0.5_f32, 0.2_f32, 15.0_f32, 11.0_f32, 0.9_f32,
+            )),
+            micro,
+            allowed_units,
+        );
+        assert!(
+            block_on(db.update_product("Apple (BrandA)", updated)).is_ok(),
+            "Expected update_product to succeed"
+        );
+        let conn = test_db.connection();
+        let fiber = conn
+            .query_first(
+                "SELECT Fiber FROM micronutrients WHERE id = 'Apple (BrandA)';",
+                |row| row.get_f32_optional(0),
+            )
+            .expect("Failed to fetch updated fiber")
+            .expect("Missing fiber value");
+        assert_eq!(fiber, Some(3.0_f32));
+    }
+
+    #[test]
+    fn test_07_delete_product_removes_all_rows() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        assert!(
+            block_on(db.delete_product("Banana")).is_ok(),
+            "Expected delete_product to succeed"
+        );
+        let conn = test_db.connection();
+        let count = conn
+            .query_first(
+                "SELECT COUNT(*) FROM products WHERE id = 'Banana';",
+                |row| row.get_i64(0),
+            )
+            .expect("Failed to check product deletion")
+            .expect("Missing count value");
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_08_new_returns_connection() {
+        let db_path = unique_test_db_path();
+        let _cleanup_guard = FileCleanup::new(db_path.clone());
+        cleanup_existing_files(&db_path).expect("Failed to remove pre-existing database file");
+        if let Some(parent) = db_path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create database directory");
+        }
+
+        let path_str = db_path
+            .to_str()
+            .expect("Database path contains invalid UTF-8");
+        let result = block_on(LocalProductDbConcrete::open(path_str));
+        assert!(
+            result.is_ok(),
+            "Expected LocalProductDbConcrete::open to succeed"
+        );
+        drop(result);
+    }
+
+    #[test]
+    fn test_09_update_product_inserts_when_missing() {
+        let test_db = TestDbGuard::create_empty().expect("Failed to prepare empty database");
+        let mut db = test_db.local_db();
+
+        let mut allowed_units: AllowedUnits = HashMap::new();
+        allowed_units.insert(
+            AllowedUnitsType::Gram,
+            UnitData {
+                amount: 1,
+                divider: 1,
+            },
+        );
+        allowed_units.insert(
+            AllowedUnitsType::Custom,
+            UnitData {
+                amount: 250,
+                divider: 2,
+            },
+        );
+
+        let mut micro = Box::new(MicroNutrients::default());
+        micro[MicroNutrientsType::Fiber] = Some(1.5_f32);
+
+        let product = Product::new(
+            "Kiwi".to_string(),
+            Some("FreshCo".to_string()),
+            Box::new(MacroElements::new(
+                0.4_f32, 0.1_f32, 15.0_f32, 9.0_f32, 1.1_f32,
+            )),
+            micro,
+            allowed_units,
+        );
+
+        let product_id = product.id();
+        assert!(
+            block_on(db.update_product(product_id.as_str(), product)).is_ok(),
+            "Expected update_product to insert when missing"
+        );
+
+        let conn = test_db.connection();
+        let name = conn
+            .query_first(
+                format!("SELECT name FROM products WHERE id = '{product_id}';").as_str(),
+                |row| row.get_string(0),
+            )
+            .expect("Failed to read inserted product")
+            .expect("Missing inserted product");
+        assert_eq!(name, "Kiwi");
+
+        let fiber = conn
+            .query_first(
+                format!("SELECT Fiber FROM micronutrients WHERE id = '{product_id}';").as_str(),
+                |row| row.get_f32_optional(0),
+            )
+            .expect("Failed to fetch inserted micronutrient")
+            .expect("Missing inserted micronutrient value");
+        assert_eq!(fiber, Some(1.5_f32));
+
+        let custom_amount = conn
+            .query_first(
+                format!("SELECT custom FROM allowed_units WHERE id = '{product_id}';").as_str(),
+                |row| row.get_u16_optional(0),
+            )
+            .expect("Failed to fetch inserted allowed unit")
+            .expect("Missing custom unit amount");
+        assert_eq!(custom_amount, Some(250));
+
+        let gram_divider = conn
+            .query_first(
+                format!("SELECT \"gram divider\" FROM allowed_units WHERE id = '{product_id}';")
+                    .as_str(),
+                |row| row.get_u16_optional(0),
+            )
+            .expect("Failed to fetch gram divider")
+            .expect("Missing gram divider");
+        assert_eq!(gram_divider, Some(1));
+    }
+
+    #[test]
+    fn test_10_import_products_overwrites_branded_product() {
+        use crate::database_access::{ConflictStrategy, ImportOutcome};
+
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        let csv = "name,brand,Fat,Protein\nApple,BrandA,1.5,0.3\nCherry,,0.2,1\n";
+
+        let report = block_on(db.import_products(csv, ConflictStrategy::Overwrite))
+            .expect("Expected import to succeed");
+        assert_eq!(report.count(&ImportOutcome::Overwritten), 1);
+        assert_eq!(report.count(&ImportOutcome::Added), 1);
+
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_relative_eq!(apple.macro_elements[MacroElementsType::Fat], 1.5_f32);
+        assert!(block_on(db.get_product_by_id("Cherry")).is_some());
+    }
+
+    #[test]
+    fn test_59_failed_bulk_add_fails_every_repeated_import_entry() {
+        use crate::database_access::{ConflictStrategy, ImportOutcome};
+
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        test_db
+            .connection()
+            .execute(
+                "CREATE TRIGGER reject_pear BEFORE INSERT ON products WHEN NEW.name = 'Pear' \
+                 BEGIN SELECT RAISE(ABORT, 'pears are rejected'); END;",
+            )
+            .expect("Failed to create trigger");
+        let mut db = test_db.local_db();
+        let csv = "name,Fat\nPear,1\nCherry,0.2\nPear,4\nPear,2\n";
+
+        for strategy in [ConflictStrategy::Overwrite, ConflictStrategy::Merge] {
+            let report =
+                block_on(db.import_products(csv, strategy)).expect("Expected import to succeed");
+            let pear_outcomes: Vec<&ImportOutcome> = report
+                .entries
+                .iter()
+                .filter(|e| e.product_id == "Pear")
+                .map(|e| &e.outcome)
+                .collect();
+            assert_eq!(pear_outcomes.len(), 3);
+            assert!(
+                pear_outcomes
+                    .iter()
+                    .all(|o| matches!(o, ImportOutcome::Failed(_))),
+                "{pear_outcomes:?}"
+            );
+            assert!(block_on(db.get_product_by_id("Pear")).is_none());
+        }
+        assert!(block_on(db.get_product_by_id("Cherry")).is_some());
+    }
+
+    #[test]
+    fn test_11_data_quality_summary_uses_aggregate_query() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        let plain = Product::new(
+            "Plain".to_string(),
+            None,
+            Box::new(MacroElements::new(
+                0.0_f32, 0.0_f32, 0.0_f32, 0.0_f32, 0.0_f32,
+            )),
+            Box::default(),
+            HashMap::new(),
+        );
+        block_on(db.add_product("Plain", plain.clone())).expect("Expected add_product to succeed");
+
+        let summary = block_on(db.data_quality_summary()).expect("Expected summary query");
+        assert_eq!(
+            summary,
+            DataQualitySummary {
+                total_products: 3,
+                without_micro_nutrients: 1,
+                incomplete_micro_nutrients: 3,
+                without_extra_units: 1,
+                without_brand: 2,
+                without_barcode: 3,
+                unused_in_plans: 3,
+            }
+        );
+
+        let week = Solution {
+            solution: SolutionEntry::Product {
+                product: plain,
+                amount_grams: 100.0,
+                unit: AllowedUnitsType::Gram,
+                amount_unit: Fraction {
+                    numerator: 1,
+                    denominator: 1,
+                },
+            },
+        };
+        block_on(db.save_plan("week", &week)).expect("Expected plan to be saved");
+        let summary = block_on(db.data_quality_summary()).expect("Expected summary query");
+        assert_eq!(summary.unused_in_plans, 2);
+
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(db.set_profile(&partner)).expect("Expected profile to be added");
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Expected apple");
+        let partner_week = Solution {
+            solution: SolutionEntry::Product {
+                product: apple,
+                amount_grams: 150.0,
+                unit: AllowedUnitsType::Gram,
+                amount_unit: Fraction {
+                    numerator: 3,
+                    denominator: 2,
+                },
+            },
+        };
+        block_on(db.save_plan("week", &partner_week)).expect("Expected plan to be saved");
+        block_on(db.set_active_profile(DEFAULT_PROFILE_ID)).expect("Expected profile switch back");
+        let summary = block_on(db.data_quality_summary()).expect("Expected summary query");
+        assert_eq!(summary.unused_in_plans, 1);
+    }
+
+    #[test]
+    fn test_12_price_is_persisted_and_old_schema_is_migrated() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN currency;")
+                .expect("Failed to drop currency column");
+            conn.execute("ALTER TABLE products DROP COLUMN price_per_100g;")
+                .expect("Failed to drop price column");
+            conn.execute("DROP TABLE schema_version;")
+                .expect("Failed to drop schema version table");
+        }
+
+        let mut db = test_db.local_db();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.price, None);
+
+        let price = Price {
+            per_100g: 0.75,
+            currency: "PLN".to_string(),
+        };
+        block_on(db.update_product("Apple (BrandA)", apple.with_price(Some(price.clone()))))
+            .expect("Expected update_product to succeed");
+
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.price, Some(price));
+
+        // the currency is free text
+        let quoted = Price {
+            per_100g: 0.5,
+            currency: "z'l".to_string(),
+        };
+        block_on(db.update_product("Apple (BrandA)", apple.with_price(Some(quoted.clone()))))
+            .expect("Expected update_product to succeed");
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.price, Some(quoted));
+    }
+
+    #[test]
+    fn test_13_legacy_database_gets_unit_dividers() {
+        let path = unique_test_db_path();
+        cleanup_existing_files(&path).expect("Failed to cleanup test database");
+        let _cleanup_guard = FileCleanup::new(path.clone());
+        let path_str = path.to_str().expect("Database path contains invalid UTF-8");
+
+        {
+            let conn = SqliteConnection::open(path_str).expect("Failed to create legacy database");
+            let macro_cols = MacroElementsType::iter()
+                .filter(|m| *m != MacroElementsType::Calories)
+                .map(|m| format!(", \"{m}\" FLOAT NOT NULL"))
+                .collect::<Vec<_>>()
+                .concat();
+            let micronutrient_cols = MicroNutrientsType::iter()
+                .map(|m| format!(", \"{m}\" FLOAT"))
+                .collect::<Vec<_>>()
+                .concat();
+            let unit_cols = AllowedUnitsType::iter()
+                .map(|u| format!(", \"{u}\" INTEGER"))
+                .collect::<Vec<_>>()
+                .concat();
+            for sql in [
+                "CREATE TABLE products (id TEXT NOT NULL PRIMARY KEY, name CHAR NOT NULL, brand CHAR);"
+                    .to_string(),
+                format!("CREATE TABLE macro_elements (id TEXT NOT NULL PRIMARY KEY{macro_cols});"),
+                format!("CREATE TABLE micronutrients (id TEXT NOT NULL PRIMARY KEY{micronutrient_cols});"),
+                format!("CREATE TABLE allowed_units (id TEXT NOT NULL PRIMARY KEY{unit_cols});"),
+                "INSERT INTO products VALUES ('Oats', 'Oats', NULL);".to_string(),
+                "INSERT INTO macro_elements VALUES ('Oats', 7, 1, 60, 1, 13);".to_string(),
+                "INSERT INTO micronutrients (id) VALUES ('Oats');".to_string(),
+                "INSERT INTO allowed_units (id, \"gram\", \"cup\") VALUES ('Oats', 1, 80);"
+                    .to_string(),
+            ] {
+                conn.execute(&sql).expect("Failed to prepare legacy database");
+            }
+        }
+
+        let db = block_on(LocalProductDbConcrete::open(path_str))
+            .expect("Expected legacy database to open");
+        let oats = block_on(db.get_product_by_id("Oats")).expect("Missing migrated product");
+        assert_eq!(
+            oats.allowed_units.get(&AllowedUnitsType::Cup),
+            Some(&UnitData {
+                amount: 80,
+                divider: 1
+            })
+        );
+        assert_eq!(
+            oats.allowed_units.get(&AllowedUnitsType::Gram),
+            Some(&UnitData {
+                amount: 1,
+                divider: 1
+            })
+        );
+        assert_eq!(oats.allowed_units.len(), 2);
+        assert_relative_eq!(oats.macro_elements[MacroElementsType::Protein], 13.0_f32);
+    }
+
+    #[test]
+    fn test_14_missing_micro_nutrient_columns_are_added_on_open() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE micronutrients DROP COLUMN \"Vitamin B12\";")
+                .expect("Failed to drop column");
+            conn.execute("ALTER TABLE micronutrients DROP COLUMN \"Iron\";")
+                .expect("Failed to drop column");
+        }
+
+        let mut db = test_db.local_db();
+        let mut apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.micro_nutrients[MicroNutrientsType::Iron], None);
+
+        apple.micro_nutrients[MicroNutrientsType::Iron] = Some(0.12);
+        block_on(db.update_product("Apple (BrandA)", apple)).expect("Expected update to succeed");
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.micro_nutrients[MicroNutrientsType::Iron], Some(0.12));
+    }
+
+    fn stored_schema_version(conn: &SqliteConnection) -> Vec<i64> {
+        conn.query_map(
+            "SELECT version FROM schema_version ORDER BY version;",
+            |row| row.get_i64(0),
+        )
+        .expect("Failed to read schema version")
+    }
+
+    #[test]
+    fn test_15_schema_version_is_recorded() {
+        let latest = i64::from(migrations::latest_version());
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        assert_eq!(stored_schema_version(&test_db.connection()), vec![latest]);
+
+        {
+            let conn = test_db.connection();
+            conn.execute("DROP TABLE schema_version;")
+                .expect("Failed to drop schema version table");
+        }
+        let _db = test_db.local_db();
+        let expected: Vec<i64> = (1..=latest).collect();
+        assert_eq!(stored_schema_version(&test_db.connection()), expected);
+
+        // applied migrations are not repeated, only enum columns are synced
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN currency;")
+                .expect("Failed to drop currency column");
+        }
+        let _db = test_db.local_db();
+        let conn = test_db.connection();
+        let has_currency = conn
+            .query_first(
+                "SELECT COUNT(*) FROM pragma_table_info('products') WHERE name = 'currency';",
+                |row| row.get_i64(0),
+            )
+            .expect("Failed to read columns");
+        assert_eq!(has_currency, Some(0));
+        assert_eq!(stored_schema_version(&conn), expected);
+    }
+
+    #[test]
+    fn test_16_negligible_flag_is_persisted_and_migrated() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN negligible;")
+                .expect("Failed to drop negligible column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 3;")
+                .expect("Failed to reset schema version");
+        }
+
+        let mut db = test_db.local_db();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert!(!apple.negligible);
+
+        block_on(db.update_product("Apple (BrandA)", apple.with_negligible(true)))
+            .expect("Expected update_product to succeed");
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert!(apple.negligible);
+    }
+
+    #[test]
+    fn test_17_user_profile_round_trip() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        assert_eq!(block_on(db.get_user_profile()), Ok(None));
+
+        let mut profile = UserProfile {
+            age_years: 35,
+            sex: Sex::Male,
+            weight_kg: 82.5,
+            height_cm: 178.0,
+            activity_level: ActivityLevel::VeryActive,
+        };
+        block_on(db.set_user_profile(&profile)).expect("Expected profile to be saved");
+        profile.weight_kg = 80.0;
+        block_on(db.set_user_profile(&profile)).expect("Expected profile to be updated");
+        assert_eq!(block_on(db.get_user_profile()), Ok(Some(profile)));
+
+        profile.age_years = 5;
+        assert!(block_on(db.set_user_profile(&profile)).is_err());
+    }
+
+    #[test]
+    fn test_18_usage_stats_round_trip() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        assert_eq!(block_on(db.get_usage_stats()), Ok(UsageStats::default()));
+
+        let mut stats = UsageStats {
+            enabled: true,
+            ..UsageStats::default()
+        };
+        stats.record(&UsageEvent::Solve);
+        stats.record(&UsageEvent::ViewOpened("chef's view".to_string()));
+        block_on(db.set_usage_stats(&stats)).expect("Expected usage stats to be saved");
+        assert_eq!(block_on(db.get_usage_stats()), Ok(stats.clone()));
+
+        stats.wipe();
+        block_on(db.set_usage_stats(&stats)).expect("Expected usage stats to be wiped");
+        assert_eq!(block_on(db.get_usage_stats()), Ok(stats));
+    }
+
+    #[test]
+    fn test_19_shared_handle_is_reused_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LocalProductDb>();
+
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let path = test_db
+            .path
+            .to_str()
+            .expect("Database path contains invalid UTF-8")
+            .to_string();
+        let first = block_on(LocalProductDb::shared(&path)).expect("Expected shared db to open");
+        let second = block_on(LocalProductDb::shared(&path)).expect("Expected shared db to open");
+        assert!(first.shares_connection_with(&second));
+
+        let mut writer = first.clone();
+        std::thread::spawn(move || {
+            let product = block_on(writer.get_product_by_id("Apple (BrandA)"))
+                .expect("Expected seeded product");
+            block_on(writer.update_product("Apple (BrandA)", product.with_negligible(true)))
+                .expect("Expected update from another thread to succeed");
+        })
+        .join()
+        .expect("Writer thread panicked");
+
+        let product =
+            block_on(second.get_product_by_id("Apple (BrandA)")).expect("Expected seeded product");
+        assert!(product.negligible);
+    }
+
+    #[test]
+    fn test_20_get_products_matching_nutrient_criteria() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let db = test_db.local_db();
+        let search = |criteria: &[DbSearchCriteria]| {
+            block_on(db.get_products_matching_criteria(criteria))
+                .into_keys()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search(&[DbSearchCriteria::ByNutrientAtLeast(
+                MicroNutrientsType::Fiber.into(),
+                2.5
+            )]),
+            vec!["Banana"]
+        );
+        // Apple has ~59 kcal, Banana too much sugar
+        assert_eq!(
+            search(&[
+                DbSearchCriteria::ByNutrientAtMost(MacroElementsType::Sugar.into(), 11.0),
+                DbSearchCriteria::ByNutrientAtLeast(MacroElementsType::Calories.into(), 50.0),
+            ]),
+            vec!["Apple (BrandA)"]
+        );
+        // Banana has unknown zinc and is left out
+        assert_eq!(
+            search(&[DbSearchCriteria::ByNutrientAtMost(
+                MicroNutrientsType::Zinc.into(),
+                1.0
+            )]),
+            vec!["Apple (BrandA)"]
+        );
+        assert!(
+            search(&[DbSearchCriteria::ByNutrientAtMost(
+                MicroNutrientsType::Zinc.into(),
+                f32::NAN
+            )])
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_21_dietary_flags_are_persisted_migrated_and_searchable() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("DROP TABLE product_flags;")
+                .expect("Failed to drop product_flags table");
+            conn.execute("DELETE FROM schema_version WHERE version >= 6;")
+                .expect("Failed to reset schema version");
+        }
+
+        let mut db = test_db.local_db();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert!(apple.allergens.is_empty());
+        let apple = apple
+            .with_allergens(BTreeSet::from([Allergen::Nuts, Allergen::Soy]))
+            .with_diet_flags(BTreeSet::from([DietFlag::Vegan]));
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+        assert_eq!(
+            block_on(db.get_product_by_id("Apple (BrandA)")).as_ref(),
+            Some(&apple)
+        );
+
+        let search = |allergens: Vec<Allergen>| {
+            block_on(
+                db.get_products_matching_criteria(&[DbSearchCriteria::ExcludeAllergens(allergens)]),
+            )
+            .into_keys()
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(search(vec![Allergen::Soy]), vec!["Banana"]);
+        assert_eq!(search(vec![Allergen::Gluten]).len(), 2);
+        assert_eq!(search(Vec::new()).len(), 2);
+
+        block_on(db.delete_product("Apple (BrandA)")).expect("Expected delete to succeed");
+        let remaining = test_db
+            .connection()
+            .query_first("SELECT COUNT(*) FROM product_flags;", |row| row.get_i64(0))
+            .expect("Failed to count flags");
+        assert_eq!(remaining, Some(0));
+    }
+
+    #[test]
+    fn test_22_add_products_bulk_reports_each_product() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.local_db();
+        let banana = block_on(db.get_product_by_id("Banana")).expect("Missing Banana");
+        let kiwi = Product::new(
+            "Kiwi".to_string(),
+            None,
+            banana.macro_elements.clone(),
+            Box::default(),
+            HashMap::new(),
+        );
+        let pear = Product::new(
+            "Pear".to_string(),
+            None,
+            banana.macro_elements.clone(),
+            Box::default(),
+            HashMap::new(),
+        );
+
+        let report = block_on(db.add_products_bulk(vec![
+            ("Kiwi".to_string(), kiwi),
+            ("Banana".to_string(), banana.clone().with_negligible(true)),
+            ("Pear".to_string(), pear),
+        ]))
+        .expect("Expected bulk insert to run");
+
+        assert_eq!(report.added(), 2);
+        assert_eq!(
+            report
+                .failures()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            vec!["Banana"]
+        );
+        assert!(block_on(db.get_product_by_id("Kiwi")).is_some());
+        assert!(block_on(db.get_product_by_id("Pear")).is_some());
+        // the failed product is rolled back completely
+        assert_eq!(block_on(db.get_product_by_id("Banana")), Some(banana));
+    }
+
+    #[test]
+    fn test_23_invalid_products_are_not_stored() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        let banana = block_on(db.get_product_by_id("Banana")).expect("Missing Banana");
+        let mut broken = banana.clone();
+        broken.macro_elements = Box::new(MacroElements::new(1.0, 5.0, 10.0, 2.0, 1.0));
+
+        let err = block_on(db.update_product("Banana", broken.clone()))
+            .expect_err("Expected update to fail");
+        assert_eq!(err, "Saturated fat (5g) exceeds fat (1g)");
+        assert_eq!(
+            block_on(db.get_product_by_id("Banana")),
+            Some(banana.clone())
+        );
+
+        let kiwi = Product::new(
+            "Kiwi".to_string(),
+            None,
+            banana.macro_elements.clone(),
+            Box::default(),
+            HashMap::new(),
+        );
+        let report = block_on(db.add_products_bulk(vec![
+            ("Broken".to_string(), broken),
+            ("Kiwi".to_string(), kiwi),
+        ]))
+        .expect("Expected bulk insert to run");
+        assert_eq!(report.entries[0].0, "Broken");
+        assert!(report.entries[0].1.is_err());
+        assert_eq!(report.entries[1], ("Kiwi".to_string(), Ok(())));
+        assert!(block_on(db.get_product_by_id("Broken")).is_none());
+    }
+
+    #[test]
+    fn test_24_user_profiles_are_kept_per_profile() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            // single user schema of version 6, with a saved profile
+            let conn = test_db.connection();
+            conn.execute(
+                "DROP TABLE user_profile; DROP TABLE profiles; \
+                 CREATE TABLE user_profile (id INTEGER NOT NULL PRIMARY KEY CHECK (id = 1), \
+                 age_years INTEGER NOT NULL, sex TEXT NOT NULL, weight_kg FLOAT NOT NULL, \
+                 height_cm FLOAT NOT NULL, activity_level TEXT NOT NULL); \
+                 INSERT INTO user_profile VALUES (1, 35, 'male', 82.5, 178.0, 'very active'); \
+                 DELETE FROM schema_version WHERE version >= 7;",
             )
-            .expect("Failed to check table existence")
-            .unwrap_or(0);
-        assert!(count > 0, "Expected '{table}' table to exist");
+            .expect("Failed to restore single user schema");
+        }
+        let mut db = test_db.handle();
+        let stored = block_on(db.get_user_profile())
+            .expect("Expected profile to be read")
+            .expect("Expected profile to be migrated");
+        assert_eq!(stored.age_years, 35);
+
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(db.set_profile(&partner)).expect("Expected profile to be added");
+        assert_eq!(
+            block_on(db.get_profiles()),
+            Ok(vec![Profile::default_profile(), partner.clone()])
+        );
+        assert!(block_on(db.set_active_profile("nobody")).is_err());
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_user_profile()), Ok(None));
+        let partner_profile = UserProfile {
+            age_years: 32,
+            sex: Sex::Female,
+            weight_kg: 60.0,
+            height_cm: 165.0,
+            activity_level: ActivityLevel::Moderate,
+        };
+        block_on(db.set_user_profile(&partner_profile)).expect("Expected profile to be saved");
+        assert!(block_on(db.delete_profile("partner")).is_err());
 
-        let columns = connection
-            .query_map(
-                format!("SELECT name FROM pragma_table_info('{table}');").as_str(),
-                |row| row.get_string(0),
+        block_on(db.set_active_profile(DEFAULT_PROFILE_ID)).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_user_profile()), Ok(Some(stored)));
+        assert!(block_on(db.delete_profile(DEFAULT_PROFILE_ID)).is_err());
+        block_on(db.delete_profile("partner")).expect("Expected profile to be deleted");
+        assert_eq!(
+            block_on(db.get_profiles()),
+            Ok(vec![Profile::default_profile()])
+        );
+        let leftover = test_db
+            .connection()
+            .query_first("SELECT COUNT(*) FROM user_profile;", |row| row.get_i64(0))
+            .expect("Expected count query to run");
+        assert_eq!(leftover, Some(1));
+    }
+
+    #[test]
+    fn test_25_updates_archive_previous_product_versions() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            // schema of version 7, without the history table
+            let conn = test_db.connection();
+            conn.execute(
+                "DROP TABLE products_history; DELETE FROM schema_version WHERE version >= 8;",
             )
-            .expect("Failed to read table columns");
+            .expect("Failed to restore schema without history");
+        }
+        let mut db = test_db.handle();
+        let original = block_on(db.get_product_by_id("Banana")).expect("Expected seeded product");
+        assert_eq!(block_on(db.get_product_history("Banana")), Ok(Vec::new()));
+
+        let mut reformulated = original.clone();
+        reformulated
+            .macro_elements
+            .set(MacroElementsType::Sugar, 5.0)
+            .expect("Expected sugar to be set");
+        block_on(db.update_product("Banana", reformulated.clone())).expect("Expected update");
+        // unchanged save, nothing to archive
+        block_on(db.update_product("Banana", reformulated.clone())).expect("Expected update");
+        let mut priced = reformulated.clone();
+        priced.price = Some(Price {
+            per_100g: 0.5,
+            currency: "PLN".to_string(),
+        });
+        block_on(db.update_product("Banana", priced)).expect("Expected update");
 
+        let history = block_on(db.get_product_history("Banana")).expect("Expected history");
+        let versions: Vec<&Product> = history.iter().map(|version| &version.product).collect();
+        assert_eq!(versions, vec![&reformulated, &original]);
+        assert!(history[0].archived_at >= history[1].archived_at);
+        assert!(history[1].archived_at > 0);
+
+        block_on(db.delete_product("Banana")).expect("Expected delete");
         assert_eq!(
-            columns, expected_columns,
-            "Unexpected columns for table '{table}'"
+            block_on(db.get_product_history("Banana")).map(|history| history.len()),
+            Ok(2)
         );
     }
 
     #[test]
-    fn test_00_local_product_db_new_creates_schema() {
-        let db_path = unique_test_db_path();
-        let _cleanup_guard = FileCleanup::new(db_path.clone());
-        cleanup_existing_files(&db_path).expect("Failed to clean up test database files");
-        assert!(
-            !db_path.exists(),
-            "Expected database file to be absent before initialization"
+    fn test_26_beverage_log_is_per_profile() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        let beer = BeverageEntry {
+            product_id: Some("Beer".into()),
+            ..BeverageEntry::water(200, 500)
+        };
+        block_on(db.add_beverage_entry(&beer)).expect("Expected drink to be logged");
+        block_on(db.add_beverage_entry(&BeverageEntry::water(100, 250)))
+            .expect("Expected drink to be logged");
+        block_on(db.add_beverage_entry(&BeverageEntry::water(300, 250)))
+            .expect("Expected drink to be logged");
+        assert!(block_on(db.add_beverage_entry(&BeverageEntry::water(400, 0))).is_err());
+
+        assert_eq!(
+            block_on(db.get_beverage_entries(100, 300)),
+            Ok(vec![BeverageEntry::water(100, 250), beer])
         );
 
-        if let Some(parent) = db_path.parent() {
-            fs::create_dir_all(parent).expect("Failed to create database directory");
-        }
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(db.set_profile(&partner)).expect("Expected profile to be added");
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_beverage_entries(0, 1000)), Ok(Vec::new()));
+    }
 
-        let path_str = db_path
-            .to_str()
-            .expect("Database path contains invalid UTF-8");
+    #[test]
+    fn test_27_app_settings_round_trip() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        assert_eq!(block_on(db.get_app_settings()), Ok(AppSettings::default()));
+
+        let settings = AppSettings {
+            language: "pl-PL".to_string(),
+            sidebar_width: 256.0,
+            selected_db: Some("it's mine.db".to_string()),
+            energy_unit: crate::data_types::EnergyUnit::Kj,
+        };
+        block_on(db.set_app_settings(&settings)).expect("Expected settings to be saved");
+        assert_eq!(block_on(db.get_app_settings()), Ok(settings.clone()));
 
-        {
-            let _db = block_on(LocalProductDbConcrete::new(path_str))
-                .expect("Expected LocalProductDbConcrete::new to succeed for fresh database");
+        let invalid = AppSettings {
+            language: String::new(),
+            ..settings.clone()
+        };
+        assert!(block_on(db.set_app_settings(&invalid)).is_err());
+        let cleared = AppSettings {
+            selected_db: None,
+            ..settings
+        };
+        block_on(db.set_app_settings(&cleared)).expect("Expected settings to be saved");
+        assert_eq!(block_on(db.get_app_settings()), Ok(cleared));
+    }
+
+    #[test]
+    fn test_28_bulk_edit_and_delete_products() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        let ids = vec!["Apple (BrandA)".to_string(), "Banana".to_string()];
+        let tablespoon = UnitData {
+            amount: 15,
+            divider: 1,
+        };
+        block_on(db.edit_products(
+            &ids,
+            &ProductEdit::AddUnit(AllowedUnitsType::Tablespoon, tablespoon),
+        ))
+        .expect("Expected bulk edit");
+        for id in &ids {
+            let product = block_on(db.get_product_by_id(id)).expect("Expected product");
+            assert_eq!(
+                product.allowed_units.get(&AllowedUnitsType::Tablespoon),
+                Some(&tablespoon)
+            );
         }
 
-        assert!(db_path.exists(), "Expected SQLite file to be created");
+        // a missing product rolls back the whole edit
+        let with_missing = vec!["Banana".to_string(), "Missing".to_string()];
+        assert!(
+            block_on(db.edit_products(&with_missing, &ProductEdit::SetNegligible(true))).is_err()
+        );
+        let banana = block_on(db.get_product_by_id("Banana")).expect("Expected product");
+        assert!(!banana.negligible);
+        assert!(
+            block_on(db.edit_products(&ids, &ProductEdit::SetBrand(Some(String::new())))).is_err()
+        );
 
-        let connection = SqliteConnection::open(&db_path.to_string_lossy())
-            .expect("Failed to open created database");
+        block_on(db.delete_products(&with_missing)).expect("Expected bulk delete");
+        assert!(block_on(db.get_product_by_id("Banana")).is_none());
+        assert!(block_on(db.get_product_by_id("Apple (BrandA)")).is_some());
+        block_on(db.delete_products(&[])).expect("Expected empty delete to succeed");
+    }
 
-        let mut macro_columns = vec!["id".to_string()];
-        macro_columns.extend(
-            MacroElementsType::iter()
-                .filter(|m| *m != MacroElementsType::Calories)
-                .map(|m| m.to_string()),
+    #[test]
+    fn test_29_eaten_products_per_day() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        let banana = EatenItem {
+            meal: "Breakfast".to_string(),
+            product_id: "Banana".into(),
+            grams: 120.5,
+        };
+        let apple = EatenItem {
+            meal: "Lunch".to_string(),
+            product_id: "Apple (BrandA)".into(),
+            grams: 80.0,
+        };
+        block_on(db.mark_eaten(20_000, &banana)).expect("Expected item to be marked");
+        block_on(db.mark_eaten(20_000, &apple)).expect("Expected item to be marked");
+        let more_banana = EatenItem {
+            grams: 150.0,
+            ..banana.clone()
+        };
+        block_on(db.mark_eaten(20_000, &more_banana)).expect("Expected amount to be replaced");
+        assert!(
+            block_on(db.mark_eaten(
+                20_000,
+                &EatenItem {
+                    grams: -1.0,
+                    ..apple.clone()
+                }
+            ))
+            .is_err()
+        );
+        assert_eq!(
+            block_on(db.get_eaten(20_000)),
+            Ok(vec![more_banana, apple.clone()])
         );
+        assert_eq!(block_on(db.get_eaten(20_001)), Ok(Vec::new()));
 
-        let mut nutrient_columns = vec!["id".to_string()];
-        nutrient_columns.extend(MicroNutrientsType::iter().map(|m| m.to_string()));
+        block_on(db.unmark_eaten(20_000, "Breakfast", "Banana"))
+            .expect("Expected item to be unmarked");
+        assert_eq!(block_on(db.get_eaten(20_000)), Ok(vec![apple]));
+    }
 
-        let mut allowed_columns = vec!["id".to_string()];
-        for unit in AllowedUnitsType::iter() {
-            allowed_columns.push(unit.to_string());
-            allowed_columns.push(format!("{unit} divider"));
+    #[test]
+    fn test_30_volume_units_and_labels_are_persisted_and_migrated() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN grams_per_ml;")
+                .expect("Failed to drop grams_per_ml column");
+            conn.execute("ALTER TABLE products DROP COLUMN unit_labels;")
+                .expect("Failed to drop unit_labels column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 12;")
+                .expect("Failed to reset schema version");
         }
 
-        let product_columns = vec!["id".to_string(), "name".to_string(), "brand".to_string()];
-        assert_table_columns(&connection, "products", &product_columns);
-        assert_table_columns(&connection, "macro_elements", &macro_columns);
-        assert_table_columns(&connection, "micronutrients", &nutrient_columns);
-        assert_table_columns(&connection, "allowed_units", &allowed_columns);
+        let mut db = test_db.local_db();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.grams_per_ml, None);
+        assert!(apple.unit_labels.is_empty());
+
+        let mut juice = apple
+            .with_grams_per_ml(Some(1.05))
+            .with_unit_label(AllowedUnitsType::Box, "bottle's worth");
+        juice
+            .set_volume_unit(AllowedUnitsType::Cup, None)
+            .expect("Expected cup to be defined by volume");
+        block_on(db.update_product("Apple (BrandA)", juice.clone()))
+            .expect("Expected update_product to succeed");
+        assert_eq!(
+            block_on(db.get_product_by_id("Apple (BrandA)")),
+            Some(juice)
+        );
+    }
 
-        drop(connection);
+    #[test]
+    fn test_31_renamed_product_keeps_its_uuid() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN uuid;")
+                .expect("Failed to drop uuid column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 13;")
+                .expect("Failed to reset schema version");
+        }
+
+        let mut db = test_db.local_db();
+        let uuid = block_on(db.get_product_uuid("Apple (BrandA)"))
+            .expect("Expected get_product_uuid to succeed")
+            .expect("Expected migration to backfill the uuid");
+        assert_ne!(
+            block_on(db.get_product_uuid("Banana")).expect("Expected uuid read to succeed"),
+            Some(uuid.clone())
+        );
+
+        let renamed = ProductBuilder::new("Green Apple")
+            .brand("BrandA")
+            .carbs(14.0)
+            .build()
+            .expect("Expected valid product");
+        block_on(db.rename_product("Apple (BrandA)", renamed.clone()))
+            .expect("Expected rename_product to succeed");
+        assert_eq!(block_on(db.get_product_by_id("Apple (BrandA)")), None);
+        assert_eq!(
+            block_on(db.get_product_uuid("Green Apple (BrandA)")),
+            Ok(Some(uuid.clone()))
+        );
+        let by_uuid = block_on(db.get_product_by_uuid(&uuid)).expect("Expected lookup to succeed");
+        assert_eq!(by_uuid.map(|p| p.id()), Some(renamed.id()));
+
+        let missing = ProductBuilder::new("Pear").build_unchecked();
+        assert!(block_on(db.rename_product("Apple (BrandA)", missing)).is_err());
+        assert_eq!(block_on(db.get_product_by_id("Pear")), None);
     }
 
     #[test]
-    fn test_01_local_product_db_new_preserves_existing_data() {
-        let test_db = TestDbGuard::create_empty().expect("Failed to create empty database");
+    fn test_55_rename_moves_history_and_logs() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        let original = block_on(db.get_product_by_id("Banana")).expect("Expected seeded product");
+        let mut ripe = original.clone();
+        ripe.macro_elements
+            .set(MacroElementsType::Sugar, 15.0)
+            .expect("Expected sugar to be set");
+        block_on(db.update_product("Banana", ripe)).expect("Expected update");
+        let eaten = EatenItem {
+            meal: "Breakfast".to_string(),
+            product_id: ProductId::from("Banana"),
+            grams: 120.0,
+        };
+        block_on(db.mark_eaten(20_000, &eaten)).expect("Expected item to be marked eaten");
+        let smoothie = BeverageEntry {
+            product_id: Some(ProductId::from("Banana")),
+            ..BeverageEntry::water(100, 250)
+        };
+        block_on(db.add_beverage_entry(&smoothie)).expect("Expected drink to be logged");
+
+        let renamed = ProductBuilder::new("Ripe Banana")
+            .carbs(23.0)
+            .build()
+            .expect("Expected valid product");
+        block_on(db.rename_product("Banana", renamed)).expect("Expected rename_product to succeed");
+
+        let history = block_on(db.get_product_history("Ripe Banana")).expect("Expected history");
+        assert_eq!(
+            history
+                .iter()
+                .map(|version| &version.product)
+                .collect::<Vec<_>>(),
+            vec![&original]
+        );
+        assert_eq!(block_on(db.get_product_history("Banana")), Ok(Vec::new()));
+        assert_eq!(
+            block_on(db.get_eaten(20_000)),
+            Ok(vec![EatenItem {
+                product_id: ProductId::from("Ripe Banana"),
+                ..eaten
+            }])
+        );
+        assert_eq!(
+            block_on(db.get_beverage_entries(0, 1000)),
+            Ok(vec![BeverageEntry {
+                product_id: Some(ProductId::from("Ripe Banana")),
+                ..smoothie
+            }])
+        );
+    }
 
+    #[test]
+    fn test_32_meal_notes_per_day() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
         {
-            let mut db = test_db.local_db();
-            let mut allowed_units: AllowedUnits = HashMap::new();
-            allowed_units.insert(
-                AllowedUnitsType::Gram,
-                UnitData {
-                    amount: 1,
-                    divider: 1,
+            let conn = test_db.connection();
+            conn.execute("DROP TABLE meal_notes;")
+                .expect("Failed to drop meal_notes table");
+            conn.execute("DELETE FROM schema_version WHERE version >= 14;")
+                .expect("Failed to reset schema version");
+        }
+
+        let mut db = test_db.handle();
+        assert_eq!(block_on(db.get_meal_notes(20_000)), Ok(Vec::new()));
+        let dinner = MealNote {
+            meal: "Dinner".to_string(),
+            text: "Kids' favourite".to_string(),
+            photos: vec![
+                MealPhoto::Blob {
+                    mime_type: "image/jpeg".to_string(),
+                    data: vec![0xff, 0xd8, 0xff],
                 },
-            );
-            let product = Product::new(
-                "Persisted".to_string(),
-                Some("BrandP".to_string()),
-                Box::new(MacroElements::new(
-                    1.0_f32, 0.5_f32, 2.0_f32, 1.0_f32, 3.0_f32,
-                )),
-                Box::default(),
-                allowed_units,
-            );
+                MealPhoto::Path("/photos/dinner.jpg".to_string()),
+            ],
+        };
+        let lunch = MealNote {
+            meal: "Lunch".to_string(),
+            text: "Too salty".to_string(),
+            photos: Vec::new(),
+        };
+        block_on(db.set_meal_note(20_000, &dinner)).expect("Expected note to be saved");
+        block_on(db.set_meal_note(20_000, &lunch)).expect("Expected note to be saved");
+        let edited = MealNote {
+            photos: Vec::new(),
+            ..dinner.clone()
+        };
+        block_on(db.set_meal_note(20_000, &edited)).expect("Expected note to be replaced");
+        assert!(
+            block_on(db.set_meal_note(
+                20_000,
+                &MealNote {
+                    meal: String::new(),
+                    ..lunch.clone()
+                }
+            ))
+            .is_err()
+        );
+        assert_eq!(
+            block_on(db.get_meal_notes(20_000)),
+            Ok(vec![edited, lunch.clone()])
+        );
+        assert_eq!(block_on(db.get_meal_notes(20_001)), Ok(Vec::new()));
+
+        block_on(db.set_meal_note(20_000, &dinner)).expect("Expected photos to be saved");
+        block_on(db.set_meal_note(
+            20_000,
+            &MealNote {
+                meal: "Lunch".to_string(),
+                ..MealNote::default()
+            },
+        ))
+        .expect("Expected empty note to be removed");
+        assert_eq!(block_on(db.get_meal_notes(20_000)), Ok(vec![dinner]));
+    }
 
-            let product_id = product.id();
-            block_on(db.add_product(product_id.as_str(), product))
-                .expect("Expected add_product to succeed for persisted product");
+    #[test]
+    fn test_33_backup_and_restore() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let backup_path = unique_test_db_path();
+        let _backup_cleanup = FileCleanup::new(backup_path.clone());
+        let backup = backup_path
+            .to_str()
+            .expect("Backup path contains invalid UTF-8");
+        let other_path = unique_test_db_path();
+        let _other_cleanup = FileCleanup::new(other_path.clone());
+        let other = other_path.to_str().expect("Path contains invalid UTF-8");
+
+        let mut db = test_db.handle();
+        block_on(db.integrity_check()).expect("Expected a healthy database");
+        block_on(db.backup_to(backup)).expect("Expected backup to succeed");
+        block_on(db.delete_product("Banana")).expect("Expected delete_product to succeed");
+
+        // a backup made before the meal notes table existed
+        {
+            let conn = SqliteConnection::open(backup).expect("Failed to open backup");
+            conn.execute("DROP TABLE meal_notes;")
+                .expect("Failed to drop meal_notes table");
+            conn.execute("DELETE FROM schema_version WHERE version >= 14;")
+                .expect("Failed to reset schema version");
         }
+        SqliteConnection::open(other)
+            .and_then(|conn| conn.execute("CREATE TABLE unrelated (id INTEGER);"))
+            .expect("Failed to create unrelated database");
+        assert!(block_on(db.restore_from(other)).is_err());
+        assert!(block_on(db.get_product_by_id("Banana")).is_none());
+
+        block_on(db.restore_from(backup)).expect("Expected restore to succeed");
+        assert!(block_on(db.get_product_by_id("Banana")).is_some());
+        assert_eq!(block_on(db.get_meal_notes(20_000)), Ok(Vec::new()));
+        block_on(db.integrity_check()).expect("Expected a healthy database");
+    }
 
+    #[test]
+    fn test_34_stats_count_brands_and_file_size() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
         let db = test_db.local_db();
-        let results = block_on(
-            db.get_products_matching_criteria(&[DbSearchCriteria::ById("Persisted".to_string())]),
+
+        let stats = block_on(db.stats()).expect("Expected stats query");
+        assert_eq!(
+            stats.quality,
+            block_on(db.data_quality_summary()).expect("Expected summary query")
         );
-        let key = "Persisted (BrandP)";
-        assert!(
-            results.contains_key(key),
-            "Expected previously inserted product '{key}' to remain after reopening"
+        assert_eq!(
+            stats.products_per_brand,
+            BTreeMap::from([("BrandA".to_string(), 1)])
         );
+        let file_size = fs::metadata(&test_db.path).expect("Missing db file").len();
+        assert_eq!(stats.size_bytes, Some(file_size));
     }
 
-    struct TestDbGuard {
-        path: PathBuf,
-        _cleanup_guard: FileCleanup,
+    #[test]
+    fn test_35_serving_size_is_persisted_and_migrated() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN grams_per_serving;")
+                .expect("Failed to drop grams_per_serving column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 15;")
+                .expect("Failed to reset schema version");
+        }
+
+        let mut db = test_db.local_db();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.serving, None);
+
+        let apple = apple.with_serving(Some(ServingInfo::new(150.0)));
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+        assert_eq!(
+            block_on(db.get_product_by_id("Apple (BrandA)")),
+            Some(apple)
+        );
     }
 
-    impl TestDbGuard {
-        fn create_empty() -> Result<Self, String> {
-            let path = unique_test_db_path();
-            cleanup_existing_files(&path)?;
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-            }
-            let cleanup_guard = FileCleanup::new(path.clone());
-            let path_str = path
-                .to_str()
-                .ok_or_else(|| "Database path contains invalid UTF-8".to_string())?;
-            let db = block_on(LocalProductDbConcrete::new(path_str))
-                .ok_or_else(|| "LocalProductDbConcrete::new returned None".to_string())?;
-            drop(db);
-            Ok(Self {
-                path,
-                _cleanup_guard: cleanup_guard,
-            })
-        }
+    #[test]
+    fn test_36_mutations_are_sent_to_subscribers_of_every_handle() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        let mut events = db
+            .clone()
+            .subscribe()
+            .expect("Expected subscribe to succeed");
+
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        block_on(db.update_product("Apple (BrandA)", apple.clone().with_negligible(true)))
+            .expect("Expected update_product to succeed");
+        block_on(db.delete_product("Apple (BrandA)")).expect("Expected delete to succeed");
+        block_on(db.add_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected add_product to succeed");
+        // failed mutations are not reported
+        assert!(block_on(db.add_product("Apple (BrandA)", apple)).is_err());
+        drop(db);
+
+        let received: Vec<DbEvent> = block_on(futures::StreamExt::collect(&mut events));
+        assert_eq!(
+            received,
+            vec![
+                DbEvent::Updated("Apple (BrandA)".into()),
+                DbEvent::Deleted("Apple (BrandA)".into()),
+                DbEvent::Added("Apple (BrandA)".into()),
+            ]
+        );
+    }
 
-        fn create_seeded() -> Result<Self, String> {
-            let guard = Self::create_empty()?;
-            {
-                let mut db = guard.local_db();
-                seed_products(&mut db)?;
-            }
-            Ok(guard)
-        }
+    #[test]
+    fn test_37_products_are_filtered_and_listed_by_brand() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let db = test_db.local_db();
 
-        fn connection(&self) -> SqliteConnection {
-            SqliteConnection::open(
-                self.path
-                    .to_str()
-                    .expect("Database path contains invalid UTF-8"),
+        let brand_a = block_on(
+            db.get_products_matching_criteria(&[DbSearchCriteria::ByBrand("BrandA".to_string())]),
+        );
+        assert_eq!(
+            brand_a.keys().map(ProductId::as_str).collect::<Vec<_>>(),
+            vec!["Apple (BrandA)"]
+        );
+        assert!(
+            block_on(
+                db.get_products_matching_criteria(&[DbSearchCriteria::ByBrand(
+                    "Brand'A".to_string(),
+                )])
             )
-            .expect("Failed to reopen test database")
+            .is_empty()
+        );
+        assert_eq!(
+            block_on(db.list_brands()),
+            Ok(BTreeMap::from([("BrandA".to_string(), 1)]))
+        );
+        let has_index = test_db
+            .connection()
+            .query_first(
+                "SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'products_brand';",
+                |row| row.get_i64(0),
+            )
+            .expect("Expected index query");
+        assert_eq!(has_index, Some(1));
+    }
+
+    #[test]
+    fn test_38_cooked_variant_is_persisted_and_migrated() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            for column in ["cooked_id", "cooked_yield"] {
+                conn.execute(&format!("ALTER TABLE products DROP COLUMN {column};"))
+                    .expect("Failed to drop cooked variant column");
+            }
+            conn.execute("DELETE FROM schema_version WHERE version >= 17;")
+                .expect("Failed to reset schema version");
         }
 
-        fn local_db(&self) -> LocalProductDbConcrete {
-            block_on(LocalProductDbConcrete::new(
-                self.path
-                    .to_str()
-                    .expect("Database path contains invalid UTF-8"),
+        let mut db = test_db.handle();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.cooked, None);
+
+        let invalid = apple
+            .clone()
+            .with_cooked(Some(CookedVariant::new("Baked apple".into(), 0.0)));
+        assert!(block_on(db.update_product("Apple (BrandA)", invalid)).is_err());
+
+        let apple = apple.with_cooked(Some(CookedVariant::new("Baked apple".into(), 0.8)));
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+        assert_eq!(
+            block_on(db.get_product_by_id("Apple (BrandA)")),
+            Some(apple)
+        );
+    }
+
+    #[test]
+    fn test_39_products_are_read_regardless_of_column_order() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let apple = block_on(test_db.local_db().get_product_by_id("Apple (BrandA)"))
+            .expect("Missing Apple");
+        {
+            let conn = test_db.connection();
+            let reversed = MacroElementsType::iter()
+                .filter(|m| *m != MacroElementsType::Calories)
+                .rev()
+                .map(|m| format!("\"{m}\""))
+                .chain(["id".to_string()])
+                .collect::<Vec<_>>()
+                .join(", ");
+            conn.execute("PRAGMA foreign_keys = OFF;")
+                .expect("Failed to disable foreign keys");
+            conn.execute(&format!(
+                "CREATE TABLE macro_elements_reordered AS SELECT {reversed} FROM macro_elements;"
             ))
-            .expect("Failed to reopen seeded LocalProductDbConcrete")
+            .expect("Failed to copy macro elements");
+            conn.execute("DROP TABLE macro_elements;")
+                .expect("Failed to drop macro elements");
+            conn.execute("ALTER TABLE macro_elements_reordered RENAME TO macro_elements;")
+                .expect("Failed to rename macro elements");
         }
+
+        assert_eq!(
+            block_on(test_db.local_db().get_product_by_id("Apple (BrandA)")),
+            Some(apple)
+        );
     }
 
-    impl Drop for TestDbGuard {
-        fn drop(&mut self) {
-            let _ = cleanup_existing_files(&self.path);
+    #[test]
+    fn test_40_least_complete_products_match_product_completeness() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let db = test_db.local_db();
+        let products =
+            block_on(db.get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())]));
+        let expected = crate::database_access::least_complete(
+            products
+                .into_iter()
+                .map(|(id, product)| (id, product.completeness())),
+            2,
+        );
+        assert_eq!(expected.len(), 2);
+        assert_eq!(block_on(db.least_complete_products(2)), Ok(expected));
+        assert_eq!(block_on(db.least_complete_products(0)), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_41_calories_override_is_persisted_and_searched() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN calories_override;")
+                .expect("Failed to drop calories_override column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 18;")
+                .expect("Failed to reset schema version");
         }
+
+        let mut db = test_db.handle();
+        let mut apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(apple.macro_elements.calories_override(), None);
+        let computed = apple.macro_elements.computed_calories();
+
+        apple.macro_elements.set_calories_override(Some(-1.0));
+        assert!(block_on(db.update_product("Apple (BrandA)", apple.clone())).is_err());
+
+        apple.macro_elements.set_calories_override(Some(120.0));
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+        let stored = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(stored, apple);
+        assert_relative_eq!(stored.macro_elements[MacroElementsType::Calories], 120.0);
+        assert_relative_eq!(stored.macro_elements.computed_calories(), computed);
+
+        let found = block_on(db.get_products_matching_criteria(&[
+            DbSearchCriteria::ByNutrientAtLeast(MacroElementsType::Calories.into(), 100.0),
+            DbSearchCriteria::ByNutrientAtMost(MacroElementsType::Calories.into(), 130.0),
+        ]));
+        assert_eq!(
+            found.into_keys().collect::<Vec<_>>(),
+            vec!["Apple (BrandA)"]
+        );
     }
 
-    fn cleanup_existing_files(path: &Path) -> Result<(), String> {
-        use std::io::ErrorKind;
+    #[test]
+    fn test_42_alcohol_calories_are_searched_and_can_be_ignored() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN ignore_alcohol_calories;")
+                .expect("Failed to drop ignore_alcohol_calories column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 19;")
+                .expect("Failed to reset schema version");
+        }
 
-        let attempt_remove = |candidate: &Path| -> Result<(), String> {
-            match fs::remove_file(candidate) {
-                Ok(()) => Ok(()),
-                Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
-                Err(err) => Err(err.to_string()),
-            }
+        let mut db = test_db.handle();
+        let mut apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert!(!apple.ignore_alcohol_calories);
+        let without_alcohol = apple.computed_calories();
+        apple.micro_nutrients[MicroNutrientsType::Alcohol] = Some(10.0);
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+
+        let calories = NutrientType::Macro(MacroElementsType::Calories);
+        let search = |db: &LocalProductDb| {
+            block_on(
+                db.get_products_matching_criteria(&[DbSearchCriteria::ByNutrientAtLeast(
+                    calories,
+                    without_alcohol + 60.0,
+                )]),
+            )
+            .into_keys()
+            .collect::<Vec<_>>()
         };
+        assert_eq!(search(&db), vec!["Apple (BrandA)"]);
+
+        let apple = apple.with_ignore_alcohol_calories(true);
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+        let stored = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(stored, apple);
+        assert_relative_eq!(stored.computed_calories(), without_alcohol);
+        assert!(search(&db).is_empty());
+    }
 
-        attempt_remove(path)?;
-        for suffix in ["-journal", "-wal", "-shm"] {
-            let mut os_string = path.as_os_str().to_os_string();
-            os_string.push(suffix);
-            let candidate: PathBuf = os_string.into();
-            attempt_remove(&candidate)?;
+    #[test]
+    fn test_43_shopping_lists_keep_check_off_state_per_profile() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("DROP TABLE shopping_lists;")
+                .expect("Failed to drop shopping_lists table");
+            conn.execute("DELETE FROM schema_version WHERE version >= 20;")
+                .expect("Failed to reset schema version");
         }
-        Ok(())
+
+        let mut db = test_db.handle();
+        assert_eq!(block_on(db.get_shopping_list_ids()), Ok(Vec::new()));
+        assert_eq!(block_on(db.get_shopping_list("week")), Ok(None));
+
+        let mut list = ShoppingList::default().with_plan_id(Some("plan 1".to_string()));
+        list.add_manual_item("Napkins", "1 pack")
+            .expect("Expected manual item to be added");
+        block_on(db.save_shopping_list("week", &list)).expect("Expected list to be saved");
+        block_on(db.save_shopping_list("party's", &ShoppingList::default()))
+            .expect("Expected list to be saved");
+        assert!(block_on(db.save_shopping_list(" ", &list)).is_err());
+
+        list.toggle_manual_item("Napkins")
+            .expect("Expected manual item to be toggled");
+        block_on(db.save_shopping_list("week", &list)).expect("Expected list to be saved");
+        assert_eq!(
+            block_on(db.get_shopping_list_ids()),
+            Ok(vec!["week".to_string(), "party's".to_string()])
+        );
+        assert_eq!(block_on(db.get_shopping_list("week")), Ok(Some(list)));
+
+        block_on(db.set_profile(&Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        }))
+        .expect("Expected profile to be added");
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_shopping_list_ids()), Ok(Vec::new()));
+        block_on(db.set_active_profile(DEFAULT_PROFILE_ID)).expect("Expected profile switch");
+
+        block_on(db.delete_shopping_list("party's")).expect("Expected list to be deleted");
+        assert_eq!(
+            block_on(db.get_shopping_list_ids()),
+            Ok(vec!["week".to_string()])
+        );
     }
 
-    fn unique_test_db_path() -> PathBuf {
-        static COUNTER: AtomicUsize = AtomicUsize::new(0);
-        static INIT_CLEANUP: Once = Once::new();
-        INIT_CLEANUP.call_once(|| {
-            if let Err(err) = cleanup_previous_test_databases() {
-                panic!("Failed to cleanup previous test databases: {err}");
-            }
-        });
+    #[test]
+    fn test_44_saved_plans_keep_meal_order() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("DROP TABLE plans;")
+                .expect("Failed to drop plans table");
+            conn.execute("DELETE FROM schema_version WHERE version >= 21;")
+                .expect("Failed to reset schema version");
+        }
 
-        let suffix = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut db = test_db.handle();
+        assert_eq!(block_on(db.get_plan_ids()), Ok(Vec::new()));
+        assert!(block_on(db.get_plan("week")).unwrap().is_none());
 
-        let base_path = Path::new(DATABASE_FILENAME);
-        let parent = base_path.parent();
-        let stem = base_path.file_stem().map_or_else(
-            || "test_db".to_string(),
-            |s| s.to_string_lossy().into_owned(),
-        );
-        let filename = if let Some(ext) = base_path.extension() {
-            format!("{}_{}.{}", stem, suffix, ext.to_string_lossy())
-        } else {
-            format!("{stem}_{suffix}")
+        let meal = |name: &str| SolutionEntry::Meal {
+            name: name.to_string(),
+            slot: None,
+            entries: Vec::new(),
+        };
+        let plan = Solution {
+            solution: SolutionEntry::Day {
+                name: "Day1".to_string(),
+                entries: vec![meal("Breakfast"), meal("Lunch"), meal("Dinner")],
+            },
         };
+        block_on(db.save_plan("week", &plan)).expect("Expected plan to be saved");
+        assert!(block_on(db.save_plan(" ", &plan)).is_err());
 
-        match parent {
-            Some(dir) => dir.join(&filename),
-            None => PathBuf::from(&filename),
-        }
-    }
+        block_on(db.reorder_meal("week", "Day1", "Dinner", 0)).expect("Expected meal to be moved");
+        let saved = block_on(db.get_plan("week"))
+            .unwrap()
+            .expect("Expected saved plan");
+        assert_eq!(
+            saved.meal_names("Day1"),
+            vec!["Dinner", "Breakfast", "Lunch"]
+        );
+        assert!(block_on(db.reorder_meal("week", "Day1", "Snack", 0)).is_err());
+        assert!(block_on(db.reorder_meal("missing", "Day1", "Lunch", 0)).is_err());
 
-    fn cleanup_previous_test_databases() -> Result<(), String> {
-        let base_path = Path::new(DATABASE_FILENAME);
-        let parent = base_path
-            .parent()
-            .ok_or_else(|| "DATABASE_FILENAME has no parent directory".to_string())?;
-        let stem = base_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| "DATABASE_FILENAME stem is not valid UTF-8".to_string())?;
-        let prefix = format!("{stem}_");
-        let extension = base_path
-            .extension()
-            .and_then(|e| e.to_str())
-            .map(std::string::ToString::to_string);
+        block_on(db.delete_plan("week")).expect("Expected plan to be deleted");
+        assert_eq!(block_on(db.get_plan_ids()), Ok(Vec::new()));
+    }
 
-        let entries = fs::read_dir(parent).map_err(|e| e.to_string())?;
-        for entry in entries {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-            let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) else {
-                continue;
-            };
-            if !file_stem.starts_with(&prefix) {
-                continue;
-            }
-            if let Some(ref expected_ext) = extension {
-                let actual_ext = path.extension().and_then(|e| e.to_str());
-                if actual_ext != Some(expected_ext.as_str()) {
-                    continue;
-                }
-            }
-            cleanup_existing_files(&path)?;
+    #[test]
+    fn test_45_solved_plans_found_by_fingerprint() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE plans DROP COLUMN fingerprint;")
+                .expect("Failed to drop fingerprint column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 22;")
+                .expect("Failed to reset schema version");
         }
-        Ok(())
-    }
 
-    fn seed_products(db: &mut LocalProductDbConcrete) -> Result<(), String> {
-        let mut apple_allowed: AllowedUnits = HashMap::new();
-        apple_allowed.insert(
-            AllowedUnitsType::Gram,
-            UnitData {
-                amount: 1,
-                divider: 1,
-            },
-        );
-        apple_allowed.insert(
-            AllowedUnitsType::Cup,
-            UnitData {
-                amount: 1,
-                divider: 2,
+        let mut db = test_db.handle();
+        let plan = |meal: &str| Solution {
+            solution: SolutionEntry::Day {
+                name: "Day1".to_string(),
+                entries: vec![SolutionEntry::Meal {
+                    name: meal.to_string(),
+                    slot: None,
+                    entries: Vec::new(),
+                }],
             },
-        );
-        let mut apple_micro = Box::new(MicroNutrients::default());
-        apple_micro[MicroNutrientsType::Fiber] = Some(2.4_f32);
-        apple_micro[MicroNutrientsType::Zinc] = Some(0.1_f32);
-        apple_micro[MicroNutrientsType::Sodium] = Some(1.0_f32);
-        let apple = Product::new(
-            "Apple".to_string(),
-            Some("BrandA".to_string()),
-            Box::new(MacroElements::new(
-                0.2_f32, 0.1_f32, 14.0_f32, 10.0_f32, 0.3_f32,
-            )),
-            apple_micro,
-            apple_allowed,
-        );
-        let apple_id = apple.id();
-        block_on(db.add_product(apple_id.as_str(), apple))
-            .map_err(|e| format!("Failed to seed product {apple_id}: {e}"))?;
+        };
+        assert!(block_on(db.find_solved_plan(u64::MAX)).unwrap().is_none());
+        block_on(db.save_solved_plan("monday", u64::MAX, &plan("Lunch"))).unwrap();
+        block_on(db.save_plan("manual", &plan("Dinner"))).unwrap();
 
-        let mut banana_allowed: AllowedUnits = HashMap::new();
-        banana_allowed.insert(
-            AllowedUnitsType::Gram,
-            UnitData {
-                amount: 1,
-                divider: 1,
-            },
-        );
-        banana_allowed.insert(
-            AllowedUnitsType::Tablespoon,
-            UnitData {
-                amount: 2,
-                divider: 1,
-            },
-        );
-        banana_allowed.insert(
-            AllowedUnitsType::Custom,
-            UnitData {
-                amount: 50,
-                divider: 1,
-            },
-        );
-        let mut banana_micro = Box::new(MicroNutrients::default());
-        banana_micro[MicroNutrientsType::Fiber] = Some(2.6_f32);
-        banana_micro[MicroNutrientsType::Sodium] = Some(1.0_f32);
-        let banana = Product::new(
-            "Banana".to_string(),
-            None,
-            Box::new(MacroElements::new(
-                0.3_f32, 0.1_f32, 23.0_f32, 12.0_f32, 1.1_f32,
-            )),
-            banana_micro,
-            banana_allowed,
-        );
-        let banana_id = banana.id();
-        block_on(db.add_product(banana_id.as_str(), banana))
-            .map_err(|e| format!("Failed to seed product {banana_id}: {e}"))?;
+        let found = block_on(db.find_solved_plan(u64::MAX)).unwrap().unwrap();
+        assert_eq!(found.meal_names("Day1"), vec!["Lunch"]);
+        assert!(block_on(db.find_solved_plan(7)).unwrap().is_none());
 
-        Ok(())
-    }
+        // saving the plan again, e.g. after reordering, keeps the fingerprint
+        block_on(db.save_plan("monday", &plan("Brunch"))).unwrap();
+        let found = block_on(db.find_solved_plan(u64::MAX)).unwrap().unwrap();
+        assert_eq!(found.meal_names("Day1"), vec!["Brunch"]);
 
-    struct FileCleanup {
-        path: PathBuf,
+        block_on(db.delete_plan("monday")).unwrap();
+        assert!(block_on(db.find_solved_plan(u64::MAX)).unwrap().is_none());
     }
 
-    impl FileCleanup {
-        fn new(path: PathBuf) -> Self {
-            Self { path }
+    #[test]
+    fn test_46_data_quality_is_stored_and_searchable() {
+        use crate::data_types::{DataQuality, NUTRITION_COMPLETED_TAG};
+
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        {
+            let conn = test_db.connection();
+            conn.execute("ALTER TABLE products DROP COLUMN data_quality;")
+                .expect("Failed to drop data_quality column");
+            conn.execute("ALTER TABLE products DROP COLUMN verified_data;")
+                .expect("Failed to drop verified_data column");
+            conn.execute("DELETE FROM schema_version WHERE version >= 23;")
+                .expect("Failed to reset schema version");
         }
+
+        let mut db = test_db.handle();
+        let apple = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert!(apple.data_quality().is_none());
+
+        let verified = DataQuality {
+            completeness_tags: [NUTRITION_COMPLETED_TAG.to_string()].into(),
+            last_modified: Some(1_700_000_000),
+            ..DataQuality::default()
+        };
+        let estimated = DataQuality {
+            estimated_fields: ["fiber_100g".to_string()].into(),
+            ..verified.clone()
+        };
+        let apple = apple.with_data_quality(Some(estimated));
+        block_on(db.update_product("Apple (BrandA)", apple.clone()))
+            .expect("Expected update_product to succeed");
+        let stored = block_on(db.get_product_by_id("Apple (BrandA)")).expect("Missing Apple");
+        assert_eq!(stored, apple);
+
+        let search = |db: &LocalProductDb| {
+            block_on(db.get_products_matching_criteria(&[
+                DbSearchCriteria::ById("Apple".to_string()),
+                DbSearchCriteria::VerifiedDataOnly,
+            ]))
+            .into_keys()
+            .collect::<Vec<_>>()
+        };
+        assert!(search(&db).is_empty());
+
+        block_on(db.update_product("Apple (BrandA)", apple.with_data_quality(Some(verified))))
+            .expect("Expected update_product to succeed");
+        assert_eq!(search(&db), vec!["Apple (BrandA)"]);
     }
 
-    impl Drop for FileCleanup {
-        fn drop(&mut self) {
-            let _ = cleanup_existing_files(&self.path);
-        }
+    #[test]
+    fn test_47_newer_schema_is_refused() {
+        use crate::database_access::NewerSchema;
+
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let newer = migrations::latest_version() + 1;
+        test_db
+            .connection()
+            .execute(&migrations::record_version_sql(newer))
+            .expect("Failed to record newer schema version");
+
+        let path = test_db
+            .path
+            .to_str()
+            .expect("Database path contains invalid UTF-8");
+        let error = block_on(LocalProductDb::open(path))
+            .err()
+            .expect("Expected newer database to be refused");
+        assert!(NewerSchema::matches(&error), "{error}");
+        assert!(error.contains(&format!("schema {newer}")));
+        assert!(block_on(LocalProductDb::new(path)).is_none());
     }
 
     #[test]
-    fn test_02_initialize_and_seed_database() {
+    fn test_54_newer_backup_is_refused_before_restoring() {
+        use crate::database_access::NewerSchema;
+
         let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
-        let conn = test_db.connection();
-        let product_count = conn
-            .query_first("SELECT COUNT(*) FROM products;", |row| row.get_i64(0))
-            .expect("Failed to count products")
-            .expect("Missing count row");
-        assert_eq!(product_count, 2);
-        let macro_columns = conn
-            .query_first(
-                "SELECT COUNT(*) FROM pragma_table_info('macro_elements');",
-                |row| row.get_i64(0),
-            )
-            .expect("Failed to count macro columns")
-            .expect("Missing macro columns row");
-        assert_eq!(macro_columns, 6);
+        let backup_path = unique_test_db_path();
+        let _backup_cleanup = FileCleanup::new(backup_path.clone());
+        let backup = backup_path
+            .to_str()
+            .expect("Backup path contains invalid UTF-8");
+
+        let mut db = test_db.handle();
+        block_on(db.backup_to(backup)).expect("Expected backup to succeed");
+        SqliteConnection::open(backup)
+            .and_then(|conn| {
+                conn.execute(&migrations::record_version_sql(
+                    migrations::latest_version() + 1,
+                ))
+            })
+            .expect("Failed to record newer schema version");
+        block_on(db.delete_product("Banana")).expect("Expected delete_product to succeed");
+
+        let error =
+            block_on(db.restore_from(backup)).expect_err("Expected newer backup to be refused");
+        assert!(NewerSchema::matches(&error), "{error}");
+        assert!(block_on(db.get_product_by_id("Banana")).is_none());
+        assert!(block_on(db.get_product_by_id("Apple (BrandA)")).is_some());
+        block_on(db.integrity_check()).expect("Expected a healthy database");
+    }
+
+    #[test]
+    fn test_58_restore_checks_typed_columns() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let backup_path = unique_test_db_path();
+        let _backup_cleanup = FileCleanup::new(backup_path.clone());
+        let backup = backup_path
+            .to_str()
+            .expect("Backup path contains invalid UTF-8");
+
+        let mut db = test_db.handle();
+        block_on(db.backup_to(backup)).expect("Expected backup to succeed");
+        // macro element columns aren't re-added by migrations
+        SqliteConnection::open(backup)
+            .and_then(|conn| conn.execute("ALTER TABLE macro_elements DROP COLUMN \"Fat\";"))
+            .expect("Failed to drop column");
+
+        let error = block_on(db.restore_from(backup))
+            .expect_err("Expected backup without a macro column to be refused");
+        assert!(error.contains("macro_elements.\"Fat\""), "{error}");
     }
 
     #[test]
-    fn test_03_get_products_matching_criteria_returns_expected_product() {
+    fn test_56_read_only_open_never_writes() {
+        use crate::database_access::NewerSchema;
+
+        let missing_path = unique_test_db_path();
+        let _missing_cleanup = FileCleanup::new(missing_path.clone());
+        let missing = missing_path
+            .to_str()
+            .expect("Database path contains invalid UTF-8");
+        assert!(block_on(LocalProductDb::open_read_only(missing)).is_err());
+        assert!(!missing_path.exists());
+
         let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
-        let db = test_db.local_db();
-        let results = block_on(
-            db.get_products_matching_criteria(&[DbSearchCriteria::ById("Apple".to_string())]),
-        );
-        assert_eq!(results.len(), 1);
-        let apple = results
-            .get("Apple (BrandA)")
-            .expect("Missing Apple product");
-        assert_eq!(apple.name(), "Apple");
-        assert_eq!(apple.brand(), Some("BrandA"));
-        assert_relative_eq!(apple.macro_elements[MacroElementsType::Fat], 0.2_f32);
-        assert_eq!(
-            apple.micro_nutrients[MicroNutrientsType::Fiber],
-            Some(2.4_f32)
-        );
-        assert_eq!(apple.allowed_units[&AllowedUnitsType::Cup].amount, 1);
-        assert_eq!(apple.allowed_units[&AllowedUnitsType::Cup].divider, 2);
+        let path = test_db
+            .path
+            .to_str()
+            .expect("Database path contains invalid UTF-8")
+            .to_string();
+        let db = block_on(LocalProductDb::open_read_only(&path))
+            .expect("Expected current database to open read-only");
+        assert!(block_on(db.get_product_by_id("Banana")).is_some());
+        let shared = block_on(LocalProductDb::shared(&path)).expect("Expected shared db to open");
+        assert!(!db.shares_connection_with(&shared));
+
+        let latest = migrations::latest_version();
+        test_db
+            .connection()
+            .execute(&format!(
+                "UPDATE {} SET version = {} WHERE version = {latest};",
+                migrations::SCHEMA_VERSION_TABLE,
+                latest - 1
+            ))
+            .expect("Failed to downgrade schema version");
+        let error = block_on(LocalProductDb::open_read_only(&path))
+            .err()
+            .expect("Expected older database to be refused");
+        assert!(error.contains("needs a migration"), "{error}");
+        let version = LocalProductDbConcrete::schema_version(&test_db.connection())
+            .expect("Expected schema version");
+        assert_eq!(version, latest - 1);
+
+        test_db
+            .connection()
+            .execute(&migrations::record_version_sql(latest + 1))
+            .expect("Failed to record newer schema version");
+        let error = block_on(LocalProductDb::open_read_only(&path))
+            .err()
+            .expect("Expected newer database to be refused");
+        assert!(NewerSchema::matches(&error), "{error}");
     }
 
     #[test]
-    fn test_04_set_product_unit_updates_allowed_units_table() {
+    fn test_48_products_page_matches_in_memory_order() {
         let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
-        let mut db = test_db.local_db();
-        let result = block_on(db.set_product_unit(
-            "Apple (BrandA)",
-            AllowedUnitsType::Cup,
-            UnitData {
-                amount: 3,
-                divider: 2,
-            },
-        ));
-        assert!(result.is_ok());
-        let conn = test_db.connection();
-        let updated = conn
-            .query_first(
-                "SELECT cup FROM allowed_units WHERE id = 'Apple (BrandA)';",
-                |row| row.get_u16_optional(0),
-            )
-            .expect("Failed to fetch updated unit")
-            .expect("Missing cup value");
-        assert_eq!(updated, Some(3));
-        let updated_div = conn
-            .query_first(
-                "SELECT \"cup divider\" FROM allowed_units WHERE id = 'Apple (BrandA)';",
-                |row| row.get_u16_optional(0),
-            )
-            .expect("Failed to fetch updated divider")
-            .expect("Missing cup divider");
-        assert_eq!(updated_div, Some(2));
+        let db = test_db.local_db();
+        let products =
+            block_on(db.get_products_matching_criteria(&[DbSearchCriteria::ById(String::new())]));
+        assert!(products.len() > 1);
+        for sort in [
+            SortKey::Name,
+            SortKey::Brand,
+            SortKey::Calories,
+            SortKey::Protein,
+        ] {
+            let expected = crate::database_access::products_page(
+                products.clone().into_iter(),
+                sort,
+                0,
+                usize::MAX,
+            );
+            let page = block_on(db.get_products_page(sort, 0, products.len()));
+            assert_eq!(page.as_ref(), Ok(&expected), "{sort:?}");
+            let window = block_on(db.get_products_page(sort, 1, 1));
+            assert_eq!(window, Ok(expected[1..2].to_vec()), "{sort:?}");
+        }
+        let past_end = block_on(db.get_products_page(SortKey::Name, products.len(), 10));
+        assert_eq!(past_end, Ok(Vec::new()));
     }
 
     #[test]
-    fn test_05_add_product_inserts_all_related_rows() {
+    fn test_49_activity_log_is_per_profile() {
         let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
-        let mut db = test_db.local_db();
-        let mut allowed_units: AllowedUnits = HashMap::new();
-        allowed_units.insert(
-            AllowedUnitsType::Gram,
-            UnitData {
-                amount: 1,
-                divider: 1,
-            },
-        );
-        allowed_units.insert(
-            AllowedUnitsType::Cup,
-            UnitData {
-                amount: 2,
-                divider: 1,
-            },
-        );
-        let new_product = Product::new(
-            "Orange".to_string(),
-            Some("CitrusCo".to_string()),
-            Box::new(MacroElements::new(
-                0.1_f32, 0.05_f32, 11.0_f32, 9.0_f32, 1.0_f32,
-            )),
-            Box::default(),
-            allowed_units,
-        );
-        let new_id = new_product.id();
-        assert!(
-            block_on(db.add_product(new_id.as_str(), new_product)).is_ok(),
-            "Expected add_product to succeed"
+        let mut db = test_db.handle();
+        let run = ActivityLog {
+            name: Some("Running".to_string()),
+            ..ActivityLog::new(200, 450)
+        };
+        block_on(db.add_activity(&run)).expect("Expected activity to be logged");
+        block_on(db.add_activity(&ActivityLog::new(100, 120)))
+            .expect("Expected activity to be logged");
+        block_on(db.add_activity(&ActivityLog::new(300, 200)))
+            .expect("Expected activity to be logged");
+        assert!(block_on(db.add_activity(&ActivityLog::new(400, 0))).is_err());
+
+        assert_eq!(
+            block_on(db.get_activities(100, 300)),
+            Ok(vec![ActivityLog::new(100, 120), run])
         );
-        let conn = test_db.connection();
-        let product_count = conn
-            .query_first(
-                format!("SELECT COUNT(*) FROM products WHERE id = '{new_id}';").as_str(),
-                |row| row.get_i64(0),
-            )
-            .expect("Failed to verify inserted product")
-            .expect("Missing product count");
-        assert_eq!(product_count, 1);
+
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(db.set_profile(&partner)).expect("Expected profile to be added");
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_activities(0, 1000)), Ok(Vec::new()));
     }
 
     #[test]
-    fn test_06_update_product_modifies_macro_and_micro_nutr_values() {
+    fn test_50_search_by_name_escapes_quotes() {
         let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
-        let mut db = test_db.local_db();
-        let mut allowed_units: AllowedUnits = HashMap::new();
-        allowed_units.insert(
-            AllowedUnitsType::Gram,
-            UnitData {
-                amount: 1,
-                divider: 1,
-            },
-        );
-        allowed_units.insert(
-            AllowedUnitsType::Custom,
-            UnitData {
-                amount: 250,
-                divider: 1,
-            },
-        );
-        let mut micro = Box::new(MicroNutrients::default());
-        micro[MicroNutrientsType::Fiber] = Some(3.0_f32);
-        micro[MicroNutrientsType::Zinc] = Some(0.2_f32);
-        let updated = Product::new(
-            "Apple".to_string(),
-            Some("BrandA".to_string()),
-            Box::new(MacroElements::new(
-                0.5_f32, 0.2_f32, 15.0_f32, 11.0_f32, 0.9_f32,
-            )),
-            micro,
-            allowed_units,
+        let db = test_db.local_db();
+        let found =
+            block_on(db.get_products_matching_criteria(&[DbSearchCriteria::ById("App".into())]));
+        assert_eq!(
+            found.keys().map(ProductId::as_str).collect::<Vec<_>>(),
+            vec!["Apple (BrandA)"]
         );
-        assert!(
-            block_on(db.update_product("Apple (BrandA)", updated)).is_ok(),
-            "Expected update_product to succeed"
+        // the name is searched, not the id with the brand
+        let by_id = block_on(
+            db.get_products_matching_criteria(&[DbSearchCriteria::ById("Apple (Br".into())]),
         );
-        let conn = test_db.connection();
-        let fiber = conn
-            .query_first(
-                "SELECT Fiber FROM micronutrients WHERE id = 'Apple (BrandA)';",
-                |row| row.get_f32_optional(0),
-            )
-            .expect("Failed to fetch updated fiber")
-            .expect("Missing fiber value");
-        assert_eq!(fiber, Some(3.0_f32));
+        assert!(by_id.is_empty());
+        assert!(block_on(db.get_product_by_id("Apple (BrandA)")).is_some());
+
+        let fragment =
+            db_search_criteria_to_sql_query_fragment(&[DbSearchCriteria::ById("O'Bri".into())])
+                .expect("Expected a query fragment");
+        assert!(fragment.ends_with("name LIKE 'O''Bri%'"));
+        let quoted =
+            block_on(db.get_products_matching_criteria(&[DbSearchCriteria::ById("O'Bri".into())]));
+        assert!(quoted.is_empty());
     }
 
     #[test]
-    fn test_07_delete_product_removes_all_rows() {
+    fn test_51_targets_record_is_per_profile() {
         let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
-        let mut db = test_db.local_db();
-        assert!(
-            block_on(db.delete_product("Banana")).is_ok(),
-            "Expected delete_product to succeed"
-        );
-        let conn = test_db.connection();
-        let count = conn
-            .query_first(
-                "SELECT COUNT(*) FROM products WHERE id = 'Banana';",
-                |row| row.get_i64(0),
-            )
-            .expect("Failed to check product deletion")
-            .expect("Missing count value");
-        assert_eq!(count, 0);
+        let mut db = test_db.handle();
+        assert_eq!(block_on(db.get_targets_record()), Ok(None));
+
+        let mut record = TargetsRecord {
+            computed_at: 1_700_000_000,
+            weight_kg: 82.5,
+        };
+        block_on(db.set_targets_record(&record)).expect("Expected targets record to be saved");
+        record.weight_kg = 80.0;
+        block_on(db.set_targets_record(&record)).expect("Expected targets record to be updated");
+        assert_eq!(block_on(db.get_targets_record()), Ok(Some(record)));
+
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(db.set_profile(&partner)).expect("Expected profile to be added");
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_targets_record()), Ok(None));
     }
 
     #[test]
-    fn test_08_new_returns_connection() {
-        let db_path = unique_test_db_path();
-        let _cleanup_guard = FileCleanup::new(db_path.clone());
-        cleanup_existing_files(&db_path).expect("Failed to remove pre-existing database file");
-        if let Some(parent) = db_path.parent() {
-            fs::create_dir_all(parent).expect("Failed to create database directory");
-        }
+    fn test_52_pantry_round_trip_keeps_expiry_per_profile() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let mut db = test_db.handle();
+        assert_eq!(block_on(db.get_pantry()), Ok(Pantry::new()));
+
+        let mut pantry = Pantry::new();
+        pantry.add_with_expiry("Apple (BrandA)", 300.0, 19_700);
+        pantry.add("Banana", 120.5);
+        pantry.add("Chef's Oats", 500.0);
+        block_on(db.save_pantry(&pantry)).expect("Expected pantry to be saved");
+        let stored = block_on(db.get_pantry()).expect("Expected pantry to be read");
+        assert_eq!(stored, pantry);
+        assert_eq!(stored.expiry("Apple (BrandA)"), Some(19_700));
+        assert_eq!(stored.expiry("Banana"), None);
+
+        // saving replaces the stock, products used up are gone
+        let mut rest = Pantry::new();
+        rest.add_with_expiry("Apple (BrandA)", 100.0, 19_690);
+        block_on(db.save_pantry(&rest)).expect("Expected pantry to be replaced");
+        assert_eq!(block_on(db.get_pantry()), Ok(rest));
+
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(db.set_profile(&partner)).expect("Expected profile to be added");
+        block_on(db.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(db.get_pantry()), Ok(Pantry::new()));
+    }
 
-        let path_str = db_path
+    #[test]
+    fn test_53_active_profile_is_per_handle() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let path = test_db
+            .path
             .to_str()
-            .expect("Database path contains invalid UTF-8");
-        let result = block_on(LocalProductDbConcrete::new(path_str));
-        assert!(
-            result.is_some(),
-            "Expected LocalProductDbConcrete::new to return Some"
+            .expect("Database path contains invalid UTF-8")
+            .to_string();
+        let mut first =
+            block_on(LocalProductDb::shared(&path)).expect("Expected shared db to open");
+        let second = block_on(LocalProductDb::shared(&path)).expect("Expected shared db to open");
+        assert!(first.shares_connection_with(&second));
+
+        let profile = UserProfile {
+            age_years: 35,
+            sex: Sex::Male,
+            weight_kg: 82.5,
+            height_cm: 178.0,
+            activity_level: ActivityLevel::VeryActive,
+        };
+        block_on(first.set_user_profile(&profile)).expect("Expected profile to be saved");
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(first.set_profile(&partner)).expect("Expected profile to be added");
+        block_on(first.set_active_profile("partner")).expect("Expected profile switch");
+        assert_eq!(block_on(first.get_user_profile()), Ok(None));
+
+        assert_eq!(
+            block_on(second.active_profile()),
+            Ok(DEFAULT_PROFILE_ID.to_string())
         );
-        drop(result);
+        assert_eq!(block_on(second.get_user_profile()), Ok(Some(profile)));
+        assert_eq!(block_on(first.active_profile()), Ok("partner".to_string()));
     }
 
     #[test]
-    fn test_09_update_product_inserts_when_missing() {
-        let test_db = TestDbGuard::create_empty().expect("Failed to prepare empty database");
-        let mut db = test_db.local_db();
-
-        let mut allowed_units: AllowedUnits = HashMap::new();
-        allowed_units.insert(
-            AllowedUnitsType::Gram,
-            UnitData {
-                amount: 1,
-                divider: 1,
-            },
-        );
-        allowed_units.insert(
-            AllowedUnitsType::Custom,
-            UnitData {
-                amount: 250,
-                divider: 2,
-            },
-        );
+    fn test_57_profile_active_in_another_handle_is_not_deleted() {
+        let test_db = TestDbGuard::create_seeded().expect("Failed to prepare seeded database");
+        let path = test_db
+            .path
+            .to_str()
+            .expect("Database path contains invalid UTF-8")
+            .to_string();
+        let mut first =
+            block_on(LocalProductDb::shared(&path)).expect("Expected shared db to open");
+        let partner = Profile {
+            id: "partner".to_string(),
+            name: "Partner".to_string(),
+        };
+        block_on(first.set_profile(&partner)).expect("Expected profile to be added");
 
-        let mut micro = Box::new(MicroNutrients::default());
-        micro[MicroNutrientsType::Fiber] = Some(1.5_f32);
+        let mut second = first.clone();
+        block_on(second.set_active_profile("partner")).expect("Expected profile switch");
+        let third = second.clone();
+        assert!(block_on(first.delete_profile("partner")).is_err());
 
-        let product = Product::new(
-            "Kiwi".to_string(),
-            Some("FreshCo".to_string()),
-            Box::new(MacroElements::new(
-                0.4_f32, 0.1_f32, 15.0_f32, 9.0_f32, 1.1_f32,
-            )),
-            micro,
-            allowed_units,
-        );
+        block_on(second.set_active_profile(DEFAULT_PROFILE_ID))
+            .expect("Expected profile switch back");
+        assert!(block_on(first.delete_profile("partner")).is_err());
 
-        let product_id = product.id();
+        drop(third);
+        block_on(first.delete_profile("partner")).expect("Expected unused profile to be deleted");
         assert!(
-            block_on(db.update_product(product_id.as_str(), product)).is_ok(),
-            "Expected update_product to insert when missing"
+            block_on(first.get_profiles())
+                .expect("Expected profiles")
+                .iter()
+                .all(|p| p.id != "partner")
         );
-
-        let conn = test_db.connection();
-        let name = conn
-            .query_first(
-                format!("SELECT name FROM products WHERE id = '{product_id}';").as_str(),
-                |row| row.get_string(0),
-            )
-            .expect("Failed to read inserted product")
-            .expect("Missing inserted product");
-        assert_eq!(name, "Kiwi");
-
-        let fiber = conn
-            .query_first(
-                format!("SELECT Fiber FROM micronutrients WHERE id = '{product_id}';").as_str(),
-                |row| row.get_f32_optional(0),
-            )
-            .expect("Failed to fetch inserted micronutrient")
-            .expect("Missing inserted micronutrient value");
-        assert_eq!(fiber, Some(1.5_f32));
-
-        let custom_amount = conn
-            .query_first(
-                format!("SELECT custom FROM allowed_units WHERE id = '{product_id}';").as_str(),
-                |row| row.get_u16_optional(0),
-            )
-            .expect("Failed to fetch inserted allowed unit")
-            .expect("Missing custom unit amount");
-        assert_eq!(custom_amount, Some(250));
-
-        let gram_divider = conn
-            .query_first(
-                format!("SELECT \"gram divider\" FROM allowed_units WHERE id = '{product_id}';")
-                    .as_str(),
-                |row| row.get_u16_optional(0),
-            )
-            .expect("Failed to fetch gram divider")
-            .expect("Missing gram divider");
-        assert_eq!(gram_divider, Some(1));
     }
 }
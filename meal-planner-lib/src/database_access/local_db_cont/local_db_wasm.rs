@@ -6,12 +6,25 @@ use serde_json::{Map, Value, json};
 use strum::{EnumCount, IntoEnumIterator};
 use wasm_bindgen::JsValue;
 
+use crate::bl::constraints_solver::Solution;
+use crate::bl::grocery_haul::{Pantry, ShoppingList};
+use crate::bl::targets_review::TargetsRecord;
 use crate::data_types::{
-    AllowedUnits, AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients,
-    MicroNutrientsType, Product, UnitData,
+    ALCOHOL_KCAL_PER_G, ALLERGEN_FLAG_KIND, ActivityLog, AllowedUnits, AllowedUnitsType,
+    AppSettings, BeverageEntry, CompletenessReport, CookedVariant, DEFAULT_PROFILE_ID, EatenItem,
+    MacroElements, MacroElementsType, MealNote, MicroNutrients, MicroNutrientsType, NutrientType,
+    Price, Product, ProductEdit, ProductId, ProductVersion, Profile, ServingInfo, UnitData,
+    UsageStats, UserProfile,
 };
+use crate::database_access::local_db_cont::migrations::{self, AddColumn};
 use crate::database_access::local_db_cont::wasm_worker_client::DbWorkerHandle;
-use crate::database_access::{Database, DbSearchCriteria, MutableDatabase};
+use crate::database_access::{
+    BulkReport, DataQualitySummary, Database, DatabaseStats, DbSearchCriteria, MutableDatabase,
+    NewerSchema, SortKey,
+};
+
+// error of the worker for backups of a newer schema, followed by their schema version
+const NEWER_BACKUP_ERROR: &str = "Newer schema ";
 
 const WORKER_URL: &str = "/meal-planner-lib/local-db/wasm_worker.js";
 
@@ -24,8 +37,20 @@ thread_local! {
 #[serde(tag = "type")]
 enum WorkerResponse {
     Ok,
-    Rows { rows: Vec<Map<String, Value>> },
-    Err { message: String },
+    Rows {
+        rows: Vec<Map<String, Value>>,
+    },
+    /// Results of a `QueryBatch`, one set of rows per query in request order.
+    RowSets {
+        row_sets: Vec<Vec<Map<String, Value>>>,
+    },
+    /// Results of an `ExecGroups`, the error of each group or `None` when it succeeded.
+    GroupResults {
+        errors: Vec<Option<String>>,
+    },
+    Err {
+        message: String,
+    },
 }
 
 #[derive(Serialize)]
@@ -39,6 +64,7 @@ struct SqlStatement {
 pub struct LocalProductDbConcrete {
     worker: Rc<DbWorkerHandle>,
     key: String,
+    active_profile: String,
 }
 
 impl LocalProductDbConcrete {
@@ -88,11 +114,51 @@ impl LocalProductDbConcrete {
         match Self::send_request(&self.worker, &req).await {
             Ok(WorkerResponse::Ok) => Ok(()),
             Ok(WorkerResponse::Err { message }) => Err(message),
-            Ok(WorkerResponse::Rows { .. }) => Err("Unexpected rows for Exec".to_string()),
+            Ok(
+                WorkerResponse::Rows { .. }
+                | WorkerResponse::RowSets { .. }
+                | WorkerResponse::GroupResults { .. },
+            ) => Err("Unexpected rows for Exec".to_string()),
+            Err(e) => Err(e),
+        }
+    }
+
+    // `request_type` is one of `BeginTransaction`, `Commit` and `Rollback`
+    async fn send_transaction_control(&self, request_type: &str) -> Result<(), String> {
+        let req = json!({
+            "type": request_type,
+            "database_file": self.key,
+        });
+
+        match Self::send_request(&self.worker, &req).await {
+            Ok(WorkerResponse::Ok) => Ok(()),
+            Ok(WorkerResponse::Err { message }) => Err(message),
+            Ok(other) => Err(format!("Unexpected response for {request_type}: {other:?}")),
             Err(e) => Err(e),
         }
     }
 
+    /// Runs `operation` in a transaction, committed when it succeeds and rolled back otherwise.
+    /// `Exec` requests sent meanwhile become part of the transaction.
+    async fn in_transaction<T>(
+        &self,
+        operation: impl AsyncFnOnce() -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.send_transaction_control("BeginTransaction").await?;
+        match operation().await {
+            Ok(value) => {
+                self.send_transaction_control("Commit").await?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(rollback_err) = self.send_transaction_control("Rollback").await {
+                    tracing::error!("Rollback failed: {rollback_err}");
+                }
+                Err(e)
+            }
+        }
+    }
+
     async fn send_query(
         &self,
         sql: String,
@@ -108,32 +174,98 @@ impl LocalProductDbConcrete {
         match Self::send_request(&self.worker, &req).await {
             Ok(WorkerResponse::Rows { rows }) => Ok(rows),
             Ok(WorkerResponse::Ok) => Err("Query returned Ok without rows".to_string()),
+            Ok(WorkerResponse::RowSets { .. } | WorkerResponse::GroupResults { .. }) => {
+                Err("Query returned a batch".to_string())
+            }
             Ok(WorkerResponse::Err { message }) => Err(message),
             Err(e) => Err(e),
         }
     }
 
-    fn map_row_to_product(row: &Map<String, Value>) -> Result<(String, Product), String> {
-        let id = Self::get_string(row, "id")?;
-        let name = Self::get_string(row, "name")?;
-        let brand = Self::get_string_opt(row, "brand")?;
+    /// Runs every group of statements in one round trip and transaction. A failing group is
+    /// rolled back alone, the others are still committed.
+    async fn send_exec_groups(
+        &self,
+        groups: Vec<Vec<SqlStatement>>,
+    ) -> Result<Vec<Option<String>>, String> {
+        let group_count = groups.len();
+        let req = json!({
+            "type": "ExecGroups",
+            "database_file": self.key,
+            "groups": groups,
+        });
 
-        let mut macro_values = Vec::new();
-        for macro_type in MacroElementsType::iter() {
-            if macro_type == MacroElementsType::Calories {
-                continue;
+        match Self::send_request(&self.worker, &req).await {
+            Ok(WorkerResponse::GroupResults { errors }) if errors.len() == group_count => {
+                Ok(errors)
+            }
+            Ok(WorkerResponse::GroupResults { errors }) => Err(format!(
+                "Exec groups returned {} results for {group_count} groups",
+                errors.len()
+            )),
+            Ok(WorkerResponse::Err { message }) => Err(message),
+            Ok(other) => Err(format!("Unexpected response for ExecGroups: {other:?}")),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Runs all `queries` in one round trip, they see the same state of the database.
+    async fn send_query_batch(
+        &self,
+        queries: Vec<SqlStatement>,
+    ) -> Result<Vec<Vec<Map<String, Value>>>, String> {
+        let query_count = queries.len();
+        let req = json!({
+            "type": "QueryBatch",
+            "database_file": self.key,
+            "queries": queries,
+        });
+
+        match Self::send_request(&self.worker, &req).await {
+            Ok(WorkerResponse::RowSets { row_sets }) if row_sets.len() == query_count => {
+                Ok(row_sets)
             }
-            let key = macro_type.to_string();
-            let val = Self::get_f32(row, &key)?;
-            macro_values.push(val);
+            Ok(WorkerResponse::RowSets { row_sets }) => Err(format!(
+                "Query batch returned {} row sets for {query_count} queries",
+                row_sets.len()
+            )),
+            Ok(WorkerResponse::Err { message }) => Err(message),
+            Ok(other) => Err(format!("Unexpected response for QueryBatch: {other:?}")),
+            Err(e) => Err(e),
+        }
+    }
+
+    // missing or NULL flags read as false
+    fn get_bool(row: &Map<String, Value>, key: &str) -> Result<bool, String> {
+        match row.get(key) {
+            None | Some(Value::Null) => Ok(false),
+            Some(Value::Number(n)) => Ok(n.as_i64().is_some_and(|v| v != 0)),
+            Some(v) => Err(format!("Unexpected type for '{key}': {v}")),
         }
+    }
+
+    fn map_row_to_product(row: &Map<String, Value>) -> Result<(ProductId, Product), String> {
+        let id: ProductId = Self::get_string(row, "id")?.into();
+        let name = Self::get_string(row, "name")?;
+        let brand = Self::get_string_opt(row, "brand")?;
+        let currency = Self::get_string_opt(row, "currency")?;
+        let price = Self::get_f32_opt(row, "price_per_100g")?.map(|per_100g| Price {
+            per_100g,
+            currency: currency.unwrap_or_default(),
+        });
+        let negligible = Self::get_bool(row, "negligible")?;
+        let ignore_alcohol_calories = Self::get_bool(row, "ignore_alcohol_calories")?;
+
+        let macro_value =
+            |macro_type: MacroElementsType| Self::get_f32(row, &macro_type.to_string());
         let macro_elems = MacroElements::new(
-            macro_values[0],
-            macro_values[1],
-            macro_values[2],
-            macro_values[3],
-            macro_values[4],
-        );
+            macro_value(MacroElementsType::Fat)?,
+            macro_value(MacroElementsType::SaturatedFat)?,
+            macro_value(MacroElementsType::Carbs)?,
+            macro_value(MacroElementsType::Sugar)?,
+            macro_value(MacroElementsType::Protein)?,
+        )
+        .with_calories_override(Self::get_f32_opt(row, "calories_override")?);
 
         let mut micro = Box::new(MicroNutrients::default());
         for micro_type in MicroNutrientsType::iter() {
@@ -153,7 +285,36 @@ impl LocalProductDbConcrete {
             }
         }
 
-        let product = Product::new(name, brand, Box::new(macro_elems), micro, allowed);
+        let flags = Self::get_string_opt(row, "flags")?.unwrap_or_default();
+        let grams_per_ml = Self::get_f32_opt(row, "grams_per_ml")?;
+        let unit_labels = match Self::get_string_opt(row, "unit_labels")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid unit labels of product '{id}': {e}"))?,
+            None => BTreeMap::new(),
+        };
+
+        let serving = Self::get_f32_opt(row, "grams_per_serving")?.map(ServingInfo::new);
+        let cooked = Self::get_string_opt(row, "cooked_id")?
+            .zip(Self::get_f32_opt(row, "cooked_yield")?)
+            .map(|(cooked_id, yield_factor)| CookedVariant::new(cooked_id.into(), yield_factor));
+        let data_quality = Self::get_string_opt(row, "data_quality")?
+            .map(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid data quality of product '{id}': {e}"))
+            })
+            .transpose()?;
+
+        let product = Product::new(name, brand, Box::new(macro_elems), micro, allowed)
+            .with_price(price)
+            .with_negligible(negligible)
+            .with_grams_per_ml(grams_per_ml)
+            .with_unit_labels(unit_labels)
+            .with_serving(serving)
+            .with_cooked(cooked)
+            .with_ignore_alcohol_calories(ignore_alcohol_calories)
+            .with_data_quality(data_quality)
+            .with_barcode(Self::get_string_opt(row, "barcode")?)
+            .with_flag_entries(flags.split(',').filter(|f| !f.is_empty()));
         Ok((id, product))
     }
 
@@ -185,6 +346,33 @@ impl LocalProductDbConcrete {
         }
     }
 
+    fn get_f64(row: &Map<String, Value>, key: &str) -> Result<f64, String> {
+        match row.get(key) {
+            Some(Value::Number(n)) => n
+                .as_f64()
+                .ok_or_else(|| format!("Invalid number for '{key}'")),
+            Some(v) => Err(format!("Unexpected type for '{key}': {v}")),
+            None => Err(format!("Missing float column '{key}'")),
+        }
+    }
+
+    fn get_i64(row: &Map<String, Value>, key: &str) -> Result<i64, String> {
+        match row.get(key) {
+            Some(Value::Number(n)) => n
+                .as_i64()
+                .ok_or_else(|| format!("Invalid integer for '{key}'")),
+            Some(v) => Err(format!("Unexpected type for '{key}': {v}")),
+            None => Err(format!("Missing integer column '{key}'")),
+        }
+    }
+
+    fn get_i64_opt(row: &Map<String, Value>, key: &str) -> Result<Option<i64>, String> {
+        match row.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(_) => Self::get_i64(row, key).map(Some),
+        }
+    }
+
     fn get_u16_opt(row: &Map<String, Value>, key: &str) -> Result<Option<u16>, String> {
         match row.get(key) {
             None | Some(Value::Null) => Ok(None),
@@ -198,27 +386,134 @@ impl LocalProductDbConcrete {
     }
 
     /// Create a new DB handle backed by the OPFS worker.
-    pub async fn new(key: &str) -> Option<Self> {
-        let worker = Self::get_or_create_worker().ok()?;
+    pub async fn open(key: &str) -> Result<Self, String> {
+        let worker = Self::get_or_create_worker()?;
 
         let db = Self {
             worker,
             key: key.to_string(),
+            active_profile: DEFAULT_PROFILE_ID.to_string(),
         };
 
         if !INITIALIZED_DB
             .try_with(|cell| cell.borrow().contains(key))
             .unwrap_or_else(|_| false)
         {
-            if let Err(e) = db.init_db().await {
-                tracing::error!("Failed to initialise wasm local DB: {e}");
-                return None;
-            }
+            db.init_db()
+                .await
+                .map_err(|e| format!("Failed to initialise wasm local DB: {e}"))?;
             INITIALIZED_DB
                 .try_with(|cell| cell.borrow_mut().insert(key.to_string()))
                 .ok();
         }
-        Some(db)
+        Ok(db)
+    }
+
+    /// Opens an existing database without creating its tables or migrating it. Fails for files
+    /// of another schema version, e.g. with [`NewerSchema`] for newer ones.
+    pub async fn open_read_only(key: &str) -> Result<Self, String> {
+        let db = Self {
+            worker: Self::get_or_create_worker()?,
+            key: key.to_string(),
+            active_profile: DEFAULT_PROFILE_ID.to_string(),
+        };
+        let tables = db
+            .send_query(
+                format!(
+                    "SELECT name FROM sqlite_master WHERE type='table' \
+                     AND name IN ('products', '{}');",
+                    migrations::SCHEMA_VERSION_TABLE
+                ),
+                Vec::new(),
+            )
+            .await?;
+        let has_table = |name: &str| {
+            tables
+                .iter()
+                .any(|row| row.get("name").and_then(Value::as_str) == Some(name))
+        };
+        if !has_table("products") {
+            return Err(format!("'{key}' is not a meal planner database"));
+        }
+        let version = if has_table(migrations::SCHEMA_VERSION_TABLE) {
+            db.schema_version().await?
+        } else {
+            0
+        };
+        migrations::check_current(version)?;
+        db.check_typed_columns().await?;
+        Ok(db)
+    }
+
+    /// Copies a consistent snapshot of the database to the OPFS file `path`.
+    pub async fn backup_to(&self, path: &str) -> Result<(), String> {
+        self.send_file_request("Backup", path)
+            .await
+            .map_err(|e| format!("Failed to back up to '{path}': {e}"))
+    }
+
+    /// Replaces the whole database with the OPFS backup at `path`, which is opened read-only and
+    /// checked first. Older backups are migrated, newer ones are refused with [`NewerSchema`]
+    /// before anything is replaced. The default profile becomes active.
+    pub async fn restore_from(&mut self, path: &str) -> Result<(), String> {
+        self.send_file_request("Restore", path).await.map_err(|e| {
+            match e
+                .strip_prefix(NEWER_BACKUP_ERROR)
+                .and_then(|found| found.parse().ok())
+            {
+                Some(found) => NewerSchema {
+                    found,
+                    supported: migrations::latest_version(),
+                }
+                .into(),
+                None => format!("Failed to restore from '{path}': {e}"),
+            }
+        })?;
+        self.migrate().await?;
+        self.check_typed_columns().await?;
+        self.active_profile = DEFAULT_PROFILE_ID.to_string();
+        Ok(())
+    }
+
+    /// Makes the next queries read and write the data of `profile_id`, the handles sharing the
+    /// connection each set their own profile before querying.
+    pub(super) fn use_profile(&mut self, profile_id: &str) {
+        if self.active_profile != profile_id {
+            self.active_profile = profile_id.to_string();
+        }
+    }
+
+    /// Runs `PRAGMA integrity_check`, the error lists every problem found.
+    pub async fn integrity_check(&self) -> Result<(), String> {
+        let problems = self
+            .send_query("PRAGMA integrity_check;".to_string(), Vec::new())
+            .await?
+            .iter()
+            .map(|row| Self::get_string(row, "integrity_check"))
+            .collect::<Result<Vec<_>, _>>()?;
+        if problems == ["ok"] {
+            Ok(())
+        } else {
+            Err(format!("Database is damaged: {}", problems.join("; ")))
+        }
+    }
+
+    // `request_type` is `Backup` or `Restore`
+    async fn send_file_request(&self, request_type: &str, path: &str) -> Result<(), String> {
+        let req = json!({
+            "type": request_type,
+            "database_file": self.key,
+            "path": path,
+            // restoring a database written by a newer library is refused
+            "supported_version": migrations::latest_version(),
+        });
+
+        match Self::send_request(&self.worker, &req).await {
+            Ok(WorkerResponse::Ok) => Ok(()),
+            Ok(WorkerResponse::Err { message }) => Err(message),
+            Ok(other) => Err(format!("Unexpected response for {request_type}: {other:?}")),
+            Err(e) => Err(e),
+        }
     }
 
     async fn init_db(&self) -> Result<(), String> {
@@ -241,73 +536,201 @@ impl LocalProductDbConcrete {
             }
         }
 
-        self.send_exec(schema_statements()).await
+        let existing_db = !self
+            .send_query(
+                "SELECT name FROM sqlite_master WHERE type='table' AND name='products';"
+                    .to_string(),
+                Vec::new(),
+            )
+            .await?
+            .is_empty();
+        self.send_exec(schema_statements()).await?;
+        if existing_db {
+            self.migrate().await?;
+        } else {
+            self.send_exec(vec![SqlStatement {
+                sql: migrations::record_version_sql(migrations::latest_version()),
+                bind: None,
+            }])
+            .await?;
+        }
+        self.check_typed_columns().await
     }
-}
 
-#[async_trait::async_trait(?Send)]
-impl Database for LocalProductDbConcrete {
-    async fn get_products_matching_criteria(
-        &self,
-        criteria: &[DbSearchCriteria],
-    ) -> BTreeMap<String, Product> {
-        let (sql, bind) = build_select_query(criteria);
-        match self.send_query(sql, bind).await {
-            Ok(rows) => rows
-                .into_iter()
-                .filter_map(|row| match Self::map_row_to_product(&row) {
-                    Ok((id, product)) => Some((id, product)),
-                    Err(e) => {
-                        tracing::error!("Failed to map row to product: {e}");
-                        None
-                    }
-                })
-                .collect(),
-            Err(e) => {
-                tracing::error!("worker query failed: {e}");
-                BTreeMap::new()
-            }
+    async fn check_typed_columns(&self) -> Result<(), String> {
+        let typed_columns = migrations::typed_columns();
+        let existing = self.table_columns(&typed_columns).await?;
+        migrations::check_typed_columns(&existing)
+    }
+
+    // stored schema version, the version table has to exist
+    async fn schema_version(&self) -> Result<u32, String> {
+        let rows = self
+            .send_query(migrations::current_version_sql(), Vec::new())
+            .await?;
+        Ok(rows
+            .first()
+            .map(|row| Self::get_u16_opt(row, "version"))
+            .transpose()?
+            .flatten()
+            .map_or(0, u32::from))
+    }
+
+    // applies migrations newer than the stored schema version, each in its own transaction
+    async fn migrate(&self) -> Result<(), String> {
+        let current_version = self.schema_version().await?;
+        migrations::check_supported(current_version)?;
+
+        for migration in migrations::pending_migrations(current_version) {
+            let version = migration.version;
+            // the column check runs in the same transaction, so it can't go stale
+            self.in_transaction(async || {
+                let mut statements: Vec<SqlStatement> = migration
+                    .sql
+                    .iter()
+                    .map(|sql| SqlStatement {
+                        sql: sql.clone(),
+                        bind: None,
+                    })
+                    .collect();
+                statements.extend(self.add_missing_columns(&migration.columns).await?);
+                statements.push(SqlStatement {
+                    sql: migrations::record_version_sql(version),
+                    bind: None,
+                });
+                self.send_exec(statements).await
+            })
+            .await
+            .map_err(|e| format!("Schema migration {version} failed: {e}"))?;
+        }
+
+        let statements = self
+            .add_missing_columns(&migrations::enum_columns())
+            .await?;
+        if statements.is_empty() {
+            return Ok(());
         }
+        self.send_exec(statements).await
     }
 
-    async fn set_product_unit(
-        &mut self,
-        product_id: &str,
-        allowed_unit: AllowedUnitsType,
-        unit_data: UnitData,
-    ) -> Result<(), String> {
-        let stmt = SqlStatement {
-            sql: format!(
-                "UPDATE allowed_units SET \"{col}\" = ?, \"{col} divider\" = ? WHERE id = ?;",
-                col = allowed_unit.to_string()
-            ),
-            bind: Some(vec![
-                unit_data.amount.into(),
-                unit_data.divider.into(),
-                product_id.into(),
-            ]),
-        };
-        self.send_exec(vec![stmt]).await
+    // column names of every table `columns` belong to
+    async fn table_columns<'a>(
+        &self,
+        columns: &'a [AddColumn],
+    ) -> Result<HashMap<&'a str, HashSet<String>>, String> {
+        let mut tables: Vec<&str> = columns.iter().map(|column| column.table).collect();
+        tables.sort_unstable();
+        tables.dedup();
+        if tables.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let row_sets = self
+            .send_query_batch(
+                tables
+                    .iter()
+                    .map(|table| SqlStatement {
+                        sql: format!("SELECT name FROM pragma_table_info('{table}');"),
+                        bind: None,
+                    })
+                    .collect(),
+            )
+            .await?;
+        Ok(tables
+            .into_iter()
+            .zip(row_sets)
+            .map(|(table, rows)| {
+                let names = rows
+                    .iter()
+                    .filter_map(|row| Self::get_string_opt(row, "name").ok().flatten())
+                    .collect();
+                (table, names)
+            })
+            .collect())
     }
-}
 
-#[async_trait::async_trait(?Send)]
-impl MutableDatabase for LocalProductDbConcrete {
-    async fn add_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
-        let stmts = build_insert_statements(product_id, &product);
-        self.send_exec(stmts).await
+    async fn add_missing_columns(
+        &self,
+        columns: &[AddColumn],
+    ) -> Result<Vec<SqlStatement>, String> {
+        let existing = self.table_columns(columns).await?;
+        let mut statements = Vec::new();
+        for column in columns {
+            if existing[column.table].contains(&column.column) {
+                continue;
+            }
+            statements.push(SqlStatement {
+                sql: column.alter_sql(),
+                bind: None,
+            });
+            if let Some(backfill) = &column.backfill {
+                statements.push(SqlStatement {
+                    sql: backfill.clone(),
+                    bind: None,
+                });
+            }
+        }
+        Ok(statements)
     }
 
-    async fn update_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+    // statements upserting `product`, archiving the stored version when it differs
+    async fn update_statements(
+        &self,
+        product_id: &str,
+        product: &Product,
+    ) -> Result<Vec<SqlStatement>, String> {
+        let (price, currency) = price_bind(product);
+        let (grams_per_ml, unit_labels) = volume_bind(product);
+        let (cooked_id, cooked_yield) = cooked_bind(product);
+        let (data_quality, verified_data) = data_quality_bind(product);
         let mut stmts = Vec::new();
+        // saving an unchanged product adds no version
+        if let Some(previous) = self.get_product_by_id(product_id).await
+            && previous != *product
+        {
+            let json = serde_json::to_string(&previous)
+                .map_err(|e| format!("Failed to archive product '{product_id}': {e}"))?;
+            stmts.push(SqlStatement {
+                sql: "INSERT INTO products_history (id, archived_at, product) VALUES (?, ?, ?);"
+                    .to_string(),
+                bind: Some(vec![product_id.into(), unix_now().into(), json.into()]),
+            });
+        }
         stmts.push(SqlStatement {
-            sql: "INSERT INTO products (id, name, brand) VALUES (?, ?, ?) \
-                  ON CONFLICT(id) DO UPDATE SET name = excluded.name, brand = excluded.brand;"
-                .to_string(),
+            sql: format!(
+                "INSERT INTO products (id, name, brand, price_per_100g, currency, negligible, \
+                 grams_per_ml, unit_labels, grams_per_serving, cooked_id, cooked_yield, \
+                 calories_override, ignore_alcohol_calories, data_quality, verified_data, \
+                 barcode, uuid) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, {uuid}) \
+                 ON CONFLICT(id) DO UPDATE SET name = excluded.name, brand = excluded.brand, \
+                 price_per_100g = excluded.price_per_100g, currency = excluded.currency, \
+                 negligible = excluded.negligible, grams_per_ml = excluded.grams_per_ml, \
+                 unit_labels = excluded.unit_labels, \
+                 grams_per_serving = excluded.grams_per_serving, \
+                 cooked_id = excluded.cooked_id, cooked_yield = excluded.cooked_yield, \
+                 calories_override = excluded.calories_override, \
+                 ignore_alcohol_calories = excluded.ignore_alcohol_calories, \
+                 data_quality = excluded.data_quality, verified_data = excluded.verified_data, \
+                 barcode = excluded.barcode;",
+                uuid = migrations::NEW_UUID_SQL
+            ),
             bind: Some(vec![
                 product_id.into(),
                 product.name().into(),
                 product.brand().map(|b| b.into()).unwrap_or(Value::Null),
+                price,
+                currency,
+                i32::from(product.negligible).into(),
+                grams_per_ml,
+                unit_labels,
+                serving_bind(product),
+                cooked_id,
+                cooked_yield,
+                calories_override_bind(product),
+                i32::from(product.ignore_alcohol_calories).into(),
+                data_quality,
+                verified_data,
+                barcode_bind(product),
             ]),
         });
 
@@ -417,47 +840,1030 @@ impl MutableDatabase for LocalProductDbConcrete {
             ),
             bind: Some(allowed_bind_all),
         });
+        stmts.extend(flag_statements(product_id, product));
+        Ok(stmts)
+    }
+}
 
-        self.send_exec(stmts).await
+#[async_trait::async_trait(?Send)]
+impl Database for LocalProductDbConcrete {
+    async fn get_products_matching_criteria(
+        &self,
+        criteria: &[DbSearchCriteria],
+    ) -> BTreeMap<ProductId, Product> {
+        let (sql, bind) = build_select_query(criteria);
+        match self.send_query(sql, bind).await {
+            Ok(rows) => rows
+                .into_iter()
+                .filter_map(|row| match Self::map_row_to_product(&row) {
+                    Ok((id, product)) => Some((id, product)),
+                    Err(e) => {
+                        tracing::error!("Failed to map row to product: {e}");
+                        None
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                tracing::error!("worker query failed: {e}");
+                BTreeMap::new()
+            }
+        }
     }
 
-    async fn delete_product(&mut self, product_id: &str) -> Result<(), String> {
+    async fn set_product_unit(
+        &mut self,
+        product_id: &str,
+        allowed_unit: AllowedUnitsType,
+        unit_data: UnitData,
+    ) -> Result<(), String> {
         let stmt = SqlStatement {
-            sql: "DELETE FROM products WHERE id = ?;".to_string(),
-            bind: Some(vec![product_id.into()]),
+            sql: format!(
+                "UPDATE allowed_units SET \"{col}\" = ?, \"{col} divider\" = ? WHERE id = ?;",
+                col = allowed_unit.to_string()
+            ),
+            bind: Some(vec![
+                unit_data.amount.into(),
+                unit_data.divider.into(),
+                product_id.into(),
+            ]),
         };
         self.send_exec(vec![stmt]).await
     }
-}
 
-fn build_select_query(criteria: &[DbSearchCriteria]) -> (String, Vec<Value>) {
-    let mut sql = format!(
-        "SELECT p.id, p.name, p.brand, {} , {} , {} FROM products p \
-         INNER JOIN macro_elements me ON p.id = me.id \
-         INNER JOIN allowed_units au ON p.id = au.id \
-         LEFT JOIN micronutrients mn ON p.id = mn.id",
-        macro_columns_select(),
-        micro_columns_select(),
-        allowed_columns_select()
-    );
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        let rows = self
+            .send_query(build_data_quality_query(), Vec::new())
+            .await?;
+        let row = rows
+            .first()
+            .ok_or_else(|| "Data quality query returned no rows".to_string())?;
+        let count = |key: &str| {
+            row.get(key)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| format!("Missing count column '{key}'"))
+        };
+        Ok(DataQualitySummary {
+            total_products: count("total_products")?,
+            without_micro_nutrients: count("without_micro_nutrients")?,
+            incomplete_micro_nutrients: count("incomplete_micro_nutrients")?,
+            without_extra_units: count("without_extra_units")?,
+            without_brand: count("without_brand")?,
+            without_barcode: count("without_barcode")?,
+            unused_in_plans: count("unused_in_plans")?,
+        })
+    }
 
-    let mut bind = Vec::new();
-    if criteria.is_empty() {
-        sql.push_str(";");
-        return (sql, bind);
+    async fn get_products_page(
+        &self,
+        sort: SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, Product)>, String> {
+        let order = match sort {
+            SortKey::Name => "p.name".to_string(),
+            SortKey::Brand => "p.brand".to_string(),
+            SortKey::Calories => calories_sql(),
+            SortKey::Protein => format!("me.\"{}\"", MacroElementsType::Protein),
+        };
+        let sql = format!(
+            "{} ORDER BY {order}, p.id LIMIT ? OFFSET ?;",
+            select_products_sql()
+        );
+        self.send_query(sql, vec![limit.into(), offset.into()])
+            .await?
+            .iter()
+            .map(Self::map_row_to_product)
+            .collect()
     }
 
-    // Only ById supported today
-    if let Some(DbSearchCriteria::ById(name)) = criteria.first() {
-        sql.push_str(" WHERE p.name LIKE ? || '%';");
-        bind.push(Value::from(name.clone()));
-    } else {
-        sql.push_str(";");
+    async fn least_complete_products(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, CompletenessReport)>, String> {
+        let missing = MicroNutrientsType::iter()
+            .map(|m| format!("(mn.\"{m}\" IS NULL)"))
+            .collect::<Vec<_>>();
+        let sql = format!(
+            "SELECT p.id, {flags} FROM products p LEFT JOIN micronutrients mn ON p.id = mn.id \
+             WHERE {any_missing} ORDER BY {missing_count} DESC, p.id LIMIT {limit};",
+            flags = MicroNutrientsType::iter()
+                .zip(&missing)
+                .map(|(m, is_null)| format!("{is_null} AS \"{m}\""))
+                .collect::<Vec<_>>()
+                .join(", "),
+            any_missing = missing.join(" OR "),
+            missing_count = missing.join(" + "),
+        );
+        self.send_query(sql, Vec::new())
+            .await?
+            .iter()
+            .map(|row| {
+                let missing = MicroNutrientsType::iter()
+                    .filter(|m| {
+                        row.get(&m.to_string())
+                            .and_then(Value::as_i64)
+                            .is_some_and(|is_null| is_null != 0)
+                    })
+                    .collect();
+                Ok((
+                    Self::get_string(row, "id")?.into(),
+                    CompletenessReport::from_missing(missing),
+                ))
+            })
+            .collect()
     }
 
+    async fn list_brands(&self) -> Result<BTreeMap<String, u64>, String> {
+        self.send_query(
+            "SELECT brand, COUNT(*) AS products FROM products \
+             WHERE brand IS NOT NULL AND brand != '' GROUP BY brand;"
+                .to_string(),
+            Vec::new(),
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            let brand = row
+                .get("brand")
+                .and_then(Value::as_str)
+                .ok_or_else(|| "Missing column 'brand'".to_string())?;
+            let count = row
+                .get("products")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| "Missing count column 'products'".to_string())?;
+            Ok((brand.to_string(), count))
+        })
+        .collect()
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats, String> {
+        let quality = self.data_quality_summary().await?;
+        let count = |row: &Map<String, Value>, key: &str| {
+            row.get(key)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| format!("Missing count column '{key}'"))
+        };
+        let products_per_brand = self.list_brands().await?;
+        let size_bytes = self
+            .send_query(
+                "SELECT page_count * page_size AS size_bytes \
+                 FROM pragma_page_count(), pragma_page_size();"
+                    .to_string(),
+                Vec::new(),
+            )
+            .await?
+            .first()
+            .map(|row| count(row, "size_bytes"))
+            .transpose()?;
+        Ok(DatabaseStats {
+            quality,
+            products_per_brand,
+            size_bytes,
+        })
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl MutableDatabase for LocalProductDbConcrete {
+    async fn add_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        let stmts = build_insert_statements(product_id, &product);
+        self.send_exec(stmts).await
+    }
+
+    async fn add_products_bulk(
+        &mut self,
+        products: Vec<(String, Product)>,
+    ) -> Result<BulkReport, String> {
+        let groups = products
+            .iter()
+            .map(|(product_id, product)| build_insert_statements(product_id, product))
+            .collect();
+        let errors = self.send_exec_groups(groups).await?;
+        Ok(BulkReport {
+            entries: products
+                .into_iter()
+                .zip(errors)
+                .map(|((product_id, _), error)| (product_id, error.map_or(Ok(()), Err)))
+                .collect(),
+        })
+    }
+
+    async fn update_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        let stmts = self.update_statements(product_id, &product).await?;
+        self.send_exec(stmts).await
+    }
+
+    async fn delete_product(&mut self, product_id: &str) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "DELETE FROM products WHERE id = ?;".to_string(),
+            bind: Some(vec![product_id.into()]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn delete_products(&mut self, product_ids: &[String]) -> Result<(), String> {
+        if product_ids.is_empty() {
+            return Ok(());
+        }
+        let placeholders = vec!["?"; product_ids.len()].join(", ");
+        let stmt = SqlStatement {
+            sql: format!("DELETE FROM products WHERE id IN ({placeholders});"),
+            bind: Some(product_ids.iter().map(|id| id.as_str().into()).collect()),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    // every product is read first, then all of them are written in one exec
+    async fn edit_products(
+        &mut self,
+        product_ids: &[String],
+        edit: &ProductEdit,
+    ) -> Result<(), String> {
+        let mut stmts = Vec::new();
+        for product_id in product_ids {
+            let mut product = self
+                .get_product_by_id(product_id)
+                .await
+                .ok_or_else(|| format!("Product '{product_id}' not found"))?;
+            edit.apply(&mut product);
+            stmts.extend(self.update_statements(product_id, &product).await?);
+        }
+        self.send_exec(stmts).await
+    }
+
+    async fn get_product_history(&self, product_id: &str) -> Result<Vec<ProductVersion>, String> {
+        self.send_query(
+            "SELECT archived_at, product FROM products_history WHERE id = ? \
+             ORDER BY archived_at DESC, rowid DESC;"
+                .to_string(),
+            vec![product_id.into()],
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(ProductVersion {
+                archived_at: u64::try_from(Self::get_i64(row, "archived_at")?)
+                    .map_err(|e| format!("Invalid archive time: {e}"))?,
+                product: serde_json::from_str(&Self::get_string(row, "product")?)
+                    .map_err(|e| format!("Invalid archived product '{product_id}': {e}"))?,
+            })
+        })
+        .collect()
+    }
+
+    async fn rename_product(&mut self, product_id: &str, product: Product) -> Result<(), String> {
+        let uuid = self
+            .get_product_uuid(product_id)
+            .await?
+            .ok_or_else(|| format!("Product '{product_id}' not found"))?;
+        let new_id = product.id();
+        let mut stmts = vec![SqlStatement {
+            sql: "DELETE FROM products WHERE id = ?;".to_string(),
+            bind: Some(vec![product_id.into()]),
+        }];
+        stmts.extend(build_insert_statements(&new_id, &product));
+        stmts.push(SqlStatement {
+            sql: "UPDATE products SET uuid = ? WHERE id = ?;".to_string(),
+            bind: Some(vec![uuid.into(), new_id.as_str().into()]),
+        });
+        stmts.extend(
+            migrations::PRODUCT_ID_REFERENCES
+                .iter()
+                .map(|(table, column)| SqlStatement {
+                    sql: format!("UPDATE {table} SET {column} = ? WHERE {column} = ?;"),
+                    bind: Some(vec![new_id.as_str().into(), product_id.into()]),
+                }),
+        );
+        self.send_exec(stmts).await
+    }
+
+    async fn get_product_uuid(&self, product_id: &str) -> Result<Option<String>, String> {
+        let rows = self
+            .send_query(
+                "SELECT uuid FROM products WHERE id = ?;".to_string(),
+                vec![product_id.into()],
+            )
+            .await?;
+        match rows.first() {
+            Some(row) => Self::get_string_opt(row, "uuid"),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_product_by_uuid(&self, uuid: &str) -> Result<Option<Product>, String> {
+        let rows = self
+            .send_query(
+                "SELECT id FROM products WHERE uuid = ?;".to_string(),
+                vec![uuid.into()],
+            )
+            .await?;
+        match rows.first() {
+            Some(row) => Ok(self.get_product_by_id(&Self::get_string(row, "id")?).await),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        self.send_query(
+            "SELECT id, name FROM profiles ORDER BY id;".to_string(),
+            Vec::new(),
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(Profile {
+                id: Self::get_string(row, "id")?,
+                name: Self::get_string(row, "name")?,
+            })
+        })
+        .collect()
+    }
+
+    async fn set_profile(&mut self, profile: &Profile) -> Result<(), String> {
+        profile.validate()?;
+        let stmt = SqlStatement {
+            sql: "INSERT INTO profiles (id, name) VALUES (?, ?) \
+                  ON CONFLICT(id) DO UPDATE SET name = excluded.name;"
+                .to_string(),
+            bind: Some(vec![profile.id.clone().into(), profile.name.clone().into()]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn delete_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "DELETE FROM profiles WHERE id = ?;".to_string(),
+            bind: Some(vec![profile_id.into()]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn active_profile(&self) -> Result<String, String> {
+        Ok(self.active_profile.clone())
+    }
+
+    async fn set_active_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        let rows = self
+            .send_query(
+                "SELECT id FROM profiles WHERE id = ?;".to_string(),
+                vec![profile_id.into()],
+            )
+            .await?;
+        if rows.is_empty() {
+            return Err(format!("Profile '{profile_id}' not found"));
+        }
+        self.active_profile = profile_id.to_string();
+        Ok(())
+    }
+
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String> {
+        let rows = self
+            .send_query(
+                "SELECT age_years, sex, weight_kg, height_cm, activity_level FROM user_profile \
+                 WHERE profile_id = ?;"
+                    .to_string(),
+                vec![self.active_profile.clone().into()],
+            )
+            .await?;
+        let Some(row) = rows.first() else {
+            return Ok(None);
+        };
+        let age_years = Self::get_u16_opt(row, "age_years")?
+            .ok_or_else(|| "Missing field 'age_years'".to_string())?;
+        Ok(Some(UserProfile {
+            age_years: u8::try_from(age_years).map_err(|e| format!("Invalid stored age: {e}"))?,
+            sex: Self::get_string(row, "sex")?.parse()?,
+            weight_kg: Self::get_f32(row, "weight_kg")?,
+            height_cm: Self::get_f32(row, "height_cm")?,
+            activity_level: Self::get_string(row, "activity_level")?.parse()?,
+        }))
+    }
+
+    async fn set_user_profile(&mut self, profile: &UserProfile) -> Result<(), String> {
+        profile.validate()?;
+        let stmt = SqlStatement {
+            sql: "INSERT INTO user_profile \
+                  (profile_id, age_years, sex, weight_kg, height_cm, activity_level) \
+                  VALUES (?, ?, ?, ?, ?, ?) \
+                  ON CONFLICT(profile_id) DO UPDATE SET age_years = excluded.age_years, \
+                  sex = excluded.sex, \
+                  weight_kg = excluded.weight_kg, height_cm = excluded.height_cm, \
+                  activity_level = excluded.activity_level;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                profile.age_years.into(),
+                profile.sex.to_string().into(),
+                profile.weight_kg.into(),
+                profile.height_cm.into(),
+                profile.activity_level.to_string().into(),
+            ]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn get_targets_record(&self) -> Result<Option<TargetsRecord>, String> {
+        let rows = self
+            .send_query(
+                "SELECT computed_at, weight_kg FROM targets_records WHERE profile_id = ?;"
+                    .to_string(),
+                vec![self.active_profile.clone().into()],
+            )
+            .await?;
+        let Some(row) = rows.first() else {
+            return Ok(None);
+        };
+        Ok(Some(TargetsRecord {
+            computed_at: u64::try_from(Self::get_i64(row, "computed_at")?)
+                .map_err(|e| format!("Invalid targets calculation time: {e}"))?,
+            weight_kg: Self::get_f32(row, "weight_kg")?,
+        }))
+    }
+
+    async fn set_targets_record(&mut self, record: &TargetsRecord) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "INSERT INTO targets_records (profile_id, computed_at, weight_kg) \
+                  VALUES (?, ?, ?) \
+                  ON CONFLICT(profile_id) DO UPDATE SET computed_at = excluded.computed_at, \
+                  weight_kg = excluded.weight_kg;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                record.computed_at.into(),
+                record.weight_kg.into(),
+            ]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn add_beverage_entry(&mut self, entry: &BeverageEntry) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "INSERT INTO beverage_log (profile_id, drunk_at, volume_ml, product_id) \
+                  VALUES (?, ?, ?, ?);"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                entry.drunk_at.into(),
+                entry.volume_ml.into(),
+                entry
+                    .product_id
+                    .clone()
+                    .map_or(Value::Null, |id| String::from(id).into()),
+            ]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn get_beverage_entries(&self, from: u64, to: u64) -> Result<Vec<BeverageEntry>, String> {
+        self.send_query(
+            "SELECT drunk_at, volume_ml, product_id FROM beverage_log \
+             WHERE profile_id = ? AND drunk_at >= ? AND drunk_at < ? ORDER BY drunk_at, rowid;"
+                .to_string(),
+            vec![self.active_profile.clone().into(), from.into(), to.into()],
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(BeverageEntry {
+                drunk_at: u64::try_from(Self::get_i64(row, "drunk_at")?)
+                    .map_err(|e| format!("Invalid drink time: {e}"))?,
+                volume_ml: u32::try_from(Self::get_i64(row, "volume_ml")?)
+                    .map_err(|e| format!("Invalid drink volume: {e}"))?,
+                product_id: Self::get_string_opt(row, "product_id")?.map(ProductId::from),
+            })
+        })
+        .collect()
+    }
+
+    async fn add_activity(&mut self, activity: &ActivityLog) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "INSERT INTO activity_log (profile_id, performed_at, kcal_burned, name) \
+                  VALUES (?, ?, ?, ?);"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                activity.performed_at.into(),
+                activity.kcal_burned.into(),
+                activity.name.clone().map_or(Value::Null, Into::into),
+            ]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn get_activities(&self, from: u64, to: u64) -> Result<Vec<ActivityLog>, String> {
+        self.send_query(
+            "SELECT performed_at, kcal_burned, name FROM activity_log \
+             WHERE profile_id = ? AND performed_at >= ? AND performed_at < ? \
+             ORDER BY performed_at, rowid;"
+                .to_string(),
+            vec![self.active_profile.clone().into(), from.into(), to.into()],
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(ActivityLog {
+                performed_at: u64::try_from(Self::get_i64(row, "performed_at")?)
+                    .map_err(|e| format!("Invalid activity time: {e}"))?,
+                kcal_burned: u32::try_from(Self::get_i64(row, "kcal_burned")?)
+                    .map_err(|e| format!("Invalid burned energy: {e}"))?,
+                name: Self::get_string_opt(row, "name")?,
+            })
+        })
+        .collect()
+    }
+
+    async fn get_eaten(&self, day: u64) -> Result<Vec<EatenItem>, String> {
+        self.send_query(
+            "SELECT meal, product_id, grams FROM eaten_log \
+             WHERE profile_id = ? AND day = ? ORDER BY rowid;"
+                .to_string(),
+            vec![self.active_profile.clone().into(), day.into()],
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(EatenItem {
+                meal: Self::get_string(row, "meal")?,
+                product_id: Self::get_string(row, "product_id")?.into(),
+                grams: Self::get_f64(row, "grams")?,
+            })
+        })
+        .collect()
+    }
+
+    async fn mark_eaten(&mut self, day: u64, item: &EatenItem) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "INSERT INTO eaten_log (profile_id, day, meal, product_id, grams) \
+                  VALUES (?, ?, ?, ?, ?) \
+                  ON CONFLICT(profile_id, day, meal, product_id) DO UPDATE SET grams = excluded.grams;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                day.into(),
+                item.meal.clone().into(),
+                item.product_id.to_string().into(),
+                item.grams.into(),
+            ]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn unmark_eaten(&mut self, day: u64, meal: &str, product_id: &str) -> Result<(), String> {
+        let stmt = SqlStatement {
+            sql: "DELETE FROM eaten_log \
+                  WHERE profile_id = ? AND day = ? AND meal = ? AND product_id = ?;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                day.into(),
+                meal.into(),
+                product_id.into(),
+            ]),
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn get_meal_notes(&self, day: u64) -> Result<Vec<MealNote>, String> {
+        self.send_query(
+            "SELECT meal, note, photos FROM meal_notes \
+             WHERE profile_id = ? AND day = ? ORDER BY rowid;"
+                .to_string(),
+            vec![self.active_profile.clone().into(), day.into()],
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            let meal = Self::get_string(row, "meal")?;
+            let photos = serde_json::from_str(&Self::get_string(row, "photos")?)
+                .map_err(|e| format!("Invalid photos of '{meal}': {e}"))?;
+            Ok(MealNote {
+                text: Self::get_string(row, "note")?,
+                meal,
+                photos,
+            })
+        })
+        .collect()
+    }
+
+    async fn set_meal_note(&mut self, day: u64, note: &MealNote) -> Result<(), String> {
+        let stmt = if note.is_empty() {
+            SqlStatement {
+                sql: "DELETE FROM meal_notes WHERE profile_id = ? AND day = ? AND meal = ?;"
+                    .to_string(),
+                bind: Some(vec![
+                    self.active_profile.clone().into(),
+                    day.into(),
+                    note.meal.clone().into(),
+                ]),
+            }
+        } else {
+            let photos = serde_json::to_string(&note.photos)
+                .map_err(|e| format!("Failed to serialize photos of '{}': {e}", note.meal))?;
+            SqlStatement {
+                sql: "INSERT INTO meal_notes (profile_id, day, meal, note, photos) \
+                      VALUES (?, ?, ?, ?, ?) \
+                      ON CONFLICT(profile_id, day, meal) DO UPDATE SET note = excluded.note, \
+                      photos = excluded.photos;"
+                    .to_string(),
+                bind: Some(vec![
+                    self.active_profile.clone().into(),
+                    day.into(),
+                    note.meal.clone().into(),
+                    note.text.clone().into(),
+                    photos.into(),
+                ]),
+            }
+        };
+        self.send_exec(vec![stmt]).await
+    }
+
+    async fn get_shopping_list_ids(&self) -> Result<Vec<String>, String> {
+        self.send_query(
+            "SELECT id FROM shopping_lists WHERE profile_id = ? ORDER BY rowid;".to_string(),
+            vec![self.active_profile.clone().into()],
+        )
+        .await?
+        .iter()
+        .map(|row| Self::get_string(row, "id"))
+        .collect()
+    }
+
+    async fn get_shopping_list(&self, list_id: &str) -> Result<Option<ShoppingList>, String> {
+        self.send_query(
+            "SELECT list FROM shopping_lists WHERE profile_id = ? AND id = ?;".to_string(),
+            vec![self.active_profile.clone().into(), list_id.into()],
+        )
+        .await?
+        .first()
+        .map(|row| {
+            serde_json::from_str(&Self::get_string(row, "list")?)
+                .map_err(|e| format!("Invalid shopping list '{list_id}': {e}"))
+        })
+        .transpose()
+    }
+
+    async fn save_shopping_list(
+        &mut self,
+        list_id: &str,
+        list: &ShoppingList,
+    ) -> Result<(), String> {
+        let json = serde_json::to_string(list)
+            .map_err(|e| format!("Failed to serialize shopping list '{list_id}': {e}"))?;
+        self.send_exec(vec![SqlStatement {
+            sql: "INSERT INTO shopping_lists (profile_id, id, list) VALUES (?, ?, ?) \
+                  ON CONFLICT(profile_id, id) DO UPDATE SET list = excluded.list;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                list_id.into(),
+                json.into(),
+            ]),
+        }])
+        .await
+    }
+
+    async fn delete_shopping_list(&mut self, list_id: &str) -> Result<(), String> {
+        self.send_exec(vec![SqlStatement {
+            sql: "DELETE FROM shopping_lists WHERE profile_id = ? AND id = ?;".to_string(),
+            bind: Some(vec![self.active_profile.clone().into(), list_id.into()]),
+        }])
+        .await
+    }
+
+    async fn get_pantry(&self) -> Result<Pantry, String> {
+        let rows = self
+            .send_query(
+                "SELECT product_id, grams, expiry_day FROM pantry WHERE profile_id = ? \
+                 ORDER BY rowid;"
+                    .to_string(),
+                vec![self.active_profile.clone().into()],
+            )
+            .await?;
+        let mut pantry = Pantry::new();
+        for row in &rows {
+            let product_id = Self::get_string(row, "product_id")?;
+            let grams = Self::get_f64(row, "grams")?;
+            match Self::get_i64_opt(row, "expiry_day")? {
+                Some(day) => pantry.add_with_expiry(
+                    &product_id,
+                    grams,
+                    u64::try_from(day).map_err(|e| format!("Invalid expiry day: {e}"))?,
+                ),
+                None => pantry.add(&product_id, grams),
+            }
+        }
+        Ok(pantry)
+    }
+
+    // the statements of one exec share a transaction
+    async fn save_pantry(&mut self, pantry: &Pantry) -> Result<(), String> {
+        let mut statements = vec![SqlStatement {
+            sql: "DELETE FROM pantry WHERE profile_id = ?;".to_string(),
+            bind: Some(vec![self.active_profile.clone().into()]),
+        }];
+        statements.extend(pantry.stock().iter().map(|(product_id, grams)| {
+            SqlStatement {
+                sql: "INSERT INTO pantry (profile_id, product_id, grams, expiry_day) \
+                  VALUES (?, ?, ?, ?);"
+                    .to_string(),
+                bind: Some(vec![
+                    self.active_profile.clone().into(),
+                    product_id.as_str().into(),
+                    (*grams).into(),
+                    pantry.expiry(product_id).map_or(Value::Null, Into::into),
+                ]),
+            }
+        }));
+        self.send_exec(statements).await
+    }
+
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        self.send_query(
+            "SELECT id FROM plans WHERE profile_id = ? ORDER BY rowid;".to_string(),
+            vec![self.active_profile.clone().into()],
+        )
+        .await?
+        .iter()
+        .map(|row| Self::get_string(row, "id"))
+        .collect()
+    }
+
+    async fn get_plan(&self, plan_id: &str) -> Result<Option<Solution>, String> {
+        self.send_query(
+            "SELECT plan FROM plans WHERE profile_id = ? AND id = ?;".to_string(),
+            vec![self.active_profile.clone().into(), plan_id.into()],
+        )
+        .await?
+        .first()
+        .map(|row| {
+            serde_json::from_str(&Self::get_string(row, "plan")?)
+                .map_err(|e| format!("Invalid plan '{plan_id}': {e}"))
+        })
+        .transpose()
+    }
+
+    async fn save_plan(&mut self, plan_id: &str, plan: &Solution) -> Result<(), String> {
+        let json = serde_json::to_string(plan)
+            .map_err(|e| format!("Failed to serialize plan '{plan_id}': {e}"))?;
+        self.send_exec(vec![SqlStatement {
+            sql: "INSERT INTO plans (profile_id, id, plan) VALUES (?, ?, ?) \
+                  ON CONFLICT(profile_id, id) DO UPDATE SET plan = excluded.plan;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                plan_id.into(),
+                json.into(),
+            ]),
+        }])
+        .await
+    }
+
+    async fn find_solved_plan(&self, fingerprint: u64) -> Result<Option<Solution>, String> {
+        self.send_query(
+            "SELECT plan FROM plans WHERE profile_id = ? AND fingerprint = ? \
+             ORDER BY rowid DESC LIMIT 1;"
+                .to_string(),
+            vec![
+                self.active_profile.clone().into(),
+                format!("{fingerprint:016x}").into(),
+            ],
+        )
+        .await?
+        .first()
+        .map(|row| {
+            serde_json::from_str(&Self::get_string(row, "plan")?)
+                .map_err(|e| format!("Invalid solved plan: {e}"))
+        })
+        .transpose()
+    }
+
+    async fn save_solved_plan(
+        &mut self,
+        plan_id: &str,
+        fingerprint: u64,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        let json = serde_json::to_string(plan)
+            .map_err(|e| format!("Failed to serialize plan '{plan_id}': {e}"))?;
+        self.send_exec(vec![SqlStatement {
+            sql: "INSERT INTO plans (profile_id, id, plan, fingerprint) VALUES (?, ?, ?, ?) \
+                  ON CONFLICT(profile_id, id) DO UPDATE SET \
+                  plan = excluded.plan, fingerprint = excluded.fingerprint;"
+                .to_string(),
+            bind: Some(vec![
+                self.active_profile.clone().into(),
+                plan_id.into(),
+                json.into(),
+                format!("{fingerprint:016x}").into(),
+            ]),
+        }])
+        .await
+    }
+
+    async fn delete_plan(&mut self, plan_id: &str) -> Result<(), String> {
+        self.send_exec(vec![SqlStatement {
+            sql: "DELETE FROM plans WHERE profile_id = ? AND id = ?;".to_string(),
+            bind: Some(vec![self.active_profile.clone().into(), plan_id.into()]),
+        }])
+        .await
+    }
+
+    async fn get_app_settings(&self) -> Result<AppSettings, String> {
+        let rows = self
+            .send_query(
+                "SELECT key, value FROM app_settings;".to_string(),
+                Vec::new(),
+            )
+            .await?;
+        let rows = rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    Self::get_string(row, "key")?,
+                    Self::get_string(row, "value")?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(AppSettings::from_rows(rows))
+    }
+
+    async fn set_app_settings(&mut self, settings: &AppSettings) -> Result<(), String> {
+        let mut stmts = vec![SqlStatement {
+            sql: "DELETE FROM app_settings;".to_string(),
+            bind: None,
+        }];
+        stmts.extend(
+            settings
+                .to_rows()
+                .into_iter()
+                .map(|(key, value)| SqlStatement {
+                    sql: "INSERT INTO app_settings (key, value) VALUES (?, ?);".to_string(),
+                    bind: Some(vec![key.into(), value.into()]),
+                }),
+        );
+        self.send_exec(stmts).await
+    }
+
+    async fn get_usage_stats(&self) -> Result<UsageStats, String> {
+        let rows = self
+            .send_query(
+                "SELECT name, count FROM usage_stats;".to_string(),
+                Vec::new(),
+            )
+            .await?;
+        let rows = rows
+            .iter()
+            .map(|row| Ok((Self::get_string(row, "name")?, Self::get_i64(row, "count")?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(UsageStats::from_rows(rows))
+    }
+
+    async fn set_usage_stats(&mut self, stats: &UsageStats) -> Result<(), String> {
+        let mut stmts = vec![SqlStatement {
+            sql: "DELETE FROM usage_stats;".to_string(),
+            bind: None,
+        }];
+        stmts.extend(
+            stats
+                .to_rows()
+                .into_iter()
+                .map(|(name, count)| SqlStatement {
+                    sql: "INSERT INTO usage_stats (name, count) VALUES (?, ?);".to_string(),
+                    bind: Some(vec![name.into(), count.into()]),
+                }),
+        );
+        self.send_exec(stmts).await
+    }
+}
+
+/// Select of whole products, rows are read by `map_row_to_product`.
+fn select_products_sql() -> String {
+    format!(
+        "SELECT p.id, p.name, p.brand, p.price_per_100g, p.currency, p.negligible, \
+         p.grams_per_ml, p.unit_labels, p.grams_per_serving, p.cooked_id, p.cooked_yield, \
+         p.calories_override, p.ignore_alcohol_calories, p.data_quality, p.barcode, \
+         (SELECT GROUP_CONCAT(f.kind || ':' || f.flag) FROM product_flags f WHERE f.id = p.id) AS flags, \
+         {} , {} , {} FROM products p \
+         INNER JOIN macro_elements me ON p.id = me.id \
+         INNER JOIN allowed_units au ON p.id = au.id \
+         LEFT JOIN micronutrients mn ON p.id = mn.id",
+        macro_columns_select(),
+        micro_columns_select(),
+        allowed_columns_select()
+    )
+}
+
+fn build_select_query(criteria: &[DbSearchCriteria]) -> (String, Vec<Value>) {
+    let mut sql = select_products_sql();
+    let mut bind = Vec::new();
+    if criteria.is_empty() {
+        sql.push_str(";");
+        return (sql, bind);
+    }
+
+    let mut conditions = Vec::with_capacity(criteria.len());
+    for criterion in criteria {
+        match criterion {
+            DbSearchCriteria::ById(name) => {
+                conditions.push("p.name LIKE ? || '%'".to_string());
+                bind.push(Value::from(name.clone()));
+            }
+            DbSearchCriteria::ByBarcode(barcode) => {
+                conditions.push("p.barcode = ?".to_string());
+                bind.push(Value::from(barcode.clone()));
+            }
+            DbSearchCriteria::ByBrand(brand) => {
+                conditions.push("p.brand = ?".to_string());
+                bind.push(Value::from(brand.clone()));
+            }
+            DbSearchCriteria::ByNutrientAtLeast(nutrient, value) => {
+                conditions.push(nutrient_comparison(*nutrient, ">=", *value, &mut bind));
+            }
+            DbSearchCriteria::ByNutrientAtMost(nutrient, value) => {
+                conditions.push(nutrient_comparison(*nutrient, "<=", *value, &mut bind));
+            }
+            DbSearchCriteria::ExcludeAllergens(allergens) => {
+                if allergens.is_empty() {
+                    continue;
+                }
+                let placeholders = vec!["?"; allergens.len()].join(", ");
+                conditions.push(format!(
+                    "NOT EXISTS (SELECT 1 FROM product_flags f WHERE f.id = p.id \
+                     AND f.kind = '{ALLERGEN_FLAG_KIND}' AND f.flag IN ({placeholders}))"
+                ));
+                bind.extend(allergens.iter().map(|a| Value::from(a.to_string())));
+            }
+            DbSearchCriteria::VerifiedDataOnly => conditions.push("p.verified_data".to_string()),
+        }
+    }
+    sql.push_str(" WHERE ");
+    sql.push_str(&conditions.join(" AND "));
+    sql.push(';');
+
     (sql, bind)
 }
 
+/// Calories per 100g, only overridden ones are stored, others are derived as in
+/// `Product::computed_calories`.
+fn calories_sql() -> String {
+    format!(
+        "COALESCE(p.calories_override, 9 * me.\"{}\" + 4 * me.\"{}\" + 4 * me.\"{}\" \
+         + CASE WHEN p.ignore_alcohol_calories THEN 0 \
+         ELSE {ALCOHOL_KCAL_PER_G} * COALESCE(mn.\"{}\", 0) END)",
+        MacroElementsType::Fat,
+        MacroElementsType::Carbs,
+        MacroElementsType::Protein,
+        MicroNutrientsType::Alcohol
+    )
+}
+
+fn nutrient_comparison(
+    nutrient: NutrientType,
+    op: &str,
+    value: f32,
+    bind: &mut Vec<Value>,
+) -> String {
+    // NaN or infinity can't be bound as JSON number, such criterion matches nothing
+    if !value.is_finite() {
+        return "0".to_string();
+    }
+    let column = match nutrient {
+        NutrientType::Macro(MacroElementsType::Calories) => calories_sql(),
+        NutrientType::Macro(macro_type) => format!("me.\"{macro_type}\""),
+        NutrientType::Micro(micro_type) => format!("mn.\"{micro_type}\""),
+    };
+    bind.push(Value::from(f64::from(value)));
+    format!("{column} {op} ?")
+}
+
+fn build_data_quality_query() -> String {
+    let micro_nulls = MicroNutrientsType::iter()
+        .map(|m| format!("mn.\"{m}\" IS NULL"))
+        .collect::<Vec<_>>();
+    let extra_unit_nulls = AllowedUnitsType::iter()
+        .filter(|u| *u != AllowedUnitsType::Gram)
+        .map(|u| format!("au.\"{u}\" IS NULL"))
+        .collect::<Vec<_>>();
+    format!(
+        "WITH planned(id) AS ({planned}) \
+         SELECT COUNT(*) AS total_products, \
+         COALESCE(SUM(CASE WHEN {no_micro} THEN 1 ELSE 0 END), 0) AS without_micro_nutrients, \
+         COALESCE(SUM(CASE WHEN {any_micro_missing} THEN 1 ELSE 0 END), 0) AS incomplete_micro_nutrients, \
+         COALESCE(SUM(CASE WHEN {no_extra_units} THEN 1 ELSE 0 END), 0) AS without_extra_units, \
+         COALESCE(SUM(CASE WHEN p.brand IS NULL OR p.brand = '' THEN 1 ELSE 0 END), 0) AS without_brand, \
+         COALESCE(SUM(CASE WHEN p.barcode IS NULL OR p.barcode = '' THEN 1 ELSE 0 END), 0) AS without_barcode, \
+         COALESCE(SUM(CASE WHEN NOT EXISTS (SELECT 1 FROM planned WHERE planned.id = p.id) \
+         THEN 1 ELSE 0 END), 0) AS unused_in_plans \
+         FROM products p \
+         LEFT JOIN micronutrients mn ON p.id = mn.id \
+         LEFT JOIN allowed_units au ON p.id = au.id;",
+        no_micro = micro_nulls.join(" AND "),
+        any_micro_missing = micro_nulls.join(" OR "),
+        no_extra_units = extra_unit_nulls.join(" AND "),
+        planned = migrations::planned_product_ids_sql(),
+    )
+}
+
 fn macro_columns_select() -> String {
     MacroElementsType::iter()
         .filter(|m| *m != MacroElementsType::Calories)
@@ -486,14 +1892,113 @@ fn allowed_columns_select() -> String {
         .join(", ")
 }
 
+fn price_bind(product: &Product) -> (Value, Value) {
+    match &product.price {
+        Some(price) => (
+            Value::from(price.per_100g),
+            Value::from(price.currency.as_str()),
+        ),
+        None => (Value::Null, Value::Null),
+    }
+}
+
+// unit labels are stored as JSON, a map of unit names to strings always serializes
+fn volume_bind(product: &Product) -> (Value, Value) {
+    let grams_per_ml = product.grams_per_ml.map_or(Value::Null, Value::from);
+    let unit_labels = if product.unit_labels.is_empty() {
+        Value::Null
+    } else {
+        serde_json::to_string(&product.unit_labels).map_or(Value::Null, Value::from)
+    };
+    (grams_per_ml, unit_labels)
+}
+
+fn data_quality_bind(product: &Product) -> (Value, Value) {
+    let data_quality = product.data_quality().map_or(Value::Null, |quality| {
+        serde_json::to_string(quality).map_or(Value::Null, Value::from)
+    });
+    (data_quality, i32::from(product.has_verified_data()).into())
+}
+
+fn barcode_bind(product: &Product) -> Value {
+    product.barcode.as_deref().map_or(Value::Null, Value::from)
+}
+
+fn cooked_bind(product: &Product) -> (Value, Value) {
+    product
+        .cooked
+        .as_ref()
+        .map_or((Value::Null, Value::Null), |cooked| {
+            (
+                Value::from(cooked.product_id.as_str()),
+                Value::from(cooked.yield_factor),
+            )
+        })
+}
+
+fn calories_override_bind(product: &Product) -> Value {
+    product
+        .macro_elements
+        .calories_override()
+        .map_or(Value::Null, Value::from)
+}
+
+fn serving_bind(product: &Product) -> Value {
+    product.serving.map_or(Value::Null, |serving| {
+        Value::from(serving.grams_per_serving)
+    })
+}
+
+// deletes stored flags of the product and inserts the current ones
+fn flag_statements(product_id: &str, product: &Product) -> Vec<SqlStatement> {
+    let mut stmts = vec![SqlStatement {
+        sql: "DELETE FROM product_flags WHERE id = ?;".to_string(),
+        bind: Some(vec![product_id.into()]),
+    }];
+    stmts.extend(
+        product
+            .flag_rows()
+            .into_iter()
+            .map(|(kind, flag)| SqlStatement {
+                sql: "INSERT INTO product_flags (id, kind, flag) VALUES (?, ?, ?);".to_string(),
+                bind: Some(vec![product_id.into(), kind.into(), flag.into()]),
+            }),
+    );
+    stmts
+}
+
 fn build_insert_statements(product_id: &str, product: &Product) -> Vec<SqlStatement> {
+    let (price, currency) = price_bind(product);
+    let (grams_per_ml, unit_labels) = volume_bind(product);
+    let (cooked_id, cooked_yield) = cooked_bind(product);
+    let (data_quality, verified_data) = data_quality_bind(product);
     let mut stmts = Vec::new();
     stmts.push(SqlStatement {
-        sql: "INSERT INTO products (id, name, brand) VALUES (?, ?, ?);".to_string(),
+        sql: format!(
+            "INSERT INTO products (id, name, brand, price_per_100g, currency, negligible, \
+             grams_per_ml, unit_labels, grams_per_serving, cooked_id, cooked_yield, \
+             calories_override, ignore_alcohol_calories, data_quality, verified_data, barcode, \
+             uuid) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, {uuid});",
+            uuid = migrations::NEW_UUID_SQL
+        ),
         bind: Some(vec![
             product_id.into(),
             product.name().into(),
             product.brand().map(|b| b.into()).unwrap_or(Value::Null),
+            price,
+            currency,
+            i32::from(product.negligible).into(),
+            grams_per_ml,
+            unit_labels,
+            serving_bind(product),
+            cooked_id,
+            cooked_yield,
+            calories_override_bind(product),
+            i32::from(product.ignore_alcohol_calories).into(),
+            data_quality,
+            verified_data,
+            barcode_bind(product),
         ]),
     });
 
@@ -577,21 +2082,61 @@ fn build_insert_statements(product_id: &str, product: &Product) -> Vec<SqlStatem
         ),
         bind: Some(allowed_bind_all),
     });
+    stmts.extend(flag_statements(product_id, product));
 
     stmts
 }
 
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn unix_now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
 fn schema_statements() -> Vec<SqlStatement> {
     vec![
         SqlStatement {
             sql: "PRAGMA foreign_keys=ON;".to_string(),
             bind: None,
         },
+        SqlStatement {
+            sql: migrations::create_version_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_profiles_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::insert_default_profile_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_user_profile_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_usage_stats_table_sql(),
+            bind: None,
+        },
         SqlStatement {
             sql: r#"CREATE TABLE IF NOT EXISTS products (
     id TEXT NOT NULL PRIMARY KEY,
     name TEXT NOT NULL,
-    brand TEXT
+    brand TEXT,
+    price_per_100g FLOAT,
+    currency TEXT,
+    negligible INTEGER NOT NULL DEFAULT 0,
+    grams_per_ml FLOAT,
+    unit_labels TEXT,
+    uuid TEXT,
+    grams_per_serving FLOAT,
+    cooked_id TEXT,
+    cooked_yield FLOAT,
+    calories_override FLOAT,
+    ignore_alcohol_calories INTEGER NOT NULL DEFAULT 0,
+    data_quality TEXT,
+    verified_data INTEGER NOT NULL DEFAULT 1,
+    barcode TEXT
 );"#
             .to_string(),
             bind: None,
@@ -610,37 +2155,84 @@ fn schema_statements() -> Vec<SqlStatement> {
             bind: None,
         },
         SqlStatement {
-            sql: r#"CREATE TABLE IF NOT EXISTS micronutrients (
+            sql: format!(
+                "CREATE TABLE IF NOT EXISTS micronutrients (
     id TEXT NOT NULL PRIMARY KEY,
-    "Fiber" FLOAT,
-    "Zinc" FLOAT,
-    "Sodium" FLOAT,
-    "Alcohol" FLOAT,
+{}
     FOREIGN KEY(id) REFERENCES products(id) ON DELETE CASCADE
-);"#
-            .to_string(),
+);",
+                MicroNutrientsType::iter()
+                    .map(|m| format!("    \"{m}\" FLOAT,"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
             bind: None,
         },
         SqlStatement {
-            sql: r#"CREATE TABLE IF NOT EXISTS allowed_units (
+            sql: format!(
+                "CREATE TABLE IF NOT EXISTS allowed_units (
     id TEXT NOT NULL PRIMARY KEY,
-    "gram" INTEGER NOT NULL DEFAULT 1,
-    "gram divider" INTEGER NOT NULL DEFAULT 1,
-    "piece" INTEGER,
-    "piece divider" INTEGER,
-    "cup" INTEGER,
-    "cup divider" INTEGER,
-    "tablespoon" INTEGER,
-    "tablespoon divider" INTEGER,
-    "teaspoon" INTEGER,
-    "teaspoon divider" INTEGER,
-    "box" INTEGER,
-    "box divider" INTEGER,
-    "custom" INTEGER,
-    "custom divider" INTEGER,
+{}
     FOREIGN KEY(id) REFERENCES products(id) ON DELETE CASCADE
-);"#
-            .to_string(),
+);",
+                AllowedUnitsType::iter()
+                    .map(|u| match u {
+                        AllowedUnitsType::Gram => format!(
+                            "    \"{u}\" INTEGER NOT NULL DEFAULT 1,\n    \"{u} divider\" INTEGER NOT NULL DEFAULT 1,"
+                        ),
+                        _ => format!("    \"{u}\" INTEGER,\n    \"{u} divider\" INTEGER,"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_product_flags_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_products_history_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_beverage_log_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_activity_log_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_targets_records_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_app_settings_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_eaten_log_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_meal_notes_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_shopping_lists_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_pantry_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_plans_table_sql(),
+            bind: None,
+        },
+        SqlStatement {
+            sql: migrations::create_brand_index_sql(),
             bind: None,
         },
     ]
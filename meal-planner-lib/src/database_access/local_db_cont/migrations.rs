@@ -0,0 +1,644 @@
+//! Versioned schema migrations shared by the native and wasm local databases.
+//!
+//! Each backend reads the highest version stored in the `schema_version` table, applies every
+//! newer [`Migration`] in order and records its version in the same transaction. Columns derived
+//! from [`MicroNutrientsType`] and [`AllowedUnitsType`] are additionally synced on every open, so
+//! adding a variant needs no migration. Renaming or removing anything needs a new step here.
+//!
+//! Products are read by column name, never by position, so the order of enum variants doesn't
+//! matter. [`check_typed_columns`] verifies on open that every variant has its column.
+
+use std::collections::{HashMap, HashSet};
+
+use strum::IntoEnumIterator;
+
+use crate::data_types::{
+    AllowedUnitsType, DEFAULT_PROFILE_ID, MacroElementsType, MicroNutrientsType,
+};
+use crate::database_access::NewerSchema;
+
+pub(super) const SCHEMA_VERSION_TABLE: &str = "schema_version";
+
+/// SQL expression generating a random product uuid, kept when the product gets renamed.
+pub(super) const NEW_UUID_SQL: &str = "lower(hex(randomblob(16)))";
+
+/// `(table, column)` pairs referencing products by id, moved to the new id on a rename.
+pub(super) const PRODUCT_ID_REFERENCES: [(&str, &str); 5] = [
+    ("products_history", "id"),
+    ("eaten_log", "product_id"),
+    ("beverage_log", "product_id"),
+    ("pantry", "product_id"),
+    ("products", "cooked_id"),
+];
+
+/// Adds `column` to `table` unless it already exists, then runs `backfill` for the new column.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct AddColumn {
+    pub table: &'static str,
+    pub column: String,
+    pub col_type: &'static str,
+    pub backfill: Option<String>,
+}
+
+impl AddColumn {
+    fn new(table: &'static str, column: String, col_type: &'static str) -> Self {
+        Self {
+            table,
+            column,
+            col_type,
+            backfill: None,
+        }
+    }
+
+    pub fn alter_sql(&self) -> String {
+        format!(
+            "ALTER TABLE {} ADD COLUMN \"{}\" {};",
+            self.table, self.column, self.col_type
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Migration {
+    pub version: u32,
+    /// Statements run before the columns are added, e.g. creating new tables.
+    pub sql: Vec<String>,
+    pub columns: Vec<AddColumn>,
+}
+
+pub(super) fn create_profiles_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS profiles (
+    id TEXT NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL
+);"
+    .to_string()
+}
+
+pub(super) fn insert_default_profile_sql() -> String {
+    format!("INSERT OR IGNORE INTO profiles (id, name) VALUES ('{DEFAULT_PROFILE_ID}', 'Default');")
+}
+
+/// One body profile per entry of the `profiles` table.
+pub(super) fn create_user_profile_table_sql() -> String {
+    user_profile_table_sql("user_profile")
+}
+
+fn user_profile_table_sql(table: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {table} (
+    profile_id TEXT NOT NULL PRIMARY KEY,
+    age_years INTEGER NOT NULL,
+    sex TEXT NOT NULL,
+    weight_kg FLOAT NOT NULL,
+    height_cm FLOAT NOT NULL,
+    activity_level TEXT NOT NULL,
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    )
+}
+
+// schema of version 4, when the database had a single user
+fn single_user_profile_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS user_profile (
+    id INTEGER NOT NULL PRIMARY KEY CHECK (id = 1),
+    age_years INTEGER NOT NULL,
+    sex TEXT NOT NULL,
+    weight_kg FLOAT NOT NULL,
+    height_cm FLOAT NOT NULL,
+    activity_level TEXT NOT NULL
+);"
+    .to_string()
+}
+
+pub(super) fn create_usage_stats_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS usage_stats (
+    name TEXT NOT NULL PRIMARY KEY,
+    count INTEGER NOT NULL
+);"
+    .to_string()
+}
+
+/// Allergens and diet flags, one row per flag. `kind` tells which of the two `flag` belongs to.
+pub(super) fn create_product_flags_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS product_flags (
+    id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    flag TEXT NOT NULL,
+    PRIMARY KEY (id, kind, flag),
+    FOREIGN KEY(id) REFERENCES products(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Previous versions of updated products, serialized as JSON so they survive schema changes.
+/// Kept after the product is deleted.
+pub(super) fn create_products_history_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS products_history (
+    id TEXT NOT NULL,
+    archived_at INTEGER NOT NULL,
+    product TEXT NOT NULL
+);"
+    .to_string()
+}
+
+/// Drinks logged by each profile.
+pub(super) fn create_beverage_log_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS beverage_log (
+    profile_id TEXT NOT NULL,
+    drunk_at INTEGER NOT NULL,
+    volume_ml INTEGER NOT NULL,
+    product_id TEXT,
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Workouts logged or imported by each profile.
+pub(super) fn create_activity_log_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS activity_log (
+    profile_id TEXT NOT NULL,
+    performed_at INTEGER NOT NULL,
+    kcal_burned INTEGER NOT NULL,
+    name TEXT,
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Weight and time the nutrition targets of each profile were last computed with.
+pub(super) fn create_targets_records_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS targets_records (
+    profile_id TEXT NOT NULL PRIMARY KEY,
+    computed_at INTEGER NOT NULL,
+    weight_kg FLOAT NOT NULL,
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// App preferences as `(key, value)` rows, shared by every profile.
+pub(super) fn create_app_settings_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS app_settings (
+    key TEXT NOT NULL PRIMARY KEY,
+    value TEXT NOT NULL
+);"
+    .to_string()
+}
+
+/// Notes on how meals turned out, `photos` is the JSON of the note's photo list.
+pub(super) fn create_meal_notes_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS meal_notes (
+    profile_id TEXT NOT NULL,
+    day INTEGER NOT NULL,
+    meal TEXT NOT NULL,
+    note TEXT NOT NULL,
+    photos TEXT NOT NULL,
+    PRIMARY KEY(profile_id, day, meal),
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Shopping lists with their check-off state, `list` is the JSON of the whole list.
+pub(super) fn create_shopping_lists_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS shopping_lists (
+    profile_id TEXT NOT NULL,
+    id TEXT NOT NULL,
+    list TEXT NOT NULL,
+    PRIMARY KEY(profile_id, id),
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Products at home, `expiry_day` counted in days since the Unix epoch.
+pub(super) fn create_pantry_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS pantry (
+    profile_id TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    grams REAL NOT NULL,
+    expiry_day INTEGER,
+    PRIMARY KEY(profile_id, product_id),
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Saved plans, `plan` is the JSON of the whole solution with meals in their display order,
+/// `fingerprint` the hex hash of the constraints a solved plan came from.
+pub(super) fn create_plans_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS plans (
+    profile_id TEXT NOT NULL,
+    id TEXT NOT NULL,
+    plan TEXT NOT NULL,
+    fingerprint TEXT,
+    PRIMARY KEY(profile_id, id),
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Planned products marked as eaten, `day` counted in days since the Unix epoch.
+pub(super) fn create_eaten_log_table_sql() -> String {
+    "CREATE TABLE IF NOT EXISTS eaten_log (
+    profile_id TEXT NOT NULL,
+    day INTEGER NOT NULL,
+    meal TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    grams REAL NOT NULL,
+    PRIMARY KEY(profile_id, day, meal, product_id),
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);"
+    .to_string()
+}
+
+/// Speeds up brand filtering and listing.
+pub(super) fn create_brand_index_sql() -> String {
+    "CREATE INDEX IF NOT EXISTS products_brand ON products(brand);".to_string()
+}
+
+pub(super) fn create_version_table_sql() -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {SCHEMA_VERSION_TABLE} (version INTEGER NOT NULL PRIMARY KEY);"
+    )
+}
+
+pub(super) fn current_version_sql() -> String {
+    format!("SELECT MAX(version) AS version FROM {SCHEMA_VERSION_TABLE};")
+}
+
+/// Ids of the products in the saved plans of every profile, read from the plan JSON the way
+/// [`ProductId::new`](crate::data_types::ProductId::new) builds them.
+pub(super) fn planned_product_ids_sql() -> String {
+    "SELECT DISTINCT CASE WHEN COALESCE(json_extract(t.value, '$.brand'), '') = '' \
+         THEN json_extract(t.value, '$.name') \
+         ELSE json_extract(t.value, '$.name') || ' (' || json_extract(t.value, '$.brand') || ')' \
+     END FROM plans, json_tree(plans.plan) AS t \
+     WHERE t.key = 'product' AND t.type = 'object'"
+        .to_string()
+}
+
+pub(super) fn record_version_sql(version: u32) -> String {
+    format!("INSERT INTO {SCHEMA_VERSION_TABLE} (version) VALUES ({version});")
+}
+
+/// All migrations, ordered by version. Freshly created databases are stamped with the last one.
+#[allow(clippy::too_many_lines)]
+pub(super) fn migrations() -> Vec<Migration> {
+    vec![
+        // databases created by the old rusqlite-based `db_wrappers` stored units without
+        // dividers, every stored unit amount was a whole number so the divider defaults to 1
+        Migration {
+            version: 1,
+            sql: Vec::new(),
+            columns: AllowedUnitsType::iter()
+                .flat_map(|unit| {
+                    let divider = format!("{unit} divider");
+                    let col_type = match unit {
+                        AllowedUnitsType::Gram => "INTEGER NOT NULL DEFAULT 1",
+                        _ => "INTEGER",
+                    };
+                    [
+                        AddColumn::new("allowed_units", unit.to_string(), "INTEGER"),
+                        AddColumn {
+                            backfill: Some(format!(
+                                "UPDATE allowed_units SET \"{divider}\" = 1 WHERE \"{unit}\" IS NOT NULL;"
+                            )),
+                            ..AddColumn::new("allowed_units", divider, col_type)
+                        },
+                    ]
+                })
+                .collect(),
+        },
+        Migration {
+            version: 2,
+            sql: Vec::new(),
+            columns: vec![
+                AddColumn::new("products", "price_per_100g".to_string(), "FLOAT"),
+                AddColumn::new("products", "currency".to_string(), "TEXT"),
+            ],
+        },
+        Migration {
+            version: 3,
+            sql: Vec::new(),
+            columns: vec![AddColumn::new(
+                "products",
+                "negligible".to_string(),
+                "INTEGER NOT NULL DEFAULT 0",
+            )],
+        },
+        Migration {
+            version: 4,
+            sql: vec![single_user_profile_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 5,
+            sql: vec![create_usage_stats_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 6,
+            sql: vec![create_product_flags_table_sql()],
+            columns: Vec::new(),
+        },
+        // the single user profile becomes the one of the default profile, selecting columns by
+        // name also works when the table was already created with the new schema
+        Migration {
+            version: 7,
+            sql: vec![
+                create_profiles_table_sql(),
+                insert_default_profile_sql(),
+                user_profile_table_sql("user_profile_v7"),
+                format!(
+                    "INSERT INTO user_profile_v7 \
+                     (profile_id, age_years, sex, weight_kg, height_cm, activity_level) \
+                     SELECT '{DEFAULT_PROFILE_ID}', age_years, sex, weight_kg, height_cm, \
+                     activity_level FROM user_profile;"
+                ),
+                "DROP TABLE user_profile;".to_string(),
+                "ALTER TABLE user_profile_v7 RENAME TO user_profile;".to_string(),
+            ],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 8,
+            sql: vec![create_products_history_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 9,
+            sql: vec![create_beverage_log_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 10,
+            sql: vec![create_app_settings_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 11,
+            sql: vec![create_eaten_log_table_sql()],
+            columns: Vec::new(),
+        },
+        // unit labels are stored as a JSON object keyed by unit
+        Migration {
+            version: 12,
+            sql: Vec::new(),
+            columns: vec![
+                AddColumn::new("products", "grams_per_ml".to_string(), "FLOAT"),
+                AddColumn::new("products", "unit_labels".to_string(), "TEXT"),
+            ],
+        },
+        Migration {
+            version: 13,
+            sql: Vec::new(),
+            columns: vec![AddColumn {
+                backfill: Some(format!(
+                    "UPDATE products SET uuid = {NEW_UUID_SQL} WHERE uuid IS NULL;"
+                )),
+                ..AddColumn::new("products", "uuid".to_string(), "TEXT")
+            }],
+        },
+        Migration {
+            version: 14,
+            sql: vec![create_meal_notes_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 15,
+            sql: Vec::new(),
+            columns: vec![AddColumn::new(
+                "products",
+                "grams_per_serving".to_string(),
+                "FLOAT",
+            )],
+        },
+        Migration {
+            version: 16,
+            sql: vec![create_brand_index_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 17,
+            sql: Vec::new(),
+            columns: vec![
+                AddColumn::new("products", "cooked_id".to_string(), "TEXT"),
+                AddColumn::new("products", "cooked_yield".to_string(), "FLOAT"),
+            ],
+        },
+        Migration {
+            version: 18,
+            sql: Vec::new(),
+            columns: vec![AddColumn::new(
+                "products",
+                "calories_override".to_string(),
+                "FLOAT",
+            )],
+        },
+        Migration {
+            version: 19,
+            sql: Vec::new(),
+            columns: vec![AddColumn::new(
+                "products",
+                "ignore_alcohol_calories".to_string(),
+                "INTEGER NOT NULL DEFAULT 0",
+            )],
+        },
+        Migration {
+            version: 20,
+            sql: vec![create_shopping_lists_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 21,
+            sql: vec![create_plans_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 22,
+            sql: Vec::new(),
+            columns: vec![AddColumn::new("plans", "fingerprint".to_string(), "TEXT")],
+        },
+        Migration {
+            version: 23,
+            sql: Vec::new(),
+            columns: vec![
+                AddColumn::new("products", "data_quality".to_string(), "TEXT"),
+                AddColumn::new(
+                    "products",
+                    "verified_data".to_string(),
+                    "INTEGER NOT NULL DEFAULT 1",
+                ),
+            ],
+        },
+        Migration {
+            version: 24,
+            sql: Vec::new(),
+            columns: vec![AddColumn::new("products", "barcode".to_string(), "TEXT")],
+        },
+        Migration {
+            version: 25,
+            sql: vec![create_activity_log_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 26,
+            sql: vec![create_targets_records_table_sql()],
+            columns: Vec::new(),
+        },
+        Migration {
+            version: 27,
+            sql: vec![create_pantry_table_sql()],
+            columns: Vec::new(),
+        },
+    ]
+}
+
+pub(crate) fn latest_version() -> u32 {
+    migrations().last().map_or(0, |m| m.version)
+}
+
+/// Fails with [`NewerSchema`] when `current_version` was written by a newer library, whose
+/// changes this one can't know about.
+pub(super) fn check_supported(current_version: u32) -> Result<(), String> {
+    let supported = latest_version();
+    if current_version > supported {
+        return Err(NewerSchema {
+            found: current_version,
+            supported,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Like [`check_supported`], also failing for older versions: databases opened read-only can't
+/// be migrated.
+pub(super) fn check_current(current_version: u32) -> Result<(), String> {
+    check_supported(current_version)?;
+    let latest = latest_version();
+    if current_version < latest {
+        return Err(format!(
+            "Database of schema {current_version} needs a migration to schema {latest}, \
+             open it with write access once"
+        ));
+    }
+    Ok(())
+}
+
+pub(super) fn pending_migrations(current_version: u32) -> Vec<Migration> {
+    migrations()
+        .into_iter()
+        .filter(|m| m.version > current_version)
+        .collect()
+}
+
+/// Columns for every micro nutrient and unit, synced after the versioned migrations.
+pub(super) fn enum_columns() -> Vec<AddColumn> {
+    MicroNutrientsType::iter()
+        .map(|m| AddColumn::new("micronutrients", m.to_string(), "FLOAT"))
+        .chain(AllowedUnitsType::iter().flat_map(|u| {
+            [
+                AddColumn::new("allowed_units", u.to_string(), "INTEGER"),
+                AddColumn::new("allowed_units", format!("{u} divider"), "INTEGER"),
+            ]
+        }))
+        .collect()
+}
+
+/// Column of every stored macro element, micro nutrient and unit variant.
+pub(super) fn typed_columns() -> Vec<AddColumn> {
+    MacroElementsType::iter()
+        // calories are derived from the other macro elements, overrides are stored with products
+        .filter(|m| *m != MacroElementsType::Calories)
+        .map(|m| AddColumn::new("macro_elements", m.to_string(), "FLOAT"))
+        .chain(enum_columns())
+        .collect()
+}
+
+/// Fails when a column of [`typed_columns`] is missing from `existing`, the columns of each table.
+pub(super) fn check_typed_columns(existing: &HashMap<&str, HashSet<String>>) -> Result<(), String> {
+    let missing: Vec<String> = typed_columns()
+        .into_iter()
+        .filter(|c| {
+            !existing
+                .get(c.table)
+                .is_some_and(|cols| cols.contains(&c.column))
+        })
+        .map(|c| format!("{}.\"{}\"", c.table, c.column))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Database schema doesn't match the nutrient and unit types, missing columns: {}",
+            missing.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_strictly_increasing_from_one() {
+        let versions: Vec<u32> = migrations().iter().map(|m| m.version).collect();
+        let expected: Vec<u32> = (1..=u32::try_from(versions.len()).unwrap()).collect();
+        assert_eq!(versions, expected);
+        assert_eq!(latest_version(), *versions.last().unwrap());
+    }
+
+    #[test]
+    fn pending_migrations_skip_applied_versions() {
+        assert_eq!(pending_migrations(0), migrations());
+        assert!(pending_migrations(latest_version()).is_empty());
+        let pending: Vec<u32> = pending_migrations(1).iter().map(|m| m.version).collect();
+        assert_eq!(
+            pending,
+            vec![
+                2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+                25, 26, 27
+            ]
+        );
+    }
+
+    // products are read by column name, so names must not repeat within a product row
+    #[test]
+    fn typed_column_names_are_unique() {
+        let columns = typed_columns();
+        let names: HashSet<&str> = columns.iter().map(|c| c.column.as_str()).collect();
+        assert_eq!(names.len(), columns.len());
+    }
+
+    #[test]
+    fn missing_typed_columns_are_reported() {
+        let mut existing: HashMap<&str, HashSet<String>> = HashMap::new();
+        for column in typed_columns() {
+            existing
+                .entry(column.table)
+                .or_default()
+                .insert(column.column);
+        }
+        assert_eq!(check_typed_columns(&existing), Ok(()));
+
+        existing
+            .get_mut("micronutrients")
+            .unwrap()
+            .remove(&MicroNutrientsType::Zinc.to_string());
+        let error = check_typed_columns(&existing).unwrap_err();
+        assert!(error.contains(&format!("micronutrients.\"{}\"", MicroNutrientsType::Zinc)));
+    }
+
+    #[test]
+    fn enum_columns_cover_every_variant() {
+        let columns = enum_columns();
+        for m in MicroNutrientsType::iter() {
+            assert!(columns.iter().any(|c| c.column == m.to_string()));
+        }
+        for u in AllowedUnitsType::iter() {
+            assert!(columns.iter().any(|c| c.column == format!("{u} divider")));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub(super) mod local_db;
+pub(super) mod migrations;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod local_db_generic;
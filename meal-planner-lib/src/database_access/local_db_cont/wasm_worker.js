@@ -8,6 +8,8 @@ const DEBUG_LOG = true; // flip to false to disable debug chatter
 let sqlite3Promise = null;
 let db = null;
 let dbName = DEFAULT_DB_NAME;
+// set between BeginTransaction and Commit/Rollback
+let inTransaction = false;
 
 const locateFile = (file) => `${LOCATE_BASE}/${file}`;
 
@@ -54,17 +56,30 @@ async function ensureDb(name) {
 }
 
 function withTx(fn) {
-    db.exec("BEGIN;");
+    // inside an explicit transaction a savepoint keeps the statements atomic without committing
+    const [begin, commit, rollback] = inTransaction
+        ? ["SAVEPOINT request;", "RELEASE request;", "ROLLBACK TO request; RELEASE request;"]
+        : ["BEGIN;", "COMMIT;", "ROLLBACK;"];
+    db.exec(begin);
     try {
         const result = fn();
-        db.exec("COMMIT;");
+        db.exec(commit);
         return result;
     } catch (err) {
-        db.exec("ROLLBACK;");
+        db.exec(rollback);
         throw err;
     }
 }
 
+function query(stmt) {
+    return db.exec({
+        sql: stmt.sql,
+        bind: stmt.bind || [],
+        rowMode: "object",
+        returnValue: "resultRows",
+    });
+}
+
 async function handleMessage(evt) {
     postDebug("handleMessage: received event");
     let req;
@@ -94,18 +109,118 @@ async function handleMessage(evt) {
                 postDebug("Exec done");
                 return postResponse({ type: "Ok" });
             }
+            case "ExecGroups": {
+                postDebug("ExecGroups begin");
+                await ensureDb(req.database_file || DEFAULT_DB_NAME);
+                // every group behind its own savepoint, so a failing one is undone alone
+                const errors = withTx(() =>
+                    (req.groups || []).map((group) => {
+                        db.exec("SAVEPOINT exec_group;");
+                        try {
+                            group.forEach((stmt) => {
+                                db.exec({ sql: stmt.sql, bind: stmt.bind || [] });
+                            });
+                            db.exec("RELEASE exec_group;");
+                            return null;
+                        } catch (err) {
+                            db.exec("ROLLBACK TO exec_group; RELEASE exec_group;");
+                            return err?.message || String(err);
+                        }
+                    })
+                );
+                postDebug("ExecGroups done");
+                return postResponse({ type: "GroupResults", errors });
+            }
             case "Query": {
                 postDebug("Query begin");
                 await ensureDb(req.database_file || DEFAULT_DB_NAME);
-                const rows = db.exec({
-                    sql: req.sql,
-                    bind: req.bind || [],
-                    rowMode: "object",
-                    returnValue: "resultRows",
-                });
+                const rows = query(req);
                 postDebug("Query done");
                 return postResponse({ type: "Rows", rows });
             }
+            case "QueryBatch": {
+                postDebug("QueryBatch begin");
+                await ensureDb(req.database_file || DEFAULT_DB_NAME);
+                // one transaction, so every query sees the same state of the database
+                const row_sets = withTx(() => (req.queries || []).map(query));
+                postDebug("QueryBatch done");
+                return postResponse({ type: "RowSets", row_sets });
+            }
+            case "BeginTransaction": {
+                await ensureDb(req.database_file || DEFAULT_DB_NAME);
+                if (inTransaction) {
+                    throw new Error("A transaction is already open");
+                }
+                db.exec("BEGIN;");
+                inTransaction = true;
+                return postResponse({ type: "Ok" });
+            }
+            case "Commit":
+            case "Rollback": {
+                await ensureDb(req.database_file || DEFAULT_DB_NAME);
+                if (!inTransaction) {
+                    throw new Error(`${req.type} without an open transaction`);
+                }
+                inTransaction = false;
+                try {
+                    db.exec(req.type === "Commit" ? "COMMIT;" : "ROLLBACK;");
+                } catch (err) {
+                    // a failed commit would leave the transaction open otherwise
+                    db.exec("ROLLBACK;");
+                    throw err;
+                }
+                return postResponse({ type: "Ok" });
+            }
+            case "Backup": {
+                await ensureDb(req.database_file || DEFAULT_DB_NAME);
+                if (inTransaction) {
+                    throw new Error("Can't back up while a transaction is open");
+                }
+                const sqlite3 = await initSqlite();
+                const bytes = sqlite3.capi.sqlite3_js_db_export(db);
+                await sqlite3.oo1.OpfsDb.importDb(req.path, bytes);
+                return postResponse({ type: "Ok" });
+            }
+            case "Restore": {
+                await ensureDb(req.database_file || DEFAULT_DB_NAME);
+                if (inTransaction) {
+                    throw new Error("Can't restore while a transaction is open");
+                }
+                const sqlite3 = await initSqlite();
+                const source = new sqlite3.oo1.OpfsDb(req.path, "r");
+                let bytes;
+                try {
+                    const problems = source.selectValues("PRAGMA integrity_check;");
+                    if (problems.length !== 1 || problems[0] !== "ok") {
+                        throw new Error(`Database is damaged: ${problems.join("; ")}`);
+                    }
+                    const products = source.selectValue(
+                        "SELECT 1 FROM sqlite_master WHERE type='table' AND name='products';"
+                    );
+                    if (products === undefined) {
+                        throw new Error(`'${req.path}' is not a meal planner database`);
+                    }
+                    const versioned = source.selectValue(
+                        "SELECT 1 FROM sqlite_master WHERE type='table' AND name='schema_version';"
+                    );
+                    const version = versioned === undefined
+                        ? 0
+                        : source.selectValue("SELECT MAX(version) FROM schema_version;") ?? 0;
+                    if (version > req.supported_version) {
+                        // matched by `NEWER_BACKUP_ERROR` of local_db_wasm.rs
+                        throw new Error(`Newer schema ${version}`);
+                    }
+                    bytes = sqlite3.capi.sqlite3_js_db_export(source);
+                } finally {
+                    source.close();
+                }
+                // the file is replaced while closed, then opened again for later requests
+                db.close();
+                db = null;
+                await sqlite3.oo1.OpfsDb.importDb(dbName, bytes);
+                await ensureDb(dbName);
+                return postResponse({ type: "Ok" });
+            }
             default:
                 return postError(`Unknown request type: ${req.type}`);
         }
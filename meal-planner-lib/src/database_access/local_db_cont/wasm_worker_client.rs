@@ -6,7 +6,13 @@ use web_sys::{ErrorEvent, MessageEvent, Worker, WorkerOptions, WorkerType, conso
 /// Thin convenience wrapper to spawn the DB worker and send typed requests over `postMessage`.
 ///
 /// The worker must be built separately (see README notes) and exposed as a module script URL
-/// that accepts JSON messages matching the `Request`/`Response` enums in `wasm_worker_main.rs`.
+/// that accepts the JSON messages handled in `wasm_worker.js`: `InitDbFile`, `Exec`,
+/// `ExecGroups`, `Query`, `QueryBatch`, `BeginTransaction`, `Commit`, `Rollback`, `Backup` and
+/// `Restore`. Every request gets exactly one `Ok`, `Rows`, `RowSets`, `GroupResults` or `Err`
+/// response.
+///
+/// Requests have to be sent one at a time. While a transaction is open every `Exec` joins it,
+/// so nothing else should use the worker until it is committed or rolled back.
 #[wasm_bindgen]
 pub(super) struct DbWorkerHandle {
     worker: Worker,
@@ -1,21 +1,77 @@
 use std::collections::{BTreeMap, HashMap};
 
+use super::data_quality::{DataQualitySummary, planned_product_ids};
 use super::db_wrapper::{Database, DbSearchCriteria, MutableDatabase};
-use crate::data_types::{MacroElements, MicroNutrients, MicroNutrientsType, Product, UnitData};
+use super::product_import::{parse_products, validate_product};
+use crate::bl::constraints_solver::Solution;
+use crate::data_types::{
+    MacroElements, MicroNutrients, MicroNutrientsType, Product, ProductId, UnitData, UsageStats,
+    UserProfile,
+};
 
+/// Around 60 common unbranded foods with micro nutrients and kitchen units, CSV.
+pub const PANTRY_FIXTURE: &str = include_str!("../../fixtures/pantry.csv");
+/// Around 50 branded supermarket products with prices and package sizes, CSV.
+pub const GROCERIES_FIXTURE: &str = include_str!("../../fixtures/groceries.csv");
+
+#[derive(Clone)]
 pub struct MockProductDb {
-    pub products: HashMap<String, Product>,
+    pub products: HashMap<ProductId, Product>,
+    pub user_profile: Option<UserProfile>,
+    pub usage_stats: UsageStats,
+    /// Saved plans by ID, as JSON with the fingerprint they were solved for.
+    pub plans: BTreeMap<String, (Option<u64>, String)>,
+}
+
+impl Default for MockProductDb {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MockProductDb {
+    /// Database holding six sample products.
+    #[must_use]
     pub fn new() -> Self {
         let mut me = Self {
             products: HashMap::new(),
+            user_profile: None,
+            usage_stats: UsageStats::default(),
+            plans: BTreeMap::new(),
         };
         me.create_sample_products();
         me
     }
 
+    /// Database holding only `products`.
+    #[must_use]
+    pub fn from_products(products: Vec<Product>) -> Self {
+        let mut me = Self {
+            products: HashMap::new(),
+            user_profile: None,
+            usage_stats: UsageStats::default(),
+            plans: BTreeMap::new(),
+        };
+        for product in products {
+            me.add_or_modify_product(product);
+        }
+        me
+    }
+
+    /// Database holding the products of `data`, in the JSON or CSV format of product imports,
+    /// e.g. [`PANTRY_FIXTURE`]. Fails on the first invalid product.
+    pub fn from_fixture(data: &str) -> Result<Self, String> {
+        let products = parse_products(data)?
+            .into_iter()
+            .map(|(label, product)| {
+                product
+                    .and_then(|p| validate_product(&p).map(|()| p))
+                    .map_err(|e| format!("Invalid fixture product '{label}': {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_products(products))
+    }
+
     #[allow(clippy::too_many_lines)]
     fn create_sample_products(&mut self) {
         let macro_elements = [
@@ -164,6 +220,17 @@ impl MockProductDb {
     fn add_or_modify_product(&mut self, product: Product) {
         self.products.insert(product.id(), product);
     }
+
+    fn save_plan_json(
+        &mut self,
+        plan_id: &str,
+        fingerprint: Option<u64>,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        let json = serde_json::to_string(plan).map_err(|e| e.to_string())?;
+        self.plans.insert(plan_id.to_string(), (fingerprint, json));
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -198,6 +265,66 @@ impl MutableDatabase for MockProductDb {
             Err(format!("Product with ID '{product_id}' not found."))
         }
     }
+
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String> {
+        Ok(self.user_profile)
+    }
+
+    async fn set_user_profile(&mut self, profile: &UserProfile) -> Result<(), String> {
+        profile.validate()?;
+        self.user_profile = Some(*profile);
+        Ok(())
+    }
+
+    async fn get_usage_stats(&self) -> Result<UsageStats, String> {
+        Ok(self.usage_stats.clone())
+    }
+
+    async fn set_usage_stats(&mut self, stats: &UsageStats) -> Result<(), String> {
+        self.usage_stats = stats.clone();
+        Ok(())
+    }
+
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        Ok(self.plans.keys().cloned().collect())
+    }
+
+    async fn get_plan(&self, plan_id: &str) -> Result<Option<Solution>, String> {
+        self.plans
+            .get(plan_id)
+            .map(|(_, json)| serde_json::from_str(json).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn save_plan(&mut self, plan_id: &str, plan: &Solution) -> Result<(), String> {
+        let fingerprint = self
+            .plans
+            .get(plan_id)
+            .and_then(|(fingerprint, _)| *fingerprint);
+        self.save_plan_json(plan_id, fingerprint, plan)
+    }
+
+    async fn delete_plan(&mut self, plan_id: &str) -> Result<(), String> {
+        self.plans.remove(plan_id);
+        Ok(())
+    }
+
+    async fn find_solved_plan(&self, fingerprint: u64) -> Result<Option<Solution>, String> {
+        self.plans
+            .values()
+            .find(|(saved, _)| *saved == Some(fingerprint))
+            .map(|(_, json)| serde_json::from_str(json).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn save_solved_plan(
+        &mut self,
+        plan_id: &str,
+        fingerprint: u64,
+        plan: &Solution,
+    ) -> Result<(), String> {
+        self.save_plan_json(plan_id, Some(fingerprint), plan)
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -205,20 +332,11 @@ impl Database for MockProductDb {
     async fn get_products_matching_criteria(
         &self,
         criteria: &[DbSearchCriteria],
-    ) -> BTreeMap<String, crate::data_types::Product> {
-        let is_prod_matching_crit = |product: &Product, criterion: &DbSearchCriteria| -> bool {
-            match criterion {
-                DbSearchCriteria::ById(name_crit) => product.id().starts_with(name_crit),
-            }
-        };
-
+    ) -> BTreeMap<ProductId, crate::data_types::Product> {
         let mut results = BTreeMap::new();
 
         for (name, product) in &self.products {
-            if criteria
-                .iter()
-                .all(|crit| is_prod_matching_crit(product, crit))
-            {
+            if criteria.iter().all(|crit| crit.matches(product)) {
                 results.insert(name.clone(), product.clone());
             }
         }
@@ -226,6 +344,14 @@ impl Database for MockProductDb {
         results
     }
 
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        let planned = planned_product_ids(self).await?;
+        Ok(DataQualitySummary::from_products(
+            self.products.values(),
+            &planned,
+        ))
+    }
+
     async fn set_product_unit(
         &mut self,
         product_id: &str,
@@ -246,7 +372,7 @@ mod tests {
     use std::vec;
 
     use super::*;
-    use crate::data_types::{MacroElements, Product};
+    use crate::data_types::{Allergen, MacroElements, Product};
     use crate::database_access::DbSearchCriteria;
     use approx::assert_relative_eq;
     use futures::executor::block_on;
@@ -264,6 +390,35 @@ mod tests {
         assert!(db.products.contains_key("Banana (BrandedBanana)"));
     }
 
+    #[test]
+    fn test_from_products_and_fixtures() {
+        let banana = MockProductDb::new().products["Banana (BrandedBanana)"].clone();
+        let db = MockProductDb::from_products(vec![banana]);
+        assert_eq!(
+            db.products.keys().collect::<Vec<_>>(),
+            vec!["Banana (BrandedBanana)"]
+        );
+
+        let pantry = MockProductDb::from_fixture(PANTRY_FIXTURE).unwrap();
+        assert!(pantry.products.len() >= 50);
+        assert_eq!(
+            pantry.products["Egg"].allowed_units[&crate::data_types::AllowedUnitsType::Piece]
+                .amount,
+            60
+        );
+        let groceries = MockProductDb::from_fixture(GROCERIES_FIXTURE).unwrap();
+        assert!(groceries.products.len() >= 50);
+        assert!(
+            groceries
+                .products
+                .values()
+                .all(|p| p.brand().is_some() && p.price.is_some())
+        );
+
+        assert!(MockProductDb::from_fixture("name,Fat\nPear,-1\n").is_err());
+        assert!(MockProductDb::from_fixture("").is_err());
+    }
+
     #[test]
     fn test_add_product() {
         let mut db = MockProductDb::new();
@@ -333,6 +488,41 @@ mod tests {
         assert!(results.contains_key("Apple (BrandedApple)"));
     }
 
+    #[test]
+    fn test_get_products_matching_nutrient_criteria() {
+        let db = MockProductDb::new();
+        let crit = vec![
+            DbSearchCriteria::ByNutrientAtLeast(MicroNutrientsType::Fiber.into(), 5.0),
+            DbSearchCriteria::ByNutrientAtMost(
+                crate::data_types::MacroElementsType::Protein.into(),
+                1.0,
+            ),
+        ];
+        let results = block_on(db.get_products_matching_criteria(&crit));
+        assert_eq!(
+            results.keys().collect::<Vec<_>>(),
+            vec!["MixedNutrients (BrandedMixed)"]
+        );
+    }
+
+    #[test]
+    fn test_get_products_excluding_allergens() {
+        let mut db = MockProductDb::new();
+        let total = db.products.len();
+        db.products
+            .get_mut("Apple (BrandedApple)")
+            .unwrap()
+            .allergens
+            .insert(Allergen::Nuts);
+        let crit = vec![DbSearchCriteria::ExcludeAllergens(vec![
+            Allergen::Gluten,
+            Allergen::Nuts,
+        ])];
+        let results = block_on(db.get_products_matching_criteria(&crit));
+        assert_eq!(results.len(), total - 1);
+        assert!(!results.contains_key("Apple (BrandedApple)"));
+    }
+
     #[test]
     fn test_set_product_unit_success() {
         let mut db = MockProductDb::new();
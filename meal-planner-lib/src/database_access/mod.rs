@@ -1,11 +1,37 @@
+mod cached_db;
+mod data_quality;
+mod db_events;
+mod db_location;
 mod db_wrapper;
 
 mod local_db_cont;
 #[cfg(any(test, feature = "test-utils"))]
-mod mock_db;
+pub(crate) mod mock_db;
+mod offline_queue;
 mod open_food_facts_db_cont;
+mod product_import;
+mod product_search;
+mod read_only_db;
+mod scan_session;
+mod schema_version;
 
 use local_db_cont::local_db;
 // use open_food_facts_db_cont::open_food_facts_db;
 
+pub use cached_db::{CacheConfig, CachedDatabase};
+pub use data_quality::{DataQualitySummary, DatabaseStats};
+pub use db_events::{DbEvent, DbEventStream};
+#[cfg(not(target_arch = "wasm32"))]
+pub use db_location::app_data_dir;
+pub use db_location::{APP_DIR_NAME, resolve_db_path, set_app_data_dir};
 pub use db_wrapper::*;
+pub use local_db::LocalProductDb;
+#[cfg(feature = "test-utils")]
+pub use mock_db::{GROCERIES_FIXTURE, MockProductDb, PANTRY_FIXTURE};
+pub use offline_queue::{OfflineQueue, PendingRequest, RemoteRequest};
+pub use product_import::{BulkReport, ConflictStrategy, ImportEntry, ImportOutcome, ImportReport};
+pub use product_search::{SearchCanceller, SearchToken, search_products};
+pub use read_only_db::{PermissionDenied, ReadOnlyDatabase};
+pub use scan_session::{ScanReport, ScanResolution, ScanSession};
+pub use schema_version::NewerSchema;
+pub(crate) use schema_version::schema_version;
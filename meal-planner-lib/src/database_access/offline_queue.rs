@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+use super::db_wrapper::DbSearchCriteria;
+
+/// Request to a remote product database (e.g. `OpenFoodFacts`) that can be repeated later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RemoteRequest {
+    /// Search of the remote database.
+    Lookup(Vec<DbSearchCriteria>),
+    /// Copy of a remote product into the local database.
+    Import { product_id: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingRequest {
+    /// Identifies the request within its queue, never reused.
+    pub id: u64,
+    pub request: RemoteRequest,
+    /// Error of the most recent attempt.
+    pub last_error: String,
+    pub attempts: u32,
+    /// Unix timestamp (seconds) of the first failure.
+    pub queued_at: u64,
+}
+
+/// Remote requests that failed, e.g. while the device was offline, kept to be retried once
+/// connectivity returns. Serializable, so the app can keep it across restarts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OfflineQueue {
+    pending: Vec<PendingRequest>,
+    next_id: u64,
+}
+
+impl OfflineQueue {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a failed request and returns its id. A request already in the queue is not added
+    /// twice, only its error and attempt count are updated.
+    pub fn record_failure(&mut self, request: RemoteRequest, error: &str, now: u64) -> u64 {
+        if let Some(pending) = self.pending.iter_mut().find(|p| p.request == request) {
+            pending.last_error = error.to_string();
+            pending.attempts += 1;
+            return pending.id;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.push(PendingRequest {
+            id,
+            request,
+            last_error: error.to_string(),
+            attempts: 1,
+            queued_at: now,
+        });
+        id
+    }
+
+    /// Pending requests, oldest first.
+    #[must_use]
+    pub fn pending(&self) -> &[PendingRequest] {
+        &self.pending
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Drops a single request, e.g. one the user no longer cares about.
+    pub fn remove(&mut self, id: u64) -> Option<PendingRequest> {
+        let idx = self.pending.iter().position(|p| p.id == id)?;
+        Some(self.pending.remove(idx))
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Retries pending requests in order with `perform`, removing the ones that succeed.
+    /// Failed ones stay queued with their new error. Returns how many requests succeeded.
+    pub async fn retry_all(
+        &mut self,
+        mut perform: impl AsyncFnMut(&RemoteRequest) -> Result<(), String>,
+    ) -> usize {
+        let mut succeeded = 0;
+        let mut still_pending = Vec::with_capacity(self.pending.len());
+        for mut pending in std::mem::take(&mut self.pending) {
+            match perform(&pending.request).await {
+                Ok(()) => succeeded += 1,
+                Err(e) => {
+                    pending.last_error = e;
+                    pending.attempts += 1;
+                    still_pending.push(pending);
+                }
+            }
+        }
+        self.pending = still_pending;
+        succeeded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn lookup(prefix: &str) -> RemoteRequest {
+        RemoteRequest::Lookup(vec![DbSearchCriteria::ById(prefix.to_string())])
+    }
+
+    fn import(product_id: &str) -> RemoteRequest {
+        RemoteRequest::Import {
+            product_id: product_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn records_each_request_once() {
+        let mut queue = OfflineQueue::new();
+        let first = queue.record_failure(lookup("App"), "offline", 100);
+        let second = queue.record_failure(import("Banana"), "offline", 110);
+        assert_eq!(queue.record_failure(lookup("App"), "timeout", 120), first);
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pending()[0].attempts, 2);
+        assert_eq!(queue.pending()[0].last_error, "timeout");
+        assert_eq!(queue.pending()[0].queued_at, 100);
+
+        assert_eq!(
+            queue.remove(second).map(|p| p.request),
+            Some(import("Banana"))
+        );
+        assert_eq!(queue.remove(second), None);
+        // ids are not reused after removal
+        assert_ne!(queue.record_failure(import("Kiwi"), "offline", 130), second);
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn retry_keeps_only_failed_requests() {
+        let mut queue = OfflineQueue::new();
+        queue.record_failure(lookup("App"), "offline", 100);
+        queue.record_failure(import("Banana"), "offline", 100);
+        queue.record_failure(import("Kiwi"), "offline", 100);
+
+        let mut attempted = Vec::new();
+        let succeeded = block_on(queue.retry_all(async |request: &RemoteRequest| {
+            attempted.push(request.clone());
+            match request {
+                RemoteRequest::Import { product_id } if product_id == "Kiwi" => {
+                    Err("not found".to_string())
+                }
+                _ => Ok(()),
+            }
+        }));
+
+        assert_eq!(succeeded, 2);
+        assert_eq!(attempted.len(), 3);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pending()[0].request, import("Kiwi"));
+        assert_eq!(queue.pending()[0].attempts, 2);
+        assert_eq!(queue.pending()[0].last_error, "not found");
+    }
+
+    #[test]
+    fn survives_serialization() {
+        let mut queue = OfflineQueue::new();
+        queue.record_failure(lookup("App"), "offline", 100);
+        let json = serde_json::to_string(&queue).unwrap();
+        let mut restored: OfflineQueue = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, queue);
+        assert_eq!(restored.record_failure(import("Kiwi"), "offline", 200), 1);
+    }
+}
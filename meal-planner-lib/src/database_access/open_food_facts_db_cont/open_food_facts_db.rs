@@ -1 +1,110 @@
+use serde_json::Value;
 
+use crate::data_types::{
+    DataQuality, MacroElements, MicroNutrients, MicroNutrientsType, Product, is_valid_barcode,
+};
+
+/// Whether `value` is an `OpenFoodFacts` API response (`{"code": .., "product": {..}}`) rather
+/// than a serialized [`Product`].
+pub(crate) fn is_off_response(value: &Value) -> bool {
+    value["product"].is_object()
+}
+
+/// Product of an `OpenFoodFacts` API response, nutrients per 100g with the data quality
+/// indicators of the entry.
+pub(crate) fn product_from_off(response: &Value) -> Result<Product, String> {
+    let off = &response["product"];
+    let name = off["product_name"]
+        .as_str()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| "OpenFoodFacts product has no name".to_string())?;
+    // the first brand is the one printed on the package
+    let brand = off["brands"]
+        .as_str()
+        .and_then(|brands| brands.split(',').next())
+        .map(str::trim)
+        .filter(|brand| !brand.is_empty())
+        .map(str::to_string);
+    #[allow(clippy::cast_possible_truncation)]
+    let nutriment = |key: &str| {
+        off["nutriments"][format!("{key}_100g")]
+            .as_f64()
+            .map(|v| v as f32)
+    };
+
+    let macro_elements = MacroElements::new(
+        nutriment("fat").unwrap_or(0.0),
+        nutriment("saturated-fat").unwrap_or(0.0),
+        nutriment("carbohydrates").unwrap_or(0.0),
+        nutriment("sugars").unwrap_or(0.0),
+        nutriment("proteins").unwrap_or(0.0),
+    )
+    .with_calories_override(nutriment("energy-kcal"));
+    let mut micro_nutrients = MicroNutrients::default();
+    micro_nutrients[MicroNutrientsType::Fiber] = nutriment("fiber");
+    // OpenFoodFacts gives every nutrient in grams
+    micro_nutrients[MicroNutrientsType::Sodium] = nutriment("sodium").map(|g| g * 1000.0);
+
+    Ok(Product::new(
+        name.to_string(),
+        brand,
+        Box::new(macro_elements),
+        Box::new(micro_nutrients),
+        std::collections::HashMap::new(),
+    )
+    .with_data_quality(Some(DataQuality::from_off_product(off)))
+    .with_barcode(
+        response["code"]
+            .as_str()
+            .filter(|code| is_valid_barcode(code))
+            .map(str::to_string),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::MacroElementsType;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_product_from_off_response() {
+        let response = serde_json::json!({
+            "code": "5901234123457",
+            "product": {
+                "product_name": "Oat drink",
+                "brands": "Oatly, Oatly AB",
+                "nutriments": {
+                    "fat_100g": 1.5,
+                    "carbohydrates_100g": 6.7,
+                    "sugars_100g": 4.1,
+                    "proteins_100g": 1.0,
+                    "energy-kcal_100g": 46,
+                    "fiber_100g": 0.8,
+                    "sodium_100g": 0.04,
+                },
+                "states_tags": ["en:nutrition-facts-completed"],
+                "last_modified_t": 1_700_000_000,
+            },
+        });
+        assert!(is_off_response(&response));
+        let product = product_from_off(&response).unwrap();
+        assert_eq!(product.id(), "Oat drink (Oatly)");
+        assert_relative_eq!(product.macro_elements[MacroElementsType::Carbs], 6.7);
+        assert_eq!(product.label_calories(), Some(46.0));
+        assert_relative_eq!(
+            product.micro_nutrients[MicroNutrientsType::Sodium].unwrap(),
+            40.0,
+            epsilon = 1e-4
+        );
+        let quality = product.data_quality().unwrap();
+        assert_eq!(quality.last_modified, Some(1_700_000_000));
+        assert!(product.has_verified_data());
+        assert_eq!(product.barcode.as_deref(), Some("5901234123457"));
+
+        assert!(!is_off_response(&serde_json::json!({ "name": "Apple" })));
+        let unnamed = serde_json::json!({ "product": { "brands": "Oatly" } });
+        assert!(product_from_off(&unnamed).is_err());
+    }
+}
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+
+use strum::IntoEnumIterator;
+
+use super::open_food_facts_db_cont::open_food_facts_db::{is_off_response, product_from_off};
+use crate::bl::number_format::parse_number;
+use crate::data_types::{
+    AllowedUnits, AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients,
+    MicroNutrientsType, Price, Product, ServingInfo, UnitData,
+};
+
+/// What to do when an imported product has the same ID as one already in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Keep the stored product untouched.
+    Skip,
+    /// Replace the stored product with the imported one.
+    Overwrite,
+    /// Keep stored values the import does not provide (missing micro nutrients, extra units).
+    Merge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    Added,
+    Skipped,
+    Overwritten,
+    Merged,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportEntry {
+    /// Product ID, or the source position (e.g. `row 3`) when the row could not be parsed.
+    pub product_id: String,
+    pub outcome: ImportOutcome,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    pub entries: Vec<ImportEntry>,
+}
+
+impl ImportReport {
+    #[must_use]
+    pub fn count(&self, outcome: &ImportOutcome) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| match (&entry.outcome, outcome) {
+                (ImportOutcome::Failed(_), ImportOutcome::Failed(_)) => true,
+                (lhs, rhs) => lhs == rhs,
+            })
+            .count()
+    }
+
+    #[must_use]
+    pub fn failures(&self) -> Vec<&ImportEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.outcome, ImportOutcome::Failed(_)))
+            .collect()
+    }
+}
+
+/// Result of [`super::MutableDatabase::add_products_bulk`], one entry per product in input order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkReport {
+    pub entries: Vec<(String, Result<(), String>)>,
+}
+
+impl BulkReport {
+    #[must_use]
+    pub fn added(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .count()
+    }
+
+    /// IDs of the products that were not added, with the reason.
+    #[must_use]
+    pub fn failures(&self) -> Vec<(&str, &str)> {
+        self.entries
+            .iter()
+            .filter_map(|(product_id, result)| {
+                result
+                    .as_ref()
+                    .err()
+                    .map(|e| (product_id.as_str(), e.as_str()))
+            })
+            .collect()
+    }
+}
+
+pub(crate) type ParsedProduct = (String, Result<Product, String>);
+
+/// Parses products from either a JSON array of serialized `Product`s or a CSV document. JSON
+/// items may also be `OpenFoodFacts` API responses, imported with their data quality.
+///
+/// The format is detected from the first non-whitespace character. CSV files need a header row;
+/// columns are matched by the same names the local database uses (`name`, `brand`, nutrient names
+/// such as `Saturated Fat`, units such as `cup` and `cup divider`, `price_per_100g`, `currency`,
+/// `negligible`, `grams_per_serving`). Nutrients are per 100g in both formats.
+pub(crate) fn parse_products(data: &str) -> Result<Vec<ParsedProduct>, String> {
+    let trimmed = data.trim_start();
+    if trimmed.is_empty() {
+        return Err("Import data is empty".to_string());
+    }
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        parse_json(trimmed)
+    } else {
+        parse_csv(trimmed)
+    }
+}
+
+fn parse_json(data: &str) -> Result<Vec<ParsedProduct>, String> {
+    let values: Vec<serde_json::Value> = if data.starts_with('{') {
+        vec![serde_json::from_str(data).map_err(|e| format!("Invalid JSON: {e}"))?]
+    } else {
+        serde_json::from_str(data).map_err(|e| format!("Invalid JSON: {e}"))?
+    };
+
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let label = format!("item {}", i + 1);
+            if is_off_response(&value) {
+                return match product_from_off(&value) {
+                    Ok(product) => (product.id().into(), Ok(product)),
+                    Err(e) => (label, Err(e)),
+                };
+            }
+            match serde_json::from_value::<Product>(value) {
+                Ok(product) => {
+                    let product = normalize_product(product);
+                    (product.id().into(), Ok(product))
+                }
+                Err(e) => (label, Err(format!("Invalid product: {e}"))),
+            }
+        })
+        .collect())
+}
+
+// Rebuilds the product through the public constructors so deserialized data gets the same
+// invariants as products created in code (recomputed calories, default gram unit).
+fn normalize_product(product: Product) -> Product {
+    let data_quality = product.data_quality().cloned();
+    let mut values: HashMap<MacroElementsType, f32> = product
+        .macro_elements
+        .as_ref()
+        .clone()
+        .into_iter()
+        .collect();
+    let mut take = |key| values.remove(&key).unwrap_or(0.0);
+    let macro_elements = MacroElements::new(
+        take(MacroElementsType::Fat),
+        take(MacroElementsType::SaturatedFat),
+        take(MacroElementsType::Carbs),
+        take(MacroElementsType::Sugar),
+        take(MacroElementsType::Protein),
+    )
+    .with_calories_override(product.macro_elements.calories_override());
+    Product::new(
+        product.name().to_string(),
+        product.brand().map(str::to_string),
+        Box::new(macro_elements),
+        product.micro_nutrients,
+        product.allowed_units,
+    )
+    .with_price(product.price)
+    .with_negligible(product.negligible)
+    .with_serving(product.serving)
+    .with_cooked(product.cooked)
+    .with_ignore_alcohol_calories(product.ignore_alcohol_calories)
+    .with_data_quality(data_quality)
+    .with_barcode(product.barcode)
+}
+
+fn parse_csv(data: &str) -> Result<Vec<ParsedProduct>, String> {
+    let mut lines = data.lines().filter(|line| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| "CSV data has no header row".to_string())?;
+    let columns: HashMap<String, usize> = split_csv_line(header)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, col)| (col.trim().to_lowercase(), i))
+        .collect();
+    if !columns.contains_key("name") {
+        return Err("CSV header is missing the 'name' column".to_string());
+    }
+
+    Ok(lines
+        .enumerate()
+        .map(|(i, line)| {
+            // header is row 1
+            let label = format!("row {}", i + 2);
+            match split_csv_line(line).and_then(|fields| csv_record_to_product(&columns, &fields)) {
+                Ok(product) => (product.id().into(), Ok(product)),
+                Err(e) => (label, Err(e)),
+            }
+        })
+        .collect())
+}
+
+fn csv_record_to_product(
+    columns: &HashMap<String, usize>,
+    fields: &[String],
+) -> Result<Product, String> {
+    let field = |name: &str| -> Option<&str> {
+        columns
+            .get(&name.to_lowercase())
+            .and_then(|i| fields.get(*i))
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+    };
+    let number = |name: &str| -> Result<Option<f32>, String> {
+        field(name)
+            .map(|f| {
+                parse_number(f).map_err(|_| format!("Invalid number '{f}' in column '{name}'"))
+            })
+            .transpose()
+    };
+    let integer = |name: &str| -> Result<Option<u16>, String> {
+        field(name)
+            .map(|f| {
+                f.parse::<u16>()
+                    .map_err(|_| format!("Invalid unit amount '{f}' in column '{name}'"))
+            })
+            .transpose()
+    };
+
+    let name = field("name").ok_or_else(|| "Missing product name".to_string())?;
+    let brand = field("brand").map(str::to_string);
+
+    let macro_value = |m: MacroElementsType| number(&m.to_string()).map(|v| v.unwrap_or(0.0));
+    let macro_elements = MacroElements::new(
+        macro_value(MacroElementsType::Fat)?,
+        macro_value(MacroElementsType::SaturatedFat)?,
+        macro_value(MacroElementsType::Carbs)?,
+        macro_value(MacroElementsType::Sugar)?,
+        macro_value(MacroElementsType::Protein)?,
+    );
+
+    let mut micro_nutrients = MicroNutrients::default();
+    for micro in MicroNutrientsType::iter() {
+        micro_nutrients[micro] = number(&micro.to_string())?;
+    }
+
+    let mut allowed_units: AllowedUnits = HashMap::new();
+    for unit in AllowedUnitsType::iter() {
+        if let Some(amount) = integer(&unit.to_string())? {
+            let divider = integer(&format!("{unit} divider"))?.unwrap_or(1);
+            allowed_units.insert(unit, UnitData { amount, divider });
+        }
+    }
+
+    let price = number("price_per_100g")?.map(|per_100g| Price {
+        per_100g,
+        currency: field("currency").unwrap_or_default().to_string(),
+    });
+    let negligible = match field("negligible").map(str::to_lowercase).as_deref() {
+        None | Some("0" | "false" | "no") => false,
+        Some("1" | "true" | "yes") => true,
+        Some(other) => return Err(format!("Invalid value '{other}' in column 'negligible'")),
+    };
+    let serving = number("grams_per_serving")?.map(ServingInfo::new);
+
+    Ok(Product::new(
+        name.to_string(),
+        brand,
+        Box::new(macro_elements),
+        Box::new(micro_nutrients),
+        allowed_units,
+    )
+    .with_price(price)
+    .with_negligible(negligible)
+    .with_serving(serving))
+}
+
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted field".to_string());
+    }
+    fields.push(current);
+    Ok(fields)
+}
+
+/// Rejects values that cannot describe a real product before they reach the database.
+pub(crate) fn validate_product(product: &Product) -> Result<(), String> {
+    product.validate().map_err(|errors| {
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    })
+}
+
+/// Combines a stored product with an imported one, preferring imported values where present.
+pub(crate) fn merge_products(existing: &Product, imported: Product) -> Product {
+    let mut merged = imported;
+    for micro_type in MicroNutrientsType::iter() {
+        if merged.micro_nutrients[micro_type].is_none() {
+            merged.micro_nutrients[micro_type] = existing.micro_nutrients[micro_type];
+        }
+    }
+    for (unit, data) in &existing.allowed_units {
+        merged.allowed_units.entry(*unit).or_insert(*data);
+    }
+    if merged.price.is_none() {
+        merged.price = existing.price.clone();
+    }
+    if merged.serving.is_none() {
+        merged.serving = existing.serving;
+    }
+    if merged.cooked.is_none() {
+        merged.cooked = existing.cooked.clone();
+    }
+    if merged.macro_elements.calories_override().is_none() {
+        merged
+            .macro_elements
+            .set_calories_override(existing.macro_elements.calories_override());
+    }
+    // a missing column reads as false, so an import never clears the flags
+    merged.negligible |= existing.negligible;
+    merged.ignore_alcohol_calories |= existing.ignore_alcohol_calories;
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_access::{Database, MutableDatabase, mock_db::MockProductDb};
+    use approx::assert_relative_eq;
+    use futures::executor::block_on;
+
+    const CSV_DATA: &str = "name,brand,Fat,Saturated Fat,Carbohydrates,Sugar,Protein,Fiber,cup,cup divider,price_per_100g,currency
+Oats,\"Mill, Co\",7,1.2,60,1,13,10,80,1,0.9,PLN
+Rice,,0.5,0.1,78,0,7,,,,,
+";
+
+    #[test]
+    fn test_parse_csv_products() {
+        let parsed = parse_products(CSV_DATA).expect("csv should parse");
+        assert_eq!(parsed.len(), 2);
+
+        let (id, oats) = &parsed[0];
+        let oats = oats.as_ref().expect("oats row should be valid");
+        assert_eq!(id, "Oats (Mill, Co)");
+        assert_relative_eq!(oats.macro_elements[MacroElementsType::Carbs], 60.0);
+        assert_eq!(oats.micro_nutrients[MicroNutrientsType::Fiber], Some(10.0));
+        assert_eq!(
+            oats.allowed_units.get(&AllowedUnitsType::Cup),
+            Some(&UnitData {
+                amount: 80,
+                divider: 1
+            })
+        );
+        assert_eq!(
+            oats.price,
+            Some(Price {
+                per_100g: 0.9,
+                currency: "PLN".to_string()
+            })
+        );
+
+        let (id, rice) = &parsed[1];
+        let rice = rice.as_ref().expect("rice row should be valid");
+        assert_eq!(id, "Rice");
+        assert_eq!(rice.brand(), None);
+        assert_eq!(rice.micro_nutrients[MicroNutrientsType::Fiber], None);
+        assert_eq!(rice.allowed_units.len(), 1);
+        assert_eq!(rice.price, None);
+    }
+
+    #[test]
+    fn test_parse_csv_negligible_flag() {
+        let parsed = parse_products("name,negligible\nPepper,yes\nSalt,0\nTea,\nCoffee,maybe\n")
+            .expect("csv should parse");
+        let flags: Vec<_> = parsed
+            .iter()
+            .map(|(_, p)| p.as_ref().map(|p| p.negligible).ok())
+            .collect();
+        assert_eq!(flags, vec![Some(true), Some(false), Some(false), None]);
+    }
+
+    #[test]
+    fn test_parse_serving_size() {
+        let parsed =
+            parse_products("name,Carbohydrates,grams_per_serving\nCereal,80,30\nRice,78,\n")
+                .expect("csv should parse");
+        let servings: Vec<_> = parsed
+            .iter()
+            .map(|(_, p)| p.as_ref().unwrap().serving)
+            .collect();
+        assert_eq!(servings, vec![Some(ServingInfo::new(30.0)), None]);
+
+        let json = serde_json::to_string(&[parsed[0].1.as_ref().unwrap()]).unwrap();
+        let parsed = parse_products(&json).expect("json should parse");
+        assert_eq!(
+            parsed[0].1.as_ref().unwrap().serving,
+            Some(ServingInfo::new(30.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_reports_invalid_rows() {
+        let parsed = parse_products("name,Fat\nGood,1\nBad,abc\n,2\n").expect("csv should parse");
+        assert!(parsed[0].1.is_ok());
+        assert_eq!(parsed[1].0, "row 3");
+        assert!(parsed[1].1.is_err());
+        assert_eq!(parsed[2].1, Err("Missing product name".to_string()));
+    }
+
+    #[test]
+    fn test_parse_json_products() {
+        let product = Product::new(
+            "Yogurt".to_string(),
+            Some("Dairy".to_string()),
+            Box::new(MacroElements::new(3.0, 2.0, 4.0, 4.0, 5.0)),
+            Box::default(),
+            HashMap::new(),
+        );
+        let json = serde_json::to_string(&vec![product.clone()]).unwrap();
+        let parsed = parse_products(&json).expect("json should parse");
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].0, "Yogurt (Dairy)");
+        assert_eq!(parsed[0].1.as_ref().unwrap(), &product);
+
+        // OpenFoodFacts responses keep their data quality
+        let off = r#"[{"code": "1", "product": {"product_name": "Kefir", "brands": "Dairy",
+            "nutriments": {"proteins_100g": 3.4}, "states_tags": ["en:to-be-completed"]}}]"#;
+        let parsed = parse_products(off).expect("json should parse");
+        assert_eq!(parsed[0].0, "Kefir (Dairy)");
+        let kefir = parsed[0].1.as_ref().unwrap();
+        assert!(kefir.data_quality().is_some());
+        assert!(!kefir.has_verified_data());
+    }
+
+    #[test]
+    fn test_validate_product_rejects_negative_values() {
+        let product = Product::new(
+            "Broken".to_string(),
+            None,
+            Box::new(MacroElements::new(-1.0, 0.0, 0.0, 0.0, 0.0)),
+            Box::default(),
+            HashMap::new(),
+        );
+        assert!(validate_product(&product).is_err());
+    }
+
+    #[test]
+    fn test_import_products_conflict_strategies() {
+        let csv = "name,brand,Fat,Fiber\nMixedNutrients,BrandedMixed,9,\nPear,,1,3\n";
+
+        let mut db = MockProductDb::new();
+        let report = block_on(db.import_products(csv, ConflictStrategy::Skip)).unwrap();
+        assert_eq!(report.count(&ImportOutcome::Skipped), 1);
+        assert_eq!(report.count(&ImportOutcome::Added), 1);
+        let mixed = block_on(db.get_product_by_id("MixedNutrients (BrandedMixed)")).unwrap();
+        assert_relative_eq!(mixed.macro_elements[MacroElementsType::Fat], 0.0);
+
+        let mut db = MockProductDb::new();
+        let report = block_on(db.import_products(csv, ConflictStrategy::Overwrite)).unwrap();
+        assert_eq!(report.count(&ImportOutcome::Overwritten), 1);
+        let mixed = block_on(db.get_product_by_id("MixedNutrients (BrandedMixed)")).unwrap();
+        assert_relative_eq!(mixed.macro_elements[MacroElementsType::Fat], 9.0);
+        assert_eq!(mixed.micro_nutrients[MicroNutrientsType::Fiber], None);
+
+        let mut db = MockProductDb::new();
+        let report = block_on(db.import_products(csv, ConflictStrategy::Merge)).unwrap();
+        assert_eq!(report.count(&ImportOutcome::Merged), 1);
+        let mixed = block_on(db.get_product_by_id("MixedNutrients (BrandedMixed)")).unwrap();
+        assert_relative_eq!(mixed.macro_elements[MacroElementsType::Fat], 9.0);
+        assert_eq!(mixed.micro_nutrients[MicroNutrientsType::Fiber], Some(6.0));
+        assert!(mixed.allowed_units.contains_key(&AllowedUnitsType::Cup));
+    }
+
+    #[test]
+    fn test_import_products_reports_failures() {
+        let mut db = MockProductDb::new();
+        let report =
+            block_on(db.import_products("name,Fat\nNegative,-3\n", ConflictStrategy::Skip))
+                .unwrap();
+        assert_eq!(report.failures().len(), 1);
+        assert!(block_on(db.get_product_by_id("Negative")).is_none());
+    }
+
+    #[test]
+    fn test_import_products_repeated_in_one_import() {
+        let csv = "name,Fat\nPear,1\nPear,4\n";
+        let mut db = MockProductDb::new();
+        let report = block_on(db.import_products(csv, ConflictStrategy::Overwrite)).unwrap();
+        assert_eq!(report.count(&ImportOutcome::Added), 1);
+        assert_eq!(report.count(&ImportOutcome::Overwritten), 1);
+        let pear = block_on(db.get_product_by_id("Pear")).unwrap();
+        assert_relative_eq!(pear.macro_elements[MacroElementsType::Fat], 4.0);
+
+        let mut db = MockProductDb::new();
+        let report = block_on(db.import_products(csv, ConflictStrategy::Skip)).unwrap();
+        assert_eq!(report.count(&ImportOutcome::Skipped), 1);
+        let pear = block_on(db.get_product_by_id("Pear")).unwrap();
+        assert_relative_eq!(pear.macro_elements[MacroElementsType::Fat], 1.0);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{Database, DbSearchCriteria};
+use crate::data_types::{Product, ProductId};
+
+/// Hands out a [`SearchToken`] per search. Starting a search cancels the ones started before,
+/// so results arriving out of order can be dropped. Clones share the same searches.
+#[derive(Debug, Clone, Default)]
+pub struct SearchCanceller {
+    latest: Arc<AtomicU64>,
+}
+
+/// Generation of one search, see [`SearchCanceller`].
+#[derive(Debug, Clone)]
+pub struct SearchToken {
+    generation: u64,
+    latest: Arc<AtomicU64>,
+}
+
+impl SearchCanceller {
+    /// Starts a new search, cancelling every earlier one.
+    #[must_use]
+    pub fn start(&self) -> SearchToken {
+        let generation = self.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        SearchToken {
+            generation,
+            latest: Arc::clone(&self.latest),
+        }
+    }
+
+    /// Cancels every started search, e.g. when the search input is cleared.
+    pub fn cancel_all(&self) {
+        self.latest.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl SearchToken {
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.latest.load(Ordering::SeqCst) != self.generation
+    }
+}
+
+/// Same as [`Database::get_products_matching_criteria`], but `None` when `token` got cancelled
+/// before or while querying, so stale results are never shown.
+pub async fn search_products(
+    db: &dyn Database,
+    criteria: &[DbSearchCriteria],
+    token: &SearchToken,
+) -> Option<BTreeMap<ProductId, Product>> {
+    if token.is_cancelled() {
+        return None;
+    }
+    let products = db.get_products_matching_criteria(criteria).await;
+    (!token.is_cancelled()).then_some(products)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_access::mock_db::MockProductDb;
+    use futures::executor::block_on;
+
+    #[test]
+    fn newer_search_cancels_older_one() {
+        let db = MockProductDb::new();
+        let canceller = SearchCanceller::default();
+        let criteria = [DbSearchCriteria::ById(String::new())];
+
+        let first = canceller.start();
+        let second = canceller.clone().start();
+        assert!(first.is_cancelled());
+        assert!(second.generation() > first.generation());
+        assert_eq!(block_on(search_products(&db, &criteria, &first)), None);
+        let found = block_on(search_products(&db, &criteria, &second)).expect("Expected results");
+        assert_eq!(
+            found,
+            block_on(db.get_products_matching_criteria(&criteria))
+        );
+
+        canceller.cancel_all();
+        assert!(second.is_cancelled());
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use async_trait::async_trait;
+
+use super::data_quality::{DataQualitySummary, DatabaseStats};
+use super::db_events::DbEventStream;
+use super::db_wrapper::{Database, DbSearchCriteria, MutableDatabase, SortKey};
+use super::product_import::{BulkReport, ConflictStrategy, ImportReport};
+use crate::bl::constraints_solver::Solution;
+use crate::bl::grocery_haul::{Pantry, ShoppingList};
+use crate::bl::targets_review::TargetsRecord;
+use crate::data_types::{
+    ActivityLog, AllowedUnits, AllowedUnitsType, AppSettings, BeverageEntry, CompletenessReport,
+    EatenItem, MealNote, Product, ProductEdit, ProductId, ProductVersion, Profile, UnitData,
+    UsageStats, UserProfile,
+};
+
+const PERMISSION_DENIED_PREFIX: &str = "Permission denied";
+
+/// Mutation refused by a [`ReadOnlyDatabase`]. Database methods report errors as `String`, so
+/// it is converted into one; [`PermissionDenied::matches`] recognizes it again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionDenied {
+    /// Name of the refused operation, e.g. `add_product`.
+    pub operation: &'static str,
+}
+
+impl PermissionDenied {
+    /// Whether `error` returned by a database is a refused mutation.
+    #[must_use]
+    pub fn matches(error: &str) -> bool {
+        error.starts_with(PERMISSION_DENIED_PREFIX)
+    }
+}
+
+impl fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{PERMISSION_DENIED_PREFIX}: cannot {} in a read-only database",
+            self.operation
+        )
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+impl From<PermissionDenied> for String {
+    fn from(error: PermissionDenied) -> Self {
+        error.to_string()
+    }
+}
+
+fn denied<T>(operation: &'static str) -> Result<T, String> {
+    Err(PermissionDenied { operation }.into())
+}
+
+/// [`Database`] decorator refusing every change with [`PermissionDenied`], for databases that
+/// must not be modified such as a shared household one or a bundled starter database.
+///
+/// Reads go to the wrapped database. Switching the active profile is allowed, it only selects
+/// which data is read.
+#[derive(Clone)]
+pub struct ReadOnlyDatabase<D> {
+    inner: D,
+}
+
+impl<D> ReadOnlyDatabase<D> {
+    #[must_use]
+    pub fn new(inner: D) -> Self {
+        Self { inner }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+#[async_trait(?Send)]
+impl<D: Database> Database for ReadOnlyDatabase<D> {
+    async fn get_products_matching_criteria(
+        &self,
+        criteria: &[DbSearchCriteria],
+    ) -> BTreeMap<ProductId, Product> {
+        self.inner.get_products_matching_criteria(criteria).await
+    }
+
+    async fn set_product_unit(
+        &mut self,
+        _product_id: &str,
+        _allowed_unit: AllowedUnitsType,
+        _unit_data: UnitData,
+    ) -> Result<(), String> {
+        denied("set_product_unit")
+    }
+
+    async fn update_product_units(
+        &mut self,
+        _product_id: &str,
+        _allowed_units: &AllowedUnits,
+    ) -> Result<(), String> {
+        denied("update_product_units")
+    }
+
+    async fn clone_product_units(
+        &mut self,
+        _source_units: &AllowedUnits,
+        _target_product_id: &str,
+    ) -> Result<(), String> {
+        denied("clone_product_units")
+    }
+
+    async fn get_product_by_id(&self, product_id: &str) -> Option<Product> {
+        self.inner.get_product_by_id(product_id).await
+    }
+
+    async fn data_quality_summary(&self) -> Result<DataQualitySummary, String> {
+        self.inner.data_quality_summary().await
+    }
+
+    async fn least_complete_products(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, CompletenessReport)>, String> {
+        self.inner.least_complete_products(limit).await
+    }
+
+    async fn get_products_page(
+        &self,
+        sort: SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(ProductId, Product)>, String> {
+        self.inner.get_products_page(sort, offset, limit).await
+    }
+
+    async fn list_brands(&self) -> Result<BTreeMap<String, u64>, String> {
+        self.inner.list_brands().await
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats, String> {
+        self.inner.stats().await
+    }
+
+    fn subscribe(&self) -> Result<DbEventStream, String> {
+        self.inner.subscribe()
+    }
+}
+
+#[async_trait(?Send)]
+impl<D: MutableDatabase> MutableDatabase for ReadOnlyDatabase<D> {
+    async fn add_product(&mut self, _product_id: &str, _product: Product) -> Result<(), String> {
+        denied("add_product")
+    }
+
+    async fn update_product(&mut self, _product_id: &str, _product: Product) -> Result<(), String> {
+        denied("update_product")
+    }
+
+    async fn delete_product(&mut self, _product_id: &str) -> Result<(), String> {
+        denied("delete_product")
+    }
+
+    async fn add_products_bulk(
+        &mut self,
+        _products: Vec<(String, Product)>,
+    ) -> Result<BulkReport, String> {
+        denied("add_products_bulk")
+    }
+
+    async fn delete_products(&mut self, _product_ids: &[String]) -> Result<(), String> {
+        denied("delete_products")
+    }
+
+    async fn edit_products(
+        &mut self,
+        _product_ids: &[String],
+        _edit: &ProductEdit,
+    ) -> Result<(), String> {
+        denied("edit_products")
+    }
+
+    async fn get_product_history(&self, product_id: &str) -> Result<Vec<ProductVersion>, String> {
+        self.inner.get_product_history(product_id).await
+    }
+
+    async fn rename_product(&mut self, _product_id: &str, _product: Product) -> Result<(), String> {
+        denied("rename_product")
+    }
+
+    async fn get_product_uuid(&self, product_id: &str) -> Result<Option<String>, String> {
+        self.inner.get_product_uuid(product_id).await
+    }
+
+    async fn get_product_by_uuid(&self, uuid: &str) -> Result<Option<Product>, String> {
+        self.inner.get_product_by_uuid(uuid).await
+    }
+
+    async fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        self.inner.get_profiles().await
+    }
+
+    async fn set_profile(&mut self, _profile: &Profile) -> Result<(), String> {
+        denied("set_profile")
+    }
+
+    async fn delete_profile(&mut self, _profile_id: &str) -> Result<(), String> {
+        denied("delete_profile")
+    }
+
+    async fn active_profile(&self) -> Result<String, String> {
+        self.inner.active_profile().await
+    }
+
+    async fn set_active_profile(&mut self, profile_id: &str) -> Result<(), String> {
+        self.inner.set_active_profile(profile_id).await
+    }
+
+    async fn get_user_profile(&self) -> Result<Option<UserProfile>, String> {
+        self.inner.get_user_profile().await
+    }
+
+    async fn set_user_profile(&mut self, _profile: &UserProfile) -> Result<(), String> {
+        denied("set_user_profile")
+    }
+
+    async fn get_targets_record(&self) -> Result<Option<TargetsRecord>, String> {
+        self.inner.get_targets_record().await
+    }
+
+    async fn set_targets_record(&mut self, _record: &TargetsRecord) -> Result<(), String> {
+        denied("set_targets_record")
+    }
+
+    async fn add_beverage_entry(&mut self, _entry: &BeverageEntry) -> Result<(), String> {
+        denied("add_beverage_entry")
+    }
+
+    async fn get_beverage_entries(&self, from: u64, to: u64) -> Result<Vec<BeverageEntry>, String> {
+        self.inner.get_beverage_entries(from, to).await
+    }
+
+    async fn add_activity(&mut self, _activity: &ActivityLog) -> Result<(), String> {
+        denied("add_activity")
+    }
+
+    async fn get_activities(&self, from: u64, to: u64) -> Result<Vec<ActivityLog>, String> {
+        self.inner.get_activities(from, to).await
+    }
+
+    async fn get_eaten(&self, day: u64) -> Result<Vec<EatenItem>, String> {
+        self.inner.get_eaten(day).await
+    }
+
+    async fn mark_eaten(&mut self, _day: u64, _item: &EatenItem) -> Result<(), String> {
+        denied("mark_eaten")
+    }
+
+    async fn unmark_eaten(
+        &mut self,
+        _day: u64,
+        _meal: &str,
+        _product_id: &str,
+    ) -> Result<(), String> {
+        denied("unmark_eaten")
+    }
+
+    async fn get_meal_notes(&self, day: u64) -> Result<Vec<MealNote>, String> {
+        self.inner.get_meal_notes(day).await
+    }
+
+    async fn set_meal_note(&mut self, _day: u64, _note: &MealNote) -> Result<(), String> {
+        denied("set_meal_note")
+    }
+
+    async fn get_shopping_list_ids(&self) -> Result<Vec<String>, String> {
+        self.inner.get_shopping_list_ids().await
+    }
+
+    async fn get_shopping_list(&self, list_id: &str) -> Result<Option<ShoppingList>, String> {
+        self.inner.get_shopping_list(list_id).await
+    }
+
+    async fn save_shopping_list(
+        &mut self,
+        _list_id: &str,
+        _list: &ShoppingList,
+    ) -> Result<(), String> {
+        denied("save_shopping_list")
+    }
+
+    async fn delete_shopping_list(&mut self, _list_id: &str) -> Result<(), String> {
+        denied("delete_shopping_list")
+    }
+
+    async fn get_pantry(&self) -> Result<Pantry, String> {
+        self.inner.get_pantry().await
+    }
+
+    async fn save_pantry(&mut self, _pantry: &Pantry) -> Result<(), String> {
+        denied("save_pantry")
+    }
+
+    async fn get_plan_ids(&self) -> Result<Vec<String>, String> {
+        self.inner.get_plan_ids().await
+    }
+
+    async fn get_plan(&self, plan_id: &str) -> Result<Option<Solution>, String> {
+        self.inner.get_plan(plan_id).await
+    }
+
+    async fn save_plan(&mut self, _plan_id: &str, _plan: &Solution) -> Result<(), String> {
+        denied("save_plan")
+    }
+
+    async fn delete_plan(&mut self, _plan_id: &str) -> Result<(), String> {
+        denied("delete_plan")
+    }
+
+    async fn reorder_meal(
+        &mut self,
+        _plan_id: &str,
+        _day: &str,
+        _meal: &str,
+        _new_index: usize,
+    ) -> Result<(), String> {
+        denied("reorder_meal")
+    }
+
+    async fn find_solved_plan(&self, fingerprint: u64) -> Result<Option<Solution>, String> {
+        self.inner.find_solved_plan(fingerprint).await
+    }
+
+    async fn save_solved_plan(
+        &mut self,
+        _plan_id: &str,
+        _fingerprint: u64,
+        _plan: &Solution,
+    ) -> Result<(), String> {
+        denied("save_solved_plan")
+    }
+
+    async fn get_app_settings(&self) -> Result<AppSettings, String> {
+        self.inner.get_app_settings().await
+    }
+
+    async fn set_app_settings(&mut self, _settings: &AppSettings) -> Result<(), String> {
+        denied("set_app_settings")
+    }
+
+    async fn get_usage_stats(&self) -> Result<UsageStats, String> {
+        self.inner.get_usage_stats().await
+    }
+
+    async fn set_usage_stats(&mut self, _stats: &UsageStats) -> Result<(), String> {
+        denied("set_usage_stats")
+    }
+
+    async fn import_products(
+        &mut self,
+        _data: &str,
+        _strategy: ConflictStrategy,
+    ) -> Result<ImportReport, String> {
+        denied("import_products")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_access::mock_db::MockProductDb;
+    use futures::executor::block_on;
+
+    const APPLE: &str = "Apple (BrandedApple)";
+
+    #[test]
+    fn reads_pass_through_and_mutations_are_denied() {
+        let mut db = ReadOnlyDatabase::new(MockProductDb::new());
+        let apple = block_on(db.get_product_by_id(APPLE)).unwrap();
+        assert_eq!(apple.id(), APPLE);
+
+        let error = block_on(db.delete_product(APPLE)).unwrap_err();
+        assert!(PermissionDenied::matches(&error));
+        assert_eq!(
+            error,
+            "Permission denied: cannot delete_product in a read-only database"
+        );
+        assert!(PermissionDenied::matches(
+            &block_on(db.add_product("Pear", apple.clone())).unwrap_err()
+        ));
+        assert!(PermissionDenied::matches(
+            &block_on(db.update_product_units(APPLE, &apple.allowed_units)).unwrap_err()
+        ));
+        assert!(PermissionDenied::matches(
+            &block_on(db.import_products("name,Fat\nPear,1\n", ConflictStrategy::Skip))
+                .unwrap_err()
+        ));
+        assert!(!PermissionDenied::matches("Product 'Pear' not found"));
+        assert!(db.inner().products.contains_key(APPLE));
+    }
+}
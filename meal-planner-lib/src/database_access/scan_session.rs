@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::db_wrapper::{Database, DbSearchCriteria, MutableDatabase};
+use super::open_food_facts_db_cont::open_food_facts_db::{is_off_response, product_from_off};
+use super::product_import::BulkReport;
+use crate::data_types::{Product, ProductId, is_valid_barcode};
+
+/// What a scanned barcode turned out to be.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanResolution {
+    /// Product already in the local database.
+    FoundLocally(ProductId),
+    /// Product found in `OpenFoodFacts`, not added yet, see [`ScanReport::add_imported`].
+    Imported(Box<Product>),
+    /// Neither database knows the barcode.
+    Unknown,
+    /// Looking the barcode up failed, e.g. while offline, so it can be resolved again later.
+    Failed(String),
+}
+
+/// Resolution of every barcode of a [`ScanSession`], in scanning order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanReport {
+    pub entries: Vec<(String, ScanResolution)>,
+}
+
+impl ScanReport {
+    #[must_use]
+    pub fn found_locally(&self) -> Vec<&ProductId> {
+        self.entries
+            .iter()
+            .filter_map(|(_, resolution)| match resolution {
+                ScanResolution::FoundLocally(id) => Some(id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn imported(&self) -> Vec<&Product> {
+        self.entries
+            .iter()
+            .filter_map(|(_, resolution)| match resolution {
+                ScanResolution::Imported(product) => Some(product.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Barcodes no database knows, e.g. for entering the products by hand.
+    #[must_use]
+    pub fn unknown(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(_, resolution)| *resolution == ScanResolution::Unknown)
+            .map(|(barcode, _)| barcode.as_str())
+            .collect()
+    }
+
+    /// Barcodes that couldn't be looked up, with the reason.
+    #[must_use]
+    pub fn failed(&self) -> Vec<(&str, &str)> {
+        self.entries
+            .iter()
+            .filter_map(|(barcode, resolution)| match resolution {
+                ScanResolution::Failed(error) => Some((barcode.as_str(), error.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Adds every product found in `OpenFoodFacts` to `db`.
+    pub async fn add_imported(&self, db: &mut dyn MutableDatabase) -> Result<BulkReport, String> {
+        let products = self
+            .imported()
+            .into_iter()
+            .map(|product| (product.id().to_string(), product.clone()))
+            .collect();
+        db.add_products_bulk(products).await
+    }
+}
+
+/// Barcodes scanned one after another, e.g. while going through the pantry, resolved together
+/// once scanning is done. Serializable, so an unfinished session survives a restart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanSession {
+    barcodes: Vec<String>,
+}
+
+impl ScanSession {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a scanned barcode, surrounding whitespace ignored. `Ok(false)` when it was already
+    /// scanned in this session.
+    pub fn scan(&mut self, barcode: &str) -> Result<bool, String> {
+        let barcode = barcode.trim();
+        if !is_valid_barcode(barcode) {
+            return Err(format!("Invalid barcode '{barcode}'"));
+        }
+        if self.barcodes.iter().any(|scanned| scanned == barcode) {
+            return Ok(false);
+        }
+        self.barcodes.push(barcode.to_string());
+        Ok(true)
+    }
+
+    /// Removes a barcode scanned by mistake, `false` when it wasn't scanned.
+    pub fn remove(&mut self, barcode: &str) -> bool {
+        let before = self.barcodes.len();
+        self.barcodes.retain(|scanned| scanned != barcode.trim());
+        self.barcodes.len() != before
+    }
+
+    /// Scanned barcodes, in scanning order.
+    #[must_use]
+    pub fn barcodes(&self) -> &[String] {
+        &self.barcodes
+    }
+
+    /// Looks every barcode up in `db` and the rest with `fetch_off`, which returns the
+    /// `OpenFoodFacts` API response for a barcode. Nothing is added to `db` yet.
+    pub async fn resolve(
+        &self,
+        db: &dyn Database,
+        mut fetch_off: impl AsyncFnMut(&str) -> Result<Value, String>,
+    ) -> ScanReport {
+        let mut report = ScanReport::default();
+        for barcode in &self.barcodes {
+            let local = db
+                .get_products_matching_criteria(&[DbSearchCriteria::ByBarcode(barcode.clone())])
+                .await;
+            let resolution = if let Some(id) = local.into_keys().next() {
+                ScanResolution::FoundLocally(id)
+            } else {
+                match fetch_off(barcode).await {
+                    // OpenFoodFacts answers unknown barcodes without a product
+                    Ok(response) if !is_off_response(&response) => ScanResolution::Unknown,
+                    Ok(response) => match product_from_off(&response) {
+                        Ok(product) => ScanResolution::Imported(Box::new(
+                            product.with_barcode(Some(barcode.clone())),
+                        )),
+                        Err(e) => ScanResolution::Failed(e),
+                    },
+                    Err(e) => ScanResolution::Failed(e),
+                }
+            };
+            report.entries.push((barcode.clone(), resolution));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_access::mock_db::MockProductDb;
+    use futures::executor::block_on;
+
+    const OATS: &str = "5901234123457";
+    const KNOWN: &str = "4006381333931";
+    const MISSING: &str = "96385074";
+    const OFFLINE: &str = "12345670";
+
+    #[test]
+    fn scans_each_barcode_once() {
+        let mut session = ScanSession::new();
+        assert_eq!(session.scan(" 5901234123457 "), Ok(true));
+        assert_eq!(session.scan(OATS), Ok(false));
+        assert!(session.scan("59012-34").is_err());
+        assert!(session.scan("123").is_err());
+        assert_eq!(session.barcodes(), [OATS.to_string()]);
+        assert!(session.remove(OATS));
+        assert!(!session.remove(OATS));
+    }
+
+    #[test]
+    fn resolves_locally_then_remotely_and_adds_imported() {
+        let mut db = MockProductDb::new();
+        let known_id = db.products.keys().next().unwrap().clone();
+        let known = db.products[&known_id]
+            .clone()
+            .with_barcode(Some(KNOWN.to_string()));
+        db.products.insert(known_id.clone(), known);
+
+        let mut session = ScanSession::new();
+        for barcode in [OATS, KNOWN, MISSING, OFFLINE] {
+            session.scan(barcode).unwrap();
+        }
+        let mut fetched = Vec::new();
+        let report = block_on(session.resolve(&db, async |barcode: &str| {
+            fetched.push(barcode.to_string());
+            match barcode {
+                OATS => Ok(serde_json::json!({
+                    "code": OATS,
+                    "product": { "product_name": "Oats", "brands": "Mill" },
+                })),
+                OFFLINE => Err("Network unreachable".to_string()),
+                _ => Ok(serde_json::json!({ "status": 0 })),
+            }
+        }));
+
+        assert_eq!(fetched, [OATS, MISSING, OFFLINE]);
+        assert_eq!(report.found_locally(), [&known_id]);
+        assert_eq!(report.unknown(), [MISSING]);
+        assert_eq!(report.failed(), [(OFFLINE, "Network unreachable")]);
+        let imported = report.imported();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].barcode.as_deref(), Some(OATS));
+
+        let added = block_on(report.add_imported(&mut db)).unwrap();
+        assert_eq!(added.added(), 1);
+        let oats = block_on(
+            db.get_products_matching_criteria(&[DbSearchCriteria::ByBarcode(OATS.to_string())]),
+        );
+        assert_eq!(oats.len(), 1);
+    }
+}
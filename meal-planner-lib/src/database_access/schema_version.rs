@@ -0,0 +1,64 @@
+use std::fmt;
+
+use super::local_db_cont::migrations;
+
+const NEWER_SCHEMA_PREFIX: &str = "Database was created by a newer version";
+
+/// Local database written by a newer library than this one, opening it is refused instead of
+/// failing later on tables or columns it doesn't know. Database errors are `String`s,
+/// [`NewerSchema::matches`] recognizes this one again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewerSchema {
+    /// Schema version stored in the database.
+    pub found: u32,
+    /// Newest schema version this library supports.
+    pub supported: u32,
+}
+
+impl NewerSchema {
+    /// Whether `error` returned when opening a database is a too new schema.
+    #[must_use]
+    pub fn matches(error: &str) -> bool {
+        error.starts_with(NEWER_SCHEMA_PREFIX)
+    }
+}
+
+impl fmt::Display for NewerSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{NEWER_SCHEMA_PREFIX} of the app (schema {}, supported up to {})",
+            self.found, self.supported
+        )
+    }
+}
+
+impl std::error::Error for NewerSchema {}
+
+impl From<NewerSchema> for String {
+    fn from(error: NewerSchema) -> Self {
+        error.to_string()
+    }
+}
+
+/// Schema version local databases are created with and migrated to.
+pub(crate) fn schema_version() -> u32 {
+    migrations::latest_version()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_schema_round_trips_through_string() {
+        let error: String = NewerSchema {
+            found: 40,
+            supported: schema_version(),
+        }
+        .into();
+        assert!(NewerSchema::matches(&error));
+        assert!(error.contains("schema 40"));
+        assert!(!NewerSchema::matches("Permission denied"));
+    }
+}
@@ -0,0 +1,331 @@
+//! C ABI for native frontends (Swift, Kotlin, ...).
+//!
+//! Every value crosses the boundary as a JSON string. Functions returning `*mut c_char` give back
+//! an envelope, `{"ok": <value>}` on success or `{"error": "<message>"}` on failure, which the
+//! caller must release with [`meal_planner_string_free`]. Database handles come from
+//! [`meal_planner_db_open`] and are released with [`meal_planner_db_close`].
+
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+use futures::executor::block_on;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::bl::constraints_solver::SolveDayRequest;
+use crate::bl::presets::MacroPreset;
+use crate::data_types::{AllowedUnitsType, Product, constraints::ProductConstraint};
+use crate::database_access::{Database, DbSearchCriteria, LocalProductDb, MutableDatabase};
+
+/// Opaque handle to a local product database.
+pub struct MealPlannerDb {
+    db: LocalProductDb,
+}
+
+#[derive(Deserialize)]
+struct ProductConstraintRequest {
+    product_id: String,
+    #[serde(default)]
+    min: Option<f32>,
+    #[serde(default)]
+    max: Option<f32>,
+    unit: AllowedUnitsType,
+}
+
+/// Opens (creating if needed) the database at `path`. Returns null when it can't be opened.
+///
+/// # Safety
+/// `path` must be null or a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_db_open(path: *const c_char) -> *mut MealPlannerDb {
+    let Ok(path) = (unsafe { read_str(path) }) else {
+        return ptr::null_mut();
+    };
+    match block_on(LocalProductDb::new(path)) {
+        Some(db) => Box::into_raw(Box::new(MealPlannerDb { db })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Closes a database opened with [`meal_planner_db_open`]. Null is ignored.
+///
+/// # Safety
+/// `db` must be null or a handle from [`meal_planner_db_open`] not closed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_db_close(db: *mut MealPlannerDb) {
+    if !db.is_null() {
+        drop(unsafe { Box::from_raw(db) });
+    }
+}
+
+/// Releases a string returned by any other function of this module. Null is ignored.
+///
+/// # Safety
+/// `s` must be null or a string returned by this module, not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Returns the product with `product_id`, or `null` when there is none.
+///
+/// # Safety
+/// `db` must be a live handle, `product_id` a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_product_get(
+    db: *mut MealPlannerDb,
+    product_id: *const c_char,
+) -> *mut c_char {
+    respond((|| {
+        let db = unsafe { handle(db) }?;
+        let product_id = unsafe { read_str(product_id) }?;
+        to_value(&block_on(db.get_product_by_id(product_id)))
+    })())
+}
+
+/// Returns products matching a JSON array of search criteria, as an object keyed by product id.
+///
+/// # Safety
+/// `db` must be a live handle, `criteria_json` a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_product_search(
+    db: *mut MealPlannerDb,
+    criteria_json: *const c_char,
+) -> *mut c_char {
+    respond((|| {
+        let db = unsafe { handle(db) }?;
+        let criteria: Vec<DbSearchCriteria> = unsafe { read_json(criteria_json) }?;
+        to_value(&block_on(db.get_products_matching_criteria(&criteria)))
+    })())
+}
+
+/// Adds a JSON serialized product under its id, returning the id.
+///
+/// # Safety
+/// `db` must be a live handle, `product_json` a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_product_add(
+    db: *mut MealPlannerDb,
+    product_json: *const c_char,
+) -> *mut c_char {
+    respond((|| {
+        let db = unsafe { handle(db) }?;
+        let product: Product = unsafe { read_json(product_json) }?;
+        let product_id = product.id();
+        block_on(db.add_product(&product_id, product))?;
+        Ok(json!(product_id))
+    })())
+}
+
+/// Replaces the product stored under `product_id` with a JSON serialized one.
+///
+/// # Safety
+/// `db` must be a live handle, `product_id` and `product_json` valid NUL terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_product_update(
+    db: *mut MealPlannerDb,
+    product_id: *const c_char,
+    product_json: *const c_char,
+) -> *mut c_char {
+    respond((|| {
+        let db = unsafe { handle(db) }?;
+        let product_id = unsafe { read_str(product_id) }?;
+        let product: Product = unsafe { read_json(product_json) }?;
+        block_on(db.update_product(product_id, product))?;
+        Ok(Value::Null)
+    })())
+}
+
+/// # Safety
+/// `db` must be a live handle, `product_id` a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_product_delete(
+    db: *mut MealPlannerDb,
+    product_id: *const c_char,
+) -> *mut c_char {
+    respond((|| {
+        let db = unsafe { handle(db) }?;
+        let product_id = unsafe { read_str(product_id) }?;
+        block_on(db.delete_product(product_id))?;
+        Ok(Value::Null)
+    })())
+}
+
+/// Builds a product constraint from `{"product_id", "unit", "min"?, "max"?}`, taking the product
+/// from the database.
+///
+/// # Safety
+/// `db` must be a live handle, `request_json` a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_product_constraint(
+    db: *mut MealPlannerDb,
+    request_json: *const c_char,
+) -> *mut c_char {
+    respond((|| {
+        let db = unsafe { handle(db) }?;
+        let request: ProductConstraintRequest = unsafe { read_json(request_json) }?;
+        let product = block_on(db.get_product_by_id(&request.product_id))
+            .ok_or_else(|| format!("Product '{}' not found", request.product_id))?;
+        let constraint = ProductConstraint::new_fractional(
+            Box::new(product),
+            request.min,
+            request.max,
+            request.unit,
+        )
+        .ok_or_else(|| {
+            format!(
+                "Invalid constraint for '{}': unit {} not allowed or min above max",
+                request.product_id, request.unit
+            )
+        })?;
+        to_value(&constraint)
+    })())
+}
+
+/// Day constraint with the nutrients of a macro preset (`keto`, `high_protein`, `balanced`).
+///
+/// # Safety
+/// `preset` must be a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_preset_day_constraint(
+    preset: *const c_char,
+    calories_kcal: f32,
+) -> *mut c_char {
+    respond((|| {
+        let preset: MacroPreset = unsafe { read_str(preset) }?.parse()?;
+        to_value(&preset.day_constraint(calories_kcal)?)
+    })())
+}
+
+/// Solves `{"direction", "objective", "day"}`, where `day` is a serialized day constraint.
+///
+/// # Safety
+/// `request_json` must be a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn meal_planner_solve_day(request_json: *const c_char) -> *mut c_char {
+    respond((|| {
+        let request: SolveDayRequest = unsafe { read_json(request_json) }?;
+        to_value(&request.solve()?)
+    })())
+}
+
+unsafe fn handle<'a>(db: *mut MealPlannerDb) -> Result<&'a mut LocalProductDb, String> {
+    unsafe { db.as_mut() }
+        .map(|handle| &mut handle.db)
+        .ok_or_else(|| "Database handle is null".to_string())
+}
+
+unsafe fn read_str<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("Unexpected null string".to_string());
+    }
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| format!("String is not valid UTF-8: {e}"))
+}
+
+unsafe fn read_json<T: DeserializeOwned>(s: *const c_char) -> Result<T, String> {
+    serde_json::from_str(unsafe { read_str(s) }?).map_err(|e| format!("Invalid JSON: {e}"))
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize response: {e}"))
+}
+
+fn respond(result: Result<Value, String>) -> *mut c_char {
+    let envelope = match result {
+        Ok(value) => json!({ "ok": value }),
+        Err(e) => json!({ "error": e }),
+    };
+    // serialized JSON escapes NUL characters, so this can't fail
+    CString::new(envelope.to_string()).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::ProductBuilder;
+
+    fn call(result: *mut c_char) -> Value {
+        let text = unsafe { CStr::from_ptr(result) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { meal_planner_string_free(result) };
+        serde_json::from_str(&text).unwrap()
+    }
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    #[test]
+    fn product_crud_through_handle() {
+        let path = std::env::temp_dir().join(format!("meal_planner_ffi_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = unsafe { meal_planner_db_open(c(path.to_str().unwrap()).as_ptr()) };
+        assert!(!db.is_null());
+
+        let kiwi = ProductBuilder::new("Kiwi")
+            .carbs(15.0)
+            .protein(1.0)
+            .build()
+            .unwrap();
+        let kiwi_json = c(&serde_json::to_string(&kiwi).unwrap());
+        assert_eq!(
+            call(unsafe { meal_planner_product_add(db, kiwi_json.as_ptr()) }),
+            json!({ "ok": "Kiwi" })
+        );
+        let added = call(unsafe { meal_planner_product_add(db, kiwi_json.as_ptr()) });
+        assert!(added["error"].is_string());
+
+        let found =
+            call(unsafe { meal_planner_product_search(db, c(r#"[{"ById": "Ki"}]"#).as_ptr()) });
+        let stored: Product = serde_json::from_value(found["ok"]["Kiwi"].clone()).unwrap();
+        assert_eq!(stored.name(), "Kiwi");
+
+        let constraint = call(unsafe {
+            meal_planner_product_constraint(
+                db,
+                c(r#"{"product_id": "Kiwi", "unit": "Gram", "max": 300}"#).as_ptr(),
+            )
+        });
+        assert!(serde_json::from_value::<ProductConstraint>(constraint["ok"].clone()).is_ok());
+
+        assert_eq!(
+            call(unsafe { meal_planner_product_delete(db, c("Kiwi").as_ptr()) }),
+            json!({ "ok": null })
+        );
+        assert_eq!(
+            call(unsafe { meal_planner_product_get(db, c("Kiwi").as_ptr()) }),
+            json!({ "ok": null })
+        );
+
+        unsafe { meal_planner_db_close(db) };
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn solves_day_built_from_preset() {
+        let day = call(unsafe { meal_planner_preset_day_constraint(c("keto").as_ptr(), 2000.0) });
+        assert!(day["ok"]["meals"].as_object().unwrap().is_empty());
+
+        let request = json!({ "direction": "Min", "objective": "Cost", "day": day["ok"] });
+        let solved = call(unsafe { meal_planner_solve_day(c(&request.to_string()).as_ptr()) });
+        // no meals means nothing can satisfy the nutrients
+        assert!(solved["error"].is_string());
+
+        assert_eq!(
+            call(unsafe { meal_planner_preset_day_constraint(c("paleo").as_ptr(), 2000.0) }),
+            json!({ "error": "Unknown macro preset 'paleo'" })
+        );
+        assert_eq!(
+            call(unsafe { meal_planner_solve_day(ptr::null()) }),
+            json!({ "error": "Unexpected null string" })
+        );
+        assert!(unsafe { meal_planner_db_open(ptr::null()) }.is_null());
+    }
+}
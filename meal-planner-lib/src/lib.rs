@@ -1,8 +1,30 @@
 #![allow(dead_code)]
 
-mod bl;
-pub mod data_types;
-pub mod database_access;
+pub mod api;
+pub mod prelude;
+
+pub use version::{VersionInfo, version_info};
+
+/// Developer tooling for solver experiments. Not part of the stable [`api`], may change at any
+/// time.
+#[cfg(feature = "solver-sandbox")]
+pub mod sandbox {
+    pub use crate::bl::solver_sandbox::{
+        SandboxMeal, SandboxNutrient, SandboxProduct, SandboxRatio, SandboxReport, SandboxRequest,
+        run_sandbox,
+    };
+}
 
-pub use bl::constraints_solver;
-pub use bl::swap_products;
+/// C ABI for native frontends, built into the `cdylib` when the `ffi` feature is enabled.
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
+
+/// HTTP API for a shared backend, available with the `server` feature.
+#[cfg(all(feature = "server", not(target_arch = "wasm32")))]
+pub mod server;
+
+// internal modules, only reachable through `api` and `prelude`
+mod bl;
+mod data_types;
+mod database_access;
+mod version;
@@ -0,0 +1,14 @@
+//! Items most users of the library need, for glob imports: `use meal_planner_lib::prelude::*;`
+
+pub use crate::api::constraints_solver::{ConstraintsSolver, MinOrMax, Objective, SolutionEntry};
+pub use crate::api::data_types::constraints::{
+    DayMealPlanConstraint, MealConstraint, NutrientConstraint, ProductConstraint,
+};
+pub use crate::api::data_types::{
+    AllowedUnits, AllowedUnitsType, MacroElements, MacroElementsType, MicroNutrients,
+    MicroNutrientsType, NutrientType, Price, Product, UnitData,
+};
+pub use crate::api::database_access::{
+    DataBaseTypes, Database, DbSearchCriteria, LOCAL_DB_DEFAULT_FILE, MutableDatabase, get_db,
+    get_mutable_db,
+};
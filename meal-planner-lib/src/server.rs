@@ -0,0 +1,253 @@
+//! HTTP API over a product database and the solver, so one household backend can serve several
+//! app instances. Bodies are JSON, errors come back as `{"error": "<message>"}`.
+//!
+//! | Method   | Path               | Body                                 | Response           |
+//! |----------|--------------------|--------------------------------------|--------------------|
+//! | `GET`    | `/products/{id}`   |                                      | product            |
+//! | `POST`   | `/products`        | product                              | id, `201`          |
+//! | `PUT`    | `/products/{id}`   | product                              | `204`              |
+//! | `DELETE` | `/products/{id}`   |                                      | `204`              |
+//! | `POST`   | `/products/search` | array of search criteria             | products by id     |
+//! | `POST`   | `/solve`           | `{"direction", "objective", "day"}`  | solution           |
+
+use std::collections::BTreeMap;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+use futures::executor::block_on;
+use serde_json::{Value, json};
+
+use crate::bl::constraints_solver::{Solution, SolveDayRequest};
+use crate::data_types::{Product, ProductId};
+use crate::database_access::{DbSearchCriteria, MutableDatabase};
+
+type ApiError = (StatusCode, Json<Value>);
+
+/// Routes of the API, every request works on a clone of `db`, so clones must share storage
+/// (as [`crate::database_access::LocalProductDb`] handles do).
+pub fn router<D>(db: D) -> Router
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/products", post(add_product::<D>))
+        .route("/products/search", post(search_products::<D>))
+        .route(
+            "/products/{id}",
+            get(get_product::<D>)
+                .put(update_product::<D>)
+                .delete(delete_product::<D>),
+        )
+        .route("/solve", post(solve_day))
+        .with_state(db)
+}
+
+/// Serves [`router`] on `address` (e.g. `0.0.0.0:8080`) until the listener fails.
+/// Must run within a tokio runtime.
+pub async fn serve<D>(db: D, address: &str) -> Result<(), String>
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .map_err(|e| format!("Failed to bind to {address}: {e}"))?;
+    axum::serve(listener, router(db))
+        .await
+        .map_err(|e| format!("Server stopped: {e}"))
+}
+
+async fn get_product<D>(
+    State(db): State<D>,
+    Path(product_id): Path<String>,
+) -> Result<Json<Product>, ApiError>
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    let product = blocking(move || block_on(db.get_product_by_id(&product_id))).await?;
+    product
+        .map(Json)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Product not found"))
+}
+
+async fn search_products<D>(
+    State(db): State<D>,
+    Json(criteria): Json<Vec<DbSearchCriteria>>,
+) -> Result<Json<BTreeMap<ProductId, Product>>, ApiError>
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    let products = blocking(move || block_on(db.get_products_matching_criteria(&criteria))).await?;
+    Ok(Json(products))
+}
+
+async fn add_product<D>(
+    State(mut db): State<D>,
+    Json(product): Json<Product>,
+) -> Result<(StatusCode, Json<ProductId>), ApiError>
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    let product_id = product.id();
+    let added_id = product_id.clone();
+    blocking(move || block_on(db.add_product(&product_id, product)))
+        .await?
+        .map_err(|e| error(StatusCode::BAD_REQUEST, &e))?;
+    Ok((StatusCode::CREATED, Json(added_id)))
+}
+
+async fn update_product<D>(
+    State(mut db): State<D>,
+    Path(product_id): Path<String>,
+    Json(product): Json<Product>,
+) -> Result<StatusCode, ApiError>
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    blocking(move || block_on(db.update_product(&product_id, product)))
+        .await?
+        .map_err(|e| error(StatusCode::BAD_REQUEST, &e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_product<D>(
+    State(mut db): State<D>,
+    Path(product_id): Path<String>,
+) -> Result<StatusCode, ApiError>
+where
+    D: MutableDatabase + Clone + Send + Sync + 'static,
+{
+    blocking(move || block_on(db.delete_product(&product_id)))
+        .await?
+        .map_err(|e| error(StatusCode::BAD_REQUEST, &e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn solve_day(Json(request): Json<SolveDayRequest>) -> Result<Json<Solution>, ApiError> {
+    blocking(move || request.solve())
+        .await?
+        .map(Json)
+        .map_err(|e| error(StatusCode::UNPROCESSABLE_ENTITY, &e))
+}
+
+// database futures are not `Send` and solving is CPU bound, both run off the async workers
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Request failed: {e}"),
+        )
+    })
+}
+
+fn error(status: StatusCode, message: &str) -> ApiError {
+    (status, Json(json!({ "error": message })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::ProductBuilder;
+    use crate::database_access::LocalProductDb;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let response = app.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (
+                status,
+                serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+            )
+        })
+    }
+
+    #[test]
+    fn serves_product_crud_and_search() {
+        let path =
+            std::env::temp_dir().join(format!("meal_planner_server_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = block_on(LocalProductDb::new(path.to_str().unwrap())).unwrap();
+        let app = router(db);
+
+        let kiwi = ProductBuilder::new("Kiwi")
+            .carbs(15.0)
+            .protein(1.0)
+            .build()
+            .unwrap();
+        let kiwi_json = serde_json::to_value(&kiwi).unwrap();
+        assert_eq!(
+            send(&app, "POST", "/products", Some(kiwi_json.clone())),
+            (StatusCode::CREATED, json!("Kiwi"))
+        );
+        let (status, body) = send(&app, "POST", "/products", Some(kiwi_json));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].is_string());
+
+        let (status, body) = send(&app, "GET", "/products/Kiwi", None);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_value::<Product>(body).unwrap().name(),
+            "Kiwi"
+        );
+
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/products/search",
+            Some(json!([{ "ById": "Ki" }])),
+        );
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.get("Kiwi").is_some());
+
+        let ripe = serde_json::to_value(kiwi.with_negligible(true)).unwrap();
+        assert_eq!(
+            send(&app, "PUT", "/products/Kiwi", Some(ripe)).0,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            send(&app, "DELETE", "/products/Kiwi", None).0,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            send(&app, "GET", "/products/Kiwi", None),
+            (
+                StatusCode::NOT_FOUND,
+                json!({ "error": "Product not found" })
+            )
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn solves_days_and_rejects_malformed_requests() {
+        let app = router(crate::database_access::mock_db::MockProductDb::new());
+        let request = json!({
+            "direction": "Min",
+            "objective": "Cost",
+            "day": { "meals": {}, "nutrients": [] },
+        });
+        let (status, body) = send(&app, "POST", "/solve", Some(request));
+        assert_eq!(status, StatusCode::OK, "{body}");
+
+        let (status, _) = send(&app, "POST", "/solve", Some(json!({ "direction": "Min" })));
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -0,0 +1,45 @@
+use serde::Serialize;
+use strum::EnumCount;
+
+use crate::data_types::{AllowedUnitsType, MicroNutrientsType};
+use crate::database_access::schema_version;
+
+/// Versions of what the library reads and writes, see [`version_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VersionInfo {
+    /// Version of the library crate, same as [`crate::api::API_VERSION`].
+    pub crate_version: &'static str,
+    /// Schema version local databases are created with and migrated to.
+    pub schema_version: u32,
+    /// Number of micro nutrients, each stored in its own database column.
+    pub micro_nutrients: usize,
+    /// Number of unit variants, each stored in its own database column.
+    pub units: usize,
+}
+
+/// Versions of this build, e.g. for an about screen or for telling whether a database or an
+/// export comes from a newer app.
+#[must_use]
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        schema_version: schema_version(),
+        micro_nutrients: MicroNutrientsType::COUNT,
+        units: AllowedUnitsType::COUNT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn version_info_matches_build() {
+        let info = version_info();
+        assert_eq!(info.crate_version, crate::api::API_VERSION);
+        assert!(info.schema_version > 0);
+        assert_eq!(info.micro_nutrients, MicroNutrientsType::iter().count());
+        assert_eq!(info.units, AllowedUnitsType::iter().count());
+    }
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "test-utils")]
+//! Solves canonical scenarios over the fixture databases and compares the plans with the golden
+//! snapshots in `tests/golden`. Rerun with `UPDATE_GOLDEN=1` after a deliberate solver change.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use meal_planner_lib::api::constraints_solver::{
+    ConstraintsSolver, MinOrMax, Objective, assert_golden_plan,
+};
+use meal_planner_lib::api::data_types::{
+    AllowedUnitsType, DietaryRestrictions, MacroElementsType, MicroNutrientsType, NutrientType,
+    constraints::{DayMealPlanConstraint, MealConstraint, NutrientConstraint, ProductConstraint},
+};
+use meal_planner_lib::api::database_access::{GROCERIES_FIXTURE, MockProductDb, PANTRY_FIXTURE};
+
+const TOLERANCE: f64 = 1e-4;
+
+fn golden_path(scenario: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{scenario}.json"))
+}
+
+fn product(db: &MockProductDb, id: &str, max: u16, unit: AllowedUnitsType) -> ProductConstraint {
+    let product = db.products.get(id).expect("fixture product should exist");
+    ProductConstraint::new(Box::new(product.clone()), Some(0), Some(max), unit)
+        .expect("product constraint should be valid")
+}
+
+fn meal(products: Vec<ProductConstraint>, nutrients: Vec<NutrientConstraint>) -> MealConstraint {
+    MealConstraint {
+        products,
+        nutrients,
+        max_products: None,
+        min_products: None,
+        ratios: Vec::new(),
+        slot: None,
+        recipes: Vec::new(),
+    }
+}
+
+fn day(
+    meals: Vec<(&str, MealConstraint)>,
+    nutrients: Vec<NutrientConstraint>,
+) -> DayMealPlanConstraint {
+    DayMealPlanConstraint {
+        meals: meals
+            .into_iter()
+            .map(|(name, meal)| (name.to_string(), meal))
+            .collect::<HashMap<_, _>>(),
+        nutrients,
+        max_cost: None,
+        restrictions: DietaryRestrictions::default(),
+        macro_percents: Vec::new(),
+        products: Vec::new(),
+    }
+}
+
+fn solve(
+    min_or_max: MinOrMax,
+    objective: impl Into<Objective>,
+    day: &DayMealPlanConstraint,
+    scenario: &str,
+) {
+    let solution = ConstraintsSolver::new(min_or_max, objective)
+        .solve_day(day)
+        .expect("scenario should be feasible");
+    assert_golden_plan(&golden_path(scenario), &solution, TOLERANCE);
+}
+
+#[test]
+fn golden_breakfast_with_least_calories() {
+    let pantry = MockProductDb::from_fixture(PANTRY_FIXTURE).unwrap();
+    let breakfast = meal(
+        vec![
+            product(&pantry, "Egg", 240, AllowedUnitsType::Piece),
+            product(&pantry, "Oat flakes", 100, AllowedUnitsType::Gram),
+            product(&pantry, "Milk 2%", 300, AllowedUnitsType::Gram),
+        ],
+        vec![NutrientConstraint::new(MacroElementsType::Protein, Some(30.0), None).unwrap()],
+    );
+    solve(
+        MinOrMax::Min,
+        NutrientType::Macro(MacroElementsType::Calories),
+        &day(vec![("Breakfast", breakfast)], Vec::new()),
+        "breakfast_least_calories",
+    );
+}
+
+#[test]
+fn golden_day_with_most_protein() {
+    let pantry = MockProductDb::from_fixture(PANTRY_FIXTURE).unwrap();
+    let lunch = meal(
+        vec![
+            product(&pantry, "Chicken breast", 200, AllowedUnitsType::Gram),
+            product(&pantry, "Brown rice cooked", 300, AllowedUnitsType::Gram),
+            product(&pantry, "Broccoli", 200, AllowedUnitsType::Gram),
+        ],
+        Vec::new(),
+    );
+    let dinner = meal(
+        vec![
+            product(&pantry, "Chickpeas cooked", 300, AllowedUnitsType::Gram),
+            product(&pantry, "Greek yogurt", 250, AllowedUnitsType::Gram),
+        ],
+        vec![NutrientConstraint::new(MacroElementsType::Carbs, Some(20.0), None).unwrap()],
+    );
+    let nutrients = vec![
+        NutrientConstraint::new(MacroElementsType::Calories, None, Some(1000.0)).unwrap(),
+        NutrientConstraint::new(MicroNutrientsType::Fiber, Some(15.0), None).unwrap(),
+    ];
+    solve(
+        MinOrMax::Max,
+        NutrientType::Macro(MacroElementsType::Protein),
+        &day(vec![("Lunch", lunch), ("Dinner", dinner)], nutrients),
+        "day_most_protein",
+    );
+}
+
+#[test]
+fn golden_cheapest_breakfast() {
+    let groceries = MockProductDb::from_fixture(GROCERIES_FIXTURE).unwrap();
+    let breakfast = meal(
+        vec![
+            product(
+                &groceries,
+                "Corn flakes (Morning Gold)",
+                150,
+                AllowedUnitsType::Gram,
+            ),
+            product(
+                &groceries,
+                "Muesli with nuts (Morning Gold)",
+                150,
+                AllowedUnitsType::Gram,
+            ),
+            product(
+                &groceries,
+                "Porridge oats (Crunchy Farm)",
+                80,
+                AllowedUnitsType::Gram,
+            ),
+        ],
+        vec![
+            NutrientConstraint::new(MacroElementsType::Calories, Some(400.0), None).unwrap(),
+            NutrientConstraint::new(MacroElementsType::Protein, Some(15.0), None).unwrap(),
+        ],
+    );
+    solve(
+        MinOrMax::Min,
+        Objective::Cost,
+        &day(vec![("Breakfast", breakfast)], Vec::new()),
+        "breakfast_cheapest",
+    );
+}
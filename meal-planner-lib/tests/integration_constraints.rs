@@ -4,12 +4,14 @@ use std::collections::HashMap;
 
 use approx::assert_relative_eq;
 use futures::executor::block_on;
-use meal_planner_lib::constraints_solver::{ConstraintsSolver, MinOrMax, SolutionEntry};
-use meal_planner_lib::data_types::{
-    AllowedUnitsType, MacroElementsType, MicroNutrientsType, NutrientType,
+use meal_planner_lib::api::constraints_solver::{ConstraintsSolver, MinOrMax, SolutionEntry};
+use meal_planner_lib::api::data_types::{
+    AllowedUnitsType, DietaryRestrictions, MacroElementsType, MicroNutrientsType, NutrientType,
     constraints::{DayMealPlanConstraint, MealConstraint, NutrientConstraint, ProductConstraint},
 };
-use meal_planner_lib::database_access::{DataBaseTypes, Database, DbSearchCriteria, get_db};
+use meal_planner_lib::api::database_access::{
+    DataBaseTypes, Database, DbSearchCriteria, MockProductDb, PANTRY_FIXTURE, get_db,
+};
 
 async fn mock_db() -> Box<dyn Database> {
     get_db(DataBaseTypes::Mock)
@@ -21,9 +23,9 @@ fn extract_single_product<'a>(
     solution: &'a SolutionEntry,
     meal_name: &str,
 ) -> (
-    &'a meal_planner_lib::data_types::Product,
+    &'a meal_planner_lib::api::data_types::Product,
     f64,
-    &'a meal_planner_lib::constraints_solver::Fraction,
+    &'a meal_planner_lib::api::constraints_solver::Fraction,
 ) {
     let SolutionEntry::Week {
         entries: week_entries,
@@ -43,7 +45,7 @@ fn extract_single_product<'a>(
     assert_eq!(day_entry.len(), 1, "Expected single meal for the day");
 
     let meal_entry = match &day_entry[0] {
-        SolutionEntry::Meal { name, entries } => {
+        SolutionEntry::Meal { name, entries, .. } => {
             assert_eq!(name, meal_name);
             entries
         }
@@ -91,6 +93,11 @@ fn test_user_can_build_day_plan_from_mock_db() {
                 NutrientConstraint::new(MicroNutrientsType::Fiber, Some(5.0), Some(6.0))
                     .expect("valid fiber constraint"),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let mut meals = HashMap::new();
@@ -102,6 +109,10 @@ fn test_user_can_build_day_plan_from_mock_db() {
                 NutrientConstraint::new(MacroElementsType::Calories, Some(50.0), Some(70.0))
                     .expect("valid calorie constraint"),
             ],
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
         };
 
         let mut solver = ConstraintsSolver::new(
@@ -163,6 +174,11 @@ fn test_user_receives_infeasible_error_from_mock_db() {
                 NutrientConstraint::new(MicroNutrientsType::Fiber, Some(50.0), Some(60.0))
                     .expect("valid fiber constraint"),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let mut meals = HashMap::new();
@@ -171,6 +187,10 @@ fn test_user_receives_infeasible_error_from_mock_db() {
         let day_constraints = DayMealPlanConstraint {
             meals,
             nutrients: Vec::new(),
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
         };
 
         let mut solver = ConstraintsSolver::new(
@@ -218,6 +238,11 @@ fn test_user_retrieves_multi_meal_solution_from_mock_db() {
                 NutrientConstraint::new(MicroNutrientsType::Fiber, Some(3.0), Some(3.2))
                     .expect("valid breakfast fiber constraint"),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let dinner = MealConstraint {
@@ -226,6 +251,11 @@ fn test_user_retrieves_multi_meal_solution_from_mock_db() {
                 NutrientConstraint::new(MicroNutrientsType::Fiber, Some(4.0), Some(4.2))
                     .expect("valid dinner fiber constraint"),
             ],
+            max_products: None,
+            min_products: None,
+            ratios: Vec::new(),
+            slot: None,
+            recipes: Vec::new(),
         };
 
         let mut meals = HashMap::new();
@@ -242,6 +272,10 @@ fn test_user_retrieves_multi_meal_solution_from_mock_db() {
                 NutrientConstraint::new(MacroElementsType::Calories, Some(55.0), Some(65.0))
                     .expect("valid day calorie constraint"),
             ],
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
         };
 
         let mut solver = ConstraintsSolver::new(
@@ -271,7 +305,7 @@ fn test_user_retrieves_multi_meal_solution_from_mock_db() {
         let mut meals_map: HashMap<&str, &Vec<SolutionEntry>> = HashMap::new();
         for meal_entry in day_entries {
             match meal_entry {
-                SolutionEntry::Meal { name, entries } => {
+                SolutionEntry::Meal { name, entries, .. } => {
                     meals_map.insert(name.as_str(), entries);
                 }
                 _ => panic!("Expected meal entry"),
@@ -359,3 +393,63 @@ fn test_user_retrieves_multi_meal_solution_from_mock_db() {
         assert!((55.0 - 1e-6..=65.0 + 1e-6).contains(&total_calories));
     });
 }
+
+#[test]
+fn test_user_can_plan_from_pantry_fixture() {
+    block_on(async {
+        let db = MockProductDb::from_fixture(PANTRY_FIXTURE).expect("fixture should load");
+        let criteria = [DbSearchCriteria::ByNutrientAtLeast(
+            MacroElementsType::Protein.into(),
+            20.0,
+        )];
+        let high_protein = db.get_products_matching_criteria(&criteria).await;
+        assert!(high_protein.len() >= 10);
+
+        let products = high_protein
+            .into_values()
+            .map(|product| {
+                ProductConstraint::new(
+                    Box::new(product),
+                    Some(0),
+                    Some(200),
+                    AllowedUnitsType::Gram,
+                )
+                .expect("products should support gram unit")
+            })
+            .collect();
+        let mut meals = HashMap::new();
+        meals.insert(
+            "Dinner".to_string(),
+            MealConstraint {
+                products,
+                nutrients: Vec::new(),
+                max_products: None,
+                min_products: None,
+                ratios: Vec::new(),
+                slot: None,
+                recipes: Vec::new(),
+            },
+        );
+        let day_constraints = DayMealPlanConstraint {
+            meals,
+            nutrients: vec![
+                NutrientConstraint::new(MacroElementsType::Protein, Some(100.0), None)
+                    .expect("valid protein constraint"),
+            ],
+            max_cost: None,
+            restrictions: DietaryRestrictions::default(),
+            macro_percents: Vec::new(),
+            products: Vec::new(),
+        };
+
+        let mut solver = ConstraintsSolver::new(
+            MinOrMax::Min,
+            NutrientType::Macro(MacroElementsType::Calories),
+        );
+        let solution = solver
+            .solve_day(&day_constraints)
+            .expect("solution should be feasible");
+        let protein = solution.nutrient_total(NutrientType::Macro(MacroElementsType::Protein));
+        assert!(protein >= 100.0 - 1e-4);
+    });
+}
@@ -0,0 +1,109 @@
+//! Guards the stable API surface: every item exported from `api` and `prelude` is listed in
+//! `tests/public_api.txt`, so adding or removing one has to be done deliberately.
+
+use std::collections::HashMap;
+
+use meal_planner_lib::prelude::*;
+
+const SNAPSHOT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/public_api.txt");
+
+fn exported_items(root: &str, source: &str) -> Vec<String> {
+    let code = source
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut modules = vec![root.to_string()];
+    let mut items = Vec::new();
+    let mut rest = code.as_str();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(after) = rest.strip_prefix("pub mod ") {
+            let (name, body) = after.split_once('{').expect("module should have a body");
+            modules.push(name.trim().to_string());
+            rest = body;
+        } else if let Some(after) = rest.strip_prefix('}') {
+            modules.pop();
+            rest = after;
+        } else {
+            let (statement, after) = rest.split_once(';').expect("statement should end with ';'");
+            let prefix = modules.join("::");
+            items.extend(
+                statement_items(statement)
+                    .into_iter()
+                    .map(|name| format!("{prefix}::{name}")),
+            );
+            rest = after;
+        }
+    }
+    items
+}
+
+fn statement_items(statement: &str) -> Vec<String> {
+    if let Some(path) = statement.strip_prefix("pub use ") {
+        let names = match path.split_once('{') {
+            Some((_, group)) => group.trim_end().trim_end_matches('}').to_string(),
+            None => path.to_string(),
+        };
+        names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| name.rsplit([' ', ':']).next().unwrap_or(name).to_string())
+            .collect()
+    } else if let Some(declaration) = statement.strip_prefix("pub const ") {
+        vec![
+            declaration
+                .split(':')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        ]
+    } else {
+        Vec::new()
+    }
+}
+
+#[test]
+fn test_public_api_matches_snapshot() {
+    let mut items = exported_items("api", include_str!("../src/api.rs"));
+    items.extend(exported_items("prelude", include_str!("../src/prelude.rs")));
+    items.sort();
+    let current = items.join("\n") + "\n";
+
+    if std::env::var_os("UPDATE_PUBLIC_API").is_some() {
+        std::fs::write(SNAPSHOT_PATH, &current).expect("snapshot should be writable");
+        return;
+    }
+    let snapshot = std::fs::read_to_string(SNAPSHOT_PATH).expect("snapshot should exist");
+    assert_eq!(
+        current, snapshot,
+        "public API changed; if this is intended, bump the version accordingly and rerun with UPDATE_PUBLIC_API=1"
+    );
+}
+
+#[test]
+fn test_prelude_is_enough_to_build_a_product() {
+    let mut micro_nutrients = MicroNutrients::default();
+    micro_nutrients[MicroNutrientsType::Fiber] = Some(1.0);
+    let product = Product::new(
+        "Oats".to_string(),
+        None,
+        Box::new(MacroElements::new(7.0, 1.0, 60.0, 1.0, 13.0)),
+        Box::new(micro_nutrients),
+        HashMap::new(),
+    );
+    assert_eq!(
+        product.allowed_units.get(&AllowedUnitsType::Gram),
+        Some(&UnitData {
+            amount: 1,
+            divider: 1
+        })
+    );
+    assert!(!meal_planner_lib::api::API_VERSION.is_empty());
+}